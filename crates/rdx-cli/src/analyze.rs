@@ -0,0 +1,84 @@
+//! `rdx-cli analyze`: summarize an output directory written by `run` (or one sweep point)
+//! from its `metrics.csv`, print the headline numbers and store them as `summary.json`; with
+//! `--plots` (feature `plots`) also chart it.
+use anyhow::Context;
+use clap::Args;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+#[derive(Args, Debug)]
+pub struct AnalyzeArgs {
+    /// Output directory of a run
+    pub dir: String,
+
+    /// Also draw charts of the run into `<dir>/plots/`
+    #[cfg(feature = "plots")]
+    #[arg(long)]
+    pub plots: bool,
+
+    /// Image format of the charts: svg or png
+    #[cfg(feature = "plots")]
+    #[arg(long, value_parser = crate::plots::parse_format, default_value = "svg", requires = "plots")]
+    pub plot_format: crate::plots::PlotFormat,
+}
+
+/// Headline numbers of a run.
+#[derive(Debug, Serialize)]
+pub struct Summary {
+    pub rounds: usize,
+    pub trades: u64,
+    pub delta_u: f64,
+    /// Numeric columns of `metrics.csv` in the first and the last round.
+    pub first: BTreeMap<String, f64>,
+    pub last: BTreeMap<String, f64>,
+}
+
+/// Columns printed by `analyze`, first round against last.
+const HEADLINE: [&str; 5] = ["mean_wealth", "gini_wealth", "gini_utility", "pareto_gap", "active_traders"];
+
+/// Read `<dir>/metrics.csv` into a `Summary`.
+pub fn summarize(dir: &Path) -> anyhow::Result<Summary> {
+    let path = dir.join("metrics.csv");
+    let mut rdr = csv::Reader::from_path(&path).with_context(|| format!("failed reading {}", path.display()))?;
+    let header: Vec<String> = rdr.headers()?.iter().map(str::to_string).collect();
+    let rows = rdr.records().collect::<Result<Vec<_>, _>>()?;
+    let numeric = |row: &csv::StringRecord| -> BTreeMap<String, f64> {
+        header.iter().zip(row.iter())
+            .filter_map(|(h, v)| v.parse::<f64>().ok().map(|x| (h.clone(), x)))
+            .collect()
+    };
+    let column = |name: &str| header.iter().position(|h| h == name);
+    let total = |name: &str| -> f64 {
+        column(name).map_or(0.0, |c| rows.iter().filter_map(|r| r.get(c)?.parse::<f64>().ok()).sum())
+    };
+    Ok(Summary {
+        rounds: rows.len(),
+        trades: total("trades") as u64,
+        delta_u: total("delta_u"),
+        first: rows.first().map(numeric).unwrap_or_default(),
+        last: rows.last().map(numeric).unwrap_or_default(),
+    })
+}
+
+/// `rdx-cli analyze`.
+pub fn execute(args: &AnalyzeArgs) -> anyhow::Result<()> {
+    let dir = Path::new(&args.dir);
+    let summary = summarize(dir)?;
+    println!("{} rounds, {} trades, total utility gain {:.6}", summary.rounds, summary.trades, summary.delta_u);
+    for name in HEADLINE {
+        if let (Some(a), Some(b)) = (summary.first.get(name), summary.last.get(name)) {
+            println!("  {name:<16} {a:>14.6} -> {b:>14.6}");
+        }
+    }
+    let out = dir.join("summary.json");
+    std::fs::write(&out, serde_json::to_string_pretty(&summary)?)?;
+    println!("Wrote {}", out.display());
+    #[cfg(feature = "plots")]
+    if args.plots {
+        for path in crate::plots::write_plots(dir, args.plot_format)? {
+            println!("Wrote {}", path.display());
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,90 @@
+//! Replication bundles: one `.tar.gz` holding a run's config, manifest, metrics, figures and a
+//! sample of its trade events, plus an `index.json` describing every file in it.
+//!
+//! Entries are written with fixed mtime and permissions, so the same run bundles to the same
+//! bytes and the archive itself can be cited by checksum.
+use anyhow::Context;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::fs::{self, File};
+use std::path::Path;
+
+/// Bumped whenever the layout of the archive or of `index.json` changes.
+pub const BUNDLE_FORMAT: &str = "rdx-bundle/1";
+
+/// One file to be stored in the bundle.
+pub struct Artifact {
+    /// Name inside the archive.
+    pub name: String,
+    /// `config`, `manifest`, `metrics`, `events`, `graph`, `figure`, ...
+    pub kind: &'static str,
+    pub bytes: Vec<u8>,
+}
+
+impl Artifact {
+    pub fn new(name: impl Into<String>, kind: &'static str, bytes: Vec<u8>) -> Self {
+        Artifact { name: name.into(), kind, bytes }
+    }
+
+    /// Read `path` from disk, storing it under `prefix` + its file name.
+    pub fn from_file(path: &str, prefix: &str, kind: &'static str) -> anyhow::Result<Self> {
+        let bytes = fs::read(path).with_context(|| format!("failed reading bundle input: {}", path))?;
+        let file_name = Path::new(path)
+            .file_name()
+            .and_then(|n| n.to_str())
+            .with_context(|| format!("bundle input has no file name: {}", path))?;
+        Ok(Artifact::new(format!("{}{}", prefix, file_name), kind, bytes))
+    }
+}
+
+#[derive(Serialize)]
+struct IndexEntry<'a> {
+    name: &'a str,
+    kind: &'static str,
+    bytes: usize,
+    sha256: String,
+}
+
+#[derive(Serialize)]
+struct Index<'a> {
+    format: &'static str,
+    files: Vec<IndexEntry<'a>>,
+}
+
+pub fn sha256_hex(bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
+}
+
+/// Write `artifacts` and their `index.json` to a gzip-compressed tarball at `path`.
+pub fn write_bundle(path: &str, artifacts: &[Artifact]) -> anyhow::Result<()> {
+    let index = Index {
+        format: BUNDLE_FORMAT,
+        files: artifacts
+            .iter()
+            .map(|a| IndexEntry { name: &a.name, kind: a.kind, bytes: a.bytes.len(), sha256: sha256_hex(&a.bytes) })
+            .collect(),
+    };
+    let index_json = serde_json::to_vec_pretty(&index)?;
+
+    let file = File::create(path).with_context(|| format!("failed creating bundle: {}", path))?;
+    let mut tar = tar::Builder::new(GzEncoder::new(file, Compression::default()));
+    append(&mut tar, "index.json", &index_json)?;
+    for a in artifacts.iter() {
+        append(&mut tar, &a.name, &a.bytes)?;
+    }
+    tar.into_inner()?.finish()?;
+    Ok(())
+}
+
+fn append<W: std::io::Write>(tar: &mut tar::Builder<W>, name: &str, bytes: &[u8]) -> anyhow::Result<()> {
+    let mut header = tar::Header::new_gnu();
+    header.set_size(bytes.len() as u64);
+    header.set_mode(0o644);
+    header.set_mtime(0);
+    header.set_cksum();
+    tar.append_data(&mut header, name, bytes)
+        .with_context(|| format!("failed adding {} to bundle", name))?;
+    Ok(())
+}
@@ -0,0 +1,96 @@
+//! Where a command's `SimConfig` comes from: a config file or preset, with overlay files and
+//! `--set` assignments merged on top (see `rdx_core::overlay`).
+use anyhow::Context;
+use clap::Args;
+use rdx_core::model::SimConfig;
+use rdx_core::overlay;
+use rdx_core::scenarios;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Args, Debug, Clone)]
+pub struct ConfigArgs {
+    /// Path to JSON config
+    #[arg(long, default_value="config/example.json")]
+    pub config: String,
+
+    /// Run a built-in scenario preset instead of `--config` (e.g. barter_demo)
+    #[arg(long)]
+    pub preset: Option<String>,
+
+    /// Override file merged over the config or preset (repeatable, applied in order)
+    #[arg(long)]
+    pub overlay: Vec<String>,
+
+    /// Override one field, e.g. `--set seed=9 --set consumption.rate=0.1` (repeatable, applied
+    /// after the overlays)
+    #[arg(long = "set", value_name = "PATH=VALUE")]
+    pub set: Vec<String>,
+}
+
+impl ConfigArgs {
+    /// The layered config, not yet validated.
+    pub fn load(&self) -> anyhow::Result<SimConfig> {
+        self.load_with(&[])
+    }
+
+    /// The layered config with `extra` assignments applied after `--set`.
+    pub fn load_with(&self, extra: &[String]) -> anyhow::Result<SimConfig> {
+        let base = match &self.preset {
+            Some(name) => serde_json::to_value(scenarios::preset(name)?)?,
+            None => load_config_json(Path::new(&self.config), &mut Vec::new())?,
+        };
+        self.layer(base, extra)
+    }
+
+    /// `base` with the overlays and `--set` applied; `--config` and `--preset` are ignored.
+    /// Used to branch a resumed checkpoint under changed parameters.
+    pub fn apply_to(&self, base: &SimConfig) -> anyhow::Result<SimConfig> {
+        self.layer(serde_json::to_value(base)?, &[])
+    }
+
+    fn layer(&self, base: serde_json::Value, extra: &[String]) -> anyhow::Result<SimConfig> {
+        let overlays = self.overlay.iter()
+            .map(|path| load_config_json(Path::new(path), &mut Vec::new()))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        let mut assignments = self.set.clone();
+        assignments.extend(extra.iter().cloned());
+        Ok(overlay::layered(base, overlays, &assignments)?)
+    }
+}
+
+/// Fail with every `SimConfig::validate` problem listed.
+pub fn ensure_valid(cfg: &SimConfig) -> anyhow::Result<()> {
+    let problems = cfg.validate();
+    if !problems.is_empty() {
+        let list: Vec<String> = problems.iter().map(|p| format!("  - {p}")).collect();
+        anyhow::bail!("invalid config ({} problems):\n{}", problems.len(), list.join("\n"));
+    }
+    Ok(())
+}
+
+/// Read a config document, resolving `"extends": "<parent path>"` (relative to the file)
+/// recursively: the file is merged over its parent. `chain` guards against cycles.
+pub fn load_config_json(path: &Path, chain: &mut Vec<PathBuf>) -> anyhow::Result<serde_json::Value> {
+    let canonical = path.canonicalize()
+        .with_context(|| format!("failed reading config: {}", path.display()))?;
+    if chain.contains(&canonical) {
+        anyhow::bail!("config {} extends itself", path.display());
+    }
+    chain.push(canonical);
+    let text = fs::read_to_string(path)
+        .with_context(|| format!("failed reading config: {}", path.display()))?;
+    let mut doc: serde_json::Value = serde_json::from_str(&text)
+        .with_context(|| format!("invalid config json: {}", path.display()))?;
+    let parent = doc.as_object_mut().and_then(|o| o.remove(overlay::EXTENDS_KEY));
+    match parent {
+        Some(serde_json::Value::String(parent)) => {
+            let parent_path = path.parent().unwrap_or(Path::new(".")).join(parent);
+            let mut base = load_config_json(&parent_path, chain)?;
+            overlay::merge(&mut base, doc);
+            Ok(base)
+        }
+        Some(other) => anyhow::bail!("`extends` must be a path string, got {other}"),
+        None => Ok(doc),
+    }
+}
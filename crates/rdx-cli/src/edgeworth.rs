@@ -0,0 +1,113 @@
+//! `rdx-cli edgeworth`: the Edgeworth box of one dyad and good pair in a config's initial
+//! population, as point series for plotting (see `rdx_core::pareto_oracle::edgeworth_trace`).
+use anyhow::Context;
+use clap::Args;
+use rdx_core::model::Agent;
+use rdx_core::pareto_oracle::edgeworth_trace;
+use rdx_core::preferences::alpha_from_beta;
+use rdx_core::sim::Engine;
+use rdx_core::trade::build_oracle;
+use crate::config::{ensure_valid, ConfigArgs};
+
+#[derive(Args, Debug)]
+pub struct EdgeworthArgs {
+    #[command(flatten)]
+    pub config: ConfigArgs,
+
+    /// Index of agent i (lower-left origin of the box)
+    #[arg(long)]
+    pub i: usize,
+
+    /// Index of agent j
+    #[arg(long)]
+    pub j: usize,
+
+    /// Good A (horizontal axis), by name or index
+    #[arg(long)]
+    pub good_a: String,
+
+    /// Good B (vertical axis), by name or index; the base good if omitted
+    #[arg(long)]
+    pub good_b: Option<String>,
+
+    /// Points per curve
+    #[arg(long, default_value_t = 200)]
+    pub samples: usize,
+
+    /// Output CSV: one row per point with columns series, a_i, b_i
+    #[arg(long, default_value = "edgeworth.csv")]
+    pub out: String,
+}
+
+fn good_index(goods: &[String], spec: &str) -> anyhow::Result<usize> {
+    goods.iter().position(|g| g == spec)
+        .or_else(|| spec.parse().ok().filter(|&k: &usize| k < goods.len()))
+        .ok_or_else(|| anyhow::anyhow!("no good {} (goods: {})", spec, goods.join(", ")))
+}
+
+/// The agent's dyadic alpha on A against B, as the engine takes it: its elicited alpha to the
+/// base when B is the base good, the beta-derived one otherwise.
+fn pair_alpha(agent: &Agent, a: usize, b: usize, base: usize) -> f64 {
+    let min_alpha = 1e-6;
+    if b == base && agent.alpha_to_base.len() == agent.e.len() {
+        agent.alpha_to_base[a].clamp(min_alpha, 1.0 - min_alpha)
+    } else {
+        alpha_from_beta(&agent.beta, a, b, min_alpha)
+    }
+}
+
+/// `rdx-cli edgeworth`.
+pub fn execute(args: &EdgeworthArgs) -> anyhow::Result<()> {
+    let cfg = args.config.load()?;
+    ensure_valid(&cfg)?;
+    let goods = cfg.all_goods();
+    let a = good_index(&goods, &args.good_a)?;
+    let b = match &args.good_b {
+        Some(spec) => good_index(&goods, spec)?,
+        None => cfg.base_good,
+    };
+    anyhow::ensure!(a != b, "good A and good B must differ");
+    anyhow::ensure!(args.i != args.j, "agents i and j must differ");
+
+    let oracle = build_oracle(&cfg)?;
+    let engine = Engine::new(cfg.clone())?;
+    let agents = &engine.state().agents;
+    let agent = |k: usize| {
+        agents.get(k).ok_or_else(|| anyhow::anyhow!("no agent {} ({} agents)", k, agents.len()))
+    };
+    let (ai, aj) = (agent(args.i)?, agent(args.j)?);
+    let trace = edgeworth_trace(
+        oracle.as_ref(),
+        pair_alpha(ai, a, b, cfg.base_good), ai.e[a], ai.e[b],
+        pair_alpha(aj, a, b, cfg.base_good), aj.e[a], aj.e[b],
+        cfg.min_qty,
+        cfg.oracle_bisect_iters,
+        args.samples,
+    );
+
+    let mut wtr = csv::Writer::from_path(&args.out).with_context(|| format!("failed creating {}", args.out))?;
+    wtr.write_record(["series", "a_i", "b_i"])?;
+    let (ta, tb) = trace.totals;
+    let corners = [(0.0, 0.0), (ta, 0.0), (ta, tb), (0.0, tb), (0.0, 0.0)];
+    let series: [(&str, &[(f64, f64)]); 6] = [
+        ("box", &corners),
+        ("endowment", &[trace.endowment]),
+        ("chosen", &[trace.chosen]),
+        ("contract_curve", &trace.contract_curve),
+        ("offer_i", &trace.offer_i),
+        ("offer_j", &trace.offer_j),
+    ];
+    for (name, points) in series {
+        for &(x, y) in points {
+            wtr.write_record([name.to_string(), format!("{:.10}", x), format!("{:.10}", y)])?;
+        }
+    }
+    wtr.flush()?;
+    println!(
+        "Agents {} and {} on {} / {}: rate {:.6}, endowment ({:.4}, {:.4}) -> ({:.4}, {:.4})",
+        args.i, args.j, goods[a], goods[b], trace.q_ab,
+        trace.endowment.0, trace.endowment.1, trace.chosen.0, trace.chosen.1,
+    );
+    println!("Wrote {}", args.out);
+    Ok(())
+}
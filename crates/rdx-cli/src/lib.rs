@@ -0,0 +1,14 @@
+//! The commands of `rdx-cli`, one module each; `main.rs` only parses the command line and
+//! dispatches.
+pub mod analyze;
+pub mod bundle;
+pub mod config;
+pub mod edgeworth;
+#[cfg(feature = "plots")]
+pub mod plots;
+pub mod run;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+pub mod sweep;
+#[cfg(feature = "tui")]
+pub mod tui;
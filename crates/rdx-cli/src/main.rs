@@ -1,88 +1,55 @@
-use anyhow::Context;
-use clap::Parser;
+use clap::{Parser, Subcommand};
+use rdx_cli::{analyze, config, edgeworth, run, sweep};
 use rdx_core::model::SimConfig;
-use rdx_core::sim::{init_agents, run, mean_endowments};
-use std::fs;
+use rdx_core::sim::Engine;
+use tracing_subscriber::EnvFilter;
 
 #[derive(Parser, Debug)]
 #[command(name="rdx-cli", about="Reaction–Diffusion P2P exchange simulator for AI-complementary human services")]
-struct Args {
-    /// Path to JSON config
-    #[arg(long, default_value="config/example.json")]
-    config: String,
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
 
-    /// Output directory
-    #[arg(long, default_value="out")]
-    out_dir: String,
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Simulate a config and write its traces
+    Run(run::RunArgs),
+    /// Check a config (after overlays and `--set`) without running it
+    Validate(config::ConfigArgs),
+    /// Run a config over a grid of parameter values, one output directory per point
+    Sweep(sweep::SweepArgs),
+    /// Summarize an output directory written by `run`
+    Analyze(analyze::AnalyzeArgs),
+    /// Write the Edgeworth box of one dyad and good pair as point series
+    Edgeworth(edgeworth::EdgeworthArgs),
+    /// Print the JSON Schema of the config format
+    Schema,
 }
 
 fn main() -> anyhow::Result<()> {
-    let args = Args::parse();
-
-    let cfg_str = fs::read_to_string(&args.config)
-        .with_context(|| format!("failed reading config: {}", args.config))?;
-    let cfg: SimConfig = serde_json::from_str(&cfg_str)
-        .with_context(|| "invalid config json")?;
-
-    fs::create_dir_all(&args.out_dir)?;
-
-    // init and run
-    if cfg.base_goods.len() < 2 {
-        anyhow::bail!("config.goods must contain at least 2 entries");
-    }
-    if cfg.base_goods_quantity != cfg.base_goods.len() {
-        anyhow::bail!("base_goods_quantity out of bounds");
-    }
-    let goods = &cfg.base_goods;
-    let mut state = init_agents(&cfg);
-    run(&cfg, &mut state);
-
-    // write events csv
-    let events_path = format!("{}/p2p_trades.csv", args.out_dir);
-    let mut wtr = csv::Writer::from_path(&events_path)?;
-    wtr.write_record(&[
-        "round","i","j","good_a","good_a_name","good_b","good_b_name",
-        "q_ab","delta_a_i","delta_b_i","delta_u_i","delta_u_j"
-    ])?;
-    for ev in state.events.iter() {
-        wtr.write_record(&[
-            ev.round.to_string(),
-            ev.i.to_string(),
-            ev.j.to_string(),
-            ev.good_a.to_string(),
-            goods[ev.good_a].clone(),
-            ev.good_b.to_string(),
-            goods[ev.good_b].clone(),
-            format!("{:.10}", ev.q_ab),
-            format!("{:.10}", ev.delta_a_i),
-            format!("{:.10}", ev.delta_b_i),
-            format!("{:.10}", ev.delta_u_i),
-            format!("{:.10}", ev.delta_u_j),
-        ])?;
-    }
-    wtr.flush()?;
-
-    // write mean endowments
-    let mean = mean_endowments(&state);
-    let mean_path = format!("{}/endowments_mean.csv", args.out_dir);
-    let mut wtr2 = csv::Writer::from_path(&mean_path)?;
-    wtr2.write_record(&["good","name","mean_qty"])?;
-    for k in 0..mean.len() {
-        wtr2.write_record(&[
-            k.to_string(),
-            goods[k].to_string(),
-            format!("{:.10}", mean[k]),
-        ])?;
+    // spans and events of rdx-core to stderr, filtered by RUST_LOG (e.g. `rdx_core=debug`);
+    // silent when it is unset
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("off"));
+    tracing_subscriber::fmt().with_env_filter(filter).with_writer(std::io::stderr).init();
+
+    match Cli::parse().command {
+        Command::Run(args) => run::execute(&args),
+        Command::Validate(args) => {
+            let cfg = args.load()?;
+            config::ensure_valid(&cfg)?;
+            // the engine's own checks cover the optional sections too
+            let (agents, rounds, goods) = (cfg.num_agents, cfg.rounds, cfg.base_goods.len());
+            Engine::new(cfg)?;
+            println!("Config OK: {agents} agents, {goods} goods, {rounds} rounds");
+            Ok(())
+        }
+        Command::Sweep(args) => sweep::execute(&args),
+        Command::Analyze(args) => analyze::execute(&args),
+        Command::Edgeworth(args) => edgeworth::execute(&args),
+        Command::Schema => {
+            println!("{}", serde_json::to_string_pretty(&SimConfig::json_schema())?);
+            Ok(())
+        }
     }
-    wtr2.flush()?;
-
-    // persist config used
-    fs::write(format!("{}/config_used.json", args.out_dir), serde_json::to_string_pretty(&cfg)?)?;
-
-    println!("Done. Wrote:");
-    println!(" - {}", events_path);
-    println!(" - {}", mean_path);
-    println!(" - {}/config_used.json", args.out_dir);
-
-    Ok(())
 }
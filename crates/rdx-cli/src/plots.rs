@@ -0,0 +1,203 @@
+//! `rdx-cli analyze --plots`: quick-look charts of a run's output directory, drawn from the CSV
+//! traces so no Python is needed to eyeball a run.
+//!
+//! Writes into `<dir>/plots/`: `gini_utility` (from `metrics.csv`), `trade_volume` (units of
+//! each good given per round, from `exchange_rates.csv`), `price_convergence` (each good's
+//! emergent price over its Walrasian price, from `prices.csv`) and `mean_endowments` (mean
+//! holding of each good per round over the agents in `agent_trajectories.csv`, so only for runs
+//! with `--trajectories`). Charts whose trace is missing or empty are skipped.
+use anyhow::Context;
+use plotters::coord::Shift;
+use plotters::prelude::*;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Image format of the charts.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PlotFormat {
+    Svg,
+    Png,
+}
+
+impl PlotFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            PlotFormat::Svg => "svg",
+            PlotFormat::Png => "png",
+        }
+    }
+}
+
+pub fn parse_format(name: &str) -> Result<PlotFormat, String> {
+    match name {
+        "svg" => Ok(PlotFormat::Svg),
+        "png" => Ok(PlotFormat::Png),
+        _ => Err(format!("expected svg or png, found {}", name)),
+    }
+}
+
+/// One line chart: named series of (round, value) points.
+struct Chart {
+    name: &'static str,
+    title: &'static str,
+    y_label: &'static str,
+    series: BTreeMap<String, Vec<(f64, f64)>>,
+}
+
+const SIZE: (u32, u32) = (900, 540);
+
+/// Header and rows of a CSV trace, `None` if the file is not there.
+fn read_table(path: &Path) -> anyhow::Result<Option<(Vec<String>, Vec<csv::StringRecord>)>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let mut rdr = csv::Reader::from_path(path).with_context(|| format!("failed reading {}", path.display()))?;
+    let header = rdr.headers()?.iter().map(str::to_string).collect();
+    let rows = rdr.records().collect::<Result<Vec<_>, _>>()?;
+    Ok(Some((header, rows)))
+}
+
+fn column(header: &[String], name: &str, path: &Path) -> anyhow::Result<usize> {
+    header.iter().position(|h| h == name)
+        .ok_or_else(|| anyhow::anyhow!("{} has no {} column", path.display(), name))
+}
+
+fn number(row: &csv::StringRecord, c: usize) -> Option<f64> {
+    row.get(c)?.parse().ok()
+}
+
+fn gini_utility(dir: &Path) -> anyhow::Result<Option<Chart>> {
+    let path = dir.join("metrics.csv");
+    let Some((header, rows)) = read_table(&path)? else { return Ok(None) };
+    let (round, gini) = (column(&header, "round", &path)?, column(&header, "gini_utility", &path)?);
+    let points = rows.iter().filter_map(|r| Some((number(r, round)?, number(r, gini)?))).collect();
+    Ok(Some(Chart {
+        name: "gini_utility",
+        title: "Utility Gini",
+        y_label: "Gini coefficient",
+        series: BTreeMap::from([("gini_utility".to_string(), points)]),
+    }))
+}
+
+fn trade_volume(dir: &Path) -> anyhow::Result<Option<Chart>> {
+    let path = dir.join("exchange_rates.csv");
+    let Some((header, rows)) = read_table(&path)? else { return Ok(None) };
+    let round = column(&header, "round", &path)?;
+    let good = column(&header, "good_a_name", &path)?;
+    let volume = column(&header, "volume_a", &path)?;
+    let mut totals: BTreeMap<String, BTreeMap<u64, f64>> = BTreeMap::new();
+    for r in rows.iter() {
+        let (Some(t), Some(v), Some(g)) = (number(r, round), number(r, volume), r.get(good)) else { continue };
+        *totals.entry(g.to_string()).or_default().entry(t as u64).or_default() += v;
+    }
+    let series = totals.into_iter()
+        .map(|(g, by_round)| (g, by_round.into_iter().map(|(t, v)| (t as f64, v)).collect()))
+        .collect();
+    Ok(Some(Chart { name: "trade_volume", title: "Trade volume per good", y_label: "units given", series }))
+}
+
+fn price_convergence(dir: &Path) -> anyhow::Result<Option<Chart>> {
+    let path = dir.join("prices.csv");
+    let Some((header, rows)) = read_table(&path)? else { return Ok(None) };
+    let round = column(&header, "round", &path)?;
+    let good = column(&header, "good_name", &path)?;
+    let (ewma, walras) = (column(&header, "ewma", &path)?, column(&header, "walras", &path)?);
+    let mut series: BTreeMap<String, Vec<(f64, f64)>> = BTreeMap::new();
+    for r in rows.iter() {
+        let (Some(t), Some(p), Some(w), Some(g)) = (number(r, round), number(r, ewma), number(r, walras), r.get(good))
+        else { continue };
+        if w > 0.0 {
+            series.entry(g.to_string()).or_default().push((t, p / w));
+        }
+    }
+    Ok(Some(Chart {
+        name: "price_convergence",
+        title: "Emergent over Walrasian price",
+        y_label: "ewma / walras",
+        series,
+    }))
+}
+
+fn mean_endowments(dir: &Path) -> anyhow::Result<Option<Chart>> {
+    let path = dir.join("agent_trajectories.csv");
+    let Some((header, rows)) = read_table(&path)? else { return Ok(None) };
+    let round = column(&header, "round", &path)?;
+    let goods: Vec<(usize, &str)> = header.iter().enumerate()
+        .filter_map(|(c, h)| Some((c, h.strip_prefix("e_")?)))
+        .collect();
+    // per good and round: (sum, agents holding it)
+    let mut sums: BTreeMap<&str, BTreeMap<u64, (f64, usize)>> = BTreeMap::new();
+    for r in rows.iter() {
+        let Some(t) = number(r, round) else { continue };
+        for &(c, g) in goods.iter() {
+            if let Some(x) = number(r, c) {
+                let s = sums.entry(g).or_default().entry(t as u64).or_default();
+                s.0 += x;
+                s.1 += 1;
+            }
+        }
+    }
+    let series = sums.into_iter()
+        .map(|(g, by_round)| {
+            (g.to_string(), by_round.into_iter().map(|(t, (s, n))| (t as f64, s / n as f64)).collect())
+        })
+        .collect();
+    Ok(Some(Chart { name: "mean_endowments", title: "Mean endowment per good", y_label: "mean holding", series }))
+}
+
+/// Smallest and largest of `values`, widened when they coincide.
+fn extent(values: impl Iterator<Item = f64>) -> (f64, f64) {
+    let (lo, hi) = values.fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), x| (lo.min(x), hi.max(x)));
+    if lo == hi {
+        (lo - 0.5, hi + 0.5)
+    } else {
+        (lo, hi)
+    }
+}
+
+fn draw<DB: DrawingBackend>(root: DrawingArea<DB, Shift>, chart: &Chart) -> anyhow::Result<()>
+where
+    DB::ErrorType: 'static,
+{
+    let points = || chart.series.values().flatten();
+    let (x0, x1) = extent(points().map(|p| p.0));
+    let (y0, y1) = extent(points().map(|p| p.1));
+    root.fill(&WHITE)?;
+    let mut plot = ChartBuilder::on(&root)
+        .caption(chart.title, ("sans-serif", 22))
+        .margin(12)
+        .x_label_area_size(40)
+        .y_label_area_size(64)
+        .build_cartesian_2d(x0..x1, y0..y1)?;
+    plot.configure_mesh().x_desc("round").y_desc(chart.y_label).draw()?;
+    for (k, (name, series)) in chart.series.iter().enumerate() {
+        let color = Palette99::pick(k).to_rgba();
+        plot.draw_series(LineSeries::new(series.iter().copied(), color.stroke_width(2)))?
+            .label(name)
+            .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 16, y)], color));
+    }
+    plot.configure_series_labels().background_style(WHITE.mix(0.8)).border_style(BLACK).draw()?;
+    root.present()?;
+    Ok(())
+}
+
+/// Draw the charts of the run in `dir` into `<dir>/plots/`, returning the files written.
+pub fn write_plots(dir: &Path, format: PlotFormat) -> anyhow::Result<Vec<PathBuf>> {
+    let out = dir.join("plots");
+    std::fs::create_dir_all(&out).with_context(|| format!("failed creating {}", out.display()))?;
+    let charts = [mean_endowments(dir)?, gini_utility(dir)?, trade_volume(dir)?, price_convergence(dir)?];
+    let mut written = Vec::new();
+    for chart in charts.into_iter().flatten() {
+        if chart.series.values().all(Vec::is_empty) {
+            continue;
+        }
+        let path = out.join(format!("{}.{}", chart.name, format.extension()));
+        match format {
+            PlotFormat::Svg => draw(SVGBackend::new(&path, SIZE).into_drawing_area(), &chart),
+            PlotFormat::Png => draw(BitMapBackend::new(&path, SIZE).into_drawing_area(), &chart),
+        }
+        .with_context(|| format!("failed drawing {}", path.display()))?;
+        written.push(path);
+    }
+    Ok(written)
+}
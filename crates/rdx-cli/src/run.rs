@@ -0,0 +1,782 @@
+//! `rdx-cli run`: simulate one config and write its traces, plus the optional trade graph,
+//! butterfly analysis and replication bundle, to an output directory.
+use anyhow::Context;
+use clap::Args;
+use indicatif::{ProgressBar, ProgressStyle};
+use rand::prelude::*;
+use serde::Serialize;
+use rdx_core::codec::{CompressedWriter, Compression};
+use rdx_core::counterfactual::{butterfly, TradeEdit};
+use rdx_core::model::{SimConfig, TradeEvent};
+use rdx_core::replication::run_replications;
+use rdx_core::sim::{
+    mean_endowments, trade_graph, Encounter, Engine, Observer, Progress, ProgressCallback, RoundMetrics, SimState,
+};
+use rdx_core::trajectory::{AgentSelection, TrajectoryPoint, TrajectoryRecorder};
+use std::sync::{Arc, Mutex};
+use std::fs;
+use crate::bundle;
+use crate::config::{ensure_valid, ConfigArgs};
+
+#[derive(Args, Debug)]
+pub struct RunArgs {
+    #[command(flatten)]
+    pub config: ConfigArgs,
+
+    /// Output directory
+    #[arg(long, default_value="out")]
+    pub out_dir: String,
+
+    /// Save a resumable checkpoint to `<out-dir>/checkpoint.rdx` every N rounds
+    #[arg(long)]
+    pub checkpoint_every: Option<usize>,
+
+    /// Compress the trade event log (deflate writes p2p_trades.csv.gz, zstd p2p_trades.csv.zst)
+    #[arg(long, value_parser = parse_compression, default_value = "none")]
+    pub compress_events: Compression,
+
+    /// Also write p2p_trades.parquet and metrics.parquet (Snappy-compressed)
+    #[cfg(feature = "arrow")]
+    #[arg(long)]
+    pub parquet: bool,
+
+    /// Also write the run as `csv` traces only (default) or into `<out-dir>/run.sqlite` as well
+    /// (`sqlite`, CLI built with `--features sqlite`)
+    #[arg(long, value_parser = parse_output, default_value = "csv")]
+    pub output: Output,
+
+    /// Print every trade event and round's metrics to stdout as JSON lines while the run
+    /// progresses (the usual messages go to stderr)
+    #[arg(long)]
+    pub stream: bool,
+
+    /// No progress bar
+    #[arg(long)]
+    pub quiet: bool,
+
+    /// Show a live dashboard of the run in the terminal
+    #[cfg(feature = "tui")]
+    #[arg(long, conflicts_with = "stream")]
+    pub tui: bool,
+
+    /// Record agents' holdings and utility every round into `<out-dir>/agent_trajectories.csv`:
+    /// `all`, `sample:N` (N agents drawn with the config's seed) or `ids:3,17,42`
+    #[arg(long, value_parser = parse_selection)]
+    pub trajectories: Option<AgentSelection>,
+
+    /// Record why each encounter without a trade failed into `<out-dir>/rejections.csv`
+    #[arg(long)]
+    pub rejections: bool,
+
+    /// Continue the run saved in this checkpoint; `--overlay` and `--set` apply on top of its
+    /// config, `--config` and `--preset` are ignored
+    #[arg(long)]
+    pub resume: Option<String>,
+
+    /// Write the trade graph; format from the extension (.dot / .gv or .graphml)
+    #[arg(long)]
+    pub graph_out: Option<String>,
+
+    /// Butterfly analysis: branch the run at this trade (row of p2p_trades.csv), edited, and
+    /// replay its encounters forward (fresh runs only)
+    #[arg(long, conflicts_with = "resume")]
+    pub butterfly_trade: Option<usize>,
+
+    /// Scale the butterfly trade by this factor instead of removing it
+    #[arg(long, requires="butterfly_trade")]
+    pub butterfly_scale: Option<f64>,
+
+    /// Also pack config, manifest, metrics, figures and an event sample into this .tar.gz
+    #[arg(long)]
+    pub bundle: Option<String>,
+
+    /// Number of trade events sampled into the bundle
+    #[arg(long, default_value_t=1000, requires="bundle")]
+    pub bundle_events: usize,
+
+    /// Extra figure to include in the bundle (repeatable)
+    #[arg(long, requires="bundle")]
+    pub bundle_figure: Vec<String>,
+}
+
+pub const EVENT_COLUMNS: [&str; 19] = [
+    "round","i","j","id_i","id_j","good_a","good_a_name","good_b","good_b_name",
+    "q_ab","delta_a_i","delta_b_i","delta_u_i","delta_u_j","cost_i","cost_j","tax","time","seq"
+];
+
+pub fn event_record(ev: &TradeEvent, goods: &[String]) -> Vec<String> {
+    vec![
+        ev.round.to_string(),
+        ev.i.to_string(),
+        ev.j.to_string(),
+        ev.id_i.to_string(),
+        ev.id_j.to_string(),
+        ev.good_a.to_string(),
+        goods[ev.good_a.index()].clone(),
+        ev.good_b.to_string(),
+        goods[ev.good_b.index()].clone(),
+        format!("{:.10}", ev.q_ab),
+        format!("{:.10}", ev.delta_a_i),
+        format!("{:.10}", ev.delta_b_i),
+        format!("{:.10}", ev.delta_u_i),
+        format!("{:.10}", ev.delta_u_j),
+        format!("{:.10}", ev.cost_i),
+        format!("{:.10}", ev.cost_j),
+        format!("{:.10}", ev.tax),
+        ev.time.map_or(String::new(), |t| format!("{:.3}", t)),
+        ev.seq.to_string(),
+    ]
+}
+
+fn parse_compression(name: &str) -> Result<Compression, String> {
+    let compression = Compression::from_name(name)
+        .ok_or_else(|| format!("expected none, deflate or zstd, found {}", name))?;
+    if !compression.is_enabled() {
+        return Err(format!("{} support is not compiled in (enable the `{}` feature)", name, compression.feature()));
+    }
+    Ok(compression)
+}
+
+#[cfg(feature = "arrow")]
+fn write_parquet(batch: &arrow_array::RecordBatch, path: &str) -> anyhow::Result<()> {
+    use parquet::arrow::ArrowWriter;
+    use parquet::basic::Compression as ParquetCompression;
+    use parquet::file::properties::WriterProperties;
+    let props = WriterProperties::builder().set_compression(ParquetCompression::SNAPPY).build();
+    let file = fs::File::create(path).with_context(|| format!("failed creating {}", path))?;
+    let mut writer = ArrowWriter::try_new(file, batch.schema(), Some(props))?;
+    writer.write(batch)?;
+    writer.close()?;
+    Ok(())
+}
+
+/// One line of `run --stream` output: the record, tagged with its `kind`.
+#[derive(Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum StreamRecord<'a> {
+    Trade(&'a TradeEvent),
+    Round(&'a RoundMetrics),
+}
+
+/// Observer writing each trade and round as a JSON line, flushed per record so a reader sees
+/// them as they happen. Write errors (a closed pipe) stop the output, not the run.
+pub struct JsonLines<W: std::io::Write> {
+    out: Option<W>,
+}
+
+impl<W: std::io::Write> JsonLines<W> {
+    pub fn new(out: W) -> Self {
+        JsonLines { out: Some(out) }
+    }
+
+    fn emit(&mut self, record: StreamRecord) {
+        let Some(out) = &mut self.out else { return };
+        let written = serde_json::to_writer(&mut *out, &record)
+            .map_err(std::io::Error::from)
+            .and_then(|()| out.write_all(b"\n"))
+            .and_then(|()| out.flush());
+        if written.is_err() {
+            self.out = None;
+        }
+    }
+}
+
+impl<W: std::io::Write> Observer for JsonLines<W> {
+    fn on_trade(&mut self, event: &TradeEvent) {
+        self.emit(StreamRecord::Trade(event));
+    }
+
+    fn on_round_end(&mut self, metrics: &RoundMetrics, _state: &SimState) {
+        self.emit(StreamRecord::Round(metrics));
+    }
+}
+
+/// Where `run` writes besides the CSV traces.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Output {
+    Csv,
+    Sqlite,
+}
+
+fn parse_output(name: &str) -> Result<Output, String> {
+    match name {
+        "csv" => Ok(Output::Csv),
+        "sqlite" if cfg!(feature = "sqlite") => Ok(Output::Sqlite),
+        "sqlite" => Err("sqlite support is not compiled in (enable the `sqlite` feature)".to_string()),
+        _ => Err(format!("expected csv or sqlite, found {}", name)),
+    }
+}
+
+/// `all`, `sample:N` or `ids:a,b,...`; a sample's seed is filled in from the config by `execute`.
+fn parse_selection(spec: &str) -> Result<AgentSelection, String> {
+    if spec == "all" {
+        return Ok(AgentSelection::All);
+    }
+    if let Some(n) = spec.strip_prefix("sample:") {
+        let count = n.parse().map_err(|_| format!("sample size must be a whole number, found {}", n))?;
+        return Ok(AgentSelection::Sample { count, seed: 0 });
+    }
+    if let Some(list) = spec.strip_prefix("ids:") {
+        let ids = list.split(',')
+            .map(|id| id.trim().parse().map_err(|_| format!("agent ids must be whole numbers, found {}", id)))
+            .collect::<Result<Vec<u64>, String>>()?;
+        return Ok(AgentSelection::Ids(ids));
+    }
+    Err(format!("expected all, sample:N or ids:a,b,..., found {}", spec))
+}
+
+/// One row per point: round, id, utility and the holding of each good (blank for goods not
+/// present that round).
+fn write_trajectories(points: &[TrajectoryPoint], goods: &[String], path: &str) -> anyhow::Result<()> {
+    let mut wtr = csv::Writer::from_path(path).with_context(|| format!("failed creating {}", path))?;
+    let mut header = vec!["round".to_string(), "id".to_string(), "utility".to_string()];
+    header.extend(goods.iter().map(|g| format!("e_{}", g)));
+    wtr.write_record(&header)?;
+    for p in points.iter() {
+        let mut row = vec![p.round.to_string(), p.id.to_string(), format!("{:.10}", p.utility)];
+        row.extend((0..goods.len()).map(|g| p.e.get(g).map_or(String::new(), |x| format!("{:.10}", x))));
+        wtr.write_record(&row)?;
+    }
+    wtr.flush()?;
+    Ok(())
+}
+
+/// A progress bar on stderr over rounds `first_round..rounds` (encounters of the round so far,
+/// acceptance rate, ETA) and the engine callback driving it.
+fn progress_bar(first_round: usize, rounds: usize) -> (ProgressBar, ProgressCallback) {
+    let style = ProgressStyle::with_template("{bar:40} round {pos}/{len} {msg} [{elapsed_precise}, ETA {eta}]")
+        .expect("valid template");
+    let bar = ProgressBar::new(rounds as u64).with_style(style).with_position(first_round as u64);
+    let handle = bar.clone();
+    let callback: ProgressCallback = Box::new(move |p: &Progress| {
+        if p.round_done {
+            handle.set_position(p.round as u64 + 1);
+        }
+        handle.set_message(format!(
+            "{} encounters, {:.1}% accepted",
+            p.encounters,
+            100.0 * p.acceptance_rate(),
+        ));
+    });
+    (bar, callback)
+}
+
+/// Paths of the files written by `simulate` (and the config as written).
+pub struct Outputs {
+    pub events: String,
+    pub mean: String,
+    pub metrics: String,
+    pub rates: String,
+    pub prices: String,
+    pub partners: Option<String>,
+    pub cycles: Option<String>,
+    pub aggregate: Option<String>,
+    pub checkpoint: Option<String>,
+    pub rejections: Option<String>,
+    pub config_json: String,
+}
+
+/// Run `cfg` and write the standard traces and `config_used.json` into `out_dir`.
+pub fn simulate(cfg: &SimConfig, out_dir: &str) -> anyhow::Result<(SimState, Outputs)> {
+    simulate_from(cfg, None, None, Compression::None, Vec::new(), None, false, None, out_dir)
+}
+
+/// Engine progress callback: report every this many encounters.
+const PROGRESS_EVERY: usize = 256;
+
+/// `simulate`, continuing `resume` (a checkpoint taken under `cfg`) instead of starting
+/// afresh, saving `<out_dir>/checkpoint.rdx` every `checkpoint_every` rounds, compressing
+/// the event log with `events_compression`, registering `observers` on the engine,
+/// reporting to `progress`, with `rejections`, writing `<out_dir>/rejections.csv` and, with
+/// `encounters`, recording the run's encounter schedule into it.
+#[allow(clippy::too_many_arguments)]
+pub fn simulate_from(
+    cfg: &SimConfig,
+    resume: Option<SimState>,
+    checkpoint_every: Option<usize>,
+    events_compression: Compression,
+    observers: Vec<Box<dyn Observer + Send>>,
+    progress: Option<ProgressCallback>,
+    rejections: bool,
+    encounters: Option<&mut Vec<Encounter>>,
+    out_dir: &str,
+) -> anyhow::Result<(SimState, Outputs)> {
+    fs::create_dir_all(out_dir)?;
+    let goods = &cfg.all_goods();
+    let fresh = resume.is_none();
+    let mut engine = match resume {
+        Some(state) => Engine::resume(state)?,
+        None => Engine::new(cfg.clone())?,
+    };
+    for observer in observers {
+        engine.add_observer(observer);
+    }
+    if let Some(callback) = progress {
+        engine.set_progress(PROGRESS_EVERY, callback);
+    }
+    if rejections {
+        engine.record_rejections();
+    }
+    if encounters.is_some() {
+        engine.record_encounters();
+    }
+    let checkpoint_path = format!("{}/checkpoint.rdx", out_dir);
+    let mut checkpoint = None;
+    while engine.step_round().is_some() {
+        if checkpoint_every.is_some_and(|n| n > 0 && engine.round() % n == 0) && !engine.is_finished() {
+            engine.checkpoint().save(&checkpoint_path)?;
+            checkpoint = Some(checkpoint_path.clone());
+        }
+    }
+
+    // encounters without a trade, with the reason
+    let mut rejections_path = None;
+    if rejections {
+        let path = format!("{}/rejections.csv", out_dir);
+        let mut wtr = csv::Writer::from_path(&path)?;
+        wtr.write_record(["round","i","j","reason"])?;
+        for r in engine.rejection_log().iter() {
+            wtr.write_record(&[r.round.to_string(), r.i.to_string(), r.j.to_string(), r.reason.to_string()])?;
+        }
+        wtr.flush()?;
+        rejections_path = Some(path);
+    }
+    if let Some(log) = encounters {
+        log.extend_from_slice(engine.encounter_log());
+    }
+    let state = engine.finish();
+
+    // write events csv
+    let events_path = format!("{}/p2p_trades.csv{}", out_dir, events_compression.extension());
+    let file = std::io::BufWriter::new(fs::File::create(&events_path)?);
+    let mut wtr = csv::Writer::from_writer(CompressedWriter::new(events_compression, file)?);
+    wtr.write_record(EVENT_COLUMNS)?;
+    for ev in state.events.iter() {
+        wtr.write_record(event_record(ev, goods))?;
+    }
+    let mut file = wtr.into_inner().map_err(|e| anyhow::anyhow!("event log: {}", e))?.finish()?;
+    std::io::Write::flush(&mut file)?;
+
+    // write mean endowments
+    let mean = mean_endowments(&state);
+    let mean_path = format!("{}/endowments_mean.csv", out_dir);
+    let mut wtr2 = csv::Writer::from_path(&mean_path)?;
+    wtr2.write_record(["good","name","mean_qty"])?;
+    for k in 0..mean.len() {
+        wtr2.write_record(&[
+            k.to_string(),
+            goods[k].to_string(),
+            format!("{:.10}", mean[k]),
+        ])?;
+    }
+    wtr2.flush()?;
+
+    // write per-round metrics
+    let metrics_path = format!("{}/metrics.csv", out_dir);
+    let mut wtr3 = csv::Writer::from_path(&metrics_path)?;
+    wtr3.write_record([
+        "round","encounters","trades","delta_u","embargoed",
+        "time","population","entered","exited","shocked","preference_shocked","consumed","decayed","replenished","costs","cost_refused","cap_refused","over_budget","credit_drawn","credit_repaid","money_velocity","credit_utilization","cycles","speculative","money_good","tax_revenue","redistributed","mistakes","active_traders","reneged","mean_reputation","listings","market_sales","broker_trades","broker_wealth","imitations","pareto_gap","oracle_cache_hits","oracle_cache_misses","oracle_degenerate","oracle_unbracketed","oracle_clamped","holders","mean_wealth","w_p10","w_p25","w_p50","w_p75","w_p90",
+        "gini_wealth","theil_wealth","gini_utility","u_p10","u_p25","u_p50","u_p75","u_p90"
+    ])?;
+    for m in state.metrics.iter() {
+        let embargoed: Vec<String> = m.embargoed.iter().map(|g| g.to_string()).collect();
+        let holders: Vec<String> = m.holders.iter().map(|h| h.to_string()).collect();
+        let ineq = &m.inequality;
+        let mut row = vec![
+            m.round.to_string(),
+            m.encounters.to_string(),
+            m.trades.to_string(),
+            format!("{:.10}", m.delta_u),
+            embargoed.join(";"),
+            m.time.map_or(String::new(), |t| format!("{:.3}", t)),
+            m.population.to_string(),
+            m.entered.to_string(),
+            m.exited.to_string(),
+            m.shocked.to_string(),
+            m.preference_shocked.to_string(),
+            format!("{:.10}", m.consumed),
+            format!("{:.10}", m.decayed),
+            format!("{:.10}", m.replenished),
+            format!("{:.10}", m.costs),
+            m.cost_refused.to_string(),
+            m.cap_refused.to_string(),
+            m.over_budget.to_string(),
+            format!("{:.10}", m.credit_drawn),
+            format!("{:.10}", m.credit_repaid),
+            format!("{:.10}", m.money_velocity),
+            format!("{:.10}", m.credit_utilization),
+            m.cycles.to_string(),
+            m.speculative.to_string(),
+            m.money_good.map_or(String::new(), |g| g.to_string()),
+            format!("{:.10}", m.tax_revenue),
+            format!("{:.10}", m.redistributed),
+            m.mistakes.to_string(),
+            m.active_traders.to_string(),
+            m.reneged.to_string(),
+            format!("{:.10}", m.mean_reputation),
+            m.listings.to_string(),
+            m.market_sales.to_string(),
+            m.broker_trades.to_string(),
+            format!("{:.10}", m.broker_wealth),
+            m.imitations.to_string(),
+            format!("{:.10}", m.pareto_gap),
+            m.oracle_cache_hits.to_string(),
+            m.oracle_cache_misses.to_string(),
+            m.oracle_degenerate.to_string(),
+            m.oracle_unbracketed.to_string(),
+            m.oracle_clamped.to_string(),
+            holders.join(";"),
+            format!("{:.10}", ineq.mean_wealth),
+        ];
+        row.extend(ineq.wealth_quantiles.iter().map(|w| format!("{:.10}", w)));
+        row.push(format!("{:.10}", ineq.gini_wealth));
+        row.push(format!("{:.10}", ineq.theil_wealth));
+        row.push(format!("{:.10}", ineq.gini_utility));
+        row.extend(ineq.utility_quantiles.iter().map(|u| format!("{:.10}", u)));
+        wtr3.write_record(&row)?;
+    }
+    wtr3.flush()?;
+
+    // write per-round volume-weighted exchange rates
+    let rates_path = format!("{}/exchange_rates.csv", out_dir);
+    let mut wtr4 = csv::Writer::from_path(&rates_path)?;
+    wtr4.write_record([
+        "round","good_a","good_a_name","good_b","good_b_name",
+        "trades","volume_a","mean_q_ab","std_q_ab","min_q_ab","max_q_ab"
+    ])?;
+    for r in state.exchange_rates.iter() {
+        wtr4.write_record(&[
+            r.round.to_string(),
+            r.good_a.to_string(),
+            goods[r.good_a.index()].clone(),
+            r.good_b.to_string(),
+            goods[r.good_b.index()].clone(),
+            r.trades.to_string(),
+            format!("{:.10}", r.volume_a),
+            format!("{:.10}", r.mean_q_ab),
+            format!("{:.10}", r.std_q_ab),
+            format!("{:.10}", r.min_q_ab),
+            format!("{:.10}", r.max_q_ab),
+        ])?;
+    }
+    wtr4.flush()?;
+
+    // write per-round emergent price index
+    let prices_path = format!("{}/prices.csv", out_dir);
+    let mut wtr5 = csv::Writer::from_path(&prices_path)?;
+    wtr5.write_record(["round","good","good_name","observed","ewma","walras"])?;
+    for p in state.prices.iter() {
+        wtr5.write_record(&[
+            p.round.to_string(),
+            p.good.to_string(),
+            goods[p.good.index()].clone(),
+            p.observed.map_or(String::new(), |q| format!("{:.10}", q)),
+            format!("{:.10}", p.ewma),
+            format!("{:.10}", p.walras),
+        ])?;
+    }
+    wtr5.flush()?;
+
+    // trade ties formed under persistent matching
+    let partner_edges = state.partners.edges();
+    let mut partners_path = None;
+    if !partner_edges.is_empty() {
+        let path = format!("{}/partners.csv", out_dir);
+        let mut wtr = csv::Writer::from_path(&path)?;
+        wtr.write_record(["i","j","weight"])?;
+        for (i, j, w) in partner_edges {
+            wtr.write_record(&[i.to_string(), j.to_string(), format!("{:.10}", w)])?;
+        }
+        wtr.flush()?;
+        partners_path = Some(path);
+    }
+
+    // three-way trade cycles
+    let mut cycles_path = None;
+    if !state.cycles.is_empty() {
+        let path = format!("{}/cycles.csv", out_dir);
+        let mut wtr = csv::Writer::from_path(&path)?;
+        wtr.write_record([
+            "round","i","j","k","id_i","id_j","id_k","good_i","good_j","good_k",
+            "qty_i","qty_j","qty_k","delta_u_i","delta_u_j","delta_u_k"
+        ])?;
+        for c in state.cycles.iter() {
+            let mut row: Vec<String> = vec![c.round.to_string()];
+            row.extend(c.agents.iter().map(|a| a.to_string()));
+            row.extend(c.ids.iter().map(|id| id.to_string()));
+            row.extend(c.goods.iter().map(|g| goods[g.index()].clone()));
+            row.extend(c.quantities.iter().chain(c.delta_u.iter()).map(|x| format!("{:.10}", x)));
+            wtr.write_record(&row)?;
+        }
+        wtr.flush()?;
+        cycles_path = Some(path);
+    }
+
+    // Monte Carlo replicates over consecutive seeds (whole runs, so not for a resumed one)
+    let mut aggregate_path = None;
+    let replications = if fresh { run_replications(cfg)? } else { None };
+    if let Some(rows) = replications {
+        let path = format!("{}/metrics_aggregate.csv", out_dir);
+        let mut wtr = csv::Writer::from_path(&path)?;
+        wtr.write_record(["round","metric","runs","mean","std_dev","ci_low","ci_high"])?;
+        for r in rows.iter() {
+            wtr.write_record(&[
+                r.round.to_string(),
+                r.metric.clone(),
+                r.runs.to_string(),
+                format!("{:.10}", r.mean),
+                format!("{:.10}", r.std_dev),
+                format!("{:.10}", r.ci_low),
+                format!("{:.10}", r.ci_high),
+            ])?;
+        }
+        wtr.flush()?;
+        aggregate_path = Some(path);
+    }
+
+    // persist config used
+    let config_json = serde_json::to_string_pretty(cfg)?;
+    fs::write(format!("{}/config_used.json", out_dir), &config_json)?;
+
+    let outputs = Outputs {
+        events: events_path,
+        mean: mean_path,
+        metrics: metrics_path,
+        rates: rates_path,
+        prices: prices_path,
+        partners: partners_path,
+        cycles: cycles_path,
+        aggregate: aggregate_path,
+        checkpoint,
+        rejections: rejections_path,
+        config_json,
+    };
+    Ok((state, outputs))
+}
+
+/// `rdx-cli run`.
+pub fn execute(args: &RunArgs) -> anyhow::Result<()> {
+    // with --stream, stdout carries JSON lines only
+    macro_rules! say {
+        ($($arg:tt)*) => { if args.stream { eprintln!($($arg)*) } else { println!($($arg)*) } };
+    }
+    let (cfg, resume) = match &args.resume {
+        Some(path) => {
+            let mut saved = SimState::load(path).with_context(|| format!("failed reading checkpoint: {}", path))?;
+            let point = saved.resume.as_mut()
+                .ok_or_else(|| anyhow::anyhow!("{} is a saved state, not a resumable checkpoint", path))?;
+            let cfg = args.config.apply_to(point.config())?;
+            ensure_valid(&cfg)?;
+            *point.config_mut() = cfg.clone();
+            say!("Resuming {} at round {}", path, point.round());
+            (cfg, Some(saved))
+        }
+        None => {
+            let cfg = args.config.load()?;
+            ensure_valid(&cfg)?;
+            (cfg, None)
+        }
+    };
+    let mut observers: Vec<Box<dyn Observer + Send>> = Vec::new();
+    if args.stream {
+        observers.push(Box::new(JsonLines::new(std::io::stdout())));
+    }
+    #[cfg(feature = "tui")]
+    let dashboard_shown = args.tui;
+    #[cfg(not(feature = "tui"))]
+    let dashboard_shown = false;
+    #[cfg(feature = "tui")]
+    if args.tui {
+        let dashboard = crate::tui::Dashboard::new(cfg.all_goods(), cfg.rounds, cfg.min_qty)
+            .context("failed starting the dashboard")?;
+        observers.push(Box::new(dashboard));
+    }
+    let recorder = args.trajectories.clone().map(|selection| {
+        let selection = match selection {
+            AgentSelection::Sample { count, .. } => AgentSelection::Sample { count, seed: cfg.seed },
+            other => other,
+        };
+        Arc::new(Mutex::new(TrajectoryRecorder::new(selection, cfg.min_qty)))
+    });
+    if let Some(handle) = &recorder {
+        observers.push(Box::new(handle.clone()));
+    }
+    let first_round = resume.as_ref().and_then(|s| s.resume.as_ref()).map_or(0, |p| p.round());
+    let (bar, progress) = if args.quiet || dashboard_shown {
+        (None, None)
+    } else {
+        let (bar, callback) = progress_bar(first_round, cfg.rounds);
+        (Some(bar), Some(callback))
+    };
+    // the butterfly analysis branches from this run's encounters
+    let mut log = Vec::new();
+    let (state, out) = simulate_from(
+        &cfg, resume, args.checkpoint_every, args.compress_events, observers, progress, args.rejections,
+        args.butterfly_trade.is_some().then_some(&mut log), &args.out_dir,
+    )?;
+    if let Some(bar) = bar {
+        bar.finish_and_clear();
+    }
+    let goods = &cfg.all_goods();
+
+    match args.output {
+        Output::Csv => {}
+        #[cfg(feature = "sqlite")]
+        Output::Sqlite => {
+            let path = format!("{}/run.sqlite", args.out_dir);
+            crate::sqlite::write(&cfg, &state, &path).with_context(|| format!("failed writing {}", path))?;
+        }
+        #[cfg(not(feature = "sqlite"))]
+        Output::Sqlite => unreachable!("parse_output refuses sqlite without the feature"),
+    }
+
+    let points = recorder.map(|handle| handle.lock().unwrap().points().to_vec());
+    let mut trajectories_path = None;
+    if let Some(points) = &points {
+        let path = format!("{}/agent_trajectories.csv", args.out_dir);
+        write_trajectories(points, goods, &path)?;
+        trajectories_path = Some(path);
+    }
+
+    #[cfg(feature = "arrow")]
+    if args.parquet {
+        write_parquet(&state.events_batch()?, &format!("{}/p2p_trades.parquet", args.out_dir))?;
+        write_parquet(&state.metrics_batch()?, &format!("{}/metrics.parquet", args.out_dir))?;
+        if let Some(points) = &points {
+            let batch = rdx_core::arrow::trajectories_batch(points)?;
+            write_parquet(&batch, &format!("{}/agent_trajectories.parquet", args.out_dir))?;
+        }
+    }
+
+    // trade graph export
+    if let Some(path) = &args.graph_out {
+        let graph = trade_graph(&state);
+        let body = if path.ends_with(".graphml") {
+            graph.to_graphml(goods)
+        } else if path.ends_with(".dot") || path.ends_with(".gv") {
+            graph.to_dot(goods)
+        } else {
+            anyhow::bail!("--graph-out must end in .dot, .gv or .graphml: {}", path);
+        };
+        fs::write(path, body).with_context(|| format!("failed writing graph: {}", path))?;
+    }
+
+    // optional butterfly analysis of one trade
+    let mut butterfly_path = None;
+    if let Some(k) = args.butterfly_trade {
+        let edit = args.butterfly_scale.map_or(TradeEdit::Remove, TradeEdit::Scale);
+        let report = butterfly(&cfg, &log, k, edit)?;
+        let path = format!("{}/butterfly.csv", args.out_dir);
+        let mut wtr5 = csv::Writer::from_path(&path)?;
+        wtr5.write_record([
+            "round","endowment_l1","utility_l1","welfare_delta","agents_affected",
+            "trades_baseline","trades_variant"
+        ])?;
+        for r in report.rounds.iter() {
+            wtr5.write_record(&[
+                r.round.to_string(),
+                format!("{:.10}", r.endowment_l1),
+                format!("{:.10}", r.utility_l1),
+                format!("{:.10}", r.welfare_delta),
+                r.agents_affected.to_string(),
+                r.trades_baseline.to_string(),
+                r.trades_variant.to_string(),
+            ])?;
+        }
+        wtr5.flush()?;
+        butterfly_path = Some(path);
+    }
+
+    // optional replication bundle
+    if let Some(path) = &args.bundle {
+        // seeded, order-preserving sample of events
+        let n_sample = args.bundle_events.min(state.events.len());
+        let mut picked = rand::seq::index::sample(&mut StdRng::seed_from_u64(cfg.seed), state.events.len(), n_sample)
+            .into_vec();
+        picked.sort_unstable();
+        let mut sample = csv::Writer::from_writer(Vec::new());
+        let mut header = vec!["event"];
+        header.extend(EVENT_COLUMNS);
+        sample.write_record(&header)?;
+        for &k in picked.iter() {
+            let mut row = vec![k.to_string()];
+            row.extend(event_record(&state.events[k], goods));
+            sample.write_record(&row)?;
+        }
+        let sample = sample.into_inner().map_err(|e| anyhow::anyhow!("event sample: {}", e))?;
+
+        let manifest = serde_json::json!({
+            "format": bundle::BUNDLE_FORMAT,
+            "rdx_cli_version": env!("CARGO_PKG_VERSION"),
+            "seed": cfg.seed,
+            "config_sha256": bundle::sha256_hex(out.config_json.as_bytes()),
+            "num_agents": cfg.num_agents,
+            "rounds_configured": cfg.rounds,
+            "rounds_run": state.metrics.len(),
+            "stopped_at": state.stopped_at,
+            "events": state.events.len(),
+            "events_sampled": n_sample,
+        });
+        let mut artifacts = vec![
+            bundle::Artifact::new("config.json", "config", out.config_json.into_bytes()),
+            bundle::Artifact::new("manifest.json", "manifest", serde_json::to_vec_pretty(&manifest)?),
+            bundle::Artifact::from_file(&out.metrics, "metrics/", "metrics")?,
+            bundle::Artifact::from_file(&out.rates, "metrics/", "metrics")?,
+            bundle::Artifact::from_file(&out.prices, "metrics/", "metrics")?,
+            bundle::Artifact::from_file(&out.mean, "metrics/", "metrics")?,
+            bundle::Artifact::new("events/p2p_trades_sample.csv", "events", sample),
+        ];
+        let extra = [&out.partners, &out.cycles, &out.aggregate, &butterfly_path];
+        for p in extra.into_iter().flatten() {
+            artifacts.push(bundle::Artifact::from_file(p, "metrics/", "metrics")?);
+        }
+        if let Some(p) = &args.graph_out {
+            artifacts.push(bundle::Artifact::from_file(p, "figures/", "graph")?);
+        }
+        for p in args.bundle_figure.iter() {
+            artifacts.push(bundle::Artifact::from_file(p, "figures/", "figure")?);
+        }
+        bundle::write_bundle(path, &artifacts)?;
+    }
+
+    if let Some(t) = state.stopped_at {
+        say!("Converged: stopped after round {}", t);
+    }
+    say!("Done. Wrote:");
+    say!(" - {}", out.events);
+    say!(" - {}", out.mean);
+    say!(" - {}", out.metrics);
+    say!(" - {}", out.rates);
+    say!(" - {}", out.prices);
+    if let Some(p) = &args.graph_out {
+        say!(" - {}", p);
+    }
+    if let Some(p) = &out.partners {
+        say!(" - {}", p);
+    }
+    if let Some(p) = &out.cycles {
+        say!(" - {}", p);
+    }
+    if let Some(p) = &out.aggregate {
+        say!(" - {}", p);
+    }
+    if let Some(p) = &out.checkpoint {
+        say!(" - {}", p);
+    }
+    if let Some(p) = &out.rejections {
+        say!(" - {}", p);
+    }
+    if let Some(p) = &butterfly_path {
+        say!(" - {}", p);
+    }
+    if let Some(p) = &trajectories_path {
+        say!(" - {}", p);
+    }
+    say!(" - {}/config_used.json", args.out_dir);
+    if let Some(p) = &args.bundle {
+        say!(" - {}", p);
+    }
+
+    Ok(())
+}
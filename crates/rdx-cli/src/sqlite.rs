@@ -0,0 +1,149 @@
+//! `rdx-cli run --output sqlite`: one run in a single SQLite database, for exploring with SQL
+//! instead of joining CSVs.
+//!
+//! Tables: `config` (the config as JSON, one row; query it with `json_extract`), `goods`,
+//! `events` (one row per trade, keyed by `seq`, indexed on `round`, `i`, `j` and `good_a`),
+//! `metrics` (one row per round; per-good vectors as JSON arrays), `agents` (the final
+//! population) and `agent_goods` (each agent's final holding of and exponent on each good).
+use rdx_core::model::SimConfig;
+use rdx_core::preferences::cd_utility;
+use rdx_core::sim::SimState;
+use rusqlite::{params, Connection};
+
+const SCHEMA: &str = "
+CREATE TABLE config (json TEXT NOT NULL);
+CREATE TABLE goods (good INTEGER PRIMARY KEY, name TEXT NOT NULL);
+CREATE TABLE events (
+    seq INTEGER PRIMARY KEY, round INTEGER NOT NULL, i INTEGER NOT NULL, j INTEGER NOT NULL,
+    id_i INTEGER NOT NULL, id_j INTEGER NOT NULL, good_a INTEGER NOT NULL, good_b INTEGER NOT NULL,
+    q_ab REAL NOT NULL, delta_a_i REAL NOT NULL, delta_b_i REAL NOT NULL,
+    delta_u_i REAL NOT NULL, delta_u_j REAL NOT NULL,
+    cost_i REAL NOT NULL, cost_j REAL NOT NULL, tax REAL NOT NULL, time REAL, speculative INTEGER NOT NULL
+);
+CREATE INDEX events_round ON events (round);
+CREATE INDEX events_i ON events (i);
+CREATE INDEX events_j ON events (j);
+CREATE INDEX events_good_a ON events (good_a);
+CREATE TABLE metrics (
+    round INTEGER PRIMARY KEY, encounters INTEGER NOT NULL, trades INTEGER NOT NULL, delta_u REAL NOT NULL,
+    time REAL, population INTEGER NOT NULL, entered INTEGER NOT NULL, exited INTEGER NOT NULL,
+    consumed REAL NOT NULL, decayed REAL NOT NULL, replenished REAL NOT NULL, costs REAL NOT NULL,
+    credit_drawn REAL NOT NULL, credit_repaid REAL NOT NULL, money_velocity REAL NOT NULL,
+    cycles INTEGER NOT NULL, money_good INTEGER, tax_revenue REAL NOT NULL, redistributed REAL NOT NULL,
+    active_traders INTEGER NOT NULL, market_sales INTEGER NOT NULL, broker_trades INTEGER NOT NULL,
+    pareto_gap REAL NOT NULL, mean_wealth REAL NOT NULL, gini_wealth REAL NOT NULL,
+    theil_wealth REAL NOT NULL, gini_utility REAL NOT NULL,
+    prices TEXT NOT NULL, holders TEXT NOT NULL, embargoed TEXT NOT NULL
+);
+CREATE TABLE agents (
+    idx INTEGER NOT NULL, id INTEGER PRIMARY KEY, utility REAL NOT NULL, debt REAL NOT NULL,
+    reputation REAL NOT NULL, broker INTEGER NOT NULL, labels TEXT NOT NULL
+);
+CREATE TABLE agent_goods (
+    agent INTEGER NOT NULL REFERENCES agents (id), good INTEGER NOT NULL REFERENCES goods (good),
+    quantity REAL NOT NULL, beta REAL NOT NULL, PRIMARY KEY (agent, good)
+);
+";
+
+/// Write `state` (a finished run of `cfg`) to a new database at `path`, replacing any file there.
+pub fn write(cfg: &SimConfig, state: &SimState, path: &str) -> anyhow::Result<()> {
+    if std::path::Path::new(path).exists() {
+        std::fs::remove_file(path)?;
+    }
+    let mut db = Connection::open(path)?;
+    let tx = db.transaction()?;
+    tx.execute_batch(SCHEMA)?;
+    tx.execute("INSERT INTO config (json) VALUES (?1)", params![serde_json::to_string(cfg)?])?;
+    {
+        let mut goods = tx.prepare("INSERT INTO goods VALUES (?1, ?2)")?;
+        for (k, name) in cfg.all_goods().iter().enumerate() {
+            goods.execute(params![k as i64, name])?;
+        }
+
+        let mut events = tx.prepare(
+            "INSERT INTO events VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, \
+             ?17, ?18)",
+        )?;
+        for ev in state.events.iter() {
+            events.execute(params![
+                ev.seq as i64,
+                ev.round as i64,
+                ev.i.index() as i64,
+                ev.j.index() as i64,
+                ev.id_i as i64,
+                ev.id_j as i64,
+                ev.good_a.index() as i64,
+                ev.good_b.index() as i64,
+                ev.q_ab,
+                ev.delta_a_i,
+                ev.delta_b_i,
+                ev.delta_u_i,
+                ev.delta_u_j,
+                ev.cost_i,
+                ev.cost_j,
+                ev.tax,
+                ev.time,
+                ev.speculative,
+            ])?;
+        }
+
+        let mut metrics = tx.prepare(
+            "INSERT INTO metrics VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, \
+             ?17, ?18, ?19, ?20, ?21, ?22, ?23, ?24, ?25, ?26, ?27, ?28, ?29, ?30)",
+        )?;
+        for m in state.metrics.iter() {
+            let ineq = &m.inequality;
+            metrics.execute(params![
+                m.round as i64,
+                m.encounters as i64,
+                m.trades as i64,
+                m.delta_u,
+                m.time,
+                m.population as i64,
+                m.entered as i64,
+                m.exited as i64,
+                m.consumed,
+                m.decayed,
+                m.replenished,
+                m.costs,
+                m.credit_drawn,
+                m.credit_repaid,
+                m.money_velocity,
+                m.cycles as i64,
+                m.money_good.map(|g| g.index() as i64),
+                m.tax_revenue,
+                m.redistributed,
+                m.active_traders as i64,
+                m.market_sales as i64,
+                m.broker_trades as i64,
+                m.pareto_gap,
+                ineq.mean_wealth,
+                ineq.gini_wealth,
+                ineq.theil_wealth,
+                ineq.gini_utility,
+                serde_json::to_string(&m.prices)?,
+                serde_json::to_string(&m.holders)?,
+                serde_json::to_string(&m.embargoed)?,
+            ])?;
+        }
+
+        let mut agents = tx.prepare("INSERT INTO agents VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)")?;
+        let mut holdings = tx.prepare("INSERT INTO agent_goods VALUES (?1, ?2, ?3, ?4)")?;
+        for (k, a) in state.agents.iter().enumerate() {
+            agents.execute(params![
+                k as i64,
+                a.id as i64,
+                cd_utility(&a.beta, &a.e, cfg.min_qty),
+                a.debt,
+                a.reputation,
+                a.broker,
+                serde_json::to_string(&a.labels)?,
+            ])?;
+            for (g, (&quantity, &beta)) in a.e.iter().zip(a.beta.iter()).enumerate() {
+                holdings.execute(params![a.id as i64, g as i64, quantity, beta])?;
+            }
+        }
+    }
+    tx.commit()?;
+    Ok(())
+}
@@ -0,0 +1,263 @@
+//! `rdx-cli sweep`: run one config over many parameter points, writing each point's traces to
+//! its own directory and the final metrics of all points to `sweep_summary.csv`.
+//!
+//! Points come from inline `--param path=v1,v2,...` grids or from a sweep spec file
+//! (`--spec`) listing parameters with explicit values or `[low, high]` ranges, sampled as a
+//! grid, uniformly at random, or by Latin hypercube.
+use anyhow::Context;
+use clap::Args;
+use rand::prelude::*;
+use serde::Deserialize;
+use serde_json::Value;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use crate::analyze::summarize;
+use crate::config::{ensure_valid, ConfigArgs};
+use crate::run::simulate;
+
+#[derive(Args, Debug)]
+pub struct SweepArgs {
+    #[command(flatten)]
+    pub config: ConfigArgs,
+
+    /// Swept parameter and its values, e.g. `--param seed=1,2,3` (repeatable; the grid is the
+    /// cross product). Paths and values follow `--set`
+    #[arg(long = "param", value_name = "PATH=V1,V2,...", required_unless_present = "spec")]
+    pub params: Vec<String>,
+
+    /// Sweep spec file (JSON): parameters with values or ranges and the sampling scheme
+    #[arg(long, conflicts_with = "params")]
+    pub spec: Option<String>,
+
+    /// Points simulated concurrently
+    #[arg(long, default_value_t = 1)]
+    pub jobs: usize,
+
+    /// Output directory; point `k` is written to `<out-dir>/point_<k>`
+    #[arg(long, default_value="out/sweep")]
+    pub out_dir: String,
+}
+
+/// A sweep spec file.
+#[derive(Debug, Deserialize)]
+pub struct SweepSpec {
+    pub params: Vec<ParamSpec>,
+    #[serde(default)]
+    pub sampling: Sampling,
+    /// Seed of the random and Latin-hypercube draws.
+    #[serde(default)]
+    pub seed: u64,
+}
+
+/// One swept parameter: a `--set` path with either explicit `values` or a `[low, high]` range.
+#[derive(Debug, Deserialize)]
+pub struct ParamSpec {
+    pub path: String,
+    #[serde(default)]
+    pub values: Vec<Value>,
+    #[serde(default)]
+    pub range: Option<[f64; 2]>,
+    /// Grid points over `range`, ends included.
+    #[serde(default)]
+    pub steps: Option<usize>,
+    /// Round range values to integers.
+    #[serde(default)]
+    pub integer: bool,
+}
+
+/// How points are drawn from the parameters.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Sampling {
+    /// Cross product of every parameter's values (or `steps` points over its range).
+    #[default]
+    Grid,
+    /// `samples` independent uniform draws per parameter.
+    Random { samples: usize },
+    /// `samples` points with every parameter's range (or value list) cut into `samples` strata,
+    /// each stratum used exactly once.
+    LatinHypercube { samples: usize },
+}
+
+/// A swept parameter: its `--set` path and the values it takes.
+pub struct Axis {
+    pub path: String,
+    pub values: Vec<String>,
+}
+
+/// Parse `path=v1,v2,...`.
+pub fn parse_axis(spec: &str) -> anyhow::Result<Axis> {
+    let Some((path, values)) = spec.split_once('=') else {
+        anyhow::bail!("expected --param path=v1,v2,..., got `{spec}`");
+    };
+    let values: Vec<String> = values.split(',')
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+        .collect();
+    if values.is_empty() {
+        anyhow::bail!("--param {path} lists no values");
+    }
+    Ok(Axis { path: path.trim().to_string(), values })
+}
+
+/// Every combination of one value per axis, the last axis varying fastest.
+pub fn grid(axes: &[Axis]) -> Vec<Vec<String>> {
+    let mut points = vec![Vec::new()];
+    for axis in axes {
+        points = points.into_iter()
+            .flat_map(|p| axis.values.iter().map(move |v| {
+                let mut q = p.clone();
+                q.push(v.clone());
+                q
+            }))
+            .collect();
+    }
+    points
+}
+
+impl ParamSpec {
+    fn check(&self) -> anyhow::Result<()> {
+        match (self.values.is_empty(), self.range) {
+            (false, None) => Ok(()),
+            (true, Some([low, high])) if low.is_finite() && high.is_finite() && low <= high => Ok(()),
+            _ => anyhow::bail!("parameter {} needs either values or a finite [low, high] range", self.path),
+        }
+    }
+
+    /// The value at `u` in [0, 1): a list entry, or a point of the range.
+    fn at(&self, u: f64) -> String {
+        match self.range {
+            Some([low, high]) if self.values.is_empty() => self.number(low + u * (high - low)),
+            _ => {
+                let k = ((u * self.values.len() as f64) as usize).min(self.values.len() - 1);
+                self.values[k].to_string()
+            }
+        }
+    }
+
+    fn number(&self, x: f64) -> String {
+        if self.integer { format!("{}", x.round() as i64) } else { format!("{x}") }
+    }
+
+    fn grid_axis(&self) -> anyhow::Result<Axis> {
+        let values = match (self.range, self.steps) {
+            _ if !self.values.is_empty() => self.values.iter().map(Value::to_string).collect(),
+            (Some([low, high]), Some(steps)) if steps >= 1 => (0..steps)
+                .map(|k| {
+                    let t = if steps == 1 { 0.0 } else { k as f64 / (steps - 1) as f64 };
+                    self.number(low + t * (high - low))
+                })
+                .collect(),
+            _ => anyhow::bail!("grid parameter {} needs values or a range with steps >= 1", self.path),
+        };
+        Ok(Axis { path: self.path.clone(), values })
+    }
+}
+
+impl SweepSpec {
+    /// The swept paths and, per point, their values.
+    pub fn points(&self) -> anyhow::Result<(Vec<String>, Vec<Vec<String>>)> {
+        for p in self.params.iter() {
+            p.check()?;
+        }
+        let paths = self.params.iter().map(|p| p.path.clone()).collect();
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        let points = match self.sampling {
+            Sampling::Grid => {
+                let axes = self.params.iter().map(ParamSpec::grid_axis).collect::<anyhow::Result<Vec<_>>>()?;
+                grid(&axes)
+            }
+            Sampling::Random { samples } => (0..samples)
+                .map(|_| self.params.iter().map(|p| p.at(rng.gen::<f64>())).collect())
+                .collect(),
+            Sampling::LatinHypercube { samples } => {
+                let strata: Vec<Vec<usize>> = self.params.iter()
+                    .map(|_| {
+                        let mut s: Vec<usize> = (0..samples).collect();
+                        s.shuffle(&mut rng);
+                        s
+                    })
+                    .collect();
+                (0..samples)
+                    .map(|k| {
+                        self.params.iter().zip(strata.iter())
+                            .map(|(p, s)| p.at((s[k] as f64 + rng.gen::<f64>()) / samples as f64))
+                            .collect()
+                    })
+                    .collect()
+            }
+        };
+        Ok((paths, points))
+    }
+}
+
+/// `rdx-cli sweep`.
+pub fn execute(args: &SweepArgs) -> anyhow::Result<()> {
+    let (paths, points) = match &args.spec {
+        Some(path) => {
+            let text = std::fs::read_to_string(path).with_context(|| format!("failed reading sweep spec: {path}"))?;
+            let spec: SweepSpec = serde_json::from_str(&text).with_context(|| format!("invalid sweep spec: {path}"))?;
+            spec.points()?
+        }
+        None => {
+            let axes = args.params.iter().map(|p| parse_axis(p)).collect::<anyhow::Result<Vec<_>>>()?;
+            (axes.iter().map(|a| a.path.clone()).collect(), grid(&axes))
+        }
+    };
+
+    // check every point before running any
+    let mut configs = Vec::with_capacity(points.len());
+    for values in points.iter() {
+        let sets: Vec<String> = paths.iter().zip(values).map(|(p, v)| format!("{p}={v}")).collect();
+        let cfg = args.config.load_with(&sets)?;
+        ensure_valid(&cfg).map_err(|e| anyhow::anyhow!("point {}: {e}", sets.join(" ")))?;
+        configs.push(cfg);
+    }
+
+    std::fs::create_dir_all(&args.out_dir)?;
+    let dirs: Vec<String> = (0..configs.len()).map(|k| format!("{}/point_{k}", args.out_dir)).collect();
+    let next = AtomicUsize::new(0);
+    let failures: Vec<String> = std::thread::scope(|scope| {
+        let workers: Vec<_> = (0..args.jobs.max(1))
+            .map(|_| scope.spawn(|| {
+                let mut failed = Vec::new();
+                loop {
+                    let k = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(cfg) = configs.get(k) else { break };
+                    match simulate(cfg, &dirs[k]) {
+                        Ok(_) => println!("point {k}: {}", dirs[k]),
+                        Err(e) => failed.push(format!("point {k}: {e}")),
+                    }
+                }
+                failed
+            }))
+            .collect();
+        workers.into_iter().flat_map(|w| w.join().expect("sweep worker panicked")).collect()
+    });
+    if !failures.is_empty() {
+        anyhow::bail!("{} points failed:\n{}", failures.len(), failures.join("\n"));
+    }
+
+    // final metrics of every point, in point order
+    let summary_path = format!("{}/sweep_summary.csv", args.out_dir);
+    let mut wtr = csv::Writer::from_path(&summary_path)?;
+    let mut columns: Vec<String> = Vec::new();
+    for (k, (dir, values)) in dirs.iter().zip(points.iter()).enumerate() {
+        let summary = summarize(std::path::Path::new(dir))?;
+        if k == 0 {
+            columns = summary.last.keys().filter(|c| c.as_str() != "round").cloned().collect();
+            let mut header = vec!["point".to_string(), "dir".to_string()];
+            header.extend(paths.iter().cloned());
+            header.extend(["rounds", "total_trades", "total_delta_u"].map(String::from));
+            header.extend(columns.iter().map(|c| format!("final_{c}")));
+            wtr.write_record(&header)?;
+        }
+        let mut row = vec![k.to_string(), dir.clone()];
+        row.extend(values.iter().cloned());
+        row.extend([summary.rounds.to_string(), summary.trades.to_string(), format!("{:.10}", summary.delta_u)]);
+        row.extend(columns.iter().map(|c| summary.last.get(c).map_or(String::new(), |x| format!("{:.10}", x))));
+        wtr.write_record(&row)?;
+    }
+    wtr.flush()?;
+    println!("Done. {} points, summary in {}", points.len(), summary_path);
+    Ok(())
+}
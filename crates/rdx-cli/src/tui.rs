@@ -0,0 +1,157 @@
+//! `rdx-cli run --tui`: a live terminal dashboard of a run (round progress, trade rate, total
+//! utility, Gini coefficients and sparklines of the first goods' mean endowments), so long runs
+//! give feedback before their traces are written.
+//!
+//! `Dashboard` is an `Observer` drawing on the alternate screen at most every `FRAME`; the
+//! terminal is restored when the engine drops it at the end of the run.
+use std::io::Stdout;
+use std::time::{Duration, Instant};
+use ratatui::backend::CrosstermBackend;
+use ratatui::crossterm::execute;
+use ratatui::crossterm::terminal::{EnterAlternateScreen, LeaveAlternateScreen};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Gauge, Paragraph, Sparkline};
+use ratatui::{Frame, Terminal};
+use rdx_core::model::TradeEvent;
+use rdx_core::preferences::cd_utility;
+use rdx_core::sim::{mean_endowments, Observer, RoundMetrics, SimState};
+
+/// Shortest time between two redraws.
+const FRAME: Duration = Duration::from_millis(100);
+/// Goods with a sparkline, from good 0.
+const GOODS_SHOWN: usize = 6;
+/// Rounds of mean endowments kept per good.
+const HISTORY: usize = 512;
+
+pub struct Dashboard {
+    terminal: Option<Terminal<CrosstermBackend<Stdout>>>,
+    goods: Vec<String>,
+    rounds: usize,
+    min_qty: f64,
+    started: Instant,
+    drawn: Option<Instant>,
+    round: usize,
+    population: usize,
+    trades: usize,
+    total_utility: f64,
+    gini_utility: f64,
+    gini_wealth: f64,
+    /// Per good, mean holding after each round in millionths (sparklines take integers).
+    means: Vec<Vec<u64>>,
+}
+
+impl Dashboard {
+    /// Take over the terminal for a run of `rounds` rounds over `goods`.
+    pub fn new(goods: Vec<String>, rounds: usize, min_qty: f64) -> std::io::Result<Self> {
+        let mut stdout = std::io::stdout();
+        execute!(stdout, EnterAlternateScreen)?;
+        let mut terminal = Terminal::new(CrosstermBackend::new(stdout))?;
+        terminal.clear()?;
+        terminal.hide_cursor()?;
+        Ok(Dashboard {
+            terminal: Some(terminal),
+            goods,
+            rounds,
+            min_qty,
+            started: Instant::now(),
+            drawn: None,
+            round: 0,
+            population: 0,
+            trades: 0,
+            total_utility: 0.0,
+            gini_utility: 0.0,
+            gini_wealth: 0.0,
+            means: Vec::new(),
+        })
+    }
+
+    fn render(&self, frame: &mut Frame) {
+        let [stats, progress, sparks] =
+            Layout::vertical([Constraint::Length(6), Constraint::Length(3), Constraint::Min(3)]).areas(frame.area());
+
+        let elapsed = self.started.elapsed().as_secs_f64();
+        let rate = if elapsed > 0.0 { self.trades as f64 / elapsed } else { 0.0 };
+        let lines = vec![
+            Line::from(format!("round {} of {}   population {}", self.round, self.rounds, self.population)),
+            Line::from(format!("trades {}   {:.1} trades/s   {:.1}s elapsed", self.trades, rate, elapsed)),
+            Line::from(format!("total utility {:.6}", self.total_utility)),
+            Line::from(format!("Gini utility {:.4}   Gini wealth {:.4}", self.gini_utility, self.gini_wealth)),
+        ];
+        frame.render_widget(Paragraph::new(lines).block(Block::bordered().title(" rdx run ")), stats);
+
+        let ratio = if self.rounds > 0 { (self.round as f64 / self.rounds as f64).min(1.0) } else { 1.0 };
+        let gauge = Gauge::default()
+            .block(Block::bordered().title(" progress "))
+            .gauge_style(Style::default().fg(Color::Green))
+            .ratio(ratio)
+            .label(format!("{:.0}%", 100.0 * ratio));
+        frame.render_widget(gauge, progress);
+
+        let shown = self.means.len().min(GOODS_SHOWN);
+        if shown == 0 {
+            return;
+        }
+        let rows = Layout::vertical(vec![Constraint::Ratio(1, shown as u32); shown]).split(sparks);
+        for (g, (history, area)) in self.means.iter().zip(rows.iter()).enumerate() {
+            let width = area.width.saturating_sub(2) as usize;
+            let recent = &history[history.len().saturating_sub(width)..];
+            let last = recent.last().map_or(0.0, |&m| m as f64 / 1e6);
+            let name = self.goods.get(g).map_or("?", String::as_str);
+            let title = format!(" mean {} {:.4} ", name, last);
+            let spark = Sparkline::default()
+                .block(Block::bordered().title(title))
+                .style(Style::default().fg(Color::Cyan))
+                .data(recent);
+            frame.render_widget(spark, *area);
+        }
+    }
+
+    fn draw(&mut self) {
+        let Some(mut terminal) = self.terminal.take() else { return };
+        // a terminal that fails to draw is released for the rest of the run
+        if terminal.draw(|frame| self.render(frame)).is_ok() {
+            self.terminal = Some(terminal);
+        } else {
+            let _ = execute!(terminal.backend_mut(), LeaveAlternateScreen);
+        }
+        self.drawn = Some(Instant::now());
+    }
+}
+
+impl Observer for Dashboard {
+    fn on_trade(&mut self, _event: &TradeEvent) {
+        self.trades += 1;
+    }
+
+    fn on_round_end(&mut self, metrics: &RoundMetrics, state: &SimState) {
+        self.round = metrics.round + 1;
+        self.population = metrics.population;
+        self.gini_utility = metrics.inequality.gini_utility;
+        self.gini_wealth = metrics.inequality.gini_wealth;
+        self.total_utility = state.agents.iter().map(|a| cd_utility(&a.beta, &a.e, self.min_qty)).sum();
+        let mean = mean_endowments(state);
+        if self.means.len() < mean.len() {
+            self.means.resize(mean.len(), Vec::new());
+        }
+        for (history, m) in self.means.iter_mut().zip(mean) {
+            history.push((m.max(0.0) * 1e6).round() as u64);
+            if history.len() > HISTORY {
+                history.remove(0);
+            }
+        }
+        if self.round >= self.rounds || self.drawn.is_none_or(|t| t.elapsed() >= FRAME) {
+            self.draw();
+        }
+    }
+}
+
+impl Drop for Dashboard {
+    fn drop(&mut self) {
+        if let Some(mut terminal) = self.terminal.take() {
+            let _ = terminal.show_cursor();
+            let _ = execute!(terminal.backend_mut(), LeaveAlternateScreen);
+        }
+    }
+}
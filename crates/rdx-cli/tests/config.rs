@@ -0,0 +1,48 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use rdx_cli::config::load_config_json;
+use serde_json::json;
+
+/// A fresh directory under the system temp dir for one test's files.
+fn scratch(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("rdx-cli-{name}-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn write(path: &Path, doc: serde_json::Value) {
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, doc.to_string()).unwrap();
+}
+
+#[test]
+fn extends_chains_merge_each_file_over_its_parent() {
+    let dir = scratch("extends");
+    write(&dir.join("base.json"), json!({ "seed": 1, "rounds": 5, "matching": { "kind": "uniform" } }));
+    // parents are found relative to the extending file
+    write(&dir.join("studies/mid.json"), json!({ "extends": "../base.json", "rounds": 7 }));
+    write(&dir.join("studies/leaf.json"), json!({ "extends": "mid.json", "num_agents": 30 }));
+
+    let doc = load_config_json(&dir.join("studies/leaf.json"), &mut Vec::new()).unwrap();
+    assert_eq!(doc, json!({ "seed": 1, "rounds": 7, "num_agents": 30, "matching": { "kind": "uniform" } }));
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn extends_cycles_are_refused() {
+    let dir = scratch("cycle");
+    write(&dir.join("a.json"), json!({ "extends": "b.json", "seed": 1 }));
+    write(&dir.join("b.json"), json!({ "extends": "a.json", "seed": 2 }));
+    write(&dir.join("self.json"), json!({ "extends": "./self.json" }));
+
+    for file in ["a.json", "self.json"] {
+        let e = load_config_json(&dir.join(file), &mut Vec::new()).unwrap_err().to_string();
+        assert!(e.contains("extends itself"), "{file}: {e}");
+    }
+    let e = load_config_json(&dir.join("missing.json"), &mut Vec::new()).unwrap_err().to_string();
+    assert!(e.contains("failed reading config"), "{e}");
+    write(&dir.join("bad.json"), json!({ "extends": 3 }));
+    assert!(load_config_json(&dir.join("bad.json"), &mut Vec::new()).is_err());
+    fs::remove_dir_all(&dir).unwrap();
+}
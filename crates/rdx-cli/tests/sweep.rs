@@ -0,0 +1,61 @@
+use rdx_cli::sweep::SweepSpec;
+use serde_json::json;
+
+fn spec(v: serde_json::Value) -> SweepSpec {
+    serde_json::from_value(v).unwrap()
+}
+
+#[test]
+fn a_single_step_grid_takes_the_low_end() {
+    let s = spec(json!({
+        "params": [
+            { "path": "trade_step_cap_frac", "range": [0.2, 0.6], "steps": 1 },
+            { "path": "seed", "values": [1, 2, 3] }
+        ]
+    }));
+    let (paths, points) = s.points().unwrap();
+    assert_eq!(paths, ["trade_step_cap_frac", "seed"]);
+    assert_eq!(points, [["0.2", "1"], ["0.2", "2"], ["0.2", "3"]]);
+
+    let s = spec(json!({ "params": [{ "path": "rounds", "range": [10, 20], "steps": 3, "integer": true }] }));
+    assert_eq!(s.points().unwrap().1, [["10"], ["15"], ["20"]]);
+}
+
+#[test]
+fn latin_hypercube_draws_use_every_stratum_once() {
+    let samples = 8;
+    let s = spec(json!({
+        "params": [
+            { "path": "alpha_low", "range": [0.0, 1.0] },
+            { "path": "trade_step_cap_frac", "range": [2.0, 6.0] }
+        ],
+        "sampling": { "latin_hypercube": { "samples": samples } },
+        "seed": 11
+    }));
+    let (_, points) = s.points().unwrap();
+    assert_eq!(points.len(), samples);
+    for (axis, (low, high)) in [(0.0, 1.0), (2.0, 6.0)].into_iter().enumerate() {
+        let mut strata: Vec<usize> = points.iter()
+            .map(|p| {
+                let x: f64 = p[axis].parse().unwrap();
+                assert!((low..high).contains(&x), "{x}");
+                ((x - low) / (high - low) * samples as f64) as usize
+            })
+            .collect();
+        strata.sort();
+        assert_eq!(strata, (0..samples).collect::<Vec<_>>(), "axis {axis}");
+    }
+    // the same seed draws the same points
+    assert_eq!(s.points().unwrap().1, points);
+}
+
+#[test]
+fn a_parameter_needs_values_or_a_range_but_not_both() {
+    let s = spec(json!({ "params": [{ "path": "seed", "values": [1, 2], "range": [1.0, 2.0], "steps": 2 }] }));
+    let e = s.points().unwrap_err().to_string();
+    assert!(e.contains("seed"), "{e}");
+    let s = spec(json!({ "params": [{ "path": "seed" }] }));
+    assert!(s.points().is_err());
+    let s = spec(json!({ "params": [{ "path": "seed", "range": [2.0, 1.0], "steps": 2 }] }));
+    assert!(s.points().is_err());
+}
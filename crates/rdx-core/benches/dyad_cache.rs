@@ -0,0 +1,57 @@
+//! Compares evaluating every ordered pair in a pruned candidate set with and without a shared
+//! per-encounter `DyadCache`.
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use rand::prelude::*;
+use rdx_core::model::Agent;
+use rdx_core::preferences::beta_from_alpha_to_base;
+use rdx_core::trade::{
+    candidate_goods_pruned, default_oracle, evaluate_pairwise_trade, evaluate_pairwise_trade_cached, DyadCache,
+};
+
+fn random_agent(rng: &mut StdRng, n: usize) -> Agent {
+    let e: Vec<f64> = (0..n).map(|_| rng.gen_range(0.5..2.0)).collect();
+    let mut alpha_to_base = vec![0.5; n];
+    for a in alpha_to_base.iter_mut().skip(1) {
+        *a = rng.gen_range(0.1..0.9);
+    }
+    let beta = beta_from_alpha_to_base(&alpha_to_base, 0, 1e-6).unwrap();
+    Agent { e, beta, alpha_to_base, reaction_rules: Vec::new(), ..Default::default() }
+}
+
+fn bench_dyad_cache(c: &mut Criterion) {
+    let n = 256;
+    let mut rng = StdRng::seed_from_u64(1);
+    let i = random_agent(&mut rng, n);
+    let j = random_agent(&mut rng, n);
+    let oracle = default_oracle();
+
+    let mut group = c.benchmark_group("all_pairs_search");
+    for k in [8usize, 16, 32] {
+        let mut goods = candidate_goods_pruned(&i, &j, 0, k, 1e-9);
+        goods.push(0);
+
+        group.bench_with_input(BenchmarkId::new("uncached", k), &goods, |bch, goods| {
+            bch.iter(|| {
+                for &a in goods.iter() {
+                    for &b in goods.iter() {
+                        black_box(evaluate_pairwise_trade(&i, &j, a, b, 0, 1e-9, 60, &oracle));
+                    }
+                }
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("cached", k), &goods, |bch, goods| {
+            bch.iter(|| {
+                let mut cache = DyadCache::new();
+                for &a in goods.iter() {
+                    for &b in goods.iter() {
+                        black_box(evaluate_pairwise_trade_cached(&i, &j, a, b, 0, 1e-9, 60, &oracle, &mut cache));
+                    }
+                }
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_dyad_cache);
+criterion_main!(benches);
@@ -0,0 +1,117 @@
+//! The trade inner loop, piece by piece and as a whole: `cd_utility`, `evaluate_pairwise_trade`,
+//! `candidate_goods_pruned` and the Walras oracle at growing bundle sizes, and one full round of
+//! a 10k-agent economy.
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use rand::prelude::*;
+use rdx_core::model::{Agent, PairingMode, SimConfig};
+use rdx_core::pareto_oracle::{CobbDouglasWalrasOracle, ParetoOracle};
+use rdx_core::preferences::{beta_from_alpha_to_base, cd_utility};
+use rdx_core::sim::Engine;
+use rdx_core::trade::{candidate_goods_pruned, default_oracle, evaluate_pairwise_trade};
+
+const GOODS: [usize; 3] = [16, 64, 256];
+
+fn random_agent(rng: &mut StdRng, n: usize) -> Agent {
+    let e: Vec<f64> = (0..n).map(|_| rng.gen_range(0.5..2.0)).collect();
+    let mut alpha_to_base = vec![0.5; n];
+    for a in alpha_to_base.iter_mut().skip(1) {
+        *a = rng.gen_range(0.1..0.9);
+    }
+    let beta = beta_from_alpha_to_base(&alpha_to_base, 0, 1e-6).unwrap();
+    Agent { e, beta, alpha_to_base, reaction_rules: Vec::new(), ..Default::default() }
+}
+
+fn dyad(n: usize) -> (Agent, Agent) {
+    let mut rng = StdRng::seed_from_u64(n as u64);
+    (random_agent(&mut rng, n), random_agent(&mut rng, n))
+}
+
+fn bench_cd_utility(c: &mut Criterion) {
+    let mut group = c.benchmark_group("cd_utility");
+    for n in GOODS {
+        let (i, _) = dyad(n);
+        group.bench_with_input(BenchmarkId::from_parameter(n), &i, |bch, i| {
+            bch.iter(|| cd_utility(black_box(&i.beta), black_box(&i.e), 1e-9))
+        });
+    }
+    group.finish();
+}
+
+fn bench_evaluate_pairwise_trade(c: &mut Criterion) {
+    let oracle = default_oracle();
+    let mut group = c.benchmark_group("evaluate_pairwise_trade");
+    for n in GOODS {
+        let (i, j) = dyad(n);
+        group.bench_with_input(BenchmarkId::from_parameter(n), &(i, j), |bch, (i, j)| {
+            bch.iter(|| evaluate_pairwise_trade(black_box(i), black_box(j), 1, 0, 0, 1e-9, 60, &oracle))
+        });
+    }
+    group.finish();
+}
+
+fn bench_candidate_goods_pruned(c: &mut Criterion) {
+    let mut group = c.benchmark_group("candidate_goods_pruned");
+    for n in GOODS {
+        let (i, j) = dyad(n);
+        group.bench_with_input(BenchmarkId::from_parameter(n), &(i, j), |bch, (i, j)| {
+            bch.iter(|| candidate_goods_pruned(black_box(i), black_box(j), 0, 12, 1e-9))
+        });
+    }
+    group.finish();
+}
+
+fn bench_oracle(c: &mut Criterion) {
+    let oracle = CobbDouglasWalrasOracle;
+    let mut group = c.benchmark_group("walras_oracle");
+    for iters in [30usize, 60, 120] {
+        group.bench_with_input(BenchmarkId::from_parameter(iters), &iters, |bch, &iters| {
+            bch.iter(|| {
+                oracle.solve_two_good_exchange(
+                    black_box(0.7), black_box(2.0), black_box(1.0),
+                    black_box(0.2), black_box(1.5), black_box(3.0),
+                    1e-9, iters,
+                )
+            })
+        });
+    }
+    group.finish();
+}
+
+fn bench_full_round(c: &mut Criterion) {
+    let mut goods = vec!["Credits".to_string()];
+    goods.extend((1..64).map(|k| format!("Service {k}")));
+    let mut cfg = SimConfig::builder()
+        .seed(3)
+        .agents(10_000)
+        .rounds(1)
+        .encounters_per_round(30_000)
+        .goods(goods)
+        .build()
+        .expect("valid bench config");
+    cfg.pairing_mode = PairingMode::AllPairsPruned;
+    cfg.candidate_goods_k = 12;
+
+    let mut group = c.benchmark_group("round");
+    group.sample_size(10);
+    group.bench_function("10k_agents_64_goods", |bch| {
+        bch.iter_batched(
+            || Engine::new(cfg.clone()).expect("engine"),
+            |mut engine| {
+                engine.step_round();
+                engine
+            },
+            BatchSize::LargeInput,
+        )
+    });
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_cd_utility,
+    bench_evaluate_pairwise_trade,
+    bench_candidate_goods_pruned,
+    bench_oracle,
+    bench_full_round,
+);
+criterion_main!(benches);
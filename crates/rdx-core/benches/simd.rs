@@ -0,0 +1,70 @@
+//! The log-sum and log-MRS kernels on 256+ good economies. Run once as is and once with
+//! `--features simd` to compare: `scalar_log_sum` is the sequential loop of the default build
+//! either way, `cd_utility`, `candidate_goods_pruned` and `AgentSoA::log_utilities` use the
+//! build's kernels.
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use rand::prelude::*;
+use rdx_core::math::ln;
+use rdx_core::model::Agent;
+use rdx_core::preferences::{beta_from_alpha_to_base, cd_utility};
+use rdx_core::sim::AgentSoA;
+use rdx_core::trade::candidate_goods_pruned;
+
+const GOODS: [usize; 3] = [256, 1024, 4096];
+
+fn random_agent(rng: &mut StdRng, n: usize) -> Agent {
+    let e: Vec<f64> = (0..n).map(|_| rng.gen_range(0.5..2.0)).collect();
+    let mut alpha_to_base = vec![0.5; n];
+    for a in alpha_to_base.iter_mut().skip(1) {
+        *a = rng.gen_range(0.1..0.9);
+    }
+    let beta = beta_from_alpha_to_base(&alpha_to_base, 0, 1e-6).unwrap();
+    Agent { e, beta, alpha_to_base, reaction_rules: Vec::new(), ..Default::default() }
+}
+
+fn scalar_log_sum(beta: &[f64], x: &[f64], min_qty: f64) -> f64 {
+    let mut s = 0.0;
+    for (&b, &xi) in beta.iter().zip(x.iter()) {
+        s += b * ln(xi.max(min_qty));
+    }
+    s
+}
+
+fn bench_log_sum(c: &mut Criterion) {
+    let mut group = c.benchmark_group("log_sum");
+    for n in GOODS {
+        let a = random_agent(&mut StdRng::seed_from_u64(n as u64), n);
+        group.bench_with_input(BenchmarkId::new("scalar_log_sum", n), &a, |bch, a| {
+            bch.iter(|| scalar_log_sum(black_box(&a.beta), black_box(&a.e), 1e-9))
+        });
+        group.bench_with_input(BenchmarkId::new("cd_utility", n), &a, |bch, a| {
+            bch.iter(|| cd_utility(black_box(&a.beta), black_box(&a.e), 1e-9))
+        });
+    }
+    group.finish();
+}
+
+fn bench_pruning(c: &mut Criterion) {
+    let mut group = c.benchmark_group("candidate_goods_pruned");
+    for n in GOODS {
+        let mut rng = StdRng::seed_from_u64(n as u64);
+        let dyad = (random_agent(&mut rng, n), random_agent(&mut rng, n));
+        group.bench_with_input(BenchmarkId::from_parameter(n), &dyad, |bch, (i, j)| {
+            bch.iter(|| candidate_goods_pruned(black_box(i), black_box(j), 0, 12, 1e-9))
+        });
+    }
+    group.finish();
+}
+
+fn bench_population(c: &mut Criterion) {
+    let mut rng = StdRng::seed_from_u64(11);
+    let agents: Vec<Agent> = (0..10_000).map(|_| random_agent(&mut rng, 256)).collect();
+    let soa = AgentSoA::from_agents(&agents).unwrap();
+    let mut group = c.benchmark_group("population_log_utilities");
+    group.sample_size(20);
+    group.bench_function("10k_agents_256_goods", |bch| bch.iter(|| black_box(&soa).log_utilities(1e-9)));
+    group.finish();
+}
+
+criterion_group!(benches, bench_log_sum, bench_pruning, bench_population);
+criterion_main!(benches);
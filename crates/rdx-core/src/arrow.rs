@@ -0,0 +1,149 @@
+//! Arrow record batches of a run's event log and metrics, for analytics tools (Polars, DuckDB,
+//! pandas via pyarrow) and for Parquet output without a text round trip.
+//!
+//! `SimState::events_batch` has one row per `TradeEvent` with the same field names; state hashes
+//! are 32-byte fixed-size binary columns and `time` is null without a clock.
+//! `SimState::metrics_batch` has one row per `RoundMetrics`: counters and amounts as scalar
+//! columns, per-good vectors (`prices`, `marketability`, `holders`, `embargoed`) as lists and
+//! `inequality` flattened into `mean_wealth`, `gini_wealth` ... `utility_quantiles`. Indices and
+//! counts are `UInt64`, amounts `Float64`. `trajectories_batch` has one row per
+//! `TrajectoryPoint`, holdings as a list.
+
+use std::sync::Arc;
+use arrow_array::types::{Float64Type, UInt64Type};
+use arrow_array::{ArrayRef, BooleanArray, FixedSizeBinaryArray, Float64Array, ListArray, RecordBatch, UInt64Array};
+use crate::error::RdxError;
+use crate::sim::{RoundMetrics, SimState};
+use crate::state::Hash;
+use crate::trajectory::TrajectoryPoint;
+
+fn u64s(values: impl Iterator<Item = u64>) -> ArrayRef {
+    Arc::new(UInt64Array::from_iter_values(values))
+}
+
+fn f64s(values: impl Iterator<Item = f64>) -> ArrayRef {
+    Arc::new(Float64Array::from_iter_values(values))
+}
+
+fn hashes<'a>(values: impl Iterator<Item = &'a Hash>) -> Result<ArrayRef, RdxError> {
+    let array = FixedSizeBinaryArray::try_from_sparse_iter_with_size(values.map(Some), 32)?;
+    Ok(Arc::new(array))
+}
+
+fn f64_lists<'a>(values: impl Iterator<Item = &'a [f64]>) -> ArrayRef {
+    let rows = values.map(|v| Some(v.iter().map(|&x| Some(x)).collect::<Vec<_>>()));
+    Arc::new(ListArray::from_iter_primitive::<Float64Type, _, _>(rows))
+}
+
+fn index_lists<'a>(values: impl Iterator<Item = &'a [usize]>) -> ArrayRef {
+    let rows = values.map(|v| Some(v.iter().map(|&x| Some(x as u64)).collect::<Vec<_>>()));
+    Arc::new(ListArray::from_iter_primitive::<UInt64Type, _, _>(rows))
+}
+
+impl SimState {
+    /// The event log as one Arrow batch (see the module docs for the columns).
+    pub fn events_batch(&self) -> Result<RecordBatch, RdxError> {
+        let ev = &self.events;
+        let columns: Vec<(&str, ArrayRef, bool)> = vec![
+            ("seq", u64s(ev.iter().map(|e| e.seq)), false),
+            ("round", u64s(ev.iter().map(|e| e.round as u64)), false),
+            ("i", u64s(ev.iter().map(|e| e.i.index() as u64)), false),
+            ("j", u64s(ev.iter().map(|e| e.j.index() as u64)), false),
+            ("id_i", u64s(ev.iter().map(|e| e.id_i)), false),
+            ("id_j", u64s(ev.iter().map(|e| e.id_j)), false),
+            ("good_a", u64s(ev.iter().map(|e| e.good_a.index() as u64)), false),
+            ("good_b", u64s(ev.iter().map(|e| e.good_b.index() as u64)), false),
+            ("q_ab", f64s(ev.iter().map(|e| e.q_ab)), false),
+            ("delta_a_i", f64s(ev.iter().map(|e| e.delta_a_i)), false),
+            ("delta_b_i", f64s(ev.iter().map(|e| e.delta_b_i)), false),
+            ("delta_u_i", f64s(ev.iter().map(|e| e.delta_u_i)), false),
+            ("delta_u_j", f64s(ev.iter().map(|e| e.delta_u_j)), false),
+            ("cost_i", f64s(ev.iter().map(|e| e.cost_i)), false),
+            ("cost_j", f64s(ev.iter().map(|e| e.cost_j)), false),
+            ("tax", f64s(ev.iter().map(|e| e.tax)), false),
+            ("time", Arc::new(ev.iter().map(|e| e.time).collect::<Float64Array>()), true),
+            ("speculative", Arc::new(ev.iter().map(|e| Some(e.speculative)).collect::<BooleanArray>()), false),
+            ("pre_i", hashes(ev.iter().map(|e| &e.pre_i))?, false),
+            ("pre_j", hashes(ev.iter().map(|e| &e.pre_j))?, false),
+            ("post_i", hashes(ev.iter().map(|e| &e.post_i))?, false),
+            ("post_j", hashes(ev.iter().map(|e| &e.post_j))?, false),
+        ];
+        Ok(RecordBatch::try_from_iter_with_nullable(columns)?)
+    }
+
+    /// Per-round metrics as one Arrow batch (see the module docs for the columns).
+    pub fn metrics_batch(&self) -> Result<RecordBatch, RdxError> {
+        let m = &self.metrics;
+        let count = |f: fn(&RoundMetrics) -> usize| u64s(m.iter().map(move |r| f(r) as u64));
+        let amount = |f: fn(&RoundMetrics) -> f64| f64s(m.iter().map(f));
+        let columns: Vec<(&str, ArrayRef, bool)> = vec![
+            ("round", count(|r| r.round), false),
+            ("encounters", count(|r| r.encounters), false),
+            ("trades", count(|r| r.trades), false),
+            ("delta_u", amount(|r| r.delta_u), false),
+            ("embargoed", index_lists(m.iter().map(|r| r.embargoed.as_slice())), false),
+            ("time", Arc::new(m.iter().map(|r| r.time).collect::<Float64Array>()), true),
+            ("population", count(|r| r.population), false),
+            ("entered", count(|r| r.entered), false),
+            ("exited", count(|r| r.exited), false),
+            ("shocked", count(|r| r.shocked), false),
+            ("preference_shocked", count(|r| r.preference_shocked), false),
+            ("consumed", amount(|r| r.consumed), false),
+            ("decayed", amount(|r| r.decayed), false),
+            ("replenished", amount(|r| r.replenished), false),
+            ("prices", f64_lists(m.iter().map(|r| r.prices.as_slice())), false),
+            ("costs", amount(|r| r.costs), false),
+            ("cost_refused", count(|r| r.cost_refused), false),
+            ("cap_refused", count(|r| r.cap_refused), false),
+            ("over_budget", count(|r| r.over_budget), false),
+            ("credit_drawn", amount(|r| r.credit_drawn), false),
+            ("credit_repaid", amount(|r| r.credit_repaid), false),
+            ("money_velocity", amount(|r| r.money_velocity), false),
+            ("credit_utilization", amount(|r| r.credit_utilization), false),
+            ("cycles", count(|r| r.cycles), false),
+            ("marketability", f64_lists(m.iter().map(|r| r.marketability.as_slice())), false),
+            (
+                "money_good",
+                Arc::new(m.iter().map(|r| r.money_good.map(|g| g.index() as u64)).collect::<UInt64Array>()),
+                true,
+            ),
+            ("speculative", count(|r| r.speculative), false),
+            ("tax_revenue", amount(|r| r.tax_revenue), false),
+            ("redistributed", amount(|r| r.redistributed), false),
+            ("mistakes", count(|r| r.mistakes), false),
+            ("active_traders", count(|r| r.active_traders), false),
+            ("reneged", count(|r| r.reneged), false),
+            ("mean_reputation", amount(|r| r.mean_reputation), false),
+            ("listings", count(|r| r.listings), false),
+            ("market_sales", count(|r| r.market_sales), false),
+            ("broker_trades", count(|r| r.broker_trades), false),
+            ("broker_wealth", amount(|r| r.broker_wealth), false),
+            ("imitations", count(|r| r.imitations), false),
+            ("pareto_gap", amount(|r| r.pareto_gap), false),
+            ("oracle_cache_hits", count(|r| r.oracle_cache_hits), false),
+            ("oracle_cache_misses", count(|r| r.oracle_cache_misses), false),
+            ("oracle_degenerate", count(|r| r.oracle_degenerate), false),
+            ("oracle_unbracketed", count(|r| r.oracle_unbracketed), false),
+            ("oracle_clamped", count(|r| r.oracle_clamped), false),
+            ("holders", index_lists(m.iter().map(|r| r.holders.as_slice())), false),
+            ("mean_wealth", amount(|r| r.inequality.mean_wealth), false),
+            ("wealth_quantiles", f64_lists(m.iter().map(|r| &r.inequality.wealth_quantiles[..])), false),
+            ("gini_wealth", amount(|r| r.inequality.gini_wealth), false),
+            ("theil_wealth", amount(|r| r.inequality.theil_wealth), false),
+            ("gini_utility", amount(|r| r.inequality.gini_utility), false),
+            ("utility_quantiles", f64_lists(m.iter().map(|r| &r.inequality.utility_quantiles[..])), false),
+        ];
+        Ok(RecordBatch::try_from_iter_with_nullable(columns)?)
+    }
+}
+
+/// Trajectory points as one Arrow batch: `round`, `id`, `utility` and the holdings `e`.
+pub fn trajectories_batch(points: &[TrajectoryPoint]) -> Result<RecordBatch, RdxError> {
+    let columns: Vec<(&str, ArrayRef, bool)> = vec![
+        ("round", u64s(points.iter().map(|p| p.round as u64)), false),
+        ("id", u64s(points.iter().map(|p| p.id)), false),
+        ("utility", f64s(points.iter().map(|p| p.utility)), false),
+        ("e", f64_lists(points.iter().map(|p| p.e.as_slice())), false),
+    ];
+    Ok(RecordBatch::try_from_iter_with_nullable(columns)?)
+}
@@ -0,0 +1,155 @@
+//! Intermediaries (`SimConfig::brokers`): designated agents that trade for resale, not for use.
+//!
+//! A broker does not look for mutual utility gains. It quotes every good against the base good at
+//! a bid below and an ask above the emergent price, buys at the bid from customers who value the
+//! good less, and sells out of its inventory at the ask to customers who value it more. The
+//! customer trades to its Cobb–Douglas optimum at the quoted price, bounded by what the broker
+//! holds, so customers always gain; the broker earns the spread. Brokers do not trade with each
+//! other.
+
+use serde::{Serialize, Deserialize};
+use crate::error::RdxError;
+use crate::ids::GoodId;
+use crate::metrics::wealth;
+use crate::model::{Agent, SimConfig};
+use crate::preferences::{cd_utility, demand_at_price};
+use crate::sim::{init_agents, run, SimState};
+use crate::trade::{directional_shape, TradeCandidate, TradeRules};
+
+/// Bid and ask for every good, in base-good units, refreshed by the engine every round.
+#[derive(Clone, Debug, Default)]
+pub struct Quotes {
+    /// Per good, the price the quotes are centred on (the emergent price).
+    pub mid: Vec<f64>,
+    /// Relative bid–ask spread.
+    pub spread: f64,
+}
+
+impl Quotes {
+    /// Price a broker pays for good `g`: `mid[g] · (1 - spread / 2)`.
+    pub fn bid(&self, g: usize) -> f64 {
+        self.mid.get(g).copied().unwrap_or(1.0) * (1.0 - 0.5 * self.spread)
+    }
+
+    /// Price a broker charges for good `g`: `mid[g] · (1 + spread / 2)`.
+    pub fn ask(&self, g: usize) -> f64 {
+        self.mid.get(g).copied().unwrap_or(1.0) * (1.0 + 0.5 * self.spread)
+    }
+}
+
+/// Best trade `customer` can make at `broker`'s quotes, as a candidate with the broker as `i`.
+///
+/// For each allowed good `g` the customer moves to its optimum over `(g, base)` at the bid or the
+/// ask, capped by the broker's inventory (when buying from it) or cash (when selling to it). The
+/// good with the largest utility gain for the customer wins. The broker's own utility plays no
+/// part in the decision, so both `shape_i` and `shape_j` describe the customer's side, which keeps
+/// the adaptive step cap in the customer's terms.
+pub fn broker_trade(
+    broker: &Agent,
+    customer: &Agent,
+    quotes: &Quotes,
+    base_good: usize,
+    min_qty: f64,
+    rules: &TradeRules,
+) -> Option<TradeCandidate> {
+    let n = customer.e.len();
+    if broker.e.len() != n || base_good >= n || !rules.allows(base_good) { return None; }
+    let u0 = cd_utility(&customer.beta, &customer.e, min_qty);
+    let mut best: Option<(f64, TradeCandidate)> = None;
+    for g in (0..n).filter(|&g| g != base_good && rules.allows(g)) {
+        // the customer buys at the ask if it values g above it, sells at the bid if below
+        let ask = quotes.ask(g);
+        let bid = quotes.bid(g);
+        let want_at = |p: f64| demand_at_price(&customer.beta, &customer.e, g, base_good, p, min_qty);
+        let dx = if want_at(ask) > 0.0 {
+            want_at(ask).min(broker.e[g] - min_qty).max(0.0)
+        } else if want_at(bid) < 0.0 {
+            want_at(bid).max(-(broker.e[base_good] - min_qty).max(0.0) / bid)
+        } else {
+            0.0
+        };
+        if dx == 0.0 { continue; }
+        let p = if dx > 0.0 { ask } else { bid };
+
+        let mut xc = customer.e.clone();
+        xc[g] += dx;
+        xc[base_good] -= p * dx;
+        let du = cd_utility(&customer.beta, &xc, min_qty) - u0;
+        if du <= 0.0 || best.as_ref().is_some_and(|(b, _)| *b >= du) { continue; }
+
+        let mut xb = broker.e.clone();
+        xb[g] -= dx;
+        xb[base_good] += p * dx;
+        let shape = directional_shape(&customer.beta, &customer.e, g, base_good, dx, -p * dx, min_qty);
+        best = Some((du, TradeCandidate {
+            good_a: GoodId(g),
+            good_b: GoodId(base_good),
+            q_ab: p,
+            delta_a_i: -dx,
+            delta_b_i: p * dx,
+            delta_u_i: cd_utility(&broker.beta, &xb, min_qty) - cd_utility(&broker.beta, &broker.e, min_qty),
+            delta_u_j: du,
+            shape_i: shape,
+            shape_j: shape,
+            speculative: false,
+        }));
+    }
+    best.map(|(_, cand)| cand)
+}
+
+/// Welfare of the non-broker population with and without intermediation (see
+/// `compare_intermediation`).
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct IntermediationReport {
+    /// Utility gained over the run by the agents not designated as brokers.
+    pub welfare_with: f64,
+    /// The same agents' gain when the would-be brokers trade as ordinary agents.
+    pub welfare_without: f64,
+    /// Trades with a broker on one side.
+    pub broker_trades: usize,
+    /// Change in the brokers' wealth at the final prices.
+    pub broker_profit: f64,
+}
+
+/// Run `cfg` (which must set `brokers`) and the same config without brokers, and compare how much
+/// utility the customers gain in each. Both runs start from identical agents, and only agents
+/// present at the start and the end of a run count.
+pub fn compare_intermediation(cfg: &SimConfig) -> Result<IntermediationReport, RdxError> {
+    if cfg.brokers.is_none() {
+        return Err(RdxError::InvalidConfig("compare_intermediation needs `brokers`".into()));
+    }
+    let mut with = init_agents(cfg)?;
+    let initial = with.agents.clone();
+    run(cfg, &mut with)?;
+
+    let mut plain = cfg.clone();
+    plain.brokers = None;
+    let mut without = init_agents(&plain)?;
+    run(&plain, &mut without)?;
+
+    let prices = with.metrics.last().map(|m| m.prices.clone()).unwrap_or_default();
+    let mut broker_profit = 0.0;
+    for a in with.agents.iter().filter(|a| a.broker) {
+        if let Some(a0) = initial.iter().find(|a0| a0.id == a.id) {
+            broker_profit += wealth(a, &prices) - wealth(a0, &prices);
+        }
+    }
+    let broker_trades = with.metrics.iter().map(|m| m.broker_trades).sum();
+    Ok(IntermediationReport {
+        welfare_with: customer_gain(&initial, &with, cfg.min_qty),
+        welfare_without: customer_gain(&initial, &without, cfg.min_qty),
+        broker_trades,
+        broker_profit,
+    })
+}
+
+/// Utility gained by the initial non-brokers still present in `end`.
+fn customer_gain(initial: &[Agent], end: &SimState, min_qty: f64) -> f64 {
+    initial.iter()
+        .filter(|a| !a.broker)
+        .filter_map(|a0| {
+            let a = end.agents.iter().find(|a| a.id == a0.id)?;
+            Some(cd_utility(&a.beta, &a.e, min_qty) - cd_utility(&a0.beta, &a0.e, min_qty))
+        })
+        .sum()
+}
@@ -0,0 +1,306 @@
+//! Fluent construction of `SimConfig` and `Agent` values for library users.
+//!
+//! `SimConfig::builder()` starts from the same defaults as the presets (0.1–0.9 alphas, a 0.35
+//! step cap, 60 bisection steps) and `build` runs every check `sim::init_agents` would, so a
+//! built config is known to simulate. `Agent::builder()` derives the Cobb–Douglas profile from
+//! either `alpha_to_base` or `beta`, keeping the two consistent.
+use std::collections::BTreeMap;
+use serde_json::json;
+use crate::error::RdxError;
+use crate::model::{
+    Agent, AgentGroupSpec, EndowmentSpec, MatchingMode, PairingMode, PreferenceGenerator, Scheduler, SimConfig,
+};
+use crate::preferences::{alpha_from_beta, beta_from_alpha_to_base};
+use crate::math::normalize;
+use crate::reaction::ReactionRuleSpec;
+use crate::sim::check_config;
+
+impl SimConfig {
+    /// Start building a config: 100 agents, 30 rounds of 300 encounters, goods `base` and `g1`.
+    pub fn builder() -> SimConfigBuilder {
+        SimConfigBuilder::default()
+    }
+}
+
+/// Builder for `SimConfig` (see `SimConfig::builder`). Fields without a setter can be changed
+/// through `with`.
+#[derive(Clone, Debug)]
+pub struct SimConfigBuilder {
+    cfg: SimConfig,
+}
+
+impl Default for SimConfigBuilder {
+    fn default() -> Self {
+        let v = json!({
+            "seed": 0,
+            "num_agents": 100,
+            "rounds": 30,
+            "p2p_encounters_per_round": 300,
+            "base_good": 0,
+            "initial_endowment_scale": 1.0,
+            "alpha_low": 0.1,
+            "alpha_high": 0.9,
+            "trade_step_cap_frac": 0.35,
+            "min_qty": 1e-9,
+            "oracle_bisect_iters": 60,
+            "base_goods_quantity": 2,
+            "base_goods": ["base", "g1"],
+            "reaction_rules": [],
+        });
+        let cfg = serde_json::from_value(v).expect("builder defaults match SimConfig");
+        SimConfigBuilder { cfg }
+    }
+}
+
+impl SimConfigBuilder {
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.cfg.seed = seed;
+        self
+    }
+
+    pub fn agents(mut self, num_agents: usize) -> Self {
+        self.cfg.num_agents = num_agents;
+        self
+    }
+
+    pub fn rounds(mut self, rounds: usize) -> Self {
+        self.cfg.rounds = rounds;
+        self
+    }
+
+    pub fn encounters_per_round(mut self, encounters: usize) -> Self {
+        self.cfg.p2p_encounters_per_round = encounters;
+        self
+    }
+
+    /// The goods of the run, by name; also sets `base_goods_quantity`.
+    pub fn goods<S: Into<String>>(mut self, names: impl IntoIterator<Item = S>) -> Self {
+        self.cfg.base_goods = names.into_iter().map(Into::into).collect();
+        self.cfg.base_goods_quantity = self.cfg.base_goods.len();
+        self
+    }
+
+    /// `n` goods named `base`, `g1`, ..., `g{n-1}`.
+    pub fn num_goods(self, n: usize) -> Self {
+        self.goods((0..n).map(|k| if k == 0 { "base".to_string() } else { format!("g{k}") }))
+    }
+
+    pub fn base_good(mut self, base_good: usize) -> Self {
+        self.cfg.base_good = base_good;
+        self
+    }
+
+    /// Range `[low, high)` of the uniformly drawn `alpha_to_base`.
+    pub fn alpha_range(mut self, low: f64, high: f64) -> Self {
+        self.cfg.alpha_low = low;
+        self.cfg.alpha_high = high;
+        self
+    }
+
+    pub fn preference_generator(mut self, generator: PreferenceGenerator) -> Self {
+        self.cfg.preference_generator = generator;
+        self
+    }
+
+    pub fn endowment(mut self, endowment: EndowmentSpec) -> Self {
+        self.cfg.endowment = endowment;
+        self
+    }
+
+    pub fn endowment_scale(mut self, scale: f64) -> Self {
+        self.cfg.initial_endowment_scale = scale;
+        self
+    }
+
+    pub fn agent_group(mut self, group: AgentGroupSpec) -> Self {
+        self.cfg.agent_groups.push(group);
+        self
+    }
+
+    pub fn step_cap_frac(mut self, frac: f64) -> Self {
+        self.cfg.trade_step_cap_frac = frac;
+        self
+    }
+
+    pub fn min_qty(mut self, min_qty: f64) -> Self {
+        self.cfg.min_qty = min_qty;
+        self
+    }
+
+    pub fn oracle_bisect_iters(mut self, iters: usize) -> Self {
+        self.cfg.oracle_bisect_iters = iters;
+        self
+    }
+
+    pub fn pairing_mode(mut self, mode: PairingMode) -> Self {
+        self.cfg.pairing_mode = mode;
+        self
+    }
+
+    pub fn candidate_goods_k(mut self, k: usize) -> Self {
+        self.cfg.candidate_goods_k = k;
+        self
+    }
+
+    pub fn matching(mut self, matching: MatchingMode) -> Self {
+        self.cfg.matching = matching;
+        self
+    }
+
+    pub fn scheduler(mut self, scheduler: Scheduler) -> Self {
+        self.cfg.scheduler = scheduler;
+        self
+    }
+
+    pub fn reaction_rule(mut self, rule: ReactionRuleSpec) -> Self {
+        self.cfg.reaction_rules.push(rule);
+        self
+    }
+
+    /// Edit any other field, e.g. `.with(|c| c.consumption = Some(spec))`.
+    pub fn with(mut self, edit: impl FnOnce(&mut SimConfig)) -> Self {
+        edit(&mut self.cfg);
+        self
+    }
+
+    /// The config, if it passes `SimConfig::validate` and the engine's own checks. All
+    /// `validate` problems are reported together in one `InvalidConfig`.
+    pub fn build(self) -> Result<SimConfig, RdxError> {
+        let problems = self.cfg.validate();
+        if !problems.is_empty() {
+            let list: Vec<String> = problems.iter().map(|p| p.to_string()).collect();
+            return Err(RdxError::InvalidConfig(list.join("; ")));
+        }
+        check_config(&self.cfg)?;
+        Ok(self.cfg)
+    }
+}
+
+impl Agent {
+    /// Start building an agent; without preferences it values all goods equally.
+    pub fn builder() -> AgentBuilder {
+        AgentBuilder::default()
+    }
+}
+
+#[derive(Clone, Debug)]
+enum Preferences {
+    Equal,
+    AlphaToBase(Vec<f64>),
+    Beta(Vec<f64>),
+}
+
+/// Builder for `Agent` (see `Agent::builder`).
+#[derive(Clone, Debug)]
+pub struct AgentBuilder {
+    id: u64,
+    labels: BTreeMap<String, String>,
+    e: Vec<f64>,
+    base_good: usize,
+    preferences: Preferences,
+    reaction_rules: Vec<ReactionRuleSpec>,
+}
+
+impl Default for AgentBuilder {
+    fn default() -> Self {
+        AgentBuilder {
+            id: 0,
+            labels: BTreeMap::new(),
+            e: Vec::new(),
+            base_good: 0,
+            preferences: Preferences::Equal,
+            reaction_rules: Vec::new(),
+        }
+    }
+}
+
+impl AgentBuilder {
+    pub fn id(mut self, id: u64) -> Self {
+        self.id = id;
+        self
+    }
+
+    pub fn label(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.labels.insert(key.into(), value.into());
+        self
+    }
+
+    /// Holdings of every good; their number is the agent's goods count.
+    pub fn endowment(mut self, e: Vec<f64>) -> Self {
+        self.e = e;
+        self
+    }
+
+    /// The base good `alpha_to_base` refers to (default 0).
+    pub fn base_good(mut self, base_good: usize) -> Self {
+        self.base_good = base_good;
+        self
+    }
+
+    /// Preferences as alphas against the base good; `beta` is derived from them.
+    pub fn alpha_to_base(mut self, alpha_to_base: Vec<f64>) -> Self {
+        self.preferences = Preferences::AlphaToBase(alpha_to_base);
+        self
+    }
+
+    /// Preferences as Cobb–Douglas exponents (normalized at `build`); `alpha_to_base` is derived.
+    pub fn beta(mut self, beta: Vec<f64>) -> Self {
+        self.preferences = Preferences::Beta(beta);
+        self
+    }
+
+    pub fn reaction_rule(mut self, rule: ReactionRuleSpec) -> Self {
+        self.reaction_rules.push(rule);
+        self
+    }
+
+    /// The agent, with `beta` and `alpha_to_base` consistent. Fails on an empty or non-finite /
+    /// negative endowment, preference vectors of another length, a base good out of range, or
+    /// a `beta` with a negative or non-finite entry or no positive weight on the base good.
+    pub fn build(self) -> Result<Agent, RdxError> {
+        let n = self.e.len();
+        if n == 0 || self.e.iter().any(|&x| !(x >= 0.0 && x.is_finite())) {
+            return Err(RdxError::InvalidConfig(format!(
+                "endowment must be non-empty, finite and >= 0, got {:?}", self.e
+            )));
+        }
+        if self.base_good >= n {
+            return Err(RdxError::GoodOutOfRange { index: self.base_good, len: n });
+        }
+        let base = self.base_good;
+        let (alpha_to_base, beta) = match self.preferences {
+            Preferences::Equal => (vec![0.5; n], vec![1.0 / n as f64; n]),
+            Preferences::AlphaToBase(alpha) => {
+                if alpha.len() != n {
+                    return Err(RdxError::DimensionMismatch { expected: n, found: alpha.len() });
+                }
+                let beta = beta_from_alpha_to_base(&alpha, base, 1e-6)?;
+                (alpha, beta)
+            }
+            Preferences::Beta(mut beta) => {
+                if beta.len() != n {
+                    return Err(RdxError::DimensionMismatch { expected: n, found: beta.len() });
+                }
+                if beta.iter().any(|&b| !(b >= 0.0 && b.is_finite())) || beta[base] <= 0.0 {
+                    return Err(RdxError::DegeneratePreferences(format!(
+                        "beta needs finite weights >= 0 and a positive base weight, got {beta:?}"
+                    )));
+                }
+                normalize(&mut beta);
+                let alpha = (0..n)
+                    .map(|k| if k == base { 0.5 } else { alpha_from_beta(&beta, k, base, 0.0) })
+                    .collect();
+                (alpha, beta)
+            }
+        };
+        Ok(Agent {
+            id: self.id,
+            labels: self.labels,
+            e: self.e,
+            beta,
+            alpha_to_base,
+            reaction_rules: self.reaction_rules,
+            ..Default::default()
+        })
+    }
+}
@@ -0,0 +1,456 @@
+//! Checkpoints: a `SimState` saved mid-run in a versioned binary file, carrying what the
+//! `Engine` needs to continue exactly where it stopped.
+//!
+//! `Engine::checkpoint` returns the state with a `ResumePoint` attached: the config in force, the
+//! round counter, the engine's trackers and the position of each random stream. Continuing a
+//! resumed engine (`Engine::resume`) gives bit-for-bit the same run as never stopping. Editing
+//! the agents of a loaded checkpoint before resuming branches a counterfactual continuation.
+//!
+//! File layout: the 8-byte `CHECKPOINT_MAGIC`, the format version as a little-endian `u32`,
+//! the `Compression` byte, then the bincode-encoded `SimState` compressed accordingly. Bincode
+//! stores fields in order without names or defaults, so every new field of a saved type bumps
+//! the version, and older versions decode through frozen copies of their layout. Version 3 files
+//! (round metrics and configs without the oracle audit, trade budget and conservation fields)
+//! load with those fields zero or off; version 2 files (trade events without `seq` and state
+//! hashes, too) and version 1 files (no compression byte either) also with their events numbered
+//! in order and zero hashes. Observers, encounter logs and pending trade edits are not saved.
+
+use std::io::Write;
+use std::path::Path;
+use rand::prelude::*;
+use serde::{Serialize, Deserialize};
+use crate::codec::{compress, decompress, Compression};
+use crate::error::RdxError;
+use crate::math::Ema;
+use crate::ids::{AgentIdx, GoodId};
+use crate::matching::PartnerGraph;
+use crate::metrics::InequalityMetrics;
+use crate::model::{
+    Agent, AgentGroupSpec, BrokerSpec, ClockSpec, ConsumptionSpec, ConvergenceSpec, CycleEvent, DecisionNoise,
+    DemographySpec, Embargo, EndowmentSpec, GoodIntroduction, MatchingMode, MonetarySpec, MoneyEmergenceSpec,
+    NegotiationSpec, PairingMode, PolicySpec, PostedPriceSpec, PreferenceGenerator, PreferenceShockSpec,
+    PriceExpectationSpec, ReplicationSpec, ReputationSpec, ScheduledChange, Scheduler, ShockSpec, SimConfig,
+    SocialInfluenceSpec, StepCap, TradeEvent, TransactionCost, TriadSpec, ZeroIntelligenceSpec,
+};
+use crate::network::Graph;
+use crate::prices::{PricePoint, PriceTracker};
+use crate::reaction::ReactionRuleSpec;
+use crate::sim::{PairRateStat, RoundMetrics, SimState};
+
+/// First bytes of every checkpoint file.
+pub const CHECKPOINT_MAGIC: &[u8; 8] = b"RDXCKPT\0";
+
+/// Format version written by `SimState::save`; `load` also reads versions 1 to 3 and rejects
+/// any other.
+pub const CHECKPOINT_VERSION: u32 = 4;
+
+/// Seeded random stream that counts the 32-bit words it has produced, so its position can be
+/// saved and restored without serializing the generator itself.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(from = "StreamPosition", into = "StreamPosition")]
+pub(crate) struct StreamRng {
+    seed: u64,
+    words: u64,
+    inner: StdRng,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct StreamPosition {
+    seed: u64,
+    words: u64,
+}
+
+impl StreamRng {
+    pub(crate) fn new(seed: u64) -> Self {
+        StreamRng { seed, words: 0, inner: StdRng::seed_from_u64(seed) }
+    }
+}
+
+impl From<StreamPosition> for StreamRng {
+    fn from(p: StreamPosition) -> Self {
+        let mut rng = StreamRng::new(p.seed);
+        for _ in 0..p.words {
+            rng.inner.next_u32();
+        }
+        rng.words = p.words;
+        rng
+    }
+}
+
+impl From<StreamRng> for StreamPosition {
+    fn from(rng: StreamRng) -> Self {
+        StreamPosition { seed: rng.seed, words: rng.words }
+    }
+}
+
+impl RngCore for StreamRng {
+    fn next_u32(&mut self) -> u32 {
+        self.words += 1;
+        self.inner.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.words += 2;
+        self.inner.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.words += dest.len().div_ceil(4) as u64;
+        self.inner.fill_bytes(dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+/// Engine state beyond `SimState`, recorded by `Engine::checkpoint`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ResumePoint {
+    pub(crate) cfg: SimConfig,
+    pub(crate) round: usize,
+    pub(crate) quiet_rounds: usize,
+    pub(crate) rr_order: Vec<usize>,
+    pub(crate) rr_step: usize,
+    pub(crate) next_id: u64,
+    pub(crate) introduced: Vec<GoodIntroduction>,
+    pub(crate) prices: PriceTracker,
+    pub(crate) marketability: Vec<Ema>,
+    pub(crate) network: Option<Graph>,
+    /// Main, demography, dynamics, triad, market, reputation, noise, zero-intelligence and
+    /// learning streams, in that order.
+    pub(crate) streams: [StreamRng; 9],
+}
+
+impl ResumePoint {
+    /// The next round the engine will step.
+    pub fn round(&self) -> usize { self.round }
+
+    /// The config in force when the checkpoint was taken (schedule changes applied, introduced
+    /// goods moved to `base_goods`).
+    pub fn config(&self) -> &SimConfig { &self.cfg }
+
+    /// Change parameters before resuming, to branch a continuation under a different config.
+    /// The goods must stay those of the run.
+    pub fn config_mut(&mut self) -> &mut SimConfig { &mut self.cfg }
+}
+
+impl SimState {
+    /// Encode as a checkpoint file body: magic, version, compression, payload. Compressed with
+    /// the strongest `Compression` this build has.
+    pub fn to_checkpoint_bytes(&self) -> Result<Vec<u8>, RdxError> {
+        self.to_checkpoint_bytes_with(Compression::preferred())
+    }
+
+    /// `to_checkpoint_bytes` with the given compression.
+    pub fn to_checkpoint_bytes_with(&self, compression: Compression) -> Result<Vec<u8>, RdxError> {
+        let payload = bincode::serialize(self).map_err(|e| RdxError::Checkpoint(e.to_string()))?;
+        let mut bytes = CHECKPOINT_MAGIC.to_vec();
+        bytes.extend(CHECKPOINT_VERSION.to_le_bytes());
+        bytes.push(compression.byte());
+        bytes.extend(compress(compression, &payload)?);
+        Ok(bytes)
+    }
+
+    /// Decode `to_checkpoint_bytes` output, rejecting foreign files and other format versions.
+    pub fn from_checkpoint_bytes(bytes: &[u8]) -> Result<SimState, RdxError> {
+        let header = CHECKPOINT_MAGIC.len() + 4;
+        if bytes.len() < header || &bytes[..CHECKPOINT_MAGIC.len()] != CHECKPOINT_MAGIC {
+            return Err(RdxError::Checkpoint("not an rdx checkpoint".into()));
+        }
+        let version = u32::from_le_bytes(bytes[CHECKPOINT_MAGIC.len()..header].try_into().expect("4 bytes"));
+        let (compression, payload) = match version {
+            1 => (Compression::None, &bytes[header..]),
+            2 | 3 | CHECKPOINT_VERSION => {
+                let byte = *bytes.get(header).ok_or_else(|| RdxError::Checkpoint("truncated header".into()))?;
+                let compression = Compression::from_byte(byte)
+                    .ok_or_else(|| RdxError::Checkpoint(format!("unknown compression byte {byte}")))?;
+                (compression, &bytes[header + 1..])
+            }
+            _ => return Err(RdxError::CheckpointVersion { found: version, expected: CHECKPOINT_VERSION }),
+        };
+        let payload = decompress(compression, payload)?;
+        let invalid = |e: bincode::Error| RdxError::Checkpoint(e.to_string());
+        match version {
+            CHECKPOINT_VERSION => bincode::deserialize(&payload).map_err(invalid),
+            3 => bincode::deserialize::<LegacyState<TradeEvent>>(&payload).map(SimState::from).map_err(invalid),
+            _ => bincode::deserialize::<LegacyState<LegacyEvent>>(&payload).map(SimState::from).map_err(invalid),
+        }
+    }
+
+    /// Write the state to `path` (see the module docs for the format). The file is written
+    /// next to `path` and renamed into place, so an interrupted save leaves the previous
+    /// checkpoint intact. A state taken from `Engine::checkpoint` can be resumed after `load`;
+    /// any other state loads as a record.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), RdxError> {
+        let path = path.as_ref();
+        let bytes = self.to_checkpoint_bytes()?;
+        let mut partial = path.as_os_str().to_owned();
+        partial.push(".partial");
+        let mut file = std::fs::File::create(&partial)?;
+        file.write_all(&bytes)?;
+        file.sync_all()?;
+        std::fs::rename(&partial, path)?;
+        Ok(())
+    }
+
+    /// Read a state written by `save`.
+    pub fn load(path: impl AsRef<Path>) -> Result<SimState, RdxError> {
+        SimState::from_checkpoint_bytes(&std::fs::read(path)?)
+    }
+}
+
+/// Frozen copy of a saved type's layout in an older format version, converted to the current
+/// type with the fields added since set to `added` (what the earlier engine did).
+macro_rules! frozen {
+    (
+        $(#[$doc:meta])* $name:ident => $current:ident { $($field:ident: $ty:ty,)* }
+        added { $($new:ident: $value:expr),* }
+    ) => {
+        $(#[$doc])*
+        #[derive(Deserialize)]
+        struct $name {
+            $($field: $ty,)*
+        }
+
+        impl From<$name> for $current {
+            fn from(old: $name) -> $current {
+                $current { $($field: old.$field,)* $($new: $value,)* }
+            }
+        }
+    };
+}
+
+/// `SimState` as versions 1 to 3 laid it out: bincode has no optional fields, so the metrics,
+/// configs and (before version 3) events of older files need their own types.
+#[derive(Deserialize)]
+struct LegacyState<E> {
+    agents: Vec<Agent>,
+    events: Vec<E>,
+    metrics: Vec<LegacyMetrics>,
+    stopped_at: Option<usize>,
+    prices: Vec<PricePoint>,
+    exchange_rates: Vec<PairRateStat>,
+    partners: PartnerGraph,
+    treasury: f64,
+    cycles: Vec<CycleEvent>,
+    resume: Option<LegacyResume>,
+}
+
+impl<E> LegacyState<E> {
+    /// The current state, with `events` for the file's.
+    fn with_events(self, events: Vec<TradeEvent>) -> SimState {
+        SimState {
+            agents: self.agents,
+            events,
+            metrics: self.metrics.into_iter().map(RoundMetrics::from).collect(),
+            stopped_at: self.stopped_at,
+            prices: self.prices,
+            exchange_rates: self.exchange_rates,
+            partners: self.partners,
+            treasury: self.treasury,
+            cycles: self.cycles,
+            resume: self.resume.map(ResumePoint::from),
+        }
+    }
+}
+
+impl From<LegacyState<TradeEvent>> for SimState {
+    fn from(mut s: LegacyState<TradeEvent>) -> SimState {
+        let events = std::mem::take(&mut s.events);
+        s.with_events(events)
+    }
+}
+
+impl From<LegacyState<LegacyEvent>> for SimState {
+    fn from(mut s: LegacyState<LegacyEvent>) -> SimState {
+        let events = std::mem::take(&mut s.events).into_iter().enumerate().map(|(seq, e)| TradeEvent {
+            round: e.round,
+            i: e.i,
+            j: e.j,
+            good_a: e.good_a,
+            good_b: e.good_b,
+            q_ab: e.q_ab,
+            delta_a_i: e.delta_a_i,
+            delta_b_i: e.delta_b_i,
+            delta_u_i: e.delta_u_i,
+            delta_u_j: e.delta_u_j,
+            id_i: e.id_i,
+            id_j: e.id_j,
+            time: e.time,
+            cost_i: e.cost_i,
+            cost_j: e.cost_j,
+            tax: e.tax,
+            speculative: e.speculative,
+            seq: seq as u64,
+            pre_i: [0; 32],
+            pre_j: [0; 32],
+            post_i: [0; 32],
+            post_j: [0; 32],
+        });
+        s.with_events(events.collect())
+    }
+}
+
+/// `TradeEvent` of versions 1 and 2.
+#[derive(Deserialize)]
+struct LegacyEvent {
+    round: usize,
+    i: AgentIdx,
+    j: AgentIdx,
+    good_a: GoodId,
+    good_b: GoodId,
+    q_ab: f64,
+    delta_a_i: f64,
+    delta_b_i: f64,
+    delta_u_i: f64,
+    delta_u_j: f64,
+    id_i: u64,
+    id_j: u64,
+    time: Option<f64>,
+    cost_i: f64,
+    cost_j: f64,
+    tax: f64,
+    speculative: bool,
+}
+
+frozen! {
+    /// `RoundMetrics` of versions 1 to 3.
+    LegacyMetrics => RoundMetrics {
+        round: usize,
+        encounters: usize,
+        trades: usize,
+        delta_u: f64,
+        embargoed: Vec<usize>,
+        time: Option<f64>,
+        population: usize,
+        entered: usize,
+        exited: usize,
+        shocked: usize,
+        preference_shocked: usize,
+        consumed: f64,
+        decayed: f64,
+        replenished: f64,
+        prices: Vec<f64>,
+        costs: f64,
+        cost_refused: usize,
+        credit_drawn: f64,
+        credit_repaid: f64,
+        money_velocity: f64,
+        credit_utilization: f64,
+        cycles: usize,
+        marketability: Vec<f64>,
+        money_good: Option<GoodId>,
+        speculative: usize,
+        tax_revenue: f64,
+        redistributed: f64,
+        mistakes: usize,
+        active_traders: usize,
+        reneged: usize,
+        mean_reputation: f64,
+        listings: usize,
+        market_sales: usize,
+        broker_trades: usize,
+        broker_wealth: f64,
+        imitations: usize,
+        pareto_gap: f64,
+        holders: Vec<usize>,
+        inequality: InequalityMetrics,
+    }
+    added {
+        cap_refused: 0, over_budget: 0, oracle_cache_hits: 0, oracle_cache_misses: 0,
+        oracle_degenerate: 0, oracle_unbracketed: 0, oracle_clamped: 0
+    }
+}
+
+/// `ResumePoint` of versions 1 to 3.
+#[derive(Deserialize)]
+struct LegacyResume {
+    cfg: LegacyConfig,
+    round: usize,
+    quiet_rounds: usize,
+    rr_order: Vec<usize>,
+    rr_step: usize,
+    next_id: u64,
+    introduced: Vec<GoodIntroduction>,
+    prices: PriceTracker,
+    marketability: Vec<Ema>,
+    network: Option<Graph>,
+    streams: [StreamRng; 9],
+}
+
+impl From<LegacyResume> for ResumePoint {
+    fn from(r: LegacyResume) -> ResumePoint {
+        ResumePoint {
+            cfg: r.cfg.into(),
+            round: r.round,
+            quiet_rounds: r.quiet_rounds,
+            rr_order: r.rr_order,
+            rr_step: r.rr_step,
+            next_id: r.next_id,
+            introduced: r.introduced,
+            prices: r.prices,
+            marketability: r.marketability,
+            network: r.network,
+            streams: r.streams,
+        }
+    }
+}
+
+frozen! {
+    /// `SimConfig` of versions 1 to 3.
+    LegacyConfig => SimConfig {
+        seed: u64,
+        num_agents: usize,
+        rounds: usize,
+        p2p_encounters_per_round: usize,
+        base_good: usize,
+        initial_endowment_scale: f64,
+        endowment: EndowmentSpec,
+        agent_groups: Vec<AgentGroupSpec>,
+        consumption: Option<ConsumptionSpec>,
+        demography: Option<DemographySpec>,
+        alpha_low: f64,
+        alpha_high: f64,
+        preference_generator: PreferenceGenerator,
+        endowment_preference_correlation: f64,
+        trade_step_cap_frac: f64,
+        step_cap: StepCap,
+        transaction_cost: Option<TransactionCost>,
+        policy: Option<PolicySpec>,
+        monetary: Option<MonetarySpec>,
+        triads: Option<TriadSpec>,
+        money_emergence: Option<MoneyEmergenceSpec>,
+        brokers: Option<BrokerSpec>,
+        posted_prices: Option<PostedPriceSpec>,
+        reputation: Option<ReputationSpec>,
+        negotiation: Option<NegotiationSpec>,
+        decision_noise: Option<DecisionNoise>,
+        zero_intelligence: Option<ZeroIntelligenceSpec>,
+        price_expectations: Option<PriceExpectationSpec>,
+        social_influence: Option<SocialInfluenceSpec>,
+        min_qty: f64,
+        oracle_bisect_iters: usize,
+        pairing_mode: PairingMode,
+        candidate_goods_k: usize,
+        matching: MatchingMode,
+        scheduler: Scheduler,
+        embargoes: Vec<Embargo>,
+        price_alpha: f64,
+        schedule: Vec<ScheduledChange>,
+        new_goods: Vec<GoodIntroduction>,
+        shocks: Vec<ShockSpec>,
+        preference_shocks: Vec<PreferenceShockSpec>,
+        clock: Option<ClockSpec>,
+        stop_when_converged: Option<ConvergenceSpec>,
+        replications: Option<ReplicationSpec>,
+        base_goods: Vec<String>,
+        base_goods_quantity: usize,
+        good_decay: Vec<f64>,
+        reaction_rules: Vec<ReactionRuleSpec>,
+    }
+    added {
+        conserve_quantities: false, max_trades_per_agent: None, oracle_tol: 0.0, oracle_cache: None
+    }
+}
@@ -5,10 +5,33 @@
 //!
 //! The intention is to support P2P transmission of preference profiles / aggregated Cobb–Douglas
 //! parameters, so peers can evaluate dyadic trades.
+//!
+//! `encode_as` / `decode_tagged` choose among several `CodecFormat`s and prefix the payload
+//! with one format byte, so a receiver decodes whatever a peer chose to send. JSON is always
+//! available; the binary backends are behind features: bincode (`std`), CBOR (`cbor`) and
+//! MessagePack (`msgpack`). The format bytes cannot start a JSON document, so
+//! untagged `encode` output is told apart too.
+//!
+//! `Envelope` adds a payload schema version on top, with `MIGRATIONS` upgrading older bodies.
+//! Envelope bodies can be compressed (`Compression`, features `deflate` and `zstd`), which
+//! `CompressedWriter` also offers for streamed exports. With the `sealed` feature,
+//! `sealed::seal` / `sealed::open` encrypt envelopes to one peer. `privatize_beta` noises
+//! exponents with a differential-privacy guarantee before they are shared (see `privacy`).
 
-use serde::{Serialize, de::DeserializeOwned};
+use alloc::borrow::Cow;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+use serde::{Serialize, Deserialize, de::DeserializeOwned};
+use serde_json::Value;
 use thiserror::Error;
 
+pub mod privacy;
+#[cfg(feature = "sealed")]
+pub mod sealed;
+
+pub use privacy::privatize_beta;
+
 #[derive(Debug, Error)]
 pub enum CodecError {
     #[error("serde_json error: {0}")]
@@ -17,6 +40,302 @@ pub enum CodecError {
     #[cfg(feature = "mvcf")]
     #[error("mvcf codec error: {0}")]
     Mvcf(String),
+
+    #[error("{format} codec error: {message}")]
+    Backend { format: CodecFormat, message: String },
+
+    #[error("{0} support is not compiled in (enable the `{feature}` feature)", feature = .0.feature())]
+    FormatDisabled(CodecFormat),
+
+    #[error("unknown codec format byte {0:#04x}")]
+    UnknownFormat(u8),
+
+    #[error("empty payload")]
+    Empty,
+
+    #[error("payload header is truncated")]
+    Truncated,
+
+    #[error("payload version {found} is newer than the supported {supported}")]
+    UnsupportedVersion { found: u32, supported: u32 },
+
+    #[error("version {version} {format} payloads cannot be migrated (the format is not self-describing)")]
+    NotMigratable { format: CodecFormat, version: u32 },
+
+    #[error("{0} compression is not compiled in (enable the `{feature}` feature)", feature = .0.feature())]
+    CompressionDisabled(Compression),
+
+    #[error("unknown compression byte {0:#03x}")]
+    UnknownCompression(u8),
+
+    #[error("{compression} compression error: {message}")]
+    Compress { compression: Compression, message: String },
+
+    #[error("privacy budget epsilon must be positive and finite, found {0}")]
+    InvalidEpsilon(f64),
+
+    #[cfg(feature = "sealed")]
+    #[error("sealed payload could not be opened (wrong recipient or altered in transit)")]
+    Unseal,
+}
+
+/// Wire format of a tagged payload, identified by its first byte.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CodecFormat {
+    Json,
+    Bincode,
+    Cbor,
+    MessagePack,
+}
+
+impl CodecFormat {
+    pub const ALL: [CodecFormat; 4] =
+        [CodecFormat::Json, CodecFormat::Bincode, CodecFormat::Cbor, CodecFormat::MessagePack];
+
+    /// The prefix byte written by `encode_as`.
+    pub fn byte(self) -> u8 {
+        match self {
+            CodecFormat::Json => 0x01,
+            CodecFormat::Bincode => 0x02,
+            CodecFormat::Cbor => 0x03,
+            CodecFormat::MessagePack => 0x04,
+        }
+    }
+
+    pub fn from_byte(byte: u8) -> Option<CodecFormat> {
+        CodecFormat::ALL.into_iter().find(|f| f.byte() == byte)
+    }
+
+    /// Whether this build can encode and decode the format.
+    pub fn is_enabled(self) -> bool {
+        match self {
+            CodecFormat::Json => true,
+            CodecFormat::Bincode => cfg!(feature = "std"),
+            CodecFormat::Cbor => cfg!(feature = "cbor"),
+            CodecFormat::MessagePack => cfg!(feature = "msgpack"),
+        }
+    }
+
+    /// Cargo feature providing the format.
+    pub fn feature(self) -> &'static str {
+        match self {
+            CodecFormat::Json => "default",
+            CodecFormat::Bincode => "std",
+            CodecFormat::Cbor => "cbor",
+            CodecFormat::MessagePack => "msgpack",
+        }
+    }
+
+    /// Whether payloads can be decoded without knowing their type (needed for migrations).
+    pub fn is_self_describing(self) -> bool {
+        self != CodecFormat::Bincode
+    }
+
+    /// Format of a tagged payload, without decoding it.
+    pub fn of(bytes: &[u8]) -> Result<CodecFormat, CodecError> {
+        let &first = bytes.first().ok_or(CodecError::Empty)?;
+        CodecFormat::from_byte(first).ok_or(CodecError::UnknownFormat(first))
+    }
+}
+
+impl fmt::Display for CodecFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            CodecFormat::Json => "json",
+            CodecFormat::Bincode => "bincode",
+            CodecFormat::Cbor => "cbor",
+            CodecFormat::MessagePack => "message_pack",
+        })
+    }
+}
+
+/// Compression of an envelope body or an exported file.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Compression {
+    #[default]
+    None,
+    /// DEFLATE in a gzip container, so exported files open with `gunzip`.
+    Deflate,
+    Zstd,
+}
+
+impl Compression {
+    pub const ALL: [Compression; 3] = [Compression::None, Compression::Deflate, Compression::Zstd];
+
+    /// The value carried in the high nibble of an envelope's format byte.
+    pub fn byte(self) -> u8 {
+        match self {
+            Compression::None => 0,
+            Compression::Deflate => 1,
+            Compression::Zstd => 2,
+        }
+    }
+
+    pub fn from_byte(byte: u8) -> Option<Compression> {
+        Compression::ALL.into_iter().find(|c| c.byte() == byte)
+    }
+
+    /// Parse the `Display` name (`none`, `deflate`, `zstd`).
+    pub fn from_name(name: &str) -> Option<Compression> {
+        Compression::ALL.into_iter().find(|c| c.name() == name)
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Compression::None => "none",
+            Compression::Deflate => "deflate",
+            Compression::Zstd => "zstd",
+        }
+    }
+
+    pub fn is_enabled(self) -> bool {
+        match self {
+            Compression::None => true,
+            Compression::Deflate => cfg!(feature = "deflate"),
+            Compression::Zstd => cfg!(feature = "zstd"),
+        }
+    }
+
+    /// Cargo feature providing the compression.
+    pub fn feature(self) -> &'static str {
+        match self {
+            Compression::None => "default",
+            Compression::Deflate => "deflate",
+            Compression::Zstd => "zstd",
+        }
+    }
+
+    /// The strongest compression this build has: zstd, then deflate, then none.
+    pub fn preferred() -> Compression {
+        [Compression::Zstd, Compression::Deflate].into_iter().find(|c| c.is_enabled()).unwrap_or(Compression::None)
+    }
+
+    /// File name suffix for exports (`""`, `".gz"` or `".zst"`).
+    pub fn extension(self) -> &'static str {
+        match self {
+            Compression::None => "",
+            Compression::Deflate => ".gz",
+            Compression::Zstd => ".zst",
+        }
+    }
+}
+
+impl fmt::Display for Compression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Compress `bytes` in one go.
+pub fn compress(compression: Compression, bytes: &[u8]) -> Result<Vec<u8>, CodecError> {
+    match compression {
+        Compression::None => Ok(bytes.to_vec()),
+        #[cfg(feature = "deflate")]
+        Compression::Deflate => {
+            use std::io::Write;
+            let mut enc = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+            enc.write_all(bytes).and_then(|_| enc.finish()).map_err(|e| compress_error(compression, e))
+        }
+        #[cfg(feature = "zstd")]
+        Compression::Zstd => zstd::encode_all(bytes, 0).map_err(|e| compress_error(compression, e)),
+        #[allow(unreachable_patterns)]
+        _ => Err(CodecError::CompressionDisabled(compression)),
+    }
+}
+
+/// Undo `compress`.
+pub fn decompress(compression: Compression, bytes: &[u8]) -> Result<Cow<'_, [u8]>, CodecError> {
+    match compression {
+        Compression::None => Ok(Cow::Borrowed(bytes)),
+        #[cfg(feature = "deflate")]
+        Compression::Deflate => {
+            use std::io::Read;
+            let mut out = Vec::new();
+            flate2::read::GzDecoder::new(bytes).read_to_end(&mut out).map_err(|e| compress_error(compression, e))?;
+            Ok(Cow::Owned(out))
+        }
+        #[cfg(feature = "zstd")]
+        Compression::Zstd => Ok(Cow::Owned(zstd::decode_all(bytes).map_err(|e| compress_error(compression, e))?)),
+        #[allow(unreachable_patterns)]
+        _ => Err(CodecError::CompressionDisabled(compression)),
+    }
+}
+
+#[cfg(any(feature = "deflate", feature = "zstd"))]
+fn compress_error(compression: Compression, e: impl fmt::Display) -> CodecError {
+    CodecError::Compress { compression, message: e.to_string() }
+}
+
+/// Streaming `compress` for exports too large to hold in memory (e.g. event logs). Call
+/// `finish` to flush the compressed stream's trailer; dropping the writer truncates it.
+#[cfg(feature = "std")]
+pub struct CompressedWriter<W: std::io::Write> {
+    inner: WriterInner<W>,
+}
+
+#[cfg(feature = "std")]
+enum WriterInner<W: std::io::Write> {
+    Plain(W),
+    #[cfg(feature = "deflate")]
+    Deflate(flate2::write::GzEncoder<W>),
+    #[cfg(feature = "zstd")]
+    Zstd(zstd::Encoder<'static, W>),
+}
+
+#[cfg(feature = "std")]
+impl<W: std::io::Write> CompressedWriter<W> {
+    pub fn new(compression: Compression, w: W) -> Result<CompressedWriter<W>, CodecError> {
+        let inner = match compression {
+            Compression::None => WriterInner::Plain(w),
+            #[cfg(feature = "deflate")]
+            Compression::Deflate => {
+                WriterInner::Deflate(flate2::write::GzEncoder::new(w, flate2::Compression::default()))
+            }
+            #[cfg(feature = "zstd")]
+            Compression::Zstd => {
+                WriterInner::Zstd(zstd::Encoder::new(w, 0).map_err(|e| compress_error(compression, e))?)
+            }
+            #[allow(unreachable_patterns)]
+            _ => return Err(CodecError::CompressionDisabled(compression)),
+        };
+        Ok(CompressedWriter { inner })
+    }
+
+    /// Complete the compressed stream and return the underlying writer.
+    pub fn finish(self) -> std::io::Result<W> {
+        match self.inner {
+            WriterInner::Plain(w) => Ok(w),
+            #[cfg(feature = "deflate")]
+            WriterInner::Deflate(enc) => enc.finish(),
+            #[cfg(feature = "zstd")]
+            WriterInner::Zstd(enc) => enc.finish(),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<W: std::io::Write> std::io::Write for CompressedWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match &mut self.inner {
+            WriterInner::Plain(w) => w.write(buf),
+            #[cfg(feature = "deflate")]
+            WriterInner::Deflate(enc) => enc.write(buf),
+            #[cfg(feature = "zstd")]
+            WriterInner::Zstd(enc) => enc.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match &mut self.inner {
+            WriterInner::Plain(w) => w.flush(),
+            #[cfg(feature = "deflate")]
+            WriterInner::Deflate(enc) => enc.flush(),
+            #[cfg(feature = "zstd")]
+            WriterInner::Zstd(enc) => enc.flush(),
+        }
+    }
 }
 
 pub fn encode<T: Serialize>(v: &T) -> Result<Vec<u8>, CodecError> {
@@ -48,3 +367,169 @@ pub fn decode<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, CodecError> {
         Ok(serde_json::from_slice(bytes)?)
     }
 }
+
+/// Encode `v` in `format`, prefixed with the format byte.
+pub fn encode_as<T: Serialize>(format: CodecFormat, v: &T) -> Result<Vec<u8>, CodecError> {
+    let mut out = alloc::vec![format.byte()];
+    encode_body(format, v, &mut out)?;
+    Ok(out)
+}
+
+/// Decode a payload written by `encode_as`, in whichever format its first byte names.
+pub fn decode_tagged<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, CodecError> {
+    decode_body(CodecFormat::of(bytes)?, &bytes[1..])
+}
+
+/// Append the untagged encoding of `v` in `format` to `out`.
+fn encode_body<T: Serialize>(format: CodecFormat, v: &T, out: &mut Vec<u8>) -> Result<(), CodecError> {
+    if !format.is_enabled() {
+        return Err(CodecError::FormatDisabled(format));
+    }
+    match format {
+        CodecFormat::Json => out.extend(serde_json::to_vec(v)?),
+        #[cfg(feature = "std")]
+        CodecFormat::Bincode => bincode::serialize_into(out, v).map_err(|e| backend(format, e))?,
+        #[cfg(feature = "cbor")]
+        CodecFormat::Cbor => ciborium::into_writer(v, out).map_err(|e| backend(format, e))?,
+        #[cfg(feature = "msgpack")]
+        CodecFormat::MessagePack => rmp_serde::encode::write_named(out, v).map_err(|e| backend(format, e))?,
+        #[allow(unreachable_patterns)]
+        _ => return Err(CodecError::FormatDisabled(format)),
+    }
+    Ok(())
+}
+
+fn decode_body<T: DeserializeOwned>(format: CodecFormat, body: &[u8]) -> Result<T, CodecError> {
+    if !format.is_enabled() {
+        return Err(CodecError::FormatDisabled(format));
+    }
+    match format {
+        CodecFormat::Json => Ok(serde_json::from_slice(body)?),
+        #[cfg(feature = "std")]
+        CodecFormat::Bincode => bincode::deserialize(body).map_err(|e| backend(format, e)),
+        #[cfg(feature = "cbor")]
+        CodecFormat::Cbor => ciborium::from_reader(body).map_err(|e| backend(format, e)),
+        #[cfg(feature = "msgpack")]
+        CodecFormat::MessagePack => rmp_serde::from_slice(body).map_err(|e| backend(format, e)),
+        #[allow(unreachable_patterns)]
+        _ => Err(CodecError::FormatDisabled(format)),
+    }
+}
+
+#[cfg(feature = "std")]
+fn backend(format: CodecFormat, e: impl fmt::Display) -> CodecError {
+    CodecError::Backend { format, message: e.to_string() }
+}
+
+/// Payload schema version written by `Envelope::seal`. Version 0 is the untagged JSON of
+/// `encode`, which `Envelope::from_bytes` still accepts.
+pub const PAYLOAD_VERSION: u32 = 1;
+
+/// First byte of `Envelope::to_bytes` output; no format byte or JSON document starts with it.
+pub const ENVELOPE_TAG: u8 = 0x00;
+
+/// Upgrade of a decoded payload body from one version to the next.
+pub type Migration = fn(Value) -> Result<Value, CodecError>;
+
+/// `MIGRATIONS[k]` takes a version `k` body to version `k + 1`.
+pub const MIGRATIONS: [Migration; PAYLOAD_VERSION as usize] = [untagged_json_to_v1];
+
+/// Version 1 only added the envelope; bodies are unchanged.
+fn untagged_json_to_v1(body: Value) -> Result<Value, CodecError> {
+    Ok(body)
+}
+
+/// A payload with its schema version and wire format, so peers on older releases can still be
+/// understood: `open` migrates older bodies up to `PAYLOAD_VERSION` and refuses newer ones with
+/// `CodecError::UnsupportedVersion` rather than a decoding failure.
+///
+/// Wire layout (`to_bytes`): `ENVELOPE_TAG`, the version as a little-endian `u32`, the format
+/// byte with the `Compression` byte in its high nibble, then the body.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Envelope {
+    pub version: u32,
+    pub format: CodecFormat,
+    /// Applied to the encoded body; `body` holds the compressed bytes.
+    #[serde(default)]
+    pub compression: Compression,
+    pub body: Vec<u8>,
+}
+
+impl Envelope {
+    const HEADER: usize = 6;
+
+    /// `v` encoded in `format` at the current version.
+    pub fn seal<T: Serialize>(format: CodecFormat, v: &T) -> Result<Envelope, CodecError> {
+        let mut body = Vec::new();
+        encode_body(format, v, &mut body)?;
+        Ok(Envelope { version: PAYLOAD_VERSION, format, compression: Compression::None, body })
+    }
+
+    /// `seal`, with the encoded body compressed.
+    pub fn seal_compressed<T: Serialize>(
+        format: CodecFormat,
+        compression: Compression,
+        v: &T,
+    ) -> Result<Envelope, CodecError> {
+        let sealed = Envelope::seal(format, v)?;
+        Ok(Envelope { compression, body: compress(compression, &sealed.body)?, ..sealed })
+    }
+
+    /// Decode the body, migrating it first when it is older than `PAYLOAD_VERSION`. Migration
+    /// goes through a self-describing form, so old bincode bodies fail with `NotMigratable`.
+    pub fn open<T: DeserializeOwned>(&self) -> Result<T, CodecError> {
+        if self.version > PAYLOAD_VERSION {
+            return Err(CodecError::UnsupportedVersion { found: self.version, supported: PAYLOAD_VERSION });
+        }
+        let body = decompress(self.compression, &self.body)?;
+        if self.version == PAYLOAD_VERSION {
+            return decode_body(self.format, &body);
+        }
+        if !self.format.is_self_describing() {
+            return Err(CodecError::NotMigratable { format: self.format, version: self.version });
+        }
+        let mut value: Value = decode_body(self.format, &body)?;
+        for migrate in &MIGRATIONS[self.version as usize..] {
+            value = migrate(value)?;
+        }
+        Ok(serde_json::from_value(value)?)
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(Self::HEADER + self.body.len());
+        out.push(ENVELOPE_TAG);
+        out.extend(self.version.to_le_bytes());
+        out.push(self.format.byte() | self.compression.byte() << 4);
+        out.extend_from_slice(&self.body);
+        out
+    }
+
+    /// Parse `to_bytes` output. `encode_as` output reads as a version 1 envelope and anything
+    /// else as version 0 JSON; versions past `PAYLOAD_VERSION` are rejected up front.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Envelope, CodecError> {
+        match bytes.first() {
+            None => Err(CodecError::Empty),
+            Some(&ENVELOPE_TAG) => {
+                if bytes.len() < Self::HEADER {
+                    return Err(CodecError::Truncated);
+                }
+                let version = u32::from_le_bytes(bytes[1..5].try_into().expect("4 bytes"));
+                if version > PAYLOAD_VERSION {
+                    return Err(CodecError::UnsupportedVersion { found: version, supported: PAYLOAD_VERSION });
+                }
+                let (format, compression) = (bytes[5] & 0x0f, bytes[5] >> 4);
+                let format = CodecFormat::from_byte(format).ok_or(CodecError::UnknownFormat(format))?;
+                let compression =
+                    Compression::from_byte(compression).ok_or(CodecError::UnknownCompression(compression))?;
+                Ok(Envelope { version, format, compression, body: bytes[Self::HEADER..].to_vec() })
+            }
+            Some(&first) => {
+                let (version, format, body) = match CodecFormat::from_byte(first) {
+                    Some(format) => (1, format, &bytes[1..]),
+                    None => (0, CodecFormat::Json, bytes),
+                };
+                Ok(Envelope { version, format, compression: Compression::None, body: body.to_vec() })
+            }
+        }
+    }
+}
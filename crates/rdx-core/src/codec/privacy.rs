@@ -0,0 +1,133 @@
+//! Differentially private preference sharing: noise a peer's exponents before they leave it,
+//! and measure what trading on the noisy exponents costs.
+//!
+//! `privatize_beta` is the Laplace mechanism on the simplex. Two exponent vectors differ by at
+//! most 2 in L1 norm, so adding Laplace noise of scale `2 / epsilon` to every coordinate is
+//! `epsilon`-differentially private; the floor and renormalization that bring the result back
+//! onto the simplex are post-processing and keep the guarantee.
+
+use alloc::vec;
+use alloc::vec::Vec;
+use rand::Rng;
+use serde::{Serialize, Deserialize};
+use crate::math::{ln, normalize};
+use crate::pareto_oracle::ParetoOracle;
+use crate::preferences::cd_utility;
+use crate::trade::evaluate_exchange;
+use super::CodecError;
+
+/// Smallest exponent `privatize_beta` returns.
+pub const PRIVATE_BETA_FLOOR: f64 = 1e-6;
+
+/// Laplace noise scale giving `epsilon`-differential privacy for simplex points.
+pub fn laplace_scale(epsilon: f64) -> f64 {
+    2.0 / epsilon
+}
+
+/// `epsilon`-differentially private copy of `beta` (see the module docs). Smaller `epsilon`
+/// means more privacy and more noise.
+pub fn privatize_beta<R: Rng>(beta: &[f64], epsilon: f64, rng: &mut R) -> Result<Vec<f64>, CodecError> {
+    if !(epsilon.is_finite() && epsilon > 0.0) {
+        return Err(CodecError::InvalidEpsilon(epsilon));
+    }
+    let scale = laplace_scale(epsilon);
+    let mut noisy: Vec<f64> = beta.iter().map(|&w| (w + laplace(scale, rng)).max(PRIVATE_BETA_FLOOR)).collect();
+    normalize(&mut noisy);
+    floor_on_simplex(&mut noisy, PRIVATE_BETA_FLOOR);
+    Ok(noisy)
+}
+
+/// Raise the coordinates of a simplex point below `floor` to it, shrinking the others in
+/// proportion to keep the sum, until none is below. Needs `v.len() * floor <= 1`.
+fn floor_on_simplex(v: &mut [f64], floor: f64) {
+    let mut pinned = vec![false; v.len()];
+    loop {
+        let mut raised = false;
+        for (x, p) in v.iter_mut().zip(pinned.iter_mut()) {
+            if !*p && *x < floor {
+                (*x, *p) = (floor, true);
+                raised = true;
+            }
+        }
+        if !raised { return; }
+        let free: f64 = v.iter().zip(&pinned).filter(|(_, &p)| !p).map(|(x, _)| x).sum();
+        let left = 1.0 - floor * pinned.iter().filter(|&&p| p).count() as f64;
+        if free <= 0.0 { return; }
+        for (x, _) in v.iter_mut().zip(&pinned).filter(|(_, &p)| !p) {
+            *x *= left / free;
+        }
+    }
+}
+
+fn laplace<R: Rng>(scale: f64, rng: &mut R) -> f64 {
+    let u: f64 = rng.gen::<f64>() - 0.5;
+    let tail = 1.0 - 2.0 * u.abs();
+    let magnitude = -scale * ln(tail.max(f64::MIN_POSITIVE));
+    if u < 0.0 { -magnitude } else { magnitude }
+}
+
+/// What agent i gives up by publishing privatized exponents, over repeated draws of the noise.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PrivacyLoss {
+    pub epsilon: f64,
+    pub samples: usize,
+    /// i's utility gain from the exchange negotiated on its true exponents.
+    pub truthful_gain: f64,
+    /// Mean utility gain, under i's true exponents, from exchanges negotiated on the noisy ones
+    /// (0 for draws where no mutually improving exchange was found).
+    pub private_gain: f64,
+    /// `truthful_gain - private_gain`.
+    pub expected_loss: f64,
+    /// Mean L1 distance between the true and privatized exponents.
+    pub mean_beta_l1: f64,
+    /// Share of draws in which the noisy exponents still produced an exchange.
+    pub trade_rate: f64,
+}
+
+/// Estimate `PrivacyLoss` for agent i trading goods `good_a` and `good_b` with j, who sees
+/// only `privatize_beta(beta_i, epsilon)`: each of `samples` draws negotiates the exchange with
+/// `evaluate_exchange` on the noisy exponents, then scores i's side on the true ones.
+#[allow(clippy::too_many_arguments)]
+pub fn expected_utility_loss<R: Rng>(
+    (beta_i, e_i): (&[f64], &[f64]),
+    (beta_j, e_j): (&[f64], &[f64]),
+    good_a: usize,
+    good_b: usize,
+    epsilon: f64,
+    samples: usize,
+    min_qty: f64,
+    oracle_iters: usize,
+    oracle: &dyn ParetoOracle,
+    rng: &mut R,
+) -> Result<PrivacyLoss, CodecError> {
+    let u0 = cd_utility(beta_i, e_i, min_qty);
+    let gain = |published: &[f64]| -> Option<f64> {
+        let c = evaluate_exchange(published, e_i, beta_j, e_j, good_a, good_b, min_qty, oracle_iters, oracle)?;
+        let mut post = e_i.to_vec();
+        post[good_a] += c.delta_a_i;
+        post[good_b] += c.delta_b_i;
+        Some(cd_utility(beta_i, &post, min_qty) - u0)
+    };
+    let truthful_gain = gain(beta_i).unwrap_or(0.0);
+
+    let (mut private_gain, mut mean_beta_l1, mut trades) = (0.0, 0.0, 0usize);
+    for _ in 0..samples {
+        let noisy = privatize_beta(beta_i, epsilon, rng)?;
+        mean_beta_l1 += beta_i.iter().zip(&noisy).map(|(a, b)| (a - b).abs()).sum::<f64>();
+        if let Some(g) = gain(&noisy) {
+            private_gain += g;
+            trades += 1;
+        }
+    }
+    let n = samples.max(1) as f64;
+    let private_gain = private_gain / n;
+    Ok(PrivacyLoss {
+        epsilon,
+        samples,
+        truthful_gain,
+        private_gain,
+        expected_loss: truthful_gain - private_gain,
+        mean_beta_l1: mean_beta_l1 / n,
+        trade_rate: trades as f64 / n,
+    })
+}
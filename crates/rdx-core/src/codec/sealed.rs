@@ -0,0 +1,117 @@
+//! Payloads encrypted to one peer (feature `sealed`), so preference vectors do not travel in
+//! the clear where any listener could trade against them.
+//!
+//! `seal` wraps the payload in an `Envelope`, agrees a key between a fresh ephemeral X25519 key
+//! and the recipient's public key, and encrypts with ChaCha20-Poly1305 under a key and nonce
+//! derived by HKDF-SHA256 from the shared secret and both public keys. Each payload gets its
+//! own ephemeral key, so no key/nonce pair is ever reused. Wire layout: `SEALED_TAG`, the
+//! ephemeral public key (32 bytes), then the ciphertext with its 16-byte tag. The sender is not
+//! authenticated; sign the payload separately if the recipient must know who sent it.
+
+use alloc::vec::Vec;
+use core::fmt;
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use hkdf::Hkdf;
+use rand::{CryptoRng, RngCore};
+use serde::{Serialize, Deserialize, de::DeserializeOwned};
+use sha2::Sha256;
+use x25519_dalek::{EphemeralSecret, StaticSecret};
+use super::{CodecError, CodecFormat, Envelope};
+
+/// First byte of a sealed payload; distinct from envelope, format and JSON first bytes.
+pub const SEALED_TAG: u8 = 0x10;
+
+const KEY_LEN: usize = 32;
+const TAG_LEN: usize = 16;
+const HKDF_SALT: &[u8] = b"rdx-sealed-v1";
+
+/// A peer's X25519 public key, as published to those who want to send it payloads.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct PublicKey(pub [u8; KEY_LEN]);
+
+/// A peer's X25519 secret key. `Debug` does not print it.
+#[derive(Clone)]
+pub struct SecretKey(StaticSecret);
+
+impl SecretKey {
+    pub fn generate<R: RngCore + CryptoRng>(rng: &mut R) -> SecretKey {
+        SecretKey(StaticSecret::random_from_rng(rng))
+    }
+
+    pub fn from_bytes(bytes: [u8; KEY_LEN]) -> SecretKey {
+        SecretKey(StaticSecret::from(bytes))
+    }
+
+    pub fn to_bytes(&self) -> [u8; KEY_LEN] {
+        self.0.to_bytes()
+    }
+
+    pub fn public_key(&self) -> PublicKey {
+        PublicKey(x25519_dalek::PublicKey::from(&self.0).to_bytes())
+    }
+}
+
+impl fmt::Debug for SecretKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("SecretKey").field(&"..").finish()
+    }
+}
+
+/// Encrypt `v`, encoded in `format`, so only the holder of `recipient`'s secret key can read it.
+pub fn seal<T: Serialize, R: RngCore + CryptoRng>(
+    recipient: &PublicKey,
+    format: CodecFormat,
+    v: &T,
+    rng: &mut R,
+) -> Result<Vec<u8>, CodecError> {
+    let plaintext = Envelope::seal(format, v)?.to_bytes();
+    let ephemeral = EphemeralSecret::random_from_rng(rng);
+    let ephemeral_pub = PublicKey(x25519_dalek::PublicKey::from(&ephemeral).to_bytes());
+    let shared = ephemeral.diffie_hellman(&x25519_dalek::PublicKey::from(recipient.0));
+    if !shared.was_contributory() {
+        return Err(CodecError::Unseal);
+    }
+    let (cipher, nonce) = cipher(shared.as_bytes(), &ephemeral_pub, recipient);
+    let ciphertext = cipher
+        .encrypt(&nonce, Payload { msg: &plaintext, aad: &[SEALED_TAG] })
+        .map_err(|_| CodecError::Unseal)?;
+
+    let mut out = Vec::with_capacity(1 + KEY_LEN + ciphertext.len());
+    out.push(SEALED_TAG);
+    out.extend_from_slice(&ephemeral_pub.0);
+    out.extend(ciphertext);
+    Ok(out)
+}
+
+/// Decrypt and decode a payload sealed to `secret`'s public key. Payloads sealed to someone
+/// else, or altered in transit, fail with `CodecError::Unseal`.
+pub fn open<T: DeserializeOwned>(secret: &SecretKey, bytes: &[u8]) -> Result<T, CodecError> {
+    match bytes.first() {
+        None => return Err(CodecError::Empty),
+        Some(&SEALED_TAG) => {}
+        Some(&other) => return Err(CodecError::UnknownFormat(other)),
+    }
+    if bytes.len() < 1 + KEY_LEN + TAG_LEN {
+        return Err(CodecError::Truncated);
+    }
+    let ephemeral_pub = PublicKey(bytes[1..1 + KEY_LEN].try_into().expect("32 bytes"));
+    let shared = secret.0.diffie_hellman(&x25519_dalek::PublicKey::from(ephemeral_pub.0));
+    if !shared.was_contributory() {
+        return Err(CodecError::Unseal);
+    }
+    let (cipher, nonce) = cipher(shared.as_bytes(), &ephemeral_pub, &secret.public_key());
+    let plaintext = cipher
+        .decrypt(&nonce, Payload { msg: &bytes[1 + KEY_LEN..], aad: &[SEALED_TAG] })
+        .map_err(|_| CodecError::Unseal)?;
+    Envelope::from_bytes(&plaintext)?.open()
+}
+
+fn cipher(shared: &[u8; KEY_LEN], ephemeral: &PublicKey, recipient: &PublicKey) -> (ChaCha20Poly1305, Nonce) {
+    let mut info = [0u8; 2 * KEY_LEN];
+    info[..KEY_LEN].copy_from_slice(&ephemeral.0);
+    info[KEY_LEN..].copy_from_slice(&recipient.0);
+    let mut okm = [0u8; KEY_LEN + 12];
+    Hkdf::<Sha256>::new(Some(HKDF_SALT), shared).expand(&info, &mut okm).expect("44 bytes is a valid HKDF length");
+    (ChaCha20Poly1305::new(Key::from_slice(&okm[..KEY_LEN])), *Nonce::from_slice(&okm[KEY_LEN..]))
+}
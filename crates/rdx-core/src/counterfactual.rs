@@ -0,0 +1,261 @@
+//! Counterfactual analyses over replayed encounters.
+//!
+//! A baseline run records its encounter schedule; a second run replays the same dyads with one
+//! thing changed, so any difference between the two is caused by that change rather than by the
+//! matcher drawing different partners. The engine draws no other randomness during a round, so
+//! replaying the schedule keeps both runs on the same random path.
+//!
+//! - `butterfly`: one trade of a recorded run removed or rescaled.
+//! - `attribute_gains`: the whole exchange mechanism swapped for a baseline.
+//! - `information_loss`: quote-based negotiation against full-information trade.
+//! - `zero_intelligence_baseline`: the oracle against random (Gode–Sunder) proposals.
+
+use serde::{Serialize, Deserialize};
+use crate::error::RdxError;
+use crate::model::{Agent, SimConfig, TradeEvent, ZeroIntelligenceSpec};
+use crate::pareto_oracle::ParetoOracle;
+use crate::preferences::cd_utility;
+use crate::sim::{Encounter, Engine, SimState};
+
+/// Modification applied to one trade of a run.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TradeEdit {
+    /// The dyad meets but does not trade.
+    Remove,
+    /// Multiply the (already capped) trade quantities by a non-negative factor.
+    Scale(f64),
+}
+
+/// Difference between the baseline and the edited run at the end of one round.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RoundDivergence {
+    pub round: usize,
+    /// Sum over agents and goods of |e_variant - e_baseline|.
+    pub endowment_l1: f64,
+    /// Sum over agents of |u_variant - u_baseline|.
+    pub utility_l1: f64,
+    /// Total utility of the edited run minus that of the baseline.
+    pub welfare_delta: f64,
+    /// Agents whose holdings differ from the baseline at all.
+    pub agents_affected: usize,
+    pub trades_baseline: usize,
+    pub trades_variant: usize,
+}
+
+#[derive(Clone, Debug)]
+pub struct ButterflyReport {
+    pub trade_index: usize,
+    pub edit: TradeEdit,
+    /// The baseline trade that was edited.
+    pub trade: TradeEvent,
+    /// One entry per round, including rounds before the edit (all zero).
+    pub rounds: Vec<RoundDivergence>,
+    pub baseline: SimState,
+    pub variant: SimState,
+}
+
+impl ButterflyReport {
+    /// First round whose end state differs from the baseline.
+    pub fn first_divergent_round(&self) -> Option<usize> {
+        self.rounds.iter().find(|r| r.agents_affected > 0).map(|r| r.round)
+    }
+}
+
+/// Branch a recorded run of `cfg` at `events[trade_index]`: `log` is the run's encounter
+/// schedule (`Engine::record_encounters`), replayed once as the baseline; at the start of the
+/// edited trade's round the variant splits off from a checkpoint of it, with the trade edited
+/// and the remaining encounters unchanged, and the two are compared round by round.
+///
+/// Convergence stopping is disabled for both branches so they cover the same rounds.
+pub fn butterfly(
+    cfg: &SimConfig,
+    log: &[Encounter],
+    trade_index: usize,
+    edit: TradeEdit,
+) -> Result<ButterflyReport, RdxError> {
+    let mut cfg = cfg.clone();
+    cfg.stop_when_converged = None;
+
+    let mut baseline = Engine::new(cfg.clone())?;
+    baseline.replay_encounters(log.to_vec());
+    let mut checkpoints = Vec::with_capacity(cfg.rounds);
+    // the state before the round that executes the trade, once it is known
+    let mut branch = None;
+    let mut before = baseline.checkpoint();
+    while baseline.step_round().is_some() {
+        checkpoints.push(baseline.state().agents.clone());
+        if branch.is_none() {
+            if baseline.state().events.len() > trade_index {
+                branch = Some(std::mem::take(&mut before));
+            } else {
+                before = baseline.checkpoint();
+            }
+        }
+    }
+    let baseline = baseline.finish();
+    let (trade, branch) = match (baseline.events.get(trade_index), branch) {
+        (Some(trade), Some(branch)) => (trade.clone(), branch),
+        _ => return Err(RdxError::EventOutOfRange { index: trade_index, len: baseline.events.len() }),
+    };
+
+    let split = trade.round;
+    let mut variant = Engine::resume(branch)?;
+    variant.replay_encounters(log.iter().filter(|e| e.round >= split).copied().collect());
+    variant.edit_trade(trade_index, edit);
+    let mut rounds = Vec::with_capacity(checkpoints.len());
+    for (t, base_agents) in checkpoints.iter().enumerate() {
+        let trades_baseline = baseline.metrics.get(t).map(|m| m.trades).unwrap_or(0);
+        if t < split {
+            // shared history: the branches are the same run up to here
+            rounds.push(divergence(t, base_agents, base_agents, cfg.min_qty, trades_baseline, trades_baseline));
+            continue;
+        }
+        let trades_variant = variant.step_round().map(|m| m.trades).unwrap_or(0);
+        rounds.push(divergence(t, base_agents, &variant.state().agents, cfg.min_qty, trades_baseline, trades_variant));
+    }
+
+    Ok(ButterflyReport { trade_index, edit, trade, rounds, baseline, variant: variant.finish() })
+}
+
+fn divergence(
+    round: usize,
+    baseline: &[Agent],
+    variant: &[Agent],
+    min_qty: f64,
+    trades_baseline: usize,
+    trades_variant: usize,
+) -> RoundDivergence {
+    let mut d = RoundDivergence {
+        round, endowment_l1: 0.0, utility_l1: 0.0, welfare_delta: 0.0, agents_affected: 0,
+        trades_baseline, trades_variant,
+    };
+    for (a, b) in baseline.iter().zip(variant.iter()) {
+        if a.e == b.e { continue; }
+        d.agents_affected += 1;
+        d.endowment_l1 += a.e.iter().zip(b.e.iter()).map(|(x, y)| (y - x).abs()).sum::<f64>();
+        let du = cd_utility(&b.beta, &b.e, min_qty) - cd_utility(&a.beta, &a.e, min_qty);
+        d.utility_l1 += du.abs();
+        d.welfare_delta += du;
+    }
+    d
+}
+
+/// Realized welfare in one round under the configured and the baseline mechanism.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RoundAttribution {
+    pub round: usize,
+    /// Sum of utility gains of the round's trades under the engine's default oracle.
+    pub actual: f64,
+    /// The same with the baseline mechanism on identical encounters.
+    pub baseline: f64,
+}
+
+/// Decomposition of total realized welfare gains, `total = matching + mechanism`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GainAttribution {
+    pub total: f64,
+    /// Gains the baseline mechanism realizes on the same encounters: what who-met-whom alone
+    /// delivers.
+    pub matching: f64,
+    /// `total - matching`: what the configured mechanism adds on top.
+    pub mechanism: f64,
+    pub rounds: Vec<RoundAttribution>,
+}
+
+/// Run `cfg`, then replay its encounters with `baseline` as the exchange mechanism, and split
+/// the realized welfare gains (summed `delta_u` over all trades) into a matching and a
+/// mechanism component.
+///
+/// States diverge after the first encounter where the mechanisms differ, so later dyads meet
+/// in different bundles; the components are path-dependent by construction. Convergence
+/// stopping is disabled so both runs cover the same rounds.
+pub fn attribute_gains(cfg: &SimConfig, baseline: Box<dyn ParetoOracle>) -> Result<GainAttribution, RdxError> {
+    split_gains(cfg, cfg.clone(), |replay| replay.set_oracle(baseline))
+}
+
+/// `attribute_gains` with zero-intelligence traders as the baseline: the encounters of `cfg`
+/// are replayed with `SimConfig::zero_intelligence` set to `spec`, so `matching` is what random
+/// proposals realize on the same dyads and `mechanism` is what the oracle's structure adds.
+pub fn zero_intelligence_baseline(cfg: &SimConfig, spec: ZeroIntelligenceSpec) -> Result<GainAttribution, RdxError> {
+    let mut random = cfg.clone();
+    random.zero_intelligence = Some(spec);
+    split_gains(cfg, random, |_| {})
+}
+
+/// Run `cfg`, replay its encounters under `baseline` (after `setup`), and split the gains.
+fn split_gains(
+    cfg: &SimConfig,
+    mut baseline: SimConfig,
+    setup: impl FnOnce(&mut Engine),
+) -> Result<GainAttribution, RdxError> {
+    let mut cfg = cfg.clone();
+    cfg.stop_when_converged = None;
+    baseline.stop_when_converged = None;
+
+    let mut actual = Engine::new(cfg)?;
+    actual.record_encounters();
+    actual.run_to_end();
+    let log = actual.encounter_log().to_vec();
+    let actual = actual.finish();
+
+    let mut replay = Engine::new(baseline)?;
+    setup(&mut replay);
+    replay.replay_encounters(log);
+    replay.run_to_end();
+    let replay = replay.finish();
+
+    let rounds: Vec<RoundAttribution> = actual.metrics.iter().zip(replay.metrics.iter())
+        .map(|(a, b)| RoundAttribution { round: a.round, actual: a.delta_u, baseline: b.delta_u })
+        .collect();
+    let total: f64 = rounds.iter().map(|r| r.actual).sum();
+    let matching: f64 = rounds.iter().map(|r| r.baseline).sum();
+    Ok(GainAttribution { total, matching, mechanism: total - matching, rounds })
+}
+
+/// Welfare realized when agents negotiate from quotes versus trading on full information.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct InformationLoss {
+    /// Summed `delta_u` of the run under `SimConfig::negotiation`.
+    pub negotiated: f64,
+    /// The same encounters traded with the full-information oracle.
+    pub full_information: f64,
+    /// `1 - negotiated / full_information` (0 when full information realizes no gains).
+    pub efficiency_loss: f64,
+    pub trades_negotiated: usize,
+    pub trades_full_information: usize,
+}
+
+/// Run `cfg` (which must set `negotiation`), then replay its encounters with `negotiation`
+/// removed, and compare the realized welfare gains. Like `attribute_gains`, the runs diverge
+/// after the first differing trade, and convergence stopping is disabled.
+pub fn information_loss(cfg: &SimConfig) -> Result<InformationLoss, RdxError> {
+    if cfg.negotiation.is_none() {
+        return Err(RdxError::InvalidConfig("information_loss needs `negotiation`".into()));
+    }
+    let mut cfg = cfg.clone();
+    cfg.stop_when_converged = None;
+
+    let mut negotiated = Engine::new(cfg.clone())?;
+    negotiated.record_encounters();
+    negotiated.run_to_end();
+    let log = negotiated.encounter_log().to_vec();
+    let negotiated = negotiated.finish();
+
+    cfg.negotiation = None;
+    let mut full = Engine::new(cfg)?;
+    full.replay_encounters(log);
+    full.run_to_end();
+    let full = full.finish();
+
+    let gains = |s: &SimState| s.metrics.iter().map(|m| m.delta_u).sum::<f64>();
+    let trades = |s: &SimState| s.metrics.iter().map(|m| m.trades).sum::<usize>();
+    let (with_quotes, with_info) = (gains(&negotiated), gains(&full));
+    Ok(InformationLoss {
+        negotiated: with_quotes,
+        full_information: with_info,
+        efficiency_loss: if with_info > 0.0 { 1.0 - with_quotes / with_info } else { 0.0 },
+        trades_negotiated: trades(&negotiated),
+        trades_full_information: trades(&full),
+    })
+}
@@ -0,0 +1,158 @@
+//! Exogenous flows between rounds: consumption, depreciation and replenishment, plus scheduled
+//! supply shocks and the introduction of new goods.
+//!
+//! Trading only moves goods between agents; these phases add and remove them, turning the
+//! exchange economy into a flow economy. The engine applies them after each round's
+//! encounters, in that order (goods spoil before fresh supply arrives), before the round's
+//! metrics are taken. Shocks to supply and to preferences hit at the start of their round, before
+//! any encounter.
+//!
+//! Preferences can move too: under `SimConfig::social_influence` agents imitate the preferences
+//! of partners that did better out of their trades (`imitate`), right after the encounters.
+use rand::prelude::*;
+use crate::endowment;
+use crate::error::RdxError;
+use crate::preferences::beta_from_alpha_to_base;
+use crate::model::{Agent, EndowmentSpec, GoodIntroduction, PreferenceShockSpec, ShockSpec, TradeEvent};
+
+/// Consume part of `agent`'s bundle: good `k` shrinks by the fraction `rate · n · β_k` (clamped
+/// to [0, 1]), so on average a fraction `rate` of every good is used up, faster for goods the
+/// agent values more. Returns the total quantity consumed.
+pub fn consume(agent: &mut Agent, rate: f64) -> f64 {
+    let n = agent.e.len() as f64;
+    let mut total = 0.0;
+    for (x, &b) in agent.e.iter_mut().zip(agent.beta.iter()) {
+        let used = *x * (rate * n * b).clamp(0.0, 1.0);
+        *x -= used;
+        total += used;
+    }
+    agent.invalidate_utility();
+    total
+}
+
+/// Add `income` times a fresh draw from `spec` (times `scale`) to `agent`'s bundle. Returns the
+/// total quantity added.
+pub fn replenish<R: Rng>(agent: &mut Agent, spec: &EndowmentSpec, income: f64, scale: f64, rng: &mut R) -> f64 {
+    let fresh = endowment::draw_bundle(spec, agent.e.len(), rng);
+    let mut total = 0.0;
+    for (x, f) in agent.e.iter_mut().zip(fresh) {
+        let add = income * scale * f;
+        *x += add;
+        total += add;
+    }
+    agent.invalidate_utility();
+    total
+}
+
+/// Depreciate `agent`'s bundle: good `k` loses the fraction `rates[k]` (goods without a rate are
+/// durable). Returns the total quantity lost.
+pub fn decay(agent: &mut Agent, rates: &[f64]) -> f64 {
+    let mut total = 0.0;
+    for (x, &d) in agent.e.iter_mut().zip(rates.iter()) {
+        let lost = *x * d.clamp(0.0, 1.0);
+        *x -= lost;
+        total += lost;
+    }
+    agent.invalidate_utility();
+    total
+}
+
+/// Apply `shock` to a random `agent_fraction` of `agents`. Returns how many agents were hit.
+pub fn shock<R: Rng>(agents: &mut [Agent], shock: &ShockSpec, rng: &mut R) -> usize {
+    let hit = ((shock.agent_fraction.clamp(0.0, 1.0) * agents.len() as f64).round() as usize).min(agents.len());
+    let variance = shock.mean * shock.mean * ((shock.sigma * shock.sigma).exp() - 1.0);
+    for k in rand::seq::index::sample(rng, agents.len(), hit).into_vec() {
+        let a = &mut agents[k];
+        for (g, x) in a.e.iter_mut().enumerate() {
+            if shock.goods.is_empty() || shock.goods.iter().any(|s| s.index() == g) {
+                *x *= endowment::lognormal(rng, shock.mean, variance);
+            }
+        }
+        a.invalidate_utility();
+    }
+    hit
+}
+
+/// Apply preference `shock` to a random `agent_fraction` of `agents`, re-deriving their `beta`
+/// against `base_good`. Returns how many agents were hit.
+pub fn preference_shock<R: Rng>(
+    agents: &mut [Agent],
+    shock: &PreferenceShockSpec,
+    base_good: usize,
+    rng: &mut R,
+) -> Result<usize, RdxError> {
+    let hit = ((shock.agent_fraction.clamp(0.0, 1.0) * agents.len() as f64).round() as usize).min(agents.len());
+    for k in rand::seq::index::sample(rng, agents.len(), hit).into_vec() {
+        let a = &mut agents[k];
+        for g in shock.goods.iter().map(|g| g.index()) {
+            if let Some(alpha) = a.alpha_to_base.get_mut(g) {
+                *alpha = (*alpha + shock.shift).clamp(1e-6, 1.0 - 1e-6);
+            }
+        }
+        a.beta = beta_from_alpha_to_base(&a.alpha_to_base, base_good, 1e-6)?;
+        a.invalidate_utility();
+    }
+    Ok(hit)
+}
+
+/// Append the good described by `intro` to `agent`: with probability `intro.holders` it gets an
+/// endowment drawn from `intro.endowment` (times `scale`), and its `alpha_to_base` for the good
+/// is drawn from `[alpha_low, alpha_high)`. `beta` is rebuilt, so the new good takes its share
+/// of the agent's preferences. Returns the quantity endowed.
+pub fn introduce_good<R: Rng>(
+    agent: &mut Agent,
+    intro: &GoodIntroduction,
+    alpha_range: (f64, f64),
+    base_good: usize,
+    scale: f64,
+    rng: &mut R,
+) -> Result<f64, RdxError> {
+    let x = if rng.gen::<f64>() < intro.holders {
+        endowment::draw_bundle(&intro.endowment, 1, rng).first().copied().unwrap_or(0.0) * scale
+    } else {
+        0.0
+    };
+    agent.e.push(x);
+    agent.alpha_to_base.push(rng.gen_range(alpha_range.0..alpha_range.1));
+    agent.beta = beta_from_alpha_to_base(&agent.alpha_to_base, base_good, 1e-6)?;
+    agent.invalidate_utility();
+    Ok(x)
+}
+
+/// Imitation dynamics over a round's `events`: an agent that gained less from a trade than its
+/// partner moves its `alpha_to_base` towards the partner's, `alpha += rate · (target - alpha)`,
+/// where `target` is the mean over all such partners of the round. Targets are taken from the
+/// preferences before any agent moves, and `beta` is re-derived. Brokers neither imitate nor
+/// are imitated. Returns how many agents changed their preferences.
+pub fn imitate(agents: &mut [Agent], events: &[TradeEvent], rate: f64, base_good: usize) -> Result<usize, RdxError> {
+    let mut targets: Vec<(Vec<f64>, usize)> = vec![(Vec::new(), 0); agents.len()];
+    for ev in events.iter() {
+        let (i, j) = (ev.i.index(), ev.j.index());
+        if i >= agents.len() || j >= agents.len() || agents[i].broker || agents[j].broker { continue; }
+        let (learner, model) = if ev.delta_u_i < ev.delta_u_j {
+            (i, j)
+        } else if ev.delta_u_j < ev.delta_u_i {
+            (j, i)
+        } else {
+            continue;
+        };
+        if agents[model].alpha_to_base.len() != agents[learner].alpha_to_base.len() { continue; }
+        let (sum, count) = &mut targets[learner];
+        sum.resize(agents[model].alpha_to_base.len(), 0.0);
+        for (s, &a) in sum.iter_mut().zip(agents[model].alpha_to_base.iter()) {
+            *s += a;
+        }
+        *count += 1;
+    }
+    let mut changed = 0;
+    for (a, (sum, count)) in agents.iter_mut().zip(targets) {
+        if count == 0 { continue; }
+        for (alpha, s) in a.alpha_to_base.iter_mut().zip(sum) {
+            *alpha += rate * (s / count as f64 - *alpha);
+        }
+        a.beta = beta_from_alpha_to_base(&a.alpha_to_base, base_good, 1e-6)?;
+        a.invalidate_utility();
+        changed += 1;
+    }
+    Ok(changed)
+}
@@ -0,0 +1,80 @@
+//! Initial endowment distributions for `init_agents` (`SimConfig::endowment`).
+//!
+//! Samplers are written against `rand::Rng` directly (inverse CDF, Box–Muller) so the crate
+//! does not need `rand_distr`.
+use rand::prelude::*;
+use std::f64::consts::TAU;
+use crate::error::RdxError;
+use crate::model::EndowmentSpec;
+
+/// Reject specs that cannot produce positive, finite holdings over `n_goods` goods.
+pub fn validate(spec: &EndowmentSpec, n_goods: usize) -> Result<(), RdxError> {
+    let ok = match spec {
+        EndowmentSpec::Uniform { low, high } => *low >= 0.0 && low < high && high.is_finite(),
+        EndowmentSpec::Lognormal { mean, variance } => lognormal_ok(*mean, *variance),
+        EndowmentSpec::Pareto { shape, min } => *shape > 0.0 && *min > 0.0 && min.is_finite(),
+        EndowmentSpec::PerGood { means, variances } => {
+            if means.len() != n_goods || variances.len() != n_goods {
+                return Err(RdxError::DimensionMismatch {
+                    expected: n_goods,
+                    found: if means.len() != n_goods { means.len() } else { variances.len() },
+                });
+            }
+            means.iter().zip(variances.iter()).all(|(&m, &v)| lognormal_ok(m, v))
+        }
+        EndowmentSpec::Specialist { goods, high, low } => {
+            (1..=n_goods).contains(goods) && *low >= 0.0 && low <= high && high.is_finite()
+        }
+    };
+    if !ok {
+        return Err(RdxError::InvalidConfig(format!("invalid endowment distribution: {spec:?}")));
+    }
+    Ok(())
+}
+
+fn lognormal_ok(mean: f64, variance: f64) -> bool {
+    mean > 0.0 && mean.is_finite() && variance >= 0.0 && variance.is_finite()
+}
+
+/// One agent's endowment over `n_goods` goods (before `initial_endowment_scale`).
+pub fn draw_bundle<R: Rng>(spec: &EndowmentSpec, n_goods: usize, rng: &mut R) -> Vec<f64> {
+    match spec {
+        EndowmentSpec::Uniform { low, high } => (0..n_goods).map(|_| rng.gen_range(*low..*high)).collect(),
+        EndowmentSpec::Lognormal { mean, variance } => {
+            (0..n_goods).map(|_| lognormal(rng, *mean, *variance)).collect()
+        }
+        EndowmentSpec::Pareto { shape, min } => (0..n_goods).map(|_| pareto(rng, *shape, *min)).collect(),
+        EndowmentSpec::PerGood { means, variances } => {
+            means.iter().zip(variances.iter()).map(|(&m, &v)| lognormal(rng, m, v)).collect()
+        }
+        EndowmentSpec::Specialist { goods, high, low } => {
+            let mut e = vec![*low; n_goods];
+            for k in rand::seq::index::sample(rng, n_goods, (*goods).min(n_goods)).iter() {
+                e[k] = *high;
+            }
+            e
+        }
+    }
+}
+
+/// Standard normal via Box–Muller.
+pub fn standard_normal<R: Rng>(rng: &mut R) -> f64 {
+    // 1 - U lies in (0, 1], keeping ln finite
+    let u1 = 1.0 - rng.gen::<f64>();
+    let u2 = rng.gen::<f64>();
+    (-2.0 * u1.ln()).sqrt() * (TAU * u2).cos()
+}
+
+/// Lognormal with the given mean and variance of the variable itself (not of its log):
+/// `σ² = ln(1 + v / m²)`, `μ = ln m - σ² / 2`.
+pub fn lognormal<R: Rng>(rng: &mut R, mean: f64, variance: f64) -> f64 {
+    let s2 = (1.0 + variance / (mean * mean)).ln();
+    let mu = mean.ln() - s2 / 2.0;
+    (mu + s2.sqrt() * standard_normal(rng)).exp()
+}
+
+/// Pareto(`shape`, `min`) by inversion: `min / U^(1/shape)`.
+pub fn pareto<R: Rng>(rng: &mut R, shape: f64, min: f64) -> f64 {
+    let u = 1.0 - rng.gen::<f64>();
+    min / u.powf(1.0 / shape)
+}
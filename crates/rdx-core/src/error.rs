@@ -0,0 +1,66 @@
+//! Crate-wide error type.
+//!
+//! Public APIs in `rdx-core` do not panic on user-supplied configs or agents; inputs that cannot
+//! be simulated are reported as an `RdxError` instead.
+
+use alloc::string::String;
+use thiserror::Error;
+use crate::codec::CodecError;
+use crate::protocol::ProtocolError;
+
+/// Short name for library consumers: `rdx_core::error::Error`.
+pub type Error = RdxError;
+
+#[derive(Debug, Error)]
+pub enum RdxError {
+    #[error("invalid config: {0}")]
+    InvalidConfig(String),
+
+    #[error("good index {index} out of range for {len} goods")]
+    GoodOutOfRange { index: usize, len: usize },
+
+    #[error("dimension mismatch: expected {expected} entries, found {found}")]
+    DimensionMismatch { expected: usize, found: usize },
+
+    #[error("trade event {index} out of range for {len} events")]
+    EventOutOfRange { index: usize, len: usize },
+
+    #[error("population too small: need at least 2 agents, found {0}")]
+    PopulationTooSmall(usize),
+
+    #[error("no equilibrium price in [{lo}, {hi}]: excess demand does not change sign")]
+    UnbracketedPrice { lo: f64, hi: f64 },
+
+    #[error("degenerate preferences: {0}")]
+    DegeneratePreferences(String),
+
+    #[error("pairwise comparisons do not connect all {0} goods")]
+    DisconnectedComparisons(usize),
+
+    #[error("checkpoint: {0}")]
+    Checkpoint(String),
+
+    #[error("checkpoint format version {found} is not supported (expected {expected})")]
+    CheckpointVersion { found: u32, expected: u32 },
+
+    #[error("replay: {0}")]
+    Replay(String),
+
+    #[cfg(feature = "std")]
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+
+    #[error(transparent)]
+    Codec(#[from] CodecError),
+
+    #[error(transparent)]
+    Protocol(#[from] ProtocolError),
+
+    #[cfg(feature = "std")]
+    #[error(transparent)]
+    Log(#[from] crate::log::LogError),
+
+    #[cfg(feature = "arrow")]
+    #[error(transparent)]
+    Arrow(#[from] arrow_schema::ArrowError),
+}
@@ -0,0 +1,50 @@
+//! Typed indices for goods and agents.
+//!
+//! Trade records carry both kinds of index side by side (`i`, `j`, `good_a`, `good_b`), which
+//! makes swapping them an easy, silent bug. Record types (`TradeEvent`, `TradeCandidate`,
+//! `Encounter`, rate and graph summaries, embargoes) therefore use these newtypes. Both are
+//! `#[serde(transparent)]`, so JSON and CSV keep plain integers.
+//!
+//! Numeric kernels that take positional arguments (`evaluate_pairwise_trade`, the oracle, ...)
+//! keep `usize`; convert with `.index()` or `GoodId::from`.
+use core::fmt;
+use serde::{Serialize, Deserialize};
+#[cfg(feature = "std")]
+use schemars::JsonSchema;
+
+/// Index into a goods vector (`Agent::e`, `Agent::beta`, `SimConfig::base_goods`).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "std", derive(JsonSchema))]
+#[serde(transparent)]
+pub struct GoodId(pub usize);
+
+/// Index into the population (`SimState::agents`).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "std", derive(JsonSchema))]
+#[serde(transparent)]
+pub struct AgentIdx(pub usize);
+
+macro_rules! index_newtype {
+    ($t:ident) => {
+        impl $t {
+            pub fn index(self) -> usize { self.0 }
+        }
+
+        impl From<usize> for $t {
+            fn from(i: usize) -> Self { $t(i) }
+        }
+
+        impl From<$t> for usize {
+            fn from(id: $t) -> usize { id.0 }
+        }
+
+        impl fmt::Display for $t {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                fmt::Display::fmt(&self.0, f)
+            }
+        }
+    };
+}
+
+index_newtype!(GoodId);
+index_newtype!(AgentIdx);
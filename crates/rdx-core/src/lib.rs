@@ -2,17 +2,128 @@
 //!
 //! Key modules:
 //! - goods: service taxonomy as goods
-//! - preferences: aggregated Cobb–Douglas profile + alpha-to-base
+//! - math: numeric helpers, a dense linear solver and streaming stats (weighted moments, P²
+//!   quantiles, EMA, histograms); portable `ln` / `exp` / `powf` with the `deterministic` feature;
+//!   the `Scalar` number trait (`f64`, `f32`, downstream types) of the exchange primitives
+//! - ids: `GoodId` / `AgentIdx` newtypes used by trade records
+//! - preferences: aggregated Cobb–Douglas profile + alpha-to-base, elicited pairwise alphas,
+//!   population generators (Dirichlet, taste clusters, endowment copula)
 //! - pareto_oracle: dyadic Pareto-optimal exchange oracle for (A,B)
 //! - trade: P2P evaluation across all goods vs base
-//! - sim: simulation loop and metrics
-//! - codec: (optional) encoding/decoding boundary for preference payloads
+//! - market: posted-price marketplace phase (take-it-or-leave-it listings, greedy buyers)
+//! - broker: intermediary agents quoting bid/ask spreads, and welfare with vs without them
+//! - dynamics: between-round flows (consumption, depreciation, replenishment), shocks and
+//!   preference imitation
+//! - endowment: initial holdings (uniform, lognormal, Pareto, per-good, specialist)
+//! - matching: encounter sampling (uniform, wealth-quantile mixing, network edges)
+//! - network: seeded Erdős–Rényi / Watts–Strogatz / Barabási–Albert graphs
+//! - sim: simulation loop and metrics, event-log replay (`sim::replay`)
+//! - log: validation of event streams (sequence numbers, per-event checks, holdings hash chains)
+//! - prices: per-good EWMA price index and Walrasian benchmark prices
+//! - money: monetary mode with credit lines, money velocity and credit utilization
+//! - policy: trade tax, treasury and redistribution schemes
+//! - metrics: Gini / Theil inequality, wealth at prices, utility quantiles, Pareto gap
+//! - trade_graph: directed goods-flow multigraph with DOT / GraphML export
+//! - trajectory: per-round holdings and utility of all, sampled or listed agents (an `Observer`)
+//! - counterfactual: replayed-encounter analyses (butterfly, matching vs mechanism gains)
+//! - scenarios: named, validated preset configs
+//! - replication: Monte Carlo replicates over seeds, per-round means with bootstrap intervals
+//! - overlay: layered configs (override documents and `path=value` assignments over a base)
+//! - snapshot: quantized `AgentSnapshot`s and `PopulationSummary` payloads for gossip
+//! - state: SHA-256 Merkle commitments to the allocation (`merkle_root`) and per-agent
+//!   inclusion proofs
+//! - codec: encoding/decoding boundary for preference payloads; JSON, bincode, CBOR and
+//!   MessagePack behind a format byte (`encode_as` / `decode_tagged`)
+//! - protocol: negotiation messages (`ProposeTrade` ... `Commit`) and the `Proposer` /
+//!   `Responder` state machines for one dyadic exchange between peers
+//! - builder: fluent `SimConfig::builder()` / `Agent::builder()` with validation at `build()`
+//! - arrow: Arrow `RecordBatch`es of the event log and metrics (`arrow` feature)
+//! - checkpoint: versioned binary `SimState::save` / `load` and exact `Engine::resume`
+//! - tracing (feature): spans per run, round and dyad and events for round totals, candidate
+//!   searches and oracle solutions, for any `tracing` subscriber
+//! - error: crate-wide `RdxError` (alias `Error`); public APIs return it instead of panicking on
+//!   bad input
+//!
+//! With default features off the crate is `no_std` + `alloc` and builds only the dyadic exchange
+//! primitives: `math`, `preferences` (profiles, utilities, elicited alphas), `pareto_oracle`,
+//! `trade` (`evaluate_exchange` and the helpers it returns), `codec` and `protocol`, for peers on embedded or
+//! WASM targets that evaluate trades locally. Float functions then come from `libm`.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
 
+/// `tracing::event!` with the `tracing` feature; expands to nothing without it.
+macro_rules! trace_event {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "tracing")]
+        tracing::event!($($arg)*);
+    };
+}
+
+#[cfg(not(any(feature = "std", feature = "libm")))]
+compile_error!("rdx-core without `std` needs the `libm` (or `deterministic`) feature");
+
+#[cfg(feature = "arrow")]
+pub mod arrow;
+#[cfg(feature = "std")]
+pub mod broker;
+#[cfg(feature = "std")]
+pub mod builder;
+#[cfg(feature = "std")]
+pub mod checkpoint;
 pub mod codec;
+#[cfg(feature = "std")]
+pub mod counterfactual;
+#[cfg(feature = "std")]
+pub mod dynamics;
+#[cfg(feature = "std")]
+pub mod endowment;
+pub mod error;
+pub mod ids;
+#[cfg(feature = "std")]
+pub mod log;
+#[cfg(feature = "std")]
+pub mod market;
 pub mod math;
+#[cfg(feature = "std")]
+pub mod matching;
+#[cfg(feature = "std")]
+pub mod metrics;
+#[cfg(feature = "std")]
 pub mod model;
+#[cfg(feature = "std")]
+pub mod money;
+#[cfg(feature = "std")]
+pub mod network;
+#[cfg(feature = "std")]
+pub mod overlay;
 pub mod pareto_oracle;
+#[cfg(feature = "std")]
+pub mod policy;
 pub mod preferences;
+#[cfg(feature = "std")]
+pub mod prices;
+pub mod protocol;
+#[cfg(feature = "std")]
+pub mod replication;
+#[cfg(feature = "std")]
+pub mod trade;
+/// Without `std`, the dyadic exchange primitives of `trade` only.
+#[cfg(not(feature = "std"))]
+#[path = "trade/dyad.rs"]
 pub mod trade;
+#[cfg(feature = "std")]
+pub mod trade_graph;
+#[cfg(feature = "std")]
+pub mod trajectory;
+#[cfg(feature = "std")]
+pub mod scenarios;
+#[cfg(feature = "std")]
 pub mod sim;
-pub mod reaction;
\ No newline at end of file
+#[cfg(feature = "std")]
+pub mod snapshot;
+#[cfg(feature = "std")]
+pub mod state;
+#[cfg(feature = "std")]
+pub mod reaction;
@@ -0,0 +1,125 @@
+//! The event log as an append-only, tamper-evident stream.
+//!
+//! Every `TradeEvent` carries its position in the run's log (`seq`) and `state::leaf_hash`es of
+//! both agents' holdings just before and just after it. `validate` checks a stream, or any
+//! contiguous slice of one, without the run: sequence numbers have no gaps, rounds never go
+//! back, each event is a well-formed exchange, and each agent's holdings chain from one event to
+//! its next (an event's pre-trade hash is the previous event's post-trade hash). A dropped,
+//! reordered or edited event breaks one of these.
+//!
+//! Holdings also change between trades: consumption, decay, credit, redistribution and
+//! demography act between rounds, so by default chains restart every round (`Chaining::Round`).
+//! Runs without those can be checked across rounds with `Chaining::Run`. Cycles under
+//! `SimConfig::triads` move holdings within a round without a trade event, so their logs only
+//! pass with chaining off.
+
+use std::collections::BTreeMap;
+use serde::{Serialize, Deserialize};
+use thiserror::Error;
+use crate::model::TradeEvent;
+use crate::state::Hash;
+
+/// Over what span each agent's holdings must chain from event to event.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Chaining {
+    /// No chain checks.
+    Off,
+    /// Within each round.
+    #[default]
+    Round,
+    /// Across the whole stream.
+    Run,
+}
+
+#[derive(Clone, Debug, PartialEq, Error)]
+pub enum LogError {
+    #[error("event at position {position} has seq {found}, expected {expected}")]
+    SequenceGap { position: usize, expected: u64, found: u64 },
+
+    #[error("event {seq} is in round {round}, after an event of round {previous}")]
+    RoundOrder { seq: u64, round: usize, previous: usize },
+
+    #[error("event {seq} is not a well-formed exchange: {reason}")]
+    Malformed { seq: u64, reason: &'static str },
+
+    #[error("event {seq}: holdings of agent {agent} do not follow from event {previous}")]
+    ChainBreak { seq: u64, agent: u64, previous: u64 },
+}
+
+/// What a valid stream covers.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct LogSummary {
+    pub events: usize,
+    /// `seq` of the first and last event, if any.
+    pub first_seq: Option<u64>,
+    pub last_seq: Option<u64>,
+    /// Distinct agents involved.
+    pub agents: usize,
+    /// `(agent id, post-trade hash)` of each agent's last event, for continuing the chain in
+    /// the next slice.
+    pub heads: Vec<(u64, Hash)>,
+}
+
+/// Check one event on its own: distinct agents and goods, finite quantities, one good given for
+/// the other (`delta_a_i` and `delta_b_i` not both positive or both negative, j's side being
+/// their mirror) and non-negative costs and tax.
+pub fn check_event(ev: &TradeEvent) -> Result<(), LogError> {
+    let malformed = |reason| Err(LogError::Malformed { seq: ev.seq, reason });
+    if ev.i == ev.j || ev.id_i == ev.id_j {
+        return malformed("an agent trades with itself");
+    }
+    if ev.good_a == ev.good_b {
+        return malformed("both legs are the same good");
+    }
+    let amounts = [ev.q_ab, ev.delta_a_i, ev.delta_b_i, ev.delta_u_i, ev.delta_u_j, ev.cost_i, ev.cost_j, ev.tax];
+    if amounts.iter().any(|x| !x.is_finite()) {
+        return malformed("non-finite amount");
+    }
+    if ev.delta_a_i * ev.delta_b_i > 0.0 {
+        return malformed("one side receives both goods");
+    }
+    if ev.cost_i < 0.0 || ev.cost_j < 0.0 || ev.tax < 0.0 {
+        return malformed("negative cost or tax");
+    }
+    Ok(())
+}
+
+/// Validate `events` as a contiguous stretch of a run's log (see the module docs).
+pub fn validate(events: &[TradeEvent], chaining: Chaining) -> Result<LogSummary, LogError> {
+    // per agent: (round, seq, post-trade hash) of its latest event
+    let mut heads: BTreeMap<u64, (usize, u64, Hash)> = BTreeMap::new();
+    let mut previous: Option<&TradeEvent> = None;
+    for (position, ev) in events.iter().enumerate() {
+        if let Some(prev) = previous {
+            let expected = prev.seq + 1;
+            if ev.seq != expected {
+                return Err(LogError::SequenceGap { position, expected, found: ev.seq });
+            }
+            if ev.round < prev.round {
+                return Err(LogError::RoundOrder { seq: ev.seq, round: ev.round, previous: prev.round });
+            }
+        }
+        check_event(ev)?;
+        for (agent, pre, post) in [(ev.id_i, ev.pre_i, ev.post_i), (ev.id_j, ev.pre_j, ev.post_j)] {
+            if let Some(&(round, seq, head)) = heads.get(&agent) {
+                let chained = match chaining {
+                    Chaining::Off => false,
+                    Chaining::Round => round == ev.round,
+                    Chaining::Run => true,
+                };
+                if chained && head != pre {
+                    return Err(LogError::ChainBreak { seq: ev.seq, agent, previous: seq });
+                }
+            }
+            heads.insert(agent, (ev.round, ev.seq, post));
+        }
+        previous = Some(ev);
+    }
+    Ok(LogSummary {
+        events: events.len(),
+        first_seq: events.first().map(|e| e.seq),
+        last_seq: events.last().map(|e| e.seq),
+        agents: heads.len(),
+        heads: heads.into_iter().map(|(agent, (_, _, hash))| (agent, hash)).collect(),
+    })
+}
@@ -0,0 +1,172 @@
+//! Posted-price marketplace (`SimConfig::posted_prices`): an optional phase after a round's
+//! encounters in which sellers post take-it-or-leave-it prices and buyers shop greedily.
+//!
+//! Every agent lists the goods it holds above the population mean, at its own marginal rate of
+//! substitution against the base good plus a markup. Buyers then take turns; each keeps buying
+//! from the listing that raises its utility most, up to its optimum at the posted price, until
+//! no listing helps. Sellers never sell past their own optimum at the posted price, so like
+//! bilateral search the phase never lowers anyone's utility. There is no bargaining and no price
+//! discovery beyond the posted prices themselves.
+
+use crate::ids::GoodId;
+use crate::model::Agent;
+use crate::preferences::{cd_utility, demand_at_price};
+use crate::state::{leaf_hash, Hash};
+use crate::trade::{mrs_to_base, TradeRules};
+
+/// A seller's standing offer of `quantity` of `good` at `price` base-good units per unit.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Listing {
+    pub seller: usize,
+    pub good: GoodId,
+    pub price: f64,
+    /// Units still for sale.
+    pub quantity: f64,
+}
+
+/// One purchase from a listing.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Sale {
+    pub seller: usize,
+    pub buyer: usize,
+    pub good: GoodId,
+    pub price: f64,
+    pub quantity: f64,
+    pub delta_u_seller: f64,
+    pub delta_u_buyer: f64,
+    /// `state::leaf_hash` of the seller's and the buyer's holdings before and after the sale.
+    pub pre_seller: Hash,
+    pub pre_buyer: Hash,
+    pub post_seller: Hash,
+    pub post_buyer: Hash,
+}
+
+/// Per-agent trade budget for the round (`SimConfig::max_trades_per_agent`), shared with the
+/// round's encounters.
+#[derive(Debug)]
+pub struct Budget<'a> {
+    /// Trades allowed per agent; `None` leaves trading unlimited.
+    pub limit: Option<usize>,
+    /// Trades made so far this round, by agent. Agents past the end have made none.
+    pub spent: &'a mut [usize],
+    /// Sales refused because a side had used up its budget.
+    pub refused: usize,
+}
+
+impl Budget<'_> {
+    fn allows(&self, m: usize) -> bool {
+        self.limit.is_none_or(|limit| self.spent.get(m).copied().unwrap_or(0) < limit)
+    }
+
+    fn spend(&mut self, m: usize) {
+        if let Some(n) = self.spent.get_mut(m) {
+            *n += 1;
+        }
+    }
+}
+
+/// Listings for the round: for every agent and allowed non-base good held above the population
+/// mean, `lot` of the excess at the agent's MRS versus the base good times `1 + markup`.
+pub fn post_listings(agents: &[Agent], base_good: usize, markup: f64, lot: f64, min_qty: f64, rules: &TradeRules) -> Vec<Listing> {
+    let Some(n) = agents.first().map(|a| a.e.len()) else { return Vec::new() };
+    let mut mean = vec![0.0; n];
+    for a in agents.iter() {
+        for (m, x) in mean.iter_mut().zip(a.e.iter()) {
+            *m += x / agents.len() as f64;
+        }
+    }
+    let mut listings = Vec::new();
+    for (s, a) in agents.iter().enumerate() {
+        for (g, &m) in mean.iter().enumerate() {
+            if g == base_good || !rules.allows(g) { continue; }
+            let excess = a.e.get(g).copied().unwrap_or(0.0) - m;
+            let price = mrs_to_base(&a.beta, &a.e, g, base_good, min_qty) * (1.0 + markup);
+            if excess > 0.0 && price.is_finite() && price > 0.0 {
+                listings.push(Listing { seller: s, good: GoodId(g), price, quantity: lot * excess });
+            }
+        }
+    }
+    listings
+}
+
+/// Let `buyer` shop: repeatedly take the listing with the largest utility gain for the buyer,
+/// as much as it wants at the posted price, bounded by the listing, its base-good holdings and
+/// what the seller itself would sell at that price, provided the seller gains. Executes the
+/// sales on `agents` and returns them in order.
+///
+/// Every sale counts against both sides' `budget`. A buyer out of budget stops shopping; a
+/// seller out of budget has its listing passed over. Either way the sale is counted as refused.
+pub fn shop(
+    agents: &mut [Agent],
+    listings: &mut [Listing],
+    buyer: usize,
+    base_good: usize,
+    min_qty: f64,
+    budget: &mut Budget<'_>,
+) -> Vec<Sale> {
+    let mut sales = Vec::new();
+    let mut passed_over = vec![false; listings.len()];
+    for _ in 0..listings.len() {
+        let mut best: Option<(usize, Sale)> = None;
+        for (k, l) in listings.iter().enumerate() {
+            if l.seller == buyer || l.quantity <= 0.0 || passed_over[k] { continue; }
+            let (b, s) = (&agents[buyer], &agents[l.seller]);
+            let g = l.good.index();
+            let cash = (b.e[base_good] - min_qty).max(0.0);
+            // neither side trades past its own optimum at the posted price
+            let q = demand_at_price(&b.beta, &b.e, g, base_good, l.price, min_qty)
+                .min(-demand_at_price(&s.beta, &s.e, g, base_good, l.price, min_qty))
+                .min(l.quantity)
+                .min(cash / l.price)
+                .min(s.e[g] - min_qty);
+            if q <= min_qty { continue; }
+            let delta_u_buyer = utility_change(b, g, q, base_good, -l.price * q, min_qty);
+            let delta_u_seller = utility_change(s, g, -q, base_good, l.price * q, min_qty);
+            let better = match &best {
+                Some((_, sale)) => delta_u_buyer > sale.delta_u_buyer,
+                None => true,
+            };
+            if delta_u_buyer > 0.0 && delta_u_seller > 0.0 && better {
+                let sale = Sale {
+                    seller: l.seller, buyer, good: l.good, price: l.price, quantity: q, delta_u_seller, delta_u_buyer,
+                    pre_seller: Hash::default(), pre_buyer: Hash::default(),
+                    post_seller: Hash::default(), post_buyer: Hash::default(),
+                };
+                best = Some((k, sale));
+            }
+        }
+        let Some((k, mut sale)) = best else { break };
+        if !budget.allows(buyer) {
+            budget.refused += 1;
+            break;
+        }
+        if !budget.allows(sale.seller) {
+            budget.refused += 1;
+            passed_over[k] = true;
+            continue;
+        }
+        budget.spend(buyer);
+        budget.spend(sale.seller);
+        listings[k].quantity -= sale.quantity;
+        let g = sale.good.index();
+        let hash = |a: &Agent| leaf_hash(a.id, &a.e);
+        (sale.pre_seller, sale.pre_buyer) = (hash(&agents[sale.seller]), hash(&agents[buyer]));
+        let (q, paid) = (sale.quantity, sale.price * sale.quantity);
+        for (k, dg, dc) in [(buyer, q, -paid), (sale.seller, -q, paid)] {
+            let (x, cash) = (agents[k].e[g], agents[k].e[base_good]);
+            agents[k].set_holding(g, x + dg, min_qty);
+            agents[k].set_holding(base_good, cash + dc, min_qty);
+        }
+        (sale.post_seller, sale.post_buyer) = (hash(&agents[sale.seller]), hash(&agents[buyer]));
+        sales.push(sale);
+    }
+    sales
+}
+
+/// Utility change of `a` receiving `dg` of good `g` and `db` of the base good.
+fn utility_change(a: &Agent, g: usize, dg: f64, base_good: usize, db: f64, min_qty: f64) -> f64 {
+    let mut x = a.e.clone();
+    x[g] += dg;
+    x[base_good] += db;
+    cd_utility(&a.beta, &x, min_qty) - cd_utility(&a.beta, &a.e, min_qty)
+}
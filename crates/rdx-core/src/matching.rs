@@ -0,0 +1,303 @@
+//! Agent matching: who meets whom in a P2P encounter.
+//!
+//! `PairingMode` (in `model`) decides which *good pairs* a dyad evaluates; this module decides
+//! which *agents* form the dyad in the first place.
+use rand::prelude::*;
+use std::sync::Arc;
+use serde::{Serialize, Deserialize};
+use crate::model::{Agent, MatchingMode};
+use crate::network::Graph;
+
+/// Wealth proxy used for quantile bucketing: total holdings across all goods.
+pub fn bundle_wealth(agent: &Agent) -> f64 {
+    agent.e.iter().sum()
+}
+
+/// Assign each agent to one of `q` wealth quantiles (0 = poorest, q-1 = richest).
+pub fn wealth_quantiles(agents: &[Agent], q: usize) -> Vec<usize> {
+    let n = agents.len();
+    let q = q.max(1);
+    let wealth: Vec<f64> = agents.iter().map(bundle_wealth).collect();
+
+    let mut order: Vec<usize> = (0..n).collect();
+    order.sort_by(|&a, &b| wealth[a].partial_cmp(&wealth[b]).unwrap_or(std::cmp::Ordering::Equal));
+
+    let mut bucket = vec![0; n];
+    for (rank, &idx) in order.iter().enumerate() {
+        bucket[idx] = (rank * q / n).min(q - 1);
+    }
+    bucket
+}
+
+/// Draw an ordered pair of distinct agents uniformly at random (`None` if `n < 2`).
+pub fn draw_uniform_pair<R: Rng>(rng: &mut R, n: usize) -> Option<(usize, usize)> {
+    if n < 2 { return None; }
+    let i = rng.gen_range(0..n);
+    let mut j = rng.gen_range(0..n);
+    while j == i {
+        j = rng.gen_range(0..n);
+    }
+    Some((i, j))
+}
+
+/// Weighted, undirected trade ties between agents, reinforced by executed trades
+/// (`MatchingMode::Persistent`).
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct PartnerGraph {
+    /// `ties[i]`: `(partner, weight)` sorted by partner; symmetric.
+    ties: Vec<Vec<(usize, f64)>>,
+}
+
+impl PartnerGraph {
+    pub fn weight(&self, i: usize, j: usize) -> f64 {
+        let row = self.ties(i);
+        row.binary_search_by_key(&j, |&(p, _)| p).map_or(0.0, |k| row[k].1)
+    }
+
+    /// All ties of `i` as `(partner, weight)`, sorted by partner.
+    pub fn ties(&self, i: usize) -> &[(usize, f64)] {
+        self.ties.get(i).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    /// Total tie weight of `i`.
+    pub fn strength(&self, i: usize) -> f64 {
+        self.ties(i).iter().map(|&(_, w)| w).sum()
+    }
+
+    /// Add `amount` to the tie between `i` and `j` (no-op for `i == j`).
+    pub fn reinforce(&mut self, i: usize, j: usize, amount: f64) {
+        if i == j { return; }
+        let need = i.max(j) + 1;
+        if self.ties.len() < need {
+            self.ties.resize_with(need, Vec::new);
+        }
+        for (a, b) in [(i, j), (j, i)] {
+            let row = &mut self.ties[a];
+            match row.binary_search_by_key(&b, |&(p, _)| p) {
+                Ok(k) => row[k].1 += amount,
+                Err(k) => row.insert(k, (b, amount)),
+            }
+        }
+    }
+
+    /// Shrink every tie by the fraction `frac`; ties that become negligible are dropped.
+    pub fn decay(&mut self, frac: f64) {
+        if frac <= 0.0 { return; }
+        let keep = (1.0 - frac).max(0.0);
+        for row in self.ties.iter_mut() {
+            for t in row.iter_mut() {
+                t.1 *= keep;
+            }
+            row.retain(|&(_, w)| w > 1e-12);
+        }
+    }
+
+    /// Drop the ties of agents with `keep[i] == false` and renumber the others in order, the
+    /// way `Vec::retain` renumbers the population.
+    pub fn retain_agents(&mut self, keep: &[bool]) {
+        let mut new_index = vec![None; keep.len()];
+        for (next, (i, _)) in keep.iter().enumerate().filter(|&(_, &k)| k).enumerate() {
+            new_index[i] = Some(next);
+        }
+        let remap = |i: usize| new_index.get(i).copied().flatten();
+        let old = std::mem::take(&mut self.ties);
+        for (i, row) in old.into_iter().enumerate() {
+            let Some(ni) = remap(i) else { continue };
+            // remap is increasing, so rows stay sorted
+            let row: Vec<(usize, f64)> = row.into_iter().filter_map(|(p, w)| Some((remap(p)?, w))).collect();
+            if row.is_empty() { continue; }
+            if self.ties.len() <= ni {
+                self.ties.resize_with(ni + 1, Vec::new);
+            }
+            self.ties[ni] = row;
+        }
+    }
+
+    /// Every tie once, as `(i, j, weight)` with `i < j`.
+    pub fn edges(&self) -> Vec<(usize, usize, f64)> {
+        self.ties.iter().enumerate()
+            .flat_map(|(i, row)| row.iter().filter(move |&&(j, _)| i < j).map(move |&(j, w)| (i, j, w)))
+            .collect()
+    }
+}
+
+/// Per-round matcher built from the configured `MatchingMode` and the current population.
+#[derive(Clone, Debug)]
+pub enum Matcher {
+    Uniform { n: usize },
+    Quantile {
+        bucket_of: Vec<usize>,
+        members: Vec<Vec<usize>>,
+        mixing: Vec<Vec<f64>>,
+    },
+    Network(Arc<Graph>),
+    Persistent {
+        n: usize,
+        baseline: f64,
+        partners: PartnerGraph,
+    },
+    Reputation {
+        /// Running sums of the agents' matching weights.
+        cumulative: Vec<f64>,
+    },
+}
+
+impl Matcher {
+    /// Build the matcher for the coming round. Quantile buckets are frozen for the round.
+    ///
+    /// `network` is the run's graph for `MatchingMode::Network` (the engine generates it once);
+    /// without one, network mode falls back to uniform matching. `partners` are the trade ties
+    /// for `MatchingMode::Persistent`, frozen for the round like quantile buckets.
+    pub fn for_round(
+        mode: &MatchingMode,
+        agents: &[Agent],
+        network: Option<&Arc<Graph>>,
+        partners: Option<&PartnerGraph>,
+    ) -> Self {
+        match mode {
+            MatchingMode::Persistent { baseline, .. } => Matcher::Persistent {
+                n: agents.len(),
+                baseline: baseline.max(0.0),
+                partners: partners.cloned().unwrap_or_default(),
+            },
+            MatchingMode::Network(_) => match network {
+                Some(g) => Matcher::Network(Arc::clone(g)),
+                None => Matcher::Uniform { n: agents.len() },
+            },
+            MatchingMode::Uniform => Matcher::Uniform { n: agents.len() },
+            MatchingMode::Reputation { baseline } => {
+                let weights: Vec<f64> = agents.iter().map(|a| baseline.max(0.0) + a.reputation.max(0.0)).collect();
+                if weights.iter().filter(|&&w| w > 0.0).count() < 2 {
+                    return Matcher::Uniform { n: agents.len() };
+                }
+                let cumulative = weights.iter()
+                    .scan(0.0, |s, &w| {
+                        *s += w;
+                        Some(*s)
+                    })
+                    .collect();
+                Matcher::Reputation { cumulative }
+            }
+            MatchingMode::Quantile { quantiles, mixing } => {
+                let q = (*quantiles).max(1);
+                let bucket_of = wealth_quantiles(agents, q);
+                let mut members = vec![Vec::new(); q];
+                for (idx, &b) in bucket_of.iter().enumerate() {
+                    members[b].push(idx);
+                }
+                Matcher::Quantile { bucket_of, members, mixing: mixing.clone() }
+            }
+        }
+    }
+
+    /// Draw the (i, j) dyad for one encounter.
+    ///
+    /// Quantile mode draws `i` uniformly, then the partner's quantile `r` with weight
+    /// `mixing[q_i][r]` among quantiles that still contain an agent other than `i`, then `j`
+    /// uniformly within `r`. Missing or all-zero rows fall back to uniform matching.
+    ///
+    /// Network mode draws a uniform edge of the graph. Persistent mode draws `i` uniformly and
+    /// `j` with weight `baseline + w_ij`. Reputation mode draws both by weight, `j` again until
+    /// it differs from `i` (at least two agents have positive weight, else matching is uniform).
+    ///
+    /// Returns `None` when the population has fewer than two agents (or the graph no edges).
+    pub fn draw_pair<R: Rng>(&self, rng: &mut R) -> Option<(usize, usize)> {
+        match self {
+            Matcher::Uniform { n } => draw_uniform_pair(rng, *n),
+            Matcher::Network(g) => g.draw_edge(rng),
+            Matcher::Reputation { cumulative } => {
+                let total = *cumulative.last()?;
+                let mut pick = || {
+                    let u = rng.gen::<f64>() * total;
+                    cumulative.partition_point(|&c| c <= u).min(cumulative.len() - 1)
+                };
+                let i = pick();
+                let mut j = pick();
+                while j == i {
+                    j = pick();
+                }
+                Some((i, j))
+            }
+            Matcher::Persistent { n, baseline, partners } => {
+                let (i, j) = draw_uniform_pair(rng, *n)?;
+                let strength = partners.strength(i);
+                let uniform_mass = baseline * (*n - 1) as f64;
+                if strength <= 0.0 || rng.gen::<f64>() * (strength + uniform_mass) >= strength {
+                    return Some((i, j));
+                }
+                // choose among existing ties proportionally to weight
+                let mut u = rng.gen::<f64>() * strength;
+                let ties = partners.ties(i);
+                for &(p, w) in ties.iter() {
+                    if u < w && p < *n { return Some((i, p)); }
+                    u -= w;
+                }
+                Some((i, j))
+            }
+            Matcher::Quantile { bucket_of, members, mixing } => {
+                let n = bucket_of.len();
+                if n < 2 { return None; }
+                let i = rng.gen_range(0..n);
+                let qi = bucket_of[i];
+
+                let row = mixing.get(qi);
+                let weight = |r: usize| -> f64 {
+                    let available = if r == qi { members[r].len() > 1 } else { !members[r].is_empty() };
+                    if !available { return 0.0; }
+                    row.and_then(|w| w.get(r)).copied().unwrap_or(0.0).max(0.0)
+                };
+                let total: f64 = (0..members.len()).map(&weight).sum();
+                if total <= 0.0 {
+                    let mut j = rng.gen_range(0..n);
+                    while j == i {
+                        j = rng.gen_range(0..n);
+                    }
+                    return Some((i, j));
+                }
+
+                let mut u = rng.gen::<f64>() * total;
+                let mut r_pick = qi;
+                for r in 0..members.len() {
+                    let w = weight(r);
+                    if w <= 0.0 { continue; }
+                    r_pick = r;
+                    if u < w { break; }
+                    u -= w;
+                }
+
+                let bucket = &members[r_pick];
+                let mut j = bucket[rng.gen_range(0..bucket.len())];
+                while j == i {
+                    j = bucket[rng.gen_range(0..bucket.len())];
+                }
+                Some((i, j))
+            }
+        }
+    }
+}
+
+/// Dyads of round-robin sub-step `step` over `order` (circle method).
+///
+/// Each sub-step is a perfect matching of `order` (one agent sits out when the population is
+/// odd), and `order.len() - 1` consecutive sub-steps (`order.len()` when odd) pair every agent
+/// with every other exactly once, like an edge colouring of the complete graph.
+pub fn round_robin_pairs(order: &[usize], step: usize) -> Vec<(usize, usize)> {
+    let n = order.len();
+    let m = if n.is_multiple_of(2) { n } else { n + 1 };
+    if m < 2 { return Vec::new(); }
+    let period = m - 1;
+    let r = step % period;
+
+    let mut pairs = Vec::with_capacity(m / 2);
+    let mut push = |a: usize, b: usize| {
+        // index n is the phantom "bye" seat of an odd population
+        if a < n && b < n {
+            pairs.push((order[a], order[b]));
+        }
+    };
+    push(r, m - 1);
+    for k in 1..m / 2 {
+        push((r + k) % period, (r + period - k) % period);
+    }
+    pairs
+}
@@ -1,18 +1,546 @@
+//! Small numeric helpers (including a dense linear solver) plus a dependency-free statistics
+//! toolkit: weighted moments, streaming quantiles (P²), exponential moving averages and fixed-bin
+//! histograms.
+//!
+//! `ln`, `exp` and `powf` are the transcendental functions of the exchange core (utilities,
+//! MRS, preference estimation). The platform versions may differ in the last bit between
+//! operating systems and CPUs; with the `deterministic` feature they are routed through `libm`'s
+//! portable software implementations instead, so peers evaluating the same dyadic exchange agree
+//! bit for bit. Without `std` they always are. Everything else the core uses (`+ - * /`, `sqrt`) is correctly rounded by IEEE
+//! 754 and already identical everywhere.
+//!
+//! The exchange primitives (`dot`, `normalize`, `clamp01`, `safe_log` here, the Cobb–Douglas
+//! functions in `preferences`, the dyadic oracles and `trade::mrs_to_base`) are generic over
+//! `Scalar`, so they can be evaluated in `f32` or a downstream fixed-point or decimal type. The
+//! simulation itself, and the solver and statistics below, run on `f64`.
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+use core::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+use serde::{Serialize, Deserialize};
+
+#[cfg(feature = "simd")]
+pub mod simd;
+
+/// Number type of the exchange primitives. Implemented for `f64` (through `ln` / `exp` below,
+/// so the `deterministic` feature applies) and `f32`; another type (fixed-point, decimal)
+/// implements the arithmetic and conversions, and may compute `ln` / `exp` / `sqrt` through
+/// `f64` when it has no native versions.
+pub trait Scalar:
+    Copy
+    + PartialOrd
+    + fmt::Debug
+    + fmt::Display
+    + Send
+    + Sync
+    + 'static
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+    + Neg<Output = Self>
+    + AddAssign
+    + SubAssign
+    + MulAssign
+    + DivAssign
+{
+    const ZERO: Self;
+    const ONE: Self;
+
+    /// Nearest value to `x` (constants and tolerances in generic code go through this).
+    fn from_f64(x: f64) -> Self;
+    fn to_f64(self) -> f64;
+    fn ln(self) -> Self;
+    fn exp(self) -> Self;
+    fn sqrt(self) -> Self;
+
+    fn is_finite(self) -> bool {
+        self.to_f64().is_finite()
+    }
+
+    fn abs(self) -> Self {
+        if self < Self::ZERO { -self } else { self }
+    }
+
+    fn max(self, other: Self) -> Self {
+        if self < other { other } else { self }
+    }
+
+    fn min(self, other: Self) -> Self {
+        if other < self { other } else { self }
+    }
+
+    fn clamp(self, lo: Self, hi: Self) -> Self {
+        self.max(lo).min(hi)
+    }
+
+    /// `Σ w_k ln max(x_k, floor)` over the common length, in order; the kernel of
+    /// `preferences::cd_utility`.
+    fn weighted_log_sum(w: &[Self], x: &[Self], floor: Self) -> Self {
+        let mut s = Self::ZERO;
+        for (&wk, &xk) in w.iter().zip(x.iter()) {
+            s += wk * xk.max(floor).ln();
+        }
+        s
+    }
+}
+
+impl Scalar for f64 {
+    const ZERO: f64 = 0.0;
+    const ONE: f64 = 1.0;
+
+    fn from_f64(x: f64) -> f64 { x }
+    fn to_f64(self) -> f64 { self }
+    fn ln(self) -> f64 { ln(self) }
+    fn exp(self) -> f64 { exp(self) }
+    #[cfg(feature = "std")]
+    fn sqrt(self) -> f64 { f64::sqrt(self) }
+    #[cfg(not(feature = "std"))]
+    fn sqrt(self) -> f64 { libm::sqrt(self) }
+    fn is_finite(self) -> bool { f64::is_finite(self) }
+    #[cfg(feature = "std")]
+    fn abs(self) -> f64 { f64::abs(self) }
+    #[cfg(not(feature = "std"))]
+    fn abs(self) -> f64 { libm::fabs(self) }
+    fn max(self, other: f64) -> f64 { f64::max(self, other) }
+    fn min(self, other: f64) -> f64 { f64::min(self, other) }
+    fn clamp(self, lo: f64, hi: f64) -> f64 { f64::clamp(self, lo, hi) }
+    #[cfg(feature = "simd")]
+    fn weighted_log_sum(w: &[f64], x: &[f64], floor: f64) -> f64 { simd::weighted_log_sum(w, x, floor) }
+}
+
+impl Scalar for f32 {
+    const ZERO: f32 = 0.0;
+    const ONE: f32 = 1.0;
+
+    fn from_f64(x: f64) -> f32 { x as f32 }
+    fn to_f64(self) -> f64 { self as f64 }
+
+    fn ln(self) -> f32 {
+        #[cfg(any(feature = "deterministic", not(feature = "std")))]
+        {
+            libm::logf(self)
+        }
+        #[cfg(all(not(feature = "deterministic"), feature = "std"))]
+        {
+            f32::ln(self)
+        }
+    }
+
+    fn exp(self) -> f32 {
+        #[cfg(any(feature = "deterministic", not(feature = "std")))]
+        {
+            libm::expf(self)
+        }
+        #[cfg(all(not(feature = "deterministic"), feature = "std"))]
+        {
+            f32::exp(self)
+        }
+    }
+
+    #[cfg(feature = "std")]
+    fn sqrt(self) -> f32 { f32::sqrt(self) }
+    #[cfg(not(feature = "std"))]
+    fn sqrt(self) -> f32 { libm::sqrtf(self) }
+    fn is_finite(self) -> bool { f32::is_finite(self) }
+    #[cfg(feature = "std")]
+    fn abs(self) -> f32 { f32::abs(self) }
+    #[cfg(not(feature = "std"))]
+    fn abs(self) -> f32 { libm::fabsf(self) }
+    fn max(self, other: f32) -> f32 { f32::max(self, other) }
+    fn min(self, other: f32) -> f32 { f32::min(self, other) }
+    fn clamp(self, lo: f32, hi: f32) -> f32 { f32::clamp(self, lo, hi) }
+}
+
+/// Natural logarithm (see the module docs for the `deterministic` feature).
+#[inline]
+pub fn ln(x: f64) -> f64 {
+    #[cfg(any(feature = "deterministic", not(feature = "std")))]
+    {
+        libm::log(x)
+    }
+    #[cfg(all(not(feature = "deterministic"), feature = "std"))]
+    {
+        x.ln()
+    }
+}
+
+/// `e^x` (see the module docs for the `deterministic` feature).
+#[inline]
+pub fn exp(x: f64) -> f64 {
+    #[cfg(any(feature = "deterministic", not(feature = "std")))]
+    {
+        libm::exp(x)
+    }
+    #[cfg(all(not(feature = "deterministic"), feature = "std"))]
+    {
+        x.exp()
+    }
+}
+
+/// `x^y` (see the module docs for the `deterministic` feature).
+#[inline]
+pub fn powf(x: f64, y: f64) -> f64 {
+    #[cfg(any(feature = "deterministic", not(feature = "std")))]
+    {
+        libm::pow(x, y)
+    }
+    #[cfg(all(not(feature = "deterministic"), feature = "std"))]
+    {
+        x.powf(y)
+    }
+}
+
 pub fn dot(a: &[f64], b: &[f64]) -> f64 {
-    a.iter().zip(b.iter()).map(|(x,y)| x*y).sum()
+    dot_s(a, b)
 }
 
 pub fn normalize(v: &mut [f64]) {
-    let s: f64 = v.iter().sum();
-    if s > 0.0 {
-        for x in v.iter_mut() { *x /= s; }
-    }
+    normalize_s(v)
 }
 
 pub fn clamp01(x: f64) -> f64 {
-    if x < 0.0 { 0.0 } else if x > 1.0 { 1.0 } else { x }
+    clamp01_s(x)
 }
 
 pub fn safe_log(x: f64, min_qty: f64) -> f64 {
-    (x.max(min_qty)).ln()
+    safe_log_s(x, min_qty)
+}
+
+/// `dot` in any `Scalar`.
+pub fn dot_s<S: Scalar>(a: &[S], b: &[S]) -> S {
+    a.iter().zip(b.iter()).fold(S::ZERO, |s, (&x, &y)| s + x * y)
+}
+
+/// `normalize` in any `Scalar`.
+pub fn normalize_s<S: Scalar>(v: &mut [S]) {
+    let s = v.iter().fold(S::ZERO, |s, &x| s + x);
+    if s > S::ZERO {
+        for x in v.iter_mut() { *x /= s; }
+    }
+}
+
+/// `clamp01` in any `Scalar`.
+pub fn clamp01_s<S: Scalar>(x: S) -> S {
+    if x < S::ZERO { S::ZERO } else if x > S::ONE { S::ONE } else { x }
+}
+
+/// `safe_log` in any `Scalar`.
+pub fn safe_log_s<S: Scalar>(x: S, min_qty: S) -> S {
+    x.max(min_qty).ln()
+}
+
+/// Solve the square system `a · x = b` by Gaussian elimination with partial pivoting. `None` if
+/// the dimensions disagree or the matrix is (numerically) singular.
+pub fn solve_linear(a: &[Vec<f64>], b: &[f64]) -> Option<Vec<f64>> {
+    let n = b.len();
+    if a.len() != n || a.iter().any(|row| row.len() != n) { return None; }
+    let mut m: Vec<Vec<f64>> = a.iter().zip(b.iter()).map(|(row, &y)| {
+        let mut r = row.clone();
+        r.push(y);
+        r
+    }).collect();
+    let scale = a.iter().flat_map(|row| row.iter()).fold(0.0_f64, |s, x| s.max(x.abs())).max(1.0);
+    for col in 0..n {
+        let pivot = (col..n).max_by(|&x, &y| m[x][col].abs().total_cmp(&m[y][col].abs()))?;
+        if m[pivot][col].abs() <= 1e-12 * scale { return None; }
+        m.swap(col, pivot);
+        let (top, rest) = m.split_at_mut(col + 1);
+        let p = &top[col];
+        for row in rest.iter_mut() {
+            let f = row[col] / p[col];
+            for (x, &y) in row.iter_mut().zip(p.iter()).skip(col) {
+                *x -= f * y;
+            }
+        }
+    }
+    let mut x = vec![0.0; n];
+    for r in (0..n).rev() {
+        let tail: f64 = (r + 1..n).map(|c| m[r][c] * x[c]).sum();
+        x[r] = (m[r][n] - tail) / m[r][r];
+    }
+    Some(x)
+}
+
+/// Weighted mean `Σ w x / Σ w` (0 when the total weight is not positive). Negative weights are
+/// treated as 0; extra elements of the longer slice are ignored.
+pub fn weighted_mean(values: &[f64], weights: &[f64]) -> f64 {
+    let mut acc = WeightedStats::default();
+    for (&x, &w) in values.iter().zip(weights.iter()) {
+        acc.push(x, w);
+    }
+    acc.mean()
+}
+
+/// Weighted population variance `Σ w (x - μ)² / Σ w`, with the same conventions as
+/// `weighted_mean`.
+pub fn weighted_variance(values: &[f64], weights: &[f64]) -> f64 {
+    let mut acc = WeightedStats::default();
+    for (&x, &w) in values.iter().zip(weights.iter()) {
+        acc.push(x, w);
+    }
+    acc.variance()
+}
+
+/// Streaming weighted mean and variance (West's incremental update), plus min/max.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct WeightedStats {
+    count: usize,
+    weight: f64,
+    mean: f64,
+    /// Weighted sum of squared deviations from the running mean.
+    m2: f64,
+    min: f64,
+    max: f64,
+}
+
+impl Default for WeightedStats {
+    fn default() -> Self {
+        WeightedStats { count: 0, weight: 0.0, mean: 0.0, m2: 0.0, min: f64::INFINITY, max: f64::NEG_INFINITY }
+    }
+}
+
+impl WeightedStats {
+    /// Add an observation `x` with weight `w`. Non-finite `x` and non-positive `w` are skipped
+    /// for the moments; any finite `x` still counts towards `count`, `min` and `max`.
+    pub fn push(&mut self, x: f64, w: f64) {
+        if !x.is_finite() { return; }
+        self.count += 1;
+        self.min = self.min.min(x);
+        self.max = self.max.max(x);
+        let weighted = w > 0.0 && w.is_finite();
+        if !weighted { return; }
+        self.weight += w;
+        let d = x - self.mean;
+        self.mean += d * w / self.weight;
+        self.m2 += w * d * (x - self.mean);
+    }
+
+    /// Observations pushed (including zero-weight ones).
+    pub fn count(&self) -> usize { self.count }
+
+    pub fn total_weight(&self) -> f64 { self.weight }
+
+    /// 0 until some observation carries positive weight.
+    pub fn mean(&self) -> f64 {
+        if self.weight > 0.0 { self.mean } else { 0.0 }
+    }
+
+    /// Population (not sample) variance.
+    pub fn variance(&self) -> f64 {
+        if self.weight > 0.0 { (self.m2 / self.weight).max(0.0) } else { 0.0 }
+    }
+
+    pub fn std_dev(&self) -> f64 { self.variance().sqrt() }
+
+    /// `None` before the first observation.
+    pub fn min(&self) -> Option<f64> { (self.count > 0).then_some(self.min) }
+
+    pub fn max(&self) -> Option<f64> { (self.count > 0).then_some(self.max) }
+}
+
+/// Linearly interpolated `p`-quantile of an ascending slice (0 when empty).
+pub fn quantile(sorted: &[f64], p: f64) -> f64 {
+    if sorted.is_empty() { return 0.0; }
+    let h = p.clamp(0.0, 1.0) * (sorted.len() - 1) as f64;
+    // h >= 0, so truncation is the floor (and needs no `std`)
+    let lo = h as usize;
+    let hi = (lo + 1).min(sorted.len() - 1);
+    sorted[lo] + (h - lo as f64) * (sorted[hi] - sorted[lo])
+}
+
+/// Streaming estimate of one quantile in O(1) memory (Jain & Chlamtac's P² algorithm).
+///
+/// Exact for the first five observations, then tracks five markers whose heights are adjusted
+/// by piecewise-parabolic interpolation.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct P2Quantile {
+    p: f64,
+    /// Marker heights.
+    q: [f64; 5],
+    /// Actual marker positions (1-based ranks).
+    n: [f64; 5],
+    /// Desired marker positions.
+    np: [f64; 5],
+    /// Increments of the desired positions per observation.
+    dn: [f64; 5],
+    count: usize,
+}
+
+impl P2Quantile {
+    /// Estimator of the `p`-quantile; `p` is clamped to [0, 1].
+    pub fn new(p: f64) -> Self {
+        let p = clamp01(p);
+        P2Quantile {
+            p,
+            q: [0.0; 5],
+            n: [1.0, 2.0, 3.0, 4.0, 5.0],
+            np: [1.0, 1.0 + 2.0 * p, 1.0 + 4.0 * p, 3.0 + 2.0 * p, 5.0],
+            dn: [0.0, p / 2.0, p, (1.0 + p) / 2.0, 1.0],
+            count: 0,
+        }
+    }
+
+    pub fn p(&self) -> f64 { self.p }
+
+    pub fn count(&self) -> usize { self.count }
+
+    /// Add an observation; non-finite values are ignored.
+    pub fn push(&mut self, x: f64) {
+        if !x.is_finite() { return; }
+        if self.count < 5 {
+            self.q[self.count] = x;
+            self.count += 1;
+            if self.count == 5 {
+                self.q.sort_by(f64::total_cmp);
+            }
+            return;
+        }
+        self.count += 1;
+
+        // cell k such that q[k] <= x < q[k + 1], extending the extremes if needed
+        let k = if x < self.q[0] {
+            self.q[0] = x;
+            0
+        } else if x >= self.q[4] {
+            self.q[4] = x;
+            3
+        } else {
+            (0..4).find(|&i| x < self.q[i + 1]).unwrap_or(3)
+        };
+        for n in self.n[k + 1..].iter_mut() {
+            *n += 1.0;
+        }
+        for (np, dn) in self.np.iter_mut().zip(self.dn.iter()) {
+            *np += dn;
+        }
+
+        for i in 1..4 {
+            let d = self.np[i] - self.n[i];
+            let room_up = self.n[i + 1] - self.n[i] > 1.0;
+            let room_down = self.n[i - 1] - self.n[i] < -1.0;
+            if (d >= 1.0 && room_up) || (d <= -1.0 && room_down) {
+                let s = if d > 0.0 { 1.0 } else { -1.0 };
+                let candidate = self.parabolic(i, s);
+                self.q[i] = if self.q[i - 1] < candidate && candidate < self.q[i + 1] {
+                    candidate
+                } else {
+                    self.linear(i, s)
+                };
+                self.n[i] += s;
+            }
+        }
+    }
+
+    fn parabolic(&self, i: usize, s: f64) -> f64 {
+        let (q, n) = (&self.q, &self.n);
+        q[i] + s / (n[i + 1] - n[i - 1])
+            * ((n[i] - n[i - 1] + s) * (q[i + 1] - q[i]) / (n[i + 1] - n[i])
+                + (n[i + 1] - n[i] - s) * (q[i] - q[i - 1]) / (n[i] - n[i - 1]))
+    }
+
+    fn linear(&self, i: usize, s: f64) -> f64 {
+        let j = if s > 0.0 { i + 1 } else { i - 1 };
+        self.q[i] + s * (self.q[j] - self.q[i]) / (self.n[j] - self.n[i])
+    }
+
+    /// Current estimate (0 before the first observation). With fewer than five observations
+    /// this is the exact linearly interpolated quantile.
+    pub fn estimate(&self) -> f64 {
+        if self.count >= 5 { return self.q[2]; }
+        let mut v = self.q[..self.count].to_vec();
+        v.sort_by(f64::total_cmp);
+        quantile(&v, self.p)
+    }
+}
+
+/// Exponential moving average `m ← m + α (x - m)`, seeded by the first observation.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Ema {
+    alpha: f64,
+    value: Option<f64>,
+}
+
+impl Ema {
+    /// Smoothing factor `alpha` in (0, 1]; it is clamped into that range.
+    pub fn new(alpha: f64) -> Self {
+        Ema { alpha: alpha.clamp(f64::MIN_POSITIVE, 1.0), value: None }
+    }
+
+    /// EMA whose weights have the given half-life in observations.
+    pub fn with_half_life(half_life: f64) -> Self {
+        Ema::new(1.0 - powf(0.5, 1.0 / half_life.max(f64::MIN_POSITIVE)))
+    }
+
+    pub fn alpha(&self) -> f64 { self.alpha }
+
+    /// Add an observation and return the updated average. Non-finite values are ignored.
+    pub fn push(&mut self, x: f64) -> Option<f64> {
+        if x.is_finite() {
+            self.value = Some(match self.value {
+                Some(m) => m + self.alpha * (x - m),
+                None => x,
+            });
+        }
+        self.value
+    }
+
+    /// `None` before the first observation.
+    pub fn value(&self) -> Option<f64> { self.value }
+}
+
+/// Histogram with `bins` equal-width bins over `[lo, hi)`; values outside the range are counted
+/// separately.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Histogram {
+    lo: f64,
+    hi: f64,
+    counts: Vec<f64>,
+    underflow: f64,
+    overflow: f64,
+}
+
+impl Histogram {
+    /// `None` unless `lo < hi` (both finite) and `bins > 0`.
+    pub fn new(lo: f64, hi: f64, bins: usize) -> Option<Self> {
+        let ok = lo.is_finite() && hi.is_finite() && lo < hi && bins > 0;
+        if !ok { return None; }
+        Some(Histogram { lo, hi, counts: vec![0.0; bins], underflow: 0.0, overflow: 0.0 })
+    }
+
+    /// Add `x` with unit weight.
+    pub fn push(&mut self, x: f64) { self.push_weighted(x, 1.0); }
+
+    /// Add `x` with weight `w`; NaN is ignored.
+    pub fn push_weighted(&mut self, x: f64, w: f64) {
+        if x.is_nan() { return; }
+        if x < self.lo {
+            self.underflow += w;
+        } else if x >= self.hi {
+            self.overflow += w;
+        } else {
+            let k = ((x - self.lo) / self.bin_width()) as usize;
+            let last = self.counts.len() - 1;
+            self.counts[k.min(last)] += w;
+        }
+    }
+
+    pub fn bin_width(&self) -> f64 { (self.hi - self.lo) / self.counts.len() as f64 }
+
+    /// `[lo, hi)` of bin `k`.
+    pub fn bin_range(&self, k: usize) -> (f64, f64) {
+        let w = self.bin_width();
+        (self.lo + k as f64 * w, self.lo + (k + 1) as f64 * w)
+    }
+
+    pub fn counts(&self) -> &[f64] { &self.counts }
+
+    pub fn underflow(&self) -> f64 { self.underflow }
+
+    pub fn overflow(&self) -> f64 { self.overflow }
+
+    /// Total weight including out-of-range values.
+    pub fn total(&self) -> f64 {
+        self.counts.iter().sum::<f64>() + self.underflow + self.overflow
+    }
 }
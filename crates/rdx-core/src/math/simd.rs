@@ -0,0 +1,85 @@
+//! Chunked `f64` kernels of the `simd` feature: loops over fixed `LANES`-wide arrays that the
+//! compiler lowers to vector instructions on stable Rust, without `std::simd`.
+//!
+//! `ln_lanes` is fdlibm's logarithm (within 1 ulp) written branch-free for positive normal
+//! inputs, so it vectorizes where a call into the platform `ln` cannot; chunks holding zeros,
+//! subnormals, infinities or NaN fall back to `math::ln`. It uses only `+ - * /` and bit
+//! operations, so it is as portable as the `deterministic` build. Results can differ from the
+//! platform `ln` in the last bit, and sums keep one accumulator per lane, so kernels agree with
+//! their sequential counterparts to rounding rather than bit for bit.
+
+use super::ln;
+
+/// Width of the chunks.
+pub const LANES: usize = 4;
+
+const LN2_HI: f64 = 0.6931471803691238;
+const LN2_LO: f64 = 1.9082149292705877e-10;
+const LG1: f64 = 0.6666666666666735;
+const LG2: f64 = 0.3999999999940942;
+const LG3: f64 = 0.2857142874366239;
+const LG4: f64 = 0.22222198432149784;
+const LG5: f64 = 0.1818357216161805;
+const LG6: f64 = 0.15313837699209373;
+const LG7: f64 = 0.14798198605116586;
+
+/// Natural logarithm of every lane.
+#[inline]
+pub fn ln_lanes(x: [f64; LANES]) -> [f64; LANES] {
+    if !x.iter().all(|&v| v.is_normal() && v > 0.0) {
+        return x.map(ln);
+    }
+    let mut out = [0.0; LANES];
+    for (o, &v) in out.iter_mut().zip(x.iter()) {
+        let bits = v.to_bits();
+        // v = 2^k · m with m in [sqrt(2)/2, sqrt(2))
+        let hx = ((bits >> 32) as u32) + (0x3ff0_0000 - 0x3fe6_a09e);
+        let k = (hx >> 20) as i32 - 0x3ff;
+        let hx = (hx & 0x000f_ffff) + 0x3fe6_a09e;
+        let m = f64::from_bits((u64::from(hx) << 32) | (bits & 0xffff_ffff));
+        let f = m - 1.0;
+        let hfsq = 0.5 * f * f;
+        let s = f / (2.0 + f);
+        let z = s * s;
+        let w = z * z;
+        let t1 = w * (LG2 + w * (LG4 + w * LG6));
+        let t2 = z * (LG1 + w * (LG3 + w * (LG5 + w * LG7)));
+        let dk = f64::from(k);
+        *o = s * (hfsq + (t2 + t1)) + dk * LN2_LO - hfsq + f + dk * LN2_HI;
+    }
+    out
+}
+
+/// Replace every value by its natural logarithm.
+pub fn ln_in_place(values: &mut [f64]) {
+    let mut chunks = values.chunks_exact_mut(LANES);
+    for chunk in &mut chunks {
+        let mut lanes = [0.0; LANES];
+        lanes.copy_from_slice(chunk);
+        chunk.copy_from_slice(&ln_lanes(lanes));
+    }
+    for v in chunks.into_remainder() {
+        *v = ln(*v);
+    }
+}
+
+/// `Σ w_k ln max(x_k, floor)` over the common length of `w` and `x`.
+pub fn weighted_log_sum(w: &[f64], x: &[f64], floor: f64) -> f64 {
+    let n = w.len().min(x.len());
+    let (mut wc, mut xc) = (w[..n].chunks_exact(LANES), x[..n].chunks_exact(LANES));
+    let mut acc = [0.0; LANES];
+    for (wl, xl) in (&mut wc).zip(&mut xc) {
+        let mut lanes = [0.0; LANES];
+        for (l, &v) in lanes.iter_mut().zip(xl) {
+            *l = v.max(floor);
+        }
+        for ((a, &wk), l) in acc.iter_mut().zip(wl).zip(ln_lanes(lanes)) {
+            *a += wk * l;
+        }
+    }
+    let mut s: f64 = acc.iter().sum();
+    for (&wk, &v) in wc.remainder().iter().zip(xc.remainder()) {
+        s += wk * ln(v.max(floor));
+    }
+    s
+}
@@ -0,0 +1,126 @@
+//! Distributional and efficiency metrics: wealth at emergent prices, inequality of wealth and
+//! utility across the population, and the distance to the Pareto frontier.
+//!
+//! All functions treat their inputs as non-negative quantities (holdings, wealth, utility) and
+//! return 0 for empty or all-zero populations instead of NaN.
+use serde::{Serialize, Deserialize};
+use crate::model::Agent;
+use crate::preferences::cd_utility;
+use crate::model::SimConfig;
+use crate::prices::{walras_allocation, walras_prices};
+use crate::sim::{PairRateStat, SimState};
+
+pub use crate::math::quantile;
+
+/// Probability levels of `InequalityMetrics::utility_quantiles` and `wealth_quantiles`.
+pub const UTILITY_LEVELS: [f64; 5] = [0.1, 0.25, 0.5, 0.75, 0.9];
+
+/// Per-round inequality snapshot, stored in `sim::RoundMetrics`.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct InequalityMetrics {
+    /// Mean wealth at prices.
+    #[serde(default)]
+    pub mean_wealth: f64,
+    /// Wealth at `UTILITY_LEVELS`.
+    #[serde(default)]
+    pub wealth_quantiles: [f64; 5],
+    /// Gini coefficient of wealth at prices.
+    pub gini_wealth: f64,
+    /// Theil T index of wealth at prices.
+    pub theil_wealth: f64,
+    /// Gini coefficient of Cobb–Douglas utility.
+    pub gini_utility: f64,
+    /// Utility at `UTILITY_LEVELS`.
+    pub utility_quantiles: [f64; 5],
+}
+
+/// Gini coefficient in [0, 1): 0 for perfect equality, approaching 1 when one agent holds
+/// everything. Negative values are clamped to 0.
+pub fn gini(values: &[f64]) -> f64 {
+    let mut v: Vec<f64> = values.iter().map(|x| x.max(0.0)).collect();
+    v.sort_by(f64::total_cmp);
+    let n = v.len() as f64;
+    let total: f64 = v.iter().sum();
+    if v.is_empty() || total <= 0.0 { return 0.0; }
+    // G = 2 Σ_i i·x_(i) / (n Σ x) - (n + 1) / n, ranks i starting at 1
+    let weighted: f64 = v.iter().enumerate().map(|(i, x)| (i + 1) as f64 * x).sum();
+    (2.0 * weighted / (n * total) - (n + 1.0) / n).max(0.0)
+}
+
+/// Theil T index, `(1/n) Σ (x/μ) ln(x/μ)`, in [0, ln n]; zero entries contribute 0.
+/// Unlike the Gini it is additively decomposable into within- and between-group terms.
+pub fn theil(values: &[f64]) -> f64 {
+    let n = values.len() as f64;
+    let total: f64 = values.iter().map(|x| x.max(0.0)).sum();
+    if values.is_empty() || total <= 0.0 { return 0.0; }
+    let mean = total / n;
+    let s: f64 = values.iter()
+        .map(|x| x.max(0.0) / mean)
+        .filter(|&r| r > 0.0)
+        .map(|r| r * r.ln())
+        .sum();
+    (s / n).max(0.0)
+}
+
+/// Market value of an agent's bundle, `Σ_k prices[k] · e_k`, in units of the base good.
+/// Goods without a price are valued at 1 (the base good's price).
+pub fn wealth(agent: &Agent, prices: &[f64]) -> f64 {
+    agent.e.iter().enumerate().map(|(k, &x)| prices.get(k).copied().unwrap_or(1.0) * x).sum()
+}
+
+/// `wealth` of every agent.
+pub fn wealth_at_prices(agents: &[Agent], prices: &[f64]) -> Vec<f64> {
+    agents.iter().map(|a| wealth(a, prices)).collect()
+}
+
+/// Fold a round's exchange rates into running emergent prices versus the base good: every good
+/// traded against the base this round takes its volume-weighted mean `q_ab`; the others keep
+/// their previous estimate. `prices[base]` stays 1.
+pub fn update_emergent_prices(prices: &mut [f64], rates: &[PairRateStat], base_good: usize) {
+    for r in rates.iter().filter(|r| r.good_b.index() == base_good && r.mean_q_ab.is_finite()) {
+        if let Some(p) = prices.get_mut(r.good_a.index()) {
+            *p = r.mean_q_ab;
+        }
+    }
+    if let Some(p) = prices.get_mut(base_good) {
+        *p = 1.0;
+    }
+}
+
+/// Aggregate Cobb–Douglas utility still left on the table: `Σ_i u_i(x*_i) - Σ_i u_i(e_i)`,
+/// where `x*` is the n-good Walrasian allocation reached from the current holdings.
+///
+/// `x*` is Pareto efficient and leaves every agent at least as well off as now, so the gap is
+/// non-negative (up to numerical noise) and 0 once bilateral trade has exhausted all gains.
+pub fn pareto_gap(state: &SimState, cfg: &SimConfig) -> f64 {
+    let agents = &state.agents;
+    let prices = walras_prices(agents, cfg.base_good);
+    let current: f64 = agents.iter().map(|a| cd_utility(&a.beta, &a.e, cfg.min_qty)).sum();
+    let efficient: f64 = walras_allocation(agents, &prices).iter().zip(agents.iter())
+        .map(|(x, a)| cd_utility(&a.beta, x, cfg.min_qty))
+        .sum();
+    efficient - current
+}
+
+/// Utility of every agent at `UTILITY_LEVELS`.
+pub fn utility_quantiles(agents: &[Agent], min_qty: f64) -> [f64; 5] {
+    let mut u: Vec<f64> = agents.iter().map(|a| cd_utility(&a.beta, &a.e, min_qty)).collect();
+    u.sort_by(f64::total_cmp);
+    UTILITY_LEVELS.map(|p| quantile(&u, p))
+}
+
+/// All inequality metrics for a population valued at `prices`.
+pub fn inequality(agents: &[Agent], prices: &[f64], min_qty: f64) -> InequalityMetrics {
+    let wealth = wealth_at_prices(agents, prices);
+    let utility: Vec<f64> = agents.iter().map(|a| cd_utility(&a.beta, &a.e, min_qty)).collect();
+    let mut sorted = wealth.clone();
+    sorted.sort_by(f64::total_cmp);
+    InequalityMetrics {
+        mean_wealth: if wealth.is_empty() { 0.0 } else { wealth.iter().sum::<f64>() / wealth.len() as f64 },
+        wealth_quantiles: UTILITY_LEVELS.map(|p| quantile(&sorted, p)),
+        gini_wealth: gini(&wealth),
+        theil_wealth: theil(&wealth),
+        gini_utility: gini(&utility),
+        utility_quantiles: utility_quantiles(agents, min_qty),
+    }
+}
@@ -1,8 +1,37 @@
+use std::collections::BTreeMap;
+use crate::ids::{AgentIdx, GoodId};
+use crate::math::{exp, ln};
+use crate::preferences::log_cd_utility;
 use crate::reaction::ReactionRuleSpec;
+use crate::state::Hash;
 use serde::{Serialize, Deserialize};
+use schemars::JsonSchema;
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema)]
 pub struct Agent {
+    /// Stable identifier, unlike the agent's position in `SimState::agents`. `init_agents`
+    /// numbers agents from 0.
+    #[serde(default)]
+    pub id: u64,
+    /// Free-form metadata; `init_agents` sets `group` for agents of a named `agent_groups` entry.
+    #[serde(default)]
+    pub labels: BTreeMap<String, String>,
+    /// Outstanding credit in base-good units under `SimConfig::monetary` (0 otherwise).
+    #[serde(default)]
+    pub debt: f64,
+    /// Intermediary role under `SimConfig::brokers`: trades at its quotes for resale rather than
+    /// for its own utility (see `broker`).
+    #[serde(default)]
+    pub broker: bool,
+    /// Trust earned in trade under `SimConfig::reputation` (0 otherwise).
+    #[serde(default)]
+    pub reputation: f64,
+    /// Learned value of offering each good under `PairingMode::Learning` (empty: all 0).
+    #[serde(default)]
+    pub q_values: Vec<f64>,
+    /// Expected prices under `SimConfig::price_expectations`, created at the agent's first trade.
+    #[serde(default)]
+    pub price_memory: Option<PriceMemory>,
     /// Endowment vector across goods (length = n).
     pub e: Vec<f64>,
     /// Aggregated Cobb–Douglas exponents (length = n, sum = 1).
@@ -13,38 +42,767 @@ pub struct Agent {
     /// Endogenous transformations (reaction term) applied before diffusion/trading.
     #[serde(default)]
     pub reaction_rules: Vec<ReactionRuleSpec>,
+    /// Cached log-utility of `e` (see `Agent::log_utility`); not serialized. `set_holding` and
+    /// the `apply_*` trade functions keep it current, other edits of `e` or `beta` must clear it.
+    #[serde(skip)]
+    pub utility_cache: Option<UtilityCache>,
+}
+
+/// `Σ β_k ln max(e_k, min_qty)` of an agent's holdings at one `min_qty`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct UtilityCache {
+    pub log_utility: f64,
+    pub min_qty: f64,
+}
+
+impl Agent {
+    /// `ln cd_utility(beta, e, min_qty)`: O(1) from the cache when it was taken at `min_qty`,
+    /// recomputed over all goods otherwise.
+    pub fn log_utility(&self, min_qty: f64) -> f64 {
+        match self.utility_cache {
+            Some(c) if c.min_qty == min_qty => c.log_utility,
+            _ => log_cd_utility(&self.beta, &self.e, min_qty),
+        }
+    }
+
+    /// `cd_utility(beta, e, min_qty)`, through the cache as `log_utility`.
+    pub fn utility(&self, min_qty: f64) -> f64 {
+        exp(self.log_utility(min_qty))
+    }
+
+    /// Recompute the cache from scratch at `min_qty`.
+    pub fn refresh_utility(&mut self, min_qty: f64) {
+        let log_utility = log_cd_utility(&self.beta, &self.e, min_qty);
+        self.utility_cache = Some(UtilityCache { log_utility, min_qty });
+    }
+
+    /// Drop the cache after editing `e` or `beta` directly.
+    pub fn invalidate_utility(&mut self) {
+        self.utility_cache = None;
+    }
+
+    /// Set the holding of good `g` to `x`, moving the cached log-utility by the one term that
+    /// changes (a cache taken at another `min_qty` is dropped). Panics if `g` is out of range.
+    pub fn set_holding(&mut self, g: usize, x: f64, min_qty: f64) {
+        let old = self.e[g];
+        self.e[g] = x;
+        match &mut self.utility_cache {
+            Some(c) if c.min_qty == min_qty => {
+                let w = self.beta.get(g).copied().unwrap_or(0.0);
+                c.log_utility += w * (ln(x.max(min_qty)) - ln(old.max(min_qty)));
+            }
+            _ => self.utility_cache = None,
+        }
+    }
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
 pub struct TradeEvent {
     pub round: usize,
-    pub i: usize,
-    pub j: usize,
-    pub good_a: usize,
-    pub good_b: usize,
+    pub i: AgentIdx,
+    pub j: AgentIdx,
+    pub good_a: GoodId,
+    pub good_b: GoodId,
     pub q_ab: f64,
     pub delta_a_i: f64,
     pub delta_b_i: f64,
     pub delta_u_i: f64,
     pub delta_u_j: f64,
+    /// `Agent::id` of `i` and `j`.
+    #[serde(default)]
+    pub id_i: u64,
+    #[serde(default)]
+    pub id_j: u64,
+    /// Simulated Unix time of the trade, when `SimConfig::clock` is set.
+    #[serde(default)]
+    pub time: Option<f64>,
+    /// Transaction cost paid by `i` and `j`, in base-good units (`SimConfig::transaction_cost`).
+    #[serde(default)]
+    pub cost_i: f64,
+    #[serde(default)]
+    pub cost_j: f64,
+    /// Tax paid to the treasury on the trade's base-good leg (`SimConfig::policy`).
+    #[serde(default)]
+    pub tax: f64,
+    /// Accepted for resale value rather than direct gain (`SimConfig::money_emergence`).
+    #[serde(default)]
+    pub speculative: bool,
+    /// Position in the run's event log, `SimState::events[seq]` (see `log`).
+    #[serde(default)]
+    pub seq: u64,
+    /// `state::leaf_hash` of `i`'s and `j`'s holdings just before and just after the trade.
+    #[serde(default)]
+    pub pre_i: Hash,
+    #[serde(default)]
+    pub pre_j: Hash,
+    #[serde(default)]
+    pub post_i: Hash,
+    #[serde(default)]
+    pub post_j: Hash,
+}
+
+/// An agent's smoothed memory of the prices it accepted (`SimConfig::price_expectations`).
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct PriceMemory {
+    /// Per good, expected price in base-good units; 0 until the agent has traded the good.
+    pub expected: Vec<f64>,
+    /// Relative deviation from `expected` the agent tolerates before refusing a trade.
+    pub tolerance: f64,
+}
+
+impl PriceMemory {
+    /// True if buying (or selling) good `g` at `price` is worse than expected by more than the
+    /// tolerance: above `expected · (1 + tolerance)` for a buyer, below `expected · (1 -
+    /// tolerance)` for a seller. Goods without an expectation are always acceptable.
+    pub fn rejects(&self, g: usize, price: f64, buying: bool) -> bool {
+        let expected = self.expected.get(g).copied().unwrap_or(0.0);
+        if expected <= 0.0 { return false; }
+        if buying { price > expected * (1.0 + self.tolerance) } else { price < expected * (1.0 - self.tolerance) }
+    }
+
+    /// Move the expectation for `g` towards an accepted `price` by `memory` (the first price
+    /// observed is taken as is).
+    pub fn observe(&mut self, g: usize, price: f64, memory: f64) {
+        if self.expected.len() <= g {
+            self.expected.resize(g + 1, 0.0);
+        }
+        let e = &mut self.expected[g];
+        *e = if *e > 0.0 { *e + memory * (price - *e) } else { price };
+    }
+}
+
+/// A three-way cycle executed by the engine (`SimConfig::triads`, see
+/// `trade::best_three_way_cycle`): `agents[m]` gave `quantities[m]` of `goods[m]` to
+/// `agents[(m + 1) % 3]`.
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct CycleEvent {
+    pub round: usize,
+    pub agents: [AgentIdx; 3],
+    /// `Agent::id` of the three agents.
+    pub ids: [u64; 3],
+    pub goods: [GoodId; 3],
+    pub quantities: [f64; 3],
+    pub delta_u: [f64; 3],
+}
+
+/// Three-agent trade cycles, attempted when an encounter's dyadic search finds no trade.
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct TriadSpec {
+    /// Share of the smallest affordable gift exchanged along the cycle.
+    #[serde(default = "default_speculative_step")]
+    pub step: f64,
+}
+
+impl Default for TriadSpec {
+    fn default() -> Self { TriadSpec { step: default_speculative_step() } }
 }
 
 /// How to choose candidate good-pairs to evaluate in each P2P encounter.
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum PairingMode {
     /// Evaluate every good A against the base good B only.
+    #[default]
     AgainstBase,
     /// Evaluate all ordered pairs (A,B) but only within a pruned candidate set
     /// of size `candidate_goods_k` (plus the base good).
     AllPairsPruned,
+    /// Exchange the whole pruned candidate set (plus the base good) at once, at the dyad's
+    /// Walrasian equilibrium (`trade::evaluate_bundle_trade`). Each good's leg against the base
+    /// is recorded as its own trade event.
+    Bundle,
+    /// Like `AllPairsPruned`, but the `candidate_goods_k` goods are chosen epsilon-greedily by
+    /// each dyad's learned values of offering them (`trade::learned_candidate_goods`). After a
+    /// trade, each side's value for the good it gave moves towards its realized utility gain, at
+    /// `learning_rate`.
+    Learning {
+        #[serde(default = "default_epsilon")]
+        epsilon: f64,
+        #[serde(default = "default_learning_rate")]
+        learning_rate: f64,
+    },
 }
 
-impl Default for PairingMode {
-    fn default() -> Self { PairingMode::AgainstBase }
+/// How the two agents of each P2P encounter are drawn.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchingMode {
+    /// Both peers are drawn uniformly at random from the population.
+    #[default]
+    Uniform,
+    /// Agents are bucketed into `quantiles` wealth quantiles at the start of every round
+    /// (0 = poorest). `mixing[q][r]` is the relative weight with which an agent from
+    /// quantile `q` meets a partner from quantile `r`; rows need not be normalized.
+    Quantile {
+        quantiles: usize,
+        mixing: Vec<Vec<f64>>,
+    },
+    /// Encounters are drawn uniformly from the edges of a static random graph generated from
+    /// the seed when the engine starts.
+    Network(NetworkSpec),
+    /// Endogenous partner formation: `i` is drawn uniformly and meets `j` with weight
+    /// `baseline + w_ij`, where every executed trade adds `reinforcement` to the tie `w_ij`
+    /// and ties lose a fraction `decay` per round. Ties live in `SimState::partners`.
+    Persistent {
+        reinforcement: f64,
+        #[serde(default = "default_tie_baseline")]
+        baseline: f64,
+        #[serde(default)]
+        decay: f64,
+    },
+    /// Trust-weighted matching: both peers are drawn with weight `baseline + max(reputation, 0)`
+    /// (`Agent::reputation`, needs `SimConfig::reputation`).
+    Reputation {
+        #[serde(default = "default_tie_baseline")]
+        baseline: f64,
+    },
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+/// Random graph family for `MatchingMode::Network` (see the `network` module).
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum NetworkSpec {
+    /// Erdős–Rényi G(n, p): each pair is linked independently with probability `p`.
+    ErdosRenyi { p: f64 },
+    /// Watts–Strogatz small world: ring lattice of even degree `k`, edges rewired with
+    /// probability `beta`.
+    WattsStrogatz { k: usize, beta: f64 },
+    /// Barabási–Albert scale free: each new node attaches to `m` existing nodes.
+    BarabasiAlbert { m: usize },
+}
+
+/// Regulation shock: `goods` cannot be traded in rounds `from_round..=to_round`.
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct Embargo {
+    pub goods: Vec<GoodId>,
+    pub from_round: usize,
+    pub to_round: usize,
+}
+
+impl Embargo {
+    pub fn is_active(&self, round: usize) -> bool {
+        (self.from_round..=self.to_round).contains(&round)
+    }
+}
+
+/// Supply shock: at the start of `round`, a random `agent_fraction` of the population has its
+/// holdings of `goods` (all goods if empty) multiplied by independent lognormal factors with
+/// mean `mean` and log-standard deviation `sigma`.
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ShockSpec {
+    pub round: usize,
+    #[serde(default)]
+    pub goods: Vec<GoodId>,
+    #[serde(default = "default_shock_fraction")]
+    pub agent_fraction: f64,
+    #[serde(default = "default_shock_mean")]
+    pub mean: f64,
+    #[serde(default)]
+    pub sigma: f64,
+}
+
+/// Preference shock: at the start of `round`, a random `agent_fraction` of the population has
+/// its `alpha_to_base` for each of `goods` shifted by `shift` (kept inside (0, 1)), and `beta`
+/// re-derived; a positive shift is a demand surge for those goods.
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct PreferenceShockSpec {
+    pub round: usize,
+    pub goods: Vec<GoodId>,
+    #[serde(default = "default_shock_fraction")]
+    pub agent_fraction: f64,
+    pub shift: f64,
+}
+
+/// A parameter change taking effect at the start of `round` (`SimConfig::schedule`).
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ScheduledChange {
+    pub round: usize,
+    pub change: ParamChange,
+}
+
+/// Parameters that may change mid-run.
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ParamChange {
+    TradeStepCapFrac(f64),
+    StepCap(StepCap),
+    P2pEncountersPerRound(usize),
+    PairingMode(PairingMode),
+    CandidateGoodsK(usize),
+    /// New `alpha_low` / `alpha_high`. Only agents drawn afterwards (entrants under
+    /// `demography`) are affected; existing preferences are kept.
+    AlphaRange { low: f64, high: f64 },
+}
+
+impl ParamChange {
+    pub fn apply(&self, cfg: &mut SimConfig) {
+        match self {
+            ParamChange::TradeStepCapFrac(f) => cfg.trade_step_cap_frac = *f,
+            ParamChange::StepCap(s) => cfg.step_cap = s.clone(),
+            ParamChange::P2pEncountersPerRound(n) => cfg.p2p_encounters_per_round = *n,
+            ParamChange::PairingMode(m) => cfg.pairing_mode = m.clone(),
+            ParamChange::CandidateGoodsK(k) => cfg.candidate_goods_k = *k,
+            ParamChange::AlphaRange { low, high } => {
+                cfg.alpha_low = *low;
+                cfg.alpha_high = *high;
+            }
+        }
+    }
+}
+
+/// A good that enters the economy at the start of `round` (`SimConfig::new_goods`), e.g. an
+/// emerging service. It is appended after the existing goods, in order of `round`.
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct GoodIntroduction {
+    pub round: usize,
+    pub name: String,
+    /// Initial holdings of the agents that receive the good (times `initial_endowment_scale`).
+    #[serde(default)]
+    pub endowment: EndowmentSpec,
+    /// Fraction of agents that receive an initial endowment; the others start with none.
+    #[serde(default = "default_holders")]
+    pub holders: f64,
+    /// Range of the new good's `alpha_to_base`; defaults to the `SimConfig` range.
+    #[serde(default)]
+    pub alpha_low: Option<f64>,
+    #[serde(default)]
+    pub alpha_high: Option<f64>,
+    /// Depreciation rate of the new good (see `SimConfig::good_decay`).
+    #[serde(default)]
+    pub decay: f64,
+}
+
+/// How the encounters of a round are scheduled.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Scheduler {
+    /// Encounters run one after another, dyads drawn by `matching`.
+    #[default]
+    Sequential,
+    /// Round-robin tournament over a seeded permutation of the population: each sub-step is a
+    /// perfect matching, so its dyads are disjoint and can be searched in parallel (crate
+    /// feature `parallel`) with bitwise-reproducible results. `matching` is ignored.
+    RoundRobin,
+}
+
+/// How much of each proposed trade the engine executes.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum StepCap {
+    /// Every trade is scaled by `trade_step_cap_frac`.
+    #[default]
+    Fixed,
+    /// Per-trade fraction from the candidate's local curvature
+    /// (`TradeCandidate::adaptive_step_frac`), clamped to `[min_frac, max_frac]`.
+    Adaptive {
+        #[serde(default = "default_min_step_frac")]
+        min_frac: f64,
+        #[serde(default = "default_max_step_frac")]
+        max_frac: f64,
+    },
+}
+
+/// Per-trade friction (`SimConfig::transaction_cost`). Each side of a trade pays
+/// `fixed + proportional · value received`, in base-good units, with the received quantity
+/// valued at the payer's marginal rate of substitution to the base good.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema)]
+pub struct TransactionCost {
+    #[serde(default)]
+    pub proportional: f64,
+    #[serde(default)]
+    pub fixed: f64,
+    #[serde(default)]
+    pub settlement: CostSettlement,
+}
+
+/// Trade tax and redistribution (`SimConfig::policy`, see the `policy` module).
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema)]
+pub struct PolicySpec {
+    /// Share of the base good received in a trade that goes to the treasury.
+    pub tax_rate: f64,
+    #[serde(default)]
+    pub redistribution: Redistribution,
+}
+
+/// How the treasury is paid out at the end of every round.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Redistribution {
+    /// Equal transfer to every agent.
+    #[default]
+    Flat,
+    /// In proportion to each agent's `beta` for the base good.
+    ProportionalToBeta,
+    /// Equal transfer to the poorest 10% of agents by wealth at emergent prices.
+    PoorestDecile,
+}
+
+/// Monetary mode (`SimConfig::monetary`, see the `money` module): the base good is fiat money
+/// and each agent may owe up to `credit_limit` of it.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema)]
+pub struct MonetarySpec {
+    pub credit_limit: f64,
+    /// Share of its cash an indebted agent repays after each round's trading.
+    #[serde(default = "default_repayment_rate")]
+    pub repayment_rate: f64,
+}
+
+/// Kiyotaki–Wright style money emergence (`SimConfig::money_emergence`): no good is designated
+/// as money, and agents accept goods they do not want in proportion to how readily the rest of
+/// the population accepts them (see `trade::best_speculative_trade`).
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct MoneyEmergenceSpec {
+    /// Weight of resale value against the relative utility change.
+    #[serde(default = "default_speculation_weight")]
+    pub weight: f64,
+    /// Smoothing factor of the per-good marketability estimate (see `math::Ema`).
+    #[serde(default = "default_price_alpha")]
+    pub alpha: f64,
+    /// Fraction of the giver's holding offered in a speculative swap.
+    #[serde(default = "default_speculative_step")]
+    pub step: f64,
+}
+
+impl Default for MoneyEmergenceSpec {
+    fn default() -> Self {
+        MoneyEmergenceSpec { weight: default_speculation_weight(), alpha: default_price_alpha(), step: default_speculative_step() }
+    }
+}
+
+/// Intermediation (`SimConfig::brokers`): the first `round(share · num_agents)` agents act as
+/// brokers, quoting every good against the base good at a bid and an ask around the emergent
+/// price (see `broker`).
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct BrokerSpec {
+    /// Fraction of the initial population designated as brokers, in `(0, 1)`.
+    pub share: f64,
+    /// Relative bid–ask spread: quotes are `p · (1 ∓ spread / 2)`.
+    #[serde(default = "default_broker_spread")]
+    pub spread: f64,
+}
+
+/// Reputation dynamics (`SimConfig::reputation`). After every bilateral trade each side earns
+/// `honor_reward` plus `surplus_weight` times the utility its counterparty gained. An agent
+/// reneges on an agreed trade with probability `renege_prob`; the trade then does not happen
+/// and the reneging agent loses `breach_penalty`. Reputations shrink by `decay` per round.
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ReputationSpec {
+    #[serde(default = "default_surplus_weight")]
+    pub surplus_weight: f64,
+    #[serde(default = "default_honor_reward")]
+    pub honor_reward: f64,
+    #[serde(default)]
+    pub renege_prob: f64,
+    #[serde(default = "default_breach_penalty")]
+    pub breach_penalty: f64,
+    #[serde(default)]
+    pub decay: f64,
+}
+
+impl Default for ReputationSpec {
+    fn default() -> Self {
+        ReputationSpec {
+            surplus_weight: default_surplus_weight(),
+            honor_reward: default_honor_reward(),
+            renege_prob: 0.0,
+            breach_penalty: default_breach_penalty(),
+            decay: 0.0,
+        }
+    }
+}
+
+/// Asymmetric-information trade protocol (`SimConfig::negotiation`): agents see only each
+/// other's quotes, shaded away from their true MRS by `(1 - disclosure) / 2`. `disclosure = 1`
+/// shares the full preference information, `0` the least.
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct NegotiationSpec {
+    #[serde(default = "default_disclosure")]
+    pub disclosure: f64,
+}
+
+impl Default for NegotiationSpec {
+    fn default() -> Self {
+        NegotiationSpec { disclosure: default_disclosure() }
+    }
+}
+
+/// Oracle result cache (`SimConfig::oracle_cache`, `pareto_oracle::CachedWalrasOracle`): both
+/// alphas and i's shares of the two goods are rounded to cells of width `resolution`, and every
+/// dyad in a cell trades at the Walras price of the cell's centre, rescaled to its totals. A
+/// smaller `resolution` is more accurate and hits less often; `capacity` bounds the cells kept.
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct OracleCacheSpec {
+    #[serde(default = "default_oracle_cache_capacity")]
+    pub capacity: usize,
+    #[serde(default = "default_oracle_cache_resolution")]
+    pub resolution: f64,
+}
+
+impl Default for OracleCacheSpec {
+    fn default() -> Self {
+        OracleCacheSpec { capacity: default_oracle_cache_capacity(), resolution: default_oracle_cache_resolution() }
+    }
+}
+
+/// Decision noise (`SimConfig::decision_noise`): in every encounter each agent searches for a
+/// trade with its exponents misperceived as `β_k · exp(sigma · z_k)`, renormalized
+/// (`preferences::perturbed_beta`). The chosen trade executes and is scored with the true
+/// preferences, so noisy agents can accept trades that make them worse off.
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct DecisionNoise {
+    pub sigma: f64,
+}
+
+/// Zero-intelligence traders (`SimConfig::zero_intelligence`, Gode–Sunder): every dyad proposes a
+/// random good pair and random quantities within its holdings (`trade::zero_intelligence_trade`)
+/// instead of consulting the Pareto oracle. `constrained` (ZI-C, the default) keeps only
+/// proposals both sides gain from; without it (ZI-U) every proposal executes.
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ZeroIntelligenceSpec {
+    #[serde(default = "default_true")]
+    pub constrained: bool,
+}
+
+impl Default for ZeroIntelligenceSpec {
+    fn default() -> Self {
+        ZeroIntelligenceSpec { constrained: true }
+    }
+}
+
+/// Adaptive acceptance (`SimConfig::price_expectations`): every agent remembers the prices
+/// against the base good it traded at (an exponential average with weight `memory` on the
+/// latest price, `PriceMemory`) and refuses oracle trades (`trade::evaluate_pairwise_trade`)
+/// priced worse than that by more than `tolerance`, even if they would raise its utility.
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct PriceExpectationSpec {
+    #[serde(default = "default_price_memory")]
+    pub memory: f64,
+    #[serde(default = "default_price_tolerance")]
+    pub tolerance: f64,
+}
+
+impl Default for PriceExpectationSpec {
+    fn default() -> Self {
+        PriceExpectationSpec { memory: default_price_memory(), tolerance: default_price_tolerance() }
+    }
+}
+
+/// Preference evolution (`SimConfig::social_influence`): after each round's encounters, agents
+/// move their `alpha_to_base` towards those of partners who gained more from their trades, by
+/// `rate` (`dynamics::imitate`), so preferences co-evolve with allocations.
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct SocialInfluenceSpec {
+    pub rate: f64,
+}
+
+/// Posted-price marketplace (`SimConfig::posted_prices`, see `market`): sellers list `lot` of
+/// their holdings above the population mean at their own MRS times `1 + markup`, and buyers
+/// accept greedily by utility gain.
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct PostedPriceSpec {
+    #[serde(default = "default_markup")]
+    pub markup: f64,
+    #[serde(default = "default_lot")]
+    pub lot: f64,
+}
+
+impl Default for PostedPriceSpec {
+    fn default() -> Self {
+        PostedPriceSpec { markup: default_markup(), lot: default_lot() }
+    }
+}
+
+/// What a transaction cost takes out of the economy.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CostSettlement {
+    /// Paid out of the payer's base-good holdings.
+    #[default]
+    BaseGood,
+    /// Lost in transit: the payer receives that much less of the good it is buying.
+    Burned,
+}
+
+/// Distribution of initial holdings (see the `endowment` module). Every draw is multiplied by
+/// `SimConfig::initial_endowment_scale`.
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum EndowmentSpec {
+    /// Every good independently from `Uniform(low, high)`.
+    Uniform { low: f64, high: f64 },
+    /// Every good independently from the lognormal with the given mean and variance.
+    Lognormal { mean: f64, variance: f64 },
+    /// Every good independently from the Pareto distribution with tail index `shape` and
+    /// minimum `min`; heavy tailed, with infinite variance for `shape <= 2`.
+    Pareto { shape: f64, min: f64 },
+    /// Good `k` from the lognormal with mean `means[k]` and variance `variances[k]`.
+    PerGood { means: Vec<f64>, variances: Vec<f64> },
+    /// Each agent holds `high` of `goods` distinct goods chosen at random and `low` of all
+    /// others, so gains from trade come from specialization rather than noise.
+    Specialist {
+        #[serde(default = "default_specialist_goods")]
+        goods: usize,
+        #[serde(default = "default_specialist_high")]
+        high: f64,
+        #[serde(default = "default_specialist_low")]
+        low: f64,
+    },
+}
+
+impl Default for EndowmentSpec {
+    fn default() -> Self { EndowmentSpec::Uniform { low: 0.5, high: 2.0 } }
+}
+
+/// How initial preferences are drawn (`SimConfig::preference_generator`, see
+/// `preferences::generators`).
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PreferenceGenerator {
+    /// Every `alpha_to_base` i.i.d. uniform on `[alpha_low, alpha_high)`.
+    #[default]
+    Uniform,
+    /// `beta` from a symmetric Dirichlet; small concentrations give lopsided tastes, large ones
+    /// near-equal weights. Ignores `alpha_low` / `alpha_high`.
+    Dirichlet {
+        #[serde(default = "default_concentration")]
+        concentration: f64,
+    },
+    /// Agents fall into `clusters` taste clusters, each around a random centre profile, with
+    /// `cohesion` the Dirichlet precision (per good) around it. Members are labelled
+    /// `taste_cluster`. Ignores `alpha_low` / `alpha_high`.
+    Clustered {
+        clusters: usize,
+        #[serde(default = "default_cohesion")]
+        cohesion: f64,
+    },
+}
+
+/// A sub-population with its own parameters (`SimConfig::agent_groups`), e.g. producers and
+/// consumers. Unset fields fall back to the top-level `SimConfig` values.
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct AgentGroupSpec {
+    #[serde(default)]
+    pub name: String,
+    pub size: usize,
+    #[serde(default)]
+    pub endowment: Option<EndowmentSpec>,
+    #[serde(default)]
+    pub alpha_low: Option<f64>,
+    #[serde(default)]
+    pub alpha_high: Option<f64>,
+    #[serde(default)]
+    pub reaction_rules: Option<Vec<ReactionRuleSpec>>,
+}
+
+/// Per-round consumption and replenishment (`SimConfig::consumption`, see `dynamics`).
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ConsumptionSpec {
+    /// Average fraction of each good consumed per round, tilted towards high-`beta` goods.
+    #[serde(default)]
+    pub rate: f64,
+    /// Each round every agent receives `income` times a fresh draw from its endowment
+    /// distribution (times `initial_endowment_scale`).
+    #[serde(default)]
+    pub income: f64,
+}
+
+/// Agent entry and exit (`SimConfig::demography`), applied at the start of every round.
+///
+/// Entrants are drawn like initial agents (from `agent_groups` in proportion to their sizes, if
+/// any) and get fresh ids; positions in `SimState::agents`, and hence `TradeEvent::i` / `j`,
+/// refer to the population of the event's round, while `Agent::id` stays stable.
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct DemographySpec {
+    /// Expected entrants per round: `floor(rate)` for sure plus one more with probability
+    /// `fract(rate)`.
+    #[serde(default)]
+    pub entry_rate: f64,
+    /// Probability that each agent exits in a given round.
+    #[serde(default)]
+    pub exit_prob: f64,
+    #[serde(default)]
+    pub exit: ExitMode,
+    /// Exits are skipped once they would shrink the population below this size.
+    #[serde(default = "default_min_population")]
+    pub min_population: usize,
+}
+
+/// What happens to the holdings of an agent that exits.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ExitMode {
+    /// Holdings leave the economy with the agent.
+    #[default]
+    Remove,
+    /// Holdings are split equally among the remaining agents.
+    Redistribute,
+}
+
+/// Simulated wall clock: maps rounds onto calendar time so outputs line up with real-world
+/// dates and rates (trades per simulated week). Times are Unix seconds as `f64`, the same
+/// representation a real-time driver would stamp events with.
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ClockSpec {
+    /// Unix time at the start of round 0.
+    #[serde(default)]
+    pub start_unix: f64,
+    /// Simulated seconds per round.
+    pub round_secs: f64,
+}
+
+impl ClockSpec {
+    pub const DAY: f64 = 86_400.0;
+    pub const WEEK: f64 = 7.0 * Self::DAY;
+
+    /// Time at `fraction` (in [0, 1)) of the way through `round`.
+    pub fn at(&self, round: usize, fraction: f64) -> f64 {
+        self.start_unix + (round as f64 + fraction) * self.round_secs
+    }
+
+    /// Time at the start of `round`.
+    pub fn round_start(&self, round: usize) -> f64 {
+        self.at(round, 0.0)
+    }
+
+    /// Round in progress at Unix time `t` (0 before the start).
+    pub fn round_at(&self, t: f64) -> usize {
+        ((t - self.start_unix) / self.round_secs).floor().max(0.0) as usize
+    }
+
+    /// Convert a per-round count into a rate per `period` seconds, e.g.
+    /// `clock.per_period(trades, ClockSpec::WEEK)`.
+    pub fn per_period(&self, per_round: f64, period: f64) -> f64 {
+        per_round * period / self.round_secs
+    }
+}
+
+/// Early-stopping rule for `sim::run`.
+///
+/// A round is *quiet* when it executes no trade, or when the total utility change it produces
+/// (summed over all traders) is below `min_utility_change`. The run stops after `patience`
+/// consecutive quiet rounds.
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ConvergenceSpec {
+    #[serde(default = "default_patience")]
+    pub patience: usize,
+    #[serde(default)]
+    pub min_utility_change: f64,
+}
+
+/// Monte Carlo replication of a run: `runs` seeds starting at `SimConfig::seed` (the first
+/// replicate is the plain run), each per-round metric reported as its mean over the runs with a
+/// percentile-bootstrap confidence interval at level `confidence` from `bootstrap_samples`
+/// resamples.
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ReplicationSpec {
+    pub runs: usize,
+    #[serde(default = "default_confidence")]
+    pub confidence: f64,
+    #[serde(default = "default_bootstrap_samples")]
+    pub bootstrap_samples: usize,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
 pub struct SimConfig {
     pub seed: u64,
     pub num_agents: usize,
@@ -53,25 +811,298 @@ pub struct SimConfig {
     pub base_good: usize,
 
     pub initial_endowment_scale: f64,
+    /// Distribution of initial holdings; defaults to `Uniform(0.5, 2.0)`.
+    #[serde(default)]
+    pub endowment: EndowmentSpec,
+    /// Heterogeneous sub-populations, laid out contiguously in order (see
+    /// `sim::agent_group_ranges`); their sizes must add up to `num_agents`. Empty means one
+    /// homogeneous pool.
+    #[serde(default)]
+    pub agent_groups: Vec<AgentGroupSpec>,
+    /// Optional consumption / replenishment phase after every round's trading.
+    #[serde(default)]
+    pub consumption: Option<ConsumptionSpec>,
+    /// Optional agent entry and exit; the population is fixed without it.
+    #[serde(default)]
+    pub demography: Option<DemographySpec>,
     pub alpha_low: f64,
     pub alpha_high: f64,
+    /// Distribution of initial preferences; defaults to uniform alphas.
+    #[serde(default)]
+    pub preference_generator: PreferenceGenerator,
+    /// Rank correlation, in [-1, 1], imposed between each agent's holdings of a good and its
+    /// `alpha_to_base` for that good at initialization (a Gaussian copula within each group).
+    /// Positive means agents hold more of what they like.
+    #[serde(default)]
+    pub endowment_preference_correlation: f64,
 
     pub trade_step_cap_frac: f64,
+    /// Fixed (`trade_step_cap_frac`) or curvature-adaptive step cap.
+    #[serde(default)]
+    pub step_cap: StepCap,
+    /// Cost charged on every executed trade; trades whose gains do not cover it are refused.
+    #[serde(default)]
+    pub transaction_cost: Option<TransactionCost>,
+    /// Trade tax and redistribution of its revenue.
+    #[serde(default)]
+    pub policy: Option<PolicySpec>,
+    /// Treat the base good as money and let agents trade on credit.
+    #[serde(default)]
+    pub monetary: Option<MonetarySpec>,
+    /// When an encounter yields no bilateral trade, bring in a random third agent and try a
+    /// three-way cycle (sequential scheduler only).
+    #[serde(default)]
+    pub triads: Option<TriadSpec>,
+    /// Search all good pairs with speculative acceptance, letting a medium of exchange emerge;
+    /// `base_good` then only serves as numeraire for prices and wealth.
+    #[serde(default)]
+    pub money_emergence: Option<MoneyEmergenceSpec>,
+    /// Designate some agents as brokers that buy and sell at posted bid/ask quotes.
+    #[serde(default)]
+    pub brokers: Option<BrokerSpec>,
+    /// Close each round with a posted-price marketplace phase after the encounters.
+    #[serde(default)]
+    pub posted_prices: Option<PostedPriceSpec>,
+    /// Track a reputation per agent from realized surplus and honored commitments.
+    #[serde(default)]
+    pub reputation: Option<ReputationSpec>,
+    /// Trade from quotes instead of the full-information oracle (`trade::best_negotiated_trade`).
+    #[serde(default)]
+    pub negotiation: Option<NegotiationSpec>,
+    /// Bounded rationality: agents evaluate trades with misperceived preferences.
+    #[serde(default)]
+    pub decision_noise: Option<DecisionNoise>,
+    /// Shrink every trade just enough that no holding falls below `min_qty`, instead of raising
+    /// holdings to the floor (which creates goods), so each dyad conserves what it trades.
+    #[serde(default)]
+    pub conserve_quantities: bool,
+    /// Most executed trades (a package of goods or a three-way cycle counting once) any agent
+    /// takes part in per round, a time budget for providing services. Random encounters pass
+    /// over agents who have used theirs, so the round's remaining encounters rotate to the
+    /// rest instead of being absorbed by the most sought-after agents.
+    #[serde(default)]
+    pub max_trades_per_agent: Option<usize>,
+    /// Replace the oracle with random (zero-intelligence) proposals, as a baseline.
+    #[serde(default)]
+    pub zero_intelligence: Option<ZeroIntelligenceSpec>,
+    /// Agents learn expected prices and refuse trades priced far worse.
+    #[serde(default)]
+    pub price_expectations: Option<PriceExpectationSpec>,
+    /// Agents imitate the preferences of more successful trading partners.
+    #[serde(default)]
+    pub social_influence: Option<SocialInfluenceSpec>,
     pub min_qty: f64,
+    /// Bisection steps of the Walras oracle; the cap on them when `oracle_tol` is set.
     pub oracle_bisect_iters: usize,
+    /// Stop the oracle's bisection once excess demand for A, relative to the pair's supply of
+    /// it, is below this (0: take all `oracle_bisect_iters` steps).
+    #[serde(default)]
+    pub oracle_tol: f64,
+    /// Reuse oracle prices across nearly identical dyads (needs the `oracle-cache` feature).
+    #[serde(default)]
+    pub oracle_cache: Option<OracleCacheSpec>,
 
     #[serde(default)]
     pub pairing_mode: PairingMode,
     /// Used only when `pairing_mode = all_pairs_pruned`.
     #[serde(default = "default_candidate_goods_k")]
     pub candidate_goods_k: usize,
+    /// Who meets whom; defaults to uniform random matching.
+    #[serde(default)]
+    pub matching: MatchingMode,
+    #[serde(default)]
+    pub scheduler: Scheduler,
+    /// Per-good trading bans over round windows.
+    #[serde(default)]
+    pub embargoes: Vec<Embargo>,
+    /// Smoothing factor of the per-good EWMA price index (`prices::PriceTracker`), in (0, 1].
+    #[serde(default = "default_price_alpha")]
+    pub price_alpha: f64,
+    /// Regime changes: parameter overrides applied at the start of their round, in order.
+    #[serde(default)]
+    pub schedule: Vec<ScheduledChange>,
+    /// Goods introduced mid-run.
+    #[serde(default)]
+    pub new_goods: Vec<GoodIntroduction>,
+    /// Scheduled multiplicative endowment shocks.
+    #[serde(default)]
+    pub shocks: Vec<ShockSpec>,
+    /// Scheduled shifts of `alpha_to_base` (demand shocks).
+    #[serde(default)]
+    pub preference_shocks: Vec<PreferenceShockSpec>,
+    /// Optional simulated calendar; events and metrics carry timestamps when set.
+    #[serde(default)]
+    pub clock: Option<ClockSpec>,
+    /// Optional early stop once trading has dried up.
+    #[serde(default)]
+    pub stop_when_converged: Option<ConvergenceSpec>,
+    /// Optional Monte Carlo replication: the run repeated over consecutive seeds with per-round
+    /// metrics aggregated (see `replication`).
+    #[serde(default)]
+    pub replications: Option<ReplicationSpec>,
     
     // Incorporates Goods as config parameters
     #[serde(default)]
     pub base_goods: Vec<String>,
     #[serde(default)]
     pub base_goods_quantity: usize,
+    /// Per-round depreciation rate of each good, parallel to `base_goods` (perishable services
+    /// near 1, durables at 0). Empty means every good is storable.
+    #[serde(default)]
+    pub good_decay: Vec<f64>,
     pub reaction_rules: Vec<ReactionRuleSpec>,
 }
 
+impl SimConfig {
+    /// `base_goods` followed by the names of `new_goods` in order of introduction: the good
+    /// names for indices in the outputs of a run.
+    pub fn all_goods(&self) -> Vec<String> {
+        let mut goods = self.base_goods.clone();
+        goods.extend(self.introductions().into_iter().map(|g| g.name.clone()));
+        goods
+    }
+
+    /// `new_goods` in order of introduction (by round, ties in listed order).
+    pub fn introductions(&self) -> Vec<&GoodIntroduction> {
+        let mut v: Vec<&GoodIntroduction> = self.new_goods.iter().collect();
+        v.sort_by_key(|g| g.round);
+        v
+    }
+
+    /// JSON Schema (draft 7) of the config format, including every optional section, for
+    /// external validators and form generators. Doc comments become field descriptions.
+    pub fn json_schema() -> serde_json::Value {
+        serde_json::to_value(schemars::schema_for!(SimConfig)).expect("schemas serialize to JSON")
+    }
+
+    /// Every problem with the config's core parameters, rather than just the first: goods count
+    /// against `base_goods_quantity`, `base_good` bounds, alpha ranges (top-level and per group)
+    /// inside [0, 1], reaction rule goods against the goods of the run, and the step cap,
+    /// `min_qty` and bisection budget. Empty means these are usable; `sim::init_agents` still
+    /// checks the optional sections.
+    pub fn validate(&self) -> Vec<ConfigProblem> {
+        let mut problems = Vec::new();
+        let mut report = |field: String, message: String| problems.push(ConfigProblem { field, message });
+        let n = self.base_goods.len();
+        if n < 2 {
+            report("base_goods".into(), format!("need at least 2 goods, found {n}"));
+        }
+        if self.base_goods_quantity != n {
+            let quantity = self.base_goods_quantity;
+            report("base_goods_quantity".into(), format!("is {quantity} but base_goods lists {n}"));
+        }
+        if self.base_good >= n {
+            report("base_good".into(), format!("index {} out of range for {n} goods", self.base_good));
+        }
+        if self.num_agents < 2 {
+            report("num_agents".into(), format!("need at least 2 agents, found {}", self.num_agents));
+        }
+
+        let mut alpha_ranges = vec![("".to_string(), self.alpha_low, self.alpha_high)];
+        for (k, g) in self.agent_groups.iter().enumerate() {
+            if g.alpha_low.is_some() || g.alpha_high.is_some() {
+                let low = g.alpha_low.unwrap_or(self.alpha_low);
+                let high = g.alpha_high.unwrap_or(self.alpha_high);
+                alpha_ranges.push((format!("agent_groups[{k}]."), low, high));
+            }
+        }
+        for (prefix, low, high) in alpha_ranges {
+            if !(0.0..=1.0).contains(&low) || !(0.0..=1.0).contains(&high) || low >= high {
+                let message = format!("[{low}, {high}) must be a non-empty range within [0, 1]");
+                report(format!("{prefix}alpha_low"), message);
+            }
+        }
+
+        let goods = self.all_goods().len();
+        let mut rules: Vec<(String, &ReactionRuleSpec)> =
+            self.reaction_rules.iter().enumerate().map(|(k, r)| (format!("reaction_rules[{k}]"), r)).collect();
+        for (k, g) in self.agent_groups.iter().enumerate() {
+            for (r, rule) in g.reaction_rules.iter().flatten().enumerate() {
+                rules.push((format!("agent_groups[{k}].reaction_rules[{r}]"), rule));
+            }
+        }
+        for (field, rule) in rules {
+            let goods_used = std::iter::once(rule.lead)
+                .chain(rule.inputs.keys().copied())
+                .chain(rule.outputs.keys().copied());
+            for g in goods_used.filter(|&g| g >= goods) {
+                report(field.clone(), format!("rule `{}` refers to good {g}, but the run has {goods} goods", rule.id));
+            }
+        }
+
+        let cap = self.trade_step_cap_frac;
+        if !(cap > 0.0 && cap <= 1.0) {
+            report("trade_step_cap_frac".into(), format!("must lie in (0, 1], got {cap}"));
+        }
+        if !(self.min_qty > 0.0 && self.min_qty.is_finite()) {
+            report("min_qty".into(), format!("must be finite and > 0, got {}", self.min_qty));
+        }
+        if self.oracle_bisect_iters == 0 {
+            report("oracle_bisect_iters".into(), "must be at least 1".into());
+        }
+        if !(self.oracle_tol >= 0.0 && self.oracle_tol.is_finite()) {
+            report("oracle_tol".into(), format!("must be finite and >= 0, got {}", self.oracle_tol));
+        }
+        if self.max_trades_per_agent == Some(0) {
+            report("max_trades_per_agent".into(), "must be at least 1".into());
+        }
+        if let Some(c) = &self.oracle_cache {
+            if c.capacity == 0 {
+                report("oracle_cache.capacity".into(), "must be at least 1".into());
+            }
+            if !(c.resolution > 0.0 && c.resolution <= 0.5) {
+                report("oracle_cache.resolution".into(), format!("must lie in (0, 0.5], got {}", c.resolution));
+            }
+        }
+        problems
+    }
+}
+
+/// One issue found by `SimConfig::validate`: the offending field (a path such as
+/// `agent_groups[1].alpha_low`) and what is wrong with it.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ConfigProblem {
+    pub field: String,
+    pub message: String,
+}
+
+impl std::fmt::Display for ConfigProblem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.field, self.message)
+    }
+}
+
 fn default_candidate_goods_k() -> usize { 12 }
+fn default_patience() -> usize { 1 }
+fn default_tie_baseline() -> f64 { 1.0 }
+fn default_min_step_frac() -> f64 { 0.05 }
+fn default_max_step_frac() -> f64 { 1.0 }
+fn default_price_alpha() -> f64 { 0.2 }
+fn default_specialist_goods() -> usize { 1 }
+fn default_min_population() -> usize { 2 }
+fn default_shock_fraction() -> f64 { 1.0 }
+fn default_holders() -> f64 { 1.0 }
+fn default_repayment_rate() -> f64 { 0.1 }
+fn default_speculation_weight() -> f64 { 1.0 }
+fn default_speculative_step() -> f64 { 0.2 }
+fn default_broker_spread() -> f64 { 0.1 }
+fn default_markup() -> f64 { 0.1 }
+fn default_lot() -> f64 { 0.5 }
+fn default_surplus_weight() -> f64 { 1.0 }
+fn default_honor_reward() -> f64 { 0.1 }
+fn default_breach_penalty() -> f64 { 1.0 }
+fn default_disclosure() -> f64 { 0.5 }
+fn default_oracle_cache_capacity() -> usize { 65_536 }
+fn default_oracle_cache_resolution() -> f64 { 1e-3 }
+fn default_true() -> bool { true }
+fn default_epsilon() -> f64 { 0.1 }
+fn default_learning_rate() -> f64 { 0.2 }
+fn default_price_memory() -> f64 { 0.3 }
+fn default_price_tolerance() -> f64 { 0.1 }
+fn default_concentration() -> f64 { 1.0 }
+fn default_cohesion() -> f64 { 10.0 }
+fn default_confidence() -> f64 { 0.95 }
+fn default_bootstrap_samples() -> usize { 1000 }
+fn default_shock_mean() -> f64 { 1.0 }
+fn default_specialist_high() -> f64 { 4.0 }
+fn default_specialist_low() -> f64 { 0.25 }
@@ -0,0 +1,49 @@
+//! Monetary mode (`SimConfig::monetary`): the base good is fiat money and agents trade on credit.
+//!
+//! Before each round's trading every agent draws its unused credit line into base-good cash, so
+//! it can spend up to `credit_limit` beyond its own money; after trading it repays a share of its
+//! cash towards the outstanding debt. The net balance `e[base] - debt` can therefore go negative,
+//! but never below `-credit_limit`, and unspent money carries over to the next round.
+use crate::model::{Agent, TradeEvent};
+
+/// Draw the rest of `agent`'s credit line (`limit - debt`) into its base-good holdings. Returns
+/// the amount drawn.
+pub fn draw_credit(agent: &mut Agent, limit: f64, base_good: usize) -> f64 {
+    let Some(cash) = agent.e.get_mut(base_good) else { return 0.0 };
+    let draw = (limit - agent.debt).max(0.0);
+    *cash += draw;
+    agent.debt += draw;
+    agent.invalidate_utility();
+    draw
+}
+
+/// Repay `rate` of `agent`'s cash above `min_qty` towards its debt (never more than the debt).
+/// Returns the amount repaid.
+pub fn repay(agent: &mut Agent, rate: f64, base_good: usize, min_qty: f64) -> f64 {
+    let Some(cash) = agent.e.get_mut(base_good) else { return 0.0 };
+    let paid = ((*cash - min_qty).max(0.0) * rate.clamp(0.0, 1.0)).min(agent.debt);
+    *cash -= paid;
+    agent.debt -= paid;
+    agent.invalidate_utility();
+    paid
+}
+
+/// Money changing hands in `events`: the base-good leg of every trade that has one.
+pub fn money_volume(events: &[TradeEvent], base_good: usize) -> f64 {
+    events.iter()
+        .map(|ev| {
+            if ev.good_b.index() == base_good {
+                ev.delta_b_i.abs()
+            } else if ev.good_a.index() == base_good {
+                ev.delta_a_i.abs()
+            } else {
+                0.0
+            }
+        })
+        .sum()
+}
+
+/// Velocity of money over a round: money volume per unit of money stock (0 without money).
+pub fn velocity(volume: f64, stock: f64) -> f64 {
+    if stock > 0.0 { volume / stock } else { 0.0 }
+}
@@ -0,0 +1,165 @@
+//! Static social graphs for `MatchingMode::Network`.
+//!
+//! The graph is generated once per run from the seed; every encounter is then a uniformly drawn
+//! edge, so agents only ever trade with their neighbours.
+use rand::prelude::*;
+use serde::{Serialize, Deserialize};
+use std::collections::BTreeSet;
+use crate::error::RdxError;
+use crate::model::NetworkSpec;
+
+/// Undirected simple graph over agent indices `0..n`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Graph {
+    edges: Vec<(usize, usize)>,
+    adj: Vec<Vec<usize>>,
+}
+
+impl Graph {
+    /// Build from an edge list; self-loops and duplicate edges are dropped.
+    pub fn from_edges(n: usize, edges: impl IntoIterator<Item = (usize, usize)>) -> Result<Self, RdxError> {
+        let mut set = BTreeSet::new();
+        for (a, b) in edges {
+            if a >= n || b >= n {
+                return Err(RdxError::InvalidConfig(format!("edge ({a}, {b}) outside {n} nodes")));
+            }
+            if a != b {
+                set.insert((a.min(b), a.max(b)));
+            }
+        }
+        let mut adj = vec![Vec::new(); n];
+        for &(a, b) in set.iter() {
+            adj[a].push(b);
+            adj[b].push(a);
+        }
+        for nb in adj.iter_mut() {
+            nb.sort_unstable();
+        }
+        Ok(Graph { edges: set.into_iter().collect(), adj })
+    }
+
+    pub fn num_nodes(&self) -> usize { self.adj.len() }
+
+    /// Edges as `(a, b)` with `a < b`, sorted.
+    pub fn edges(&self) -> &[(usize, usize)] { &self.edges }
+
+    pub fn neighbors(&self, i: usize) -> &[usize] {
+        self.adj.get(i).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    pub fn degree(&self, i: usize) -> usize { self.neighbors(i).len() }
+
+    pub fn contains_edge(&self, a: usize, b: usize) -> bool {
+        self.neighbors(a).binary_search(&b).is_ok()
+    }
+
+    /// A uniformly random edge in random orientation (`None` if the graph has no edges).
+    pub fn draw_edge<R: Rng>(&self, rng: &mut R) -> Option<(usize, usize)> {
+        let &(a, b) = self.edges.choose(rng)?;
+        Some(if rng.gen::<bool>() { (a, b) } else { (b, a) })
+    }
+}
+
+/// Generate the graph described by `spec` over `n` nodes.
+pub fn generate<R: Rng>(spec: &NetworkSpec, n: usize, rng: &mut R) -> Result<Graph, RdxError> {
+    match *spec {
+        NetworkSpec::ErdosRenyi { p } => erdos_renyi(n, p, rng),
+        NetworkSpec::WattsStrogatz { k, beta } => watts_strogatz(n, k, beta, rng),
+        NetworkSpec::BarabasiAlbert { m } => barabasi_albert(n, m, rng),
+    }
+}
+
+fn check_probability(name: &str, p: f64) -> Result<(), RdxError> {
+    if (0.0..=1.0).contains(&p) {
+        Ok(())
+    } else {
+        Err(RdxError::InvalidConfig(format!("network {name} must lie in [0, 1], got {p}")))
+    }
+}
+
+/// G(n, p): each of the n(n-1)/2 pairs is linked independently with probability `p`.
+pub fn erdos_renyi<R: Rng>(n: usize, p: f64, rng: &mut R) -> Result<Graph, RdxError> {
+    check_probability("p", p)?;
+    let mut edges = Vec::new();
+    for a in 0..n {
+        for b in a + 1..n {
+            if rng.gen::<f64>() < p {
+                edges.push((a, b));
+            }
+        }
+    }
+    Graph::from_edges(n, edges)
+}
+
+/// Small world: a ring where every node links to its `k / 2` nearest neighbours on each side,
+/// then each lattice edge has its far end rewired to a uniform random node with probability
+/// `beta` (skipped if that would create a self-loop or duplicate edge).
+pub fn watts_strogatz<R: Rng>(n: usize, k: usize, beta: f64, rng: &mut R) -> Result<Graph, RdxError> {
+    check_probability("beta", beta)?;
+    if !k.is_multiple_of(2) || k == 0 || k >= n {
+        return Err(RdxError::InvalidConfig(format!(
+            "watts_strogatz needs an even k with 0 < k < n, got k = {k}, n = {n}"
+        )));
+    }
+    let mut adj: Vec<BTreeSet<usize>> = vec![BTreeSet::new(); n];
+    for a in 0..n {
+        for d in 1..=k / 2 {
+            let b = (a + d) % n;
+            adj[a].insert(b);
+            adj[b].insert(a);
+        }
+    }
+    for d in 1..=k / 2 {
+        for a in 0..n {
+            let b = (a + d) % n;
+            if !adj[a].contains(&b) || rng.gen::<f64>() >= beta {
+                continue;
+            }
+            let w = rng.gen_range(0..n);
+            if w == a || adj[a].contains(&w) {
+                continue;
+            }
+            adj[a].remove(&b);
+            adj[b].remove(&a);
+            adj[a].insert(w);
+            adj[w].insert(a);
+        }
+    }
+    let edges = adj.iter().enumerate()
+        .flat_map(|(a, nb)| nb.iter().filter(move |&&b| a < b).map(move |&b| (a, b)))
+        .collect::<Vec<_>>();
+    Graph::from_edges(n, edges)
+}
+
+/// Scale free: start from a clique on `m + 1` nodes, then attach every further node to `m`
+/// distinct existing nodes chosen with probability proportional to their degree.
+pub fn barabasi_albert<R: Rng>(n: usize, m: usize, rng: &mut R) -> Result<Graph, RdxError> {
+    if m == 0 || m >= n {
+        return Err(RdxError::InvalidConfig(format!(
+            "barabasi_albert needs 0 < m < n, got m = {m}, n = {n}"
+        )));
+    }
+    let mut edges = Vec::new();
+    // every edge endpoint once: sampling from it is degree-proportional
+    let mut endpoints = Vec::new();
+    for a in 0..=m {
+        for b in a + 1..=m {
+            edges.push((a, b));
+            endpoints.extend([a, b]);
+        }
+    }
+    for v in m + 1..n {
+        let mut targets = Vec::with_capacity(m);
+        while targets.len() < m {
+            let t = endpoints[rng.gen_range(0..endpoints.len())];
+            if !targets.contains(&t) {
+                targets.push(t);
+            }
+        }
+        for &t in targets.iter() {
+            edges.push((t, v));
+            endpoints.extend([t, v]);
+        }
+    }
+    Graph::from_edges(n, edges)
+}
@@ -0,0 +1,91 @@
+//! Layered configs: a base config document with override documents and `path=value`
+//! assignments merged on top before it is parsed and validated, so sweeps and per-experiment
+//! tweaks only state what differs (see `rdx-cli --overlay` / `--set`).
+//!
+//! Everything works on the JSON form; `layered` parses the result into a `SimConfig`.
+use serde_json::{Map, Value};
+use crate::error::RdxError;
+use crate::model::SimConfig;
+
+/// Key naming a parent document in a config file, resolved by the loader (e.g. `rdx-cli`):
+/// the file is merged over its parent as an overlay.
+pub const EXTENDS_KEY: &str = "extends";
+
+/// Merge `overlay` into `base`: objects merge key by key, recursively; anything else, arrays
+/// included, replaces the base value. A `null` removes the key, restoring its serde default.
+pub fn merge(base: &mut Value, overlay: Value) {
+    match (base, overlay) {
+        (Value::Object(b), Value::Object(o)) => {
+            for (k, v) in o {
+                if v.is_null() {
+                    b.remove(&k);
+                } else if let Some(slot) = b.get_mut(&k) {
+                    merge(slot, v);
+                } else {
+                    b.insert(k, v);
+                }
+            }
+        }
+        (b, o) => *b = o,
+    }
+}
+
+/// Apply one `path=value` assignment to `doc`. `path` is dot-separated: keys address object
+/// fields (created when missing or null; other values cannot be passed through) and numeric
+/// segments index existing array elements, e.g. `shocks.0.factor=0.5`. `value` is parsed as
+/// JSON, falling back to a plain string, so `pairing_mode=bundle` and `seed=9` both work.
+pub fn set(doc: &mut Value, assignment: &str) -> Result<(), RdxError> {
+    let (path, raw) = assignment.split_once('=').ok_or_else(|| {
+        RdxError::InvalidConfig(format!("expected path=value, got `{assignment}`"))
+    })?;
+    let path = path.trim();
+    if path.is_empty() {
+        return Err(RdxError::InvalidConfig(format!("empty path in `{assignment}`")));
+    }
+    let value = serde_json::from_str(raw.trim()).unwrap_or_else(|_| Value::String(raw.trim().to_string()));
+
+    let mut node = doc;
+    for (k, segment) in path.split('.').enumerate() {
+        node = match node {
+            Value::Array(items) => {
+                let len = items.len();
+                segment.parse::<usize>().ok()
+                    .and_then(|k| items.get_mut(k))
+                    .ok_or_else(|| RdxError::InvalidConfig(format!(
+                        "`{segment}` in `{path}` is not an index into an array of {len}"
+                    )))?
+            }
+            other => {
+                if other.is_null() {
+                    *other = Value::Object(Map::new());
+                }
+                let Value::Object(fields) = other else {
+                    let parent: Vec<&str> = path.split('.').take(k).collect();
+                    return Err(RdxError::InvalidConfig(format!("`{}` is not an object", parent.join("."))));
+                };
+                fields.entry(segment.to_string()).or_insert(Value::Null)
+            }
+        };
+    }
+    *node = value;
+    Ok(())
+}
+
+/// `base` with `overlays` merged in order and then `assignments` applied, parsed into a
+/// `SimConfig`. Validation is left to the caller (`SimConfig::validate`, `sim::init_agents`).
+pub fn layered(
+    mut base: Value,
+    overlays: impl IntoIterator<Item = Value>,
+    assignments: &[String],
+) -> Result<SimConfig, RdxError> {
+    for overlay in overlays {
+        merge(&mut base, overlay);
+    }
+    for assignment in assignments {
+        set(&mut base, assignment)?;
+    }
+    if let Value::Object(fields) = &mut base {
+        fields.remove(EXTENDS_KEY);
+    }
+    serde_json::from_value(base).map_err(|e| RdxError::InvalidConfig(format!("layered config: {e}")))
+}
@@ -1,16 +1,151 @@
-use crate::math::clamp01;
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicUsize, Ordering};
+use serde::{Serialize, Deserialize};
+use crate::math::{clamp01, clamp01_s, exp, ln, Scalar};
+use crate::error::RdxError;
+
+#[cfg(feature = "oracle-cache")]
+mod cache;
+
+#[cfg(feature = "oracle-cache")]
+pub use cache::CachedWalrasOracle;
+
+/// Range of price ratios pA/pB searched by `CobbDouglasWalrasOracle`.
+pub const PRICE_BRACKET: (f64, f64) = (1e-6, 1e6);
 
 /// Output of dyadic exchange oracle for two goods (A,B) between two agents i and j.
 #[derive(Clone, Debug)]
-pub struct DyadExchange {
+pub struct DyadExchange<S = f64> {
     /// Implied exchange rate / quotient Q_AB (interpreted here as price ratio pA/pB).
-    pub q_ab: f64,
+    pub q_ab: S,
     /// Post-trade quantities for agent i: (a_i', b_i')
-    pub ai_post: f64,
-    pub bi_post: f64,
+    pub ai_post: S,
+    pub bi_post: S,
     /// Post-trade quantities for agent j: (a_j', b_j')
-    pub aj_post: f64,
-    pub bj_post: f64,
+    pub aj_post: S,
+    pub bj_post: S,
+    /// Excess demand for A at `q_ab` relative to the pair's supply of A, `z / (a_i + a_j)`; far
+    /// from 0 when the solve did not converge (e.g. a price pinned at a `PRICE_BRACKET` end).
+    pub residual: S,
+    /// Bisection steps taken (0 for oracles that do not iterate).
+    pub iterations: usize,
+    /// Whether the solve is degenerate, unbracketed or clamped (see `OracleStatus`).
+    pub status: OracleStatus,
+}
+
+/// How far a `DyadExchange` can be trusted. Where several apply, the first listed is reported.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum OracleStatus {
+    /// Market-clearing price inside `PRICE_BRACKET`, every post-trade holding above `min_qty`.
+    #[default]
+    Ok,
+    /// An alpha at 0 or 1 after clamping (or not finite), or a non-finite holding: some demand
+    /// is nil or undefined, and the price says nothing about the agent's tastes.
+    Degenerate,
+    /// Excess demand for A does not change sign over `PRICE_BRACKET`, so `q_ab` is pinned at an
+    /// end of it rather than clearing the market.
+    Unbracketed,
+    /// Some post-trade holding was raised to `min_qty`, so the allocation leaves the budget
+    /// lines and does not conserve the pair's totals.
+    Clamped,
+}
+
+/// Status of a Cobb–Douglas exchange of the floored holdings `(ai, bi, aj, bj)` with clamped
+/// alphas `a_i`, `a_j`, whose post-trade holdings were `raw` before flooring at `min_qty`.
+fn exchange_status<S: Scalar>(a_i: S, a_j: S, held: [S; 4], raw: [S; 4], min_qty: S) -> OracleStatus {
+    let open = |a: S| a > S::ZERO && a < S::ONE;
+    if !(open(a_i) && open(a_j) && held.iter().all(|x| x.is_finite())) {
+        return OracleStatus::Degenerate;
+    }
+    let [ai, bi, aj, bj] = held;
+    let (lo, hi) = (S::from_f64(PRICE_BRACKET.0), S::from_f64(PRICE_BRACKET.1));
+    let z_lo = CobbDouglasWalrasOracle::excess_demand_a(a_i, ai, bi, a_j, aj, bj, lo);
+    let z_hi = CobbDouglasWalrasOracle::excess_demand_a(a_i, ai, bi, a_j, aj, bj, hi);
+    if !(z_lo > S::ZERO && z_hi < S::ZERO) {
+        return OracleStatus::Unbracketed;
+    }
+    if raw.iter().any(|&x| x < min_qty) {
+        return OracleStatus::Clamped;
+    }
+    OracleStatus::Ok
+}
+
+/// Per-status counts of the exchanges an `AuditedOracle` has returned.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OracleStatusCounts {
+    pub degenerate: usize,
+    pub unbracketed: usize,
+    pub clamped: usize,
+}
+
+/// An oracle that counts the statuses of its answers (the engine wraps its oracle in one to
+/// fill the `oracle_*` fields of `RoundMetrics`). Counting is order-independent, so counts are
+/// the same under the `parallel` scheduler.
+pub struct AuditedOracle {
+    inner: Box<dyn ParetoOracle>,
+    counts: [AtomicUsize; 3],
+}
+
+impl AuditedOracle {
+    pub fn new(inner: Box<dyn ParetoOracle>) -> Self {
+        AuditedOracle { inner, counts: Default::default() }
+    }
+
+    /// The wrapped oracle, to solve without counting.
+    pub fn inner(&self) -> &dyn ParetoOracle {
+        self.inner.as_ref()
+    }
+
+    /// Counts since the last call, resetting them.
+    pub fn take_counts(&self) -> OracleStatusCounts {
+        let [degenerate, unbracketed, clamped] = self.counts.each_ref().map(|c| c.swap(0, Ordering::Relaxed));
+        OracleStatusCounts { degenerate, unbracketed, clamped }
+    }
+}
+
+impl ParetoOracle for AuditedOracle {
+    fn solve_two_good_exchange(
+        &self,
+        alpha_i: f64, ai: f64, bi: f64,
+        alpha_j: f64, aj: f64, bj: f64,
+        min_qty: f64,
+        iters: usize,
+    ) -> DyadExchange<f64> {
+        let ex = self.inner.solve_two_good_exchange(alpha_i, ai, bi, alpha_j, aj, bj, min_qty, iters);
+        let slot = match ex.status {
+            OracleStatus::Ok => None,
+            OracleStatus::Degenerate => Some(0),
+            OracleStatus::Unbracketed => Some(1),
+            OracleStatus::Clamped => Some(2),
+        };
+        if let Some(k) = slot {
+            self.counts[k].fetch_add(1, Ordering::Relaxed);
+        }
+        ex
+    }
+
+    fn cache_stats(&self) -> Option<OracleCacheStats> {
+        self.inner.cache_stats()
+    }
+}
+
+/// Lookups of an oracle that caches its results (`ParetoOracle::cache_stats`).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OracleCacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    /// Entries dropped to stay within capacity.
+    pub evictions: u64,
+}
+
+impl OracleCacheStats {
+    /// Share of lookups answered from the cache (0 before any lookup).
+    pub fn hit_rate(&self) -> f64 {
+        let total = self.hits + self.misses;
+        if total == 0 { 0.0 } else { self.hits as f64 / total as f64 }
+    }
 }
 
 /// Trait boundary representing the endogenous functions:
@@ -18,15 +153,40 @@ pub struct DyadExchange {
 /// Q_AB = f(alpha_i, a_i, b_i, alpha_j, a_j, b_j)
 /// (e1_AB, e2_AB) = Exchange(..., Q_AB)
 ///
-/// Implementations must return a Pareto-optimal allocation for the two-good exchange.
-pub trait ParetoOracle: Send + Sync {
+/// Implementations must return a Pareto-optimal allocation for the two-good exchange. The
+/// number type defaults to the simulation's `f64`; the built-in oracles implement it for `f64`
+/// and solve in any `Scalar` through their `solve_two_good_exchange_s`.
+pub trait ParetoOracle<S: Scalar = f64>: Send + Sync {
+    #[allow(clippy::too_many_arguments)]
     fn solve_two_good_exchange(
         &self,
-        alpha_i: f64, ai: f64, bi: f64,
-        alpha_j: f64, aj: f64, bj: f64,
-        min_qty: f64,
+        alpha_i: S, ai: S, bi: S,
+        alpha_j: S, aj: S, bj: S,
+        min_qty: S,
         iters: usize,
-    ) -> DyadExchange;
+    ) -> DyadExchange<S>;
+
+    /// Running lookup counts, for oracles that cache their results (`None` otherwise).
+    fn cache_stats(&self) -> Option<OracleCacheStats> {
+        None
+    }
+}
+
+/// A boxed oracle (such as the engine's `Box<dyn ParetoOracle>`) solves as the oracle inside.
+impl<S: Scalar, O: ParetoOracle<S> + ?Sized> ParetoOracle<S> for Box<O> {
+    fn solve_two_good_exchange(
+        &self,
+        alpha_i: S, ai: S, bi: S,
+        alpha_j: S, aj: S, bj: S,
+        min_qty: S,
+        iters: usize,
+    ) -> DyadExchange<S> {
+        (**self).solve_two_good_exchange(alpha_i, ai, bi, alpha_j, aj, bj, min_qty, iters)
+    }
+
+    fn cache_stats(&self) -> Option<OracleCacheStats> {
+        (**self).cache_stats()
+    }
 }
 
 /// Default implementation: compute a Walrasian equilibrium for a 2-good exchange economy
@@ -35,11 +195,12 @@ pub trait ParetoOracle: Send + Sync {
 ///   u_j = a^{alpha_j} b^{1-alpha_j}
 ///
 /// This equilibrium is Pareto efficient. We find price ratio p = pA/pB by bisection on
-/// excess demand for good A, then compute final allocations via Marshallian demands.
+/// excess demand for good A, then compute final allocations via Marshallian demands. Only
+/// `+ - * /` and `sqrt` are involved, so the result is bit-identical on every IEEE 754 platform.
 pub struct CobbDouglasWalrasOracle;
 
 impl CobbDouglasWalrasOracle {
-    fn excess_demand_a(alpha_i: f64, ai: f64, bi: f64, alpha_j: f64, aj: f64, bj: f64, p: f64) -> f64 {
+    fn excess_demand_a<S: Scalar>(alpha_i: S, ai: S, bi: S, alpha_j: S, aj: S, bj: S, p: S) -> S {
         // numeraire pB = 1, so prices: pA = p, pB = 1.
         let wi = p * ai + bi;
         let wj = p * aj + bj;
@@ -49,34 +210,112 @@ impl CobbDouglasWalrasOracle {
 
         (di_a + dj_a) - (ai + aj)
     }
-}
 
-impl ParetoOracle for CobbDouglasWalrasOracle {
-    fn solve_two_good_exchange(
+    /// Checked `solve_two_good_exchange`: where the trait method clamps alphas into [0, 1] and
+    /// settles on a bracket end, this fails with `DegeneratePreferences` for a non-finite or
+    /// out-of-range alpha, and with `UnbracketedPrice` when excess demand for A does not change
+    /// sign over `PRICE_BRACKET` (e.g. neither agent values A, or both value only A).
+    #[allow(clippy::too_many_arguments)]
+    pub fn try_solve_two_good_exchange(
         &self,
         alpha_i: f64, ai: f64, bi: f64,
         alpha_j: f64, aj: f64, bj: f64,
         min_qty: f64,
         iters: usize,
+    ) -> Result<DyadExchange, RdxError> {
+        self.try_solve_two_good_exchange_s(alpha_i, ai, bi, alpha_j, aj, bj, min_qty, iters)
+    }
+
+    /// `try_solve_two_good_exchange` in any `Scalar`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn try_solve_two_good_exchange_s<S: Scalar>(
+        &self,
+        alpha_i: S, ai: S, bi: S,
+        alpha_j: S, aj: S, bj: S,
+        min_qty: S,
+        iters: usize,
+    ) -> Result<DyadExchange<S>, RdxError> {
+        for alpha in [alpha_i, alpha_j] {
+            if !(S::ZERO..=S::ONE).contains(&alpha) {
+                return Err(RdxError::DegeneratePreferences(format!("alpha {alpha} outside [0, 1]")));
+            }
+        }
+        let (lo, hi) = PRICE_BRACKET;
+        let (ai_, bi_, aj_, bj_) = (ai.max(min_qty), bi.max(min_qty), aj.max(min_qty), bj.max(min_qty));
+        let z_lo = Self::excess_demand_a(alpha_i, ai_, bi_, alpha_j, aj_, bj_, S::from_f64(lo));
+        let z_hi = Self::excess_demand_a(alpha_i, ai_, bi_, alpha_j, aj_, bj_, S::from_f64(hi));
+        if !(z_lo > S::ZERO && z_hi < S::ZERO) {
+            return Err(RdxError::UnbracketedPrice { lo, hi });
+        }
+        Ok(self.solve_two_good_exchange_s(alpha_i, ai, bi, alpha_j, aj, bj, min_qty, iters))
+    }
+
+    /// `solve_two_good_exchange` in any `Scalar`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn solve_two_good_exchange_s<S: Scalar>(
+        &self,
+        alpha_i: S, ai: S, bi: S,
+        alpha_j: S, aj: S, bj: S,
+        min_qty: S,
+        iters: usize,
+    ) -> DyadExchange<S> {
+        self.solve_to_tolerance_s(alpha_i, ai, bi, alpha_j, aj, bj, min_qty, S::ZERO, iters)
+    }
+}
+
+impl CobbDouglasWalrasOracle {
+    /// `solve_two_good_exchange` that stops as soon as the relative excess demand for A,
+    /// `|z| / (a_i + a_j)`, falls below `tol`, or after `max_iters` steps. With `tol` 0 it takes
+    /// all `max_iters` steps, as the trait method does.
+    #[allow(clippy::too_many_arguments)]
+    pub fn solve_to_tolerance(
+        &self,
+        alpha_i: f64, ai: f64, bi: f64,
+        alpha_j: f64, aj: f64, bj: f64,
+        min_qty: f64,
+        tol: f64,
+        max_iters: usize,
     ) -> DyadExchange {
+        self.solve_to_tolerance_s(alpha_i, ai, bi, alpha_j, aj, bj, min_qty, tol, max_iters)
+    }
+
+    /// `solve_to_tolerance` in any `Scalar`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn solve_to_tolerance_s<S: Scalar>(
+        &self,
+        alpha_i: S, ai: S, bi: S,
+        alpha_j: S, aj: S, bj: S,
+        min_qty: S,
+        tol: S,
+        max_iters: usize,
+    ) -> DyadExchange<S> {
         // Guard rails
         let ai = ai.max(min_qty);
         let bi = bi.max(min_qty);
         let aj = aj.max(min_qty);
         let bj = bj.max(min_qty);
 
-        let a_i = clamp01(alpha_i);
-        let a_j = clamp01(alpha_j);
+        let a_i = clamp01_s(alpha_i);
+        let a_j = clamp01_s(alpha_j);
 
         // Bracket pA/pB. We search p in [p_lo, p_hi] such that excess demand changes sign.
-        let mut p_lo: f64 = 1e-6;
-        let mut p_hi: f64 = 1e6;
+        let (mut p_lo, mut p_hi) = (S::from_f64(PRICE_BRACKET.0), S::from_f64(PRICE_BRACKET.1));
+
+        let supply = ai + aj;
+        let bound = tol * supply;
+        let mut converged = None;
+        let mut iterations = 0;
 
         // Bisection on p. (Excess demand is decreasing in p.)
-        for _ in 0..iters {
+        while iterations < max_iters {
+            iterations += 1;
             let p_mid = (p_lo * p_hi).sqrt(); // geometric mid improves scaling across magnitudes
             let z = Self::excess_demand_a(a_i, ai, bi, a_j, aj, bj, p_mid);
-            if z > 0.0 {
+            if z.abs() < bound {
+                converged = Some(p_mid);
+                break;
+            }
+            if z > S::ZERO {
                 // demand > supply => p too low
                 p_lo = p_mid;
             } else {
@@ -84,18 +323,290 @@ impl ParetoOracle for CobbDouglasWalrasOracle {
                 p_hi = p_mid;
             }
         }
-        let p = (p_lo * p_hi).sqrt();
+        let p = converged.unwrap_or_else(|| (p_lo * p_hi).sqrt());
+        let residual = Self::excess_demand_a(a_i, ai, bi, a_j, aj, bj, p) / supply;
+        trace_event!(
+            tracing::Level::TRACE, q_ab = p.to_f64(), iterations, residual = residual.to_f64(), "walras oracle"
+        );
 
         // Compute allocations at p, pB=1
         let wi = p * ai + bi;
         let wj = p * aj + bj;
 
-        let ai_post = (a_i * wi / p).max(min_qty);
-        let bi_post = ((1.0 - a_i) * wi).max(min_qty);
+        let raw = [a_i * wi / p, (S::ONE - a_i) * wi, a_j * wj / p, (S::ONE - a_j) * wj];
+        let status = exchange_status(a_i, a_j, [ai, bi, aj, bj], raw, min_qty);
+        let [ai_post, bi_post, aj_post, bj_post] = raw.map(|x| x.max(min_qty));
+
+        DyadExchange { q_ab: p, ai_post, bi_post, aj_post, bj_post, residual, iterations, status }
+    }
+}
+
+impl ParetoOracle for CobbDouglasWalrasOracle {
+    fn solve_two_good_exchange(
+        &self,
+        alpha_i: f64, ai: f64, bi: f64,
+        alpha_j: f64, aj: f64, bj: f64,
+        min_qty: f64,
+        iters: usize,
+    ) -> DyadExchange {
+        self.solve_two_good_exchange_s(alpha_i, ai, bi, alpha_j, aj, bj, min_qty, iters)
+    }
+}
+
+/// `CobbDouglasWalrasOracle` stopping at a relative excess demand below `tol`
+/// (`SimConfig::oracle_tol`); the trait's `iters` caps the bisection steps.
+#[derive(Clone, Copy, Debug)]
+pub struct TolerantWalrasOracle {
+    pub tol: f64,
+}
+
+impl TolerantWalrasOracle {
+    /// `solve_two_good_exchange` in any `Scalar`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn solve_two_good_exchange_s<S: Scalar>(
+        &self,
+        alpha_i: S, ai: S, bi: S,
+        alpha_j: S, aj: S, bj: S,
+        min_qty: S,
+        iters: usize,
+    ) -> DyadExchange<S> {
+        CobbDouglasWalrasOracle.solve_to_tolerance_s(
+            alpha_i, ai, bi, alpha_j, aj, bj, min_qty, S::from_f64(self.tol), iters,
+        )
+    }
+}
+
+impl ParetoOracle for TolerantWalrasOracle {
+    fn solve_two_good_exchange(
+        &self,
+        alpha_i: f64, ai: f64, bi: f64,
+        alpha_j: f64, aj: f64, bj: f64,
+        min_qty: f64,
+        iters: usize,
+    ) -> DyadExchange {
+        self.solve_two_good_exchange_s(alpha_i, ai, bi, alpha_j, aj, bj, min_qty, iters)
+    }
+}
+
+/// Baseline bargaining mechanism for A/B comparisons: the dyad trades at the geometric mean
+/// of the two agents' marginal rates of substitution, and the volume is set by the short side
+/// (the agent wanting the smaller adjustment gets exactly its demand, the other is rationed).
+///
+/// Both agents move along their budget line towards their demand, so the exchange is mutually
+/// beneficial, but unlike the Walras allocation it is generally not Pareto optimal.
+pub struct ShortSideOracle;
+
+impl ShortSideOracle {
+    /// `solve_two_good_exchange` in any `Scalar`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn solve_two_good_exchange_s<S: Scalar>(
+        &self,
+        alpha_i: S, ai: S, bi: S,
+        alpha_j: S, aj: S, bj: S,
+        min_qty: S,
+        _iters: usize,
+    ) -> DyadExchange<S> {
+        let ai = ai.max(min_qty);
+        let bi = bi.max(min_qty);
+        let aj = aj.max(min_qty);
+        let bj = bj.max(min_qty);
+
+        let eps = S::from_f64(1e-9);
+        let a_i = clamp01_s(alpha_i).clamp(eps, S::ONE - eps);
+        let a_j = clamp01_s(alpha_j).clamp(eps, S::ONE - eps);
+
+        // MRS_AB = (alpha / (1 - alpha)) * (b / a)
+        let mrs_i = a_i / (S::ONE - a_i) * bi / ai;
+        let mrs_j = a_j / (S::ONE - a_j) * bj / aj;
+        let p = (mrs_i * mrs_j).sqrt();
+
+        // Excess demand for A at p (pB = 1)
+        let zi = a_i * (p * ai + bi) / p - ai;
+        let zj = a_j * (p * aj + bj) / p - aj;
+
+        // Trade only when one wants to buy A and the other to sell it.
+        let v = if zi * zj < S::ZERO {
+            let v = zi.abs().min(zj.abs());
+            if zi < S::ZERO { -v } else { v }
+        } else {
+            S::ZERO
+        };
+
+        let raw = [ai + v, bi - p * v, aj - v, bj + p * v];
+        let open = |a: S| a > S::ZERO && a < S::ONE;
+        let status = if !(open(clamp01_s(alpha_i)) && open(clamp01_s(alpha_j)) && p.is_finite()) {
+            OracleStatus::Degenerate
+        } else if raw.iter().any(|&x| x < min_qty) {
+            OracleStatus::Clamped
+        } else {
+            OracleStatus::Ok
+        };
+        let [ai_post, bi_post, aj_post, bj_post] = raw.map(|x| x.max(min_qty));
+        DyadExchange {
+            q_ab: p,
+            ai_post,
+            bi_post,
+            aj_post,
+            bj_post,
+            residual: (zi + zj) / (ai + aj),
+            iterations: 0,
+            status,
+        }
+    }
+}
+
+impl ParetoOracle for ShortSideOracle {
+    fn solve_two_good_exchange(
+        &self,
+        alpha_i: f64, ai: f64, bi: f64,
+        alpha_j: f64, aj: f64, bj: f64,
+        min_qty: f64,
+        iters: usize,
+    ) -> DyadExchange {
+        self.solve_two_good_exchange_s(alpha_i, ai, bi, alpha_j, aj, bj, min_qty, iters)
+    }
+}
+
+/// An Edgeworth box for one dyad and good pair (A, B) as point series, in agent i's coordinates:
+/// i holds `(a, b)` measured from the lower-left corner, j holds `totals` minus that.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct EdgeworthTrace {
+    /// Width and height of the box: the dyad's total A and B.
+    pub totals: (f64, f64),
+    pub endowment: (f64, f64),
+    /// i's allocation chosen by the oracle, and the rate `q_ab` it clears at.
+    pub chosen: (f64, f64),
+    pub q_ab: f64,
+    /// Pareto-efficient allocations (equal marginal rates of substitution) from i's origin to j's.
+    pub contract_curve: Vec<(f64, f64)>,
+    /// Each agent's demand from the endowment as the price ratio pA/pB varies, over the prices
+    /// at which it stays inside the box.
+    pub offer_i: Vec<(f64, f64)>,
+    pub offer_j: Vec<(f64, f64)>,
+}
+
+/// `samples` (at least 2) evenly spaced points of [0, 1].
+fn unit_steps(samples: usize) -> impl Iterator<Item = f64> {
+    let n = samples.max(2);
+    (0..n).map(move |k| k as f64 / (n - 1) as f64)
+}
 
-        let aj_post = (a_j * wj / p).max(min_qty);
-        let bj_post = ((1.0 - a_j) * wj).max(min_qty);
+/// A two-agent allocation of A and B on the contract curve, at position `t` = a_i / (total A):
+/// 0 at i's origin (j holds everything), 1 at j's.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Allocation {
+    pub t: f64,
+    pub ai: f64,
+    pub bi: f64,
+    pub aj: f64,
+    pub bj: f64,
+}
+
+/// The Cobb–Douglas contract curve at `t`: b_i = k_j a_i B / (k_i (A - a_i) + k_j a_i) with
+/// k = alpha / (1 - alpha), alphas already clamped into (0, 1).
+fn contract_point(alpha_i: f64, alpha_j: f64, (ta, tb): (f64, f64), t: f64) -> Allocation {
+    let (ki, kj) = (alpha_i / (1.0 - alpha_i), alpha_j / (1.0 - alpha_j));
+    let ai = t * ta;
+    let bi = kj * ai * tb / (ki * (ta - ai) + kj * ai);
+    Allocation { t, ai, bi, aj: ta - ai, bj: tb - bi }
+}
+
+fn open_unit(alpha: f64) -> f64 {
+    let eps = 1e-9;
+    clamp01(alpha).clamp(eps, 1.0 - eps)
+}
+
+/// `samples` (at least 2) evenly spaced points of the contract curve of two Cobb–Douglas agents
+/// sharing `totals` of (A, B): every Pareto-efficient allocation, marginal rates of substitution
+/// equal. Alphas are clamped into (0, 1), where the curve is well defined.
+pub fn contract_curve(alpha_i: f64, alpha_j: f64, totals: (f64, f64), samples: usize) -> Vec<Allocation> {
+    let (alpha_i, alpha_j) = (open_unit(alpha_i), open_unit(alpha_j));
+    unit_steps(samples).map(|t| contract_point(alpha_i, alpha_j, totals, t)).collect()
+}
+
+/// The core of the dyad's exchange economy: the part of the contract curve where both agents
+/// are at least as well off as at their endowments (floored at `min_qty`), as `samples`
+/// allocations from the end where i just keeps its endowment utility to the end where j does.
+/// Along the curve i's utility rises and j's falls, so the bounds are found by bisection.
+#[allow(clippy::too_many_arguments)]
+pub fn core_segment(
+    alpha_i: f64, ai: f64, bi: f64,
+    alpha_j: f64, aj: f64, bj: f64,
+    min_qty: f64,
+    samples: usize,
+) -> Vec<Allocation> {
+    let (alpha_i, alpha_j) = (open_unit(alpha_i), open_unit(alpha_j));
+    let (ai, bi, aj, bj) = (ai.max(min_qty), bi.max(min_qty), aj.max(min_qty), bj.max(min_qty));
+    let totals = (ai + aj, bi + bj);
+    let log_u = |alpha: f64, a: f64, b: f64| alpha * ln(a.max(min_qty)) + (1.0 - alpha) * ln(b.max(min_qty));
+    let (ui, uj) = (log_u(alpha_i, ai, bi), log_u(alpha_j, aj, bj));
+    let at = |t| contract_point(alpha_i, alpha_j, totals, t);
+    let i_gains = |t| {
+        let p = at(t);
+        log_u(alpha_i, p.ai, p.bi) >= ui
+    };
+    let j_gains = |t| {
+        let p = at(t);
+        log_u(alpha_j, p.aj, p.bj) >= uj
+    };
+    // the t in [0, 1] where `flips(t)` turns from false to true
+    let boundary = |flips: &dyn Fn(f64) -> bool| {
+        let (mut lo, mut hi) = (0.0, 1.0);
+        for _ in 0..100 {
+            let mid = 0.5 * (lo + hi);
+            if flips(mid) { hi = mid } else { lo = mid }
+        }
+        0.5 * (lo + hi)
+    };
+    let t_lo = boundary(&i_gains);
+    let t_hi = boundary(&|t| !j_gains(t)).max(t_lo);
+    unit_steps(samples).map(|s| at(t_lo + s * (t_hi - t_lo))).collect()
+}
+
+/// Demand of an agent with `alpha` and endowment `(a, b)` over geometrically spaced prices pA/pB,
+/// restricted to the prices where it fits in a box of `totals`; in the agent's own coordinates.
+fn offer_points(alpha: f64, a: f64, b: f64, totals: (f64, f64), samples: usize) -> Vec<(f64, f64)> {
+    let eps = 1e-9;
+    let alpha = alpha.clamp(eps, 1.0 - eps);
+    let (ta, tb) = totals;
+    // demand for A is at most ta from p_lo up, demand for B at most tb up to p_hi
+    let p_lo = (alpha * b / (ta - alpha * a)).max(PRICE_BRACKET.0);
+    let p_hi = ((tb - (1.0 - alpha) * b) / ((1.0 - alpha) * a)).min(PRICE_BRACKET.1);
+    let (l0, l1) = (ln(p_lo), ln(p_hi.max(p_lo)));
+    unit_steps(samples)
+        .map(|t| {
+            let p = exp(l0 + t * (l1 - l0));
+            let w = p * a + b;
+            (alpha * w / p, (1.0 - alpha) * w)
+        })
+        .collect()
+}
 
-        DyadExchange { q_ab: p, ai_post, bi_post, aj_post, bj_post }
+/// Sample the Edgeworth box of agents i and j trading A against B: `samples` points per curve,
+/// the allocation `oracle` picks from the endowment, and the endowment itself (floored at
+/// `min_qty`, as the oracles do).
+#[allow(clippy::too_many_arguments)]
+pub fn edgeworth_trace(
+    oracle: &dyn ParetoOracle,
+    alpha_i: f64, ai: f64, bi: f64,
+    alpha_j: f64, aj: f64, bj: f64,
+    min_qty: f64,
+    iters: usize,
+    samples: usize,
+) -> EdgeworthTrace {
+    let (ai, bi, aj, bj) = (ai.max(min_qty), bi.max(min_qty), aj.max(min_qty), bj.max(min_qty));
+    let totals = (ai + aj, bi + bj);
+    let ex = oracle.solve_two_good_exchange(alpha_i, ai, bi, alpha_j, aj, bj, min_qty, iters);
+    let offer_j = offer_points(alpha_j, aj, bj, totals, samples).into_iter()
+        .map(|(a, b)| (totals.0 - a, totals.1 - b))
+        .collect();
+    EdgeworthTrace {
+        totals,
+        endowment: (ai, bi),
+        chosen: (ex.ai_post, ex.bi_post),
+        q_ab: ex.q_ab,
+        contract_curve: contract_curve(alpha_i, alpha_j, totals, samples).iter().map(|p| (p.ai, p.bi)).collect(),
+        offer_i: offer_points(alpha_i, ai, bi, totals, samples),
+        offer_j,
     }
 }
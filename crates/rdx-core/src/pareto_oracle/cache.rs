@@ -0,0 +1,201 @@
+//! `CachedWalrasOracle`: the Cobb–Douglas Walras oracle behind a bounded LRU cache of
+//! quantised dyads, for large runs where many encounters are nearly the same exchange.
+//!
+//! The Walras price pA/pB of a dyad is `tb / ta` times a function of the two alphas and of i's
+//! shares `ai / ta` and `bi / tb` of the pair's totals. Those four inputs are rounded to cells
+//! of width `resolution` (the cache key, with the bisection iterations); a miss solves the dyad
+//! at the cell's centre with unit totals and keeps that normalised price. Every lookup then
+//! rescales the price to its own totals, gives i its demand at that price and j the rest, so
+//! the pair's holdings are conserved exactly. A cached price is a function of the key alone,
+//! which keeps runs deterministic whatever the order of lookups or evictions.
+
+use std::collections::HashMap;
+use std::sync::{Mutex, PoisonError};
+use crate::math::clamp01;
+use super::{exchange_status, CobbDouglasWalrasOracle, DyadExchange, OracleCacheStats, ParetoOracle};
+
+/// Alphas of i and j, i's shares of A and B (cell indices), and the bisection iterations.
+type Key = [u32; 5];
+
+const NIL: usize = usize::MAX;
+
+struct Node {
+    key: Key,
+    price: f64,
+    prev: usize,
+    next: usize,
+}
+
+/// Least recently used first at `tail`; `slots` indexes `nodes`, which never shrinks.
+struct Lru {
+    capacity: usize,
+    slots: HashMap<Key, usize>,
+    nodes: Vec<Node>,
+    head: usize,
+    tail: usize,
+    stats: OracleCacheStats,
+}
+
+impl Lru {
+    fn unlink(&mut self, k: usize) {
+        let (prev, next) = (self.nodes[k].prev, self.nodes[k].next);
+        if prev == NIL { self.head = next } else { self.nodes[prev].next = next }
+        if next == NIL { self.tail = prev } else { self.nodes[next].prev = prev }
+    }
+
+    fn push_front(&mut self, k: usize) {
+        self.nodes[k].prev = NIL;
+        self.nodes[k].next = self.head;
+        if self.head != NIL {
+            self.nodes[self.head].prev = k;
+        }
+        self.head = k;
+        if self.tail == NIL {
+            self.tail = k;
+        }
+    }
+
+    fn get(&mut self, key: &Key) -> Option<f64> {
+        let k = *self.slots.get(key)?;
+        self.unlink(k);
+        self.push_front(k);
+        Some(self.nodes[k].price)
+    }
+
+    fn insert(&mut self, key: Key, price: f64) {
+        if self.slots.contains_key(&key) {
+            return;
+        }
+        let k = if self.nodes.len() < self.capacity {
+            self.nodes.push(Node { key, price, prev: NIL, next: NIL });
+            self.nodes.len() - 1
+        } else {
+            let k = self.tail;
+            self.unlink(k);
+            self.slots.remove(&self.nodes[k].key);
+            self.nodes[k].key = key;
+            self.nodes[k].price = price;
+            self.stats.evictions += 1;
+            k
+        };
+        self.slots.insert(key, k);
+        self.push_front(k);
+    }
+}
+
+pub struct CachedWalrasOracle {
+    inner: CobbDouglasWalrasOracle,
+    /// Misses solve to this relative excess demand (`CobbDouglasWalrasOracle::solve_to_tolerance`).
+    tol: f64,
+    cells: u32,
+    lru: Mutex<Lru>,
+}
+
+impl CachedWalrasOracle {
+    /// Keep up to `capacity` (at least 1) cells of width `resolution` per input.
+    pub fn new(capacity: usize, resolution: f64) -> Self {
+        let capacity = capacity.max(1);
+        let cells = (1.0 / resolution).round().clamp(1.0, f64::from(u32::MAX)) as u32;
+        CachedWalrasOracle {
+            inner: CobbDouglasWalrasOracle,
+            tol: 0.0,
+            cells,
+            lru: Mutex::new(Lru {
+                capacity,
+                slots: HashMap::with_capacity(capacity),
+                nodes: Vec::with_capacity(capacity),
+                head: NIL,
+                tail: NIL,
+                stats: OracleCacheStats::default(),
+            }),
+        }
+    }
+
+    /// Solve misses to relative excess demand `tol` (`SimConfig::oracle_tol`) instead of
+    /// taking every bisection step.
+    pub fn with_tolerance(mut self, tol: f64) -> Self {
+        self.tol = tol;
+        self
+    }
+
+    fn cell(&self, x: f64) -> u32 {
+        ((clamp01(x) * f64::from(self.cells)) as u32).min(self.cells - 1)
+    }
+
+    fn centre(&self, k: u32) -> f64 {
+        (f64::from(k) + 0.5) / f64::from(self.cells)
+    }
+
+    /// Walras price of the key's cell centre with unit totals, and the steps it took.
+    fn unit_price(&self, key: &Key) -> (f64, usize) {
+        let (sa, sb) = (self.centre(key[2]), self.centre(key[3]));
+        let unit = self.inner.solve_to_tolerance(
+            self.centre(key[0]), sa, sb,
+            self.centre(key[1]), 1.0 - sa, 1.0 - sb,
+            f64::MIN_POSITIVE,
+            self.tol,
+            key[4] as usize,
+        );
+        (unit.q_ab, unit.iterations)
+    }
+}
+
+impl ParetoOracle<f64> for CachedWalrasOracle {
+    fn solve_two_good_exchange(
+        &self,
+        alpha_i: f64, ai: f64, bi: f64,
+        alpha_j: f64, aj: f64, bj: f64,
+        min_qty: f64,
+        iters: usize,
+    ) -> DyadExchange<f64> {
+        let (ai, bi, aj, bj) = (ai.max(min_qty), bi.max(min_qty), aj.max(min_qty), bj.max(min_qty));
+        let (a_i, a_j) = (clamp01(alpha_i), clamp01(alpha_j));
+        let (ta, tb) = (ai + aj, bi + bj);
+        let key = [
+            self.cell(a_i),
+            self.cell(a_j),
+            self.cell(ai / ta),
+            self.cell(bi / tb),
+            u32::try_from(iters).unwrap_or(u32::MAX),
+        ];
+
+        let cached = {
+            let mut lru = self.lru.lock().unwrap_or_else(PoisonError::into_inner);
+            let price = lru.get(&key);
+            if price.is_some() { lru.stats.hits += 1 } else { lru.stats.misses += 1 }
+            price
+        };
+        let (unit, iterations) = cached.map_or_else(
+            || {
+                let (price, steps) = self.unit_price(&key);
+                self.lru.lock().unwrap_or_else(PoisonError::into_inner).insert(key, price);
+                (price, steps)
+            },
+            |price| (price, 0),
+        );
+
+        let p = unit * tb / ta;
+        let wi = p * ai + bi;
+        let wj = p * aj + bj;
+        let residual = (a_i * wi / p + a_j * wj / p - ta) / ta;
+        let (ai_raw, bi_raw) = (a_i * wi / p, (1.0 - a_i) * wi);
+        let ai_post = ai_raw.min(ta - min_qty).max(min_qty);
+        let bi_post = bi_raw.min(tb - min_qty).max(min_qty);
+        // j's share off its budget line (by the rounding of the price) is still conserved
+        let raw = [ai_raw, bi_raw, ta - ai_raw, tb - bi_raw];
+        DyadExchange {
+            q_ab: p,
+            ai_post,
+            bi_post,
+            aj_post: (ta - ai_post).max(min_qty),
+            bj_post: (tb - bi_post).max(min_qty),
+            residual,
+            iterations,
+            status: exchange_status(a_i, a_j, [ai, bi, aj, bj], raw, min_qty),
+        }
+    }
+
+    fn cache_stats(&self) -> Option<OracleCacheStats> {
+        Some(self.lru.lock().unwrap_or_else(PoisonError::into_inner).stats)
+    }
+}
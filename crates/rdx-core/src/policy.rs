@@ -0,0 +1,53 @@
+//! Fiscal policy (`SimConfig::policy`): a tax on the base-good leg of every trade, collected into
+//! a treasury (`SimState::treasury`) and paid back out at the end of each round.
+//!
+//! Trades between two non-base goods are untaxed. The tax is levied on the side that receives
+//! the base good, out of what it receives; redistribution hands the whole balance back in units
+//! of the base good, so goods are conserved over a round up to the tax still in the treasury.
+use crate::metrics::wealth_at_prices;
+use crate::model::{Agent, Redistribution};
+use crate::trade::TradeCandidate;
+
+/// Tax owed on `cand` at `rate`: `(paid by i, paid by j)`, at most one of them non-zero.
+pub fn trade_tax(cand: &TradeCandidate, rate: f64, base_good: usize) -> (f64, f64) {
+    let base_delta_i = if cand.good_b.index() == base_good {
+        cand.delta_b_i
+    } else if cand.good_a.index() == base_good {
+        cand.delta_a_i
+    } else {
+        return (0.0, 0.0);
+    };
+    let rate = rate.clamp(0.0, 1.0);
+    if base_delta_i > 0.0 { (rate * base_delta_i, 0.0) } else { (0.0, rate * -base_delta_i) }
+}
+
+/// Pay `amount` of the base good out to `agents` under `scheme`; returns the amount paid (all of
+/// it unless there is nobody to pay). `prices` rank agents by wealth for `PoorestDecile`.
+pub fn redistribute(agents: &mut [Agent], amount: f64, scheme: Redistribution, base_good: usize, prices: &[f64]) -> f64 {
+    if agents.is_empty() || amount <= 0.0 { return 0.0; }
+    let shares: Vec<f64> = match scheme {
+        Redistribution::Flat => vec![1.0; agents.len()],
+        Redistribution::ProportionalToBeta => {
+            agents.iter().map(|a| a.beta.get(base_good).copied().unwrap_or(0.0).max(0.0)).collect()
+        }
+        Redistribution::PoorestDecile => {
+            let wealth = wealth_at_prices(agents, prices);
+            let mut order: Vec<usize> = (0..agents.len()).collect();
+            order.sort_by(|&x, &y| wealth[x].total_cmp(&wealth[y]));
+            let mut shares = vec![0.0; agents.len()];
+            for &k in order.iter().take(agents.len().div_ceil(10)) {
+                shares[k] = 1.0;
+            }
+            shares
+        }
+    };
+    let total: f64 = shares.iter().sum();
+    if total <= 0.0 { return 0.0; }
+    for (a, s) in agents.iter_mut().zip(shares) {
+        if let Some(x) = a.e.get_mut(base_good) {
+            *x += amount * s / total;
+        }
+        a.invalidate_utility();
+    }
+    amount
+}
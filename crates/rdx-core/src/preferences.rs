@@ -1,4 +1,17 @@
-use crate::math::normalize;
+use alloc::format;
+use alloc::vec;
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use rand::Rng;
+use crate::math::{exp, ln, normalize, normalize_s, solve_linear, Scalar};
+#[cfg(feature = "std")]
+use crate::endowment::standard_normal;
+use crate::error::RdxError;
+#[cfg(feature = "std")]
+use crate::model::TradeEvent;
+
+#[cfg(feature = "std")]
+pub mod generators;
 
 /// Build an aggregated Cobb–Douglas exponent vector beta from per-good alphas
 /// against a fixed base good B (numeraire).
@@ -9,42 +22,297 @@ use crate::math::normalize;
 ///
 /// This construction is inherently cycle-consistent because all ratios are anchored
 /// to the same base good.
-pub fn beta_from_alpha_to_base(alpha_to_base: &[f64], base: usize, min_alpha: f64) -> Vec<f64> {
+///
+/// Fails if `base` is not an index into `alpha_to_base`, or with `DegeneratePreferences` if a
+/// non-base alpha is not finite (finite alphas are clamped to `[min_alpha, 1 - min_alpha]`).
+pub fn beta_from_alpha_to_base(alpha_to_base: &[f64], base: usize, min_alpha: f64) -> Result<Vec<f64>, RdxError> {
+    beta_from_alpha_to_base_s(alpha_to_base, base, min_alpha)
+}
+
+/// `beta_from_alpha_to_base` in any `Scalar`.
+pub fn beta_from_alpha_to_base_s<S: Scalar>(
+    alpha_to_base: &[S],
+    base: usize,
+    min_alpha: S,
+) -> Result<Vec<S>, RdxError> {
     let n = alpha_to_base.len();
-    assert!(base < n);
+    if base >= n {
+        return Err(RdxError::GoodOutOfRange { index: base, len: n });
+    }
 
     // set beta_B = 1 for convenience, then scale others by ratio
-    let beta_b = 1.0;
-    let mut beta = vec![0.0; n];
+    let beta_b = S::ONE;
+    let mut beta = vec![S::ZERO; n];
     beta[base] = beta_b;
 
     for k in 0..n {
         if k == base { continue; }
-        let a = alpha_to_base[k].clamp(min_alpha, 1.0 - min_alpha);
-        let ratio = a / (1.0 - a);
+        if !alpha_to_base[k].is_finite() {
+            return Err(RdxError::DegeneratePreferences(format!("alpha_to_base[{k}] is {}", alpha_to_base[k])));
+        }
+        let a = alpha_to_base[k].clamp(min_alpha, S::ONE - min_alpha);
+        let ratio = a / (S::ONE - a);
         beta[k] = ratio * beta_b;
     }
 
-    normalize(&mut beta);
-    beta
+    normalize_s(&mut beta);
+    Ok(beta)
+}
+
+/// Pairwise alphas `alpha_{AB} = beta_A / (beta_A + beta_B)` for arbitrary good pairs, e.g.
+/// elicited comparisons, rather than alphas anchored to one base good.
+///
+/// Such alphas come from a Cobb–Douglas profile only if they are cycle-consistent: in log-odds
+/// `l_AB = ln(alpha_AB / (1 - alpha_AB)) = ln beta_A - ln beta_B`, so `l_AB + l_BC = l_AC`
+/// around every cycle. `consistency_residual` measures the violation and `nearest_beta` projects
+/// the comparisons onto the closest profile; both fit `ln beta` by least squares over the
+/// compared pairs, and fail unless the comparisons connect all goods.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AlphaMatrix {
+    n: usize,
+    /// Row-major `n × n`; `None` for pairs that were not compared.
+    alpha: Vec<Option<f64>>,
+}
+
+impl AlphaMatrix {
+    /// `n` goods, no comparisons yet.
+    pub fn new(n: usize) -> Self {
+        AlphaMatrix { n, alpha: vec![None; n * n] }
+    }
+
+    /// All comparisons implied by `alpha_to_base` (see `beta_from_alpha_to_base`); consistent by
+    /// construction.
+    pub fn from_alpha_to_base(alpha_to_base: &[f64], base: usize, min_alpha: f64) -> Result<Self, RdxError> {
+        let beta = beta_from_alpha_to_base(alpha_to_base, base, min_alpha)?;
+        let mut m = AlphaMatrix::new(beta.len());
+        for a in 0..beta.len() {
+            for b in a + 1..beta.len() {
+                m.set(a, b, alpha_from_beta(&beta, a, b, min_alpha))?;
+            }
+        }
+        Ok(m)
+    }
+
+    /// Number of goods.
+    pub fn goods(&self) -> usize {
+        self.n
+    }
+
+    /// Record `alpha_{ab} = alpha` (and hence `alpha_{ba} = 1 - alpha`), replacing any earlier
+    /// comparison of the pair. `alpha` must lie strictly inside (0, 1).
+    pub fn set(&mut self, a: usize, b: usize, alpha: f64) -> Result<(), RdxError> {
+        for g in [a, b] {
+            if g >= self.n {
+                return Err(RdxError::GoodOutOfRange { index: g, len: self.n });
+            }
+        }
+        let inside = alpha > 0.0 && alpha < 1.0;
+        if a == b || !inside {
+            return Err(RdxError::InvalidConfig(format!(
+                "a pairwise alpha needs two distinct goods and a value in (0, 1), got ({a}, {b}) = {alpha}"
+            )));
+        }
+        self.alpha[a * self.n + b] = Some(alpha);
+        self.alpha[b * self.n + a] = Some(1.0 - alpha);
+        Ok(())
+    }
+
+    /// `alpha_{ab}`, if the pair was compared.
+    pub fn get(&self, a: usize, b: usize) -> Option<f64> {
+        if a >= self.n || b >= self.n { return None; }
+        self.alpha[a * self.n + b]
+    }
+
+    /// Compared pairs `a < b` with their log-odds `l_ab`.
+    fn log_odds(&self) -> Vec<(usize, usize, f64)> {
+        let mut pairs = Vec::new();
+        for a in 0..self.n {
+            for b in a + 1..self.n {
+                if let Some(alpha) = self.get(a, b) {
+                    pairs.push((a, b, ln(alpha / (1.0 - alpha))));
+                }
+            }
+        }
+        pairs
+    }
+
+    /// Least-squares `ln beta` (mean zero): solves the graph-Laplacian normal equations of
+    /// `min Σ (l_ab - x_a + x_b)²`, pinned by `Σ x = 0`.
+    fn fit_log_beta(&self) -> Result<Vec<f64>, RdxError> {
+        let n = self.n;
+        // the all-ones term fixes the free additive constant without changing the fit
+        let mut lap = vec![vec![1.0; n]; n];
+        let mut rhs = vec![0.0; n];
+        for (a, b, l) in self.log_odds() {
+            lap[a][a] += 1.0;
+            lap[b][b] += 1.0;
+            lap[a][b] -= 1.0;
+            lap[b][a] -= 1.0;
+            rhs[a] += l;
+            rhs[b] -= l;
+        }
+        solve_linear(&lap, &rhs).ok_or(RdxError::DisconnectedComparisons(n))
+    }
+
+    /// Root-mean-square log-odds error of the best-fitting Cobb–Douglas profile over the compared
+    /// pairs: 0 for cycle-consistent comparisons, growing with the violations.
+    pub fn consistency_residual(&self) -> Result<f64, RdxError> {
+        let x = self.fit_log_beta()?;
+        let pairs = self.log_odds();
+        if pairs.is_empty() { return Ok(0.0); }
+        let sq: f64 = pairs.iter().map(|&(a, b, l)| {
+            let r = l - (x[a] - x[b]);
+            r * r
+        }).sum();
+        Ok((sq / pairs.len() as f64).sqrt())
+    }
+
+    /// The beta vector (summing to 1) whose pairwise alphas are closest to the comparisons in
+    /// log-odds; reproduces them exactly when they are consistent.
+    pub fn nearest_beta(&self) -> Result<Vec<f64>, RdxError> {
+        let x = self.fit_log_beta()?;
+        let mut beta: Vec<f64> = x.iter().map(|&v| exp(v)).collect();
+        normalize(&mut beta);
+        Ok(beta)
+    }
+}
+
+/// Revealed-preference estimate of every agent's `beta` from a trade log.
+///
+/// `endow_history[r][k]` is agent `k`'s bundle at the start of round `r`'s encounters (as seen by
+/// `Observer::on_round_start`); each round's `events` are replayed on it in order to recover the
+/// bundle right after every trade. A Cobb–Douglas agent that trades `A` for `B` at `q_ab = p_A /
+/// p_B` up to its optimum spends the share `alpha_AB` of the pair's value on `A`, so each trade
+/// reveals `logit alpha_AB = ln(q_ab · a / b)` at its post-trade holdings `(a, b)`. Under
+/// Gaussian noise in log-odds, the maximum-likelihood `beta` is the least-squares fit of
+/// `AlphaMatrix::nearest_beta` to the per-pair mean of these samples.
+///
+/// Exact in the engine when trades go all the way to the dyad's optimum (`trade_step_cap_frac =
+/// 1`); partial steps stop short of it and bias the estimate towards the agent's initial MRS.
+/// Transaction costs, taxes and three-way cycles are not replayed. Agents whose trades do not
+/// connect all goods get `None`.
+#[cfg(feature = "std")]
+pub fn estimate_beta_from_events(
+    events: &[TradeEvent],
+    endow_history: &[Vec<Vec<f64>>],
+) -> Result<Vec<Option<Vec<f64>>>, RdxError> {
+    let agents = endow_history.first().map_or(0, |r| r.len());
+    let n = endow_history.first().and_then(|r| r.first()).map_or(0, |x| x.len());
+    // per agent, row-major n × n sums and counts of revealed log-odds for pairs a < b
+    let mut sums = vec![vec![0.0; n * n]; agents];
+    let mut counts = vec![vec![0usize; n * n]; agents];
+    let mut holdings: Vec<Vec<f64>> = Vec::new();
+    let mut round = None;
+    for ev in events.iter() {
+        if round != Some(ev.round) {
+            let start = endow_history.get(ev.round)
+                .ok_or(RdxError::DimensionMismatch { expected: ev.round + 1, found: endow_history.len() })?;
+            holdings = start.clone();
+            round = Some(ev.round);
+        }
+        let (i, j, a, b) = (ev.i.index(), ev.j.index(), ev.good_a.index(), ev.good_b.index());
+        for k in [i, j] {
+            if k >= holdings.len() || k >= agents {
+                return Err(RdxError::DimensionMismatch { expected: k + 1, found: holdings.len().min(agents) });
+            }
+        }
+        if a >= n || b >= n {
+            return Err(RdxError::GoodOutOfRange { index: a.max(b), len: n });
+        }
+        if let Some(x) = [&holdings[i], &holdings[j]].into_iter().find(|x| x.len() != n) {
+            return Err(RdxError::DimensionMismatch { expected: n, found: x.len() });
+        }
+        holdings[i][a] += ev.delta_a_i;
+        holdings[i][b] += ev.delta_b_i;
+        holdings[j][a] -= ev.delta_a_i;
+        holdings[j][b] -= ev.delta_b_i;
+        for k in [i, j] {
+            let l = ln(ev.q_ab * holdings[k][a] / holdings[k][b]);
+            if !l.is_finite() { continue; }
+            // store as (low, high) with the sign flipped when a > b
+            let (lo, hi, l) = if a < b { (a, b, l) } else { (b, a, -l) };
+            sums[k][lo * n + hi] += l;
+            counts[k][lo * n + hi] += 1;
+        }
+    }
+
+    let mut estimates = Vec::with_capacity(agents);
+    for (sum, count) in sums.iter().zip(counts.iter()) {
+        let mut m = AlphaMatrix::new(n);
+        for a in 0..n {
+            for b in a + 1..n {
+                let c = count[a * n + b];
+                if c == 0 { continue; }
+                let l = sum[a * n + b] / c as f64;
+                m.set(a, b, (1.0 / (1.0 + exp(-l))).clamp(1e-12, 1.0 - 1e-12))?;
+            }
+        }
+        estimates.push(m.nearest_beta().ok());
+    }
+    Ok(estimates)
 }
 
 /// Given full beta, derive the implied pairwise alpha_{AB} for a dyadic (A,B) evaluation:
 ///
 /// alpha_{AB} = beta_A / (beta_A + beta_B)
+///
+/// Indices outside `beta` are treated as zero weight.
 pub fn alpha_from_beta(beta: &[f64], a: usize, b: usize, min_alpha: f64) -> f64 {
-    let ba = beta[a].max(0.0);
-    let bb = beta[b].max(0.0);
-    let denom = (ba + bb).max(1e-18);
-    (ba / denom).clamp(min_alpha, 1.0 - min_alpha)
+    alpha_from_beta_s(beta, a, b, min_alpha)
+}
+
+/// `alpha_from_beta` in any `Scalar`.
+pub fn alpha_from_beta_s<S: Scalar>(beta: &[S], a: usize, b: usize, min_alpha: S) -> S {
+    let ba = beta.get(a).copied().unwrap_or(S::ZERO).max(S::ZERO);
+    let bb = beta.get(b).copied().unwrap_or(S::ZERO).max(S::ZERO);
+    let denom = (ba + bb).max(S::from_f64(1e-18));
+    (ba / denom).clamp(min_alpha, S::ONE - min_alpha)
 }
 
 /// Cobb–Douglas utility over n goods.
 pub fn cd_utility(beta: &[f64], x: &[f64], min_qty: f64) -> f64 {
-    // compute exp(sum beta_k log x_k)
-    let mut s = 0.0;
-    for (b, &xi) in beta.iter().zip(x.iter()) {
-        s += b * (xi.max(min_qty)).ln();
-    }
-    s.exp()
+    cd_utility_s(beta, x, min_qty)
+}
+
+/// `ln cd_utility(beta, x, min_qty)`: Σ β_k ln max(x_k, min_qty).
+pub fn log_cd_utility(beta: &[f64], x: &[f64], min_qty: f64) -> f64 {
+    log_cd_utility_s(beta, x, min_qty)
+}
+
+/// `cd_utility` in any `Scalar`.
+pub fn cd_utility_s<S: Scalar>(beta: &[S], x: &[S], min_qty: S) -> S {
+    log_cd_utility_s(beta, x, min_qty).exp()
+}
+
+/// `log_cd_utility` in any `Scalar`.
+pub fn log_cd_utility_s<S: Scalar>(beta: &[S], x: &[S], min_qty: S) -> S {
+    S::weighted_log_sum(beta, x, min_qty)
+}
+
+/// Change in holdings of good `g` that maximizes Cobb–Douglas utility over `(g, base)` when `g`
+/// trades at `price` base-good units, other goods held fixed: the optimum spends the share
+/// `beta_g / (beta_g + beta_base)` of the pair's value on `g`. Positive to buy, negative to sell;
+/// 0 if neither good is valued.
+pub fn demand_at_price(beta: &[f64], x: &[f64], g: usize, base: usize, price: f64, min_qty: f64) -> f64 {
+    demand_at_price_s(beta, x, g, base, price, min_qty)
+}
+
+/// `demand_at_price` in any `Scalar`.
+pub fn demand_at_price_s<S: Scalar>(beta: &[S], x: &[S], g: usize, base: usize, price: S, min_qty: S) -> S {
+    let bg = beta.get(g).copied().unwrap_or(S::ZERO).max(S::ZERO);
+    let bb = beta.get(base).copied().unwrap_or(S::ZERO).max(S::ZERO);
+    if bg + bb <= S::ZERO || price <= S::ZERO { return S::ZERO; }
+    let xg = x.get(g).copied().unwrap_or(S::ZERO).max(min_qty);
+    let xb = x.get(base).copied().unwrap_or(S::ZERO).max(min_qty);
+    bg / (bg + bb) * (price * xg + xb) / price - xg
+}
+
+/// `beta` as misperceived under decision noise: every exponent times `exp(sigma · z)` with `z`
+/// standard normal, renormalized to sum to 1. Perturbs the MRS and utility changes computed from
+/// it while keeping a valid Cobb–Douglas profile.
+#[cfg(feature = "std")]
+pub fn perturbed_beta<R: Rng>(beta: &[f64], sigma: f64, rng: &mut R) -> Vec<f64> {
+    let mut b: Vec<f64> = beta.iter().map(|&w| w * exp(sigma * standard_normal(rng))).collect();
+    normalize(&mut b);
+    b
 }
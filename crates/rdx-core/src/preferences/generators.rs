@@ -0,0 +1,82 @@
+//! Random preference profiles for initial populations (`SimConfig::preference_generator`).
+//!
+//! The default draws every `alpha_to_base` i.i.d. uniform. The alternatives draw `beta` directly:
+//! from a Dirichlet distribution, or around a few shared "taste cluster" centres so that agents
+//! of a cluster want similar goods. `rank_correlate` then optionally couples holdings and tastes
+//! through a Gaussian copula, keeping both marginal distributions intact.
+use rand::Rng;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use crate::endowment::standard_normal;
+use crate::math::{ln, normalize, powf};
+
+/// Gamma(`shape`, 1) by Marsaglia–Tsang; shapes below 1 are boosted by `U^(1/shape)`.
+pub fn gamma<R: Rng>(shape: f64, rng: &mut R) -> f64 {
+    if shape < 1.0 {
+        let u = 1.0 - rng.gen::<f64>();
+        return gamma(shape + 1.0, rng) * powf(u, 1.0 / shape);
+    }
+    let d = shape - 1.0 / 3.0;
+    let c = 1.0 / (9.0 * d).sqrt();
+    loop {
+        let z = standard_normal(rng);
+        let v = (1.0 + c * z).powi(3);
+        if v <= 0.0 { continue; }
+        let u = 1.0 - rng.gen::<f64>();
+        if ln(u) < 0.5 * z * z + d - d * v + d * ln(v) {
+            return d * v;
+        }
+    }
+}
+
+/// A draw from Dirichlet(`concentration`): independent gammas, normalized. Zero components are
+/// floored at a tiny positive weight so every good stays valued.
+pub fn dirichlet<R: Rng>(concentration: &[f64], rng: &mut R) -> Vec<f64> {
+    let mut beta: Vec<f64> = concentration.iter().map(|&a| gamma(a, rng).max(1e-12)).collect();
+    normalize(&mut beta);
+    beta
+}
+
+/// `clusters` taste centres over `n` goods, each a Dirichlet(1, ..., 1) draw. Drawn from their
+/// own stream seeded by `seed`, so initial agents and later entrants share the same centres.
+pub fn taste_centres(seed: u64, clusters: usize, n: usize) -> Vec<Vec<f64>> {
+    let mut rng = StdRng::seed_from_u64(seed ^ 0x7A57_EC1A_5E75);
+    (0..clusters).map(|_| dirichlet(&vec![1.0; n], &mut rng)).collect()
+}
+
+/// A member of the cluster centred on `centre`: Dirichlet(`cohesion · n · centre`), whose mean
+/// is `centre` and whose spread shrinks as `cohesion` grows.
+pub fn clustered_beta<R: Rng>(centre: &[f64], cohesion: f64, rng: &mut R) -> Vec<f64> {
+    let n = centre.len() as f64;
+    let concentration: Vec<f64> = centre.iter().map(|&c| (cohesion * n * c).max(1e-3)).collect();
+    dirichlet(&concentration, rng)
+}
+
+/// Reorder `x` and `y` (the same good's holdings and tastes across a population) so that their
+/// ranks follow a Gaussian copula with correlation `rho`: latent normal pairs `(z, rho · z +
+/// sqrt(1 - rho²) · w)` are drawn per position, and each vector is rearranged to share the rank
+/// order of its latent. The multisets of values are unchanged.
+pub fn rank_correlate<R: Rng>(x: &mut [f64], y: &mut [f64], rho: f64, rng: &mut R) {
+    let m = x.len().min(y.len());
+    let rho = rho.clamp(-1.0, 1.0);
+    let mut zx = Vec::with_capacity(m);
+    let mut zy = Vec::with_capacity(m);
+    for _ in 0..m {
+        let (z, w) = (standard_normal(rng), standard_normal(rng));
+        zx.push(z);
+        zy.push(rho * z + (1.0 - rho * rho).sqrt() * w);
+    }
+    reorder_by(&mut x[..m], &zx);
+    reorder_by(&mut y[..m], &zy);
+}
+
+/// Give the position with the k-th smallest `latent` the k-th smallest value.
+fn reorder_by(values: &mut [f64], latent: &[f64]) {
+    let mut sorted = values.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    let mut order: Vec<usize> = (0..latent.len()).collect();
+    order.sort_by(|&a, &b| latent[a].total_cmp(&latent[b]));
+    for (&pos, v) in order.iter().zip(sorted) {
+        values[pos] = v;
+    }
+}
@@ -0,0 +1,140 @@
+//! Emergent price index: decentralized price discovery versus the Walrasian benchmark.
+//!
+//! Every accepted trade implies a bilateral price `q_ab`. Each round the per-pair summaries of
+//! `sim::exchange_rate_stats` against the base good are folded into a per-good exponentially
+//! weighted estimate; alongside it the tracker records the Cobb–Douglas Walrasian equilibrium
+//! prices of the current allocation, so the two can be compared round by round.
+use serde::{Serialize, Deserialize};
+use crate::ids::GoodId;
+use crate::math::Ema;
+use crate::metrics::update_emergent_prices;
+use crate::model::Agent;
+use crate::sim::PairRateStat;
+
+/// One good's price after one round, in units of the base good.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PricePoint {
+    pub round: usize,
+    pub good: GoodId,
+    /// Volume-weighted mean `q_ab` against the base this round, if the pair traded.
+    pub observed: Option<f64>,
+    /// Exponentially weighted estimate over all rounds so far (1 until first traded).
+    pub ewma: f64,
+    /// Walrasian equilibrium price of the end-of-round allocation.
+    pub walras: f64,
+}
+
+/// Per-good price estimates maintained by the engine.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PriceTracker {
+    base_good: usize,
+    /// Last observed price per good (see `metrics::update_emergent_prices`).
+    last: Vec<f64>,
+    ewma: Vec<Ema>,
+}
+
+impl PriceTracker {
+    /// Tracker over `n_goods` goods with smoothing factor `alpha` (see `math::Ema`).
+    pub fn new(n_goods: usize, base_good: usize, alpha: f64) -> Self {
+        PriceTracker { base_good, last: vec![1.0; n_goods], ewma: vec![Ema::new(alpha); n_goods] }
+    }
+
+    /// Fold one round's exchange rates in and return the round's price points, with the
+    /// Walrasian benchmark computed from `agents`.
+    pub fn update(&mut self, round: usize, rates: &[PairRateStat], agents: &[Agent]) -> Vec<PricePoint> {
+        update_emergent_prices(&mut self.last, rates, self.base_good);
+        let mut observed = vec![None; self.last.len()];
+        for r in rates.iter().filter(|r| r.good_b.index() == self.base_good && r.mean_q_ab.is_finite()) {
+            if let Some(o) = observed.get_mut(r.good_a.index()) {
+                *o = Some(r.mean_q_ab);
+            }
+        }
+        if let Some(o) = observed.get_mut(self.base_good) {
+            *o = Some(1.0);
+        }
+        for (ema, o) in self.ewma.iter_mut().zip(observed.iter()) {
+            if let Some(q) = *o {
+                ema.push(q);
+            }
+        }
+        let walras = walras_prices(agents, self.base_good);
+        observed.into_iter().enumerate()
+            .map(|(k, observed)| PricePoint {
+                round,
+                good: GoodId(k),
+                observed,
+                ewma: self.ewma[k].value().unwrap_or(1.0),
+                walras: walras.get(k).copied().unwrap_or(1.0),
+            })
+            .collect()
+    }
+
+    /// Start tracking one more good (see `SimConfig::new_goods`).
+    pub fn add_good(&mut self) {
+        let alpha = self.ewma.first().map_or(1.0, Ema::alpha);
+        self.last.push(1.0);
+        self.ewma.push(Ema::new(alpha));
+    }
+
+    /// Last observed price per good.
+    pub fn last(&self) -> &[f64] { &self.last }
+
+    /// Current exponentially weighted estimate per good.
+    pub fn ewma(&self) -> Vec<f64> {
+        self.ewma.iter().map(|e| e.value().unwrap_or(1.0)).collect()
+    }
+}
+
+/// Cobb–Douglas demands at `prices`: agent `i` spends the share `β_ik` of its wealth `p · e_i`
+/// on good `k`. At `walras_prices` this is the competitive allocation.
+pub fn walras_allocation(agents: &[Agent], prices: &[f64]) -> Vec<Vec<f64>> {
+    agents.iter()
+        .map(|a| {
+            let wealth: f64 = a.e.iter().zip(prices.iter()).map(|(&x, &p)| x.max(0.0) * p).sum();
+            let beta_sum: f64 = a.beta.iter().map(|b| b.max(0.0)).sum();
+            if beta_sum <= 0.0 { return a.e.clone(); }
+            a.beta.iter().zip(prices.iter())
+                .map(|(&b, &p)| b.max(0.0) / beta_sum * wealth / p)
+                .collect()
+        })
+        .collect()
+}
+
+/// Competitive equilibrium prices of a Cobb–Douglas exchange economy, normalized so that
+/// `prices[base_good] = 1`.
+///
+/// Market clearing requires `p_k X_k = Σ_i β_ik (p · e_i)` for every good `k`, with `X_k` the
+/// aggregate supply; the prices are the fixed point of that map, found by damped iteration. Goods
+/// nobody holds get price 1.
+pub fn walras_prices(agents: &[Agent], base_good: usize) -> Vec<f64> {
+    let n_goods = agents.iter().map(|a| a.e.len()).max().unwrap_or(0);
+    let mut supply = vec![0.0; n_goods];
+    for a in agents.iter() {
+        for (s, &x) in supply.iter_mut().zip(a.e.iter()) {
+            *s += x.max(0.0);
+        }
+    }
+    let mut p = vec![1.0; n_goods];
+    for _ in 0..200 {
+        let mut demand = vec![0.0; n_goods];
+        for a in agents.iter() {
+            let wealth: f64 = a.e.iter().zip(p.iter()).map(|(&x, &pk)| x.max(0.0) * pk).sum();
+            let beta_sum: f64 = a.beta.iter().map(|b| b.max(0.0)).sum();
+            if beta_sum <= 0.0 { continue; }
+            for (d, &b) in demand.iter_mut().zip(a.beta.iter()) {
+                *d += b.max(0.0) / beta_sum * wealth;
+            }
+        }
+        let next: Vec<f64> = demand.iter().zip(supply.iter())
+            .map(|(&d, &s)| if s > 0.0 && d > 0.0 { d / s } else { 1.0 })
+            .collect();
+        let numeraire = next.get(base_good).copied().filter(|&x| x > 0.0).unwrap_or(1.0);
+        let moved = next.iter().zip(p.iter()).map(|(a, b)| (a / numeraire - b).abs() / b).fold(0.0, f64::max);
+        // damped step: the undamped map can cycle when holdings and tastes are complementary
+        for (pk, x) in p.iter_mut().zip(next.iter()) {
+            *pk = 0.5 * (*pk + x / numeraire);
+        }
+        if moved < 1e-10 { break; }
+    }
+    p
+}
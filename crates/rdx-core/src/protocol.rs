@@ -0,0 +1,348 @@
+//! Wire protocol for negotiating one dyadic exchange between two peers.
+//!
+//! A `Proposer` opens with `ProposeTrade`, carrying its published profile and an `Offer` (for
+//! example one found with `trade::evaluate_exchange`). The `Responder` accepts an offer that
+//! strictly improves its own utility; otherwise it answers with a `CounterOffer` negotiated by
+//! the oracle on both profiles, or rejects. Whoever receives `Accept` sends `Commit`, after
+//! which both sides hold the same `Committed` offer and settle it. As in `trade`, an offer is
+//! only accepted when it leaves the accepting side strictly better off and its holdings
+//! non-negative.
+//!
+//! Offers are always written from the proposer's side: `delta_a` and `delta_b` are what the
+//! proposer receives (one positive, one negative) and the responder receives their negation,
+//! in every message of the negotiation.
+
+use alloc::vec::Vec;
+use serde::{Serialize, Deserialize};
+use thiserror::Error;
+use crate::ids::GoodId;
+use crate::pareto_oracle::ParetoOracle;
+use crate::preferences::cd_utility;
+use crate::trade::{evaluate_exchange, TradeCandidate};
+
+/// Identifies one negotiation; chosen by the proposer and echoed in every message.
+pub type NegotiationId = u64;
+
+/// Exchange of goods A and B, seen from the proposer.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Offer {
+    pub good_a: GoodId,
+    pub good_b: GoodId,
+    /// Change of the proposer's holding of good A.
+    pub delta_a: f64,
+    /// Change of the proposer's holding of good B.
+    pub delta_b: f64,
+}
+
+impl Offer {
+    /// Price of A in units of B implied by the offer.
+    pub fn q_ab(&self) -> f64 {
+        -self.delta_b / self.delta_a
+    }
+
+    /// The same offer scaled by `frac` (partial execution).
+    pub fn scaled(self, frac: f64) -> Offer {
+        Offer { delta_a: self.delta_a * frac, delta_b: self.delta_b * frac, ..self }
+    }
+
+    /// Same goods and quantities up to a relative `1e-12`, so offers echoed through text
+    /// encodings (which may round the last bit) still match.
+    pub fn matches(&self, other: &Offer) -> bool {
+        let close = |x: f64, y: f64| (x - y).abs() <= 1e-12 * x.abs().max(y.abs());
+        self.good_a == other.good_a
+            && self.good_b == other.good_b
+            && close(self.delta_a, other.delta_a)
+            && close(self.delta_b, other.delta_b)
+    }
+
+    fn is_well_formed(&self, goods: usize) -> bool {
+        self.good_a != self.good_b
+            && self.good_a.index() < goods
+            && self.good_b.index() < goods
+            && self.delta_a.is_finite()
+            && self.delta_b.is_finite()
+            && self.delta_a * self.delta_b < 0.0
+    }
+}
+
+/// The candidate's exchange, with its `i` as the proposer.
+impl From<&TradeCandidate> for Offer {
+    fn from(c: &TradeCandidate) -> Offer {
+        Offer { good_a: c.good_a, good_b: c.good_b, delta_a: c.delta_a_i, delta_b: c.delta_b_i }
+    }
+}
+
+/// Exponents and holdings a peer discloses to its counterpart (possibly privatized, see
+/// `codec::privatize_beta`).
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Profile {
+    pub beta: Vec<f64>,
+    pub holdings: Vec<f64>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RejectReason {
+    /// The offer does not strictly improve the rejecting side.
+    NoGain,
+    /// Goods out of range, non-finite quantities or both deltas of one sign.
+    Malformed,
+    /// The agent is already negotiating another exchange.
+    Busy,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Message {
+    ProposeTrade { id: NegotiationId, offer: Offer, proposer: Profile },
+    CounterOffer { id: NegotiationId, offer: Offer },
+    Accept { id: NegotiationId, offer: Offer },
+    Reject { id: NegotiationId, reason: RejectReason },
+    Commit { id: NegotiationId, offer: Offer },
+}
+
+impl Message {
+    pub fn id(&self) -> NegotiationId {
+        match self {
+            Message::ProposeTrade { id, .. }
+            | Message::CounterOffer { id, .. }
+            | Message::Accept { id, .. }
+            | Message::Reject { id, .. }
+            | Message::Commit { id, .. } => *id,
+        }
+    }
+
+    fn kind(&self) -> &'static str {
+        match self {
+            Message::ProposeTrade { .. } => "propose_trade",
+            Message::CounterOffer { .. } => "counter_offer",
+            Message::Accept { .. } => "accept",
+            Message::Reject { .. } => "reject",
+            Message::Commit { .. } => "commit",
+        }
+    }
+}
+
+/// Where one side of a negotiation stands.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NegotiationState {
+    /// Nothing sent or received yet.
+    Open,
+    /// This side sent a proposal or counter-offer and awaits the answer.
+    Offered(Offer),
+    /// This side accepted the offer and awaits `Commit`.
+    Accepted(Offer),
+    /// Both sides agreed on the offer; settle it.
+    Committed(Offer),
+    Rejected(RejectReason),
+}
+
+impl NegotiationState {
+    pub fn is_finished(&self) -> bool {
+        matches!(self, NegotiationState::Committed(_) | NegotiationState::Rejected(_))
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            NegotiationState::Open => "open",
+            NegotiationState::Offered(_) => "offered",
+            NegotiationState::Accepted(_) => "accepted",
+            NegotiationState::Committed(_) => "committed",
+            NegotiationState::Rejected(_) => "rejected",
+        }
+    }
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum ProtocolError {
+    #[error("message for negotiation {found} received by negotiation {expected}")]
+    WrongNegotiation { expected: NegotiationId, found: NegotiationId },
+
+    #[error("unexpected {message} message in state {state}")]
+    Unexpected { state: &'static str, message: &'static str },
+
+    #[error("offers need two distinct goods in range and deltas of opposite sign")]
+    MalformedOffer,
+}
+
+/// A side's own (true) exponents and holdings, used to judge offers.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Party {
+    pub beta: Vec<f64>,
+    pub holdings: Vec<f64>,
+    pub min_qty: f64,
+}
+
+impl Party {
+    /// Utility change from receiving `sign * offer`, or `None` if that leaves a holding
+    /// negative.
+    fn gain(&self, offer: &Offer, sign: f64) -> Option<f64> {
+        let mut post = self.holdings.clone();
+        post[offer.good_a.index()] += sign * offer.delta_a;
+        post[offer.good_b.index()] += sign * offer.delta_b;
+        if post.iter().any(|&x| x < 0.0) {
+            return None;
+        }
+        Some(cd_utility(&self.beta, &post, self.min_qty) - cd_utility(&self.beta, &self.holdings, self.min_qty))
+    }
+
+    fn improves(&self, offer: &Offer, sign: f64) -> bool {
+        offer.is_well_formed(self.holdings.len()) && self.gain(offer, sign).is_some_and(|g| g > 0.0)
+    }
+}
+
+fn unexpected(state: &NegotiationState, message: &Message) -> ProtocolError {
+    ProtocolError::Unexpected { state: state.name(), message: message.kind() }
+}
+
+/// The side that opens a negotiation. It accepts a counter-offer that improves it and rejects
+/// any other; it never counters itself.
+#[derive(Clone, Debug)]
+pub struct Proposer {
+    id: NegotiationId,
+    party: Party,
+    profile: Profile,
+    state: NegotiationState,
+}
+
+impl Proposer {
+    /// `profile` is what the counterpart is told about this side; `party` is the truth.
+    pub fn new(id: NegotiationId, party: Party, profile: Profile) -> Proposer {
+        Proposer { id, party, profile, state: NegotiationState::Open }
+    }
+
+    pub fn id(&self) -> NegotiationId { self.id }
+
+    pub fn state(&self) -> &NegotiationState { &self.state }
+
+    /// Open the negotiation with `offer`.
+    pub fn propose(&mut self, offer: Offer) -> Result<Message, ProtocolError> {
+        if self.state != NegotiationState::Open {
+            return Err(ProtocolError::Unexpected { state: self.state.name(), message: "propose_trade" });
+        }
+        if !offer.is_well_formed(self.party.holdings.len()) {
+            return Err(ProtocolError::MalformedOffer);
+        }
+        self.state = NegotiationState::Offered(offer);
+        Ok(Message::ProposeTrade { id: self.id, offer, proposer: self.profile.clone() })
+    }
+
+    /// Advance on a message from the responder, returning the reply to send, if any.
+    pub fn handle(&mut self, message: &Message) -> Result<Option<Message>, ProtocolError> {
+        let id = self.id;
+        if message.id() != id {
+            return Err(ProtocolError::WrongNegotiation { expected: id, found: message.id() });
+        }
+        let (next, reply) = match (&self.state.clone(), message) {
+            (NegotiationState::Offered(o), Message::Accept { offer, .. }) if o.matches(offer) => {
+                (NegotiationState::Committed(*offer), Some(Message::Commit { id, offer: *offer }))
+            }
+            (NegotiationState::Offered(_), Message::CounterOffer { offer, .. }) => {
+                if self.party.improves(offer, 1.0) {
+                    (NegotiationState::Accepted(*offer), Some(Message::Accept { id, offer: *offer }))
+                } else {
+                    let reason = if offer.is_well_formed(self.party.holdings.len()) {
+                        RejectReason::NoGain
+                    } else {
+                        RejectReason::Malformed
+                    };
+                    (NegotiationState::Rejected(reason), Some(Message::Reject { id, reason }))
+                }
+            }
+            (NegotiationState::Offered(_), Message::Reject { reason, .. }) => {
+                (NegotiationState::Rejected(*reason), None)
+            }
+            (NegotiationState::Accepted(o), Message::Commit { offer, .. }) if o.matches(offer) => {
+                (NegotiationState::Committed(*offer), None)
+            }
+            (state, message) => return Err(unexpected(state, message)),
+        };
+        self.state = next;
+        Ok(reply)
+    }
+}
+
+/// The side that answers a proposal. It accepts an improving offer; otherwise, if `counter`
+/// is set, it counters once with the oracle's exchange on the proposer's profile and its own.
+pub struct Responder<'o> {
+    id: NegotiationId,
+    party: Party,
+    counter: bool,
+    oracle: &'o dyn ParetoOracle,
+    oracle_iters: usize,
+    state: NegotiationState,
+}
+
+impl<'o> Responder<'o> {
+    pub fn new(
+        id: NegotiationId,
+        party: Party,
+        counter: bool,
+        oracle: &'o dyn ParetoOracle,
+        oracle_iters: usize,
+    ) -> Responder<'o> {
+        Responder { id, party, counter, oracle, oracle_iters, state: NegotiationState::Open }
+    }
+
+    pub fn id(&self) -> NegotiationId { self.id }
+
+    pub fn state(&self) -> &NegotiationState { &self.state }
+
+    /// Advance on a message from the proposer, returning the reply to send, if any.
+    pub fn handle(&mut self, message: &Message) -> Result<Option<Message>, ProtocolError> {
+        let id = self.id;
+        if message.id() != id {
+            return Err(ProtocolError::WrongNegotiation { expected: id, found: message.id() });
+        }
+        let (next, reply) = match (&self.state.clone(), message) {
+            (NegotiationState::Open, Message::ProposeTrade { offer, proposer, .. }) => {
+                self.answer(offer, proposer)
+            }
+            (NegotiationState::Offered(o), Message::Accept { offer, .. }) if o.matches(offer) => {
+                (NegotiationState::Committed(*offer), Some(Message::Commit { id, offer: *offer }))
+            }
+            (NegotiationState::Offered(_), Message::Reject { reason, .. }) => {
+                (NegotiationState::Rejected(*reason), None)
+            }
+            (NegotiationState::Accepted(o), Message::Commit { offer, .. }) if o.matches(offer) => {
+                (NegotiationState::Committed(*offer), None)
+            }
+            (state, message) => return Err(unexpected(state, message)),
+        };
+        self.state = next;
+        Ok(reply)
+    }
+
+    fn answer(&self, offer: &Offer, proposer: &Profile) -> (NegotiationState, Option<Message>) {
+        let id = self.id;
+        if !offer.is_well_formed(self.party.holdings.len()) {
+            let reason = RejectReason::Malformed;
+            return (NegotiationState::Rejected(reason), Some(Message::Reject { id, reason }));
+        }
+        if self.party.improves(offer, -1.0) {
+            return (NegotiationState::Accepted(*offer), Some(Message::Accept { id, offer: *offer }));
+        }
+        let counter = self.counter.then(|| evaluate_exchange(
+            &proposer.beta,
+            &proposer.holdings,
+            &self.party.beta,
+            &self.party.holdings,
+            offer.good_a.index(),
+            offer.good_b.index(),
+            self.party.min_qty,
+            self.oracle_iters,
+            self.oracle,
+        ));
+        let counter = counter.flatten().map(|c| Offer::from(&c)).filter(|c| self.party.improves(c, -1.0));
+        match counter {
+            Some(counter) => {
+                (NegotiationState::Offered(counter), Some(Message::CounterOffer { id, offer: counter }))
+            }
+            None => {
+                let reason = RejectReason::NoGain;
+                (NegotiationState::Rejected(reason), Some(Message::Reject { id, reason }))
+            }
+        }
+    }
+}
@@ -13,7 +13,7 @@
 extern crate alloc;
 use alloc::collections::btree_map::BTreeMap;
 
-#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize, schemars::JsonSchema)]
 pub struct ReactionRuleSpec {
     pub id: String,
     pub size_class: String,   // or enum SizeClass with serde(rename_all="UPPERCASE")
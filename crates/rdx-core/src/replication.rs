@@ -0,0 +1,146 @@
+//! Monte Carlo replication: one config re-run over consecutive seeds, with every per-round
+//! metric summarized across the runs by its mean and a percentile-bootstrap confidence interval
+//! of that mean (`SimConfig::replications`).
+//!
+//! Replicate `k` runs with seed `SimConfig::seed + k`, so replicate 0 is the plain run. Runs
+//! stopped early by `stop_when_converged` contribute to the rounds they reached; `runs` in each
+//! aggregate row says how many did.
+
+use rand::prelude::*;
+use serde::{Serialize, Deserialize};
+use serde_json::Value;
+use crate::error::RdxError;
+use crate::model::{ReplicationSpec, SimConfig};
+use crate::sim::{Engine, RoundMetrics};
+
+/// One metric in one round, across replicates.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MetricAggregate {
+    pub round: usize,
+    pub metric: String,
+    /// Replicates that reached the round.
+    pub runs: usize,
+    pub mean: f64,
+    /// Sample standard deviation over the replicates (0 for a single run).
+    pub std_dev: f64,
+    /// Bootstrap confidence interval of the mean.
+    pub ci_low: f64,
+    pub ci_high: f64,
+}
+
+/// Per-round metrics of every replicate of `cfg`, in seed order. The replicates run
+/// concurrently with the `parallel` feature; results do not depend on it.
+pub fn replicate(cfg: &SimConfig, spec: &ReplicationSpec) -> Result<Vec<Vec<RoundMetrics>>, RdxError> {
+    let run = |k: usize| -> Result<Vec<RoundMetrics>, RdxError> {
+        let mut cfg = cfg.clone();
+        cfg.seed = cfg.seed.wrapping_add(k as u64);
+        cfg.replications = None;
+        let mut engine = Engine::new(cfg)?;
+        engine.run_to_end();
+        Ok(engine.finish().metrics)
+    };
+
+    #[cfg(feature = "parallel")]
+    let out = {
+        use rayon::prelude::*;
+        (0..spec.runs).into_par_iter().map(run).collect()
+    };
+    #[cfg(not(feature = "parallel"))]
+    let out = (0..spec.runs).map(run).collect();
+    out
+}
+
+/// The numeric scalar fields of `m`, sorted by name: counters and totals, plus the
+/// `inequality` measures under their own names. Per-good vectors and the round index and
+/// money good (identifiers rather than quantities) are left out.
+pub fn scalar_metrics(m: &RoundMetrics) -> Vec<(String, f64)> {
+    fn collect(value: &Value, out: &mut Vec<(String, f64)>) {
+        let Value::Object(fields) = value else { return };
+        for (name, v) in fields {
+            match v {
+                Value::Number(_) if name == "round" || name == "money_good" => {}
+                Value::Number(x) => out.extend(x.as_f64().map(|x| (name.clone(), x))),
+                Value::Object(_) => collect(v, out),
+                _ => {}
+            }
+        }
+    }
+    let mut out = Vec::new();
+    collect(&serde_json::to_value(m).expect("round metrics serialize to JSON"), &mut out);
+    out.sort_by(|a, b| a.0.cmp(&b.0));
+    out
+}
+
+/// Percentile-bootstrap confidence interval at level `confidence` for the mean of `values`,
+/// from `samples` resamples drawn with replacement. Collapses to the mean for fewer than two
+/// values.
+pub fn bootstrap_mean_ci<R: Rng>(
+    values: &[f64],
+    confidence: f64,
+    samples: usize,
+    rng: &mut R,
+) -> (f64, f64) {
+    let n = values.len();
+    if n == 0 {
+        return (0.0, 0.0);
+    }
+    let mean = values.iter().sum::<f64>() / n as f64;
+    if n < 2 || samples == 0 {
+        return (mean, mean);
+    }
+    let mut means: Vec<f64> = (0..samples)
+        .map(|_| (0..n).map(|_| values[rng.gen_range(0..n)]).sum::<f64>() / n as f64)
+        .collect();
+    means.sort_by(f64::total_cmp);
+    let tail = (1.0 - confidence.clamp(0.0, 1.0)) / 2.0;
+    let at = |q: f64| means[((q * samples as f64).floor() as usize).min(samples - 1)];
+    (at(tail), at(1.0 - tail))
+}
+
+/// Aggregate replicate metrics round by round, metrics sorted by name. A metric missing from
+/// some replicates (e.g. `time` without a clock) is aggregated over those that report it. The
+/// bootstrap draws from its own stream seeded with `seed`.
+pub fn aggregate(runs: &[Vec<RoundMetrics>], spec: &ReplicationSpec, seed: u64) -> Vec<MetricAggregate> {
+    let mut rng = StdRng::seed_from_u64(seed ^ 0xB007_57A9_0C1E);
+    let rounds = runs.iter().map(Vec::len).max().unwrap_or(0);
+    let mut out = Vec::new();
+    for t in 0..rounds {
+        let reached: Vec<Vec<(String, f64)>> = runs.iter()
+            .filter_map(|r| r.get(t))
+            .map(scalar_metrics)
+            .collect();
+        let mut names: Vec<&String> = reached.iter().flatten().map(|(name, _)| name).collect();
+        names.sort();
+        names.dedup();
+        for metric in names {
+            let values: Vec<f64> = reached.iter()
+                .filter_map(|r| r.iter().find(|(name, _)| name == metric).map(|p| p.1))
+                .collect();
+            let n = values.len() as f64;
+            let mean = values.iter().sum::<f64>() / n;
+            let std_dev = if values.len() < 2 {
+                0.0
+            } else {
+                (values.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (n - 1.0)).sqrt()
+            };
+            let (ci_low, ci_high) = bootstrap_mean_ci(&values, spec.confidence, spec.bootstrap_samples, &mut rng);
+            out.push(MetricAggregate {
+                round: t,
+                metric: metric.clone(),
+                runs: values.len(),
+                mean,
+                std_dev,
+                ci_low,
+                ci_high,
+            });
+        }
+    }
+    out
+}
+
+/// `replicate` then `aggregate` under `cfg.replications`; `None` when replication is not set.
+pub fn run_replications(cfg: &SimConfig) -> Result<Option<Vec<MetricAggregate>>, RdxError> {
+    let Some(spec) = &cfg.replications else { return Ok(None) };
+    let runs = replicate(cfg, spec)?;
+    Ok(Some(aggregate(&runs, spec, cfg.seed)))
+}
@@ -0,0 +1,81 @@
+//! Curated quick-start configurations, addressable by name from the library and from
+//! `rdx-cli run --preset <name>`.
+//!
+//! Every preset is a complete `SimConfig` that passes the engine's config checks; tweak the
+//! returned value for variations.
+use crate::error::RdxError;
+use crate::ids::GoodId;
+use crate::model::{MatchingMode, PairingMode, ShockSpec, SimConfig};
+
+/// Names accepted by `preset`.
+pub const PRESETS: &[&str] = &["barter_demo", "services_economy", "shock_and_recovery", "two_community"];
+
+/// Look up a preset by name.
+pub fn preset(name: &str) -> Result<SimConfig, RdxError> {
+    match name {
+        "barter_demo" => Ok(barter_demo()),
+        "services_economy" => Ok(services_economy()),
+        "shock_and_recovery" => Ok(shock_and_recovery()),
+        "two_community" => Ok(two_community()),
+        other => Err(RdxError::InvalidConfig(format!(
+            "unknown preset `{other}` (available: {})", PRESETS.join(", ")
+        ))),
+    }
+}
+
+/// Common skeleton; unspecified fields take the `SimConfig::builder` defaults.
+fn skeleton(seed: u64, num_agents: usize, rounds: usize, encounters: usize, goods: Vec<String>) -> SimConfig {
+    SimConfig::builder()
+        .seed(seed)
+        .agents(num_agents)
+        .rounds(rounds)
+        .encounters_per_round(encounters)
+        .goods(goods)
+        .build()
+        .expect("preset skeleton is valid")
+}
+
+/// Four goods, twenty agents: small enough to read every trade.
+fn barter_demo() -> SimConfig {
+    let goods = ["Credits", "Data labeling", "Human review", "Safety audit"]
+        .iter().map(|s| s.to_string()).collect();
+    skeleton(1, 20, 20, 40, goods)
+}
+
+/// A 64-good services economy (the paper's taxonomy size) with pruned all-pairs search.
+fn services_economy() -> SimConfig {
+    let mut goods = vec!["Credits".to_string()];
+    goods.extend((1..64).map(|k| format!("Service {k}")));
+    let mut cfg = skeleton(42, 1000, 50, 3000, goods);
+    cfg.pairing_mode = PairingMode::AllPairsPruned;
+    cfg.candidate_goods_k = 12;
+    cfg
+}
+
+/// A supply disruption: at round 20 of 60, half the population loses most of its holdings of
+/// three services (lognormal factors of mean 0.3), leaving 40 rounds to re-converge.
+fn shock_and_recovery() -> SimConfig {
+    let mut goods = vec!["Credits".to_string()];
+    goods.extend((1..8).map(|k| format!("Service {k}")));
+    let mut cfg = skeleton(11, 200, 60, 400, goods);
+    cfg.shocks = vec![ShockSpec {
+        round: 20,
+        goods: vec![GoodId(1), GoodId(2), GoodId(3)],
+        agent_fraction: 0.5,
+        mean: 0.3,
+        sigma: 0.2,
+    }];
+    cfg
+}
+
+/// Two wealth communities that mostly trade internally (5% cross-community encounters).
+fn two_community() -> SimConfig {
+    let mut goods = vec!["Credits".to_string()];
+    goods.extend((1..8).map(|k| format!("Service {k}")));
+    let mut cfg = skeleton(7, 200, 40, 400, goods);
+    cfg.matching = MatchingMode::Quantile {
+        quantiles: 2,
+        mixing: vec![vec![0.95, 0.05], vec![0.05, 0.95]],
+    };
+    cfg
+}
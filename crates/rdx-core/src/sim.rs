@@ -1,122 +1,2425 @@
 use rand::prelude::*;
-use crate::model::{Agent, SimConfig, TradeEvent, PairingMode};
-use crate::preferences::{beta_from_alpha_to_base, cd_utility};
-use crate::trade::{best_trade_against_base, best_trade_over_all_pairs_pruned, apply_trade, default_oracle};
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use serde::{Serialize, Deserialize};
+use crate::model::{
+    Agent, CostSettlement, CycleEvent, EndowmentSpec, ExitMode, GoodIntroduction, PreferenceGenerator, PriceMemory,
+    SimConfig, TradeEvent, PairingMode, MatchingMode, Scheduler, StepCap,
+};
+use crate::preferences::{alpha_from_beta, beta_from_alpha_to_base, cd_utility, generators, perturbed_beta};
+use crate::trade::{
+    best_trade_against_base_with, best_trade_over_all_pairs_pruned_with, best_speculative_trade, apply_trade,
+    apply_trade_with_cost, apply_three_way_cycle, best_three_way_cycle, apply_bundle_trade, evaluate_bundle_trade,
+    best_negotiated_trade, zero_intelligence_trade, best_trade_over_goods_with, learned_candidate_goods,
+    learn_offer_value, base_price, build_oracle, scale_and_validate, scale_and_validate_bundle, conserving_fraction,
+    charged_fraction, apply_trade_with_cost_conserving,
+    bundle_conserving_fraction, apply_trade_conserving, apply_bundle_trade_conserving, BundleTrade, Marketability,
+    TradeCandidate, TradeRules,
+};
+use crate::matching::{Matcher, PartnerGraph, round_robin_pairs};
+use crate::dynamics;
+use crate::broker::{broker_trade, Quotes};
+use crate::market;
+use crate::money;
+use crate::policy;
+use crate::endowment;
+use crate::network::{self, Graph};
+use crate::pareto_oracle::{AuditedOracle, OracleCacheStats, ParetoOracle};
+use crate::error::RdxError;
+use crate::ids::{AgentIdx, GoodId};
+use crate::reaction::ReactionRuleSpec;
+use crate::counterfactual::TradeEdit;
+use crate::snapshot::{PopulationSummary, SummaryTracker};
+use crate::trade_graph::TradeGraph;
+use crate::math::{Ema, WeightedStats};
+use crate::metrics::{inequality, pareto_gap, wealth, InequalityMetrics};
+use crate::prices::{PricePoint, PriceTracker};
+use crate::checkpoint::{ResumePoint, StreamRng};
+use crate::state::leaf_hash;
 
-#[derive(Clone, Debug)]
+mod rejections;
+mod soa;
+
+pub use rejections::{Rejection, RejectionReason};
+pub use soa::AgentSoA;
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct SimState {
     pub agents: Vec<Agent>,
     pub events: Vec<TradeEvent>,
+    /// One entry per completed round.
+    pub metrics: Vec<RoundMetrics>,
+    /// Round index at which `stop_when_converged` halted the run, if it did.
+    pub stopped_at: Option<usize>,
+    /// Per-round, per-good price index: observed, EWMA and Walrasian (see `prices`).
+    pub prices: Vec<PricePoint>,
+    /// Per-round, per-good-pair exchange rate summaries (see `exchange_rate_stats`).
+    pub exchange_rates: Vec<PairRateStat>,
+    /// Trade ties grown under `MatchingMode::Persistent` (empty otherwise).
+    pub partners: PartnerGraph,
+    /// Tax collected under `SimConfig::policy` and not yet paid out.
+    pub treasury: f64,
+    /// Three-way cycles executed under `SimConfig::triads`.
+    pub cycles: Vec<CycleEvent>,
+    /// Where the engine stood, in a state taken by `Engine::checkpoint` (see `checkpoint`).
+    #[serde(default)]
+    pub resume: Option<ResumePoint>,
+}
+
+/// Aggregate counters for a single round.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RoundMetrics {
+    pub round: usize,
+    pub encounters: usize,
+    pub trades: usize,
+    /// Sum of `delta_u_i + delta_u_j` over the round's executed trades.
+    pub delta_u: f64,
+    /// Goods under an active embargo this round.
+    #[serde(default)]
+    pub embargoed: Vec<usize>,
+    /// Simulated Unix time at the start of the round, when `SimConfig::clock` is set.
+    #[serde(default)]
+    pub time: Option<f64>,
+    /// Agents present during the round, and how many entered / exited at its start.
+    #[serde(default)]
+    pub population: usize,
+    #[serde(default)]
+    pub entered: usize,
+    #[serde(default)]
+    pub exited: usize,
+    /// Agents hit by `SimConfig::shocks` at the start of the round.
+    #[serde(default)]
+    pub shocked: usize,
+    /// Agents hit by `SimConfig::preference_shocks` at the start of the round.
+    #[serde(default)]
+    pub preference_shocked: usize,
+    /// Total quantity consumed, lost to depreciation and replenished after the round's trading
+    /// (`SimConfig::consumption`, `SimConfig::good_decay`).
+    #[serde(default)]
+    pub consumed: f64,
+    #[serde(default)]
+    pub decayed: f64,
+    #[serde(default)]
+    pub replenished: f64,
+    /// Emergent price of each good in base units after the round (see
+    /// `metrics::update_emergent_prices`); 1 for goods not yet traded against the base.
+    #[serde(default)]
+    pub prices: Vec<f64>,
+    /// Transaction costs paid this round, in base-good units, and trades refused because their
+    /// gains did not cover the cost and tax (`SimConfig::transaction_cost`, `SimConfig::policy`).
+    #[serde(default)]
+    pub costs: f64,
+    #[serde(default)]
+    pub cost_refused: usize,
+    /// Trades dropped because scaling by the step cap (or the shrink of
+    /// `SimConfig::conserve_quantities`) left one side no better off (`trade::scale_and_validate`).
+    #[serde(default)]
+    pub cap_refused: usize,
+    /// Encounter draws passed over and trades refused because a side had used up its
+    /// `SimConfig::max_trades_per_agent`.
+    #[serde(default)]
+    pub over_budget: usize,
+    /// Under `SimConfig::monetary`: credit drawn before and repaid after the round's trading,
+    /// money volume over the money stock held at the start of trading, and outstanding debt as a
+    /// share of all credit lines after repayment.
+    #[serde(default)]
+    pub credit_drawn: f64,
+    #[serde(default)]
+    pub credit_repaid: f64,
+    #[serde(default)]
+    pub money_velocity: f64,
+    #[serde(default)]
+    pub credit_utilization: f64,
+    /// Three-way cycles executed this round (`SimConfig::triads`); their utility gains are
+    /// included in `delta_u`.
+    #[serde(default)]
+    pub cycles: usize,
+    /// Under `SimConfig::money_emergence`: per-good marketability after the round, the good
+    /// currently most accepted in trade, and trades accepted for resale value.
+    #[serde(default)]
+    pub marketability: Vec<f64>,
+    #[serde(default)]
+    pub money_good: Option<GoodId>,
+    #[serde(default)]
+    pub speculative: usize,
+    /// Tax collected this round and treasury paid out at its end (`SimConfig::policy`).
+    #[serde(default)]
+    pub tax_revenue: f64,
+    #[serde(default)]
+    pub redistributed: f64,
+    /// Trades that lowered a side's true utility under `SimConfig::decision_noise`.
+    #[serde(default)]
+    pub mistakes: usize,
+    /// Agents that took part in at least one trade this round: a measure of market thickness.
+    #[serde(default)]
+    pub active_traders: usize,
+    /// Trades that fell through because a side reneged, and the mean `Agent::reputation` after
+    /// the round (`SimConfig::reputation`).
+    #[serde(default)]
+    pub reneged: usize,
+    #[serde(default)]
+    pub mean_reputation: f64,
+    /// Listings posted and sales made in the round's marketplace phase (`SimConfig::posted_prices`);
+    /// sales are also counted in `trades` and `delta_u`.
+    #[serde(default)]
+    pub listings: usize,
+    #[serde(default)]
+    pub market_sales: usize,
+    /// Trades with a broker on one side, and the brokers' total wealth at `prices` after the
+    /// round (`SimConfig::brokers`).
+    #[serde(default)]
+    pub broker_trades: usize,
+    #[serde(default)]
+    pub broker_wealth: f64,
+    /// Agents that moved their preferences towards a partner's (`SimConfig::social_influence`).
+    #[serde(default)]
+    pub imitations: usize,
+    /// Aggregate utility gap to the Walrasian allocation after the round (`metrics::pareto_gap`).
+    #[serde(default)]
+    pub pareto_gap: f64,
+    /// Lookups answered from, and missing, the oracle cache this round (`SimConfig::oracle_cache`).
+    #[serde(default)]
+    pub oracle_cache_hits: usize,
+    #[serde(default)]
+    pub oracle_cache_misses: usize,
+    /// Oracle solutions this round that were degenerate, unbracketed or clamped to `min_qty`
+    /// (`pareto_oracle::OracleStatus`).
+    #[serde(default)]
+    pub oracle_degenerate: usize,
+    #[serde(default)]
+    pub oracle_unbracketed: usize,
+    #[serde(default)]
+    pub oracle_clamped: usize,
+    /// Agents holding more than `min_qty` of each good after the round; tracks the diffusion of
+    /// `SimConfig::new_goods`.
+    #[serde(default)]
+    pub holders: Vec<usize>,
+    /// Wealth (at `prices`) and utility inequality after the round.
+    #[serde(default)]
+    pub inequality: InequalityMetrics,
+}
+
+/// Volume-weighted exchange rate summary for one good pair within one round.
+///
+/// Pairs are oriented so that the base good, when involved, is always `good_b`; trades recorded
+/// as (base, k) are folded into (k, base) with `q_ab` inverted. Rows with `good_b == base` are
+/// therefore the per-good prices versus the numeraire.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PairRateStat {
+    pub round: usize,
+    pub good_a: GoodId,
+    pub good_b: GoodId,
+    pub trades: usize,
+    /// Total quantity of `good_a` that changed hands.
+    pub volume_a: f64,
+    /// Mean of `q_ab` weighted by the traded quantity of `good_a`.
+    pub mean_q_ab: f64,
+    /// Volume-weighted standard deviation of `q_ab`.
+    pub std_q_ab: f64,
+    pub min_q_ab: f64,
+    pub max_q_ab: f64,
+}
+
+/// Goods-flow multigraph of every trade in `state` (see `trade_graph::TradeGraph`).
+///
+/// Nodes are positions in the population; under `SimConfig::demography` a position may stand
+/// for different agents over the run (use `TradeEvent::id_i` / `id_j` to tell them apart).
+pub fn trade_graph(state: &SimState) -> TradeGraph {
+    let seen = state.events.iter().map(|ev| ev.i.index().max(ev.j.index()) + 1).max().unwrap_or(0);
+    TradeGraph::from_events(state.agents.len().max(seen), &state.events)
+}
+
+/// Aggregate a round's trade events into volume-weighted exchange rates per good pair.
+///
+/// If every trade of a pair has zero volume, the rates are averaged unweighted.
+pub fn exchange_rate_stats(round: usize, events: &[TradeEvent], base_good: usize) -> Vec<PairRateStat> {
+    // (good_a, good_b) -> [(q, volume)]
+    let mut by_pair: BTreeMap<(GoodId, GoodId), Vec<(f64, f64)>> = BTreeMap::new();
+    for ev in events.iter() {
+        let (a, b, q, vol) = if ev.good_a.index() == base_good {
+            (ev.good_b, ev.good_a, 1.0 / ev.q_ab, ev.delta_b_i.abs())
+        } else {
+            (ev.good_a, ev.good_b, ev.q_ab, ev.delta_a_i.abs())
+        };
+        if !q.is_finite() { continue; }
+        by_pair.entry((a, b)).or_default().push((q, vol));
+    }
+
+    by_pair
+        .into_iter()
+        .map(|((good_a, good_b), obs)| {
+            let volume_a: f64 = obs.iter().map(|&(_, v)| v).sum();
+            let mut acc = WeightedStats::default();
+            for &(q, v) in obs.iter() {
+                acc.push(q, if volume_a > 0.0 { v } else { 1.0 });
+            }
+            PairRateStat {
+                round,
+                good_a,
+                good_b,
+                trades: obs.len(),
+                volume_a,
+                mean_q_ab: acc.mean(),
+                std_q_ab: acc.std_dev(),
+                min_q_ab: acc.min().unwrap_or(f64::INFINITY),
+                max_q_ab: acc.max().unwrap_or(f64::NEG_INFINITY),
+            }
+        })
+        .collect()
 }
 
-pub fn init_agents(cfg: &SimConfig) -> SimState {
-    let goods_qty = cfg.base_goods_quantity;
+/// Reject configs that cannot be simulated (goods/base mismatch, oracle parameters, empty alpha
+/// range, bad embargo, endowment distribution, preference generator, endowment-preference
+/// correlation, agent groups, decay, schedule, new goods, shocks, preference shocks, consumption,
+/// demography, replications, price smoothing, step cap, transaction cost, tax rate, credit terms,
+/// money emergence, triads, brokers, posted prices, reputation, disclosure, decision noise, zero
+/// intelligence, learning rates, price expectations, social influence or incompatible combinations
+/// of these).
+pub(crate) fn check_config(cfg: &SimConfig) -> Result<(), RdxError> {
     let n = cfg.base_goods.len();
-    
-    assert_eq!(goods_qty, n, "[Safe Panic] Goods Quantity mismatch in configuration");
-    assert!(n >= 2, "[Safe Panic] Goods quantity is less than 2");
-    
+    if cfg.base_goods_quantity != n {
+        return Err(RdxError::DimensionMismatch { expected: cfg.base_goods_quantity, found: n });
+    }
+    if n < 2 {
+        return Err(RdxError::InvalidConfig(format!("need at least 2 goods, found {n}")));
+    }
+    if cfg.base_good >= n {
+        return Err(RdxError::GoodOutOfRange { index: cfg.base_good, len: n });
+    }
+    build_oracle(cfg)?;
+    for g in resolved_groups(cfg) {
+        let alpha_range_ok = g.alpha_low.is_finite() && g.alpha_high.is_finite() && g.alpha_low < g.alpha_high;
+        if !alpha_range_ok {
+            return Err(RdxError::InvalidConfig(format!(
+                "alpha_low ({}) must be finite and strictly below alpha_high ({})", g.alpha_low, g.alpha_high
+            )));
+        }
+        endowment::validate(g.endowment, n)?;
+    }
+    let generator_ok = match &cfg.preference_generator {
+        PreferenceGenerator::Uniform => true,
+        PreferenceGenerator::Dirichlet { concentration } => *concentration > 0.0 && concentration.is_finite(),
+        PreferenceGenerator::Clustered { clusters, cohesion } => {
+            *clusters >= 1 && *cohesion > 0.0 && cohesion.is_finite()
+        }
+    };
+    if !generator_ok {
+        return Err(RdxError::InvalidConfig(format!(
+            "preference generator needs a finite concentration / cohesion > 0 and at least one cluster, got {:?}",
+            cfg.preference_generator
+        )));
+    }
+    if !(-1.0..=1.0).contains(&cfg.endowment_preference_correlation) {
+        return Err(RdxError::InvalidConfig(format!(
+            "endowment_preference_correlation must lie in [-1, 1], got {}", cfg.endowment_preference_correlation
+        )));
+    }
+    if !cfg.agent_groups.is_empty() {
+        let total: usize = cfg.agent_groups.iter().map(|g| g.size).sum();
+        if total != cfg.num_agents {
+            return Err(RdxError::DimensionMismatch { expected: cfg.num_agents, found: total });
+        }
+    }
+    for em in cfg.embargoes.iter() {
+        if let Some(&g) = em.goods.iter().find(|g| g.index() >= n) {
+            return Err(RdxError::GoodOutOfRange { index: g.index(), len: n });
+        }
+        if em.from_round > em.to_round {
+            return Err(RdxError::InvalidConfig(format!(
+                "embargo window {}..={} is empty", em.from_round, em.to_round
+            )));
+        }
+    }
+    if !cfg.good_decay.is_empty() {
+        if cfg.good_decay.len() != n {
+            return Err(RdxError::DimensionMismatch { expected: n, found: cfg.good_decay.len() });
+        }
+        if let Some(&d) = cfg.good_decay.iter().find(|d| !(0.0..=1.0).contains(*d)) {
+            return Err(RdxError::InvalidConfig(format!("good_decay rates must lie in [0, 1], got {d}")));
+        }
+    }
+    if cfg.max_trades_per_agent == Some(0) {
+        return Err(RdxError::InvalidConfig("max_trades_per_agent must be at least 1".into()));
+    }
+    if !cfg.schedule.is_empty() {
+        // every regime the schedule produces must itself be valid
+        let mut regime = cfg.clone();
+        regime.schedule.clear();
+        let mut changes: Vec<_> = cfg.schedule.iter().collect();
+        changes.sort_by_key(|c| c.round);
+        for c in changes {
+            c.change.apply(&mut regime);
+            check_config(&regime).map_err(|e| {
+                RdxError::InvalidConfig(format!("schedule change at round {}: {e}", c.round))
+            })?;
+        }
+    }
+    let mut names: Vec<&str> = cfg.base_goods.iter().map(String::as_str).collect();
+    for g in cfg.new_goods.iter() {
+        if names.contains(&g.name.as_str()) {
+            return Err(RdxError::InvalidConfig(format!("new good {:?} is already a good", g.name)));
+        }
+        names.push(&g.name);
+        let (low, high) = (g.alpha_low.unwrap_or(cfg.alpha_low), g.alpha_high.unwrap_or(cfg.alpha_high));
+        let ok = (0.0..=1.0).contains(&g.holders) && (0.0..=1.0).contains(&g.decay)
+            && low.is_finite() && high.is_finite() && low < high;
+        if !ok {
+            return Err(RdxError::InvalidConfig(format!(
+                "new good {:?} needs holders and decay in [0, 1] and alpha_low < alpha_high", g.name
+            )));
+        }
+        endowment::validate(&g.endowment, 1)?;
+    }
+    for sh in cfg.shocks.iter() {
+        if let Some(&g) = sh.goods.iter().find(|g| g.index() >= n) {
+            return Err(RdxError::GoodOutOfRange { index: g.index(), len: n });
+        }
+        let ok = (0.0..=1.0).contains(&sh.agent_fraction)
+            && sh.mean.is_finite() && sh.mean > 0.0
+            && sh.sigma.is_finite() && sh.sigma >= 0.0;
+        if !ok {
+            return Err(RdxError::InvalidConfig(format!(
+                "shock at round {} needs agent_fraction in [0, 1], mean > 0 and sigma >= 0", sh.round
+            )));
+        }
+    }
+    for sh in cfg.preference_shocks.iter() {
+        if let Some(&g) = sh.goods.iter().find(|g| g.index() >= n) {
+            return Err(RdxError::GoodOutOfRange { index: g.index(), len: n });
+        }
+        let ok = !sh.goods.is_empty() && sh.goods.iter().all(|g| g.index() != cfg.base_good)
+            && (0.0..=1.0).contains(&sh.agent_fraction) && sh.shift.is_finite();
+        if !ok {
+            return Err(RdxError::InvalidConfig(format!(
+                "preference shock at round {} needs non-base goods, agent_fraction in [0, 1] and a finite shift",
+                sh.round
+            )));
+        }
+    }
+    if let Some(c) = &cfg.consumption {
+        let ok = (0.0..=1.0).contains(&c.rate) && c.income.is_finite() && c.income >= 0.0;
+        if !ok {
+            return Err(RdxError::InvalidConfig(format!(
+                "consumption needs rate in [0, 1] and income >= 0, got {}, {}", c.rate, c.income
+            )));
+        }
+    }
+    if let Some(demo) = &cfg.demography {
+        let ok = demo.entry_rate.is_finite() && demo.entry_rate >= 0.0 && (0.0..=1.0).contains(&demo.exit_prob);
+        if !ok {
+            return Err(RdxError::InvalidConfig(format!(
+                "demography needs entry_rate >= 0 and exit_prob in [0, 1], got {}, {}",
+                demo.entry_rate, demo.exit_prob
+            )));
+        }
+    }
+    if let MatchingMode::Persistent { reinforcement, baseline, decay } = cfg.matching {
+        let ok = reinforcement.is_finite() && reinforcement >= 0.0
+            && baseline.is_finite() && baseline >= 0.0
+            && (0.0..=1.0).contains(&decay);
+        if !ok {
+            return Err(RdxError::InvalidConfig(format!(
+                "persistent matching needs reinforcement, baseline >= 0 and decay in [0, 1], got \
+                 {reinforcement}, {baseline}, {decay}"
+            )));
+        }
+    }
+    if let Some(clock) = &cfg.clock {
+        let ok = clock.round_secs.is_finite() && clock.round_secs > 0.0 && clock.start_unix.is_finite();
+        if !ok {
+            return Err(RdxError::InvalidConfig(format!(
+                "clock needs a finite start and positive round_secs, got {}", clock.round_secs
+            )));
+        }
+    }
+    if let Some(rep) = &cfg.replications {
+        let ok = rep.runs >= 1 && rep.bootstrap_samples >= 1 && rep.confidence > 0.0 && rep.confidence < 1.0;
+        if !ok {
+            return Err(RdxError::InvalidConfig(format!(
+                "replications need runs >= 1, bootstrap_samples >= 1 and confidence in (0, 1), got {}, {}, {}",
+                rep.runs, rep.bootstrap_samples, rep.confidence
+            )));
+        }
+    }
+    let ok = cfg.price_alpha > 0.0 && cfg.price_alpha <= 1.0;
+    if !ok {
+        return Err(RdxError::InvalidConfig(format!("price_alpha must lie in (0, 1], got {}", cfg.price_alpha)));
+    }
+    if let StepCap::Adaptive { min_frac, max_frac } = cfg.step_cap {
+        if !(0.0..=1.0).contains(&min_frac) || !(min_frac..=1.0).contains(&max_frac) {
+            return Err(RdxError::InvalidConfig(format!(
+                "adaptive step cap needs 0 <= min_frac <= max_frac <= 1, got [{min_frac}, {max_frac}]"
+            )));
+        }
+    }
+    if let PairingMode::Bundle = cfg.pairing_mode {
+        let frictions = cfg.transaction_cost.is_some() || cfg.policy.is_some() || cfg.money_emergence.is_some();
+        if frictions {
+            return Err(RdxError::InvalidConfig(
+                "bundle pairing does not support transaction_cost, policy or money_emergence".into(),
+            ));
+        }
+    }
+    if let Some(b) = &cfg.brokers {
+        let ok = b.share > 0.0 && b.share < 1.0 && (0.0..1.0).contains(&b.spread);
+        if !ok {
+            return Err(RdxError::InvalidConfig(format!(
+                "brokers need share in (0, 1) and spread in [0, 1), got {b:?}"
+            )));
+        }
+        // brokers accept trades that lower their own utility, which the net-gain check would refuse
+        let frictions = cfg.transaction_cost.is_some() || cfg.policy.is_some() || cfg.money_emergence.is_some()
+            || matches!(cfg.pairing_mode, PairingMode::Bundle);
+        if frictions {
+            return Err(RdxError::InvalidConfig(
+                "brokers do not support transaction_cost, policy, money_emergence or bundle pairing".into(),
+            ));
+        }
+    }
+    if let Some(s) = &cfg.social_influence {
+        if !(0.0..=1.0).contains(&s.rate) {
+            return Err(RdxError::InvalidConfig(format!("social influence rate must lie in [0, 1], got {}", s.rate)));
+        }
+    }
+    if let Some(spec) = &cfg.price_expectations {
+        let ok = spec.memory > 0.0 && spec.memory <= 1.0 && spec.tolerance >= 0.0 && spec.tolerance.is_finite();
+        if !ok {
+            return Err(RdxError::InvalidConfig(format!(
+                "price expectations need memory in (0, 1] and tolerance >= 0, got {} and {}",
+                spec.memory, spec.tolerance
+            )));
+        }
+    }
+    if let PairingMode::Learning { epsilon, learning_rate } = cfg.pairing_mode {
+        let ok = (0.0..=1.0).contains(&epsilon) && learning_rate > 0.0 && learning_rate <= 1.0;
+        if !ok {
+            return Err(RdxError::InvalidConfig(format!(
+                "learning needs epsilon in [0, 1] and learning_rate in (0, 1], got {epsilon} and {learning_rate}"
+            )));
+        }
+        let exclusive = cfg.negotiation.is_some() || cfg.money_emergence.is_some() || cfg.zero_intelligence.is_some();
+        if exclusive {
+            return Err(RdxError::InvalidConfig(
+                "learning pairing does not combine with negotiation, money_emergence or zero_intelligence".into(),
+            ));
+        }
+    }
+    if cfg.zero_intelligence.is_some() {
+        let exclusive = cfg.negotiation.is_some() || cfg.money_emergence.is_some() || cfg.brokers.is_some()
+            || cfg.decision_noise.is_some() || matches!(cfg.pairing_mode, PairingMode::Bundle);
+        if exclusive {
+            return Err(RdxError::InvalidConfig(
+                "zero_intelligence replaces the trade search; it does not combine with negotiation, \
+                 money_emergence, brokers, decision_noise or bundle pairing".into(),
+            ));
+        }
+    }
+    if let Some(noise) = &cfg.decision_noise {
+        let ok = noise.sigma >= 0.0 && noise.sigma.is_finite();
+        if !ok {
+            return Err(RdxError::InvalidConfig(format!("decision noise sigma must be finite and >= 0, got {}", noise.sigma)));
+        }
+    }
+    if let Some(neg) = &cfg.negotiation {
+        if !(0.0..=1.0).contains(&neg.disclosure) {
+            return Err(RdxError::InvalidConfig(format!("disclosure must lie in [0, 1], got {}", neg.disclosure)));
+        }
+        let exclusive = cfg.money_emergence.is_some() || matches!(cfg.pairing_mode, PairingMode::Bundle);
+        if exclusive {
+            return Err(RdxError::InvalidConfig(
+                "negotiation does not combine with money_emergence or bundle pairing".into(),
+            ));
+        }
+    }
+    if let Some(r) = &cfg.reputation {
+        let ok = [r.surplus_weight, r.honor_reward, r.breach_penalty].iter().all(|w| w.is_finite() && *w >= 0.0)
+            && (0.0..=1.0).contains(&r.renege_prob)
+            && (0.0..=1.0).contains(&r.decay);
+        if !ok {
+            return Err(RdxError::InvalidConfig(format!(
+                "reputation needs finite weights >= 0 and renege_prob, decay in [0, 1], got {r:?}"
+            )));
+        }
+    }
+    if let MatchingMode::Reputation { baseline } = cfg.matching {
+        let ok = cfg.reputation.is_some() && baseline.is_finite() && baseline >= 0.0;
+        if !ok {
+            return Err(RdxError::InvalidConfig(format!(
+                "reputation matching needs `reputation` and a finite baseline >= 0, got {baseline}"
+            )));
+        }
+    }
+    if let Some(p) = &cfg.posted_prices {
+        let ok = p.markup >= 0.0 && p.markup.is_finite() && p.lot > 0.0 && p.lot <= 1.0;
+        if !ok {
+            return Err(RdxError::InvalidConfig(format!(
+                "posted prices need a finite markup >= 0 and lot in (0, 1], got {p:?}"
+            )));
+        }
+    }
+    if let Some(tr) = &cfg.triads {
+        let ok = tr.step > 0.0 && tr.step <= 1.0;
+        if !ok {
+            return Err(RdxError::InvalidConfig(format!("triads step must lie in (0, 1], got {}", tr.step)));
+        }
+    }
+    if let Some(m) = &cfg.money_emergence {
+        let ok = m.weight >= 0.0 && m.weight.is_finite() && m.alpha > 0.0 && m.alpha <= 1.0
+            && m.step > 0.0 && m.step <= 1.0;
+        if !ok {
+            return Err(RdxError::InvalidConfig(format!(
+                "money emergence needs a finite weight >= 0 and alpha, step in (0, 1], got {m:?}"
+            )));
+        }
+    }
+    if let Some(m) = &cfg.monetary {
+        let ok = m.credit_limit >= 0.0 && m.credit_limit.is_finite() && (0.0..=1.0).contains(&m.repayment_rate);
+        if !ok {
+            return Err(RdxError::InvalidConfig(format!(
+                "monetary mode needs a finite credit_limit >= 0 and repayment_rate in [0, 1], got {m:?}"
+            )));
+        }
+    }
+    if let Some(p) = &cfg.policy {
+        if !(0.0..1.0).contains(&p.tax_rate) {
+            return Err(RdxError::InvalidConfig(format!("tax_rate must lie in [0, 1), got {}", p.tax_rate)));
+        }
+    }
+    if let Some(cost) = &cfg.transaction_cost {
+        let ok = (0.0..1.0).contains(&cost.proportional) && cost.fixed >= 0.0 && cost.fixed.is_finite();
+        if !ok {
+            return Err(RdxError::InvalidConfig(format!(
+                "transaction cost needs proportional in [0, 1) and a finite fixed >= 0, got {cost:?}"
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Reject populations the engine cannot step: fewer than two agents, or agents whose vectors
+/// do not match the configured goods count.
+fn check_population(cfg: &SimConfig, agents: &[Agent]) -> Result<(), RdxError> {
+    if agents.len() < 2 {
+        return Err(RdxError::PopulationTooSmall(agents.len()));
+    }
+    let n = cfg.base_goods.len();
+    for ag in agents.iter() {
+        for len in [ag.e.len(), ag.beta.len(), ag.alpha_to_base.len()] {
+            if len != n {
+                return Err(RdxError::DimensionMismatch { expected: n, found: len });
+            }
+        }
+    }
+    Ok(())
+}
+
+pub fn init_agents(cfg: &SimConfig) -> Result<SimState, RdxError> {
+    check_config(cfg)?;
+    let n = cfg.base_goods.len();
+
     let mut rng = StdRng::seed_from_u64(cfg.seed);
 
     let mut agents = Vec::with_capacity(cfg.num_agents);
-    for _ in 0..cfg.num_agents {
-        // endowments: positive, comparable scale
-        let e = (0..n)
-            .map(|_| rng.gen_range(0.5..2.0) * cfg.initial_endowment_scale)
-            .collect::<Vec<f64>>();
+    for g in resolved_groups(cfg) {
+        for _ in 0..g.size {
+            let id = agents.len() as u64;
+            agents.push(draw_agent(cfg, &g, id, n, &mut rng)?);
+        }
+    }
+    if cfg.endowment_preference_correlation != 0.0 {
+        correlate_endowments(cfg, &mut agents, &mut rng)?;
+    }
 
-        // alpha_to_base: only meaningful for k != base, set base to 0.5 convention
-        let mut alpha_to_base = vec![0.5; n];
-        for k in 0..n {
-            if k == cfg.base_good { continue; }
-            alpha_to_base[k] = rng.gen_range(cfg.alpha_low..cfg.alpha_high);
+    if let Some(b) = &cfg.brokers {
+        let count = (b.share * agents.len() as f64).round() as usize;
+        for a in agents.iter_mut().take(count) {
+            a.broker = true;
         }
+    }
 
-        let beta = beta_from_alpha_to_base(&alpha_to_base, cfg.base_good, 1e-6);
-        let reaction_rules = cfg.reaction_rules.to_vec().clone(); // TODO: generate random agent's reaction rules
+    Ok(SimState { agents, ..SimState::default() })
+}
+
+/// One fresh agent of group `g`.
+fn draw_agent<R: Rng>(cfg: &SimConfig, g: &ResolvedGroup<'_>, id: u64, n: usize, rng: &mut R) -> Result<Agent, RdxError> {
+    let e = endowment::draw_bundle(g.endowment, n, rng)
+        .into_iter()
+        .map(|x| x * cfg.initial_endowment_scale)
+        .collect::<Vec<f64>>();
+
+    let mut labels = BTreeMap::new();
+    if !g.name.is_empty() {
+        labels.insert("group".to_string(), g.name.to_string());
+    }
 
-        agents.push(Agent { e, beta, alpha_to_base , reaction_rules});
+    // alpha_to_base: only meaningful for k != base, set base to 0.5 convention
+    let mut alpha_to_base = vec![0.5; n];
+    let drawn = match &cfg.preference_generator {
+        PreferenceGenerator::Uniform => None,
+        PreferenceGenerator::Dirichlet { concentration } => {
+            Some(generators::dirichlet(&vec![*concentration; n], rng))
+        }
+        PreferenceGenerator::Clustered { clusters, cohesion } => {
+            let centres = generators::taste_centres(cfg.seed, *clusters, n);
+            let c = rng.gen_range(0..centres.len());
+            labels.insert("taste_cluster".to_string(), c.to_string());
+            Some(generators::clustered_beta(&centres[c], *cohesion, rng))
+        }
+    };
+    for (k, alpha) in alpha_to_base.iter_mut().enumerate() {
+        if k == cfg.base_good { continue; }
+        *alpha = match &drawn {
+            Some(beta) => alpha_from_beta(beta, k, cfg.base_good, 1e-6),
+            None => rng.gen_range(g.alpha_low..g.alpha_high),
+        };
     }
 
-    SimState { agents, events: Vec::new() }
+    let beta = beta_from_alpha_to_base(&alpha_to_base, cfg.base_good, 1e-6)?;
+    let reaction_rules = g.reaction_rules.to_vec(); // TODO: generate random agent's reaction rules
+
+    Ok(Agent { id, labels, e, beta, alpha_to_base, reaction_rules, ..Default::default() })
 }
 
-/// Run diffusion rounds with P2P encounters. Reaction rules can be plugged in before calling `run`.
-pub fn run(cfg: &SimConfig, state: &mut SimState) {
-    let mut rng = StdRng::seed_from_u64(cfg.seed ^ 0xA5A5_A5A5_A5A5_A5A5);
-    let oracle = default_oracle();
-
-    for t in 0..cfg.rounds {
-        for _ in 0..cfg.p2p_encounters_per_round {
-            let i = rng.gen_range(0..state.agents.len());
-            let mut j = rng.gen_range(0..state.agents.len());
-            while j == i {
-                j = rng.gen_range(0..state.agents.len());
-            }
-
-            let (ai, aj) = {
-                let (left, right) = state.agents.split_at_mut(j.max(i));
-                if i < j {
-                    (&mut left[i], &mut right[0])
-                } else {
-                    (&mut right[0], &mut left[j])
+/// Re-pair holdings and tastes within each group so that, per non-base good, ranks of `e` and
+/// `alpha_to_base` follow `SimConfig::endowment_preference_correlation`; each agent then
+/// re-derives `beta`.
+fn correlate_endowments<R: Rng>(cfg: &SimConfig, agents: &mut [Agent], rng: &mut R) -> Result<(), RdxError> {
+    let rho = cfg.endowment_preference_correlation;
+    for range in agent_group_ranges(cfg) {
+        let group = &mut agents[range];
+        for k in (0..cfg.base_goods.len()).filter(|&k| k != cfg.base_good) {
+            let mut e: Vec<f64> = group.iter().map(|a| a.e[k]).collect();
+            let mut alpha: Vec<f64> = group.iter().map(|a| a.alpha_to_base[k]).collect();
+            generators::rank_correlate(&mut e, &mut alpha, rho, rng);
+            for (a, (x, al)) in group.iter_mut().zip(e.into_iter().zip(alpha)) {
+                a.e[k] = x;
+                a.alpha_to_base[k] = al;
+            }
+        }
+        for a in group.iter_mut() {
+            a.beta = beta_from_alpha_to_base(&a.alpha_to_base, cfg.base_good, 1e-6)?;
+        }
+    }
+    Ok(())
+}
+
+/// Index range of each `SimConfig::agent_groups` entry in `SimState::agents` (a single
+/// `0..num_agents` range without groups).
+pub fn agent_group_ranges(cfg: &SimConfig) -> Vec<std::ops::Range<usize>> {
+    let mut start = 0;
+    resolved_groups(cfg).into_iter()
+        .map(|g| {
+            start += g.size;
+            start - g.size..start
+        })
+        .collect()
+}
+
+/// Endowment distribution of `agent`'s group (matched by its `group` label), else the
+/// top-level one.
+fn group_endowment<'a>(cfg: &'a SimConfig, agent: &Agent) -> &'a EndowmentSpec {
+    agent.labels.get("group")
+        .and_then(|name| cfg.agent_groups.iter().find(|g| &g.name == name))
+        .and_then(|g| g.endowment.as_ref())
+        .unwrap_or(&cfg.endowment)
+}
+
+/// Number of agents holding more than `min_qty` of each of `n_goods` goods.
+pub fn good_holders(agents: &[Agent], n_goods: usize, min_qty: f64) -> Vec<usize> {
+    let mut holders = vec![0; n_goods];
+    for a in agents.iter() {
+        for (h, &x) in holders.iter_mut().zip(a.e.iter()) {
+            if x > min_qty { *h += 1; }
+        }
+    }
+    holders
+}
+
+/// The good with the strictly highest marketability, if one stands out.
+fn money_good(shares: &[f64]) -> Option<GoodId> {
+    let (k, &best) = shares.iter().enumerate().max_by(|a, b| a.1.total_cmp(b.1))?;
+    let unique = shares.iter().enumerate().all(|(g, &s)| g == k || s < best);
+    unique.then_some(GoodId(k))
+}
+
+fn intro_alpha_range(cfg: &SimConfig, intro: &GoodIntroduction) -> (f64, f64) {
+    (intro.alpha_low.unwrap_or(cfg.alpha_low), intro.alpha_high.unwrap_or(cfg.alpha_high))
+}
+
+/// An agent group with the `SimConfig` fallbacks filled in.
+struct ResolvedGroup<'a> {
+    name: &'a str,
+    size: usize,
+    endowment: &'a EndowmentSpec,
+    alpha_low: f64,
+    alpha_high: f64,
+    reaction_rules: &'a [ReactionRuleSpec],
+}
+
+fn resolved_groups(cfg: &SimConfig) -> Vec<ResolvedGroup<'_>> {
+    if cfg.agent_groups.is_empty() {
+        return vec![ResolvedGroup {
+            name: "",
+            size: cfg.num_agents,
+            endowment: &cfg.endowment,
+            alpha_low: cfg.alpha_low,
+            alpha_high: cfg.alpha_high,
+            reaction_rules: &cfg.reaction_rules,
+        }];
+    }
+    cfg.agent_groups.iter()
+        .map(|g| ResolvedGroup {
+            name: &g.name,
+            size: g.size,
+            endowment: g.endowment.as_ref().unwrap_or(&cfg.endowment),
+            alpha_low: g.alpha_low.unwrap_or(cfg.alpha_low),
+            alpha_high: g.alpha_high.unwrap_or(cfg.alpha_high),
+            reaction_rules: g.reaction_rules.as_deref().unwrap_or(&cfg.reaction_rules),
+        })
+        .collect()
+}
+
+/// Callbacks fired by `Engine` while it steps, for streaming events to disk, online statistics,
+/// or driving a UI without post-processing `SimState::events`. Every method defaults to a no-op.
+///
+/// Observers are owned by the engine; to read an observer's results after the run, register a
+/// shared handle (`Arc<Mutex<T>>` forwards to `T`).
+pub trait Observer {
+    fn on_round_start(&mut self, _round: usize, _state: &SimState) {}
+    fn on_trade(&mut self, _event: &TradeEvent) {}
+    /// Fired for each reaction rule applied to an agent. The engine has no reaction phase yet
+    /// (see `reaction`), so this does not fire today.
+    fn on_reaction(&mut self, _round: usize, _agent: usize, _rule: &ReactionRuleSpec) {}
+    fn on_round_end(&mut self, _metrics: &RoundMetrics, _state: &SimState) {}
+}
+
+impl<T: Observer> Observer for Arc<Mutex<T>> {
+    fn on_round_start(&mut self, round: usize, state: &SimState) {
+        if let Ok(mut o) = self.lock() { o.on_round_start(round, state) }
+    }
+    fn on_trade(&mut self, event: &TradeEvent) {
+        if let Ok(mut o) = self.lock() { o.on_trade(event) }
+    }
+    fn on_reaction(&mut self, round: usize, agent: usize, rule: &ReactionRuleSpec) {
+        if let Ok(mut o) = self.lock() { o.on_reaction(round, agent, rule) }
+    }
+    fn on_round_end(&mut self, metrics: &RoundMetrics, state: &SimState) {
+        if let Ok(mut o) = self.lock() { o.on_round_end(metrics, state) }
+    }
+}
+
+/// Where a run is, reported to the callback of `Engine::set_progress`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Progress {
+    /// Round being stepped and the rounds configured.
+    pub round: usize,
+    pub rounds: usize,
+    /// Encounters and trades in the round so far.
+    pub encounters: usize,
+    pub trades: usize,
+    /// Encounters and trades since the callback was set, this round's included.
+    pub total_encounters: u64,
+    pub total_trades: u64,
+    /// Set on the report closing the round.
+    pub round_done: bool,
+}
+
+impl Progress {
+    /// Share of the encounters counted in `total_encounters` that ended in a trade.
+    pub fn acceptance_rate(&self) -> f64 {
+        if self.total_encounters == 0 { 0.0 } else { self.total_trades as f64 / self.total_encounters as f64 }
+    }
+}
+
+/// Engine progress callback (`Engine::set_progress`).
+pub type ProgressCallback = Box<dyn FnMut(&Progress) + Send>;
+
+/// Progress callback and the totals of the rounds finished since it was set.
+struct ProgressHook {
+    every: usize,
+    callback: ProgressCallback,
+    encounters: u64,
+    trades: u64,
+}
+
+impl ProgressHook {
+    fn report(&mut self, rounds: usize, m: &RoundMetrics, round_done: bool) {
+        (self.callback)(&Progress {
+            round: m.round,
+            rounds,
+            encounters: m.encounters,
+            trades: m.trades,
+            total_encounters: self.encounters + m.encounters as u64,
+            total_trades: self.trades + m.trades as u64,
+            round_done,
+        });
+    }
+}
+
+/// One scheduled P2P encounter, whether or not it produced a trade.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Encounter {
+    pub round: usize,
+    pub i: AgentIdx,
+    pub j: AgentIdx,
+}
+
+/// Stepping simulation driver.
+///
+/// `Engine` owns the config, state, and RNG stream of a run so callers can interleave their own
+/// logic (policy shocks, inspection, visualization) between rounds:
+///
+/// ```ignore
+/// let mut engine = Engine::new(cfg)?;
+/// while engine.step_round().is_some() {
+///     inspect(engine.state());
+/// }
+/// let state = engine.finish();
+/// ```
+pub struct Engine {
+    cfg: SimConfig,
+    state: SimState,
+    rng: StreamRng,
+    oracle: AuditedOracle,
+    round: usize,
+    quiet_rounds: usize,
+    observers: Vec<Box<dyn Observer + Send>>,
+    /// Seeded agent permutation and next sub-step for `Scheduler::RoundRobin`.
+    rr_order: Vec<usize>,
+    rr_step: usize,
+    encounter_log: Option<Vec<Encounter>>,
+    /// Encounters without a trade, once `record_rejections` is called.
+    rejection_log: Option<Vec<Rejection>>,
+    /// Why the proposal being executed was refused, for `rejection_log`.
+    refusal: Option<RejectionReason>,
+    /// Schedule being replayed and the position of the next encounter in it.
+    replay: Option<(Vec<Encounter>, usize)>,
+    /// Pending edit of the trade that would become `events[index]`.
+    trade_edit: Option<(usize, TradeEdit)>,
+    /// Social graph for `MatchingMode::Network`.
+    network: Option<Arc<Graph>>,
+    /// Incremental population summary, once `track_summary` is called.
+    summary: Option<SummaryTracker>,
+    /// Set by `state_mut`; the tracker is rebuilt before the next round.
+    summary_stale: bool,
+    /// Trade restrictions of the round being stepped.
+    rules: TradeRules,
+    /// Emergent price estimates of each good in base units.
+    prices: PriceTracker,
+    /// Encounters so far in the current round, for sub-round event timestamps.
+    encounter_seq: usize,
+    /// Separate stream for `SimConfig::demography`, so entry and exit do not shift encounters.
+    demo_rng: StreamRng,
+    /// Id of the next entrant.
+    next_id: u64,
+    /// Separate stream for the `dynamics` phases.
+    dyn_rng: StreamRng,
+    /// Goods of `SimConfig::new_goods` introduced so far, in order; they have been moved from
+    /// `cfg.new_goods` to the end of `cfg.base_goods`.
+    introduced: Vec<GoodIntroduction>,
+    /// Trades refused this round because their gains did not cover the transaction cost.
+    cost_refused: usize,
+    /// Trades refused this round because a side no longer gained once the step cap scaled them.
+    cap_refused: usize,
+    /// Trades each agent has taken part in this round, under `SimConfig::max_trades_per_agent`.
+    round_trades: Vec<usize>,
+    /// Draws passed over and trades refused this round for `round_trades`.
+    over_budget: usize,
+    /// Per-good share of trades, smoothed, under `SimConfig::money_emergence`.
+    marketability: Vec<Ema>,
+    /// Separate stream for the third agents of `SimConfig::triads`.
+    triad_rng: StreamRng,
+    /// Buyer order of the `SimConfig::posted_prices` phase.
+    market_rng: StreamRng,
+    /// Reneging draws under `SimConfig::reputation`, and this round's broken commitments.
+    rep_rng: StreamRng,
+    reneged: usize,
+    /// Misperceptions under `SimConfig::decision_noise`.
+    noise_rng: StreamRng,
+    /// Random proposals under `SimConfig::zero_intelligence`.
+    zi_rng: StreamRng,
+    /// Exploration draws of `PairingMode::Learning`.
+    learn_rng: StreamRng,
+    /// Set by `set_progress`.
+    progress: Option<ProgressHook>,
+    /// Oracle cache counts at the end of the previous round.
+    oracle_seen: OracleCacheStats,
+}
+
+impl Engine {
+    /// Initialize agents from `cfg` (see `init_agents`) and prepare to step from round 0.
+    pub fn new(cfg: SimConfig) -> Result<Self, RdxError> {
+        let state = init_agents(&cfg)?;
+        Self::with_state(cfg, state)
+    }
+
+    /// Step an existing state, e.g. one whose agents were edited after `init_agents`. A resume
+    /// point in `state` is dropped: the run starts from round 0 (see `resume`).
+    pub fn with_state(cfg: SimConfig, mut state: SimState) -> Result<Self, RdxError> {
+        state.resume = None;
+        check_config(&cfg)?;
+        check_population(&cfg, &state.agents)?;
+        let rng = StreamRng::new(cfg.seed ^ 0xA5A5_A5A5_A5A5_A5A5);
+        let n_goods = cfg.base_goods.len();
+        let prices = PriceTracker::new(n_goods, cfg.base_good, cfg.price_alpha);
+        let demo_rng = StreamRng::new(cfg.seed ^ 0xD3E0_6A4F_B1E7);
+        let dyn_rng = StreamRng::new(cfg.seed ^ 0xC0A5_11E0_F10E);
+        let triad_rng = StreamRng::new(cfg.seed ^ 0x7A1A_D5C0_FFEE);
+        let market_rng = StreamRng::new(cfg.seed ^ 0x9057_ED9A_1CE5);
+        let rep_rng = StreamRng::new(cfg.seed ^ 0x7E57_ED0B_0A7D);
+        let noise_rng = StreamRng::new(cfg.seed ^ 0x0015_E0FF_DEC1);
+        let zi_rng = StreamRng::new(cfg.seed ^ 0x0201_CE5A_1D0B);
+        let learn_rng = StreamRng::new(cfg.seed ^ 0x1EA2_4ED0_0F4E);
+        let next_id = state.agents.iter().map(|a| a.id + 1).max().unwrap_or(0);
+        let mut rr_order: Vec<usize> = (0..state.agents.len()).collect();
+        rr_order.shuffle(&mut StdRng::seed_from_u64(cfg.seed ^ 0x5EED_0F2B_1B1B));
+        let network = build_network(&cfg, state.agents.len())?;
+        let oracle = AuditedOracle::new(build_oracle(&cfg)?);
+        Ok(Engine {
+            cfg, state, rng, oracle, round: 0, quiet_rounds: 0, observers: Vec::new(),
+            rr_order, rr_step: 0, encounter_log: None, replay: None, trade_edit: None, network,
+            summary: None, summary_stale: false, rules: TradeRules::default(),
+            prices, encounter_seq: 0, demo_rng, next_id, dyn_rng, introduced: Vec::new(), cost_refused: 0,
+            cap_refused: 0, marketability: Vec::new(), triad_rng, market_rng, rep_rng, reneged: 0,
+            noise_rng, zi_rng, learn_rng, progress: None, oracle_seen: OracleCacheStats::default(),
+            rejection_log: None, refusal: None, round_trades: Vec::new(), over_budget: 0,
+        })
+    }
+
+    /// Continue a state taken by `checkpoint` (possibly saved and loaded since) from the round
+    /// it was taken at, under the config then in force. Agents may have been edited meanwhile;
+    /// the same agents continue exactly as the uninterrupted run would.
+    pub fn resume(mut state: SimState) -> Result<Self, RdxError> {
+        let point = state.resume.take()
+            .ok_or_else(|| RdxError::Checkpoint("state has no resume point".into()))?;
+        let ResumePoint {
+            cfg, round, quiet_rounds, rr_order, rr_step, next_id, introduced, prices, marketability, network,
+            streams,
+        } = point;
+        check_config(&cfg)?;
+        check_population(&cfg, &state.agents)?;
+        let [rng, demo_rng, dyn_rng, triad_rng, market_rng, rep_rng, noise_rng, zi_rng, learn_rng] = streams;
+        let oracle = AuditedOracle::new(build_oracle(&cfg)?);
+        Ok(Engine {
+            cfg, state, rng, oracle, round, quiet_rounds, observers: Vec::new(),
+            rr_order, rr_step, encounter_log: None, replay: None, trade_edit: None, network: network.map(Arc::new),
+            summary: None, summary_stale: false, rules: TradeRules::default(),
+            prices, encounter_seq: 0, demo_rng, next_id, dyn_rng, introduced, cost_refused: 0,
+            cap_refused: 0, marketability, triad_rng, market_rng, rep_rng, reneged: 0,
+            noise_rng, zi_rng, learn_rng, progress: None, oracle_seen: OracleCacheStats::default(),
+            rejection_log: None, refusal: None, round_trades: Vec::new(), over_budget: 0,
+        })
+    }
+
+    /// The current state with a resume point attached, to `save` and later `resume`. Taken
+    /// between rounds; observers, encounter recording and pending edits are not part of it.
+    pub fn checkpoint(&self) -> SimState {
+        let mut state = self.state.clone();
+        state.resume = Some(ResumePoint {
+            cfg: self.cfg.clone(),
+            round: self.round,
+            quiet_rounds: self.quiet_rounds,
+            rr_order: self.rr_order.clone(),
+            rr_step: self.rr_step,
+            next_id: self.next_id,
+            introduced: self.introduced.clone(),
+            prices: self.prices.clone(),
+            marketability: self.marketability.clone(),
+            network: self.network.as_deref().cloned(),
+            streams: [
+                self.rng.clone(), self.demo_rng.clone(), self.dyn_rng.clone(), self.triad_rng.clone(),
+                self.market_rng.clone(), self.rep_rng.clone(), self.noise_rng.clone(), self.zi_rng.clone(),
+                self.learn_rng.clone(),
+            ],
+        });
+        state
+    }
+
+    /// Register an observer; observers are notified in registration order.
+    pub fn add_observer(&mut self, observer: Box<dyn Observer + Send>) {
+        self.observers.push(observer);
+    }
+
+    /// Call `callback` every `every` encounters (at least 1) and once at the end of each round,
+    /// e.g. to drive a progress bar. Cheaper than an `Observer` for this, since it sees counts
+    /// only. Replaces any callback set before.
+    pub fn set_progress(&mut self, every: usize, callback: ProgressCallback) {
+        self.progress = Some(ProgressHook { every: every.max(1), callback, encounters: 0, trades: 0 });
+    }
+
+    /// Replace the exchange mechanism (default: `CobbDouglasWalrasOracle`), e.g. to A/B a
+    /// bargaining rule over replayed encounters.
+    pub fn set_oracle(&mut self, oracle: Box<dyn ParetoOracle>) {
+        self.oracle = AuditedOracle::new(oracle);
+        self.oracle_seen = self.oracle.cache_stats().unwrap_or_default();
+    }
+
+    /// Start recording every encounter from now on (see `encounter_log`).
+    pub fn record_encounters(&mut self) {
+        self.encounter_log.get_or_insert_with(Vec::new);
+    }
+
+    /// Encounters recorded since `record_encounters` (empty if recording is off).
+    pub fn encounter_log(&self) -> &[Encounter] {
+        self.encounter_log.as_deref().unwrap_or(&[])
+    }
+
+    /// Replay a recorded schedule instead of drawing dyads: under `Scheduler::Sequential`, round
+    /// `t` runs exactly the logged encounters with `round == t`, in order, and the matcher and
+    /// `p2p_encounters_per_round` are bypassed. Round-robin schedules are already deterministic
+    /// and ignore the log.
+    pub fn replay_encounters(&mut self, log: Vec<Encounter>) {
+        self.replay = Some((log, 0));
+    }
+
+    /// Remove or rescale the trade that would be recorded as `events[index]`. The edit fires
+    /// once; later trades are executed normally.
+    pub fn edit_trade(&mut self, index: usize, edit: TradeEdit) {
+        self.trade_edit = Some((index, edit));
+    }
+
+    /// The config in force, with the `schedule` changes of past rounds applied and the goods
+    /// introduced so far moved from `new_goods` to `base_goods`.
+    pub fn config(&self) -> &SimConfig { &self.cfg }
+
+    /// The graph encounters are drawn from under `MatchingMode::Network`.
+    pub fn network(&self) -> Option<&Graph> { self.network.as_deref() }
+
+    pub fn state(&self) -> &SimState { &self.state }
+
+    /// Mutable access for between-round interventions.
+    pub fn state_mut(&mut self) -> &mut SimState {
+        self.summary_stale = true;
+        &mut self.state
+    }
+
+    /// Maintain a `PopulationSummary` as trades execute (see `population_summary`).
+    pub fn track_summary(&mut self) {
+        if self.summary.is_none() {
+            self.summary = Some(SummaryTracker::new(&self.state.agents));
+            self.summary_stale = false;
+        }
+    }
+
+    /// Current population summary for gossip; incremental when `track_summary` is on,
+    /// otherwise computed from scratch.
+    pub fn population_summary(&self) -> PopulationSummary {
+        match &self.summary {
+            Some(t) if !self.summary_stale => t.summary(self.round),
+            _ => PopulationSummary::from_agents(self.round, &self.state.agents),
+        }
+    }
+
+    /// Index of the next round to be executed.
+    pub fn round(&self) -> usize { self.round }
+
+    /// True once `cfg.rounds` have run or the convergence rule has fired.
+    pub fn is_finished(&self) -> bool {
+        self.round >= self.cfg.rounds || self.state.stopped_at.is_some()
+    }
+
+    /// Execute one round of P2P encounters. Returns the round's metrics, or `None` if the
+    /// run was already finished.
+    pub fn step_round(&mut self) -> Option<&RoundMetrics> {
+        if self.is_finished() { return None; }
+        let t = self.round;
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("round", round = t).entered();
+        self.oracle.take_counts();
+        for c in self.cfg.schedule.clone().iter().filter(|c| c.round == t) {
+            c.change.apply(&mut self.cfg);
+        }
+        self.introduce_goods(t);
+        let (entered, exited) = self.apply_demography();
+        let shocked = self.apply_shocks(t);
+        let preference_shocked = self.apply_preference_shocks(t);
+        let (credit_drawn, money_stock) = self.draw_credit();
+        if self.summary_stale {
+            if let Some(tracker) = &mut self.summary {
+                *tracker = SummaryTracker::new(&self.state.agents);
+            }
+            self.summary_stale = false;
+        }
+        // exact utilities once per round; trades then move them by the goods they touch
+        for a in self.state.agents.iter_mut() {
+            a.refresh_utility(self.cfg.min_qty);
+        }
+        for o in self.observers.iter_mut() {
+            o.on_round_start(t, &self.state);
+        }
+
+        self.rules = TradeRules::from_embargoes(&self.cfg.embargoes, self.cfg.base_goods.len(), t);
+        self.rules.speculation = self.marketability_view();
+        self.rules.quotes = self.cfg.brokers.as_ref()
+            .map(|b| Quotes { mid: self.prices.last().to_vec(), spread: b.spread });
+        let mut metrics = RoundMetrics {
+            round: t, encounters: 0, trades: 0, delta_u: 0.0, embargoed: self.rules.blocked_goods(),
+            time: self.cfg.clock.as_ref().map(|c| c.round_start(t)),
+            population: self.state.agents.len(), entered, exited, shocked, preference_shocked,
+            consumed: 0.0, decayed: 0.0, replenished: 0.0,
+            prices: Vec::new(),
+            costs: 0.0, cost_refused: 0, cap_refused: 0, over_budget: 0,
+            credit_drawn, credit_repaid: 0.0, money_velocity: 0.0, credit_utilization: 0.0,
+            cycles: 0, marketability: Vec::new(), money_good: None, speculative: 0,
+            tax_revenue: 0.0, redistributed: 0.0,
+            listings: 0, market_sales: 0,
+            active_traders: 0, reneged: 0, mean_reputation: 0.0, mistakes: 0,
+            broker_trades: 0, broker_wealth: 0.0, imitations: 0,
+            pareto_gap: 0.0, oracle_cache_hits: 0, oracle_cache_misses: 0,
+            oracle_degenerate: 0, oracle_unbracketed: 0, oracle_clamped: 0,
+            holders: Vec::new(),
+            inequality: InequalityMetrics::default(),
+        };
+        let first_event = self.state.events.len();
+        self.encounter_seq = 0;
+        self.cost_refused = 0;
+        self.cap_refused = 0;
+        self.reneged = 0;
+        self.over_budget = 0;
+        self.round_trades.clear();
+        if self.cfg.max_trades_per_agent.is_some() {
+            self.round_trades.resize(self.state.agents.len(), 0);
+        }
+        match self.cfg.scheduler {
+            Scheduler::Sequential => self.sequential_encounters(t, &mut metrics),
+            Scheduler::RoundRobin => self.round_robin_encounters(t, &mut metrics),
+        }
+
+        self.market_phase(t, &mut metrics);
+
+        if let MatchingMode::Persistent { decay, .. } = self.cfg.matching {
+            self.state.partners.decay(decay);
+        }
+        if let Some(r) = &self.cfg.reputation {
+            let keep = 1.0 - r.decay;
+            for a in self.state.agents.iter_mut() {
+                a.reputation *= keep;
+            }
+            let n = self.state.agents.len().max(1) as f64;
+            metrics.mean_reputation = self.state.agents.iter().map(|a| a.reputation).sum::<f64>() / n;
+            metrics.reneged = self.reneged;
+        }
+        let mut traded = vec![false; self.state.agents.len()];
+        for ev in self.state.events[first_event..].iter() {
+            traded[ev.i.index()] = true;
+            traded[ev.j.index()] = true;
+        }
+        metrics.active_traders = traded.iter().filter(|&&x| x).count();
+        if let Some(s) = &self.cfg.social_influence {
+            let events = &self.state.events[first_event..];
+            // cannot fail: `check_config` keeps the base good in range
+            metrics.imitations = dynamics::imitate(&mut self.state.agents, events, s.rate, self.cfg.base_good)
+                .unwrap_or(0);
+        }
+        if self.cfg.decision_noise.is_some() {
+            metrics.mistakes = self.state.events[first_event..].iter()
+                .filter(|e| !e.speculative && (e.delta_u_i <= 0.0 || e.delta_u_j <= 0.0))
+                .count();
+        }
+
+        metrics.costs = self.state.events[first_event..].iter().map(|e| e.cost_i + e.cost_j).sum();
+        metrics.cost_refused = self.cost_refused;
+        metrics.cap_refused = self.cap_refused;
+        metrics.over_budget = self.over_budget;
+        metrics.tax_revenue = self.state.events[first_event..].iter().map(|e| e.tax).sum();
+        let agents = &self.state.agents;
+        metrics.broker_trades = self.state.events[first_event..].iter()
+            .filter(|e| agents[e.i.index()].broker || agents[e.j.index()].broker)
+            .count();
+        if let Some(spec) = &self.cfg.money_emergence {
+            let n = self.cfg.base_goods.len();
+            self.marketability.resize(n, Ema::new(spec.alpha));
+            let events = &self.state.events[first_event..];
+            if !events.is_empty() {
+                let mut counts = vec![0.0; n];
+                for ev in events.iter() {
+                    counts[ev.good_a.index()] += 1.0;
+                    counts[ev.good_b.index()] += 1.0;
+                }
+                for (ema, c) in self.marketability.iter_mut().zip(counts) {
+                    ema.push(c / events.len() as f64);
+                }
+            }
+            metrics.speculative = events.iter().filter(|e| e.speculative).count();
+            metrics.marketability = self.marketability_shares();
+            metrics.money_good = money_good(&metrics.marketability);
+        }
+        if let Some(m) = &self.cfg.monetary {
+            let volume = money::money_volume(&self.state.events[first_event..], self.cfg.base_good);
+            metrics.money_velocity = money::velocity(volume, money_stock);
+            for a in self.state.agents.iter_mut() {
+                metrics.credit_repaid += money::repay(a, m.repayment_rate, self.cfg.base_good, self.cfg.min_qty);
+            }
+            let debt: f64 = self.state.agents.iter().map(|a| a.debt).sum();
+            let lines = m.credit_limit * self.state.agents.len() as f64;
+            metrics.credit_utilization = if lines > 0.0 { debt / lines } else { 0.0 };
+            self.summary_stale = true;
+        }
+        self.apply_dynamics(&mut metrics);
+        if let Some(p) = &self.cfg.policy {
+            let paid = policy::redistribute(
+                &mut self.state.agents, self.state.treasury, p.redistribution, self.cfg.base_good, self.prices.last(),
+            );
+            self.state.treasury -= paid;
+            metrics.redistributed = paid;
+            if paid > 0.0 {
+                self.summary_stale = true;
+            }
+        }
+
+        let rates = exchange_rate_stats(t, &self.state.events[first_event..], self.cfg.base_good);
+        let points = self.prices.update(t, &rates, &self.state.agents);
+        self.state.prices.extend(points);
+        self.state.exchange_rates.extend(rates);
+        metrics.inequality = inequality(&self.state.agents, self.prices.last(), self.cfg.min_qty);
+        metrics.prices = self.prices.last().to_vec();
+        metrics.broker_wealth = self.state.agents.iter()
+            .filter(|a| a.broker)
+            .map(|a| wealth(a, &metrics.prices))
+            .sum();
+        metrics.pareto_gap = pareto_gap(&self.state, &self.cfg);
+        if let Some(seen) = self.oracle.cache_stats() {
+            metrics.oracle_cache_hits = (seen.hits - self.oracle_seen.hits) as usize;
+            metrics.oracle_cache_misses = (seen.misses - self.oracle_seen.misses) as usize;
+            self.oracle_seen = seen;
+        }
+        let statuses = self.oracle.take_counts();
+        metrics.oracle_degenerate = statuses.degenerate;
+        metrics.oracle_unbracketed = statuses.unbracketed;
+        metrics.oracle_clamped = statuses.clamped;
+        metrics.holders = good_holders(&self.state.agents, self.cfg.base_goods.len(), self.cfg.min_qty);
+
+        let quiet = metrics.trades == 0
+            || self.cfg.stop_when_converged.as_ref().is_some_and(|c| metrics.delta_u < c.min_utility_change);
+        trace_event!(
+            tracing::Level::DEBUG,
+            encounters = metrics.encounters, trades = metrics.trades, delta_u = metrics.delta_u,
+            population = metrics.population, "round done"
+        );
+        self.state.metrics.push(metrics);
+        self.round += 1;
+        if let Some(m) = self.state.metrics.last() {
+            for o in self.observers.iter_mut() {
+                o.on_round_end(m, &self.state);
+            }
+            if let Some(hook) = &mut self.progress {
+                hook.report(self.cfg.rounds, m, true);
+                hook.encounters += m.encounters as u64;
+                hook.trades += m.trades as u64;
+            }
+        }
+
+        if let Some(conv) = &self.cfg.stop_when_converged {
+            self.quiet_rounds = if quiet { self.quiet_rounds + 1 } else { 0 };
+            if self.quiet_rounds >= conv.patience.max(1) {
+                self.state.stopped_at = Some(t);
+            }
+        }
+        self.state.metrics.last()
+    }
+
+    /// Step until finished.
+    pub fn run_to_end(&mut self) {
+        while self.step_round().is_some() {}
+    }
+
+    /// Consume the engine and return the final state.
+    pub fn finish(self) -> SimState {
+        self.state
+    }
+
+    /// `p2p_encounters_per_round` encounters drawn one at a time by the configured matcher.
+    /// Exogenous end-of-round flows (`dynamics`).
+    fn apply_dynamics(&mut self, metrics: &mut RoundMetrics) {
+        let consumption = self.cfg.consumption.as_ref();
+        let decaying = self.cfg.good_decay.iter().any(|&d| d > 0.0);
+        if consumption.is_none() && !decaying { return; }
+        for a in self.state.agents.iter_mut() {
+            if let Some(c) = consumption.filter(|c| c.rate > 0.0) {
+                metrics.consumed += dynamics::consume(a, c.rate);
+            }
+            if decaying {
+                metrics.decayed += dynamics::decay(a, &self.cfg.good_decay);
+            }
+            if let Some(c) = consumption.filter(|c| c.income > 0.0) {
+                let spec = group_endowment(&self.cfg, a);
+                metrics.replenished +=
+                    dynamics::replenish(a, spec, c.income, self.cfg.initial_endowment_scale, &mut self.dyn_rng);
+            }
+        }
+        self.summary_stale = true;
+    }
+
+    /// Smoothed share of trades each good took part in; `2 / n` before the first trade.
+    fn marketability_shares(&self) -> Vec<f64> {
+        let n = self.cfg.base_goods.len();
+        (0..n)
+            .map(|k| self.marketability.get(k).and_then(Ema::value).unwrap_or(2.0 / n.max(1) as f64))
+            .collect()
+    }
+
+    /// Resale prospects for this round's speculative search under `SimConfig::money_emergence`.
+    fn marketability_view(&self) -> Option<Marketability> {
+        let spec = self.cfg.money_emergence.as_ref()?;
+        Some(Marketability {
+            share: self.marketability_shares(),
+            mean_holding: mean_endowments(&self.state),
+            weight: spec.weight,
+            step: spec.step,
+        })
+    }
+
+    /// The `SimConfig::posted_prices` phase: post listings, then let every agent shop in random
+    /// order. Sales are recorded as trade events, seller as `i`, at the end of the round's clock.
+    fn market_phase(&mut self, t: usize, metrics: &mut RoundMetrics) {
+        let Some(spec) = &self.cfg.posted_prices else { return };
+        let (base, min_qty) = (self.cfg.base_good, self.cfg.min_qty);
+        let mut listings = market::post_listings(&self.state.agents, base, spec.markup, spec.lot, min_qty, &self.rules);
+        metrics.listings = listings.len();
+        let mut order: Vec<usize> = (0..self.state.agents.len()).collect();
+        order.shuffle(&mut self.market_rng);
+        let time = self.cfg.clock.as_ref().map(|c| c.at(t, 1.0 - f64::EPSILON));
+        let first = self.state.events.len();
+        let mut budget = market::Budget {
+            limit: self.cfg.max_trades_per_agent, spent: &mut self.round_trades, refused: 0,
+        };
+        for buyer in order {
+            for sale in market::shop(&mut self.state.agents, &mut listings, buyer, base, min_qty, &mut budget) {
+                metrics.market_sales += 1;
+                metrics.trades += 1;
+                metrics.delta_u += sale.delta_u_seller + sale.delta_u_buyer;
+                self.state.events.push(TradeEvent {
+                    round: t,
+                    i: AgentIdx(sale.seller),
+                    j: AgentIdx(sale.buyer),
+                    good_a: sale.good,
+                    good_b: GoodId(base),
+                    q_ab: sale.price,
+                    delta_a_i: -sale.quantity,
+                    delta_b_i: sale.price * sale.quantity,
+                    delta_u_i: sale.delta_u_seller,
+                    delta_u_j: sale.delta_u_buyer,
+                    id_i: self.state.agents[sale.seller].id,
+                    id_j: self.state.agents[sale.buyer].id,
+                    time,
+                    cost_i: 0.0,
+                    cost_j: 0.0,
+                    tax: 0.0,
+                    speculative: false,
+                    seq: self.state.events.len() as u64,
+                    pre_i: sale.pre_seller,
+                    pre_j: sale.pre_buyer,
+                    post_i: sale.post_seller,
+                    post_j: sale.post_buyer,
+                });
+            }
+        }
+        self.over_budget += budget.refused;
+        if self.state.events.len() > first {
+            self.summary_stale = true;
+        }
+        for ev in self.state.events[first..].iter() {
+            for o in self.observers.iter_mut() {
+                o.on_trade(ev);
+            }
+        }
+    }
+
+    /// Under `SimConfig::monetary`, draw every agent's unused credit line before trading; returns
+    /// the credit drawn and the money stock afterwards.
+    fn draw_credit(&mut self) -> (f64, f64) {
+        let Some(m) = &self.cfg.monetary else { return (0.0, 0.0) };
+        let (mut drawn, mut stock) = (0.0, 0.0);
+        for a in self.state.agents.iter_mut() {
+            drawn += money::draw_credit(a, m.credit_limit, self.cfg.base_good);
+            stock += a.e.get(self.cfg.base_good).copied().unwrap_or(0.0);
+        }
+        if drawn > 0.0 {
+            self.summary_stale = true;
+        }
+        (drawn, stock)
+    }
+
+    /// Apply the shocks scheduled for round `t`; returns the number of agents hit.
+    fn apply_shocks(&mut self, t: usize) -> usize {
+        let mut hit = 0;
+        for sh in self.cfg.shocks.iter().filter(|sh| sh.round == t) {
+            hit += dynamics::shock(&mut self.state.agents, sh, &mut self.dyn_rng);
+        }
+        if hit > 0 {
+            self.summary_stale = true;
+        }
+        hit
+    }
+
+    /// Apply the preference shocks scheduled for round `t`; returns the number of agents hit.
+    fn apply_preference_shocks(&mut self, t: usize) -> usize {
+        let mut hit = 0;
+        for sh in self.cfg.preference_shocks.iter().filter(|sh| sh.round == t) {
+            // cannot fail: `check_config` keeps the base good in range
+            hit += dynamics::preference_shock(&mut self.state.agents, sh, self.cfg.base_good, &mut self.dyn_rng)
+                .unwrap_or(0);
+        }
+        hit
+    }
+
+    /// Add the goods of `SimConfig::new_goods` that enter in round `t`.
+    fn introduce_goods(&mut self, t: usize) {
+        let intros: Vec<GoodIntroduction> =
+            self.cfg.introductions().into_iter().filter(|g| g.round <= t).cloned().collect();
+        if intros.is_empty() { return; }
+        self.cfg.new_goods.retain(|g| g.round > t);
+        for intro in intros.into_iter() {
+            let range = intro_alpha_range(&self.cfg, &intro);
+            for a in self.state.agents.iter_mut() {
+                // alpha range and base were validated up front
+                let _ = dynamics::introduce_good(
+                    a, &intro, range, self.cfg.base_good, self.cfg.initial_endowment_scale, &mut self.dyn_rng,
+                );
+            }
+            let n = self.cfg.base_goods.len();
+            if intro.decay > 0.0 && self.cfg.good_decay.is_empty() {
+                self.cfg.good_decay = vec![0.0; n];
+            }
+            if !self.cfg.good_decay.is_empty() {
+                self.cfg.good_decay.push(intro.decay);
+            }
+            self.cfg.base_goods.push(intro.name.clone());
+            self.cfg.base_goods_quantity = self.cfg.base_goods.len();
+            self.prices.add_good();
+            self.introduced.push(intro);
+            self.summary_stale = true;
+        }
+    }
+
+    /// Apply `SimConfig::demography` for the coming round; returns (entered, exited).
+    fn apply_demography(&mut self) -> (usize, usize) {
+        let Some(demo) = self.cfg.demography.clone() else { return (0, 0) };
+        let n_goods = self.cfg.base_goods.len();
+
+        let mut exited = 0;
+        if demo.exit_prob > 0.0 {
+            let min_pop = demo.min_population.max(2);
+            let mut keep = vec![true; self.state.agents.len()];
+            for k in keep.iter_mut() {
+                if self.demo_rng.gen::<f64>() < demo.exit_prob {
+                    *k = false;
+                }
+            }
+            // honour the floor by readmitting the last exits
+            let mut remaining = keep.iter().filter(|&&k| k).count();
+            for k in keep.iter_mut().rev() {
+                if remaining >= min_pop { break; }
+                if !*k {
+                    *k = true;
+                    remaining += 1;
+                }
+            }
+            exited = keep.len() - remaining;
+            if exited > 0 {
+                let mut left = vec![0.0; n_goods];
+                for (a, _) in self.state.agents.iter().zip(keep.iter()).filter(|&(_, &k)| !k) {
+                    for (l, &x) in left.iter_mut().zip(a.e.iter()) {
+                        *l += x;
+                    }
                 }
+                let mut it = keep.iter();
+                self.state.agents.retain(|_| *it.next().unwrap_or(&true));
+                self.state.partners.retain_agents(&keep);
+                if let ExitMode::Redistribute = demo.exit {
+                    let share = 1.0 / self.state.agents.len() as f64;
+                    for a in self.state.agents.iter_mut() {
+                        for (x, &l) in a.e.iter_mut().zip(left.iter()) {
+                            *x += l * share;
+                        }
+                        a.invalidate_utility();
+                    }
+                }
+            }
+        }
+
+        let rate = demo.entry_rate.max(0.0);
+        let mut entered = rate.floor() as usize;
+        if self.demo_rng.gen::<f64>() < rate.fract() {
+            entered += 1;
+        }
+        if entered > 0 {
+            let groups = resolved_groups(&self.cfg);
+            for _ in 0..entered {
+                let total: usize = groups.iter().map(|g| g.size).sum();
+                let mut pick = self.demo_rng.gen_range(0..total.max(1));
+                let g = groups.iter()
+                    .find(|g| {
+                        let hit = pick < g.size;
+                        pick = pick.saturating_sub(g.size);
+                        hit
+                    })
+                    .unwrap_or(&groups[0]);
+                // init_agents validated the groups, so drawing cannot fail; the initial goods are
+                // drawn from the group's spec, the goods introduced since from theirs
+                let n_initial = n_goods - self.introduced.len();
+                let Ok(mut a) = draw_agent(&self.cfg, g, self.next_id, n_initial, &mut self.demo_rng) else { continue };
+                for intro in self.introduced.iter() {
+                    let range = intro_alpha_range(&self.cfg, intro);
+                    let _ = dynamics::introduce_good(
+                        &mut a, intro, range, self.cfg.base_good, self.cfg.initial_endowment_scale, &mut self.demo_rng,
+                    );
+                }
+                self.state.agents.push(a);
+                self.next_id += 1;
+            }
+        }
+
+        if entered + exited > 0 {
+            self.summary_stale = true;
+        }
+        (entered, exited)
+    }
+
+    fn sequential_encounters(&mut self, t: usize, metrics: &mut RoundMetrics) {
+        if self.replay.is_some() {
+            return self.replayed_encounters(t, metrics);
+        }
+        if self.network.as_ref().is_some_and(|g| g.num_nodes() != self.state.agents.len()) {
+            // population edited through `state_mut`: regenerate over the new population
+            self.network = build_network(&self.cfg, self.state.agents.len()).ok().flatten();
+        }
+        let matcher = Matcher::for_round(
+            &self.cfg.matching, &self.state.agents, self.network.as_ref(), Some(&self.state.partners),
+        );
+        for _ in 0..self.cfg.p2p_encounters_per_round {
+            let Some((i, j)) = self.draw_within_budget(&matcher) else { break };
+            self.note_encounter(t, i, j, metrics);
+            self.trade_dyad(t, i, j, metrics);
+        }
+    }
+
+    /// The next encounter of `matcher`, passing over pairs with a side that has used up its
+    /// `SimConfig::max_trades_per_agent`. Gives up after as many draws as there are agents, as
+    /// then hardly anyone has budget left.
+    fn draw_within_budget(&mut self, matcher: &Matcher) -> Option<(usize, usize)> {
+        let draws = if self.cfg.max_trades_per_agent.is_some() { self.state.agents.len().max(1) } else { 1 };
+        for _ in 0..draws {
+            let (i, j) = matcher.draw_pair(&mut self.rng)?;
+            if self.within_budget(i) && self.within_budget(j) {
+                return Some((i, j));
+            }
+            self.over_budget += 1;
+        }
+        None
+    }
+
+    /// Whether agent `m` may take part in another trade this round.
+    fn within_budget(&self, m: usize) -> bool {
+        match self.cfg.max_trades_per_agent {
+            Some(budget) => self.round_trades.get(m).copied().unwrap_or(0) < budget,
+            None => true,
+        }
+    }
+
+    /// Count an executed trade against the budget of each of `agents`.
+    fn spend_budget(&mut self, agents: &[usize]) {
+        for &m in agents {
+            if let Some(n) = self.round_trades.get_mut(m) {
+                *n += 1;
+            }
+        }
+    }
+
+    /// Search and execute the best trade between `i` and `j`; failing that, try a three-way
+    /// cycle with a random third agent when `SimConfig::triads` is set.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", name = "dyad", skip(self, t, metrics)))]
+    fn trade_dyad(&mut self, t: usize, i: usize, j: usize, metrics: &mut RoundMetrics) {
+        let (vi, vj) = (self.perceive(i), self.perceive(j));
+        let goods = self.learned_goods(i, j);
+        let ai = vi.as_ref().unwrap_or(&self.state.agents[i]);
+        let aj = vj.as_ref().unwrap_or(&self.state.agents[j]);
+        let cand = match self.cfg.zero_intelligence {
+            Some(_) => self.zero_intelligence_proposal(i, j),
+            None => find_trade(&self.cfg, &self.oracle, &self.rules, ai, aj, goods.as_deref()),
+        };
+        if let Some((trades, du)) = cand.and_then(|p| self.execute_proposal(t, i, j, p)) {
+            trace_event!(tracing::Level::TRACE, trades, delta_u = du, "accepted");
+            metrics.trades += trades;
+            metrics.delta_u += du;
+        } else if let Some(du) = self.try_cycle(t, i, j) {
+            trace_event!(tracing::Level::TRACE, delta_u = du, "cycle");
+            metrics.cycles += 1;
+            metrics.delta_u += du;
+        } else {
+            self.note_rejection(t, i, j);
+        }
+    }
+
+    /// Complete the failed encounter (i, j) with a uniformly drawn third agent `k` and execute
+    /// the best three-way cycle among them, if any. Returns the total utility gain.
+    fn try_cycle(&mut self, t: usize, i: usize, j: usize) -> Option<f64> {
+        let step = self.cfg.triads.as_ref()?.step;
+        let n = self.state.agents.len();
+        if n < 3 || i == j { return None; }
+        let mut k = self.triad_rng.gen_range(0..n - 2);
+        for skip in [i.min(j), i.max(j)] {
+            if k >= skip { k += 1; }
+        }
+        if ![i, j, k].into_iter().all(|m| self.within_budget(m)) {
+            return None;
+        }
+        let agents = &self.state.agents;
+        let cycle = best_three_way_cycle(&agents[i], &agents[j], &agents[k], step, self.cfg.min_qty, &self.rules)?;
+
+        let mut trio = [i, j, k].map(|m| std::mem::take(&mut self.state.agents[m]));
+        let [ai, aj, ak] = &mut trio;
+        let applied = apply_three_way_cycle(ai, aj, ak, &cycle, self.cfg.min_qty);
+        let ids = [ai.id, aj.id, ak.id];
+        for (m, a) in [i, j, k].into_iter().zip(trio) {
+            self.state.agents[m] = a;
+        }
+        applied.ok()?;
+        self.summary_stale = true;
+        self.spend_budget(&[i, j, k]);
+        self.state.cycles.push(CycleEvent {
+            round: t,
+            agents: [AgentIdx(i), AgentIdx(j), AgentIdx(k)],
+            ids,
+            goods: cycle.goods,
+            quantities: cycle.quantities,
+            delta_u: cycle.delta_u,
+        });
+        Some(cycle.delta_u.iter().sum())
+    }
+
+    /// Logged encounters of round `t` (see `replay_encounters`). Entries of earlier rounds that
+    /// were not consumed, and entries naming agents outside the population, are skipped.
+    fn replayed_encounters(&mut self, t: usize, metrics: &mut RoundMetrics) {
+        let n = self.state.agents.len();
+        while let Some((log, cursor)) = &mut self.replay {
+            let Some(next) = log.get(*cursor).copied().filter(|e| e.round <= t) else { break };
+            *cursor += 1;
+            let (i, j) = (next.i.index(), next.j.index());
+            if next.round < t || i >= n || j >= n || i == j { continue; }
+
+            self.note_encounter(t, i, j, metrics);
+            self.trade_dyad(t, i, j, metrics);
+        }
+    }
+
+    fn note_encounter(&mut self, t: usize, i: usize, j: usize, metrics: &mut RoundMetrics) {
+        metrics.encounters += 1;
+        self.encounter_seq = metrics.encounters;
+        if let Some(hook) = self.progress.as_mut().filter(|h| metrics.encounters.is_multiple_of(h.every)) {
+            hook.report(self.cfg.rounds, metrics, false);
+        }
+        if let Some(log) = &mut self.encounter_log {
+            log.push(Encounter { round: t, i: AgentIdx(i), j: AgentIdx(j) });
+        }
+    }
+
+    /// Round-robin sub-steps: each is a perfect matching of the population (see
+    /// `matching::round_robin_pairs`), so its dyads are disjoint. Candidates for a sub-step are
+    /// searched against the same pre-sub-step state (concurrently with the `parallel` feature)
+    /// and then executed in pair order, making results independent of thread scheduling.
+    fn round_robin_encounters(&mut self, t: usize, metrics: &mut RoundMetrics) {
+        if self.rr_order.len() != self.state.agents.len() {
+            // population edited through `state_mut`: re-seed the tournament
+            self.rr_order = (0..self.state.agents.len()).collect();
+            self.rr_order.shuffle(&mut StdRng::seed_from_u64(self.cfg.seed ^ 0x5EED_0F2B_1B1B));
+            self.rr_step = 0;
+        }
+        let mut budget = self.cfg.p2p_encounters_per_round;
+        while budget > 0 {
+            let mut pairs = round_robin_pairs(&self.rr_order, self.rr_step);
+            self.rr_step += 1;
+            if pairs.is_empty() { break; }
+            pairs.truncate(budget);
+            budget -= pairs.len();
+
+            let views: Vec<_> = pairs.iter().map(|&(i, j)| (self.perceive(i), self.perceive(j))).collect();
+            let goods: Vec<_> = pairs.iter().map(|&(i, j)| self.learned_goods(i, j)).collect();
+            let cands = match self.cfg.zero_intelligence {
+                // random proposals draw from one stream, so they are made in pair order
+                Some(_) => pairs.iter().map(|&(i, j)| self.zero_intelligence_proposal(i, j)).collect::<Vec<_>>(),
+                None => self.search_disjoint(&pairs, &views, &goods),
             };
+            for (&(i, j), cand) in pairs.iter().zip(cands) {
+                self.note_encounter(t, i, j, metrics);
+                if let Some((trades, du)) = cand.and_then(|p| self.execute_proposal(t, i, j, p)) {
+                    metrics.trades += trades;
+                    metrics.delta_u += du;
+                } else {
+                    self.note_rejection(t, i, j);
+                }
+            }
+        }
+    }
+
+    /// Search every dyad of a conflict-free sub-step, each side as seen in `views` (see
+    /// `perceive`), over the candidate goods drawn in `goods` (see `learned_goods`).
+    fn search_disjoint(
+        &self,
+        pairs: &[(usize, usize)],
+        views: &[(Option<Agent>, Option<Agent>)],
+        goods: &[Option<Vec<usize>>],
+    ) -> Vec<Option<Proposal>> {
+        let (cfg, oracle, rules, agents) = (&self.cfg, &self.oracle, &self.rules, &self.state.agents);
+        // a dyad, its views and its candidate goods, as zipped below
+        type Job<'a> = ((&'a (usize, usize), &'a (Option<Agent>, Option<Agent>)), &'a Option<Vec<usize>>);
+        let search = |((&(i, j), (vi, vj)), g): Job<'_>| {
+            let (ai, aj) = (vi.as_ref().unwrap_or(&agents[i]), vj.as_ref().unwrap_or(&agents[j]));
+            find_trade(cfg, oracle, rules, ai, aj, g.as_deref())
+        };
+
+        #[cfg(feature = "parallel")]
+        let out = {
+            use rayon::prelude::*;
+            pairs.par_iter().zip(views.par_iter()).zip(goods.par_iter()).map(search).collect()
+        };
+        #[cfg(not(feature = "parallel"))]
+        let out = pairs.iter().zip(views.iter()).zip(goods.iter()).map(search).collect();
+        out
+    }
 
-            // Snapshot utilities pre-trade for logging
-            let ui0 = cd_utility(&ai.beta, &ai.e, cfg.min_qty);
-            let uj0 = cd_utility(&aj.beta, &aj.e, cfg.min_qty);
-
-            let cand = match cfg.pairing_mode {
-                PairingMode::AgainstBase => best_trade_against_base(
-                    ai, aj, cfg.base_good, cfg.min_qty, cfg.oracle_bisect_iters, &oracle
-                ),
-                PairingMode::AllPairsPruned => best_trade_over_all_pairs_pruned(
-                    ai, aj, cfg.base_good, cfg.candidate_goods_k, cfg.min_qty, cfg.oracle_bisect_iters, &oracle
-                ),
+    /// Candidate goods of (i, j) under `PairingMode::Learning`, drawn before the search so that
+    /// exploration does not depend on thread scheduling. `None` in other modes.
+    fn learned_goods(&mut self, i: usize, j: usize) -> Option<Vec<usize>> {
+        let PairingMode::Learning { epsilon, .. } = self.cfg.pairing_mode else { return None };
+        let (ai, aj) = (&self.state.agents[i], &self.state.agents[j]);
+        let k = self.cfg.candidate_goods_k;
+        Some(learned_candidate_goods(ai, aj, self.cfg.base_good, k, epsilon, &self.rules, &mut self.learn_rng))
+    }
+
+    /// Under `PairingMode::Learning`, update both sides' value of the good they gave in each trade
+    /// recorded from event `first` on, towards the utility they gained.
+    fn learn_offers(&mut self, first: usize) {
+        let PairingMode::Learning { learning_rate, .. } = self.cfg.pairing_mode else { return };
+        for k in first..self.state.events.len() {
+            let ev = &self.state.events[k];
+            // the leg with a negative delta for i is the one i gave
+            let (gave_i, gave_j) = if ev.delta_a_i < 0.0 { (ev.good_a, ev.good_b) } else { (ev.good_b, ev.good_a) };
+            let (i, j, du_i, du_j) = (ev.i.index(), ev.j.index(), ev.delta_u_i, ev.delta_u_j);
+            learn_offer_value(&mut self.state.agents[i], gave_i.index(), du_i, learning_rate);
+            learn_offer_value(&mut self.state.agents[j], gave_j.index(), du_j, learning_rate);
+        }
+    }
+
+    /// A random proposal for (i, j) under `SimConfig::zero_intelligence`.
+    fn zero_intelligence_proposal(&mut self, i: usize, j: usize) -> Option<Proposal> {
+        let constrained = self.cfg.zero_intelligence.as_ref()?.constrained;
+        let (ai, aj) = (&self.state.agents[i], &self.state.agents[j]);
+        let cand = zero_intelligence_trade(ai, aj, constrained, self.cfg.min_qty, &self.rules, &mut self.zi_rng);
+        cand.map(Proposal::Pair)
+    }
+
+    /// Agent `i` as it sees itself this encounter under `SimConfig::decision_noise`: its current
+    /// holdings with freshly misperceived exponents, and the pairwise alphas they imply (which is
+    /// what the oracle reads). `None` without noise (search the agent itself).
+    fn perceive(&mut self, i: usize) -> Option<Agent> {
+        let sigma = self.cfg.decision_noise.as_ref()?.sigma;
+        let base = self.cfg.base_good;
+        let a = &self.state.agents[i];
+        let beta = perturbed_beta(&a.beta, sigma, &mut self.noise_rng);
+        let alpha_to_base = (0..beta.len())
+            .map(|k| if k == base { 0.5 } else { alpha_from_beta(&beta, k, base, 1e-6) })
+            .collect();
+        Some(Agent { beta, alpha_to_base, utility_cache: None, ..a.clone() })
+    }
+
+    /// Execute a proposal; returns the number of trade events recorded and the realized total
+    /// utility change.
+    fn execute_proposal(&mut self, t: usize, i: usize, j: usize, proposal: Proposal) -> Option<(usize, f64)> {
+        self.refusal = None;
+        if !(self.within_budget(i) && self.within_budget(j)) {
+            self.over_budget += 1;
+            self.refusal = Some(RejectionReason::OverBudget);
+            return None;
+        }
+        if self.reneges(i, j) {
+            self.refusal = Some(RejectionReason::Reneged);
+            return None;
+        }
+        let first = self.state.events.len();
+        let done = match proposal {
+            Proposal::Pair(cand) => self.execute(t, i, j, cand).map(|du| (1, du)),
+            Proposal::Bundle(trade) => self.execute_bundle(t, i, j, trade),
+        };
+        if done.is_some() {
+            self.spend_budget(&[i, j]);
+            self.earn_reputation(i, j, first);
+            self.learn_offers(first);
+            self.remember_prices(first);
+        }
+        done
+    }
+
+    /// Under `SimConfig::reputation`, each side reneges on the agreed trade with `renege_prob`
+    /// and pays `breach_penalty`; true if either did.
+    fn reneges(&mut self, i: usize, j: usize) -> bool {
+        let Some(r) = &self.cfg.reputation else { return false };
+        if r.renege_prob <= 0.0 { return false; }
+        let mut broken = false;
+        for k in [i, j] {
+            if self.rep_rng.gen::<f64>() < r.renege_prob {
+                self.state.agents[k].reputation -= r.breach_penalty;
+                broken = true;
+            }
+        }
+        if broken {
+            self.reneged += 1;
+        }
+        broken
+    }
+
+    /// Under `SimConfig::price_expectations`, fold the base-good prices of the trades recorded
+    /// from event `first` on into both sides' `PriceMemory`.
+    fn remember_prices(&mut self, first: usize) {
+        let Some(spec) = &self.cfg.price_expectations else { return };
+        for k in first..self.state.events.len() {
+            let ev = &self.state.events[k];
+            let (a, b) = (ev.good_a.index(), ev.good_b.index());
+            let Some((g, price)) = base_price(a, b, ev.q_ab, self.cfg.base_good) else { continue };
+            for m in [ev.i.index(), ev.j.index()] {
+                let memory = self.state.agents[m].price_memory.get_or_insert_with(PriceMemory::default);
+                memory.tolerance = spec.tolerance;
+                memory.observe(g, price, spec.memory);
+            }
+        }
+    }
+
+    /// Under `SimConfig::reputation`, credit `i` and `j` for the trade recorded from event
+    /// `first` on: `honor_reward` each, plus `surplus_weight` times the counterparty's gain.
+    fn earn_reputation(&mut self, i: usize, j: usize, first: usize) {
+        let Some(r) = &self.cfg.reputation else { return };
+        let (mut gain_i, mut gain_j) = (0.0, 0.0);
+        for ev in self.state.events[first..].iter() {
+            gain_i += ev.delta_u_i;
+            gain_j += ev.delta_u_j;
+        }
+        self.state.agents[i].reputation += r.honor_reward + r.surplus_weight * gain_j;
+        self.state.agents[j].reputation += r.honor_reward + r.surplus_weight * gain_i;
+    }
+
+    /// Simulated time of the current encounter, when `SimConfig::clock` is set.
+    fn trade_time(&self, t: usize) -> Option<f64> {
+        self.cfg.clock.as_ref().map(|c| {
+            // spread the round's encounters evenly over its duration
+            let per_round = self.cfg.p2p_encounters_per_round.max(1) as f64;
+            let k = self.encounter_seq.saturating_sub(1) as f64;
+            c.at(t, (k / per_round).min(1.0 - f64::EPSILON))
+        })
+    }
+
+    /// Execute a package trade between agents i and j (after the step cap: `trade_step_cap_frac`,
+    /// or `max_frac` under the adaptive cap) and record one event per leg, each carrying the
+    /// utility change of applying its leg after the previous ones.
+    fn execute_bundle(&mut self, t: usize, i: usize, j: usize, mut trade: BundleTrade) -> Option<(usize, f64)> {
+        let legs = trade.legs().len();
+        let first = self.state.events.len();
+        let edit = match self.trade_edit {
+            Some((k, edit)) if (first..first + legs).contains(&k) => {
+                self.trade_edit = None;
+                Some(edit)
+            }
+            _ => None,
+        };
+        match edit {
+            Some(TradeEdit::Remove) => {
+                self.refusal = Some(RejectionReason::Removed);
+                return None;
+            }
+            Some(TradeEdit::Scale(f)) => trade.scale(f.max(0.0)),
+            None => {}
+        }
+        let cap = match self.cfg.step_cap {
+            StepCap::Fixed => self.cfg.trade_step_cap_frac.clamp(0.0, 1.0),
+            StepCap::Adaptive { max_frac, .. } => max_frac,
+        };
+        let time = self.trade_time(t);
+
+        let min_qty = self.cfg.min_qty;
+        let conserve = self.cfg.conserve_quantities;
+        let (ai, aj) = pair_mut(&mut self.state.agents, i, j);
+        let cap = if conserve { cap.min(bundle_conserving_fraction(ai, aj, &trade, min_qty)) } else { cap };
+        if cap <= 0.0 {
+            self.refusal = Some(RejectionReason::CappedOut);
+            return None;
+        }
+        // as in `execute`: a capped package that was a mutual gain must still be one
+        if cap < 1.0 {
+            let vetted = self.cfg.decision_noise.is_none() && trade.delta_u_i > 0.0 && trade.delta_u_j > 0.0;
+            if !scale_and_validate_bundle(ai, aj, &mut trade, cap, min_qty) && vetted {
+                self.cap_refused += 1;
+                self.refusal = Some(RejectionReason::CappedOut);
+                return None;
+            }
+        }
+        let (mut xi, mut xj) = (ai.e.clone(), aj.e.clone());
+        if conserve {
+            apply_bundle_trade_conserving(ai, aj, &trade, min_qty).ok()?;
+        } else {
+            apply_bundle_trade(ai, aj, &trade, min_qty).ok()?;
+        }
+        if let Some(tracker) = &mut self.summary {
+            for g in trade.goods.iter().map(|g| g.index()) {
+                tracker.update(g, xi[g], ai.e[g]);
+                tracker.update(g, xj[g], aj.e[g]);
+            }
+        }
+        let mut total = 0.0;
+        for (k, leg) in trade.legs().into_iter().enumerate() {
+            let (g, base) = (leg.good_a.index(), leg.good_b.index());
+            let ui0 = cd_utility(&ai.beta, &xi, min_qty);
+            let uj0 = cd_utility(&aj.beta, &xj, min_qty);
+            let (pre_i, pre_j) = (leaf_hash(ai.id, &xi), leaf_hash(aj.id, &xj));
+            xi[g] += leg.delta_a_i;
+            xi[base] += leg.delta_b_i;
+            xj[g] -= leg.delta_a_i;
+            xj[base] -= leg.delta_b_i;
+            let delta_u_i = cd_utility(&ai.beta, &xi, min_qty) - ui0;
+            let delta_u_j = cd_utility(&aj.beta, &xj, min_qty) - uj0;
+            total += delta_u_i + delta_u_j;
+            // the last leg ends at the holdings actually reached (floors included)
+            let (post_i, post_j) = if k + 1 == legs {
+                (leaf_hash(ai.id, &ai.e), leaf_hash(aj.id, &aj.e))
+            } else {
+                (leaf_hash(ai.id, &xi), leaf_hash(aj.id, &xj))
             };
+            self.state.events.push(TradeEvent {
+                round: t,
+                i: AgentIdx(i),
+                j: AgentIdx(j),
+                good_a: leg.good_a,
+                good_b: leg.good_b,
+                q_ab: leg.q_ab,
+                delta_a_i: leg.delta_a_i,
+                delta_b_i: leg.delta_b_i,
+                delta_u_i,
+                delta_u_j,
+                id_i: ai.id,
+                id_j: aj.id,
+                time,
+                cost_i: 0.0,
+                cost_j: 0.0,
+                tax: 0.0,
+                speculative: false,
+                seq: self.state.events.len() as u64,
+                pre_i,
+                pre_j,
+                post_i,
+                post_j,
+            });
+        }
+        if let MatchingMode::Persistent { reinforcement, .. } = self.cfg.matching {
+            self.state.partners.reinforce(i, j, reinforcement);
+        }
+        for ev in self.state.events[first..].iter() {
+            for o in self.observers.iter_mut() {
+                o.on_trade(ev);
+            }
+        }
+        Some((legs, total))
+    }
+
+    /// Execute a mutually beneficial candidate between agents i and j (after the step cap).
+    /// Returns the realized total utility change.
+    fn execute(&mut self, t: usize, i: usize, j: usize, mut cand: TradeCandidate) -> Option<f64> {
+        let edit = match self.trade_edit {
+            Some((k, edit)) if k == self.state.events.len() => {
+                self.trade_edit = None;
+                Some(edit)
+            }
+            _ => None,
+        };
+        match edit {
+            Some(TradeEdit::Remove) => {
+                self.refusal = Some(RejectionReason::Removed);
+                return None;
+            }
+            Some(TradeEdit::Scale(f)) => {
+                let f = f.max(0.0);
+                cand.delta_a_i *= f;
+                cand.delta_b_i *= f;
+            }
+            None => {}
+        }
+
+        let time = self.trade_time(t);
+        let cfg = &self.cfg;
+        let (ai, aj) = pair_mut(&mut self.state.agents, i, j);
+
+        // Snapshot utilities and holdings pre-trade for logging
+        let ui0 = ai.utility(cfg.min_qty);
+        let uj0 = aj.utility(cfg.min_qty);
+        let (pre_i, pre_j) = (leaf_hash(ai.id, &ai.e), leaf_hash(aj.id, &aj.e));
+
+        // Apply (conservative step cap): scale deltas to avoid huge jumps.
+        let cap = match cfg.step_cap {
+            StepCap::Fixed => cfg.trade_step_cap_frac.clamp(0.0, 1.0),
+            StepCap::Adaptive { min_frac, max_frac } => cand.adaptive_step_frac(min_frac, max_frac),
+        };
+        // under `conserve_quantities`, also just small enough that no holding needs its floor, with
+        // transaction costs and the trade tax paid
+        let cap = if cfg.conserve_quantities {
+            let tax = cfg.policy.as_ref().map_or((0.0, 0.0), |p| policy::trade_tax(&cand, p.tax_rate, cfg.base_good));
+            let fits = charged_fraction(ai, aj, &cand, cfg.transaction_cost.as_ref(), tax, cfg.base_good, cfg.min_qty);
+            if fits <= 0.0 && conserving_fraction(ai, aj, &cand, cfg.min_qty) > 0.0 {
+                self.cost_refused += 1;
+                self.refusal = Some(RejectionReason::CostRefused);
+                return None;
+            }
+            cap.min(fits)
+        } else {
+            cap
+        };
+        if cap <= 0.0 {
+            self.refusal = Some(RejectionReason::CappedOut);
+            return None;
+        }
+        // Scaling can cost one side its gain, so a capped trade that was a mutual gain is checked
+        // again. Speculative and ZI-U trades accept a loss by design, and noisy agents judged on
+        // misperceived tastes, so their mistakes stand.
+        if cap < 1.0 {
+            let vetted = !cand.speculative && cfg.decision_noise.is_none()
+                && cand.delta_u_i > 0.0 && cand.delta_u_j > 0.0;
+            if !scale_and_validate(ai, aj, &mut cand, cap, cfg.min_qty) && vetted {
+                self.cap_refused += 1;
+                self.refusal = Some(RejectionReason::CappedOut);
+                return None;
+            }
+        }
 
-            if let Some(mut cand) = cand {
-                // Apply (conservative step cap): scale deltas to avoid huge jumps.
-                let cap = cfg.trade_step_cap_frac.clamp(0.0, 1.0);
-                if cap < 1.0 {
-                    cand.delta_a_i *= cap;
-                    cand.delta_b_i *= cap;
+        // Cannot fail: `check_population` guarantees every agent holds all goods.
+        let (a, b, base) = (cand.good_a.index(), cand.good_b.index(), cfg.base_good);
+        // a transaction cost can also move the base good
+        let touched: Vec<(usize, bool)> = [a, b, base].into_iter().enumerate()
+            .filter(|&(k, g)| k < 2 || (g != a && g != b))
+            .flat_map(|(_, g)| [(g, true), (g, false)])
+            .collect();
+        let holding = |ai: &Agent, aj: &Agent, (g, of_i): (usize, bool)| if of_i { ai.e[g] } else { aj.e[g] };
+        let before: Vec<f64> = touched.iter().map(|&k| holding(&*ai, &*aj, k)).collect();
+        let caches = (ai.utility_cache, aj.utility_cache);
+        let (cost_i, cost_j) = match &cfg.transaction_cost {
+            None if cfg.conserve_quantities => {
+                apply_trade_conserving(ai, aj, &cand, cfg.min_qty).ok()?;
+                (0.0, 0.0)
+            }
+            None => {
+                apply_trade(ai, aj, &cand, cfg.min_qty).ok()?;
+                (0.0, 0.0)
+            }
+            Some(cost) if cfg.conserve_quantities => {
+                let (_, cost_i, cost_j) = apply_trade_with_cost_conserving(ai, aj, &cand, cost, base, cfg.min_qty).ok()?;
+                (cost_i, cost_j)
+            }
+            Some(cost) => apply_trade_with_cost(ai, aj, &cand, cost, base, cfg.min_qty).ok()?,
+        };
+        let (tax_i, tax_j) = cfg.policy.as_ref().map_or((0.0, 0.0), |p| policy::trade_tax(&cand, p.tax_rate, base));
+        // the cap above leaves room for the tax when conserving
+        let floor = |x: f64| if cfg.conserve_quantities { x } else { x.max(cfg.min_qty) };
+        ai.set_holding(base, floor(ai.e[base] - tax_i), cfg.min_qty);
+        aj.set_holding(base, floor(aj.e[base] - tax_j), cfg.min_qty);
+        if cfg.transaction_cost.is_some() || tax_i + tax_j > 0.0 {
+            // both sides must still gain once cost and tax are paid
+            let cleared = ai.utility(cfg.min_qty) > ui0 && aj.utility(cfg.min_qty) > uj0;
+            if !cleared {
+                for (&(g, of_i), &x) in touched.iter().zip(before.iter()) {
+                    if of_i { ai.e[g] = x } else { aj.e[g] = x }
                 }
+                (ai.utility_cache, aj.utility_cache) = caches;
+                self.cost_refused += 1;
+                self.refusal = Some(RejectionReason::CostRefused);
+                return None;
+            }
+        }
+        self.state.treasury += tax_i + tax_j;
+        if let Some(tracker) = &mut self.summary {
+            for (&k, &old) in touched.iter().zip(before.iter()) {
+                tracker.update(k.0, old, holding(&*ai, &*aj, k));
+            }
+        }
 
-                apply_trade(ai, aj, &cand, cfg.min_qty);
+        if let MatchingMode::Persistent { reinforcement, .. } = cfg.matching {
+            self.state.partners.reinforce(i, j, reinforcement);
+        }
 
-                // Utilities post trade
-                let ui1 = cd_utility(&ai.beta, &ai.e, cfg.min_qty);
-                let uj1 = cd_utility(&aj.beta, &aj.e, cfg.min_qty);
+        // Utilities post trade
+        let ui1 = ai.utility(cfg.min_qty);
+        let uj1 = aj.utility(cfg.min_qty);
+        let (id_i, id_j) = (ai.id, aj.id);
 
-                state.events.push(TradeEvent {
-                    round: t,
-                    i,
-                    j,
-                    good_a: cand.good_a,
-                    good_b: cand.good_b,
-                    q_ab: cand.q_ab,
-                    delta_a_i: cand.delta_a_i,
-                    delta_b_i: cand.delta_b_i,
-                    delta_u_i: ui1 - ui0,
-                    delta_u_j: uj1 - uj0,
-                });
+        self.state.events.push(TradeEvent {
+            round: t,
+            i: AgentIdx(i),
+            j: AgentIdx(j),
+            good_a: cand.good_a,
+            good_b: cand.good_b,
+            q_ab: cand.q_ab,
+            delta_a_i: cand.delta_a_i,
+            delta_b_i: cand.delta_b_i,
+            delta_u_i: ui1 - ui0,
+            delta_u_j: uj1 - uj0,
+            id_i,
+            id_j,
+            cost_i,
+            cost_j,
+            tax: tax_i + tax_j,
+            speculative: cand.speculative,
+            time,
+            seq: self.state.events.len() as u64,
+            pre_i,
+            pre_j,
+            post_i: leaf_hash(id_i, &ai.e),
+            post_j: leaf_hash(id_j, &aj.e),
+        });
+        if let Some(ev) = self.state.events.last() {
+            for o in self.observers.iter_mut() {
+                o.on_trade(ev);
+            }
+        }
+        Some((ui1 - ui0) + (uj1 - uj0))
+    }
+}
+
+/// Generate the run's social graph when `cfg.matching` asks for one.
+fn build_network(cfg: &SimConfig, n: usize) -> Result<Option<Arc<Graph>>, RdxError> {
+    match &cfg.matching {
+        MatchingMode::Network(spec) => {
+            let mut rng = StdRng::seed_from_u64(cfg.seed ^ 0x0E76_A4F1_D3C0);
+            Ok(Some(Arc::new(network::generate(spec, n, &mut rng)?)))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// What the search proposes for a dyad.
+enum Proposal {
+    Pair(TradeCandidate),
+    Bundle(BundleTrade),
+}
+
+/// Mutable references to two distinct agents.
+fn pair_mut<T>(items: &mut [T], i: usize, j: usize) -> (&mut T, &mut T) {
+    let (left, right) = items.split_at_mut(j.max(i));
+    if i < j {
+        (&mut left[i], &mut right[0])
+    } else {
+        (&mut right[0], &mut left[j])
+    }
+}
+
+/// Best proposal for the dyad under the configured pairing mode. Pure, so disjoint dyads can
+/// be searched concurrently.
+/// `goods` are the candidate goods drawn for `PairingMode::Learning`.
+fn find_trade(
+    cfg: &SimConfig,
+    oracle: &dyn ParetoOracle,
+    rules: &TradeRules,
+    ai: &Agent,
+    aj: &Agent,
+    goods: Option<&[usize]>,
+) -> Option<Proposal> {
+    if let Some(quotes) = &rules.quotes {
+        let (base, min_qty) = (cfg.base_good, cfg.min_qty);
+        match (ai.broker, aj.broker) {
+            (false, false) => {}
+            (true, false) => return broker_trade(ai, aj, quotes, base, min_qty, rules).map(Proposal::Pair),
+            (false, true) => {
+                return broker_trade(aj, ai, quotes, base, min_qty, rules).map(|c| Proposal::Pair(c.reversed()));
             }
+            (true, true) => return None,
         }
     }
+    if let PairingMode::Bundle = cfg.pairing_mode {
+        return evaluate_bundle_trade(ai, aj, cfg.base_good, cfg.candidate_goods_k, cfg.min_qty, rules)
+            .map(Proposal::Bundle);
+    }
+    find_pair_trade(cfg, oracle, rules, ai, aj, goods).map(Proposal::Pair)
+}
+
+fn find_pair_trade(
+    cfg: &SimConfig,
+    oracle: &dyn ParetoOracle,
+    rules: &TradeRules,
+    ai: &Agent,
+    aj: &Agent,
+    goods: Option<&[usize]>,
+) -> Option<TradeCandidate> {
+    if let Some(neg) = &cfg.negotiation {
+        return best_negotiated_trade(ai, aj, cfg.base_good, neg.disclosure, cfg.min_qty, rules);
+    }
+    if rules.speculation.is_some() {
+        return best_speculative_trade(ai, aj, cfg.min_qty, cfg.oracle_bisect_iters, oracle, rules);
+    }
+    match cfg.pairing_mode {
+        PairingMode::AgainstBase => best_trade_against_base_with(
+            ai, aj, cfg.base_good, cfg.min_qty, cfg.oracle_bisect_iters, oracle, rules
+        ),
+        PairingMode::AllPairsPruned | PairingMode::Bundle => best_trade_over_all_pairs_pruned_with(
+            ai, aj, cfg.base_good, cfg.candidate_goods_k, cfg.min_qty, cfg.oracle_bisect_iters, oracle, rules
+        ),
+        PairingMode::Learning { .. } => best_trade_over_goods_with(
+            ai, aj, goods.unwrap_or_default(), cfg.base_good, cfg.min_qty, cfg.oracle_bisect_iters, oracle, rules
+        ),
+    }
+}
+
+/// Run diffusion rounds with P2P encounters. Reaction rules can be plugged in before calling `run`.
+///
+/// With `stop_when_converged` set, the loop exits early and records the last executed round in
+/// `state.stopped_at`. Equivalent to driving an `Engine` to completion.
+///
+/// On error `state` is left untouched.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "info", skip_all, fields(agents = state.agents.len(), rounds = cfg.rounds))
+)]
+pub fn run(cfg: &SimConfig, state: &mut SimState) -> Result<(), RdxError> {
+    check_config(cfg)?;
+    check_population(cfg, &state.agents)?;
+    let mut engine = Engine::with_state(cfg.clone(), std::mem::take(state))?;
+    engine.run_to_end();
+    *state = engine.finish();
+    Ok(())
+}
+
+/// Largest relative difference, `|replayed - simulated| / max(1, |simulated|)`, at which a
+/// replayed holding still counts as matching (see `replay`).
+pub const REPLAY_TOLERANCE: f64 = 1e-9;
+
+/// Holdings rebuilt from a trade event log, checked against a fresh run.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Replay {
+    /// `holdings[t][i][g]`: agent `i`'s holding of good `g` at the end of round `t`.
+    pub holdings: Vec<Vec<Vec<f64>>>,
+    /// Per round, the largest relative difference to the fresh run's holdings.
+    pub max_error: Vec<f64>,
+    /// First round whose error exceeds `REPLAY_TOLERANCE`.
+    pub first_mismatch: Option<usize>,
+}
+
+impl Replay {
+    /// True when every round matches the fresh run.
+    pub fn verified(&self) -> bool { self.first_mismatch.is_none() }
+}
+
+/// Config features that move holdings outside trade events, so that a run using them cannot be
+/// rebuilt from its event log alone.
+pub fn replay_blockers(cfg: &SimConfig) -> Vec<&'static str> {
+    let mut blockers = Vec::new();
+    let mut block = |on: bool, name| if on { blockers.push(name) };
+    block(cfg.consumption.is_some(), "consumption");
+    block(cfg.good_decay.iter().any(|&d| d > 0.0), "good_decay");
+    block(!cfg.shocks.is_empty(), "shocks");
+    block(cfg.demography.is_some(), "demography");
+    block(!cfg.new_goods.is_empty(), "new_goods");
+    block(cfg.monetary.is_some(), "monetary");
+    block(cfg.policy.is_some(), "policy");
+    block(cfg.triads.is_some(), "triads");
+    block(cfg.transaction_cost.as_ref().is_some_and(|c| c.settlement == CostSettlement::Burned), "transaction_cost");
+    blockers
+}
+
+/// Rebuild the end-of-round holdings of every agent from the initial population of `cfg` (drawn
+/// from its seed) and `events` alone: each trade moves its two legs between `i` and `j` (floored
+/// at `min_qty`, as the engine does, and for a package trade once all its legs are in), then
+/// base-good transaction costs and the trade tax, paid by the side receiving the base good. One
+/// entry per round up to `cfg.rounds` or the last event's round, whichever is later.
+///
+/// Fails on configs with `replay_blockers` and on events out of round order or out of range.
+pub fn reconstruct(cfg: &SimConfig, events: &[TradeEvent]) -> Result<Vec<Vec<Vec<f64>>>, RdxError> {
+    let blockers = replay_blockers(cfg);
+    if !blockers.is_empty() {
+        return Err(RdxError::Replay(format!("holdings also change outside trades ({})", blockers.join(", "))));
+    }
+    let mut holdings: Vec<Vec<f64>> = init_agents(cfg)?.agents.into_iter().map(|a| a.e).collect();
+    let (base, min_qty, n) = (cfg.base_good, cfg.min_qty, cfg.base_goods.len());
+    let rounds = events.last().map_or(0, |e| e.round + 1).max(cfg.rounds);
+    let bundled = matches!(cfg.pairing_mode, PairingMode::Bundle);
+    // a package trade is applied whole and floored once (see `execute_bundle`): its legs are
+    // consecutive events of one dyad, each of a different good against the base good
+    let settle = |holdings: &mut [Vec<f64>], (i, j, goods): (usize, usize, Vec<usize>)| {
+        for g in goods {
+            holdings[i][g] = holdings[i][g].max(min_qty);
+            holdings[j][g] = holdings[j][g].max(min_qty);
+        }
+    };
+    let mut trajectory = Vec::with_capacity(rounds);
+    let mut next = events.iter().peekable();
+    for t in 0..rounds {
+        let mut package: Option<(usize, usize, Vec<usize>)> = None;
+        while let Some(ev) = next.next_if(|e| e.round == t) {
+            let (i, j, a, b) = (ev.i.index(), ev.j.index(), ev.good_a.index(), ev.good_b.index());
+            if i >= holdings.len() || j >= holdings.len() || i == j {
+                return Err(RdxError::Replay(format!("event in round {t} between agents {i} and {j}")));
+            }
+            if a >= n || b >= n {
+                return Err(RdxError::GoodOutOfRange { index: a.max(b), len: n });
+            }
+            let leg = bundled && b == base;
+            let continues = leg && package.as_ref().is_some_and(|(pi, pj, goods)| {
+                (*pi, *pj) == (i, j) && !goods.contains(&a)
+            });
+            if !continues {
+                if let Some(done) = package.take() { settle(&mut holdings, done); }
+            }
+            let (hi, hj) = pair_mut(&mut holdings, i, j);
+            hi[a] += ev.delta_a_i;
+            hi[b] += ev.delta_b_i;
+            hj[a] -= ev.delta_a_i;
+            hj[b] -= ev.delta_b_i;
+            if leg {
+                package.get_or_insert_with(|| (i, j, vec![base])).2.push(a);
+                continue;
+            }
+            settle(&mut holdings, (i, j, vec![a, b]));
+            let (hi, hj) = pair_mut(&mut holdings, i, j);
+            hi[base] = (hi[base] - ev.cost_i).max(min_qty);
+            hj[base] = (hj[base] - ev.cost_j).max(min_qty);
+            if ev.tax > 0.0 {
+                let base_to_i = if b == base { ev.delta_b_i } else { ev.delta_a_i };
+                let payer = if base_to_i > 0.0 { &mut *hi } else { &mut *hj };
+                payer[base] = (payer[base] - ev.tax).max(min_qty);
+            }
+        }
+        if let Some(done) = package.take() { settle(&mut holdings, done); }
+        trajectory.push(holdings.clone());
+    }
+    if let Some(ev) = next.next() {
+        return Err(RdxError::Replay(format!("event of round {} out of round order", ev.round)));
+    }
+    Ok(trajectory)
+}
+
+/// `reconstruct` the holdings trajectory from `events`, then run `cfg` afresh with the same seed
+/// and compare round by round: an audit that a recorded log explains the run's outcome, and a
+/// way to recover any round's holdings without stored snapshots.
+pub fn replay(cfg: &SimConfig, events: &[TradeEvent]) -> Result<Replay, RdxError> {
+    let holdings = reconstruct(cfg, events)?;
+    let mut engine = Engine::new(cfg.clone())?;
+    let mut max_error = Vec::with_capacity(holdings.len());
+    for replayed in holdings.iter() {
+        // past an early stop the fresh state stays put, and so must the replayed one
+        engine.step_round();
+        let error = replayed.iter().zip(engine.state().agents.iter())
+            .flat_map(|(r, a)| r.iter().zip(a.e.iter()))
+            .map(|(x, y)| (x - y).abs() / y.abs().max(1.0))
+            .fold(0.0, |m: f64, d| if d.is_nan() { f64::INFINITY } else { m.max(d) });
+        max_error.push(error);
+    }
+    let first_mismatch = max_error.iter().position(|&e| e > REPLAY_TOLERANCE);
+    Ok(Replay { holdings, max_error, first_mismatch })
 }
 
+/// Mean holdings per good; empty for an empty population.
 pub fn mean_endowments(state: &SimState) -> Vec<f64> {
-    let n = state.agents[0].e.len();
+    let Some(first) = state.agents.first() else { return Vec::new() };
+    let n = first.e.len();
     let mut mean = vec![0.0; n];
     for ag in state.agents.iter() {
-        for k in 0..n {
-            mean[k] += ag.e[k];
+        for (k, m) in mean.iter_mut().enumerate() {
+            *m += ag.e.get(k).copied().unwrap_or(0.0);
         }
     }
-    for k in 0..n {
-        mean[k] /= state.agents.len() as f64;
+    for m in mean.iter_mut() {
+        *m /= state.agents.len() as f64;
     }
     mean
 }
@@ -0,0 +1,146 @@
+//! Trade rejection diagnostics: after `Engine::record_rejections`, every encounter that ends
+//! without a trade (or a three-way cycle) is logged with the reason, to tell economic reasons
+//! for low trade rates (agents already agree on prices) from numerical ones (a degenerate
+//! oracle, trades capped out).
+//!
+//! Refusals at execution (step cap, transaction cost, trade budget, reneging, edits) are noted
+//! where they happen. An encounter without any proposal is classified afterwards against the
+//! true holdings: identical MRS on every tradable good first, then degenerate oracle solutions
+//! on some good, else no candidate. Classification neither draws random numbers nor touches the
+//! metrics' oracle counts, so recording does not change the run.
+
+use core::fmt;
+use core::sync::atomic::{AtomicBool, Ordering};
+use serde::{Deserialize, Serialize};
+use crate::ids::AgentIdx;
+use crate::math::ln;
+use crate::pareto_oracle::{DyadExchange, OracleStatus, ParetoOracle};
+use crate::trade::{evaluate_pairwise_trade, mrs_to_base};
+use super::Engine;
+
+/// Two MRS closer than this in log terms count as identical.
+const MRS_TOLERANCE: f64 = 1e-9;
+
+/// Why an encounter ended without a trade.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RejectionReason {
+    /// The search found no exchange that improves both utilities.
+    NoCandidate,
+    /// Both agents' MRS against the base agree on every tradable good: no gains to trade.
+    IdenticalMrs,
+    /// The oracle's solution was degenerate or unbracketed on some good (`OracleStatus`), and no
+    /// other good gave a candidate.
+    DegenerateOracle,
+    /// A candidate no longer improved both sides once scaled by the step cap (or the shrink of
+    /// `SimConfig::conserve_quantities`).
+    CappedOut,
+    /// The gains did not cover the transaction cost and tax.
+    CostRefused,
+    /// A side had used up its trades for the round (`SimConfig::max_trades_per_agent`).
+    OverBudget,
+    /// A side reneged on the agreed trade (`SimConfig::reputation`).
+    Reneged,
+    /// The trade was removed by an edit (`Engine::edit_trade`).
+    Removed,
+}
+
+impl fmt::Display for RejectionReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            RejectionReason::NoCandidate => "no_candidate",
+            RejectionReason::IdenticalMrs => "identical_mrs",
+            RejectionReason::DegenerateOracle => "degenerate_oracle",
+            RejectionReason::CappedOut => "capped_out",
+            RejectionReason::CostRefused => "cost_refused",
+            RejectionReason::OverBudget => "over_budget",
+            RejectionReason::Reneged => "reneged",
+            RejectionReason::Removed => "removed",
+        })
+    }
+}
+
+/// One encounter that ended without a trade.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Rejection {
+    pub round: usize,
+    pub i: AgentIdx,
+    pub j: AgentIdx,
+    pub reason: RejectionReason,
+}
+
+/// Forwards to the engine's oracle and remembers whether any solution was degenerate or
+/// unbracketed.
+struct Probe<'a> {
+    inner: &'a dyn ParetoOracle,
+    failed: AtomicBool,
+}
+
+impl ParetoOracle for Probe<'_> {
+    fn solve_two_good_exchange(
+        &self,
+        alpha_i: f64, ai: f64, bi: f64,
+        alpha_j: f64, aj: f64, bj: f64,
+        min_qty: f64,
+        iters: usize,
+    ) -> DyadExchange {
+        let ex = self.inner.solve_two_good_exchange(alpha_i, ai, bi, alpha_j, aj, bj, min_qty, iters);
+        if matches!(ex.status, OracleStatus::Degenerate | OracleStatus::Unbracketed) {
+            self.failed.store(true, Ordering::Relaxed);
+        }
+        ex
+    }
+}
+
+impl Engine {
+    /// Start logging encounters that end without a trade (see `rejection_log`).
+    pub fn record_rejections(&mut self) {
+        self.rejection_log.get_or_insert_with(Vec::new);
+    }
+
+    /// Rejections recorded since `record_rejections` (empty if recording is off).
+    pub fn rejection_log(&self) -> &[Rejection] {
+        self.rejection_log.as_deref().unwrap_or(&[])
+    }
+
+    /// Log the failed encounter (i, j), with the refusal noted while executing its proposal or,
+    /// without one, the classified reason.
+    pub(super) fn note_rejection(&mut self, t: usize, i: usize, j: usize) {
+        let refusal = self.refusal.take();
+        if self.rejection_log.is_none() {
+            return;
+        }
+        let reason = refusal.unwrap_or_else(|| self.classify_rejection(i, j));
+        if let Some(log) = &mut self.rejection_log {
+            log.push(Rejection { round: t, i: AgentIdx(i), j: AgentIdx(j), reason });
+        }
+    }
+
+    fn classify_rejection(&self, i: usize, j: usize) -> RejectionReason {
+        let (ai, aj) = (&self.state.agents[i], &self.state.agents[j]);
+        let (base, min_qty) = (self.cfg.base_good, self.cfg.min_qty);
+        let goods: Vec<usize> = (0..ai.e.len().min(aj.e.len()))
+            .filter(|&g| g != base && !self.rules.blocked.get(g).copied().unwrap_or(false))
+            .collect();
+        if goods.is_empty() {
+            return RejectionReason::NoCandidate;
+        }
+        let same = |g: usize| {
+            let mi = mrs_to_base(&ai.beta, &ai.e, g, base, min_qty);
+            let mj = mrs_to_base(&aj.beta, &aj.e, g, base, min_qty);
+            mi > 0.0 && mj > 0.0 && (ln(mi) - ln(mj)).abs() < MRS_TOLERANCE
+        };
+        if goods.iter().all(|&g| same(g)) {
+            return RejectionReason::IdenticalMrs;
+        }
+        let probe = Probe { inner: self.oracle.inner(), failed: AtomicBool::new(false) };
+        for &g in goods.iter() {
+            evaluate_pairwise_trade(ai, aj, g, base, base, min_qty, self.cfg.oracle_bisect_iters, &probe);
+        }
+        if probe.failed.into_inner() {
+            RejectionReason::DegenerateOracle
+        } else {
+            RejectionReason::NoCandidate
+        }
+    }
+}
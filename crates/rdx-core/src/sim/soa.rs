@@ -0,0 +1,151 @@
+//! Structure-of-arrays agent storage for large populations: holdings, exponents and alphas as
+//! flat agent-major `f64` matrices, so population-wide kernels stream through contiguous memory
+//! (and vectorize) instead of chasing one heap allocation per agent and field.
+//!
+//! `AgentSoA` mirrors the trade-relevant fields of a `[Agent]` (`e`, `beta`, `alpha_to_base`
+//! and `id`); the engine keeps `SimState::agents` as the record of a run, and `write_back`
+//! returns edited holdings and preferences to it. The kernels give the same values as their
+//! per-agent counterparts (`cd_utility`, `mrs_to_base`, `metrics::wealth`), bit for bit.
+
+use crate::error::RdxError;
+use crate::math::exp;
+use crate::model::Agent;
+use crate::preferences::log_cd_utility;
+use crate::trade::mrs_to_base;
+use super::SimState;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AgentSoA {
+    goods: usize,
+    ids: Vec<u64>,
+    /// Row `k` (`goods` entries from `k · goods`) is agent `k`.
+    e: Vec<f64>,
+    beta: Vec<f64>,
+    alpha: Vec<f64>,
+}
+
+impl AgentSoA {
+    /// Copy `agents` into flat storage. Fails with `DimensionMismatch` unless every agent's
+    /// `e`, `beta` and `alpha_to_base` have the first agent's number of goods.
+    pub fn from_agents(agents: &[Agent]) -> Result<Self, RdxError> {
+        let goods = agents.first().map_or(0, |a| a.e.len());
+        let mut soa = AgentSoA {
+            goods,
+            ids: Vec::with_capacity(agents.len()),
+            e: Vec::with_capacity(agents.len() * goods),
+            beta: Vec::with_capacity(agents.len() * goods),
+            alpha: Vec::with_capacity(agents.len() * goods),
+        };
+        for a in agents.iter() {
+            for row in [&a.e, &a.beta, &a.alpha_to_base] {
+                if row.len() != goods {
+                    return Err(RdxError::DimensionMismatch { expected: goods, found: row.len() });
+                }
+            }
+            soa.ids.push(a.id);
+            soa.e.extend_from_slice(&a.e);
+            soa.beta.extend_from_slice(&a.beta);
+            soa.alpha.extend_from_slice(&a.alpha_to_base);
+        }
+        Ok(soa)
+    }
+
+    /// Copy holdings and preferences back into `agents`, which must be the population this was
+    /// built from (same length and goods); their utility caches are dropped. Fails without
+    /// modifying any agent on a shape mismatch.
+    pub fn write_back(&self, agents: &mut [Agent]) -> Result<(), RdxError> {
+        if agents.len() != self.len() {
+            return Err(RdxError::DimensionMismatch { expected: self.len(), found: agents.len() });
+        }
+        for a in agents.iter() {
+            for row in [&a.e, &a.beta, &a.alpha_to_base] {
+                if row.len() != self.goods {
+                    return Err(RdxError::DimensionMismatch { expected: self.goods, found: row.len() });
+                }
+            }
+        }
+        for (k, a) in agents.iter_mut().enumerate() {
+            a.e.copy_from_slice(self.e(k));
+            a.beta.copy_from_slice(self.beta(k));
+            a.alpha_to_base.copy_from_slice(self.alpha(k));
+            a.invalidate_utility();
+        }
+        Ok(())
+    }
+
+    pub fn len(&self) -> usize { self.ids.len() }
+
+    pub fn is_empty(&self) -> bool { self.ids.is_empty() }
+
+    /// Number of goods per agent.
+    pub fn goods(&self) -> usize { self.goods }
+
+    /// `Agent::id` of every agent, in population order.
+    pub fn ids(&self) -> &[u64] { &self.ids }
+
+    fn range(&self, k: usize) -> std::ops::Range<usize> {
+        k * self.goods..(k + 1) * self.goods
+    }
+
+    /// Holdings of agent `k`. Panics if `k` is out of range.
+    pub fn e(&self, k: usize) -> &[f64] { &self.e[self.range(k)] }
+
+    pub fn e_mut(&mut self, k: usize) -> &mut [f64] {
+        let r = self.range(k);
+        &mut self.e[r]
+    }
+
+    pub fn beta(&self, k: usize) -> &[f64] { &self.beta[self.range(k)] }
+
+    pub fn alpha(&self, k: usize) -> &[f64] { &self.alpha[self.range(k)] }
+
+    fn rows<'a>(&self, m: &'a [f64]) -> impl Iterator<Item = &'a [f64]> + 'a {
+        let g = self.goods;
+        (0..self.len()).map(move |k| &m[k * g..(k + 1) * g])
+    }
+
+    /// `ln cd_utility` of every agent.
+    pub fn log_utilities(&self, min_qty: f64) -> Vec<f64> {
+        self.rows(&self.beta).zip(self.rows(&self.e)).map(|(b, x)| log_cd_utility(b, x, min_qty)).collect()
+    }
+
+    /// `cd_utility` of every agent.
+    pub fn utilities(&self, min_qty: f64) -> Vec<f64> {
+        self.log_utilities(min_qty).into_iter().map(exp).collect()
+    }
+
+    /// `mrs_to_base(beta, e, g, base, min_qty)` of every agent.
+    pub fn mrs_to_base(&self, g: usize, base: usize, min_qty: f64) -> Vec<f64> {
+        self.rows(&self.beta).zip(self.rows(&self.e)).map(|(b, x)| mrs_to_base(b, x, g, base, min_qty)).collect()
+    }
+
+    /// `metrics::wealth` of every agent at `prices`.
+    pub fn wealth(&self, prices: &[f64]) -> Vec<f64> {
+        let p: Vec<f64> = (0..self.goods).map(|k| prices.get(k).copied().unwrap_or(1.0)).collect();
+        self.rows(&self.e).map(|x| p.iter().zip(x).map(|(p, x)| p * x).sum()).collect()
+    }
+
+    /// Mean holdings per good, as `mean_endowments`; empty for an empty population.
+    pub fn mean_endowments(&self) -> Vec<f64> {
+        if self.is_empty() {
+            return Vec::new();
+        }
+        let mut mean = vec![0.0; self.goods];
+        for x in self.rows(&self.e) {
+            for (m, &v) in mean.iter_mut().zip(x) {
+                *m += v;
+            }
+        }
+        for m in mean.iter_mut() {
+            *m /= self.len() as f64;
+        }
+        mean
+    }
+}
+
+impl SimState {
+    /// The population as an `AgentSoA` (see `AgentSoA::from_agents`).
+    pub fn agents_soa(&self) -> Result<AgentSoA, RdxError> {
+        AgentSoA::from_agents(&self.agents)
+    }
+}
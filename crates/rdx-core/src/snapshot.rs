@@ -0,0 +1,200 @@
+//! Compact agent snapshots.
+//!
+//! `AgentSnapshot` is the serde-friendly form used for periodic snapshots, gossip summaries and
+//! checkpoint deltas: endowments are quantized to integer multiples of a step and the beta
+//! vector can be replaced by a 64-bit hash, keeping payloads small for large populations.
+//!
+//! `PopulationSummary` is the aggregate counterpart, gossiped so nodes can estimate market
+//! conditions without seeing individual agents.
+use serde::{Serialize, Deserialize};
+use crate::error::RdxError;
+use crate::metrics::quantile;
+use crate::model::Agent;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AgentSnapshot {
+    /// Quantization step for endowments.
+    pub quantum: f64,
+    /// Endowments as rounded multiples of `quantum` (saturating at `u32::MAX`).
+    pub e: Vec<u32>,
+    /// `beta_hash(&agent.beta)`; always present so peers can detect preference changes.
+    pub beta_hash: u64,
+    /// Full beta vector, only when requested at capture time.
+    #[serde(default)]
+    pub beta: Option<Vec<f64>>,
+}
+
+/// Changes between two snapshots of the same agent taken with the same quantum.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SnapshotDelta {
+    /// (good index, new quantized value) for every entry that changed.
+    pub changes: Vec<(u32, u32)>,
+    /// New beta hash when preferences changed.
+    #[serde(default)]
+    pub beta_hash: Option<u64>,
+}
+
+/// FNV-1a over the IEEE-754 bit patterns of `beta`; stable across platforms and releases.
+pub fn beta_hash(beta: &[f64]) -> u64 {
+    let mut h: u64 = 0xcbf2_9ce4_8422_2325;
+    for x in beta.iter() {
+        for byte in x.to_bits().to_le_bytes() {
+            h ^= byte as u64;
+            h = h.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+    h
+}
+
+impl AgentSnapshot {
+    /// Capture `agent`, quantizing endowments to multiples of `quantum` (> 0).
+    pub fn new(agent: &Agent, quantum: f64, include_beta: bool) -> Result<Self, RdxError> {
+        if !(quantum.is_finite() && quantum > 0.0) {
+            return Err(RdxError::InvalidConfig(format!("snapshot quantum must be positive, got {quantum}")));
+        }
+        Ok(AgentSnapshot {
+            quantum,
+            e: agent.e.iter().map(|&x| (x / quantum).round() as u32).collect(),
+            beta_hash: beta_hash(&agent.beta),
+            beta: include_beta.then(|| agent.beta.clone()),
+        })
+    }
+
+    /// Dequantized endowments (within `quantum / 2` of the originals, unless saturated).
+    pub fn endowments(&self) -> Vec<f64> {
+        self.e.iter().map(|&q| q as f64 * self.quantum).collect()
+    }
+
+    /// Whether `beta` is the preference vector this snapshot was taken from.
+    pub fn matches_beta(&self, beta: &[f64]) -> bool {
+        beta_hash(beta) == self.beta_hash
+    }
+
+    /// Entries that differ from `prev`. Snapshots must share a quantum and goods count.
+    pub fn delta_from(&self, prev: &AgentSnapshot) -> Result<SnapshotDelta, RdxError> {
+        if self.e.len() != prev.e.len() {
+            return Err(RdxError::DimensionMismatch { expected: prev.e.len(), found: self.e.len() });
+        }
+        if self.quantum != prev.quantum {
+            return Err(RdxError::InvalidConfig("snapshot quanta differ".into()));
+        }
+        let changes = self.e.iter().zip(prev.e.iter()).enumerate()
+            .filter(|(_, (now, before))| now != before)
+            .map(|(g, (&now, _))| (g as u32, now))
+            .collect();
+        let beta_hash = (self.beta_hash != prev.beta_hash).then_some(self.beta_hash);
+        Ok(SnapshotDelta { changes, beta_hash })
+    }
+
+    /// Apply a delta produced by `delta_from`. The full beta, if any, is dropped when the hash
+    /// changes since the delta does not carry it.
+    pub fn apply_delta(&mut self, delta: &SnapshotDelta) -> Result<(), RdxError> {
+        for &(g, v) in delta.changes.iter() {
+            let len = self.e.len();
+            let slot = self.e.get_mut(g as usize)
+                .ok_or(RdxError::GoodOutOfRange { index: g as usize, len })?;
+            *slot = v;
+        }
+        if let Some(h) = delta.beta_hash {
+            self.beta_hash = h;
+            self.beta = None;
+        }
+        Ok(())
+    }
+}
+
+/// Snapshot a whole population with the same quantum.
+pub fn snapshot_population(agents: &[Agent], quantum: f64, include_beta: bool) -> Result<Vec<AgentSnapshot>, RdxError> {
+    agents.iter().map(|a| AgentSnapshot::new(a, quantum, include_beta)).collect()
+}
+
+/// Probability levels of `GoodStats::quantiles`.
+pub const SUMMARY_LEVELS: [f64; 5] = [0.1, 0.25, 0.5, 0.75, 0.9];
+
+/// Mean and `SUMMARY_LEVELS` quantiles of one per-good quantity across the population.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GoodStats {
+    pub mean: f32,
+    pub quantiles: [f32; 5],
+}
+
+/// Population-level market picture for gossip: per-good holdings and beta distributions,
+/// without any per-agent data. Values are single precision; the payload grows with the number
+/// of goods only, not with the population.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PopulationSummary {
+    /// Completed rounds when the summary was taken.
+    pub round: u32,
+    pub count: u32,
+    pub holdings: Vec<GoodStats>,
+    pub beta: Vec<GoodStats>,
+}
+
+impl PopulationSummary {
+    /// Summarize `agents` from scratch.
+    pub fn from_agents(round: usize, agents: &[Agent]) -> Self {
+        SummaryTracker::new(agents).summary(round)
+    }
+}
+
+/// Sorted per-good holdings kept up to date trade by trade, so `summary` costs O(goods)
+/// instead of a sort per good. Betas are assumed fixed and summarized once.
+#[derive(Clone, Debug, Default)]
+pub struct SummaryTracker {
+    sorted_e: Vec<Vec<f64>>,
+    sum_e: Vec<f64>,
+    beta: Vec<GoodStats>,
+}
+
+impl SummaryTracker {
+    pub fn new(agents: &[Agent]) -> Self {
+        let n = agents.iter().map(|a| a.e.len()).max().unwrap_or(0);
+        let column = |g: usize, f: fn(&Agent) -> &[f64]| -> Vec<f64> {
+            let mut v: Vec<f64> = agents.iter().filter_map(|a| f(a).get(g).copied()).collect();
+            v.sort_by(f64::total_cmp);
+            v
+        };
+        let sorted_e: Vec<Vec<f64>> = (0..n).map(|g| column(g, |a| a.e.as_slice())).collect();
+        let sum_e = sorted_e.iter().map(|v| v.iter().sum()).collect();
+        let beta = (0..n).map(|g| stats(&column(g, |a| a.beta.as_slice()))).collect();
+        SummaryTracker { sorted_e, sum_e, beta }
+    }
+
+    /// Record that one agent's holding of `good` changed from `old` to `new`.
+    pub fn update(&mut self, good: usize, old: f64, new: f64) {
+        let (Some(col), Some(sum)) = (self.sorted_e.get_mut(good), self.sum_e.get_mut(good)) else { return };
+        let at = col.partition_point(|x| x.total_cmp(&old).is_lt());
+        if col.get(at).is_some_and(|x| x.total_cmp(&old).is_eq()) {
+            col.remove(at);
+            *sum -= old;
+        }
+        let to = col.partition_point(|x| x.total_cmp(&new).is_lt());
+        col.insert(to, new);
+        *sum += new;
+    }
+
+    pub fn summary(&self, round: usize) -> PopulationSummary {
+        let holdings = self.sorted_e.iter().zip(self.sum_e.iter())
+            .map(|(col, &sum)| GoodStats {
+                mean: if col.is_empty() { 0.0 } else { (sum / col.len() as f64) as f32 },
+                quantiles: quantiles(col),
+            })
+            .collect();
+        PopulationSummary {
+            round: round as u32,
+            count: self.sorted_e.first().map_or(0, Vec::len) as u32,
+            holdings,
+            beta: self.beta.clone(),
+        }
+    }
+}
+
+fn stats(sorted: &[f64]) -> GoodStats {
+    let mean = if sorted.is_empty() { 0.0 } else { sorted.iter().sum::<f64>() / sorted.len() as f64 };
+    GoodStats { mean: mean as f32, quantiles: quantiles(sorted) }
+}
+
+/// `SUMMARY_LEVELS` quantiles of an ascending slice (zeros when empty).
+fn quantiles(sorted: &[f64]) -> [f32; 5] {
+    SUMMARY_LEVELS.map(|p| quantile(sorted, p) as f32)
+}
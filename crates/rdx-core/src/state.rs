@@ -0,0 +1,145 @@
+//! Commitments to the global allocation.
+//!
+//! `merkle_root` hashes every agent's endowments into one SHA-256 root, so peers that applied
+//! the same event log can check they agree on the allocation by comparing 32 bytes, and
+//! `MerkleTree::proof` shows one agent's holdings are part of a committed state without
+//! disclosing the others.
+//!
+//! Leaves are ordered by agent id, not by position in `SimState::agents`. A leaf hashes
+//! `0x00 || id (u64 LE) || goods (u32 LE) || each endowment's IEEE-754 bits (u64 LE)`, with
+//! `-0.0` written as `0.0`; an inner node hashes `0x01 || left || right`, and the last node of
+//! an odd level moves up unpaired. The root of an empty population is the hash of no input.
+
+use serde::{Serialize, Deserialize};
+use sha2::{Digest, Sha256};
+use crate::sim::SimState;
+
+/// A SHA-256 digest.
+pub type Hash = [u8; 32];
+
+const LEAF_PREFIX: u8 = 0x00;
+const NODE_PREFIX: u8 = 0x01;
+
+/// Hash of one agent's leaf: its id and endowment vector in the canonical encoding.
+pub fn leaf_hash(id: u64, e: &[f64]) -> Hash {
+    let mut h = Sha256::new();
+    h.update([LEAF_PREFIX]);
+    h.update(id.to_le_bytes());
+    h.update((e.len() as u32).to_le_bytes());
+    for &x in e {
+        // one encoding for both zeros, so `a - a` and `-0.0` commit alike
+        let x = if x == 0.0 { 0.0f64 } else { x };
+        h.update(x.to_bits().to_le_bytes());
+    }
+    h.finalize().into()
+}
+
+fn node_hash(left: &Hash, right: &Hash) -> Hash {
+    let mut h = Sha256::new();
+    h.update([NODE_PREFIX]);
+    h.update(left);
+    h.update(right);
+    h.finalize().into()
+}
+
+/// One level of an inclusion proof: the sibling's hash and the side it sits on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProofStep {
+    pub sibling: Hash,
+    pub sibling_on_left: bool,
+}
+
+/// Evidence that an agent's endowments are a leaf under a root: the sibling hashes from the leaf
+/// up, skipping levels where its node moved up unpaired.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InclusionProof {
+    pub id: u64,
+    pub steps: Vec<ProofStep>,
+}
+
+impl InclusionProof {
+    /// Whether agent `self.id` holding `e` is a leaf of the tree with `root`.
+    pub fn verify(&self, root: &Hash, e: &[f64]) -> bool {
+        let mut h = leaf_hash(self.id, e);
+        for step in &self.steps {
+            h = if step.sibling_on_left { node_hash(&step.sibling, &h) } else { node_hash(&h, &step.sibling) };
+        }
+        h == *root
+    }
+}
+
+/// Every level of the tree over a population, leaves first, kept for issuing proofs.
+#[derive(Clone, Debug)]
+pub struct MerkleTree {
+    ids: Vec<u64>,
+    levels: Vec<Vec<Hash>>,
+}
+
+impl MerkleTree {
+    pub fn from_state(state: &SimState) -> MerkleTree {
+        let mut leaves: Vec<(u64, Hash)> = state.agents.iter().map(|a| (a.id, leaf_hash(a.id, &a.e))).collect();
+        // duplicate ids (hand-built states) still get one canonical order
+        leaves.sort();
+        let (ids, leaves): (Vec<u64>, Vec<Hash>) = leaves.into_iter().unzip();
+        let mut levels = vec![leaves];
+        while levels.last().is_some_and(|l| l.len() > 1) {
+            let next = levels
+                .last()
+                .expect("at least the leaf level")
+                .chunks(2)
+                .map(|pair| match pair {
+                    [left, right] => node_hash(left, right),
+                    [single] => *single,
+                    _ => unreachable!("chunks of two"),
+                })
+                .collect();
+            levels.push(next);
+        }
+        MerkleTree { ids, levels }
+    }
+
+    pub fn root(&self) -> Hash {
+        match self.levels.last().and_then(|l| l.first()) {
+            Some(root) => *root,
+            None => Sha256::digest(b"").into(),
+        }
+    }
+
+    /// Number of leaves (agents).
+    pub fn len(&self) -> usize { self.ids.len() }
+
+    pub fn is_empty(&self) -> bool { self.ids.is_empty() }
+
+    /// Inclusion proof for the agent with `id`, or `None` if there is none (the first, for
+    /// duplicate ids).
+    pub fn proof(&self, id: u64) -> Option<InclusionProof> {
+        let mut k = self.ids.binary_search(&id).ok()?;
+        while k > 0 && self.ids[k - 1] == id {
+            k -= 1;
+        }
+        let mut steps = Vec::new();
+        for level in &self.levels[..self.levels.len() - 1] {
+            let sibling = k ^ 1;
+            if sibling < level.len() {
+                steps.push(ProofStep { sibling: level[sibling], sibling_on_left: sibling < k });
+            }
+            k /= 2;
+        }
+        Some(InclusionProof { id, steps })
+    }
+}
+
+/// Root of the Merkle tree over all agents' endowments (see the module docs for the encoding).
+pub fn merkle_root(state: &SimState) -> Hash {
+    MerkleTree::from_state(state).root()
+}
+
+/// `MerkleTree::proof` for one agent, building the tree once for it.
+pub fn inclusion_proof(state: &SimState, id: u64) -> Option<InclusionProof> {
+    MerkleTree::from_state(state).proof(id)
+}
+
+/// Lowercase hex of a hash, for logs and comparing roots by eye.
+pub fn to_hex(hash: &Hash) -> String {
+    hash.iter().map(|b| format!("{b:02x}")).collect()
+}
@@ -1,26 +1,155 @@
-use crate::model::{Agent};
-use crate::preferences::{cd_utility, alpha_from_beta};
-use crate::pareto_oracle::{ParetoOracle, CobbDouglasWalrasOracle};
-
-#[derive(Clone, Debug)]
-pub struct TradeCandidate {
-    pub good_a: usize,
-    pub good_b: usize,
-    pub q_ab: f64,
-    pub delta_a_i: f64,
-    pub delta_b_i: f64,
-    pub delta_u_i: f64,
-    pub delta_u_j: f64,
+use std::collections::HashMap;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use crate::broker::Quotes;
+use crate::ids::GoodId;
+use crate::model::{Agent, CostSettlement, Embargo, SimConfig, TransactionCost};
+use crate::error::RdxError;
+use crate::math::{exp, ln};
+use crate::preferences::{cd_utility, demand_at_price};
+use crate::pareto_oracle::{ParetoOracle, CobbDouglasWalrasOracle, TolerantWalrasOracle};
+use crate::prices::{walras_allocation, walras_prices};
+
+mod dyad;
+
+pub use dyad::{
+    base_price, directional_shape, evaluate_exchange, mrs_to_base, mrs_to_base_s, DirectionalShape, TradeCandidate,
+};
+use dyad::{exchange_candidate, log_utility_change};
+
+/// Restrictions on what a dyad may trade in the current round; the default allows everything.
+#[derive(Clone, Debug, Default)]
+pub struct TradeRules {
+    /// `blocked[g]`: good `g` cannot change hands. Goods past the end are allowed.
+    pub blocked: Vec<bool>,
+    /// Resale prospects when goods may be accepted as media of exchange
+    /// (`SimConfig::money_emergence`); `None` for ordinary trading.
+    pub speculation: Option<Marketability>,
+    /// Broker quotes (`SimConfig::brokers`); `None` without brokers.
+    pub quotes: Option<Quotes>,
+}
+
+/// How readily each good is accepted in trade, refreshed by the engine every round.
+#[derive(Clone, Debug, Default)]
+pub struct Marketability {
+    /// Per good, smoothed share of trades in which the good changed hands.
+    pub share: Vec<f64>,
+    /// Per good, mean holding across the population; scales quantities into comparable units.
+    pub mean_holding: Vec<f64>,
+    /// Weight of resale value against the relative utility change.
+    pub weight: f64,
+    /// Fraction of the giver's holding offered in a speculative swap.
+    pub step: f64,
+}
+
+impl Marketability {
+    /// Resale value of receiving `dx` of good `g`: `weight · share[g] · dx / mean_holding[g]`.
+    pub fn resale_value(&self, g: usize, dx: f64) -> f64 {
+        let share = self.share.get(g).copied().unwrap_or(0.0);
+        let scale = self.mean_holding.get(g).copied().unwrap_or(1.0).max(1e-12);
+        self.weight * share * dx / scale
+    }
+}
+
+impl TradeRules {
+    /// Block every good under an embargo whose window contains `round`.
+    pub fn from_embargoes(embargoes: &[Embargo], n_goods: usize, round: usize) -> Self {
+        let mut blocked = vec![false; n_goods];
+        for em in embargoes.iter().filter(|em| em.is_active(round)) {
+            for &g in em.goods.iter() {
+                if let Some(b) = blocked.get_mut(g.index()) {
+                    *b = true;
+                }
+            }
+        }
+        TradeRules { blocked, speculation: None, quotes: None }
+    }
+
+    pub fn allows(&self, good: usize) -> bool {
+        !self.blocked.get(good).copied().unwrap_or(false)
+    }
+
+    /// Indices of blocked goods, ascending.
+    pub fn blocked_goods(&self) -> Vec<usize> {
+        self.blocked.iter().enumerate().filter(|(_, &b)| b).map(|(g, _)| g).collect()
+    }
 }
 
-/// Compute a Cobb–Douglas marginal rate of substitution (price ratio) for good k vs base:
-/// MRS_{k,base} = (beta_k/beta_base) * (x_base/x_k).
-fn mrs_to_base(beta: &[f64], x: &[f64], k: usize, base: usize, min_qty: f64) -> f64 {
-    let bk = beta[k].max(0.0);
-    let bb = beta[base].max(1e-18);
-    let xb = x[base].max(min_qty);
-    let xk = x[k].max(min_qty);
-    (bk / bb) * (xb / xk)
+/// Per-encounter memo for a dyad (i, j), passed through the trade search so quantities shared
+/// by many candidate pairs are computed once:
+///
+/// - pre-trade full-bundle utilities of both agents (otherwise O(n) per candidate),
+/// - beta-derived dyadic alphas, stored per unordered pair so evaluating (B,A) after (A,B)
+///   reuses the same weights (results are bit-identical to `alpha_from_beta`),
+/// - log-MRS values versus the base good used by `candidate_goods_pruned`.
+///
+/// A cache is only valid for one (i, j) dyad and one state of their endowments; build a fresh
+/// one per encounter.
+#[derive(Clone, Debug, Default)]
+pub struct DyadCache {
+    pre_utility: Option<(f64, f64)>,
+    /// (lo, hi) -> [alpha_i(lo,hi), alpha_i(hi,lo), alpha_j(lo,hi), alpha_j(hi,lo)], unclamped.
+    beta_alphas: HashMap<(usize, usize), [f64; 4]>,
+    /// g -> (ln MRS_i(g, base), ln MRS_j(g, base))
+    log_mrs: HashMap<usize, (f64, f64)>,
+}
+
+impl DyadCache {
+    pub fn new() -> Self { Self::default() }
+
+    /// Pre-trade utilities `(u_i, u_j)` over the full bundles, from the agents' utility caches
+    /// when they are current (see `Agent::log_utility`).
+    pub fn pre_utilities(&mut self, i: &Agent, j: &Agent, min_qty: f64) -> (f64, f64) {
+        *self.pre_utility.get_or_insert_with(|| {
+            (i.utility(min_qty), j.utility(min_qty))
+        })
+    }
+
+    /// Beta-derived alphas `(alpha_i, alpha_j)` for the ordered pair (a, b); same values as
+    /// `alpha_from_beta` for each agent.
+    pub fn beta_alphas(&mut self, i: &Agent, j: &Agent, a: usize, b: usize, min_alpha: f64) -> (f64, f64) {
+        let (lo, hi) = if a <= b { (a, b) } else { (b, a) };
+        let w = self.beta_alphas.entry((lo, hi)).or_insert_with(|| {
+            let (i_lh, i_hl) = split_weights(&i.beta, lo, hi);
+            let (j_lh, j_hl) = split_weights(&j.beta, lo, hi);
+            [i_lh, i_hl, j_lh, j_hl]
+        });
+        let (ai, aj) = if a <= b { (w[0], w[2]) } else { (w[1], w[3]) };
+        (ai.clamp(min_alpha, 1.0 - min_alpha), aj.clamp(min_alpha, 1.0 - min_alpha))
+    }
+
+    /// `(ln MRS_i, ln MRS_j)` of good `g` versus `base`.
+    pub fn log_mrs(&mut self, i: &Agent, j: &Agent, g: usize, base: usize, min_qty: f64) -> (f64, f64) {
+        *self.log_mrs.entry(g).or_insert_with(|| {
+            (
+                ln(mrs_to_base(&i.beta, &i.e, g, base, min_qty).max(1e-18)),
+                ln(mrs_to_base(&j.beta, &j.e, g, base, min_qty).max(1e-18)),
+            )
+        })
+    }
+
+    /// `log_mrs` of every good at once, the logarithms taken in `math::simd` chunks.
+    #[cfg(feature = "simd")]
+    fn fill_log_mrs(&mut self, i: &Agent, j: &Agent, base: usize, min_qty: f64) {
+        let n = i.e.len();
+        if self.log_mrs.len() >= n { return; }
+        let row = |a: &Agent| {
+            let mut r: Vec<f64> = (0..n).map(|g| mrs_to_base(&a.beta, &a.e, g, base, min_qty).max(1e-18)).collect();
+            crate::math::simd::ln_in_place(&mut r);
+            r
+        };
+        for (g, pair) in row(i).into_iter().zip(row(j)).enumerate() {
+            self.log_mrs.entry(g).or_insert(pair);
+        }
+    }
+}
+
+/// Unclamped `(beta_a/(beta_a+beta_b), beta_b/(beta_a+beta_b))`, matching `alpha_from_beta`.
+fn split_weights(beta: &[f64], a: usize, b: usize) -> (f64, f64) {
+    let ba = beta.get(a).copied().unwrap_or(0.0).max(0.0);
+    let bb = beta.get(b).copied().unwrap_or(0.0).max(0.0);
+    let denom = (ba + bb).max(1e-18);
+    (ba / denom, bb / denom)
 }
 
 /// Select a pruned candidate set of goods (excluding base) for a dyad (i,j).
@@ -32,14 +161,40 @@ pub fn candidate_goods_pruned(
     base: usize,
     k: usize,
     min_qty: f64,
+) -> Vec<usize> {
+    candidate_goods_pruned_cached(i, j, base, k, min_qty, &mut DyadCache::new())
+}
+
+/// `candidate_goods_pruned` reading and filling the encounter's `DyadCache`.
+pub fn candidate_goods_pruned_cached(
+    i: &Agent,
+    j: &Agent,
+    base: usize,
+    k: usize,
+    min_qty: f64,
+    cache: &mut DyadCache,
+) -> Vec<usize> {
+    ranked_candidate_goods(i, j, base, k, min_qty, cache, &TradeRules::default())
+}
+
+/// Pruned candidate goods among those `rules` allows, so blocked goods do not use up slots.
+fn ranked_candidate_goods(
+    i: &Agent,
+    j: &Agent,
+    base: usize,
+    k: usize,
+    min_qty: f64,
+    cache: &mut DyadCache,
+    rules: &TradeRules,
 ) -> Vec<usize> {
     let n = i.e.len();
     let mut scored: Vec<(usize, f64)> = Vec::with_capacity(n.saturating_sub(1));
+    #[cfg(feature = "simd")]
+    cache.fill_log_mrs(i, j, base, min_qty);
 
     for g in 0..n {
-        if g == base { continue; }
-        let mi = mrs_to_base(&i.beta, &i.e, g, base, min_qty).max(1e-18).ln();
-        let mj = mrs_to_base(&j.beta, &j.e, g, base, min_qty).max(1e-18).ln();
+        if g == base || !rules.allows(g) { continue; }
+        let (mi, mj) = cache.log_mrs(i, j, g, base, min_qty);
         scored.push((g, (mi - mj).abs()));
     }
 
@@ -48,11 +203,48 @@ pub fn candidate_goods_pruned(
     scored.into_iter().map(|(g,_)| g).collect()
 }
 
+/// Candidate goods (excluding base) for a dyad under `PairingMode::Learning`: `k` slots, each
+/// filled with probability `epsilon` by a uniformly drawn remaining good and otherwise by the
+/// remaining good with the highest combined offer value `q_values[g]` of `i` and `j` (ties
+/// broken at random). Replaces the MRS-disagreement ranking of `candidate_goods_pruned`.
+pub fn learned_candidate_goods<R: Rng>(
+    i: &Agent,
+    j: &Agent,
+    base: usize,
+    k: usize,
+    epsilon: f64,
+    rules: &TradeRules,
+    rng: &mut R,
+) -> Vec<usize> {
+    let q = |a: &Agent, g: usize| a.q_values.get(g).copied().unwrap_or(0.0);
+    let mut remaining: Vec<usize> = (0..i.e.len()).filter(|&g| g != base && rules.allows(g)).collect();
+    remaining.shuffle(rng);
+    // stable: shuffled order breaks ties
+    remaining.sort_by(|&x, &y| (q(j, y) + q(i, y)).total_cmp(&(q(j, x) + q(i, x))));
+    let mut chosen = Vec::with_capacity(k.min(remaining.len()));
+    while chosen.len() < k && !remaining.is_empty() {
+        let pick = if rng.gen::<f64>() < epsilon { rng.gen_range(0..remaining.len()) } else { 0 };
+        chosen.push(remaining.remove(pick));
+    }
+    chosen
+}
+
+/// Move `agent`'s value of offering good `g` towards the utility `reward` it realized doing so:
+/// `q += learning_rate · (reward - q)`. Missing entries start at 0.
+pub fn learn_offer_value(agent: &mut Agent, g: usize, reward: f64, learning_rate: f64) {
+    if agent.q_values.len() <= g {
+        agent.q_values.resize(agent.e.len().max(g + 1), 0.0);
+    }
+    let q = &mut agent.q_values[g];
+    *q += learning_rate * (reward - *q);
+}
+
 /// Evaluate a single ordered good-pair (A,B) P2P exchange candidate between agents i and j.
 ///
 /// - Uses dyadic Cobb–Douglas alphas inferred from each agent's beta (or alpha_to_base when B is base).
 /// - Calls the oracle to get the Pareto-optimal two-good allocation.
 /// - Scores by requiring both delta_u_i > 0 and delta_u_j > 0 (strict improvement).
+#[allow(clippy::too_many_arguments)]
 pub fn evaluate_pairwise_trade(
     i: &Agent,
     j: &Agent,
@@ -62,64 +254,65 @@ pub fn evaluate_pairwise_trade(
     min_qty: f64,
     oracle_iters: usize,
     oracle: &dyn ParetoOracle,
+) -> Option<TradeCandidate> {
+    evaluate_pairwise_trade_cached(i, j, good_a, good_b, base_good, min_qty, oracle_iters, oracle, &mut DyadCache::new())
+}
+
+/// `evaluate_pairwise_trade` sharing per-encounter work through `cache`.
+#[allow(clippy::too_many_arguments)]
+pub fn evaluate_pairwise_trade_cached(
+    i: &Agent,
+    j: &Agent,
+    good_a: usize,
+    good_b: usize,
+    base_good: usize,
+    min_qty: f64,
+    oracle_iters: usize,
+    oracle: &dyn ParetoOracle,
+    cache: &mut DyadCache,
 ) -> Option<TradeCandidate> {
     if good_a == good_b { return None; }
     if good_a >= i.e.len() || good_b >= i.e.len() { return None; }
     if i.e.len() != j.e.len() { return None; }
 
-    // Extract quantities (only A,B change; other goods fixed)
-    let ai = i.e[good_a];
-    let bi = i.e[good_b];
-    let aj = j.e[good_a];
-    let bj = j.e[good_b];
-
     // Determine alpha parameters for dyadic utility u(a,b)=a^alpha b^(1-alpha)
     let min_alpha = 1e-6;
-    let alpha_i = if good_b == base_good && i.alpha_to_base.len() == i.e.len() {
-        i.alpha_to_base[good_a].clamp(min_alpha, 1.0 - min_alpha)
-    } else {
-        alpha_from_beta(&i.beta, good_a, good_b, min_alpha)
-    };
-    let alpha_j = if good_b == base_good && j.alpha_to_base.len() == j.e.len() {
-        j.alpha_to_base[good_a].clamp(min_alpha, 1.0 - min_alpha)
+    let i_anchored = good_b == base_good && i.alpha_to_base.len() == i.e.len();
+    let j_anchored = good_b == base_good && j.alpha_to_base.len() == j.e.len();
+    let (alpha_i, alpha_j) = if i_anchored && j_anchored {
+        (
+            i.alpha_to_base[good_a].clamp(min_alpha, 1.0 - min_alpha),
+            j.alpha_to_base[good_a].clamp(min_alpha, 1.0 - min_alpha),
+        )
     } else {
-        alpha_from_beta(&j.beta, good_a, good_b, min_alpha)
+        let (bi_alpha, bj_alpha) = cache.beta_alphas(i, j, good_a, good_b, min_alpha);
+        (
+            if i_anchored { i.alpha_to_base[good_a].clamp(min_alpha, 1.0 - min_alpha) } else { bi_alpha },
+            if j_anchored { j.alpha_to_base[good_a].clamp(min_alpha, 1.0 - min_alpha) } else { bj_alpha },
+        )
     };
 
-    let ex = oracle.solve_two_good_exchange(alpha_i, ai, bi, alpha_j, aj, bj, min_qty, oracle_iters);
-
-    // Build counterfactual post-trade full bundles (n goods), changing only A,B
-    let mut xi_post = i.e.clone();
-    xi_post[good_a] = ex.ai_post;
-    xi_post[good_b] = ex.bi_post;
-
-    let mut xj_post = j.e.clone();
-    xj_post[good_a] = ex.aj_post;
-    xj_post[good_b] = ex.bj_post;
-
-    // Compute utility deltas using full n-good CD utility
-    let ui0 = cd_utility(&i.beta, &i.e, min_qty);
-    let uj0 = cd_utility(&j.beta, &j.e, min_qty);
-
-    let ui1 = cd_utility(&i.beta, &xi_post, min_qty);
-    let uj1 = cd_utility(&j.beta, &xj_post, min_qty);
-
-    let delta_u_i = ui1 - ui0;
-    let delta_u_j = uj1 - uj0;
-
-    if delta_u_i > 0.0 && delta_u_j > 0.0 {
-        Some(TradeCandidate {
-            good_a,
-            good_b,
-            q_ab: ex.q_ab,
-            delta_a_i: ex.ai_post - ai,
-            delta_b_i: ex.bi_post - bi,
-            delta_u_i,
-            delta_u_j,
-        })
-    } else {
-        None
+    let pre = cache.pre_utilities(i, j, min_qty);
+    let cand = exchange_candidate(
+        (&i.beta, &i.e),
+        (&j.beta, &j.e),
+        good_a,
+        good_b,
+        (alpha_i, alpha_j),
+        pre,
+        min_qty,
+        oracle_iters,
+        oracle,
+    )?;
+    // agents with price expectations refuse trades priced far worse than they expect
+    if let Some((g, price)) = base_price(good_a, good_b, cand.q_ab, base_good) {
+        let i_buys = if g == good_a { cand.delta_a_i > 0.0 } else { cand.delta_b_i > 0.0 };
+        let refused = |a: &Agent, buying: bool| {
+            a.price_memory.as_ref().is_some_and(|m| m.rejects(g, price, buying))
+        };
+        if refused(i, i_buys) || refused(j, !i_buys) { return None; }
     }
+    Some(cand)
 }
 
 /// Evaluate every good A against the base good B for a P2P encounter, and return the best candidate.
@@ -130,16 +323,31 @@ pub fn best_trade_against_base(
     min_qty: f64,
     oracle_iters: usize,
     oracle: &dyn ParetoOracle,
+) -> Option<TradeCandidate> {
+    best_trade_against_base_with(i, j, base_good, min_qty, oracle_iters, oracle, &TradeRules::default())
+}
+
+/// `best_trade_against_base` skipping goods blocked by `rules`.
+pub fn best_trade_against_base_with(
+    i: &Agent,
+    j: &Agent,
+    base_good: usize,
+    min_qty: f64,
+    oracle_iters: usize,
+    oracle: &dyn ParetoOracle,
+    rules: &TradeRules,
 ) -> Option<TradeCandidate> {
     let n = i.e.len();
     if n != j.e.len() { return None; }
+    if !rules.allows(base_good) { return None; }
 
     let mut best: Option<TradeCandidate> = None;
+    let mut cache = DyadCache::new();
 
     for a in 0..n {
-        if a == base_good { continue; }
-        if let Some(cand) = evaluate_pairwise_trade(
-            i, j, a, base_good, base_good, min_qty, oracle_iters, oracle
+        if a == base_good || !rules.allows(a) { continue; }
+        if let Some(cand) = evaluate_pairwise_trade_cached(
+            i, j, a, base_good, base_good, min_qty, oracle_iters, oracle, &mut cache
         ) {
             let score = cand.delta_u_i.min(cand.delta_u_j); // conservative
             match &best {
@@ -169,20 +377,75 @@ pub fn best_trade_over_all_pairs_pruned(
     min_qty: f64,
     oracle_iters: usize,
     oracle: &dyn ParetoOracle,
+) -> Option<TradeCandidate> {
+    best_trade_over_all_pairs_pruned_with(
+        i, j, base_good, candidate_goods_k, min_qty, oracle_iters, oracle, &TradeRules::default()
+    )
+}
+
+/// `best_trade_over_all_pairs_pruned` restricted to goods allowed by `rules`.
+#[allow(clippy::too_many_arguments)]
+pub fn best_trade_over_all_pairs_pruned_with(
+    i: &Agent,
+    j: &Agent,
+    base_good: usize,
+    candidate_goods_k: usize,
+    min_qty: f64,
+    oracle_iters: usize,
+    oracle: &dyn ParetoOracle,
+    rules: &TradeRules,
 ) -> Option<TradeCandidate> {
     let n = i.e.len();
     if n != j.e.len() { return None; }
 
-    let mut cand_goods = candidate_goods_pruned(i, j, base_good, candidate_goods_k, min_qty);
-    // Always include base good in the candidate pool
-    cand_goods.push(base_good);
+    let mut cache = DyadCache::new();
+    let cand_goods = ranked_candidate_goods(i, j, base_good, candidate_goods_k, min_qty, &mut cache, rules);
+    best_trade_among(i, j, cand_goods, base_good, min_qty, oracle_iters, oracle, rules, &mut cache)
+}
+
+/// `best_trade_over_all_pairs_pruned_with` over a given candidate set `goods` (the base good is
+/// added), e.g. one chosen by `learned_candidate_goods`. Goods `rules` blocks are skipped.
+#[allow(clippy::too_many_arguments)]
+pub fn best_trade_over_goods_with(
+    i: &Agent,
+    j: &Agent,
+    goods: &[usize],
+    base_good: usize,
+    min_qty: f64,
+    oracle_iters: usize,
+    oracle: &dyn ParetoOracle,
+    rules: &TradeRules,
+) -> Option<TradeCandidate> {
+    let n = i.e.len();
+    if n != j.e.len() { return None; }
+    let cand_goods = goods.iter().copied().filter(|&g| g < n && g != base_good && rules.allows(g)).collect();
+    best_trade_among(i, j, cand_goods, base_good, min_qty, oracle_iters, oracle, rules, &mut DyadCache::new())
+}
+
+/// Best trade over all ordered pairs of `cand_goods` plus the base good, by the smaller gain.
+#[allow(clippy::too_many_arguments)]
+fn best_trade_among(
+    i: &Agent,
+    j: &Agent,
+    mut cand_goods: Vec<usize>,
+    base_good: usize,
+    min_qty: f64,
+    oracle_iters: usize,
+    oracle: &dyn ParetoOracle,
+    rules: &TradeRules,
+    cache: &mut DyadCache,
+) -> Option<TradeCandidate> {
+    // Always include base good in the candidate pool (unless it is blocked)
+    if rules.allows(base_good) {
+        cand_goods.push(base_good);
+    }
 
     let mut best: Option<TradeCandidate> = None;
 
     for &a in cand_goods.iter() {
         for &b in cand_goods.iter() {
             if a == b { continue; }
-            if let Some(cand) = evaluate_pairwise_trade(i, j, a, b, base_good, min_qty, oracle_iters, oracle) {
+            if let Some(cand) = evaluate_pairwise_trade_cached(i, j, a, b, base_good, min_qty, oracle_iters, oracle, cache) {
                 let score = cand.delta_u_i.min(cand.delta_u_j);
                 match &best {
                     None => best = Some(cand),
@@ -196,23 +459,757 @@ pub fn best_trade_over_all_pairs_pruned(
             }
         }
     }
+    trace_event!(
+        tracing::Level::TRACE,
+        goods = cand_goods.len(), pairs = cand_goods.len() * cand_goods.len().saturating_sub(1),
+        found = best.is_some(), "candidate search"
+    );
     best
 }
 
+/// Best trade for a dyad when no good is designated as base and goods may be accepted as media
+/// of exchange (Kiyotaki–Wright). Every ordered pair (A, B) is evaluated twice: as the oracle's
+/// mutually improving exchange, and as a speculative swap in which i gives `step` of its B for A
+/// at the geometric mean of both agents' MRS. A candidate is acceptable when each side's
+/// relative utility change `Δu / u` plus the resale value of what it receives, minus that of what
+/// it gives (`Marketability::resale_value`), is positive; the best by the smaller of the two
+/// scores is returned. Without `rules.speculation` this never proposes a trade.
+pub fn best_speculative_trade(
+    i: &Agent,
+    j: &Agent,
+    min_qty: f64,
+    oracle_iters: usize,
+    oracle: &dyn ParetoOracle,
+    rules: &TradeRules,
+) -> Option<TradeCandidate> {
+    let market = rules.speculation.as_ref()?;
+    let n = i.e.len();
+    if n != j.e.len() { return None; }
+    let mut cache = DyadCache::new();
+    let (ui0, uj0) = cache.pre_utilities(i, j, min_qty);
+    if ui0 <= 0.0 || uj0 <= 0.0 { return None; }
+
+    let score = |c: &TradeCandidate| {
+        let (a, b) = (c.good_a.index(), c.good_b.index());
+        // resale value i gains net of what j gains
+        let flow = market.resale_value(a, c.delta_a_i) + market.resale_value(b, c.delta_b_i);
+        (c.delta_u_i / ui0 + flow).min(c.delta_u_j / uj0 - flow)
+    };
+    let mut best: Option<(f64, TradeCandidate)> = None;
+    for a in (0..n).filter(|&g| rules.allows(g)) {
+        for b in (0..n).filter(|&g| g != a && rules.allows(g)) {
+            // `n` as base: dyadic alphas always come from beta
+            let direct = evaluate_pairwise_trade_cached(i, j, a, b, n, min_qty, oracle_iters, oracle, &mut cache);
+            for cand in direct.into_iter().chain(speculative_swap(i, j, a, b, market.step, min_qty)) {
+                let s = score(&cand);
+                let better = match &best {
+                    None => true,
+                    Some((bs, _)) => s > *bs,
+                };
+                if s > 0.0 && better {
+                    best = Some((s, cand));
+                }
+            }
+        }
+    }
+    best.map(|(_, c)| c)
+}
+
+/// i gives `step` of its good `b` (or less, so it receives at most `step` of j's `a`) for `a`,
+/// priced at the geometric mean of both agents' MRS of `a` in units of `b`.
+fn speculative_swap(i: &Agent, j: &Agent, a: usize, b: usize, step: f64, min_qty: f64) -> Option<TradeCandidate> {
+    let price = (mrs_to_base(&i.beta, &i.e, a, b, min_qty) * mrs_to_base(&j.beta, &j.e, a, b, min_qty)).sqrt();
+    if !price.is_finite() || price <= 0.0 { return None; }
+    let mut give_b = step.clamp(0.0, 1.0) * i.e[b];
+    let mut get_a = give_b / price;
+    let cap = step.clamp(0.0, 1.0) * j.e[a];
+    if get_a > cap {
+        get_a = cap;
+        give_b = get_a * price;
+    }
+    if get_a <= min_qty || give_b <= min_qty { return None; }
+
+    let mut xi = i.e.clone();
+    xi[a] += get_a;
+    xi[b] -= give_b;
+    let mut xj = j.e.clone();
+    xj[a] -= get_a;
+    xj[b] += give_b;
+    let delta_u_i = cd_utility(&i.beta, &xi, min_qty) - cd_utility(&i.beta, &i.e, min_qty);
+    let delta_u_j = cd_utility(&j.beta, &xj, min_qty) - cd_utility(&j.beta, &j.e, min_qty);
+    Some(TradeCandidate {
+        good_a: GoodId(a),
+        good_b: GoodId(b),
+        q_ab: price,
+        delta_a_i: get_a,
+        delta_b_i: -give_b,
+        delta_u_i,
+        delta_u_j,
+        shape_i: directional_shape(&i.beta, &i.e, a, b, get_a, -give_b, min_qty),
+        shape_j: directional_shape(&j.beta, &j.e, a, b, -get_a, give_b, min_qty),
+        speculative: delta_u_i <= 0.0 || delta_u_j <= 0.0,
+    })
+}
+
+/// Trade of good `a` against good `b` negotiated from quotes alone, without either side seeing
+/// the other's preferences (`SimConfig::negotiation`).
+///
+/// The agent with the lower MRS sells `a`. Each side quotes its MRS shaded by `s = (1 -
+/// disclosure) / 2`: the seller asks `mrs_s · (1 + s)`, the buyer bids `mrs_b / (1 + s)`. If the
+/// bid covers the ask they trade at the geometric midpoint `sqrt(ask · bid)`, each revealing a
+/// quantity cap (its own optimum at that price), and the smaller cap executes; each side then
+/// accepts only if its own utility rises. Shading both quotes by the same factor leaves the
+/// midpoint at `sqrt(mrs_s · mrs_b)` whatever the disclosure, so less disclosure only widens the
+/// spread and some gains from trade go unrealized.
+pub fn negotiate_trade(
+    i: &Agent,
+    j: &Agent,
+    a: usize,
+    b: usize,
+    disclosure: f64,
+    min_qty: f64,
+) -> Option<TradeCandidate> {
+    let n = i.e.len();
+    if n != j.e.len() || a >= n || b >= n || a == b { return None; }
+    let (mi, mj) = (mrs_to_base(&i.beta, &i.e, a, b, min_qty), mrs_to_base(&j.beta, &j.e, a, b, min_qty));
+    let i_sells = mi < mj;
+    let (seller, buyer) = if i_sells { (i, j) } else { (j, i) };
+    let shade = 0.5 * (1.0 - disclosure.clamp(0.0, 1.0));
+    let ask = mi.min(mj) * (1.0 + shade);
+    let bid = mi.max(mj) / (1.0 + shade);
+    if bid < ask || !bid.is_finite() { return None; }
+    let p = (ask * bid).sqrt();
+
+    let want = demand_at_price(&buyer.beta, &buyer.e, a, b, p, min_qty);
+    let offer = -demand_at_price(&seller.beta, &seller.e, a, b, p, min_qty);
+    let q = want.min(offer)
+        .min(seller.e[a] - min_qty)
+        .min((buyer.e[b] - min_qty) / p);
+    if q <= 0.0 { return None; }
+
+    // i's side of the trade
+    let (da, db) = if i_sells { (-q, p * q) } else { (q, -p * q) };
+    let mut xi = i.e.clone();
+    xi[a] += da;
+    xi[b] += db;
+    let mut xj = j.e.clone();
+    xj[a] -= da;
+    xj[b] -= db;
+    let delta_u_i = cd_utility(&i.beta, &xi, min_qty) - cd_utility(&i.beta, &i.e, min_qty);
+    let delta_u_j = cd_utility(&j.beta, &xj, min_qty) - cd_utility(&j.beta, &j.e, min_qty);
+    let accepted = delta_u_i > 0.0 && delta_u_j > 0.0;
+    if !accepted { return None; }
+    Some(TradeCandidate {
+        good_a: GoodId(a),
+        good_b: GoodId(b),
+        q_ab: p,
+        delta_a_i: da,
+        delta_b_i: db,
+        delta_u_i,
+        delta_u_j,
+        shape_i: directional_shape(&i.beta, &i.e, a, b, da, db, min_qty),
+        shape_j: directional_shape(&j.beta, &j.e, a, b, -da, -db, min_qty),
+        speculative: false,
+    })
+}
+
+/// `negotiate_trade` of every allowed good against the base good. Without access to each other's
+/// utilities the dyad settles on the good whose quotes cross by the widest margin (largest
+/// `bid / ask`), which both sides observe.
+pub fn best_negotiated_trade(
+    i: &Agent,
+    j: &Agent,
+    base_good: usize,
+    disclosure: f64,
+    min_qty: f64,
+    rules: &TradeRules,
+) -> Option<TradeCandidate> {
+    let n = i.e.len();
+    if n != j.e.len() || base_good >= n || !rules.allows(base_good) { return None; }
+    let shade = 0.5 * (1.0 - disclosure.clamp(0.0, 1.0));
+    let mut best: Option<(f64, TradeCandidate)> = None;
+    for a in (0..n).filter(|&a| a != base_good && rules.allows(a)) {
+        let (mi, mj) = (mrs_to_base(&i.beta, &i.e, a, base_good, min_qty), mrs_to_base(&j.beta, &j.e, a, base_good, min_qty));
+        let margin = mi.max(mj) / (mi.min(mj) * (1.0 + shade).powi(2));
+        if best.as_ref().is_some_and(|(m, _)| *m >= margin) { continue; }
+        if let Some(cand) = negotiate_trade(i, j, a, base_good, disclosure, min_qty) {
+            best = Some((margin, cand));
+        }
+    }
+    best.map(|(_, cand)| cand)
+}
+
+/// Zero-intelligence trade (Gode–Sunder baseline, `SimConfig::zero_intelligence`): a uniformly
+/// random ordered pair `(a, b)` of distinct allowed goods, and uniform quantities within what
+/// each side holds above `min_qty` — `j` gives up to all of its `a`, `i` up to all of its `b`.
+///
+/// With `constrained` (ZI-C) the trade is proposed only if both agents gain, otherwise (ZI-U) it
+/// is proposed regardless. The utility changes are the true ones either way.
+pub fn zero_intelligence_trade<R: Rng>(
+    i: &Agent,
+    j: &Agent,
+    constrained: bool,
+    min_qty: f64,
+    rules: &TradeRules,
+    rng: &mut R,
+) -> Option<TradeCandidate> {
+    let n = i.e.len();
+    if n != j.e.len() { return None; }
+    let goods: Vec<usize> = (0..n).filter(|&g| rules.allows(g)).collect();
+    if goods.len() < 2 { return None; }
+    let a = goods[rng.gen_range(0..goods.len())];
+    let mut b = goods[rng.gen_range(0..goods.len())];
+    while b == a {
+        b = goods[rng.gen_range(0..goods.len())];
+    }
+    let get_a = rng.gen::<f64>() * (j.e[a] - min_qty).max(0.0);
+    let give_b = rng.gen::<f64>() * (i.e[b] - min_qty).max(0.0);
+    if get_a <= 0.0 || give_b <= 0.0 { return None; }
+
+    let mut xi = i.e.clone();
+    xi[a] += get_a;
+    xi[b] -= give_b;
+    let mut xj = j.e.clone();
+    xj[a] -= get_a;
+    xj[b] += give_b;
+    let delta_u_i = cd_utility(&i.beta, &xi, min_qty) - cd_utility(&i.beta, &i.e, min_qty);
+    let delta_u_j = cd_utility(&j.beta, &xj, min_qty) - cd_utility(&j.beta, &j.e, min_qty);
+    let mutual = delta_u_i > 0.0 && delta_u_j > 0.0;
+    if constrained && !mutual { return None; }
+    Some(TradeCandidate {
+        good_a: GoodId(a),
+        good_b: GoodId(b),
+        q_ab: give_b / get_a,
+        delta_a_i: get_a,
+        delta_b_i: -give_b,
+        delta_u_i,
+        delta_u_j,
+        shape_i: directional_shape(&i.beta, &i.e, a, b, get_a, -give_b, min_qty),
+        shape_j: directional_shape(&j.beta, &j.e, a, b, -get_a, give_b, min_qty),
+        speculative: false,
+    })
+}
+
+/// A simultaneous exchange of several goods within a dyad (see `evaluate_bundle_trade`).
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BundleTrade {
+    /// Goods exchanged, the base good last.
+    pub goods: Vec<GoodId>,
+    /// Change in i's holdings of each of `goods`; j's change is the negative.
+    pub delta_i: Vec<f64>,
+    /// Dyadic equilibrium prices of `goods` in units of the base good. The trade is balanced:
+    /// `Σ prices[m] · delta_i[m] = 0`.
+    pub prices: Vec<f64>,
+    pub delta_u_i: f64,
+    pub delta_u_j: f64,
+}
+
+impl BundleTrade {
+    /// Scale every quantity by `f` (utility changes are left as evaluated).
+    pub fn scale(&mut self, f: f64) {
+        for d in self.delta_i.iter_mut() {
+            *d *= f;
+        }
+    }
+
+    /// The trade as simultaneous two-good swaps of each non-base good against the base good at
+    /// `prices`; their base-good legs add up to the bundle's.
+    pub fn legs(&self) -> Vec<TradeCandidate> {
+        let Some(&base) = self.goods.last() else { return Vec::new() };
+        self.goods.iter().zip(self.delta_i.iter()).zip(self.prices.iter())
+            .filter(|((&g, _), _)| g != base)
+            .map(|((&g, &d), &p)| TradeCandidate {
+                good_a: g,
+                good_b: base,
+                q_ab: p,
+                delta_a_i: d,
+                delta_b_i: -p * d,
+                ..Default::default()
+            })
+            .collect()
+    }
+}
+
+/// Multi-good package trade for a dyad: the two-agent Walrasian equilibrium over the pruned
+/// candidate goods (`candidate_goods_k` by MRS disagreement) plus the base good, other goods
+/// held fixed.
+///
+/// Cobb–Douglas preferences are separable, so restricted to the candidate set each agent has
+/// exponents `β_g / Σ_S β`. The equilibrium allocation is Pareto efficient over the whole set
+/// in one step, where two-good swaps only equalize one MRS at a time. Returns the trade if both
+/// agents strictly gain.
+pub fn evaluate_bundle_trade(
+    i: &Agent,
+    j: &Agent,
+    base_good: usize,
+    candidate_goods_k: usize,
+    min_qty: f64,
+    rules: &TradeRules,
+) -> Option<BundleTrade> {
+    let n = i.e.len();
+    if n != j.e.len() || base_good >= n || !rules.allows(base_good) { return None; }
+    let mut goods = ranked_candidate_goods(i, j, base_good, candidate_goods_k, min_qty, &mut DyadCache::new(), rules);
+    if goods.is_empty() { return None; }
+    goods.push(base_good);
+
+    let restrict = |a: &Agent| Agent {
+        e: goods.iter().map(|&g| a.e[g].max(min_qty)).collect(),
+        beta: goods.iter().map(|&g| a.beta.get(g).copied().unwrap_or(0.0)).collect(),
+        ..Default::default()
+    };
+    let dyad = [restrict(i), restrict(j)];
+    let prices = walras_prices(&dyad, goods.len() - 1);
+    let alloc = walras_allocation(&dyad, &prices);
+    let delta_i: Vec<f64> = alloc[0].iter().zip(dyad[0].e.iter()).map(|(x, e)| x - e).collect();
+
+    let mut xi = i.e.clone();
+    let mut xj = j.e.clone();
+    for (&g, &d) in goods.iter().zip(delta_i.iter()) {
+        xi[g] += d;
+        xj[g] -= d;
+    }
+    let delta_u_i = cd_utility(&i.beta, &xi, min_qty) - cd_utility(&i.beta, &i.e, min_qty);
+    let delta_u_j = cd_utility(&j.beta, &xj, min_qty) - cd_utility(&j.beta, &j.e, min_qty);
+    let mutual = delta_u_i > 0.0 && delta_u_j > 0.0;
+    if !mutual { return None; }
+    Some(BundleTrade { goods: goods.into_iter().map(GoodId).collect(), delta_i, prices, delta_u_i, delta_u_j })
+}
+
+/// Execute `trade` between `i` and `j`. Fails without modifying either agent if a good is out
+/// of range.
+pub fn apply_bundle_trade(i: &mut Agent, j: &mut Agent, trade: &BundleTrade, min_qty: f64) -> Result<(), RdxError> {
+    let n = i.e.len().min(j.e.len());
+    if let Some(g) = trade.goods.iter().find(|g| g.index() >= n) {
+        return Err(RdxError::GoodOutOfRange { index: g.index(), len: n });
+    }
+    for (g, &d) in trade.goods.iter().zip(trade.delta_i.iter()) {
+        let g = g.index();
+        i.set_holding(g, (i.e[g] + d).max(min_qty), min_qty);
+        j.set_holding(g, (j.e[g] - d).max(min_qty), min_qty);
+    }
+    Ok(())
+}
+
+/// A cyclical exchange among three agents: `i` gives `quantities[0]` of `goods[0]` to `j`, `j`
+/// gives `quantities[1]` of `goods[1]` to `k`, and `k` gives `quantities[2]` of `goods[2]` to `i`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ThreeWayCycle {
+    pub goods: [GoodId; 3],
+    pub quantities: [f64; 3],
+    /// Utility change of i, j and k.
+    pub delta_u: [f64; 3],
+}
+
+/// Best utility-improving three-way cycle among `i`, `j` and `k`, for trades that fail the
+/// double coincidence of wants (each agent only wants what the next-but-one holds).
+///
+/// Every ordered triple of distinct allowed goods is tried. The legs are value-balanced at
+/// prices `p_g`, the mean marginal utility `β_g / x_g` of the three agents, so each agent gives
+/// and receives the same value `V`; `V` is `step` (then `step / 2`, `step / 4`) times the
+/// smallest gift the agents can afford. A cycle qualifies when all three gain; the best by the
+/// smallest gain is returned.
+pub fn best_three_way_cycle(
+    i: &Agent,
+    j: &Agent,
+    k: &Agent,
+    step: f64,
+    min_qty: f64,
+    rules: &TradeRules,
+) -> Option<ThreeWayCycle> {
+    let n = i.e.len();
+    if n != j.e.len() || n != k.e.len() { return None; }
+    let agents = [i, j, k];
+    let u0 = agents.map(|a| cd_utility(&a.beta, &a.e, min_qty));
+    let price = |g: usize| {
+        agents.iter()
+            .map(|a| a.beta.get(g).copied().unwrap_or(0.0).max(0.0) / a.e[g].max(min_qty))
+            .sum::<f64>() / 3.0
+    };
+    let prices: Vec<f64> = (0..n).map(price).collect();
+
+    let mut best: Option<ThreeWayCycle> = None;
+    let allowed: Vec<usize> = (0..n).filter(|&g| rules.allows(g)).collect();
+    for &g0 in allowed.iter() {
+        for &g1 in allowed.iter().filter(|&&g| g != g0) {
+            for &g2 in allowed.iter().filter(|&&g| g != g0 && g != g1) {
+                let goods = [g0, g1, g2];
+                if goods.iter().any(|&g| prices[g] <= 0.0) { continue; }
+                // agent m gives goods[m]; the value it can afford to give
+                let capacity = (0..3).map(|m| prices[goods[m]] * agents[m].e[goods[m]]).fold(f64::INFINITY, f64::min);
+                for scale in [1.0, 0.5, 0.25] {
+                    let value = step.clamp(0.0, 1.0) * scale * capacity;
+                    let q = goods.map(|g| value / prices[g]);
+                    if q.iter().any(|&x| x <= min_qty) { break; }
+                    let delta_u: [f64; 3] = std::array::from_fn(|m| {
+                        let mut x = agents[m].e.clone();
+                        x[goods[m]] -= q[m];
+                        // agent m receives from its predecessor in the cycle
+                        let from = (m + 2) % 3;
+                        x[goods[from]] += q[from];
+                        cd_utility(&agents[m].beta, &x, min_qty) - u0[m]
+                    });
+                    let worst = delta_u.iter().copied().fold(f64::INFINITY, f64::min);
+                    if worst <= 0.0 { continue; }
+                    let better = match &best {
+                        None => true,
+                        Some(b) => worst > b.delta_u.iter().copied().fold(f64::INFINITY, f64::min),
+                    };
+                    if better {
+                        best = Some(ThreeWayCycle { goods: goods.map(GoodId), quantities: q, delta_u });
+                    }
+                    break;
+                }
+            }
+        }
+    }
+    best
+}
+
+/// Execute `cycle` among `i`, `j` and `k` (see `ThreeWayCycle`).
+pub fn apply_three_way_cycle(
+    i: &mut Agent,
+    j: &mut Agent,
+    k: &mut Agent,
+    cycle: &ThreeWayCycle,
+    min_qty: f64,
+) -> Result<(), RdxError> {
+    let n = i.e.len().min(j.e.len()).min(k.e.len());
+    if let Some(g) = cycle.goods.iter().find(|g| g.index() >= n) {
+        return Err(RdxError::GoodOutOfRange { index: g.index(), len: n });
+    }
+    let agents = [i, j, k];
+    for (m, (g, &q)) in cycle.goods.iter().zip(cycle.quantities.iter()).enumerate() {
+        let g = g.index();
+        let (given, next) = (agents[m].e[g], (m + 1) % 3);
+        agents[m].set_holding(g, (given - q).max(min_qty), min_qty);
+        let received = agents[next].e[g];
+        agents[next].set_holding(g, received + q, min_qty);
+    }
+    Ok(())
+}
+
 /// Execute a trade candidate by mutating both agents' endowments for goods (A,B).
-pub fn apply_trade(i: &mut Agent, j: &mut Agent, cand: &TradeCandidate, min_qty: f64) {
-    let a = cand.good_a;
-    let b = cand.good_b;
+///
+/// Fails without modifying either agent if the candidate's goods are not held by both.
+pub fn apply_trade(i: &mut Agent, j: &mut Agent, cand: &TradeCandidate, min_qty: f64) -> Result<(), RdxError> {
+    let a = cand.good_a.index();
+    let b = cand.good_b.index();
+    let n = i.e.len().min(j.e.len());
+    for g in [a, b] {
+        if g >= n {
+            return Err(RdxError::GoodOutOfRange { index: g, len: n });
+        }
+    }
 
     // Update i; j gets opposite deltas due to conservation of A and B within the dyad.
-    i.e[a] = (i.e[a] + cand.delta_a_i).max(min_qty);
-    i.e[b] = (i.e[b] + cand.delta_b_i).max(min_qty);
+    i.set_holding(a, (i.e[a] + cand.delta_a_i).max(min_qty), min_qty);
+    i.set_holding(b, (i.e[b] + cand.delta_b_i).max(min_qty), min_qty);
+
+    j.set_holding(a, (j.e[a] - cand.delta_a_i).max(min_qty), min_qty);
+    j.set_holding(b, (j.e[b] - cand.delta_b_i).max(min_qty), min_qty);
+    Ok(())
+}
+
+/// Largest fraction in [0, 1] of exchanging `deltas` (good, change in i's holding; j's is the
+/// negative) that leaves every holding of both agents at or above `min_qty`. Goods must be in
+/// range.
+fn floor_fraction(i: &Agent, j: &Agent, deltas: impl Iterator<Item = (usize, f64)>, min_qty: f64) -> f64 {
+    let mut t: f64 = 1.0;
+    for (g, d) in deltas {
+        for (x, d) in [(i.e[g], d), (j.e[g], -d)] {
+            if d < 0.0 {
+                t = t.min((x - min_qty) / -d);
+            }
+        }
+    }
+    t.max(0.0)
+}
+
+/// Fraction of `cand` that `apply_trade` can execute without raising any of the four holdings
+/// to `min_qty`, i.e. without creating goods (0 when a good is out of range).
+pub fn conserving_fraction(i: &Agent, j: &Agent, cand: &TradeCandidate, min_qty: f64) -> f64 {
+    let (a, b) = (cand.good_a.index(), cand.good_b.index());
+    if a.max(b) >= i.e.len().min(j.e.len()) {
+        return 0.0;
+    }
+    floor_fraction(i, j, [(a, cand.delta_a_i), (b, cand.delta_b_i)].into_iter(), min_qty)
+}
+
+/// `conserving_fraction` for a package trade.
+pub fn bundle_conserving_fraction(i: &Agent, j: &Agent, trade: &BundleTrade, min_qty: f64) -> f64 {
+    let n = i.e.len().min(j.e.len());
+    if trade.goods.iter().any(|g| g.index() >= n) {
+        return 0.0;
+    }
+    floor_fraction(i, j, trade.goods.iter().map(|g| g.index()).zip(trade.delta_i.iter().copied()), min_qty)
+}
+
+/// `apply_trade` that conserves both goods within the dyad: where the floors of `apply_trade`
+/// would create goods, the trade is first shrunk by `conserving_fraction` just enough to keep
+/// every holding at or above `min_qty`. Returns the fraction executed; `cand` is left as passed.
+/// Debug builds assert that the pair's totals of A and B are unchanged.
+pub fn apply_trade_conserving(
+    i: &mut Agent,
+    j: &mut Agent,
+    cand: &TradeCandidate,
+    min_qty: f64,
+) -> Result<f64, RdxError> {
+    let (a, b) = (cand.good_a.index(), cand.good_b.index());
+    let n = i.e.len().min(j.e.len());
+    if let Some(g) = [a, b].into_iter().find(|&g| g >= n) {
+        return Err(RdxError::GoodOutOfRange { index: g, len: n });
+    }
+    let t = conserving_fraction(i, j, cand, min_qty);
+    let totals = [i.e[a] + j.e[a], i.e[b] + j.e[b]];
+    for (g, d) in [(a, t * cand.delta_a_i), (b, t * cand.delta_b_i)] {
+        let (xi, xj) = (i.e[g], j.e[g]);
+        i.set_holding(g, xi + d, min_qty);
+        j.set_holding(g, xj - d, min_qty);
+    }
+    debug_assert_conserved(&totals, [a, b], i, j);
+    Ok(t)
+}
+
+/// `apply_bundle_trade` shrunk by `bundle_conserving_fraction`, so every good of the package is
+/// conserved within the dyad. Returns the fraction executed.
+pub fn apply_bundle_trade_conserving(
+    i: &mut Agent,
+    j: &mut Agent,
+    trade: &BundleTrade,
+    min_qty: f64,
+) -> Result<f64, RdxError> {
+    let n = i.e.len().min(j.e.len());
+    if let Some(g) = trade.goods.iter().find(|g| g.index() >= n) {
+        return Err(RdxError::GoodOutOfRange { index: g.index(), len: n });
+    }
+    let t = bundle_conserving_fraction(i, j, trade, min_qty);
+    let goods: Vec<usize> = trade.goods.iter().map(|g| g.index()).collect();
+    let totals: Vec<f64> = goods.iter().map(|&g| i.e[g] + j.e[g]).collect();
+    for (&g, &d) in goods.iter().zip(trade.delta_i.iter()) {
+        let (xi, xj) = (i.e[g], j.e[g]);
+        i.set_holding(g, xi + t * d, min_qty);
+        j.set_holding(g, xj - t * d, min_qty);
+    }
+    debug_assert_conserved(&totals, goods, i, j);
+    Ok(t)
+}
+
+/// Debug builds: the dyad still holds `totals` of `goods`, to rounding.
+fn debug_assert_conserved(totals: &[f64], goods: impl IntoIterator<Item = usize>, i: &Agent, j: &Agent) {
+    if cfg!(debug_assertions) {
+        for (&total, g) in totals.iter().zip(goods) {
+            let now = i.e[g] + j.e[g];
+            assert!(
+                (now - total).abs() <= 1e-12 * total.abs().max(1.0),
+                "good {} not conserved in the dyad: {} before, {} after", g, total, now,
+            );
+        }
+    }
+}
+
+/// Scale `cand` by `frac` (the step cap) and re-evaluate it on the capped allocation, floors
+/// included, as `apply_trade` would leave it: `delta_u_i` and `delta_u_j` become the capped
+/// trade's utility changes. Returns whether both agents still strictly gain; scaling a trade
+/// towards the endowment can cost one side its gain, e.g. when a holding sits at `min_qty`.
+pub fn scale_and_validate(i: &Agent, j: &Agent, cand: &mut TradeCandidate, frac: f64, min_qty: f64) -> bool {
+    cand.delta_a_i *= frac;
+    cand.delta_b_i *= frac;
+    let (a, b) = (cand.good_a.index(), cand.good_b.index());
+    let n = i.e.len().min(j.e.len());
+    if a >= n || b >= n {
+        return false;
+    }
+    let (ui0, uj0) = (i.utility(min_qty), j.utility(min_qty));
+    let di = log_utility_change(&i.beta, &i.e, (a, i.e[a] + cand.delta_a_i), (b, i.e[b] + cand.delta_b_i), min_qty);
+    let dj = log_utility_change(&j.beta, &j.e, (a, j.e[a] - cand.delta_a_i), (b, j.e[b] - cand.delta_b_i), min_qty);
+    cand.delta_u_i = ui0 * exp(di) - ui0;
+    cand.delta_u_j = uj0 * exp(dj) - uj0;
+    cand.delta_u_i > 0.0 && cand.delta_u_j > 0.0
+}
+
+/// `scale_and_validate` for a package trade: scale it by `frac` and re-evaluate both agents on
+/// the capped bundles as `apply_bundle_trade` would leave them.
+pub fn scale_and_validate_bundle(i: &Agent, j: &Agent, trade: &mut BundleTrade, frac: f64, min_qty: f64) -> bool {
+    trade.scale(frac);
+    let n = i.e.len().min(j.e.len());
+    if trade.goods.iter().any(|g| g.index() >= n) {
+        return false;
+    }
+    let (mut xi, mut xj) = (i.e.clone(), j.e.clone());
+    for (g, &d) in trade.goods.iter().zip(trade.delta_i.iter()) {
+        xi[g.index()] = (xi[g.index()] + d).max(min_qty);
+        xj[g.index()] = (xj[g.index()] - d).max(min_qty);
+    }
+    trade.delta_u_i = cd_utility(&i.beta, &xi, min_qty) - cd_utility(&i.beta, &i.e, min_qty);
+    trade.delta_u_j = cd_utility(&j.beta, &xj, min_qty) - cd_utility(&j.beta, &j.e, min_qty);
+    trade.delta_u_i > 0.0 && trade.delta_u_j > 0.0
+}
+
+/// One side's cost for receiving `received` of good `g`: `fixed + proportional · received ·
+/// MRS_{g,base}` at the payer's pre-trade bundle, in base-good units.
+pub fn trade_cost(cost: &TransactionCost, payer: &Agent, g: usize, received: f64, base_good: usize, min_qty: f64) -> f64 {
+    let price = if g == base_good { 1.0 } else { mrs_to_base(&payer.beta, &payer.e, g, base_good, min_qty) };
+    cost.fixed + cost.proportional * received.max(0.0) * price
+}
 
-    j.e[a] = (j.e[a] - cand.delta_a_i).max(min_qty);
-    j.e[b] = (j.e[b] - cand.delta_b_i).max(min_qty);
+/// `apply_trade`, then charge both sides their `trade_cost`: out of their base-good holdings, or
+/// (`CostSettlement::Burned`) out of the good they receive. Returns the costs of i and j.
+pub fn apply_trade_with_cost(
+    i: &mut Agent,
+    j: &mut Agent,
+    cand: &TradeCandidate,
+    cost: &TransactionCost,
+    base_good: usize,
+    min_qty: f64,
+) -> Result<(f64, f64), RdxError> {
+    let (a, b) = (cand.good_a.index(), cand.good_b.index());
+    let n = i.e.len().min(j.e.len());
+    if base_good >= n {
+        return Err(RdxError::GoodOutOfRange { index: base_good, len: n });
+    }
+    // i receives the leg it gains, j the other one
+    let (recv_i, qty_i) = if cand.delta_a_i > 0.0 { (a, cand.delta_a_i) } else { (b, cand.delta_b_i) };
+    let (recv_j, qty_j) = if cand.delta_a_i > 0.0 { (b, -cand.delta_b_i) } else { (a, -cand.delta_a_i) };
+    let cost_i = trade_cost(cost, i, recv_i, qty_i, base_good, min_qty);
+    let cost_j = trade_cost(cost, j, recv_j, qty_j, base_good, min_qty);
+    let price_i = if recv_i == base_good { 1.0 } else { mrs_to_base(&i.beta, &i.e, recv_i, base_good, min_qty) };
+    let price_j = if recv_j == base_good { 1.0 } else { mrs_to_base(&j.beta, &j.e, recv_j, base_good, min_qty) };
+    apply_trade(i, j, cand, min_qty)?;
+    for (agent, c, g, price) in [(i, cost_i, recv_i, price_i), (j, cost_j, recv_j, price_j)] {
+        match cost.settlement {
+            CostSettlement::BaseGood => {
+                agent.set_holding(base_good, (agent.e[base_good] - c).max(min_qty), min_qty)
+            }
+            CostSettlement::Burned => agent.set_holding(g, (agent.e[g] - c / price.max(1e-18)).max(min_qty), min_qty),
+        }
+    }
+    Ok((cost_i, cost_j))
+}
+
+/// Fraction of `cand` that can execute without creating goods once each side also pays its
+/// `trade_cost` and its `tax` (what `policy::trade_tax` charges i and j on the whole of `cand`):
+/// `conserving_fraction`, shrunk further until every holding a charge is paid from stays at or
+/// above `min_qty`. Proportional charges shrink with the trade and fixed costs do not, so this is
+/// 0 when a side cannot pay its fixed cost at all.
+pub fn charged_fraction(
+    i: &Agent,
+    j: &Agent,
+    cand: &TradeCandidate,
+    cost: Option<&TransactionCost>,
+    tax: (f64, f64),
+    base_good: usize,
+    min_qty: f64,
+) -> f64 {
+    let (a, b) = (cand.good_a.index(), cand.good_b.index());
+    if base_good >= i.e.len().min(j.e.len()) {
+        return 0.0;
+    }
+    let mut t = conserving_fraction(i, j, cand, min_qty);
+    let (recv_i, qty_i) = if cand.delta_a_i > 0.0 { (a, cand.delta_a_i) } else { (b, cand.delta_b_i) };
+    let (recv_j, qty_j) = if cand.delta_a_i > 0.0 { (b, -cand.delta_b_i) } else { (a, -cand.delta_a_i) };
+    for (agent, sign, recv, qty, tax) in [(i, 1.0, recv_i, qty_i, tax.0), (j, -1.0, recv_j, qty_j, tax.1)] {
+        let price = if recv == base_good { 1.0 } else { mrs_to_base(&agent.beta, &agent.e, recv, base_good, min_qty) };
+        let (paid_in, fixed, proportional) = match cost.map(|c| (c, c.settlement)) {
+            Some((c, CostSettlement::BaseGood)) => (base_good, c.fixed, c.proportional * qty.max(0.0) * price),
+            Some((c, CostSettlement::Burned)) => (recv, c.fixed / price.max(1e-18), c.proportional * qty.max(0.0)),
+            None => (base_good, 0.0, 0.0),
+        };
+        // a charged holding ends at `x + t · (traded - charged) - fixed`
+        let goods = if paid_in == base_good { vec![base_good] } else { vec![paid_in, base_good] };
+        for g in goods {
+            let fixed = if g == paid_in { fixed } else { 0.0 };
+            let charged = if g == paid_in { proportional } else { 0.0 } + if g == base_good { tax } else { 0.0 };
+            if fixed <= 0.0 && charged <= 0.0 { continue; }
+            let traded = sign * if g == a { cand.delta_a_i } else if g == b { cand.delta_b_i } else { 0.0 };
+            let room = agent.e[g] - fixed - min_qty;
+            if room < 0.0 {
+                return 0.0;
+            }
+            if traded - charged < 0.0 {
+                t = t.min(room / (charged - traded));
+            }
+        }
+    }
+    t.max(0.0)
+}
+
+/// `apply_trade_with_cost` that conserves what it does not charge: the trade is shrunk by
+/// `charged_fraction` so that neither the trade nor the costs need the floor, and no holding
+/// is raised to `min_qty`. Returns the fraction executed and the costs of i and j.
+pub fn apply_trade_with_cost_conserving(
+    i: &mut Agent,
+    j: &mut Agent,
+    cand: &TradeCandidate,
+    cost: &TransactionCost,
+    base_good: usize,
+    min_qty: f64,
+) -> Result<(f64, f64, f64), RdxError> {
+    let n = i.e.len().min(j.e.len());
+    if let Some(g) = [cand.good_a.index(), cand.good_b.index(), base_good].into_iter().find(|&g| g >= n) {
+        return Err(RdxError::GoodOutOfRange { index: g, len: n });
+    }
+    let t = charged_fraction(i, j, cand, Some(cost), (0.0, 0.0), base_good, min_qty);
+    let shrunk = TradeCandidate { delta_a_i: t * cand.delta_a_i, delta_b_i: t * cand.delta_b_i, ..cand.clone() };
+    let (a, b) = (cand.good_a.index(), cand.good_b.index());
+    let (recv_i, qty_i) = if shrunk.delta_a_i > 0.0 { (a, shrunk.delta_a_i) } else { (b, shrunk.delta_b_i) };
+    let (recv_j, qty_j) = if shrunk.delta_a_i > 0.0 { (b, -shrunk.delta_b_i) } else { (a, -shrunk.delta_a_i) };
+    let cost_i = trade_cost(cost, i, recv_i, qty_i, base_good, min_qty);
+    let cost_j = trade_cost(cost, j, recv_j, qty_j, base_good, min_qty);
+    let price_i = if recv_i == base_good { 1.0 } else { mrs_to_base(&i.beta, &i.e, recv_i, base_good, min_qty) };
+    let price_j = if recv_j == base_good { 1.0 } else { mrs_to_base(&j.beta, &j.e, recv_j, base_good, min_qty) };
+    apply_trade_conserving(i, j, &shrunk, min_qty)?;
+    for (agent, c, g, price) in [(i, cost_i, recv_i, price_i), (j, cost_j, recv_j, price_j)] {
+        let (paid_in, amount) = match cost.settlement {
+            CostSettlement::BaseGood => (base_good, c),
+            CostSettlement::Burned => (g, c / price.max(1e-18)),
+        };
+        agent.set_holding(paid_in, agent.e[paid_in] - amount, min_qty);
+    }
+    Ok((t, cost_i, cost_j))
 }
 
 /// Convenience: build default oracle
 pub fn default_oracle() -> CobbDouglasWalrasOracle {
     CobbDouglasWalrasOracle
 }
+
+/// The engine's exchange oracle for `cfg`, after checking the parameters it is driven with:
+/// `min_qty` (the holdings floor) must be finite and positive and `oracle_bisect_iters` at least
+/// 1, since the oracle would otherwise quietly price every dyad at 1. Under
+/// `SimConfig::oracle_cache` it is a `CachedWalrasOracle`, else with a positive
+/// `SimConfig::oracle_tol` a `TolerantWalrasOracle`.
+pub fn build_oracle(cfg: &SimConfig) -> Result<Box<dyn ParetoOracle>, RdxError> {
+    if !(cfg.min_qty > 0.0 && cfg.min_qty.is_finite()) {
+        return Err(RdxError::InvalidConfig(format!("min_qty must be finite and > 0, got {}", cfg.min_qty)));
+    }
+    if cfg.oracle_bisect_iters == 0 {
+        return Err(RdxError::InvalidConfig("oracle_bisect_iters must be at least 1".to_string()));
+    }
+    if !(cfg.oracle_tol >= 0.0 && cfg.oracle_tol.is_finite()) {
+        return Err(RdxError::InvalidConfig(format!("oracle_tol must be finite and >= 0, got {}", cfg.oracle_tol)));
+    }
+    if let Some(c) = &cfg.oracle_cache {
+        if c.capacity == 0 {
+            return Err(RdxError::InvalidConfig("oracle_cache.capacity must be at least 1".to_string()));
+        }
+        if !(c.resolution > 0.0 && c.resolution <= 0.5) {
+            return Err(RdxError::InvalidConfig(format!(
+                "oracle_cache.resolution must lie in (0, 0.5], got {}",
+                c.resolution
+            )));
+        }
+    }
+    #[cfg(feature = "oracle-cache")]
+    if let Some(c) = &cfg.oracle_cache {
+        let cached = crate::pareto_oracle::CachedWalrasOracle::new(c.capacity, c.resolution);
+        return Ok(Box::new(cached.with_tolerance(cfg.oracle_tol)));
+    }
+    #[cfg(not(feature = "oracle-cache"))]
+    if cfg.oracle_cache.is_some() {
+        return Err(RdxError::InvalidConfig("oracle_cache needs the oracle-cache feature".to_string()));
+    }
+    if cfg.oracle_tol > 0.0 {
+        return Ok(Box::new(TolerantWalrasOracle { tol: cfg.oracle_tol }));
+    }
+    Ok(Box::new(default_oracle()))
+}
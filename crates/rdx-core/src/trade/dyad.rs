@@ -0,0 +1,211 @@
+//! Dyadic exchange primitives of `trade`: the candidate record, local utility shapes, MRS and
+//! the evaluation of one good pair from the two sides' exponents and holdings. These need no
+//! `Agent` or config and build without `std`.
+
+use crate::ids::GoodId;
+use crate::math::{exp, ln, Scalar};
+use crate::pareto_oracle::ParetoOracle;
+use crate::preferences::{alpha_from_beta, cd_utility};
+
+#[derive(Clone, Debug, Default)]
+pub struct TradeCandidate {
+    pub good_a: GoodId,
+    pub good_b: GoodId,
+    pub q_ab: f64,
+    pub delta_a_i: f64,
+    pub delta_b_i: f64,
+    pub delta_u_i: f64,
+    pub delta_u_j: f64,
+    /// Local shape of i's log-utility along (delta_a_i, delta_b_i).
+    pub shape_i: DirectionalShape,
+    /// Local shape of j's log-utility along (-delta_a_i, -delta_b_i).
+    pub shape_j: DirectionalShape,
+    /// Accepted for resale value although one side loses direct utility
+    /// (see `best_speculative_trade`).
+    pub speculative: bool,
+}
+
+impl TradeCandidate {
+    /// Fraction of the proposed trade to execute, from both agents' quadratic models: the
+    /// smaller of their optimal fractions, clamped to `[min_frac, max_frac]`. Near indifference
+    /// (small slope, strong curvature) this shrinks; when gains are robust it grows to `max_frac`.
+    pub fn adaptive_step_frac(&self, min_frac: f64, max_frac: f64) -> f64 {
+        let t = self.shape_i.optimal_fraction().min(self.shape_j.optimal_fraction());
+        t.max(min_frac).min(max_frac)
+    }
+
+    /// The same trade seen from the other side: `i` and `j` swap roles.
+    pub fn reversed(self) -> TradeCandidate {
+        TradeCandidate {
+            delta_a_i: -self.delta_a_i,
+            delta_b_i: -self.delta_b_i,
+            delta_u_i: self.delta_u_j,
+            delta_u_j: self.delta_u_i,
+            shape_i: self.shape_j,
+            shape_j: self.shape_i,
+            ..self
+        }
+    }
+}
+
+/// Slope and curvature of ln u(x + t·d) at t = 0, for a trade direction d over goods (A,B).
+///
+/// For Cobb–Douglas, slope = Σ β_k d_k / x_k and curvature = -Σ β_k d_k² / x_k² (always
+/// `<= 0`), so ln u(t) ≈ ln u(0) + slope·t + ½·curvature·t².
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DirectionalShape {
+    pub slope: f64,
+    pub curvature: f64,
+}
+
+impl DirectionalShape {
+    /// Maximizer `slope / -curvature` of the quadratic model: 0 when the direction is not
+    /// improving, unbounded when the model has no curvature.
+    pub fn optimal_fraction(&self) -> f64 {
+        if self.slope <= 0.0 {
+            0.0
+        } else if self.curvature >= 0.0 {
+            f64::INFINITY
+        } else {
+            self.slope / -self.curvature
+        }
+    }
+}
+
+/// `DirectionalShape` of an agent with exponents `beta` at bundle `x`, moving `da` of good `a`
+/// and `db` of good `b`.
+pub fn directional_shape(beta: &[f64], x: &[f64], a: usize, b: usize, da: f64, db: f64, min_qty: f64) -> DirectionalShape {
+    let mut s = DirectionalShape::default();
+    for (g, d) in [(a, da), (b, db)] {
+        let w = beta.get(g).copied().unwrap_or(0.0);
+        let xg = x.get(g).copied().unwrap_or(0.0).max(min_qty);
+        s.slope += w * d / xg;
+        s.curvature -= w * d * d / (xg * xg);
+    }
+    s
+}
+
+/// Compute a Cobb–Douglas marginal rate of substitution (price ratio) for good k vs base:
+/// MRS_{k,base} = (beta_k/beta_base) * (x_base/x_k).
+///
+/// Missing entries are treated as zero (weights) or `min_qty` (quantities).
+pub fn mrs_to_base(beta: &[f64], x: &[f64], k: usize, base: usize, min_qty: f64) -> f64 {
+    mrs_to_base_s(beta, x, k, base, min_qty)
+}
+
+/// `mrs_to_base` in any `Scalar`.
+pub fn mrs_to_base_s<S: Scalar>(beta: &[S], x: &[S], k: usize, base: usize, min_qty: S) -> S {
+    let bk = beta.get(k).copied().unwrap_or(S::ZERO).max(S::ZERO);
+    let bb = beta.get(base).copied().unwrap_or(S::ZERO).max(S::from_f64(1e-18));
+    let xb = x.get(base).copied().unwrap_or(S::ZERO).max(min_qty);
+    let xk = x.get(k).copied().unwrap_or(S::ZERO).max(min_qty);
+    (bk / bb) * (xb / xk)
+}
+
+/// `evaluate_pairwise_trade` from the two sides' exponents `beta` and holdings `e` alone, for
+/// peers holding preference payloads rather than `Agent`s: dyadic alphas come from `beta`
+/// (`alpha_from_beta`) and price expectations play no part. Gives the same candidate as
+/// `evaluate_pairwise_trade` for agents without `alpha_to_base` or price memory.
+#[allow(clippy::too_many_arguments)]
+pub fn evaluate_exchange(
+    beta_i: &[f64],
+    e_i: &[f64],
+    beta_j: &[f64],
+    e_j: &[f64],
+    good_a: usize,
+    good_b: usize,
+    min_qty: f64,
+    oracle_iters: usize,
+    oracle: &dyn ParetoOracle,
+) -> Option<TradeCandidate> {
+    if good_a == good_b { return None; }
+    if good_a >= e_i.len() || good_b >= e_i.len() { return None; }
+    if e_i.len() != e_j.len() { return None; }
+
+    let min_alpha = 1e-6;
+    let alphas = (
+        alpha_from_beta(beta_i, good_a, good_b, min_alpha),
+        alpha_from_beta(beta_j, good_a, good_b, min_alpha),
+    );
+    let pre = (cd_utility(beta_i, e_i, min_qty), cd_utility(beta_j, e_j, min_qty));
+    exchange_candidate((beta_i, e_i), (beta_j, e_j), good_a, good_b, alphas, pre, min_qty, oracle_iters, oracle)
+}
+
+/// Change of `ln cd_utility(beta, x)` when the holdings of goods `a` and `b` become `a_post` and
+/// `b_post`: Σ β_k ln x_k differs only in those two terms, so the bundle is neither copied nor
+/// re-evaluated.
+pub(crate) fn log_utility_change(
+    beta: &[f64],
+    x: &[f64],
+    (a, a_post): (usize, f64),
+    (b, b_post): (usize, f64),
+    min_qty: f64,
+) -> f64 {
+    let term = |g: usize, post: f64| {
+        let w = beta.get(g).copied().unwrap_or(0.0);
+        w * (ln(post.max(min_qty)) - ln(x[g].max(min_qty)))
+    };
+    term(a, a_post) + term(b, b_post)
+}
+
+/// The oracle's exchange of goods A and B at dyadic alphas `(alpha_i, alpha_j)`, scored on the
+/// full bundles against pre-trade utilities `pre` (which must be those of `e_i` and `e_j`, the
+/// post-trade ones are derived from them in O(1)); `None` unless both sides strictly gain. Goods
+/// must be in range.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn exchange_candidate(
+    (beta_i, e_i): (&[f64], &[f64]),
+    (beta_j, e_j): (&[f64], &[f64]),
+    good_a: usize,
+    good_b: usize,
+    (alpha_i, alpha_j): (f64, f64),
+    (ui0, uj0): (f64, f64),
+    min_qty: f64,
+    oracle_iters: usize,
+    oracle: &dyn ParetoOracle,
+) -> Option<TradeCandidate> {
+    // Extract quantities (only A,B change; other goods fixed)
+    let ai = e_i[good_a];
+    let bi = e_i[good_b];
+    let aj = e_j[good_a];
+    let bj = e_j[good_b];
+
+    let ex = oracle.solve_two_good_exchange(alpha_i, ai, bi, alpha_j, aj, bj, min_qty, oracle_iters);
+
+    // Post-trade full-bundle utilities: only the A and B terms of the log-utility change
+    let ui1 = ui0 * exp(log_utility_change(beta_i, e_i, (good_a, ex.ai_post), (good_b, ex.bi_post), min_qty));
+    let uj1 = uj0 * exp(log_utility_change(beta_j, e_j, (good_a, ex.aj_post), (good_b, ex.bj_post), min_qty));
+
+    let delta_u_i = ui1 - ui0;
+    let delta_u_j = uj1 - uj0;
+
+    if delta_u_i > 0.0 && delta_u_j > 0.0 {
+        let (da, db) = (ex.ai_post - ai, ex.bi_post - bi);
+        Some(TradeCandidate {
+            good_a: GoodId(good_a),
+            good_b: GoodId(good_b),
+            q_ab: ex.q_ab,
+            delta_a_i: da,
+            delta_b_i: db,
+            delta_u_i,
+            delta_u_j,
+            shape_i: directional_shape(beta_i, e_i, good_a, good_b, da, db, min_qty),
+            shape_j: directional_shape(beta_j, e_j, good_a, good_b, -da, -db, min_qty),
+            speculative: false,
+        })
+    } else {
+        None
+    }
+}
+
+/// The non-base good of a trade between `good_a` and `good_b` at `q_ab = p_A / p_B`, and its
+/// price in base-good units; `None` unless exactly one side is the base good.
+pub fn base_price(good_a: usize, good_b: usize, q_ab: f64, base_good: usize) -> Option<(usize, f64)> {
+    if good_b == base_good && good_a != base_good {
+        Some((good_a, q_ab))
+    } else if good_a == base_good && good_b != base_good && q_ab > 0.0 {
+        Some((good_b, 1.0 / q_ab))
+    } else {
+        None
+    }
+}
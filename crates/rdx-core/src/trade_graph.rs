@@ -0,0 +1,101 @@
+//! Who-traded-what-with-whom graph for downstream network analysis.
+//!
+//! Nodes are agents; every executed trade contributes two directed edges, one per good, pointing
+//! from the agent that gave the good to the one that received it. Edges are aggregated per
+//! (from, to, good), so two agents trading several goods are joined by parallel edges.
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use crate::ids::{AgentIdx, GoodId};
+use crate::model::TradeEvent;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TradeEdge {
+    pub from: AgentIdx,
+    pub to: AgentIdx,
+    pub good: GoodId,
+    /// Total quantity of `good` that flowed along this edge.
+    pub quantity: f64,
+    pub trades: usize,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct TradeGraph {
+    pub num_agents: usize,
+    /// Sorted by (from, to, good).
+    pub edges: Vec<TradeEdge>,
+}
+
+impl TradeGraph {
+    /// Aggregate `events` over a population of `num_agents`.
+    pub fn from_events(num_agents: usize, events: &[TradeEvent]) -> Self {
+        let mut agg: BTreeMap<(AgentIdx, AgentIdx, GoodId), (f64, usize)> = BTreeMap::new();
+        for ev in events.iter() {
+            for (good, delta_i) in [(ev.good_a, ev.delta_a_i), (ev.good_b, ev.delta_b_i)] {
+                if delta_i == 0.0 { continue; }
+                let (from, to) = if delta_i > 0.0 { (ev.j, ev.i) } else { (ev.i, ev.j) };
+                let e = agg.entry((from, to, good)).or_insert((0.0, 0));
+                e.0 += delta_i.abs();
+                e.1 += 1;
+            }
+        }
+        let edges = agg.into_iter()
+            .map(|((from, to, good), (quantity, trades))| TradeEdge { from, to, good, quantity, trades })
+            .collect();
+        TradeGraph { num_agents, edges }
+    }
+
+    /// Graphviz DOT. `goods` names are used as edge labels when available.
+    pub fn to_dot(&self, goods: &[String]) -> String {
+        let mut out = String::from("digraph trades {\n");
+        for a in 0..self.num_agents {
+            let _ = writeln!(out, "  {a};");
+        }
+        for e in self.edges.iter() {
+            let _ = writeln!(
+                out,
+                "  {} -> {} [label=\"{}\", good={}, weight={}, trades={}];",
+                e.from, e.to, dot_escape(&good_name(goods, e.good)), e.good, e.quantity, e.trades
+            );
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    /// GraphML with `good`, `good_name`, `quantity` and `trades` edge attributes.
+    pub fn to_graphml(&self, goods: &[String]) -> String {
+        let mut out = String::from(concat!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+            "<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n",
+            "  <key id=\"good\" for=\"edge\" attr.name=\"good\" attr.type=\"int\"/>\n",
+            "  <key id=\"good_name\" for=\"edge\" attr.name=\"good_name\" attr.type=\"string\"/>\n",
+            "  <key id=\"quantity\" for=\"edge\" attr.name=\"quantity\" attr.type=\"double\"/>\n",
+            "  <key id=\"trades\" for=\"edge\" attr.name=\"trades\" attr.type=\"int\"/>\n",
+            "  <graph id=\"trades\" edgedefault=\"directed\">\n",
+        ));
+        for a in 0..self.num_agents {
+            let _ = writeln!(out, "    <node id=\"n{a}\"/>");
+        }
+        for (k, e) in self.edges.iter().enumerate() {
+            let _ = writeln!(out, "    <edge id=\"e{k}\" source=\"n{}\" target=\"n{}\">", e.from, e.to);
+            let _ = writeln!(out, "      <data key=\"good\">{}</data>", e.good);
+            let _ = writeln!(out, "      <data key=\"good_name\">{}</data>", xml_escape(&good_name(goods, e.good)));
+            let _ = writeln!(out, "      <data key=\"quantity\">{}</data>", e.quantity);
+            let _ = writeln!(out, "      <data key=\"trades\">{}</data>", e.trades);
+            out.push_str("    </edge>\n");
+        }
+        out.push_str("  </graph>\n</graphml>\n");
+        out
+    }
+}
+
+fn good_name(goods: &[String], g: GoodId) -> String {
+    goods.get(g.index()).cloned().unwrap_or_else(|| format!("good {g}"))
+}
+
+fn dot_escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
@@ -0,0 +1,83 @@
+//! Per-agent trajectories: the holdings and utility of selected agents after every round, which
+//! the population means in `RoundMetrics` average away.
+//!
+//! `TrajectoryRecorder` is an `Observer`; register a shared handle (`Arc<Mutex<_>>`) with
+//! `Engine::add_observer` and read `points` after the run. A random sample is drawn from the
+//! agents present at the end of the first recorded round and keeps following them (agents that
+//! enter later are not added); `AgentSelection::All` records everyone present each round.
+
+use std::collections::BTreeSet;
+use rand::prelude::*;
+use serde::{Serialize, Deserialize};
+use crate::preferences::cd_utility;
+use crate::sim::{Observer, RoundMetrics, SimState};
+
+/// Which agents to follow, by `Agent::id`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum AgentSelection {
+    All,
+    /// `count` agents drawn with `seed`.
+    Sample { count: usize, seed: u64 },
+    Ids(Vec<u64>),
+}
+
+/// One agent after one round.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TrajectoryPoint {
+    pub round: usize,
+    pub id: u64,
+    /// Cobb–Douglas utility of `e`.
+    pub utility: f64,
+    /// Holdings of every good present in the round.
+    pub e: Vec<f64>,
+}
+
+#[derive(Clone, Debug)]
+pub struct TrajectoryRecorder {
+    selection: AgentSelection,
+    min_qty: f64,
+    /// Ids followed, once resolved (always `None` for `All`).
+    chosen: Option<BTreeSet<u64>>,
+    points: Vec<TrajectoryPoint>,
+}
+
+impl TrajectoryRecorder {
+    /// Follow `selection`; `min_qty` floors holdings in the utility as the engine does.
+    pub fn new(selection: AgentSelection, min_qty: f64) -> Self {
+        let chosen = match &selection {
+            AgentSelection::Ids(ids) => Some(ids.iter().copied().collect()),
+            _ => None,
+        };
+        TrajectoryRecorder { selection, min_qty, chosen, points: Vec::new() }
+    }
+
+    /// Points recorded so far, by round, then in population order.
+    pub fn points(&self) -> &[TrajectoryPoint] { &self.points }
+
+    pub fn into_points(self) -> Vec<TrajectoryPoint> { self.points }
+
+    /// Ids of the agents followed, once known (after the first round for a sample, never
+    /// for `All`).
+    pub fn followed(&self) -> Option<&BTreeSet<u64>> { self.chosen.as_ref() }
+}
+
+impl Observer for TrajectoryRecorder {
+    fn on_round_end(&mut self, metrics: &RoundMetrics, state: &SimState) {
+        if let (AgentSelection::Sample { count, seed }, None) = (&self.selection, &self.chosen) {
+            let mut rng = StdRng::seed_from_u64(*seed);
+            let ids = state.agents.choose_multiple(&mut rng, *count).map(|a| a.id).collect();
+            self.chosen = Some(ids);
+        }
+        for a in state.agents.iter() {
+            if self.chosen.as_ref().is_some_and(|ids| !ids.contains(&a.id)) {
+                continue;
+            }
+            self.points.push(TrajectoryPoint {
+                round: metrics.round,
+                id: a.id,
+                utility: cd_utility(&a.beta, &a.e, self.min_qty),
+                e: a.e.clone(),
+            });
+        }
+    }
+}
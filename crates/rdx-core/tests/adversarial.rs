@@ -0,0 +1,116 @@
+//! Adversarial inputs must surface as `RdxError`, never as panics.
+mod common;
+
+use rdx_core::error::{Error, RdxError};
+use rdx_core::ids::GoodId;
+use rdx_core::model::Agent;
+use rdx_core::pareto_oracle::{CobbDouglasWalrasOracle, PRICE_BRACKET};
+use rdx_core::preferences::{alpha_from_beta, beta_from_alpha_to_base};
+use rdx_core::sim::{init_agents, mean_endowments, run, Engine, SimState};
+use rdx_core::trade::{apply_trade, best_trade_over_all_pairs_pruned, build_oracle, default_oracle, TradeCandidate};
+
+#[test]
+fn base_good_out_of_range() {
+    let mut cfg = common::small_config();
+    cfg.base_good = 99;
+    assert!(matches!(init_agents(&cfg), Err(RdxError::GoodOutOfRange { index: 99, .. })));
+}
+
+#[test]
+fn goods_quantity_mismatch() {
+    let mut cfg = common::small_config();
+    cfg.base_goods_quantity = 3;
+    assert!(matches!(init_agents(&cfg), Err(RdxError::DimensionMismatch { .. })));
+}
+
+#[test]
+fn too_few_goods() {
+    let mut cfg = common::small_config();
+    cfg.base_goods = vec!["only".into()];
+    cfg.base_goods_quantity = 1;
+    assert!(init_agents(&cfg).is_err());
+}
+
+#[test]
+fn empty_or_nan_alpha_range() {
+    let mut cfg = common::small_config();
+    cfg.alpha_low = 0.5;
+    cfg.alpha_high = 0.5;
+    assert!(matches!(init_agents(&cfg), Err(RdxError::InvalidConfig(_))));
+
+    cfg.alpha_low = f64::NAN;
+    assert!(matches!(init_agents(&cfg), Err(RdxError::InvalidConfig(_))));
+}
+
+#[test]
+fn tiny_populations() {
+    for n in [0, 1] {
+        let mut cfg = common::small_config();
+        cfg.num_agents = n;
+        assert!(matches!(Engine::new(cfg), Err(RdxError::PopulationTooSmall(k)) if k == n));
+    }
+
+    let cfg = common::small_config();
+    let mut empty = SimState::default();
+    assert!(run(&cfg, &mut empty).is_err());
+    assert!(mean_endowments(&empty).is_empty());
+}
+
+#[test]
+fn ragged_agents_rejected() {
+    let cfg = common::small_config();
+    let mut state = init_agents(&cfg).unwrap();
+    state.agents[3].e.pop();
+    assert!(matches!(Engine::with_state(cfg, state), Err(RdxError::DimensionMismatch { .. })));
+}
+
+#[test]
+fn out_of_range_indices_in_primitives() {
+    assert!(beta_from_alpha_to_base(&[0.3, 0.6], 5, 1e-6).is_err());
+    let a = alpha_from_beta(&[0.5, 0.5], 0, 9, 1e-6);
+    assert!(a > 0.0 && a < 1.0);
+
+    let mut i = Agent { e: vec![1.0, 2.0], beta: vec![0.5], alpha_to_base: vec![], reaction_rules: vec![], ..Default::default() };
+    let mut j = Agent { e: vec![2.0, 1.0, 3.0], beta: vec![], alpha_to_base: vec![], reaction_rules: vec![], ..Default::default() };
+    let cand = TradeCandidate {
+        good_a: GoodId(7), good_b: GoodId(0), q_ab: 1.0,
+        delta_a_i: 0.1, delta_b_i: -0.1, ..Default::default()
+    };
+    assert!(apply_trade(&mut i, &mut j, &cand, 1e-9).is_err());
+    assert_eq!(i.e, vec![1.0, 2.0]);
+
+    // Mismatched dimensions and a base index past the end must not panic.
+    let oracle = default_oracle();
+    let _ = best_trade_over_all_pairs_pruned(&i, &j, 10, 4, 1e-9, 40, &oracle);
+    let _ = best_trade_over_all_pairs_pruned(&i, &i.clone(), 10, 4, 1e-9, 40, &oracle);
+}
+
+#[test]
+fn oracle_parameters_validated() {
+    let mut cfg = common::small_config();
+    assert!(build_oracle(&cfg).is_ok());
+    cfg.oracle_bisect_iters = 0;
+    assert!(matches!(build_oracle(&cfg), Err(Error::InvalidConfig(_))));
+    assert!(matches!(init_agents(&cfg), Err(Error::InvalidConfig(_))));
+
+    let mut cfg = common::small_config();
+    cfg.min_qty = 0.0;
+    assert!(matches!(Engine::new(cfg), Err(RdxError::InvalidConfig(_))));
+}
+
+#[test]
+fn checked_oracle_reports_degenerate_dyads() {
+    let oracle = CobbDouglasWalrasOracle;
+    let ex = oracle.try_solve_two_good_exchange(0.7, 2.0, 1.0, 0.2, 1.5, 3.0, 1e-9, 80).expect("bracketed");
+    assert!(ex.q_ab > PRICE_BRACKET.0 && ex.q_ab < PRICE_BRACKET.1);
+
+    let nan = oracle.try_solve_two_good_exchange(f64::NAN, 2.0, 1.0, 0.2, 1.5, 3.0, 1e-9, 80);
+    assert!(matches!(nan, Err(RdxError::DegeneratePreferences(_))));
+    let wide = oracle.try_solve_two_good_exchange(1.2, 2.0, 1.0, 0.2, 1.5, 3.0, 1e-9, 80);
+    assert!(matches!(wide, Err(RdxError::DegeneratePreferences(_))));
+    // nobody values A: the bisection would quietly settle on the lower bracket end
+    let unvalued = oracle.try_solve_two_good_exchange(0.0, 2.0, 1.0, 0.0, 1.5, 3.0, 1e-9, 80);
+    assert!(matches!(unvalued, Err(RdxError::UnbracketedPrice { .. })));
+
+    assert!(matches!(beta_from_alpha_to_base(&[0.5, f64::NAN], 0, 1e-6), Err(RdxError::DegeneratePreferences(_))));
+}
@@ -0,0 +1,71 @@
+mod common;
+
+use rdx_core::error::RdxError;
+use rdx_core::model::{AgentGroupSpec, EndowmentSpec};
+use rdx_core::sim::{agent_group_ranges, init_agents, run};
+
+fn group(name: &str, size: usize, endowment: EndowmentSpec) -> AgentGroupSpec {
+    AgentGroupSpec {
+        name: name.to_string(),
+        size,
+        endowment: Some(endowment),
+        alpha_low: None,
+        alpha_high: None,
+        reaction_rules: None,
+    }
+}
+
+#[test]
+fn groups_get_their_own_endowments_and_preferences() {
+    let mut cfg = common::small_config();
+    let mut consumers = group("consumers", 16, EndowmentSpec::Uniform { low: 0.1, high: 0.2 });
+    consumers.alpha_low = Some(0.8);
+    consumers.alpha_high = Some(0.9);
+    cfg.agent_groups = vec![group("producers", 8, EndowmentSpec::Uniform { low: 5.0, high: 6.0 }), consumers];
+
+    let ranges = agent_group_ranges(&cfg);
+    assert_eq!(ranges, vec![0..8, 8..24]);
+    let mut state = init_agents(&cfg).expect("init");
+    assert_eq!(state.agents.len(), 24);
+    let scale = cfg.initial_endowment_scale;
+    for a in &state.agents[ranges[0].clone()] {
+        assert!(a.e.iter().all(|&x| x >= 5.0 * scale));
+    }
+    for a in &state.agents[ranges[1].clone()] {
+        assert!(a.e.iter().all(|&x| x < 0.2 * scale));
+        let non_base = a.alpha_to_base.iter().enumerate().filter(|&(k, _)| k != cfg.base_good);
+        assert!(non_base.map(|(_, &al)| al).all(|al| (0.8..0.9).contains(&al)));
+    }
+    run(&cfg, &mut state).expect("run");
+    assert!(!state.events.is_empty());
+}
+
+#[test]
+fn group_sizes_must_cover_the_population() {
+    let mut cfg = common::small_config();
+    assert_eq!(agent_group_ranges(&cfg), vec![0..cfg.num_agents]);
+    cfg.agent_groups = vec![group("a", 10, EndowmentSpec::default())];
+    assert!(matches!(init_agents(&cfg), Err(RdxError::DimensionMismatch { expected: 24, found: 10 })));
+}
+
+#[test]
+fn agents_carry_ids_and_group_labels_into_events() {
+    let mut cfg = common::small_config();
+    cfg.agent_groups = vec![
+        group("producers", 4, EndowmentSpec::default()),
+        group("", 20, EndowmentSpec::default()),
+    ];
+    let mut state = init_agents(&cfg).expect("init");
+    for (k, a) in state.agents.iter().enumerate() {
+        assert_eq!(a.id, k as u64);
+        assert_eq!(a.labels.get("group").map(String::as_str), if k < 4 { Some("producers") } else { None });
+    }
+    // ids survive reordering the population
+    state.agents.reverse();
+    run(&cfg, &mut state).expect("run");
+    for ev in state.events.iter() {
+        assert_eq!(state.agents[ev.i.index()].id, ev.id_i);
+        assert_eq!(state.agents[ev.j.index()].id, ev.id_j);
+    }
+    assert!(state.events.iter().any(|ev| ev.id_i != ev.i.index() as u64));
+}
@@ -0,0 +1,50 @@
+use rdx_core::error::RdxError;
+use rdx_core::math::solve_linear;
+use rdx_core::preferences::{alpha_from_beta, AlphaMatrix};
+
+#[test]
+fn consistent_comparisons_recover_beta() {
+    let beta = [0.1, 0.2, 0.3, 0.4];
+    let mut m = AlphaMatrix::new(4);
+    // a spanning set of comparisons, none against good 0 except one
+    for (a, b) in [(0, 1), (1, 2), (2, 3), (1, 3)] {
+        m.set(a, b, alpha_from_beta(&beta, a, b, 1e-9)).unwrap();
+    }
+    assert!((m.get(1, 2).unwrap() - 0.4).abs() < 1e-12);
+    assert!((m.get(2, 1).unwrap() - 0.6).abs() < 1e-12);
+    assert!(m.consistency_residual().unwrap() < 1e-9);
+    for (x, y) in m.nearest_beta().unwrap().iter().zip(beta.iter()) {
+        assert!((x - y).abs() < 1e-9);
+    }
+
+    let anchored = AlphaMatrix::from_alpha_to_base(&[0.5, 0.7, 0.2], 0, 1e-6).unwrap();
+    assert!(anchored.consistency_residual().unwrap() < 1e-9);
+}
+
+#[test]
+fn cycle_violations_are_measured_and_projected_away() {
+    // A beats B, B beats C, C beats A: no Cobb–Douglas profile does that
+    let mut m = AlphaMatrix::new(3);
+    m.set(0, 1, 0.8).unwrap();
+    m.set(1, 2, 0.8).unwrap();
+    m.set(2, 0, 0.8).unwrap();
+    assert!(m.consistency_residual().unwrap() > 0.5);
+    // the symmetric cycle projects onto equal weights
+    for b in m.nearest_beta().unwrap() {
+        assert!((b - 1.0 / 3.0).abs() < 1e-9);
+    }
+
+    assert!(m.set(0, 0, 0.5).is_err() && m.set(0, 3, 0.5).is_err() && m.set(0, 1, 1.0).is_err());
+    let mut split = AlphaMatrix::new(4);
+    split.set(0, 1, 0.6).unwrap();
+    split.set(2, 3, 0.6).unwrap();
+    assert!(matches!(split.nearest_beta(), Err(RdxError::DisconnectedComparisons(4))));
+}
+
+#[test]
+fn solver_handles_pivoting_and_singularity() {
+    let a = vec![vec![0.0, 2.0], vec![3.0, 1.0]];
+    let x = solve_linear(&a, &[4.0, 5.0]).unwrap();
+    assert!((x[0] - 1.0).abs() < 1e-12 && (x[1] - 2.0).abs() < 1e-12);
+    assert!(solve_linear(&[vec![1.0, 2.0], vec![2.0, 4.0]], &[1.0, 2.0]).is_none());
+}
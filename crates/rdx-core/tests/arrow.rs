@@ -0,0 +1,54 @@
+#![cfg(feature = "arrow")]
+mod common;
+
+use arrow_array::cast::AsArray;
+use arrow_array::types::{Float64Type, UInt64Type};
+use arrow_schema::DataType;
+use rdx_core::sim::{init_agents, run, SimState};
+
+fn finished() -> SimState {
+    let cfg = common::small_config();
+    let mut state = init_agents(&cfg).unwrap();
+    run(&cfg, &mut state).unwrap();
+    state
+}
+
+#[test]
+fn events_batch_has_one_row_per_event() {
+    let state = finished();
+    let batch = state.events_batch().unwrap();
+    assert_eq!(batch.num_rows(), state.events.len());
+
+    let seq = batch.column_by_name("seq").unwrap().as_primitive::<UInt64Type>();
+    let delta = batch.column_by_name("delta_a_i").unwrap().as_primitive::<Float64Type>();
+    for (k, ev) in state.events.iter().enumerate() {
+        assert_eq!(seq.value(k), ev.seq);
+        assert_eq!(delta.value(k).to_bits(), ev.delta_a_i.to_bits());
+    }
+    let pre_i = batch.column_by_name("pre_i").unwrap();
+    assert_eq!(pre_i.data_type(), &DataType::FixedSizeBinary(32));
+    assert_eq!(pre_i.as_fixed_size_binary().value(0), &state.events[0].pre_i[..]);
+    // no clock: every time is null
+    assert_eq!(batch.column_by_name("time").unwrap().null_count(), state.events.len());
+}
+
+#[test]
+fn metrics_batch_flattens_rounds() {
+    let state = finished();
+    let batch = state.metrics_batch().unwrap();
+    assert_eq!(batch.num_rows(), state.metrics.len());
+    let trades = batch.column_by_name("trades").unwrap().as_primitive::<UInt64Type>();
+    let prices = batch.column_by_name("prices").unwrap().as_list::<i32>();
+    for (k, m) in state.metrics.iter().enumerate() {
+        assert_eq!(trades.value(k), m.trades as u64);
+        assert_eq!(prices.value(k).len(), m.prices.len());
+    }
+    assert!(batch.column_by_name("gini_wealth").is_some());
+}
+
+#[test]
+fn empty_states_give_empty_batches() {
+    let state = SimState::default();
+    assert_eq!(state.events_batch().unwrap().num_rows(), 0);
+    assert_eq!(state.metrics_batch().unwrap().num_rows(), 0);
+}
@@ -32,7 +32,7 @@ fn beta_alpha_base_roundtrip() {
         }
 
         // recover beta'
-        let beta2 = beta_from_alpha_to_base(&alpha_to_base, base, 1e-6);
+        let beta2 = beta_from_alpha_to_base(&alpha_to_base, base, 1e-6).expect("base in range");
 
         // Compare up to tolerance (they should match closely after normalization)
         let err: f64 = beta.iter().zip(beta2.iter()).map(|(x,y)| (x-y).abs()).sum();
@@ -0,0 +1,61 @@
+mod common;
+
+use rdx_core::broker::{broker_trade, compare_intermediation, Quotes};
+use rdx_core::ids::GoodId;
+use rdx_core::model::{BrokerSpec, PolicySpec, Redistribution};
+use rdx_core::sim::{init_agents, run};
+use rdx_core::trade::TradeRules;
+
+#[test]
+fn brokers_trade_at_their_quotes() {
+    let quotes = Quotes { mid: vec![1.0, 1.0], spread: 0.2 };
+    let broker = common::agent(vec![5.0, 5.0], vec![0.5, 0.5]);
+    let rules = TradeRules::default();
+
+    // a customer valuing good 1 above the ask buys from the broker at the ask
+    let keen = common::agent(vec![2.0, 1.0], vec![0.2, 0.8]);
+    let cand = broker_trade(&broker, &keen, &quotes, 0, 1e-9, &rules).expect("buys");
+    assert_eq!((cand.good_a, cand.good_b), (GoodId(1), GoodId(0)));
+    assert!((cand.q_ab - 1.1).abs() < 1e-12);
+    assert!(cand.delta_a_i < 0.0 && (cand.delta_b_i + cand.q_ab * cand.delta_a_i).abs() < 1e-12);
+    assert!(cand.delta_u_j > 0.0);
+
+    // one valuing it below the bid sells at the bid
+    let tired = common::agent(vec![1.0, 2.0], vec![0.8, 0.2]);
+    let cand = broker_trade(&broker, &tired, &quotes, 0, 1e-9, &rules).expect("sells");
+    assert!((cand.q_ab - 0.9).abs() < 1e-12 && cand.delta_a_i > 0.0 && cand.delta_u_j > 0.0);
+
+    // inside the spread there is nothing to do, and an empty inventory cannot sell
+    let content = common::agent(vec![1.0, 1.0], vec![0.5, 0.5]);
+    assert!(broker_trade(&broker, &content, &quotes, 0, 1e-9, &rules).is_none());
+    let empty = common::agent(vec![5.0, 1e-9], vec![0.5, 0.5]);
+    assert!(broker_trade(&empty, &keen, &quotes, 0, 1e-9, &rules).is_none());
+}
+
+#[test]
+fn intermediation_report_compares_welfare() {
+    let mut cfg = common::small_config();
+    cfg.brokers = Some(BrokerSpec { share: 0.25, spread: 0.1 });
+    let state = {
+        let mut s = init_agents(&cfg).expect("init");
+        run(&cfg, &mut s).expect("run");
+        s
+    };
+    assert_eq!(state.agents.iter().filter(|a| a.broker).count(), 6);
+    let brokered: usize = state.metrics.iter().map(|m| m.broker_trades).sum();
+    assert!(brokered > 0);
+    for e in state.events.iter() {
+        // brokers never trade with each other
+        assert!(!(state.agents[e.i.index()].broker && state.agents[e.j.index()].broker));
+    }
+
+    let report = compare_intermediation(&cfg).expect("compare");
+    assert_eq!(report.broker_trades, brokered);
+    assert!(report.welfare_with > 0.0 && report.welfare_without > 0.0);
+
+    cfg.policy = Some(PolicySpec { tax_rate: 0.05, redistribution: Redistribution::Flat });
+    assert!(init_agents(&cfg).is_err());
+    cfg.policy = None;
+    cfg.brokers = None;
+    assert!(compare_intermediation(&cfg).is_err());
+}
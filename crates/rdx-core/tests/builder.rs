@@ -0,0 +1,55 @@
+use rdx_core::error::RdxError;
+use rdx_core::model::{Agent, ConsumptionSpec, PairingMode, SimConfig};
+use rdx_core::sim::{init_agents, run};
+
+#[test]
+fn built_configs_simulate() {
+    let cfg = SimConfig::builder()
+        .seed(7)
+        .agents(24)
+        .rounds(3)
+        .encounters_per_round(40)
+        .num_goods(5)
+        .pairing_mode(PairingMode::AllPairsPruned)
+        .with(|c| c.consumption = Some(ConsumptionSpec { rate: 0.05, income: 0.05 }))
+        .build()
+        .expect("valid");
+    assert_eq!(cfg.base_goods, vec!["base", "g1", "g2", "g3", "g4"]);
+    assert_eq!(cfg.base_goods_quantity, 5);
+    let mut state = init_agents(&cfg).expect("init");
+    run(&cfg, &mut state).expect("run");
+    assert_eq!(state.metrics.len(), 3);
+}
+
+#[test]
+fn build_reports_all_problems() {
+    let err = SimConfig::builder().base_good(4).step_cap_frac(2.0).build().unwrap_err();
+    let RdxError::InvalidConfig(msg) = err else { panic!("expected InvalidConfig, got {err:?}") };
+    assert!(msg.contains("base_good") && msg.contains("trade_step_cap_frac"), "{msg}");
+
+    // problems outside `validate` still surface from the engine checks
+    let err = SimConfig::builder().with(|c| c.price_alpha = 0.0).build();
+    assert!(matches!(err, Err(RdxError::InvalidConfig(_))));
+}
+
+#[test]
+fn agent_builder_keeps_preferences_consistent() {
+    let a = Agent::builder().id(3).label("group", "x").endowment(vec![1.0, 2.0, 3.0]).build().expect("agent");
+    assert_eq!(a.alpha_to_base, vec![0.5; 3]);
+    assert!(a.beta.iter().all(|&b| (b - 1.0 / 3.0).abs() < 1e-12));
+    assert_eq!((a.id, a.labels["group"].as_str()), (3, "x"));
+
+    let a = Agent::builder().endowment(vec![1.0; 3]).beta(vec![2.0, 1.0, 1.0]).build().expect("agent");
+    assert_eq!(a.beta, vec![0.5, 0.25, 0.25]);
+    assert!((a.alpha_to_base[1] - 1.0 / 3.0).abs() < 1e-12);
+
+    let a = Agent::builder().endowment(vec![1.0; 3]).base_good(2).alpha_to_base(vec![0.75, 0.5, 0.5]).build().unwrap();
+    assert!((a.beta[0] / a.beta[2] - 3.0).abs() < 1e-9);
+
+    let short = Agent::builder().endowment(vec![1.0; 3]).beta(vec![1.0, 1.0]).build();
+    assert!(matches!(short, Err(RdxError::DimensionMismatch { expected: 3, found: 2 })));
+    let no_base = Agent::builder().endowment(vec![1.0; 2]).beta(vec![0.0, 1.0]).build();
+    assert!(matches!(no_base, Err(RdxError::DegeneratePreferences(_))));
+    assert!(Agent::builder().endowment(vec![1.0, -1.0]).build().is_err());
+    assert!(Agent::builder().endowment(vec![1.0; 2]).base_good(2).build().is_err());
+}
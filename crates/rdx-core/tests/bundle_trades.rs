@@ -0,0 +1,55 @@
+mod common;
+
+use rdx_core::ids::GoodId;
+use rdx_core::model::{PairingMode, TransactionCost};
+use rdx_core::preferences::cd_utility;
+use rdx_core::sim::{init_agents, run};
+use rdx_core::trade::{apply_bundle_trade, evaluate_bundle_trade, TradeRules};
+
+#[test]
+fn bundle_reaches_the_dyad_equilibrium() {
+    let mut i = common::agent(vec![1.0, 3.0, 0.5, 2.0], vec![0.1, 0.2, 0.4, 0.3]);
+    let mut j = common::agent(vec![2.0, 0.5, 3.0, 1.0], vec![0.3, 0.4, 0.1, 0.2]);
+    let trade = evaluate_bundle_trade(&i, &j, 0, 3, 1e-9, &TradeRules::default()).expect("gains from trade");
+    assert_eq!(trade.goods.last(), Some(&GoodId(0)));
+    assert_eq!(trade.goods.len(), 4);
+    assert!(trade.delta_u_i > 0.0 && trade.delta_u_j > 0.0);
+    let value: f64 = trade.prices.iter().zip(trade.delta_i.iter()).map(|(p, d)| p * d).sum();
+    assert!(value.abs() < 1e-9);
+
+    // the legs against the base good add up to the bundle
+    let legs = trade.legs();
+    assert_eq!(legs.len(), 3);
+    let base_leg: f64 = legs.iter().map(|l| l.delta_b_i).sum();
+    assert!((base_leg - trade.delta_i[3]).abs() < 1e-9);
+
+    let totals: Vec<f64> = (0..4).map(|g| i.e[g] + j.e[g]).collect();
+    let (ui, uj) = (cd_utility(&i.beta, &i.e, 1e-9), cd_utility(&j.beta, &j.e, 1e-9));
+    apply_bundle_trade(&mut i, &mut j, &trade, 1e-9).unwrap();
+    for (g, t) in totals.iter().enumerate() {
+        assert!((i.e[g] + j.e[g] - t).abs() < 1e-9);
+    }
+    assert!((cd_utility(&i.beta, &i.e, 1e-9) - ui - trade.delta_u_i).abs() < 1e-9);
+    assert!((cd_utility(&j.beta, &j.e, 1e-9) - uj - trade.delta_u_j).abs() < 1e-9);
+
+    // at the equilibrium nothing is left to trade
+    if let Some(t) = evaluate_bundle_trade(&i, &j, 0, 3, 1e-9, &TradeRules::default()) {
+        assert!(t.delta_u_i + t.delta_u_j < 1e-9);
+    }
+}
+
+#[test]
+fn engine_logs_bundle_legs_against_the_base() {
+    let mut cfg = common::small_config();
+    cfg.pairing_mode = PairingMode::Bundle;
+    cfg.candidate_goods_k = 3;
+    let mut state = init_agents(&cfg).expect("init");
+    run(&cfg, &mut state).expect("run");
+    assert!(!state.events.is_empty());
+    assert!(state.events.iter().all(|e| e.good_b == GoodId(cfg.base_good)));
+    let trades: usize = state.metrics.iter().map(|m| m.trades).sum();
+    assert_eq!(trades, state.events.len());
+
+    cfg.transaction_cost = Some(TransactionCost::default());
+    assert!(init_agents(&cfg).is_err());
+}
@@ -0,0 +1,50 @@
+mod common;
+
+use rdx_core::ids::GoodId;
+use rdx_core::model::Agent;
+use rdx_core::sim::{init_agents, Engine};
+use rdx_core::trade::{scale_and_validate, TradeCandidate};
+
+/// i holds base below a floor of 1, so only the last part of the base it receives counts.
+fn floored_pair() -> (Agent, Agent, TradeCandidate) {
+    let state = init_agents(&common::small_config()).expect("init");
+    let (mut i, mut j) = (state.agents[0].clone(), state.agents[1].clone());
+    for (a, e) in [(&mut i, [0.5, 5.0]), (&mut j, [5.0, 2.0])] {
+        a.beta = vec![0.5, 0.5, 0.0, 0.0, 0.0];
+        a.e[..2].copy_from_slice(&e);
+        a.invalidate_utility();
+    }
+    let cand = TradeCandidate {
+        good_a: GoodId(1),
+        good_b: GoodId(0),
+        q_ab: 0.8,
+        delta_a_i: -1.0,
+        delta_b_i: 0.8,
+        ..Default::default()
+    };
+    (i, j, cand)
+}
+
+#[test]
+fn full_trade_gains_but_capped_trade_does_not() {
+    let (i, j, cand) = floored_pair();
+    let mut full = cand.clone();
+    assert!(scale_and_validate(&i, &j, &mut full, 1.0, 1.0));
+    assert!(full.delta_u_i > 0.0 && full.delta_u_j > 0.0);
+
+    let mut capped = cand;
+    assert!(!scale_and_validate(&i, &j, &mut capped, 0.35, 1.0));
+    assert!((capped.delta_a_i + 0.35).abs() < 1e-15 && (capped.delta_b_i - 0.28).abs() < 1e-15);
+    assert!(capped.delta_u_i < 0.0, "{}", capped.delta_u_i);
+}
+
+#[test]
+fn capped_runs_only_apply_mutual_gains() {
+    let mut engine = Engine::new(common::small_config()).unwrap();
+    engine.run_to_end();
+    let state = engine.finish();
+    assert!(!state.events.is_empty());
+    for e in state.events.iter().filter(|e| !e.speculative) {
+        assert!(e.delta_u_i > 0.0 && e.delta_u_j > 0.0, "round {}: {} {}", e.round, e.delta_u_i, e.delta_u_j);
+    }
+}
@@ -0,0 +1,107 @@
+mod common;
+
+use rdx_core::checkpoint::{CHECKPOINT_MAGIC, CHECKPOINT_VERSION};
+use rdx_core::error::RdxError;
+use rdx_core::model::{DemographySpec, ExitMode, PairingMode, SimConfig};
+use rdx_core::sim::{Engine, SimState};
+
+fn busy_config() -> SimConfig {
+    let mut cfg = common::small_config();
+    cfg.rounds = 8;
+    cfg.demography =
+        Some(DemographySpec { entry_rate: 1.5, exit_prob: 0.05, exit: ExitMode::Remove, min_population: 2 });
+    cfg.pairing_mode = PairingMode::Learning { epsilon: 0.2, learning_rate: 0.5 };
+    cfg
+}
+
+fn fingerprint(state: &SimState) -> (Vec<(usize, u64)>, Vec<Vec<u64>>) {
+    let metrics = state.metrics.iter().map(|m| (m.trades, m.delta_u.to_bits())).collect();
+    let holdings = state.agents.iter().map(|a| a.e.iter().map(|x| x.to_bits()).collect()).collect();
+    (metrics, holdings)
+}
+
+#[test]
+fn resumed_run_matches_the_uninterrupted_one() {
+    let cfg = busy_config();
+    let mut straight = Engine::new(cfg.clone()).unwrap();
+    straight.run_to_end();
+    let straight = straight.finish();
+
+    let mut first = Engine::new(cfg).unwrap();
+    for _ in 0..3 {
+        first.step_round();
+    }
+    let bytes = first.checkpoint().to_checkpoint_bytes().expect("encode");
+    assert!(bytes.starts_with(CHECKPOINT_MAGIC));
+    drop(first);
+
+    let saved = SimState::from_checkpoint_bytes(&bytes).expect("decode");
+    assert_eq!(saved.resume.as_ref().map(|r| r.round()), Some(3));
+    assert_eq!(saved.metrics.len(), 3);
+    let mut resumed = Engine::resume(saved).expect("resume");
+    assert_eq!(resumed.round(), 3);
+    resumed.run_to_end();
+    let resumed = resumed.finish();
+
+    assert!(resumed.resume.is_none());
+    assert_eq!(fingerprint(&resumed), fingerprint(&straight));
+    assert_eq!(resumed.events.len(), straight.events.len());
+}
+
+#[test]
+fn checkpoint_files_roundtrip_and_branch() {
+    let dir = std::env::temp_dir().join(format!("rdx-checkpoint-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("run.rdx");
+
+    let mut engine = Engine::new(common::small_config()).unwrap();
+    engine.step_round();
+    engine.checkpoint().save(&path).expect("save");
+    let mut saved = SimState::load(&path).expect("load");
+
+    // branch: same state, faster trading
+    let point = saved.resume.as_mut().unwrap();
+    point.config_mut().p2p_encounters_per_round *= 3;
+    let mut branch = Engine::resume(saved).unwrap();
+    let encounters = branch.step_round().map(|m| m.encounters).unwrap();
+    assert!(encounters > common::small_config().p2p_encounters_per_round);
+    std::fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn foreign_and_future_files_are_rejected() {
+    assert!(matches!(SimState::from_checkpoint_bytes(b"not a checkpoint"), Err(RdxError::Checkpoint(_))));
+
+    let mut bytes = SimState::default().to_checkpoint_bytes().unwrap();
+    bytes[CHECKPOINT_MAGIC.len()] = 99;
+    assert!(matches!(
+        SimState::from_checkpoint_bytes(&bytes),
+        Err(RdxError::CheckpointVersion { found: 99, expected: CHECKPOINT_VERSION })
+    ));
+
+    // a plain state loads but cannot be resumed
+    let plain = SimState::default().to_checkpoint_bytes().unwrap();
+    let state = SimState::from_checkpoint_bytes(&plain).unwrap();
+    assert!(Engine::resume(state).is_err());
+}
+
+#[test]
+fn checkpoints_from_before_the_oracle_audit_still_load_and_resume() {
+    // written by the version 3 engine: 8 agents, 2 of 4 rounds, no oracle audit, trade budget or
+    // conservation fields in the config or the metrics
+    let state = SimState::from_checkpoint_bytes(include_bytes!("fixtures/checkpoint_v3.bin")).expect("decode");
+    assert_eq!(state.agents.len(), 8);
+    assert_eq!(state.events.len(), 12);
+    assert_eq!(state.metrics.iter().map(|m| m.trades).sum::<usize>(), 12);
+    assert!(state.metrics.iter().all(|m| m.cap_refused == 0 && m.over_budget == 0 && m.oracle_clamped == 0));
+    let point = state.resume.as_ref().unwrap();
+    assert_eq!(point.round(), 2);
+    let cfg = point.config();
+    assert_eq!((cfg.num_agents, cfg.rounds), (8, 4));
+    assert!(!cfg.conserve_quantities && cfg.max_trades_per_agent.is_none() && cfg.oracle_cache.is_none());
+    assert_eq!(cfg.oracle_tol, 0.0);
+
+    let mut engine = Engine::resume(state).expect("resume");
+    engine.run_to_end();
+    assert_eq!(engine.finish().metrics.len(), 4);
+}
@@ -0,0 +1,36 @@
+mod common;
+
+use rdx_core::model::ClockSpec;
+use rdx_core::sim::{init_agents, run};
+
+#[test]
+fn events_are_stamped_within_their_round() {
+    let mut cfg = common::small_config();
+    let clock = ClockSpec { start_unix: 1_700_000_000.0, round_secs: ClockSpec::DAY };
+    cfg.clock = Some(clock.clone());
+
+    let mut state = init_agents(&cfg).expect("init");
+    run(&cfg, &mut state).expect("run");
+    assert!(!state.events.is_empty());
+
+    let mut last = f64::NEG_INFINITY;
+    for ev in state.events.iter() {
+        let t = ev.time.expect("clock configured");
+        assert_eq!(clock.round_at(t), ev.round);
+        assert!(t >= last, "timestamps follow execution order");
+        last = t;
+    }
+    for m in state.metrics.iter() {
+        assert_eq!(m.time, Some(clock.round_start(m.round)));
+    }
+    assert_eq!(clock.per_period(2.0, ClockSpec::WEEK), 14.0);
+}
+
+#[test]
+fn without_clock_nothing_is_stamped() {
+    let cfg = common::small_config();
+    let mut state = init_agents(&cfg).expect("init");
+    run(&cfg, &mut state).expect("run");
+    assert!(state.events.iter().all(|ev| ev.time.is_none()));
+    assert!(state.metrics.iter().all(|m| m.time.is_none()));
+}
@@ -0,0 +1,89 @@
+mod common;
+
+use serde::Serialize;
+use serde_json::Value;
+use rdx_core::codec::{decode_tagged, encode, encode_as, CodecError, CodecFormat};
+use rdx_core::model::{Agent, SimConfig};
+use rdx_core::sim::init_agents;
+use rdx_core::snapshot::{AgentSnapshot, PopulationSummary};
+
+fn enabled() -> Vec<CodecFormat> {
+    CodecFormat::ALL.into_iter().filter(|f| f.is_enabled()).collect()
+}
+
+/// Binary formats carry floats bit for bit; JSON text may come back one ulp off.
+fn assert_same<T: Serialize>(format: CodecFormat, a: &T, b: &T) {
+    fn close(x: &Value, y: &Value) -> bool {
+        match (x, y) {
+            (Value::Number(p), Value::Number(q)) => {
+                let (p, q) = (p.as_f64().unwrap(), q.as_f64().unwrap());
+                p == q || (p - q).abs() <= 1e-15 * p.abs().max(q.abs())
+            }
+            (Value::Array(p), Value::Array(q)) => p.len() == q.len() && p.iter().zip(q).all(|(p, q)| close(p, q)),
+            (Value::Object(p), Value::Object(q)) => {
+                p.len() == q.len() && p.iter().all(|(k, v)| q.get(k).is_some_and(|w| close(v, w)))
+            }
+            _ => x == y,
+        }
+    }
+    let (x, y) = (serde_json::to_value(a).unwrap(), serde_json::to_value(b).unwrap());
+    if format == CodecFormat::Json {
+        assert!(close(&x, &y), "{format}");
+    } else {
+        assert_eq!(x, y, "{format}");
+    }
+}
+
+#[test]
+fn payloads_roundtrip_in_every_enabled_format() {
+    let cfg = common::small_config();
+    let agents = init_agents(&cfg).unwrap().agents;
+    let snapshot = AgentSnapshot::new(&agents[0], 1e-6, true).unwrap();
+    let summary = PopulationSummary::from_agents(3, &agents);
+    assert!(enabled().contains(&CodecFormat::Json) && enabled().contains(&CodecFormat::Bincode));
+
+    for format in enabled() {
+        let bytes = encode_as(format, &agents[0]).unwrap();
+        assert_eq!(CodecFormat::of(&bytes).unwrap(), format);
+        let agent: Agent = decode_tagged(&bytes).unwrap();
+        assert_same(format, &agent, &agents[0]);
+
+        let config: SimConfig = decode_tagged(&encode_as(format, &cfg).unwrap()).unwrap();
+        assert_same(format, &config, &cfg);
+
+        let back: AgentSnapshot = decode_tagged(&encode_as(format, &snapshot).unwrap()).unwrap();
+        assert_eq!(back, snapshot, "{format}");
+        let back: PopulationSummary = decode_tagged(&encode_as(format, &summary).unwrap()).unwrap();
+        assert_same(format, &back, &summary);
+        let back: Vec<f64> = decode_tagged(&encode_as(format, &agents[1].beta).unwrap()).unwrap();
+        assert_same(format, &back, &agents[1].beta);
+    }
+}
+
+#[test]
+fn binary_formats_are_smaller_than_json() {
+    let agents = init_agents(&common::small_config()).unwrap().agents;
+    let json = encode_as(CodecFormat::Json, &agents).unwrap().len();
+    for format in enabled().into_iter().filter(|&f| f != CodecFormat::Json) {
+        assert!(encode_as(format, &agents).unwrap().len() < json, "{format}");
+    }
+}
+
+#[test]
+fn bad_prefixes_are_typed_errors() {
+    assert!(matches!(decode_tagged::<Vec<f64>>(&[]), Err(CodecError::Empty)));
+    assert!(matches!(decode_tagged::<Vec<f64>>(&[0x7f, 1, 2]), Err(CodecError::UnknownFormat(0x7f))));
+    // untagged JSON starts with a byte no format uses
+    let plain = encode(&vec![1.0, 2.0]).unwrap();
+    assert!(matches!(CodecFormat::of(&plain), Err(CodecError::UnknownFormat(b'['))));
+
+    let mut corrupt = encode_as(CodecFormat::Bincode, &vec![1.0, 2.0]).unwrap();
+    corrupt.truncate(4);
+    let truncated = decode_tagged::<Vec<f64>>(&corrupt);
+    assert!(matches!(truncated, Err(CodecError::Backend { format: CodecFormat::Bincode, .. })));
+
+    for format in CodecFormat::ALL.into_iter().filter(|f| !f.is_enabled()) {
+        assert!(matches!(encode_as(format, &1u8), Err(CodecError::FormatDisabled(f)) if f == format));
+        assert!(matches!(decode_tagged::<u8>(&[format.byte(), 0]), Err(CodecError::FormatDisabled(_))));
+    }
+}
@@ -0,0 +1,30 @@
+#![allow(dead_code)]
+use rdx_core::model::{Agent, SimConfig};
+
+/// Small, fast configuration used across integration tests.
+pub fn small_config() -> SimConfig {
+    serde_json::from_str(
+        r#"{
+            "seed": 7,
+            "num_agents": 24,
+            "rounds": 6,
+            "p2p_encounters_per_round": 40,
+            "base_good": 0,
+            "initial_endowment_scale": 1.0,
+            "alpha_low": 0.1,
+            "alpha_high": 0.9,
+            "trade_step_cap_frac": 0.35,
+            "min_qty": 1e-9,
+            "oracle_bisect_iters": 60,
+            "base_goods_quantity": 5,
+            "base_goods": ["base", "g1", "g2", "g3", "g4"],
+            "reaction_rules": []
+        }"#,
+    )
+    .expect("valid test config")
+}
+
+/// An agent holding `e` with exponents `beta`, every `alpha_to_base` at 0.5.
+pub fn agent(e: Vec<f64>, beta: Vec<f64>) -> Agent {
+    Agent { alpha_to_base: vec![0.5; e.len()], e, beta, ..Default::default() }
+}
@@ -0,0 +1,89 @@
+mod common;
+
+use std::io::Write;
+use rdx_core::checkpoint::{CHECKPOINT_MAGIC, CHECKPOINT_VERSION};
+use rdx_core::codec::{compress, decompress, CodecError, CodecFormat, CompressedWriter, Compression, Envelope};
+use rdx_core::model::TradeEvent;
+use rdx_core::sim::{Engine, SimState};
+
+fn events() -> Vec<TradeEvent> {
+    let mut engine = Engine::new(common::small_config()).unwrap();
+    engine.run_to_end();
+    engine.finish().events
+}
+
+fn enabled() -> Vec<Compression> {
+    Compression::ALL.into_iter().filter(|c| c.is_enabled()).collect()
+}
+
+#[test]
+fn compressed_envelopes_roundtrip_and_shrink() {
+    let events = events();
+    assert!(!events.is_empty());
+    let plain = Envelope::seal(CodecFormat::Json, &events).unwrap();
+    for compression in enabled() {
+        let sealed = Envelope::seal_compressed(CodecFormat::Json, compression, &events).unwrap();
+        let back = Envelope::from_bytes(&sealed.to_bytes()).unwrap();
+        assert_eq!(back.compression, compression);
+        assert_eq!(back.open::<Vec<TradeEvent>>().unwrap().len(), events.len());
+        if compression != Compression::None {
+            assert!(sealed.body.len() < plain.body.len() / 2, "{compression}");
+        }
+        assert_eq!(Compression::from_name(&compression.to_string()), Some(compression));
+    }
+
+    let mut bytes = plain.to_bytes();
+    bytes[5] |= 0x70;
+    assert!(matches!(Envelope::from_bytes(&bytes), Err(CodecError::UnknownCompression(7))));
+    for compression in Compression::ALL.into_iter().filter(|c| !c.is_enabled()) {
+        assert!(matches!(compress(compression, b"x"), Err(CodecError::CompressionDisabled(c)) if c == compression));
+    }
+}
+
+#[test]
+fn streamed_exports_match_one_shot_compression() {
+    let text = "round,i,j\n".repeat(500);
+    for compression in enabled() {
+        let mut w = CompressedWriter::new(compression, Vec::new()).unwrap();
+        w.write_all(text.as_bytes()).unwrap();
+        let streamed = w.finish().unwrap();
+        assert_eq!(decompress(compression, &streamed).unwrap().as_ref(), text.as_bytes(), "{compression}");
+        assert_eq!(decompress(compression, &compress(compression, text.as_bytes()).unwrap()).unwrap().as_ref(),
+            text.as_bytes());
+    }
+}
+
+#[test]
+fn checkpoints_are_compressed_and_old_files_still_load() {
+    let mut engine = Engine::new(common::small_config()).unwrap();
+    engine.step_round();
+    let state = engine.checkpoint();
+    let plain = state.to_checkpoint_bytes_with(Compression::None).unwrap();
+    let header = CHECKPOINT_MAGIC.len() + 4;
+    assert_eq!(plain[header], Compression::None.byte());
+
+    for compression in enabled() {
+        let bytes = state.to_checkpoint_bytes_with(compression).unwrap();
+        let back = SimState::from_checkpoint_bytes(&bytes).unwrap();
+        assert_eq!(back.resume.map(|r| r.round()), Some(1));
+    }
+    let preferred = state.to_checkpoint_bytes().unwrap();
+    assert_eq!(preferred[header], Compression::preferred().byte());
+
+    // version 1: no compression byte, and events without `seq` or hashes
+    let v1 = include_bytes!("fixtures/checkpoint_v1.bin");
+    assert_eq!(u32::from_le_bytes(v1[CHECKPOINT_MAGIC.len()..header].try_into().unwrap()), 1);
+    assert_ne!(CHECKPOINT_VERSION, 1);
+    let back = SimState::from_checkpoint_bytes(v1).unwrap();
+    assert_eq!(back.agents.len(), 8);
+    assert_eq!(back.events.len(), 12);
+
+    // version 2 added the compression byte; both number their events in order, without hashes
+    let v2 = SimState::from_checkpoint_bytes(include_bytes!("fixtures/checkpoint_v2.bin")).unwrap();
+    assert_eq!(serde_json::to_string(&v2.events).unwrap(), serde_json::to_string(&back.events).unwrap());
+    for (k, e) in back.events.iter().enumerate() {
+        assert_eq!(e.seq, k as u64);
+        assert_eq!([e.pre_i, e.pre_j, e.post_i, e.post_j], [[0; 32]; 4]);
+    }
+    assert!(back.events.windows(2).all(|w| w[0].round <= w[1].round));
+}
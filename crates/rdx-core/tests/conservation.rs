@@ -0,0 +1,163 @@
+mod common;
+
+use rdx_core::ids::GoodId;
+use rdx_core::model::{Agent, CostSettlement, PolicySpec, SimConfig, TransactionCost};
+use rdx_core::sim::{init_agents, Engine};
+use rdx_core::trade::{
+    apply_bundle_trade_conserving, apply_trade, apply_trade_conserving, apply_trade_with_cost_conserving,
+    charged_fraction, conserving_fraction, BundleTrade, TradeCandidate,
+};
+
+fn pair() -> (Agent, Agent) {
+    let state = init_agents(&common::small_config()).expect("init");
+    let (mut i, mut j) = (state.agents[0].clone(), state.agents[1].clone());
+    i.e[..2].copy_from_slice(&[2.0, 0.5]);
+    j.e[..2].copy_from_slice(&[1.0, 3.0]);
+    (i, j)
+}
+
+/// i gives 1.0 of good 1, holding only 0.5, for 0.4 of the base.
+fn overdrawn() -> TradeCandidate {
+    TradeCandidate {
+        good_a: GoodId(1),
+        good_b: GoodId(0),
+        q_ab: 0.4,
+        delta_a_i: -1.0,
+        delta_b_i: 0.4,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn flooring_creates_goods_but_the_conserving_variant_does_not() {
+    let min_qty = 0.1;
+    let (mut i, mut j) = pair();
+    apply_trade(&mut i, &mut j, &overdrawn(), min_qty).unwrap();
+    assert!(i.e[1] + j.e[1] > 3.5 + 0.05, "the floor raised i's holding out of nothing");
+
+    let (mut i, mut j) = pair();
+    assert!((conserving_fraction(&i, &j, &overdrawn(), min_qty) - 0.4).abs() < 1e-15);
+    let t = apply_trade_conserving(&mut i, &mut j, &overdrawn(), min_qty).unwrap();
+    assert!((t - 0.4).abs() < 1e-15);
+    assert!((i.e[1] - min_qty).abs() < 1e-12);
+    assert!((i.e[1] + j.e[1] - 3.5).abs() < 1e-12);
+    assert!((i.e[0] + j.e[0] - 3.0).abs() < 1e-12);
+    assert!((i.e[0] - (2.0 + 0.4 * t)).abs() < 1e-12);
+}
+
+#[test]
+fn trades_clear_of_the_floor_run_in_full() {
+    let (mut i, mut j) = pair();
+    let mut cand = overdrawn();
+    cand.delta_a_i = -0.2;
+    assert_eq!(conserving_fraction(&i, &j, &cand, 0.1), 1.0);
+    assert_eq!(apply_trade_conserving(&mut i, &mut j, &cand, 0.1).unwrap(), 1.0);
+    assert!((i.e[1] - 0.3).abs() < 1e-12);
+}
+
+#[test]
+fn conserving_bundles_shrink_to_the_tightest_good() {
+    let (mut i, mut j) = pair();
+    let trade = BundleTrade {
+        goods: vec![GoodId(1), GoodId(0)],
+        delta_i: vec![-1.0, 0.4],
+        prices: vec![0.4, 1.0],
+        ..Default::default()
+    };
+    let t = apply_bundle_trade_conserving(&mut i, &mut j, &trade, 0.1).unwrap();
+    assert!((t - 0.4).abs() < 1e-15);
+    assert!((i.e[1] + j.e[1] - 3.5).abs() < 1e-12);
+}
+
+#[test]
+fn costs_shrink_a_conserving_trade_until_they_are_paid() {
+    let cost = TransactionCost { fixed: 0.1, ..Default::default() };
+    let (i, j) = pair();
+    let t = charged_fraction(&i, &j, &overdrawn(), Some(&cost), (0.0, 0.0), 0, 0.1);
+    assert!(t > 0.0 && t <= conserving_fraction(&i, &j, &overdrawn(), 0.1));
+
+    // j pays its fixed cost out of the base good it is giving up
+    let (mut i, mut j) = pair();
+    let mut cand = overdrawn();
+    cand.delta_a_i = -0.2;
+    cand.delta_b_i = 0.8;
+    j.e[0] = 1.0;
+    let t = charged_fraction(&i, &j, &cand, Some(&cost), (0.0, 0.0), 0, 0.1);
+    assert!((t - 1.0).abs() < 1e-12);
+    let (applied, cost_i, cost_j) = apply_trade_with_cost_conserving(&mut i, &mut j, &cand, &cost, 0, 0.1).unwrap();
+    assert_eq!(applied, t);
+    assert!((i.e[0] + j.e[0] + cost_i + cost_j - 3.0).abs() < 1e-12);
+    assert!((j.e[0] - 0.1).abs() < 1e-12);
+
+    // a fixed cost no holding can cover refuses the trade
+    let cost = TransactionCost { fixed: 5.0, ..Default::default() };
+    assert_eq!(charged_fraction(&i, &j, &cand, Some(&cost), (0.0, 0.0), 0, 0.1), 0.0);
+
+    // so does a tax the payer cannot afford at any size of trade
+    assert_eq!(charged_fraction(&i, &j, &cand, None, (0.0, 10.0), 0, 0.1), 0.0);
+}
+
+fn totals(agents: &[Agent]) -> Vec<f64> {
+    (0..agents[0].e.len()).map(|g| agents.iter().map(|a| a.e[g]).sum()).collect()
+}
+
+fn conserving(mut cfg: SimConfig) -> SimConfig {
+    cfg.conserve_quantities = true;
+    cfg.min_qty = 0.05;
+    cfg
+}
+
+#[test]
+fn conserving_runs_with_costs_account_for_every_unit() {
+    let mut cfg = conserving(common::small_config());
+    cfg.transaction_cost = Some(TransactionCost { proportional: 0.05, fixed: 0.02, ..Default::default() });
+    let mut engine = Engine::new(cfg.clone()).unwrap();
+    let before = totals(&engine.state().agents);
+    engine.run_to_end();
+    let state = engine.finish();
+    assert!(!state.events.is_empty());
+    let paid: f64 = state.events.iter().map(|e| e.cost_i + e.cost_j).sum();
+    assert!(paid > 0.0);
+    let after = totals(&state.agents);
+    for (g, (b, a)) in before.iter().zip(&after).enumerate() {
+        let a = if g == cfg.base_good { a + paid } else { *a };
+        assert!((a - b).abs() < 1e-9 * b.max(1.0), "good {}: {} -> {}", g, b, a);
+    }
+    assert!(state.agents.iter().all(|a| a.e.iter().all(|&x| x >= 0.05 - 1e-12)));
+
+    cfg.transaction_cost = Some(TransactionCost { proportional: 0.05, fixed: 0.02, settlement: CostSettlement::Burned });
+    let mut engine = Engine::new(cfg).unwrap();
+    engine.run_to_end();
+    assert!(engine.state().agents.iter().all(|a| a.e.iter().all(|&x| x >= 0.05 - 1e-12)));
+}
+
+#[test]
+fn conserving_runs_with_a_tax_keep_it_in_the_treasury() {
+    let mut cfg = conserving(common::small_config());
+    cfg.policy = Some(PolicySpec { tax_rate: 0.2, ..Default::default() });
+    let mut engine = Engine::new(cfg.clone()).unwrap();
+    let before = totals(&engine.state().agents);
+    engine.run_to_end();
+    let state = engine.finish();
+    assert!(state.events.iter().any(|e| e.tax > 0.0));
+    let after = totals(&state.agents);
+    for (g, (b, a)) in before.iter().zip(&after).enumerate() {
+        let a = if g == cfg.base_good { a + state.treasury } else { *a };
+        assert!((a - b).abs() < 1e-9 * b.max(1.0), "good {}: {} -> {}", g, b, a);
+    }
+    assert!(state.agents.iter().all(|a| a.e.iter().all(|&x| x >= 0.05 - 1e-12)));
+}
+
+#[test]
+fn conserving_runs_keep_population_totals() {
+    let cfg = conserving(common::small_config());
+    let mut engine = Engine::new(cfg).unwrap();
+    let before = totals(&engine.state().agents);
+    engine.run_to_end();
+    let state = engine.finish();
+    assert!(!state.events.is_empty());
+    for (b, a) in before.iter().zip(totals(&state.agents)) {
+        assert!((a - b).abs() < 1e-9 * b.max(1.0), "{} -> {}", b, a);
+    }
+    assert!(state.agents.iter().all(|a| a.e.iter().all(|&x| x >= 0.05 - 1e-12)));
+}
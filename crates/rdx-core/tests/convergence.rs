@@ -0,0 +1,56 @@
+mod common;
+
+use rdx_core::model::ConvergenceSpec;
+use rdx_core::sim::{init_agents, run, Engine, RoundMetrics};
+
+#[test]
+fn quiet_rounds_end_the_run_after_patience() {
+    let mut cfg = common::small_config();
+    cfg.rounds = 50;
+    // every round gains less than this, so every round is quiet
+    cfg.stop_when_converged = Some(ConvergenceSpec { patience: 3, min_utility_change: f64::INFINITY });
+
+    let mut engine = Engine::new(cfg.clone()).unwrap();
+    for t in 0..3 {
+        assert!(!engine.is_finished(), "round {t}");
+        assert!(engine.step_round().is_some());
+    }
+    assert!(engine.is_finished());
+    assert!(engine.step_round().is_none());
+    let state = engine.finish();
+    assert_eq!(state.stopped_at, Some(2));
+    assert_eq!(state.metrics.len(), 3);
+
+    // `run` stops at the same round
+    let mut run_state = init_agents(&cfg).unwrap();
+    run(&cfg, &mut run_state).unwrap();
+    assert_eq!(run_state.stopped_at, Some(2));
+    assert_eq!(run_state.events.len(), state.events.len());
+}
+
+#[test]
+fn only_rounds_below_min_utility_change_count_as_quiet() {
+    let mut cfg = common::small_config();
+    cfg.rounds = 200;
+    let (patience, min_utility_change) = (4, 1e-3);
+    cfg.stop_when_converged = Some(ConvergenceSpec { patience, min_utility_change });
+    let mut state = init_agents(&cfg).unwrap();
+    run(&cfg, &mut state).unwrap();
+
+    let t = state.stopped_at.expect("the gains die out before the last round");
+    assert!(t < cfg.rounds - 1);
+    assert_eq!(state.metrics.len(), t + 1);
+    let quiet = |m: &RoundMetrics| m.trades == 0 || m.delta_u < min_utility_change;
+    assert!(state.metrics[t + 1 - patience..].iter().all(quiet));
+    // the round before the quiet stretch was not quiet, or the rule would have fired earlier
+    if t >= patience {
+        assert!(!quiet(&state.metrics[t - patience]));
+    }
+
+    // without the rule the same config runs to the end
+    cfg.stop_when_converged = None;
+    let mut state = init_agents(&cfg).unwrap();
+    run(&cfg, &mut state).unwrap();
+    assert_eq!(state.stopped_at, None);
+    assert_eq!(state.metrics.len(), cfg.rounds);
+}
@@ -0,0 +1,39 @@
+mod common;
+
+use rand::prelude::*;
+use rdx_core::model::DecisionNoise;
+use rdx_core::preferences::perturbed_beta;
+use rdx_core::sim::{init_agents, run};
+
+#[test]
+fn perturbed_beta_stays_a_profile() {
+    let beta = [0.2, 0.3, 0.5];
+    let mut rng = StdRng::seed_from_u64(3);
+    assert_eq!(perturbed_beta(&beta, 0.0, &mut rng), beta.to_vec());
+    let noisy = perturbed_beta(&beta, 0.5, &mut rng);
+    assert!((noisy.iter().sum::<f64>() - 1.0).abs() < 1e-12);
+    assert!(noisy.iter().all(|&b| b > 0.0));
+    assert_ne!(noisy, beta.to_vec());
+}
+
+#[test]
+fn noise_causes_mistakes_and_leaves_gains_unrealized() {
+    let mut cfg = common::small_config();
+    let mut clean = init_agents(&cfg).expect("init");
+    run(&cfg, &mut clean).expect("run");
+    assert!(clean.metrics.iter().all(|m| m.mistakes == 0));
+
+    cfg.decision_noise = Some(DecisionNoise { sigma: 1.0 });
+    let mut noisy = init_agents(&cfg).expect("init");
+    run(&cfg, &mut noisy).expect("run");
+    let mistakes: usize = noisy.metrics.iter().map(|m| m.mistakes).sum();
+    assert!(mistakes > 0);
+    let counted = noisy.events.iter().filter(|e| e.delta_u_i <= 0.0 || e.delta_u_j <= 0.0).count();
+    assert_eq!(mistakes, counted);
+    let gap = |s: &rdx_core::sim::SimState| s.metrics.last().map(|m| m.pareto_gap).unwrap_or(0.0);
+    assert!(gap(&noisy) > gap(&clean));
+
+
+    cfg.decision_noise = Some(DecisionNoise { sigma: -1.0 });
+    assert!(init_agents(&cfg).is_err());
+}
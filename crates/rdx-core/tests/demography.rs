@@ -0,0 +1,58 @@
+mod common;
+
+use rdx_core::matching::PartnerGraph;
+use rdx_core::model::{DemographySpec, ExitMode};
+use rdx_core::sim::{init_agents, mean_endowments, run};
+
+fn total_holdings(state: &rdx_core::sim::SimState) -> f64 {
+    state.agents.iter().flat_map(|a| a.e.iter()).sum()
+}
+
+#[test]
+fn population_changes_and_ids_stay_unique() {
+    let mut cfg = common::small_config();
+    cfg.demography = Some(DemographySpec { entry_rate: 2.5, exit_prob: 0.1, exit: ExitMode::Remove, min_population: 2 });
+    let mut state = init_agents(&cfg).expect("init");
+    run(&cfg, &mut state).expect("run");
+
+    assert!(state.metrics.iter().any(|m| m.entered > 0));
+    assert!(state.metrics.iter().any(|m| m.exited > 0));
+    let mut pop = cfg.num_agents;
+    for m in state.metrics.iter() {
+        pop = pop + m.entered - m.exited;
+        assert_eq!(m.population, pop);
+    }
+    assert_eq!(state.agents.len(), pop);
+
+    let mut ids: Vec<u64> = state.agents.iter().map(|a| a.id).collect();
+    ids.sort_unstable();
+    ids.dedup();
+    assert_eq!(ids.len(), state.agents.len());
+    assert!(ids.iter().any(|&id| id >= cfg.num_agents as u64));
+    assert_eq!(mean_endowments(&state).len(), cfg.base_goods_quantity);
+}
+
+#[test]
+fn redistribution_conserves_goods_across_exits() {
+    let mut cfg = common::small_config();
+    cfg.demography = Some(DemographySpec { entry_rate: 0.0, exit_prob: 0.2, exit: ExitMode::Redistribute, min_population: 10 });
+    let mut state = init_agents(&cfg).expect("init");
+    let before = total_holdings(&state);
+    run(&cfg, &mut state).expect("run");
+    assert!(state.agents.len() >= 10 && state.agents.len() < cfg.num_agents);
+    assert!((total_holdings(&state) - before).abs() < 1e-6 * before);
+}
+
+#[test]
+fn partner_ties_follow_renumbering() {
+    let mut g = PartnerGraph::default();
+    g.reinforce(0, 3, 1.0);
+    g.reinforce(1, 3, 2.0);
+    g.reinforce(0, 2, 0.5);
+    g.retain_agents(&[true, false, true, true]);
+    // 2 -> 1, 3 -> 2
+    assert_eq!(g.weight(0, 2), 1.0);
+    assert_eq!(g.weight(0, 1), 0.5);
+    assert_eq!(g.weight(1, 2), 0.0);
+    assert_eq!(g.edges().len(), 2);
+}
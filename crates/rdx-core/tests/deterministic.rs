@@ -0,0 +1,56 @@
+mod common;
+
+use rdx_core::math::{exp, ln, powf};
+use rdx_core::pareto_oracle::{CobbDouglasWalrasOracle, ParetoOracle};
+use rdx_core::sim::{init_agents, run};
+
+#[test]
+fn portable_functions_agree_with_std() {
+    for &x in &[1e-9, 0.3, 1.0, 2.5, 17.0, 1e6] {
+        assert!((ln(x) - x.ln()).abs() <= 1e-15 * x.ln().abs().max(1.0), "ln({x})");
+        assert!((exp(x.ln()) - x).abs() <= 1e-14 * x, "exp(ln({x}))");
+        assert!((powf(x, 0.37) - x.powf(0.37)).abs() <= 1e-15 * x.powf(0.37), "powf({x})");
+    }
+    assert_eq!(exp(0.0), 1.0);
+    assert_eq!(ln(1.0), 0.0);
+    assert_eq!(powf(0.5, 2.0), 0.25);
+}
+
+#[cfg(feature = "deterministic")]
+#[test]
+fn software_functions_give_the_same_bits_everywhere() {
+    // pinned from libm: reproducible across platforms, though not always correctly rounded
+    // (`exp(1.0)` is one ulp above `f64::consts::E`)
+    assert_eq!(exp(1.0).to_bits(), 0x4005bf0a8b14576a);
+    assert_eq!(exp(-2.5).to_bits(), 0x3fb50385c094f425);
+    assert_eq!(ln(2.0).to_bits(), 0x3fe62e42fefa39ef);
+    assert_eq!(ln(0.3).to_bits(), 0xbff34378fcbda721);
+    assert_eq!(powf(7.3, -1.7).to_bits(), 0x3fa171683b6b5e01);
+}
+
+#[test]
+fn exchange_core_is_reproducible() {
+    let oracle = CobbDouglasWalrasOracle;
+    let a = oracle.solve_two_good_exchange(0.7, 2.0, 1.0, 0.2, 1.5, 3.0, 1e-9, 80);
+    let b = oracle.solve_two_good_exchange(0.7, 2.0, 1.0, 0.2, 1.5, 3.0, 1e-9, 80);
+    let bits = |d: &rdx_core::pareto_oracle::DyadExchange| [d.q_ab, d.ai_post, d.bi_post].map(f64::to_bits);
+    assert_eq!(bits(&a), bits(&b));
+
+    // utilities go through the same functions as the caller's own arithmetic (the `simd` kernels
+    // have their own logarithm)
+    #[cfg(not(feature = "simd"))]
+    {
+        use rdx_core::preferences::cd_utility;
+        let u: f64 = cd_utility(&[0.2, 0.3, 0.5], &[1.0, 2.0, 3.0], 1e-9);
+        let expected = exp(0.0 + 0.2 * ln(1.0) + 0.3 * ln(2.0) + 0.5 * ln(3.0));
+        assert_eq!(u.to_bits(), expected.to_bits());
+    }
+
+    let cfg = common::small_config();
+    let fingerprint = || {
+        let mut state = init_agents(&cfg).unwrap();
+        run(&cfg, &mut state).unwrap();
+        state.agents.iter().flat_map(|a| a.e.iter().map(|x| x.to_bits())).collect::<Vec<_>>()
+    };
+    assert_eq!(fingerprint(), fingerprint());
+}
@@ -0,0 +1,76 @@
+mod common;
+
+use rand::prelude::*;
+use rdx_core::dynamics::{consume, decay, replenish, shock};
+use rdx_core::ids::GoodId;
+use rdx_core::model::{Agent, ConsumptionSpec, EndowmentSpec, ShockSpec};
+use rdx_core::sim::{init_agents, run};
+
+fn total(agents: &[Agent]) -> f64 {
+    agents.iter().flat_map(|a| a.e.iter()).sum()
+}
+
+#[test]
+fn consumption_is_tilted_by_beta() {
+    let mut a = Agent { e: vec![10.0, 10.0], beta: vec![0.25, 0.75], ..Default::default() };
+    // fractions 0.2 * 2 * beta_k
+    let used = consume(&mut a, 0.2);
+    assert!((a.e[0] - 9.0).abs() < 1e-12 && (a.e[1] - 7.0).abs() < 1e-12);
+    assert!((used - 4.0).abs() < 1e-12);
+
+    let mut rng = StdRng::seed_from_u64(0);
+    let spec = EndowmentSpec::Uniform { low: 1.0, high: 1.0 + 1e-12 };
+    let added = replenish(&mut a, &spec, 0.5, 2.0, &mut rng);
+    assert!((added - 2.0).abs() < 1e-9);
+}
+
+#[test]
+fn flow_economy_balances_its_books() {
+    let mut cfg = common::small_config();
+    cfg.consumption = Some(ConsumptionSpec { rate: 0.1, income: 0.1 });
+    let mut state = init_agents(&cfg).expect("init");
+    let before = total(&state.agents);
+    run(&cfg, &mut state).expect("run");
+    let consumed: f64 = state.metrics.iter().map(|m| m.consumed).sum();
+    let replenished: f64 = state.metrics.iter().map(|m| m.replenished).sum();
+    assert!(consumed > 0.0 && replenished > 0.0);
+    assert!((total(&state.agents) - (before - consumed + replenished)).abs() < 1e-6 * before);
+}
+
+#[test]
+fn perishable_goods_decay_every_round() {
+    let mut a = Agent { e: vec![4.0, 4.0, 4.0], ..Default::default() };
+    let lost = decay(&mut a, &[0.0, 0.5]);
+    assert_eq!(a.e, vec![4.0, 2.0, 4.0]);
+    assert_eq!(lost, 2.0);
+
+    let mut cfg = common::small_config();
+    cfg.good_decay = vec![0.0, 0.9, 0.0, 0.0, 0.0];
+    let mut state = init_agents(&cfg).expect("init");
+    let before: f64 = state.agents.iter().map(|a| a.e[1]).sum();
+    run(&cfg, &mut state).expect("run");
+    let after: f64 = state.agents.iter().map(|a| a.e[1]).sum();
+    let decayed: f64 = state.metrics.iter().map(|m| m.decayed).sum();
+    assert!(after < before * 0.1f64.powi(cfg.rounds as i32 - 1));
+    assert!((before - after - decayed).abs() < 1e-6 * before);
+
+    cfg.good_decay = vec![0.5; 2];
+    assert!(init_agents(&cfg).is_err());
+}
+
+#[test]
+fn shocks_hit_the_scheduled_fraction() {
+    let spec = ShockSpec { round: 2, goods: vec![GoodId(1)], agent_fraction: 0.25, mean: 0.5, sigma: 0.0 };
+    let mut agents: Vec<Agent> = (0..8).map(|_| Agent { e: vec![2.0, 2.0], ..Default::default() }).collect();
+    let hit = shock(&mut agents, &spec, &mut StdRng::seed_from_u64(5));
+    assert_eq!(hit, 2);
+    assert!(agents.iter().all(|a| a.e[0] == 2.0));
+    assert_eq!(agents.iter().filter(|a| (a.e[1] - 1.0).abs() < 1e-12).count(), 2);
+
+    let mut cfg = common::small_config();
+    cfg.shocks = vec![ShockSpec { agent_fraction: 0.5, sigma: 0.3, ..spec }];
+    let mut state = init_agents(&cfg).expect("init");
+    run(&cfg, &mut state).expect("run");
+    let shocked: Vec<usize> = state.metrics.iter().map(|m| m.shocked).collect();
+    assert_eq!(shocked, vec![0, 0, 12, 0, 0, 0]);
+}
@@ -0,0 +1,97 @@
+use rdx_core::pareto_oracle::{
+    contract_curve, core_segment, edgeworth_trace, CobbDouglasWalrasOracle, ParetoOracle, ShortSideOracle,
+};
+
+const DYAD: (f64, f64, f64, f64, f64, f64) = (0.7, 2.0, 1.0, 0.2, 1.5, 3.0);
+
+fn distance(p: (f64, f64), q: (f64, f64)) -> f64 {
+    ((p.0 - q.0).powi(2) + (p.1 - q.1).powi(2)).sqrt()
+}
+
+#[test]
+fn contract_curve_spans_the_box() {
+    let (alpha_i, ai, bi, alpha_j, aj, bj) = DYAD;
+    let trace = edgeworth_trace(&CobbDouglasWalrasOracle, alpha_i, ai, bi, alpha_j, aj, bj, 1e-9, 80, 101);
+    assert_eq!(trace.totals, (3.5, 4.0));
+    assert_eq!(trace.endowment, (2.0, 1.0));
+    assert_eq!(trace.contract_curve.len(), 101);
+    assert_eq!(trace.contract_curve[0], (0.0, 0.0));
+    let end = *trace.contract_curve.last().unwrap();
+    assert!(distance(end, trace.totals) < 1e-12);
+    for curve in [&trace.contract_curve, &trace.offer_i, &trace.offer_j] {
+        for &(a, b) in curve.iter() {
+            assert!((-1e-9..=3.5 + 1e-9).contains(&a) && (-1e-9..=4.0 + 1e-9).contains(&b), "({a}, {b})");
+        }
+    }
+}
+
+#[test]
+fn walras_allocation_is_where_offer_curves_meet_on_the_contract_curve() {
+    let (alpha_i, ai, bi, alpha_j, aj, bj) = DYAD;
+    let trace = edgeworth_trace(&CobbDouglasWalrasOracle, alpha_i, ai, bi, alpha_j, aj, bj, 1e-9, 80, 4001);
+    let chosen = trace.chosen;
+
+    // equal marginal rates of substitution
+    let (ta, tb) = trace.totals;
+    let mrs_i = alpha_i / (1.0 - alpha_i) * chosen.1 / chosen.0;
+    let mrs_j = alpha_j / (1.0 - alpha_j) * (tb - chosen.1) / (ta - chosen.0);
+    assert!((mrs_i - mrs_j).abs() < 1e-6 * mrs_i);
+    assert!((mrs_i - trace.q_ab).abs() < 1e-6 * mrs_i);
+
+    let nearest = |curve: &[(f64, f64)]| curve.iter().map(|&p| distance(p, chosen)).fold(f64::INFINITY, f64::min);
+    assert!(nearest(&trace.contract_curve) < 1e-2);
+    assert!(nearest(&trace.offer_i) < 1e-2);
+    assert!(nearest(&trace.offer_j) < 1e-2);
+}
+
+#[test]
+fn short_side_allocation_is_off_the_contract_curve() {
+    let (alpha_i, ai, bi, alpha_j, aj, bj) = DYAD;
+    let trace = edgeworth_trace(&ShortSideOracle, alpha_i, ai, bi, alpha_j, aj, bj, 1e-9, 80, 4001);
+    let (ta, tb) = trace.totals;
+    let (a, b) = trace.chosen;
+    let mrs_i = alpha_i / (1.0 - alpha_i) * b / a;
+    let mrs_j = alpha_j / (1.0 - alpha_j) * (tb - b) / (ta - a);
+    assert!((mrs_i - mrs_j).abs() > 1e-3);
+}
+
+#[test]
+fn contract_curve_points_equalize_marginal_rates() {
+    let (alpha_i, alpha_j) = (0.7, 0.2);
+    let curve = contract_curve(alpha_i, alpha_j, (3.5, 4.0), 51);
+    assert_eq!(curve.len(), 51);
+    assert_eq!((curve[0].t, curve[50].t), (0.0, 1.0));
+    for p in curve[1..50].iter() {
+        assert!((p.ai + p.aj - 3.5).abs() < 1e-12 && (p.bi + p.bj - 4.0).abs() < 1e-12);
+        let mrs_i = alpha_i / (1.0 - alpha_i) * p.bi / p.ai;
+        let mrs_j = alpha_j / (1.0 - alpha_j) * p.bj / p.aj;
+        assert!((mrs_i - mrs_j).abs() < 1e-9 * mrs_i);
+    }
+}
+
+#[test]
+fn walras_solution_is_on_the_curve_and_in_the_core() {
+    let (alpha_i, ai, bi, alpha_j, aj, bj) = DYAD;
+    let ex = CobbDouglasWalrasOracle.solve_two_good_exchange(alpha_i, ai, bi, alpha_j, aj, bj, 1e-9, 80);
+    let (ta, tb) = (ai + aj, bi + bj);
+
+    let t = ex.ai_post / ta;
+    let on_curve = contract_curve(alpha_i, alpha_j, (ta, tb), 2001).into_iter()
+        .min_by(|p, q| (p.t - t).abs().total_cmp(&(q.t - t).abs()))
+        .unwrap();
+    assert!((on_curve.bi - ex.bi_post).abs() < 1e-2);
+
+    let core = core_segment(alpha_i, ai, bi, alpha_j, aj, bj, 1e-9, 101);
+    let (lo, hi) = (core[0], core[100]);
+    assert!(lo.t < t && t < hi.t, "{} not in [{}, {}]", t, lo.t, hi.t);
+
+    // the ends are where one agent is back at its endowment utility
+    let u = |alpha: f64, a: f64, b: f64| a.powf(alpha) * b.powf(1.0 - alpha);
+    assert!((u(alpha_i, lo.ai, lo.bi) - u(alpha_i, ai, bi)).abs() < 1e-9);
+    assert!((u(alpha_j, hi.aj, hi.bj) - u(alpha_j, aj, bj)).abs() < 1e-9);
+    // and everyone in between is better off than at the endowment
+    for p in core.iter() {
+        assert!(u(alpha_i, p.ai, p.bi) >= u(alpha_i, ai, bi) - 1e-9);
+        assert!(u(alpha_j, p.aj, p.bj) >= u(alpha_j, aj, bj) - 1e-9);
+    }
+}
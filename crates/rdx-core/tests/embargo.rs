@@ -0,0 +1,34 @@
+mod common;
+
+use rdx_core::error::RdxError;
+use rdx_core::ids::GoodId;
+use rdx_core::model::{Embargo, PairingMode};
+use rdx_core::sim::{init_agents, run};
+
+#[test]
+fn embargoed_goods_do_not_trade_inside_the_window() {
+    for mode in [PairingMode::AgainstBase, PairingMode::AllPairsPruned] {
+        let mut cfg = common::small_config();
+        cfg.pairing_mode = mode;
+        cfg.embargoes = vec![Embargo { goods: vec![GoodId(1), GoodId(3)], from_round: 1, to_round: 3 }];
+
+        let mut state = init_agents(&cfg).expect("init");
+        run(&cfg, &mut state).expect("run");
+
+        for ev in state.events.iter().filter(|ev| (1..=3).contains(&ev.round)) {
+            assert!(![1, 3].contains(&ev.good_a.index()) && ![1, 3].contains(&ev.good_b.index()));
+        }
+        assert!(state.events.iter().any(|ev| ev.round == 0 && ([1, 3].contains(&ev.good_a.index()) || [1, 3].contains(&ev.good_b.index()))));
+        for m in state.metrics.iter() {
+            let expected: &[usize] = if (1..=3).contains(&m.round) { &[1, 3] } else { &[] };
+            assert_eq!(m.embargoed, expected);
+        }
+    }
+}
+
+#[test]
+fn embargo_on_unknown_good_is_rejected() {
+    let mut cfg = common::small_config();
+    cfg.embargoes = vec![Embargo { goods: vec![GoodId(99)], from_round: 0, to_round: 1 }];
+    assert!(matches!(init_agents(&cfg), Err(RdxError::GoodOutOfRange { index: 99, .. })));
+}
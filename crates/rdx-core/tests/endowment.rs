@@ -0,0 +1,56 @@
+mod common;
+
+use rand::prelude::*;
+use rdx_core::endowment::{draw_bundle, lognormal, pareto};
+use rdx_core::error::RdxError;
+use rdx_core::model::EndowmentSpec;
+use rdx_core::sim::init_agents;
+
+fn mean_var(xs: &[f64]) -> (f64, f64) {
+    let m = xs.iter().sum::<f64>() / xs.len() as f64;
+    (m, xs.iter().map(|x| (x - m).powi(2)).sum::<f64>() / xs.len() as f64)
+}
+
+#[test]
+fn samplers_match_their_moments() {
+    let mut rng = StdRng::seed_from_u64(3);
+    let xs: Vec<f64> = (0..50_000).map(|_| lognormal(&mut rng, 2.0, 1.0)).collect();
+    let (m, v) = mean_var(&xs);
+    assert!((m - 2.0).abs() < 0.03, "{m}");
+    assert!((v - 1.0).abs() < 0.1, "{v}");
+
+    // Pareto(3, 1): mean 3/2, never below the minimum
+    let xs: Vec<f64> = (0..50_000).map(|_| pareto(&mut rng, 3.0, 1.0)).collect();
+    assert!(xs.iter().all(|&x| x >= 1.0));
+    assert!((mean_var(&xs).0 - 1.5).abs() < 0.03);
+}
+
+#[test]
+fn specialists_hold_few_goods_heavily() {
+    let mut cfg = common::small_config();
+    cfg.endowment = EndowmentSpec::Specialist { goods: 2, high: 5.0, low: 0.5 };
+    let state = init_agents(&cfg).expect("init");
+    for a in state.agents.iter() {
+        let heavy = a.e.iter().filter(|&&x| x == 5.0 * cfg.initial_endowment_scale).count();
+        assert_eq!(heavy, 2);
+    }
+
+    let mut rng = StdRng::seed_from_u64(1);
+    let spec = EndowmentSpec::PerGood { means: vec![1.0, 10.0], variances: vec![0.0, 0.0] };
+    let e = draw_bundle(&spec, 2, &mut rng);
+    assert!((e[0] - 1.0).abs() < 1e-12 && (e[1] - 10.0).abs() < 1e-12);
+}
+
+#[test]
+fn default_endowment_keeps_legacy_draws_and_bad_specs_are_rejected() {
+    let cfg = common::small_config();
+    let state = init_agents(&cfg).expect("init");
+    let scale = cfg.initial_endowment_scale;
+    assert!(state.agents.iter().flat_map(|a| a.e.iter()).all(|&x| (0.5 * scale..2.0 * scale).contains(&x)));
+
+    let mut bad = cfg.clone();
+    bad.endowment = EndowmentSpec::PerGood { means: vec![1.0], variances: vec![0.1] };
+    assert!(matches!(init_agents(&bad), Err(RdxError::DimensionMismatch { .. })));
+    bad.endowment = EndowmentSpec::Specialist { goods: 0, high: 2.0, low: 1.0 };
+    assert!(matches!(init_agents(&bad), Err(RdxError::InvalidConfig(_))));
+}
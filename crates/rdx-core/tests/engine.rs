@@ -0,0 +1,26 @@
+mod common;
+
+use rdx_core::sim::{init_agents, run, Engine};
+
+#[test]
+fn stepping_matches_monolithic_run() {
+    let cfg = common::small_config();
+
+    let mut state = init_agents(&cfg).expect("init");
+    run(&cfg, &mut state).expect("run");
+
+    let mut engine = Engine::new(cfg.clone()).expect("engine");
+    let mut rounds = 0;
+    while let Some(m) = engine.step_round() {
+        assert_eq!(m.round, rounds);
+        rounds += 1;
+    }
+    assert!(engine.is_finished());
+    assert_eq!(rounds, cfg.rounds);
+
+    let stepped = engine.finish();
+    assert_eq!(stepped.events.len(), state.events.len());
+    for (a, b) in stepped.agents.iter().zip(state.agents.iter()) {
+        assert_eq!(a.e, b.e);
+    }
+}
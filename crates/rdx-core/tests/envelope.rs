@@ -0,0 +1,65 @@
+mod common;
+
+use rdx_core::codec::{encode, encode_as, CodecError, CodecFormat, Envelope, MIGRATIONS, PAYLOAD_VERSION};
+use rdx_core::model::Agent;
+use rdx_core::sim::init_agents;
+
+fn agent() -> Agent {
+    init_agents(&common::small_config()).unwrap().agents.remove(0)
+}
+
+fn same(a: &Agent, b: &Agent) -> bool {
+    a.id == b.id && a.beta.iter().zip(&b.beta).all(|(x, y)| (x - y).abs() <= 1e-15 * x.abs().max(1.0))
+}
+
+#[test]
+fn sealed_payloads_open_in_every_enabled_format() {
+    let agent = agent();
+    assert_eq!(MIGRATIONS.len(), PAYLOAD_VERSION as usize);
+    for format in CodecFormat::ALL.into_iter().filter(|f| f.is_enabled()) {
+        let sealed = Envelope::seal(format, &agent).unwrap();
+        assert_eq!((sealed.version, sealed.format), (PAYLOAD_VERSION, format));
+        let bytes = sealed.to_bytes();
+        let back = Envelope::from_bytes(&bytes).unwrap();
+        assert_eq!(back, sealed);
+        assert!(same(&back.open::<Agent>().unwrap(), &agent), "{format}");
+    }
+}
+
+#[test]
+fn older_payloads_are_migrated() {
+    let agent = agent();
+    let legacy = Envelope::from_bytes(&encode(&agent).unwrap()).unwrap();
+    assert_eq!((legacy.version, legacy.format), (0, CodecFormat::Json));
+    assert!(same(&legacy.open::<Agent>().unwrap(), &agent));
+
+    let tagged = Envelope::from_bytes(&encode_as(CodecFormat::Bincode, &agent).unwrap()).unwrap();
+    assert_eq!((tagged.version, tagged.format), (1, CodecFormat::Bincode));
+    assert!(same(&tagged.open::<Agent>().unwrap(), &agent));
+
+    let mut old = Envelope::seal(CodecFormat::Bincode, &agent).unwrap();
+    old.version = 0;
+    assert!(matches!(
+        old.open::<Agent>(),
+        Err(CodecError::NotMigratable { format: CodecFormat::Bincode, version: 0 })
+    ));
+}
+
+#[test]
+fn future_and_truncated_payloads_are_rejected() {
+    let mut future = Envelope::seal(CodecFormat::Json, &agent()).unwrap();
+    future.version = PAYLOAD_VERSION + 1;
+    let expected = |r: Result<_, CodecError>| {
+        matches!(r, Err(CodecError::UnsupportedVersion { found, supported: PAYLOAD_VERSION })
+            if found == PAYLOAD_VERSION + 1)
+    };
+    assert!(expected(future.open::<Agent>().map(|_| ())));
+    assert!(expected(Envelope::from_bytes(&future.to_bytes()).map(|_| ())));
+
+    let mut unknown = future.to_bytes();
+    unknown[5] = 0x7f;
+    assert!(expected(Envelope::from_bytes(&unknown).map(|_| ())), "the version is checked first");
+
+    assert!(matches!(Envelope::from_bytes(&[]), Err(CodecError::Empty)));
+    assert!(matches!(Envelope::from_bytes(&[0x00, 1, 0]), Err(CodecError::Truncated)));
+}
@@ -0,0 +1,78 @@
+mod common;
+
+use rdx_core::pareto_oracle::CobbDouglasWalrasOracle;
+use rdx_core::sim::init_agents;
+use rdx_core::trade::{evaluate_exchange, evaluate_pairwise_trade};
+
+#[test]
+fn payload_level_evaluation_matches_the_agent_one() {
+    let cfg = common::small_config();
+    let mut agents = init_agents(&cfg).unwrap().agents;
+    for a in agents.iter_mut() {
+        a.alpha_to_base.clear();
+    }
+    let oracle = CobbDouglasWalrasOracle;
+    let (n, iters) = (cfg.base_goods.len(), cfg.oracle_bisect_iters);
+    let mut found = 0;
+    for pair in agents.windows(2).take(6) {
+        let (i, j) = (&pair[0], &pair[1]);
+        for a in 0..n {
+            for b in 0..n {
+                let by_agent = evaluate_pairwise_trade(i, j, a, b, 0, cfg.min_qty, iters, &oracle);
+                let by_payload = evaluate_exchange(&i.beta, &i.e, &j.beta, &j.e, a, b, cfg.min_qty, iters, &oracle);
+                assert_eq!(by_agent.is_some(), by_payload.is_some(), "pair ({a}, {b})");
+                if let (Some(x), Some(y)) = (by_agent, by_payload) {
+                    found += 1;
+                    assert_eq!(x.q_ab.to_bits(), y.q_ab.to_bits());
+                    assert_eq!(x.delta_a_i.to_bits(), y.delta_a_i.to_bits());
+                    assert_eq!(x.delta_u_j.to_bits(), y.delta_u_j.to_bits());
+                }
+            }
+        }
+    }
+    assert!(found > 0);
+}
+
+#[test]
+fn malformed_payloads_yield_no_trade() {
+    let oracle = CobbDouglasWalrasOracle;
+    let beta = [0.5, 0.5];
+    assert!(evaluate_exchange(&beta, &[1.0, 2.0], &beta, &[2.0, 1.0], 0, 0, 1e-9, 40, &oracle).is_none());
+    assert!(evaluate_exchange(&beta, &[1.0, 2.0], &beta, &[2.0, 1.0], 0, 2, 1e-9, 40, &oracle).is_none());
+    assert!(evaluate_exchange(&beta, &[1.0, 2.0], &beta, &[2.0], 0, 1, 1e-9, 40, &oracle).is_none());
+    let ex = evaluate_exchange(&[0.8, 0.2], &[1.0, 2.0], &[0.2, 0.8], &[2.0, 1.0], 0, 1, 1e-9, 40, &oracle);
+    assert!(ex.is_some_and(|c| c.delta_a_i > 0.0 && c.delta_u_i > 0.0 && c.delta_u_j > 0.0));
+}
+
+#[test]
+fn utility_deltas_match_full_bundle_recomputation() {
+    use rdx_core::preferences::cd_utility;
+    // many goods, so a clone-and-recompute error would show
+    let n = 256;
+    let beta_i: Vec<f64> = (0..n).map(|k| 1.0 + (k % 7) as f64).collect();
+    let beta_j: Vec<f64> = (0..n).map(|k| 1.0 + (k % 5) as f64).collect();
+    let norm = |b: Vec<f64>| {
+        let s: f64 = b.iter().sum();
+        b.into_iter().map(|x| x / s).collect::<Vec<_>>()
+    };
+    let (beta_i, beta_j) = (norm(beta_i), norm(beta_j));
+    let e_i: Vec<f64> = (0..n).map(|k| 0.5 + (k % 3) as f64).collect();
+    let e_j: Vec<f64> = (0..n).map(|k| 0.5 + (k % 4) as f64).collect();
+    let oracle = CobbDouglasWalrasOracle;
+    let mut found = 0;
+    for (a, b) in [(1, 0), (2, 5), (100, 3), (255, 17)] {
+        let Some(c) = evaluate_exchange(&beta_i, &e_i, &beta_j, &e_j, a, b, 1e-9, 60, &oracle) else { continue };
+        found += 1;
+        let moved = |e: &[f64], da: f64, db: f64| {
+            let mut x = e.to_vec();
+            x[a] += da;
+            x[b] += db;
+            x
+        };
+        let ui = cd_utility(&beta_i, &moved(&e_i, c.delta_a_i, c.delta_b_i), 1e-9) - cd_utility(&beta_i, &e_i, 1e-9);
+        let uj = cd_utility(&beta_j, &moved(&e_j, -c.delta_a_i, -c.delta_b_i), 1e-9) - cd_utility(&beta_j, &e_j, 1e-9);
+        assert!((ui - c.delta_u_i).abs() < 1e-12, "({a}, {b}): {ui} vs {}", c.delta_u_i);
+        assert!((uj - c.delta_u_j).abs() < 1e-12, "({a}, {b}): {uj} vs {}", c.delta_u_j);
+    }
+    assert!(found > 0);
+}
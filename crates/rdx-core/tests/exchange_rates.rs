@@ -0,0 +1,67 @@
+use rdx_core::ids::{AgentIdx, GoodId};
+use rdx_core::model::TradeEvent;
+use rdx_core::sim::exchange_rate_stats;
+
+fn event(good_a: usize, good_b: usize, q_ab: f64, delta_a_i: f64, delta_b_i: f64) -> TradeEvent {
+    TradeEvent {
+        round: 3, i: AgentIdx(0), j: AgentIdx(1), good_a: GoodId(good_a), good_b: GoodId(good_b), q_ab,
+        delta_a_i, delta_b_i, delta_u_i: 0.1, delta_u_j: 0.1, id_i: 0, id_j: 1, time: None,
+        cost_i: 0.0, cost_j: 0.0, tax: 0.0, speculative: false,
+        seq: 0, pre_i: [0; 32], pre_j: [0; 32], post_i: [0; 32], post_j: [0; 32],
+    }
+}
+
+fn close(a: f64, b: f64) -> bool {
+    (a - b).abs() < 1e-12
+}
+
+#[test]
+fn base_good_trades_are_folded_into_prices_versus_the_base() {
+    let events = [
+        event(1, 0, 2.0, 1.0, -2.0),
+        // recorded as (base, 1): 0.25 of good 1 per unit of base, i.e. 4 base per unit of good 1
+        event(0, 1, 0.25, -12.0, 3.0),
+    ];
+    let stats = exchange_rate_stats(3, &events, 0);
+    assert_eq!(stats.len(), 1);
+    let s = &stats[0];
+    assert_eq!((s.round, s.good_a, s.good_b, s.trades), (3, GoodId(1), GoodId(0), 2));
+    assert_eq!(s.volume_a, 4.0);
+    // weighted by the good-1 volumes 1 and 3
+    assert!(close(s.mean_q_ab, 3.5), "{}", s.mean_q_ab);
+    assert!(close(s.std_q_ab, 0.75f64.sqrt()), "{}", s.std_q_ab);
+    assert_eq!((s.min_q_ab, s.max_q_ab), (2.0, 4.0));
+
+    // with another base the same trades keep their recorded orientation
+    let stats = exchange_rate_stats(3, &events, 2);
+    let pairs: Vec<_> = stats.iter().map(|s| (s.good_a, s.good_b, s.mean_q_ab)).collect();
+    assert_eq!(pairs, [(GoodId(0), GoodId(1), 0.25), (GoodId(1), GoodId(0), 2.0)]);
+}
+
+#[test]
+fn zero_volume_pairs_fall_back_to_unweighted_rates() {
+    let events = [event(2, 1, 1.0, 0.0, 0.0), event(2, 1, 3.0, 0.0, 0.0), event(2, 1, 2.0, -0.0, 0.0)];
+    let stats = exchange_rate_stats(0, &events, 0);
+    assert_eq!(stats.len(), 1);
+    let s = &stats[0];
+    assert_eq!((s.trades, s.volume_a), (3, 0.0));
+    assert!(close(s.mean_q_ab, 2.0));
+    assert!(close(s.std_q_ab, (2.0f64 / 3.0).sqrt()));
+    assert_eq!((s.min_q_ab, s.max_q_ab), (1.0, 3.0));
+}
+
+#[test]
+fn identical_rates_have_no_dispersion_and_unpriced_trades_are_skipped() {
+    let events = [
+        event(1, 2, 0.5, 2.0, -1.0),
+        event(1, 2, 0.5, -6.0, 3.0),
+        // inverting a zero rate leaves nothing to average
+        event(0, 3, 0.0, 1.0, 0.0),
+    ];
+    let stats = exchange_rate_stats(0, &events, 0);
+    assert_eq!(stats.len(), 1);
+    let s = &stats[0];
+    assert_eq!((s.good_a, s.good_b, s.trades, s.volume_a), (GoodId(1), GoodId(2), 2, 8.0));
+    assert_eq!((s.mean_q_ab, s.std_q_ab), (0.5, 0.0));
+    assert!(exchange_rate_stats(0, &[], 0).is_empty());
+}
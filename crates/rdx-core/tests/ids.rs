@@ -0,0 +1,18 @@
+use rdx_core::ids::{AgentIdx, GoodId};
+use rdx_core::model::TradeEvent;
+
+#[test]
+fn newtypes_serialize_as_plain_integers() {
+    let json = r#"{"round":2,"i":5,"j":9,"good_a":3,"good_b":0,"q_ab":1.5,
+        "delta_a_i":0.1,"delta_b_i":-0.15,"delta_u_i":0.01,"delta_u_j":0.02}"#;
+    let ev: TradeEvent = serde_json::from_str(json).expect("pre-newtype event json");
+    assert_eq!((ev.i, ev.j), (AgentIdx(5), AgentIdx(9)));
+    assert_eq!((ev.good_a, ev.good_b), (GoodId(3), GoodId(0)));
+    assert!(ev.time.is_none());
+
+    let v = serde_json::to_value(&ev).unwrap();
+    assert_eq!(v["i"], 5);
+    assert_eq!(v["good_a"], 3);
+    assert_eq!(GoodId::from(4).index(), 4);
+    assert_eq!(AgentIdx(7).to_string(), "7");
+}
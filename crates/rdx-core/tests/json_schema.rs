@@ -0,0 +1,20 @@
+use rdx_core::model::SimConfig;
+
+#[test]
+fn schema_covers_the_config_surface() {
+    let schema = SimConfig::json_schema();
+    let props = schema["properties"].as_object().expect("properties");
+    for field in ["seed", "base_goods", "endowment", "pairing_mode", "preference_generator", "consumption"] {
+        assert!(props.contains_key(field), "missing {field}");
+    }
+
+    // fields with serde defaults are optional
+    let required: Vec<&str> = schema["required"].as_array().unwrap().iter().map(|v| v.as_str().unwrap()).collect();
+    assert!(required.contains(&"seed") && required.contains(&"min_qty"));
+    assert!(!required.contains(&"consumption") && !required.contains(&"pairing_mode"));
+
+    let definitions = schema["definitions"].as_object().expect("definitions");
+    assert!(definitions.contains_key("EndowmentSpec") && definitions.contains_key("ReactionRuleSpec"));
+    let doc = props["endowment"]["description"].as_str().unwrap_or_default();
+    assert!(doc.contains("initial holdings"), "{doc}");
+}
@@ -0,0 +1,49 @@
+mod common;
+
+use rand::prelude::*;
+use rdx_core::model::{Agent, PairingMode};
+use rdx_core::sim::{init_agents, run};
+use rdx_core::trade::{learn_offer_value, learned_candidate_goods, TradeRules};
+
+fn agent(q_values: Vec<f64>) -> Agent {
+    Agent { e: vec![1.0; 5], beta: vec![0.2; 5], alpha_to_base: vec![0.5; 5], q_values, ..Default::default() }
+}
+
+#[test]
+fn greedy_selection_follows_learned_values() {
+    let i = agent(vec![0.0, 0.1, 0.0, 0.5, 0.0]);
+    let j = agent(vec![0.0, 0.3, 0.0, 0.0, 0.0]);
+    let mut rng = StdRng::seed_from_u64(1);
+    assert_eq!(learned_candidate_goods(&i, &j, 0, 2, 0.0, &TradeRules::default(), &mut rng), vec![3, 1]);
+
+    // blocked goods are never drawn, and the base never is
+    let rules = TradeRules { blocked: vec![false, false, false, true, false], ..Default::default() };
+    for _ in 0..50 {
+        let goods = learned_candidate_goods(&i, &j, 0, 3, 1.0, &rules, &mut rng);
+        assert_eq!(goods.len(), 3);
+        assert!(!goods.contains(&0) && !goods.contains(&3));
+    }
+
+    let mut a = agent(Vec::new());
+    learn_offer_value(&mut a, 2, 1.0, 0.5);
+    learn_offer_value(&mut a, 2, 1.0, 0.5);
+    assert_eq!(a.q_values, vec![0.0, 0.0, 0.75, 0.0, 0.0]);
+}
+
+#[test]
+fn agents_learn_from_realized_gains() {
+    let mut cfg = common::small_config();
+    cfg.candidate_goods_k = 2;
+    cfg.pairing_mode = PairingMode::Learning { epsilon: 0.2, learning_rate: 0.5 };
+    let mut state = init_agents(&cfg).expect("init");
+    run(&cfg, &mut state).expect("run");
+    assert!(!state.events.is_empty());
+    // only goods given away in a trade carry value, and gains are positive
+    for a in state.agents.iter() {
+        assert!(a.q_values.iter().all(|&q| q >= 0.0));
+    }
+    assert!(state.agents.iter().any(|a| a.q_values.iter().any(|&q| q > 0.0)));
+
+    cfg.pairing_mode = PairingMode::Learning { epsilon: 1.5, learning_rate: 0.5 };
+    assert!(init_agents(&cfg).is_err());
+}
@@ -0,0 +1,100 @@
+mod common;
+
+use rdx_core::log::{validate, Chaining, LogError};
+use rdx_core::model::{ConsumptionSpec, PairingMode, PostedPriceSpec, SimConfig};
+use rdx_core::sim::{init_agents, run, SimState};
+use rdx_core::state::leaf_hash;
+
+fn finished(cfg: &SimConfig) -> SimState {
+    let mut state = init_agents(cfg).unwrap();
+    run(cfg, &mut state).unwrap();
+    assert!(!state.events.is_empty());
+    state
+}
+
+#[test]
+fn engine_logs_chain_across_the_run() {
+    let state = finished(&common::small_config());
+    for (k, ev) in state.events.iter().enumerate() {
+        assert_eq!(ev.seq, k as u64);
+    }
+    let summary = validate(&state.events, Chaining::Run).expect("valid log");
+    assert_eq!(summary.events, state.events.len());
+    assert_eq!(summary.last_seq, Some(state.events.len() as u64 - 1));
+
+    // without between-round dynamics, the chain heads are the final holdings
+    for (id, head) in &summary.heads {
+        let agent = state.agents.iter().find(|a| a.id == *id).unwrap();
+        assert_eq!(*head, leaf_hash(agent.id, &agent.e));
+    }
+
+    // any contiguous slice validates on its own
+    let half = state.events.len() / 2;
+    assert!(validate(&state.events[half..], Chaining::Run).is_ok());
+}
+
+#[test]
+fn bundle_and_market_trades_chain_within_rounds() {
+    let mut cfg = common::small_config();
+    cfg.pairing_mode = PairingMode::Bundle;
+    validate(&finished(&cfg).events, Chaining::Run).expect("bundle legs chain");
+
+    let mut cfg = common::small_config();
+    cfg.posted_prices = Some(PostedPriceSpec::default());
+    validate(&finished(&cfg).events, Chaining::Run).expect("sales chain");
+}
+
+#[test]
+fn dynamics_break_chains_only_between_rounds() {
+    let mut cfg = common::small_config();
+    cfg.consumption = Some(ConsumptionSpec { rate: 0.1, income: 0.1 });
+    let state = finished(&cfg);
+    validate(&state.events, Chaining::Round).expect("valid within rounds");
+    assert!(matches!(validate(&state.events, Chaining::Run), Err(LogError::ChainBreak { .. })));
+}
+
+#[test]
+fn tampering_is_detected() {
+    let state = finished(&common::small_config());
+    let events = &state.events;
+
+    // dropped event: a gap in the sequence
+    let mut dropped = events.clone();
+    dropped.remove(1);
+    assert!(matches!(
+        validate(&dropped, Chaining::Run),
+        Err(LogError::SequenceGap { position: 1, expected: 1, found: 2 })
+    ));
+
+    // dropped and renumbered: the agent's next trade no longer follows from its previous one
+    let involves = |k: usize, id: u64| events[k].id_i == id || events[k].id_j == id;
+    let traded_around = |k: usize| {
+        let id = events[k].id_i;
+        (0..k).any(|m| involves(m, id)) && (k + 1..events.len()).any(|m| involves(m, id))
+    };
+    let k = (1..events.len()).find(|&k| traded_around(k)).expect("an agent trading before and after some event");
+    let mut renumbered = events.clone();
+    renumbered.remove(k);
+    for (n, ev) in renumbered.iter_mut().enumerate() {
+        ev.seq = n as u64;
+    }
+    assert!(matches!(validate(&renumbered, Chaining::Run), Err(LogError::ChainBreak { .. })));
+
+    // a forged post-trade hash
+    let mut forged = events.clone();
+    forged[k].post_i = [7; 32];
+    assert!(matches!(validate(&forged, Chaining::Run), Err(LogError::ChainBreak { .. })));
+
+    // an edited exchange in which one side receives both goods
+    let mut edited = events.clone();
+    edited[2].delta_a_i = edited[2].delta_a_i.abs();
+    edited[2].delta_b_i = edited[2].delta_b_i.abs();
+    assert!(matches!(validate(&edited, Chaining::Off), Err(LogError::Malformed { seq: 2, .. })));
+
+    // rounds never go back
+    let mut reordered = events.clone();
+    let last = reordered.len() - 1;
+    reordered[last].round = 0;
+    reordered[0].round = 1;
+    assert!(validate(&reordered, Chaining::Off).is_err());
+}
@@ -0,0 +1,50 @@
+mod common;
+
+use rdx_core::ids::GoodId;
+use rdx_core::market::{post_listings, shop, Budget};
+use rdx_core::model::PostedPriceSpec;
+use rdx_core::sim::{init_agents, run};
+use rdx_core::trade::TradeRules;
+
+#[test]
+fn sellers_post_excess_and_buyers_shop_greedily() {
+    let mut agents = vec![
+        common::agent(vec![1.0, 4.0, 1.0], vec![0.4, 0.2, 0.4]),
+        common::agent(vec![1.0, 1.0, 4.0], vec![0.4, 0.4, 0.2]),
+        common::agent(vec![4.0, 1.0, 1.0], vec![0.2, 0.4, 0.4]),
+    ];
+    let mut listings = post_listings(&agents, 0, 0.1, 0.5, 1e-9, &TradeRules::default());
+    // only the holdings above the mean (2) are listed, never the base good
+    assert_eq!(listings.len(), 2);
+    assert_eq!((listings[0].seller, listings[0].good, listings[0].quantity), (0, GoodId(1), 1.0));
+    assert!((listings[0].price - 0.5 * 0.25 * 1.1).abs() < 1e-12);
+
+    let totals: Vec<f64> = (0..3).map(|g| agents.iter().map(|a| a.e[g]).sum()).collect();
+    let mut budget = Budget { limit: None, spent: &mut [], refused: 0 };
+    let sales = shop(&mut agents, &mut listings, 2, 0, 1e-9, &mut budget);
+    assert!(!sales.is_empty());
+    for s in sales.iter() {
+        assert_eq!(s.buyer, 2);
+        assert!(s.delta_u_buyer > 0.0 && s.delta_u_seller > 0.0);
+    }
+    assert!(listings.iter().all(|l| l.quantity >= 0.0));
+    for (g, t) in totals.iter().enumerate() {
+        assert!((agents.iter().map(|a| a.e[g]).sum::<f64>() - t).abs() < 1e-9);
+    }
+}
+
+#[test]
+fn marketplace_phase_runs_alone_or_after_encounters() {
+    let mut cfg = common::small_config();
+    cfg.posted_prices = Some(PostedPriceSpec::default());
+    cfg.p2p_encounters_per_round = 0;
+    let mut state = init_agents(&cfg).expect("init");
+    run(&cfg, &mut state).expect("run");
+    let sales: usize = state.metrics.iter().map(|m| m.market_sales).sum();
+    assert!(sales > 0 && sales == state.events.len());
+    assert!(state.events.iter().all(|e| e.delta_u_i > 0.0 && e.delta_u_j > 0.0));
+    assert!(state.metrics.iter().all(|m| m.listings > 0 || m.market_sales == 0));
+
+    cfg.posted_prices = Some(PostedPriceSpec { markup: 0.1, lot: 0.0 });
+    assert!(init_agents(&cfg).is_err());
+}
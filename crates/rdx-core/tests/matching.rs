@@ -0,0 +1,77 @@
+use rand::prelude::*;
+use rdx_core::matching::{wealth_quantiles, Matcher, PartnerGraph};
+use rdx_core::model::{Agent, MatchingMode};
+
+fn agent_with_wealth(w: f64) -> Agent {
+    Agent {
+        e: vec![w / 2.0, w / 2.0],
+        beta: vec![0.5, 0.5],
+        alpha_to_base: vec![0.5, 0.5],
+        reaction_rules: Vec::new(),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn quantiles_order_by_wealth() {
+    let agents: Vec<Agent> = [4.0, 1.0, 3.0, 2.0].iter().map(|&w| agent_with_wealth(w)).collect();
+    let q = wealth_quantiles(&agents, 2);
+    assert_eq!(q, vec![1, 0, 1, 0]);
+}
+
+#[test]
+fn segregated_mixing_keeps_encounters_within_quantile() {
+    let agents: Vec<Agent> = (1..=20).map(|w| agent_with_wealth(w as f64)).collect();
+    let mode = MatchingMode::Quantile {
+        quantiles: 2,
+        mixing: vec![vec![1.0, 0.0], vec![0.0, 1.0]],
+    };
+    let matcher = Matcher::for_round(&mode, &agents, None, None);
+    let buckets = wealth_quantiles(&agents, 2);
+
+    let mut rng = StdRng::seed_from_u64(7);
+    for _ in 0..500 {
+        let (i, j) = matcher.draw_pair(&mut rng).expect("population of 20");
+        assert_ne!(i, j);
+        assert_eq!(buckets[i], buckets[j]);
+    }
+}
+
+#[test]
+fn partner_graph_is_symmetric_and_decays() {
+    let mut g = PartnerGraph::default();
+    g.reinforce(3, 1, 2.0);
+    g.reinforce(1, 3, 1.0);
+    g.reinforce(1, 0, 0.5);
+    assert_eq!(g.weight(1, 3), 3.0);
+    assert_eq!(g.weight(3, 1), 3.0);
+    assert_eq!(g.strength(1), 3.5);
+    assert_eq!(g.edges(), vec![(0, 1, 0.5), (1, 3, 3.0)]);
+
+    g.decay(0.5);
+    assert_eq!(g.weight(1, 3), 1.5);
+    g.decay(1.0);
+    assert!(g.edges().is_empty());
+}
+
+#[test]
+fn persistent_matching_concentrates_on_past_partners() {
+    let agents: Vec<Agent> = (0..50).map(|_| agent_with_wealth(2.0)).collect();
+    let mut partners = PartnerGraph::default();
+    partners.reinforce(0, 1, 1000.0);
+    let mode = MatchingMode::Persistent { reinforcement: 1.0, baseline: 1.0, decay: 0.0 };
+    let matcher = Matcher::for_round(&mode, &agents, None, Some(&partners));
+
+    let mut rng = StdRng::seed_from_u64(11);
+    let (mut from_zero, mut with_one) = (0, 0);
+    for _ in 0..5000 {
+        let (i, j) = matcher.draw_pair(&mut rng).expect("population of 50");
+        assert_ne!(i, j);
+        if i == 0 {
+            from_zero += 1;
+            if j == 1 { with_one += 1; }
+        }
+    }
+    // weight 1000 against 49 partners at baseline 1: ~95% of agent 0's encounters
+    assert!(with_one as f64 > 0.85 * from_zero as f64);
+}
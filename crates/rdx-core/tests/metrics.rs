@@ -0,0 +1,52 @@
+mod common;
+
+use rdx_core::metrics::{gini, pareto_gap, quantile, theil};
+use rdx_core::sim::{init_agents, run};
+
+#[test]
+fn inequality_indices_on_known_distributions() {
+    assert_eq!(gini(&[2.0, 2.0, 2.0]), 0.0);
+    assert_eq!(theil(&[2.0, 2.0, 2.0]), 0.0);
+    assert_eq!(gini(&[]), 0.0);
+    assert_eq!(theil(&[0.0, 0.0]), 0.0);
+
+    // one agent holds everything: G = (n-1)/n, T = ln n
+    let v = [0.0, 0.0, 0.0, 8.0];
+    assert!((gini(&v) - 0.75).abs() < 1e-12);
+    assert!((theil(&v) - 4f64.ln()).abs() < 1e-12);
+
+    // order does not matter
+    assert!((gini(&[3.0, 1.0, 2.0]) - gini(&[1.0, 2.0, 3.0])).abs() < 1e-15);
+    assert!((gini(&[1.0, 2.0, 3.0]) - 2.0 / 9.0).abs() < 1e-12);
+
+    assert_eq!(quantile(&[1.0, 2.0, 3.0, 4.0, 5.0], 0.5), 3.0);
+    assert_eq!(quantile(&[0.0, 10.0], 0.25), 2.5);
+}
+
+#[test]
+fn every_round_records_inequality() {
+    let cfg = common::small_config();
+    let mut state = init_agents(&cfg).expect("init");
+    run(&cfg, &mut state).expect("run");
+    for m in state.metrics.iter() {
+        let q = m.inequality.utility_quantiles;
+        assert!(m.inequality.gini_wealth > 0.0 && m.inequality.gini_wealth < 1.0);
+        assert!(m.inequality.theil_wealth >= 0.0);
+        assert!(q.windows(2).all(|w| w[0] <= w[1]));
+    }
+}
+
+#[test]
+fn pareto_gap_is_non_negative_and_shrinks_with_trade() {
+    let cfg = common::small_config();
+    let mut state = init_agents(&cfg).expect("init");
+    let initial = pareto_gap(&state, &cfg);
+    assert!(initial > 0.0);
+    run(&cfg, &mut state).expect("run");
+    for m in state.metrics.iter() {
+        assert!(m.pareto_gap >= -1e-9, "round {}: {}", m.round, m.pareto_gap);
+    }
+    let last = state.metrics.last().expect("metrics").pareto_gap;
+    assert!((last - pareto_gap(&state, &cfg)).abs() < 1e-12);
+    assert!(last < initial, "{last} >= {initial}");
+}
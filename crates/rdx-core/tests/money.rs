@@ -0,0 +1,47 @@
+mod common;
+
+use rdx_core::model::{Agent, MonetarySpec};
+use rdx_core::money::{draw_credit, repay};
+use rdx_core::sim::{init_agents, run};
+
+#[test]
+fn credit_lines_bound_negative_balances() {
+    let mut a = Agent { e: vec![0.2, 1.0], ..Default::default() };
+    assert_eq!(draw_credit(&mut a, 0.5, 0), 0.5);
+    assert_eq!((a.e[0], a.debt), (0.7, 0.5));
+    // a drawn line cannot be drawn twice
+    assert_eq!(draw_credit(&mut a, 0.5, 0), 0.0);
+
+    // spend everything: net balance sits at the limit
+    a.e[0] = 0.0;
+    assert_eq!(repay(&mut a, 1.0, 0, 0.0), 0.0);
+    assert_eq!(a.e[0] - a.debt, -0.5);
+
+    // income arrives: repay a share, then the rest
+    a.e[0] = 1.0;
+    assert_eq!(repay(&mut a, 0.25, 0, 0.0), 0.25);
+    assert_eq!(repay(&mut a, 1.0, 0, 0.0), 0.25);
+    assert_eq!((a.e[0], a.debt), (0.5, 0.0));
+}
+
+#[test]
+fn monetary_runs_report_velocity_and_utilization() {
+    let mut cfg = common::small_config();
+    cfg.monetary = Some(MonetarySpec { credit_limit: 0.5, repayment_rate: 0.5 });
+    let mut state = init_agents(&cfg).expect("init");
+    run(&cfg, &mut state).expect("run");
+
+    let first = &state.metrics[0];
+    assert!((first.credit_drawn - 0.5 * cfg.num_agents as f64).abs() < 1e-9);
+    assert!(state.metrics.iter().any(|m| m.money_velocity > 0.0));
+    for m in state.metrics.iter() {
+        assert!((0.0..=1.0).contains(&m.credit_utilization));
+    }
+    for a in state.agents.iter() {
+        assert!(a.debt >= 0.0 && a.debt <= 0.5 + 1e-12);
+        assert!(a.e[cfg.base_good] - a.debt >= -0.5 - 1e-12);
+    }
+
+    cfg.monetary = Some(MonetarySpec { credit_limit: -1.0, repayment_rate: 0.5 });
+    assert!(init_agents(&cfg).is_err());
+}
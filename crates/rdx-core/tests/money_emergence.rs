@@ -0,0 +1,52 @@
+mod common;
+
+use rdx_core::model::MoneyEmergenceSpec;
+use rdx_core::sim::{init_agents, run};
+use rdx_core::trade::{best_speculative_trade, default_oracle, Marketability, TradeRules};
+
+#[test]
+fn resale_value_alone_cannot_create_a_trade() {
+    let oracle = default_oracle();
+    let i = common::agent(vec![1.0, 2.0, 3.0], vec![0.2, 0.3, 0.5]);
+    let j = common::agent(vec![3.0, 1.0, 1.0], vec![0.6, 0.2, 0.2]);
+    assert!(best_speculative_trade(&i, &j, 1e-9, 60, &oracle, &TradeRules::default()).is_none());
+
+    let rules = TradeRules {
+        speculation: Some(Marketability {
+            share: vec![1.0, 0.5, 0.5],
+            mean_holding: vec![1.0; 3],
+            weight: 5.0,
+            step: 0.2,
+        }),
+        ..Default::default()
+    };
+    // without resale weight only direct, mutual gains are accepted
+    let direct = TradeRules {
+        speculation: rules.speculation.clone().map(|m| Marketability { weight: 0.0, ..m }),
+        ..Default::default()
+    };
+    let cand = best_speculative_trade(&i, &j, 1e-9, 60, &oracle, &direct).expect("gains from trade exist");
+    assert!(cand.delta_u_i > 0.0 && cand.delta_u_j > 0.0 && !cand.speculative);
+
+    // identical agents have nothing to gain; resale value is zero-sum within the dyad
+    assert!(best_speculative_trade(&i, &i.clone(), 1e-9, 60, &oracle, &rules).is_none());
+}
+
+#[test]
+fn marketability_is_tracked_per_round() {
+    let mut cfg = common::small_config();
+    cfg.money_emergence = Some(MoneyEmergenceSpec::default());
+    let mut state = init_agents(&cfg).expect("init");
+    run(&cfg, &mut state).expect("run");
+    assert!(!state.events.is_empty());
+    for m in state.metrics.iter() {
+        assert_eq!(m.marketability.len(), 5);
+        assert!((m.marketability.iter().sum::<f64>() - 2.0).abs() < 1e-9);
+        if let Some(g) = m.money_good {
+            assert!(m.marketability.iter().all(|&s| s <= m.marketability[g.index()]));
+        }
+    }
+
+    cfg.money_emergence = Some(MoneyEmergenceSpec { step: 0.0, ..Default::default() });
+    assert!(init_agents(&cfg).is_err());
+}
@@ -0,0 +1,42 @@
+mod common;
+
+use rdx_core::counterfactual::information_loss;
+use rdx_core::model::NegotiationSpec;
+use rdx_core::trade::{best_negotiated_trade, negotiate_trade, TradeRules};
+
+#[test]
+fn shading_widens_the_spread() {
+    // MRS of good 1 in base units: 0.5 for i, 2 for j
+    let i = common::agent(vec![1.0, 2.0], vec![0.5, 0.5]);
+    let j = common::agent(vec![2.0, 1.0], vec![0.5, 0.5]);
+    let truthful = negotiate_trade(&i, &j, 1, 0, 1.0, 1e-9).expect("quotes cross");
+    assert!((truthful.q_ab - 1.0).abs() < 1e-12);
+    assert!(truthful.delta_a_i < 0.0 && truthful.delta_u_i > 0.0 && truthful.delta_u_j > 0.0);
+
+    let shaded = negotiate_trade(&i, &j, 1, 0, 0.5, 1e-9).expect("bid 1.6 covers ask 0.625");
+    assert!(shaded.delta_u_i > 0.0 && shaded.delta_u_j > 0.0);
+    // ask 0.75 and bid 1.33 still cross at zero disclosure; a smaller MRS gap does not
+    assert!(negotiate_trade(&i, &j, 1, 0, 0.0, 1e-9).is_some());
+    let close = common::agent(vec![1.0, 1.0], vec![0.5, 0.5]);
+    assert!(negotiate_trade(&i, &close, 1, 0, 1.0, 1e-9).is_some());
+    assert!(negotiate_trade(&i, &close, 1, 0, 0.0, 1e-9).is_none());
+
+    let rules = TradeRules { blocked: vec![false, true], ..Default::default() };
+    assert!(best_negotiated_trade(&i, &j, 0, 1.0, 1e-9, &rules).is_none());
+}
+
+#[test]
+fn less_disclosure_loses_more_welfare() {
+    let mut cfg = common::small_config();
+    let mut losses = Vec::new();
+    for disclosure in [1.0, 0.2] {
+        cfg.negotiation = Some(NegotiationSpec { disclosure });
+        let report = information_loss(&cfg).expect("report");
+        assert!(report.full_information > 0.0 && report.negotiated > 0.0);
+        losses.push(report.efficiency_loss);
+    }
+    assert!(losses[1] > losses[0], "{losses:?}");
+
+    cfg.negotiation = Some(NegotiationSpec { disclosure: 1.5 });
+    assert!(information_loss(&cfg).is_err());
+}
@@ -0,0 +1,48 @@
+mod common;
+
+use rand::prelude::*;
+use rdx_core::model::{MatchingMode, NetworkSpec};
+use rdx_core::network::{barabasi_albert, generate, watts_strogatz};
+use rdx_core::sim::Engine;
+
+#[test]
+fn unrewired_small_world_is_a_regular_ring() {
+    let g = watts_strogatz(30, 4, 0.0, &mut StdRng::seed_from_u64(1)).expect("valid");
+    assert_eq!(g.edges().len(), 60);
+    assert!((0..30).all(|i| g.degree(i) == 4));
+    assert!(g.contains_edge(0, 29) && g.contains_edge(0, 28) && !g.contains_edge(0, 3));
+}
+
+#[test]
+fn scale_free_graph_has_expected_edge_count() {
+    let (n, m) = (200, 3);
+    let g = barabasi_albert(n, m, &mut StdRng::seed_from_u64(2)).expect("valid");
+    assert_eq!(g.edges().len(), m * (m + 1) / 2 + (n - m - 1) * m);
+    assert!((0..n).all(|i| g.degree(i) >= m));
+    assert!((0..n).map(|i| g.degree(i)).max().unwrap_or(0) > 4 * m);
+}
+
+#[test]
+fn generation_is_seeded_and_validated() {
+    let spec = NetworkSpec::ErdosRenyi { p: 0.1 };
+    let a = generate(&spec, 50, &mut StdRng::seed_from_u64(3)).expect("valid");
+    let b = generate(&spec, 50, &mut StdRng::seed_from_u64(3)).expect("valid");
+    assert_eq!(a.edges(), b.edges());
+
+    assert!(generate(&NetworkSpec::ErdosRenyi { p: 1.5 }, 10, &mut StdRng::seed_from_u64(0)).is_err());
+    assert!(generate(&NetworkSpec::WattsStrogatz { k: 3, beta: 0.1 }, 10, &mut StdRng::seed_from_u64(0)).is_err());
+    assert!(generate(&NetworkSpec::BarabasiAlbert { m: 10 }, 10, &mut StdRng::seed_from_u64(0)).is_err());
+}
+
+#[test]
+fn trades_only_happen_along_graph_edges() {
+    let mut cfg = common::small_config();
+    cfg.matching = MatchingMode::Network(NetworkSpec::WattsStrogatz { k: 4, beta: 0.2 });
+
+    let mut engine = Engine::new(cfg).expect("engine");
+    engine.run_to_end();
+    let graph = engine.network().expect("network mode builds a graph").clone();
+    let state = engine.finish();
+    assert!(!state.events.is_empty());
+    assert!(state.events.iter().all(|ev| graph.contains_edge(ev.i.index(), ev.j.index())));
+}
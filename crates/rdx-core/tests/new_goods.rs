@@ -0,0 +1,64 @@
+mod common;
+
+use rdx_core::model::{DemographySpec, EndowmentSpec, ExitMode, GoodIntroduction, SimConfig};
+use rdx_core::sim::{init_agents, run, Engine};
+
+fn with_new_good(holders: f64) -> SimConfig {
+    let mut cfg = common::small_config();
+    cfg.new_goods = vec![GoodIntroduction {
+        round: 3,
+        name: "cloud".into(),
+        endowment: EndowmentSpec::Uniform { low: 1.0, high: 2.0 },
+        holders,
+        alpha_low: None,
+        alpha_high: None,
+        decay: 0.0,
+    }];
+    cfg
+}
+
+#[test]
+fn new_good_extends_every_agent_at_its_round() {
+    let cfg = with_new_good(0.25);
+    assert_eq!(cfg.all_goods().last().map(String::as_str), Some("cloud"));
+    let mut state = init_agents(&cfg).expect("init");
+    run(&cfg, &mut state).expect("run");
+
+    for a in state.agents.iter() {
+        assert_eq!(a.e.len(), 6);
+        assert_eq!(a.alpha_to_base.len(), 6);
+        assert_eq!(a.beta.len(), 6);
+        assert!((a.beta.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+    }
+    let holders: Vec<usize> = state.metrics.iter().map(|m| m.holders.len()).collect();
+    assert_eq!(holders, vec![5, 5, 5, 6, 6, 6]);
+    // the good starts with a minority of holders and can only spread through trade
+    let first = state.metrics[3].holders[5];
+    assert!(first > 0 && first < cfg.num_agents);
+    assert!(state.metrics[5].holders[5] >= first);
+
+    let mut engine = Engine::new(cfg).expect("engine");
+    engine.run_to_end();
+    assert_eq!(engine.config().base_goods.len(), 6);
+    assert!(engine.config().new_goods.is_empty());
+}
+
+#[test]
+fn entrants_receive_introduced_goods() {
+    let mut cfg = with_new_good(1.0);
+    cfg.new_goods[0].round = 0;
+    cfg.demography = Some(DemographySpec { entry_rate: 3.0, exit_prob: 0.0, exit: ExitMode::Remove, min_population: 2 });
+    let mut state = init_agents(&cfg).expect("init");
+    run(&cfg, &mut state).expect("run");
+    assert!(state.agents.len() > cfg.num_agents);
+    assert!(state.agents.iter().all(|a| a.e.len() == 6 && a.e[5] > 0.0));
+}
+
+#[test]
+fn invalid_new_goods_are_rejected() {
+    let mut cfg = with_new_good(1.5);
+    assert!(init_agents(&cfg).is_err());
+    cfg = with_new_good(0.5);
+    cfg.new_goods[0].name = "g2".into();
+    assert!(init_agents(&cfg).is_err());
+}
@@ -0,0 +1,43 @@
+mod common;
+
+use std::sync::{Arc, Mutex};
+use rdx_core::model::TradeEvent;
+use rdx_core::sim::{Engine, Observer, RoundMetrics, SimState};
+
+#[derive(Default)]
+struct Counter {
+    starts: usize,
+    ends: usize,
+    trades: usize,
+    trades_per_round: Vec<usize>,
+}
+
+impl Observer for Counter {
+    fn on_round_start(&mut self, _round: usize, _state: &SimState) {
+        self.starts += 1;
+    }
+    fn on_trade(&mut self, _event: &TradeEvent) {
+        self.trades += 1;
+    }
+    fn on_round_end(&mut self, metrics: &RoundMetrics, _state: &SimState) {
+        self.ends += 1;
+        self.trades_per_round.push(metrics.trades);
+    }
+}
+
+#[test]
+fn observer_sees_every_round_and_trade() {
+    let cfg = common::small_config();
+    let counter = Arc::new(Mutex::new(Counter::default()));
+
+    let mut engine = Engine::new(cfg.clone()).unwrap();
+    engine.add_observer(Box::new(counter.clone()));
+    engine.run_to_end();
+    let state = engine.finish();
+
+    let c = counter.lock().unwrap();
+    assert_eq!(c.starts, cfg.rounds);
+    assert_eq!(c.ends, cfg.rounds);
+    assert_eq!(c.trades, state.events.len());
+    assert_eq!(c.trades_per_round.iter().sum::<usize>(), state.events.len());
+}
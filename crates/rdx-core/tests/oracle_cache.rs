@@ -0,0 +1,85 @@
+#![cfg(feature = "oracle-cache")]
+
+mod common;
+
+use rdx_core::error::RdxError;
+use rdx_core::model::OracleCacheSpec;
+use rdx_core::pareto_oracle::{CachedWalrasOracle, CobbDouglasWalrasOracle, ParetoOracle};
+use rdx_core::sim::Engine;
+use rdx_core::trade::build_oracle;
+
+#[test]
+fn repeated_dyads_hit_the_cache() {
+    let oracle = CachedWalrasOracle::new(16, 1e-3);
+    let first = oracle.solve_two_good_exchange(0.3, 2.0, 1.0, 0.7, 1.0, 3.0, 1e-9, 60);
+    let second = oracle.solve_two_good_exchange(0.3, 2.0, 1.0, 0.7, 1.0, 3.0, 1e-9, 60);
+    assert_eq!(first.q_ab, second.q_ab);
+    // same shares at 10x the totals: same cell, price rescaled
+    let scaled = oracle.solve_two_good_exchange(0.3, 20.0, 10.0, 0.7, 10.0, 30.0, 1e-9, 60);
+    assert!((scaled.q_ab - first.q_ab).abs() < 1e-12);
+    let stats = oracle.cache_stats().unwrap();
+    assert_eq!((stats.hits, stats.misses, stats.evictions), (2, 1, 0));
+    assert!((stats.hit_rate() - 2.0 / 3.0).abs() < 1e-12);
+}
+
+#[test]
+fn cached_prices_track_the_exact_oracle_and_conserve_holdings() {
+    let exact = CobbDouglasWalrasOracle;
+    for resolution in [1e-2, 1e-3, 1e-4] {
+        let cached = CachedWalrasOracle::new(1024, resolution);
+        let mut worst: f64 = 0.0;
+        for k in 0..50 {
+            let t = k as f64 / 50.0;
+            let (ai, bi, aj, bj) = (0.5 + 2.0 * t, 1.5 - t, 2.0 - t, 0.4 + 3.0 * t);
+            let (alpha_i, alpha_j) = (0.1 + 0.8 * t, 0.9 - 0.7 * t);
+            let c = cached.solve_two_good_exchange(alpha_i, ai, bi, alpha_j, aj, bj, 1e-9, 80);
+            let e = exact.solve_two_good_exchange(alpha_i, ai, bi, alpha_j, aj, bj, 1e-9, 80);
+            worst = worst.max((c.q_ab / e.q_ab).ln().abs());
+            assert!((c.ai_post + c.aj_post - (ai + aj)).abs() < 1e-12);
+            assert!((c.bi_post + c.bj_post - (bi + bj)).abs() < 1e-12);
+        }
+        assert!(worst < 10.0 * resolution, "resolution {}: log price error {}", resolution, worst);
+    }
+}
+
+#[test]
+fn least_recently_used_cells_are_evicted() {
+    let oracle = CachedWalrasOracle::new(2, 1e-2);
+    let solve = |alpha: f64| oracle.solve_two_good_exchange(alpha, 1.0, 1.0, 0.5, 1.0, 1.0, 1e-9, 60);
+    solve(0.2);
+    solve(0.4);
+    solve(0.2);
+    solve(0.6); // evicts 0.4
+    solve(0.2);
+    solve(0.4);
+    let stats = oracle.cache_stats().unwrap();
+    assert_eq!((stats.hits, stats.misses, stats.evictions), (2, 4, 2));
+}
+
+#[test]
+fn cached_runs_are_deterministic_and_report_hits() {
+    let mut cfg = common::small_config();
+    cfg.oracle_cache = Some(OracleCacheSpec { capacity: 64, resolution: 0.25 });
+    let run = || {
+        let mut engine = Engine::new(cfg.clone()).unwrap();
+        engine.run_to_end();
+        engine.finish()
+    };
+    let (a, b) = (run(), run());
+    assert_eq!(serde_json::to_string(&a.agents).unwrap(), serde_json::to_string(&b.agents).unwrap());
+    let hits: usize = a.metrics.iter().map(|m| m.oracle_cache_hits).sum();
+    let misses: usize = a.metrics.iter().map(|m| m.oracle_cache_misses).sum();
+    assert!(hits > 0 && misses > 0, "hits {} misses {}", hits, misses);
+    assert!(a.metrics.iter().any(|m| m.trades > 0));
+}
+
+#[test]
+fn invalid_cache_specs_are_rejected() {
+    let mut cfg = common::small_config();
+    cfg.oracle_cache = Some(OracleCacheSpec { capacity: 0, resolution: 1e-3 });
+    assert!(Engine::new(cfg.clone()).is_err());
+    cfg.oracle_cache = Some(OracleCacheSpec { capacity: 16, resolution: 0.0 });
+    assert!(Engine::new(cfg.clone()).is_err());
+    cfg.oracle_cache = Some(OracleCacheSpec { capacity: 16, resolution: 0.75 });
+    assert!(matches!(build_oracle(&cfg), Err(RdxError::InvalidConfig(_))));
+}
@@ -0,0 +1,77 @@
+mod common;
+
+use rdx_core::pareto_oracle::{
+    AuditedOracle, CobbDouglasWalrasOracle, DyadExchange, OracleStatus, ParetoOracle, ShortSideOracle,
+};
+use rdx_core::sim::Engine;
+
+fn walras(alpha_i: f64, ai: f64, bi: f64, alpha_j: f64, aj: f64, bj: f64, min_qty: f64) -> OracleStatus {
+    CobbDouglasWalrasOracle.solve_two_good_exchange(alpha_i, ai, bi, alpha_j, aj, bj, min_qty, 80).status
+}
+
+#[test]
+fn walras_statuses_tell_the_failure_modes_apart() {
+    assert_eq!(walras(0.7, 2.0, 1.0, 0.2, 1.5, 3.0, 1e-9), OracleStatus::Ok);
+    assert_eq!(walras(0.0, 2.0, 1.0, 0.2, 1.5, 3.0, 1e-9), OracleStatus::Degenerate);
+    assert_eq!(walras(0.7, 2.0, 1.0, 1.0, 1.5, 3.0, 1e-9), OracleStatus::Degenerate);
+    assert_eq!(walras(0.7, f64::INFINITY, 1.0, 0.2, 1.5, 3.0, 1e-9), OracleStatus::Degenerate);
+    // almost no B and hardly any taste for A: demand for A stays short of supply at every price
+    assert_eq!(walras(1e-3, 1.0, 1e-6, 1e-3, 1.0, 1e-6, 1e-9), OracleStatus::Unbracketed);
+    // i's demand for A (0.2) lies below the floor
+    assert_eq!(walras(0.1, 1.0, 1.0, 0.9, 1.0, 1.0, 0.5), OracleStatus::Clamped);
+}
+
+#[test]
+fn short_side_statuses() {
+    let s = |alpha_i: f64, min_qty: f64| {
+        ShortSideOracle.solve_two_good_exchange(alpha_i, 2.0, 1.0, 0.2, 1.5, 3.0, min_qty, 0).status
+    };
+    assert_eq!(s(0.7, 1e-9), OracleStatus::Ok);
+    assert_eq!(s(f64::NAN, 1e-9), OracleStatus::Degenerate);
+    assert_eq!(s(0.7, 1.2), OracleStatus::Clamped);
+}
+
+#[test]
+fn audited_oracle_counts_and_resets() {
+    let audited = AuditedOracle::new(Box::new(CobbDouglasWalrasOracle));
+    for alpha in [0.0, 0.0, 0.5] {
+        audited.solve_two_good_exchange(alpha, 2.0, 1.0, 0.2, 1.5, 3.0, 1e-9, 60);
+    }
+    audited.solve_two_good_exchange(0.1, 1.0, 1.0, 0.9, 1.0, 1.0, 0.5, 60);
+    let counts = audited.take_counts();
+    assert_eq!((counts.degenerate, counts.unbracketed, counts.clamped), (2, 0, 1));
+    assert_eq!(audited.take_counts(), Default::default());
+}
+
+/// Never trades and reports every dyad as unbracketed.
+struct Stuck;
+
+impl ParetoOracle for Stuck {
+    fn solve_two_good_exchange(
+        &self,
+        _alpha_i: f64, ai: f64, bi: f64,
+        _alpha_j: f64, aj: f64, bj: f64,
+        _min_qty: f64,
+        _iters: usize,
+    ) -> DyadExchange {
+        DyadExchange {
+            q_ab: 1.0, ai_post: ai, bi_post: bi, aj_post: aj, bj_post: bj,
+            residual: 1.0, iterations: 0, status: OracleStatus::Unbracketed,
+        }
+    }
+}
+
+#[test]
+fn rounds_count_oracle_failures() {
+    let cfg = common::small_config();
+    let mut clean = Engine::new(cfg.clone()).unwrap();
+    clean.run_to_end();
+    assert!(clean.state().metrics.iter().all(|m| m.oracle_unbracketed == 0 && m.oracle_degenerate == 0));
+
+    let mut engine = Engine::new(cfg).unwrap();
+    engine.set_oracle(Box::new(Stuck));
+    engine.run_to_end();
+    let metrics = &engine.state().metrics;
+    assert!(metrics.iter().all(|m| m.trades == 0));
+    assert!(metrics.iter().all(|m| m.encounters > 0 && m.oracle_unbracketed > 0));
+}
@@ -0,0 +1,52 @@
+mod common;
+
+use rdx_core::pareto_oracle::{CobbDouglasWalrasOracle, DyadExchange, ParetoOracle, TolerantWalrasOracle};
+use rdx_core::sim::Engine;
+
+#[test]
+fn fixed_iterations_report_steps_and_a_small_residual() {
+    let ex: DyadExchange = CobbDouglasWalrasOracle.solve_two_good_exchange(0.7, 2.0, 1.0, 0.2, 1.5, 3.0, 1e-9, 80);
+    assert_eq!(ex.iterations, 80);
+    assert!(ex.residual.abs() < 1e-12, "{}", ex.residual);
+    let zero_tol = CobbDouglasWalrasOracle.solve_to_tolerance(0.7, 2.0, 1.0, 0.2, 1.5, 3.0, 1e-9, 0.0, 80);
+    assert_eq!(zero_tol.q_ab, ex.q_ab);
+}
+
+#[test]
+fn tolerance_stops_early_within_tolerance() {
+    let full: DyadExchange = CobbDouglasWalrasOracle.solve_two_good_exchange(0.7, 2.0, 1.0, 0.2, 1.5, 3.0, 1e-9, 200);
+    for tol in [1e-3, 1e-6, 1e-9] {
+        let oracle = TolerantWalrasOracle { tol };
+        let ex: DyadExchange = oracle.solve_two_good_exchange(0.7, 2.0, 1.0, 0.2, 1.5, 3.0, 1e-9, 200);
+        assert!(ex.iterations < 200, "tol {}: {} steps", tol, ex.iterations);
+        assert!(ex.residual.abs() < tol, "tol {}: residual {}", tol, ex.residual);
+        assert!((ex.q_ab / full.q_ab - 1.0).abs() < 10.0 * tol);
+    }
+    let loose = TolerantWalrasOracle { tol: 1e-3 }.solve_two_good_exchange(0.7, 2.0, 1.0, 0.2, 1.5, 3.0, 1e-9, 200);
+    let tight = TolerantWalrasOracle { tol: 1e-9 }.solve_two_good_exchange(0.7, 2.0, 1.0, 0.2, 1.5, 3.0, 1e-9, 200);
+    assert!(loose.iterations < tight.iterations);
+}
+
+#[test]
+fn unbracketed_dyads_show_in_the_residual() {
+    // neither agent values A: the price runs to the bracket's lower end without clearing
+    let oracle = TolerantWalrasOracle { tol: 1e-9 };
+    let ex: DyadExchange = oracle.solve_two_good_exchange(0.0, 1.0, 1.0, 0.0, 1.0, 1.0, 1e-9, 60);
+    assert_eq!(ex.iterations, 60);
+    assert!((ex.residual + 1.0).abs() < 1e-9, "{}", ex.residual);
+}
+
+#[test]
+fn config_tolerance_drives_the_engine() {
+    let mut cfg = common::small_config();
+    cfg.oracle_tol = 1e-10;
+    cfg.oracle_bisect_iters = 200;
+    let mut engine = Engine::new(cfg.clone()).unwrap();
+    engine.run_to_end();
+    assert!(engine.state().metrics.iter().any(|m| m.trades > 0));
+
+    cfg.oracle_tol = -1.0;
+    assert!(Engine::new(cfg.clone()).is_err());
+    cfg.oracle_tol = f64::NAN;
+    assert!(!cfg.validate().is_empty());
+}
@@ -0,0 +1,51 @@
+mod common;
+
+use serde_json::json;
+use rdx_core::model::PairingMode;
+use rdx_core::overlay::{layered, merge, set};
+
+#[test]
+fn overlays_merge_objects_and_replace_the_rest() {
+    let mut base = json!({"a": 1, "nested": {"x": 1, "y": 2}, "list": [1, 2, 3], "gone": true});
+    merge(&mut base, json!({"nested": {"y": 5, "z": 6}, "list": [9], "gone": null, "new": "s"}));
+    assert_eq!(base, json!({"a": 1, "nested": {"x": 1, "y": 5, "z": 6}, "list": [9], "new": "s"}));
+}
+
+#[test]
+fn assignments_follow_paths() {
+    let mut doc = json!({"shocks": [{"round": 1, "factor": 0.5}], "seed": 1});
+    set(&mut doc, "seed=9").unwrap();
+    set(&mut doc, "shocks.0.factor = 2").unwrap();
+    set(&mut doc, "consumption.rate=0.1").unwrap();
+    set(&mut doc, "pairing_mode=bundle").unwrap();
+    assert_eq!(doc, json!({
+        "shocks": [{"round": 1, "factor": 2}], "seed": 9,
+        "consumption": {"rate": 0.1}, "pairing_mode": "bundle",
+    }));
+
+    assert!(set(&mut doc, "no_equals_sign").is_err());
+    assert!(set(&mut doc, "shocks.3.factor=1").is_err());
+    assert!(set(&mut doc, "=1").is_err());
+
+    // a path cannot run through a scalar; a null is replaced like a missing field
+    let e = set(&mut doc, "seed.x=1").unwrap_err().to_string();
+    assert!(e.contains("`seed` is not an object"), "{e}");
+    let e = set(&mut doc, "shocks.0.round.x=1").unwrap_err().to_string();
+    assert!(e.contains("`shocks.0.round` is not an object"), "{e}");
+    assert_eq!(doc["seed"], json!(9));
+    doc["demography"] = json!(null);
+    set(&mut doc, "demography.entry_rate=0.5").unwrap();
+    assert_eq!(doc["demography"], json!({"entry_rate": 0.5}));
+}
+
+#[test]
+fn layered_configs_parse() {
+    let base = serde_json::to_value(common::small_config()).unwrap();
+    let experiment = json!({"extends": "base.json", "rounds": 2, "pairing_mode": "all_pairs_pruned"});
+    let cfg = layered(base.clone(), [experiment], &["seed=11".to_string()]).expect("layered");
+    assert_eq!((cfg.seed, cfg.rounds, cfg.num_agents), (11, 2, 24));
+    assert!(matches!(cfg.pairing_mode, PairingMode::AllPairsPruned));
+    assert!(cfg.validate().is_empty());
+
+    assert!(layered(base, Vec::new(), &["rounds=many".to_string()]).is_err());
+}
@@ -0,0 +1,115 @@
+mod common;
+
+use rand::prelude::*;
+use rdx_core::model::{Agent, PreferenceGenerator};
+use rdx_core::preferences::generators::{dirichlet, gamma, rank_correlate};
+use rdx_core::sim::init_agents;
+
+#[test]
+fn dirichlet_draws_are_profiles_with_the_right_mean() {
+    let mut rng = StdRng::seed_from_u64(3);
+    let draws = 4000;
+    let shape_mean = (0..draws).map(|_| gamma(0.5, &mut rng)).sum::<f64>() / draws as f64;
+    assert!((shape_mean - 0.5).abs() < 0.05);
+
+    let mut mean = [0.0; 3];
+    for _ in 0..draws {
+        let beta = dirichlet(&[1.0, 2.0, 5.0], &mut rng);
+        assert!((beta.iter().sum::<f64>() - 1.0).abs() < 1e-12 && beta.iter().all(|&b| b > 0.0));
+        for (m, b) in mean.iter_mut().zip(beta) {
+            *m += b / draws as f64;
+        }
+    }
+    for (m, expected) in mean.iter().zip([0.125, 0.25, 0.625]) {
+        assert!((m - expected).abs() < 0.02, "{mean:?}");
+    }
+}
+
+#[test]
+fn rank_correlation_keeps_marginals() {
+    let mut rng = StdRng::seed_from_u64(5);
+    let x0: Vec<f64> = (0..50).map(|k| k as f64).collect();
+    let y0: Vec<f64> = (0..50).map(|k| 0.01 * k as f64).collect();
+    let sorted = |v: &[f64]| {
+        let mut v = v.to_vec();
+        v.sort_by(f64::total_cmp);
+        v
+    };
+    let order = |v: &[f64]| {
+        let mut idx: Vec<usize> = (0..v.len()).collect();
+        idx.sort_by(|&a, &b| v[a].total_cmp(&v[b]));
+        idx
+    };
+
+    let (mut x, mut y) = (x0.clone(), y0.clone());
+    rank_correlate(&mut x, &mut y, 1.0, &mut rng);
+    assert_eq!(sorted(&x), x0);
+    assert_eq!(sorted(&y), y0);
+    assert_eq!(order(&x), order(&y));
+
+    rank_correlate(&mut x, &mut y, -1.0, &mut rng);
+    let mut reversed = order(&y);
+    reversed.reverse();
+    assert_eq!(order(&x), reversed);
+}
+
+#[test]
+fn engine_draws_from_the_configured_generator() {
+    let mut cfg = common::small_config();
+    let uniform = init_agents(&cfg).expect("init");
+    cfg.preference_generator = PreferenceGenerator::Uniform;
+    let explicit = init_agents(&cfg).expect("init");
+    for (a, b) in uniform.agents.iter().zip(explicit.agents.iter()) {
+        assert_eq!(a.beta, b.beta);
+    }
+
+    cfg.preference_generator = PreferenceGenerator::Dirichlet { concentration: 0.5 };
+    let state = init_agents(&cfg).expect("init");
+    for a in state.agents.iter() {
+        assert!((a.beta.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+        assert_eq!(a.alpha_to_base[0], 0.5);
+    }
+
+    cfg.preference_generator = PreferenceGenerator::Clustered { clusters: 2, cohesion: 1000.0 };
+    let state = init_agents(&cfg).expect("init");
+    let cluster = |k: usize| state.agents[k].labels["taste_cluster"].parse::<usize>().unwrap();
+    let distance = |a: usize, b: usize| {
+        let (x, y) = (&state.agents[a].beta, &state.agents[b].beta);
+        x.iter().zip(y).map(|(p, q)| (p - q).abs()).sum::<f64>()
+    };
+    for a in 0..state.agents.len() {
+        assert!(cluster(a) < 2);
+        for b in 0..state.agents.len() {
+            if cluster(a) == cluster(b) {
+                assert!(distance(a, b) < 0.2);
+            }
+        }
+    }
+
+    cfg.preference_generator = PreferenceGenerator::Clustered { clusters: 0, cohesion: 10.0 };
+    assert!(init_agents(&cfg).is_err());
+}
+
+#[test]
+fn endowments_follow_tastes_under_positive_correlation() {
+    let mut cfg = common::small_config();
+    let independent = init_agents(&cfg).expect("init");
+    cfg.endowment_preference_correlation = 1.0;
+    let state = init_agents(&cfg).expect("init");
+    for k in 1..5 {
+        let mut idx: Vec<usize> = (0..state.agents.len()).collect();
+        idx.sort_by(|&a, &b| state.agents[a].e[k].total_cmp(&state.agents[b].e[k]));
+        assert!(idx.windows(2).all(|w| state.agents[w[0]].alpha_to_base[k] <= state.agents[w[1]].alpha_to_base[k]));
+
+        // the same holdings, only handed to different agents
+        let column = |agents: &[Agent]| {
+            let mut v: Vec<f64> = agents.iter().map(|a| a.e[k]).collect();
+            v.sort_by(f64::total_cmp);
+            v
+        };
+        assert_eq!(column(&state.agents), column(&independent.agents));
+    }
+
+    cfg.endowment_preference_correlation = 1.5;
+    assert!(init_agents(&cfg).is_err());
+}
@@ -0,0 +1,46 @@
+mod common;
+
+use rand::prelude::*;
+use rdx_core::dynamics::preference_shock;
+use rdx_core::ids::GoodId;
+use rdx_core::model::{Agent, PreferenceShockSpec};
+use rdx_core::preferences::beta_from_alpha_to_base;
+use rdx_core::sim::{init_agents, run};
+
+#[test]
+fn shift_raises_demand_for_the_shocked_good() {
+    let alpha_to_base = vec![0.5, 0.4, 0.9];
+    let beta = beta_from_alpha_to_base(&alpha_to_base, 0, 1e-6).unwrap();
+    let mut agents = vec![Agent { e: vec![1.0; 3], beta, alpha_to_base, ..Default::default() }; 4];
+    let before = agents[0].beta.clone();
+    let spec = PreferenceShockSpec { round: 0, goods: vec![GoodId(1)], agent_fraction: 0.5, shift: 0.2 };
+    assert_eq!(preference_shock(&mut agents, &spec, 0, &mut StdRng::seed_from_u64(2)).unwrap(), 2);
+
+    let hit: Vec<&Agent> = agents.iter().filter(|a| a.alpha_to_base[1] > 0.4).collect();
+    assert_eq!(hit.len(), 2);
+    for a in hit {
+        assert!((a.alpha_to_base[1] - 0.6).abs() < 1e-12);
+        assert_eq!(a.alpha_to_base[2], 0.9);
+        assert!(a.beta[1] > before[1] && a.beta[0] < before[0]);
+    }
+}
+
+#[test]
+fn scheduled_preference_shocks_hit_their_round() {
+    let mut cfg = common::small_config();
+    let shock = PreferenceShockSpec { round: 3, goods: vec![GoodId(2)], agent_fraction: 0.25, shift: 0.3 };
+    cfg.preference_shocks = vec![shock];
+    let mut state = init_agents(&cfg).expect("init");
+    run(&cfg, &mut state).expect("run");
+    let hit: Vec<usize> = state.metrics.iter().map(|m| m.preference_shocked).collect();
+    assert_eq!(hit, vec![0, 0, 0, 6, 0, 0]);
+    // alphas stay inside (0, 1) however large the shift
+    cfg.preference_shocks[0].shift = 5.0;
+    let mut state = init_agents(&cfg).expect("init");
+    run(&cfg, &mut state).expect("run");
+    assert!(state.agents.iter().all(|a| a.alpha_to_base[2] < 1.0 && a.beta.iter().all(|b| b.is_finite())));
+
+    // the base good's alpha is a convention, not a preference
+    cfg.preference_shocks[0].goods = vec![GoodId(0)];
+    assert!(init_agents(&cfg).is_err());
+}
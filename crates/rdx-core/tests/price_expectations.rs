@@ -0,0 +1,48 @@
+mod common;
+
+use rdx_core::model::{PriceExpectationSpec, PriceMemory};
+use rdx_core::sim::{init_agents, run};
+use rdx_core::trade::{default_oracle, evaluate_pairwise_trade};
+
+#[test]
+fn memory_smooths_accepted_prices() {
+    let mut m = PriceMemory { expected: Vec::new(), tolerance: 0.1 };
+    assert!(!m.rejects(1, 100.0, true));
+    m.observe(1, 2.0, 0.5);
+    m.observe(1, 4.0, 0.5);
+    assert_eq!(m.expected, vec![0.0, 3.0]);
+    assert!(!m.rejects(1, 3.25, true) && m.rejects(1, 3.4, true));
+    assert!(!m.rejects(1, 2.75, false) && m.rejects(1, 2.6, false));
+}
+
+#[test]
+fn expectations_veto_mutually_beneficial_trades() {
+    let oracle = default_oracle();
+    let mut i = common::agent(vec![1.0, 4.0], vec![0.7, 0.3]);
+    let mut j = common::agent(vec![4.0, 1.0], vec![0.3, 0.7]);
+    let cand = evaluate_pairwise_trade(&i, &j, 1, 0, 0, 1e-9, 60, &oracle).expect("gains from trade");
+    let (buyer, seller) = if cand.delta_a_i > 0.0 { (&mut i, &mut j) } else { (&mut j, &mut i) };
+
+    // a seller expecting a slightly lower price is happy to sell
+    seller.price_memory = Some(PriceMemory { expected: vec![0.0, 0.95 * cand.q_ab], tolerance: 0.1 });
+    // a buyer used to paying half as much is not
+    buyer.price_memory = Some(PriceMemory { expected: vec![0.0, 0.5 * cand.q_ab], tolerance: 0.1 });
+    assert!(evaluate_pairwise_trade(&i, &j, 1, 0, 0, 1e-9, 60, &oracle).is_none());
+}
+
+#[test]
+fn engine_builds_memories_from_trades() {
+    let mut cfg = common::small_config();
+    cfg.price_expectations = Some(PriceExpectationSpec::default());
+    let mut state = init_agents(&cfg).expect("init");
+    run(&cfg, &mut state).expect("run");
+    assert!(!state.events.is_empty());
+    for ev in state.events.iter() {
+        let i = &state.agents[ev.i.index()];
+        let m = i.price_memory.as_ref().expect("traders remember prices");
+        assert!(m.expected[ev.good_a.index()] > 0.0);
+    }
+
+    cfg.price_expectations = Some(PriceExpectationSpec { memory: 0.0, tolerance: 0.1 });
+    assert!(init_agents(&cfg).is_err());
+}
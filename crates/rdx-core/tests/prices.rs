@@ -0,0 +1,43 @@
+mod common;
+
+use rdx_core::model::Agent;
+use rdx_core::prices::walras_prices;
+use rdx_core::sim::{init_agents, run};
+
+fn agent(e: Vec<f64>, beta: Vec<f64>) -> Agent {
+    Agent { e, beta, alpha_to_base: vec![], reaction_rules: vec![], ..Default::default() }
+}
+
+#[test]
+fn walras_prices_clear_a_two_good_economy() {
+    // p_1 X_1 = Σ β_i1 w_i with X = (2, 2): p_1 = (0.25 w_1 + 0.75 w_2) / 2, w_1 = 2, w_2 = 2 p_1
+    let agents = vec![agent(vec![2.0, 0.0], vec![0.75, 0.25]), agent(vec![0.0, 2.0], vec![0.25, 0.75])];
+    let p = walras_prices(&agents, 0);
+    assert_eq!(p[0], 1.0);
+    assert!((p[1] - 1.0).abs() < 1e-8, "{p:?}");
+
+    let agents = vec![agent(vec![1.0, 1.0], vec![0.5, 0.5]), agent(vec![1.0, 3.0], vec![0.5, 0.5])];
+    // symmetric tastes: p_1 / p_0 = X_0 / X_1
+    let p = walras_prices(&agents, 0);
+    assert!((p[1] - 0.5).abs() < 1e-8, "{p:?}");
+}
+
+#[test]
+fn price_index_covers_every_round_and_good() {
+    let cfg = common::small_config();
+    let mut state = init_agents(&cfg).expect("init");
+    run(&cfg, &mut state).expect("run");
+    let n = cfg.base_goods_quantity;
+    assert_eq!(state.prices.len(), state.metrics.len() * n);
+    for p in state.prices.iter() {
+        assert!(p.ewma.is_finite() && p.ewma > 0.0);
+        assert!(p.walras.is_finite() && p.walras > 0.0);
+        if p.good.index() == cfg.base_good {
+            assert_eq!((p.observed, p.ewma, p.walras), (Some(1.0), 1.0, 1.0));
+        }
+    }
+    // observed prices feed the EWMA
+    let first_traded = state.prices.iter().find(|p| p.good.index() != cfg.base_good && p.observed.is_some());
+    let first_traded = first_traded.expect("some good traded against the base");
+    assert_eq!(Some(first_traded.ewma), first_traded.observed);
+}
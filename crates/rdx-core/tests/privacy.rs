@@ -0,0 +1,60 @@
+use rand::prelude::*;
+use rdx_core::codec::privacy::{expected_utility_loss, laplace_scale, PRIVATE_BETA_FLOOR};
+use rdx_core::codec::{privatize_beta, CodecError};
+use rdx_core::pareto_oracle::CobbDouglasWalrasOracle;
+
+const BETA: [f64; 4] = [0.1, 0.2, 0.3, 0.4];
+
+fn l1(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b).map(|(x, y)| (x - y).abs()).sum()
+}
+
+#[test]
+fn privatized_exponents_stay_on_the_simplex() {
+    let mut rng = StdRng::seed_from_u64(3);
+    for epsilon in [0.1, 1.0, 10.0] {
+        for _ in 0..1000 {
+            let noisy = privatize_beta(&BETA, epsilon, &mut rng).unwrap();
+            assert_eq!(noisy.len(), BETA.len());
+            assert!((noisy.iter().sum::<f64>() - 1.0).abs() < 1e-12);
+            assert!(noisy.iter().all(|&w| w >= PRIVATE_BETA_FLOOR), "{noisy:?}");
+        }
+    }
+    assert_eq!(laplace_scale(0.5), 4.0);
+    for bad in [0.0, -1.0, f64::NAN, f64::INFINITY] {
+        assert!(matches!(privatize_beta(&BETA, bad, &mut rng), Err(CodecError::InvalidEpsilon(_))));
+    }
+}
+
+#[test]
+fn more_budget_means_less_noise() {
+    let mut rng = StdRng::seed_from_u64(4);
+    let mean_error = |epsilon: f64, rng: &mut StdRng| {
+        (0..400).map(|_| l1(&BETA, &privatize_beta(&BETA, epsilon, rng).unwrap())).sum::<f64>() / 400.0
+    };
+    let (loose, tight) = (mean_error(100.0, &mut rng), mean_error(0.5, &mut rng));
+    assert!(loose < 0.1, "{loose}");
+    assert!(tight > 3.0 * loose, "{tight} vs {loose}");
+}
+
+#[test]
+fn utility_loss_grows_as_privacy_tightens() {
+    let beta_i = [0.8, 0.2];
+    let beta_j = [0.2, 0.8];
+    let (e_i, e_j) = ([1.0, 4.0], [4.0, 1.0]);
+    let mut rng = StdRng::seed_from_u64(5);
+    let loss = |epsilon: f64, rng: &mut StdRng| {
+        expected_utility_loss(
+            (&beta_i, &e_i), (&beta_j, &e_j), 0, 1, epsilon, 300, 1e-9, 60, &CobbDouglasWalrasOracle, rng,
+        )
+        .unwrap()
+    };
+    let loose = loss(1000.0, &mut rng);
+    let tight = loss(0.2, &mut rng);
+    assert!(loose.truthful_gain > 0.0);
+    assert_eq!(loose.truthful_gain, tight.truthful_gain);
+    assert!(loose.expected_loss.abs() < 0.05 * loose.truthful_gain, "{loose:?}");
+    assert!(tight.expected_loss > loose.expected_loss, "{tight:?} vs {loose:?}");
+    assert!(tight.mean_beta_l1 > loose.mean_beta_l1);
+    assert!((0.0..=1.0).contains(&tight.trade_rate));
+}
@@ -0,0 +1,55 @@
+mod common;
+
+use std::sync::{Arc, Mutex};
+use rdx_core::sim::{Engine, Progress};
+
+fn reports(every: usize) -> (Vec<Progress>, rdx_core::sim::SimState) {
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let sink = seen.clone();
+    let mut engine = Engine::new(common::small_config()).unwrap();
+    engine.set_progress(every, Box::new(move |p: &Progress| sink.lock().unwrap().push(*p)));
+    engine.run_to_end();
+    let state = engine.finish();
+    let reports = seen.lock().unwrap().clone();
+    (reports, state)
+}
+
+#[test]
+fn progress_closes_every_round_with_its_counts() {
+    let (reports, state) = reports(10);
+    let done: Vec<_> = reports.iter().filter(|p| p.round_done).collect();
+    assert_eq!(done.len(), state.metrics.len());
+    let mut encounters = 0;
+    let mut trades = 0;
+    for (p, m) in done.iter().zip(state.metrics.iter()) {
+        encounters += m.encounters as u64;
+        trades += m.trades as u64;
+        assert_eq!((p.round, p.encounters, p.trades), (m.round, m.encounters, m.trades));
+        assert_eq!((p.total_encounters, p.total_trades), (encounters, trades));
+        assert_eq!(p.rounds, 6);
+    }
+    let last = done.last().unwrap();
+    assert_eq!(last.acceptance_rate(), trades as f64 / encounters as f64);
+}
+
+#[test]
+fn progress_reports_within_rounds_every_n_encounters() {
+    let (reports, state) = reports(10);
+    let within: Vec<_> = reports.iter().filter(|p| !p.round_done).collect();
+    let expected: usize = state.metrics.iter().map(|m| m.encounters / 10).sum();
+    assert_eq!(within.len(), expected);
+    assert!(within.iter().all(|p| p.encounters % 10 == 0 && p.encounters > 0));
+    // totals never go back
+    assert!(reports.windows(2).all(|w| w[0].total_encounters <= w[1].total_encounters));
+}
+
+#[test]
+fn progress_does_not_change_the_run() {
+    let (_, with) = reports(1);
+    let without = {
+        let mut engine = Engine::new(common::small_config()).unwrap();
+        engine.run_to_end();
+        engine.finish()
+    };
+    assert_eq!(serde_json::to_string(&with.events).unwrap(), serde_json::to_string(&without.events).unwrap());
+}
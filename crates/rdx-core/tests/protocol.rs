@@ -0,0 +1,101 @@
+use rdx_core::codec::{decode, encode};
+use rdx_core::pareto_oracle::CobbDouglasWalrasOracle;
+use rdx_core::protocol::{
+    Message, NegotiationState, Offer, Party, Profile, ProtocolError, Proposer, RejectReason, Responder,
+};
+use rdx_core::trade::evaluate_exchange;
+
+const ORACLE: CobbDouglasWalrasOracle = CobbDouglasWalrasOracle;
+
+fn parties() -> (Party, Party) {
+    (
+        Party { beta: vec![0.8, 0.2], holdings: vec![1.0, 4.0], min_qty: 1e-9 },
+        Party { beta: vec![0.2, 0.8], holdings: vec![4.0, 1.0], min_qty: 1e-9 },
+    )
+}
+
+fn profile(p: &Party) -> Profile {
+    Profile { beta: p.beta.clone(), holdings: p.holdings.clone() }
+}
+
+fn fair_offer(i: &Party, j: &Party) -> Offer {
+    let c = evaluate_exchange(&i.beta, &i.holdings, &j.beta, &j.holdings, 0, 1, 1e-9, 60, &ORACLE).unwrap();
+    Offer::from(&c)
+}
+
+/// Deliver `message` over the wire (JSON), as a network layer would.
+fn wire(message: Message) -> Message {
+    decode(&encode(&message).unwrap()).unwrap()
+}
+
+#[test]
+fn fair_offers_are_accepted_and_committed() {
+    let (i, j) = parties();
+    let offer = fair_offer(&i, &j);
+    assert!(offer.delta_a > 0.0 && offer.delta_b < 0.0);
+    let mut proposer = Proposer::new(7, i.clone(), profile(&i));
+    let mut responder = Responder::new(7, j, false, &ORACLE, 60);
+
+    let propose = wire(proposer.propose(offer).unwrap());
+    let accept = wire(responder.handle(&propose).unwrap().expect("accept"));
+    assert!(matches!(accept, Message::Accept { id: 7, .. }));
+    assert!(matches!(responder.state(), NegotiationState::Accepted(_)));
+    let commit = wire(proposer.handle(&accept).unwrap().expect("commit"));
+    assert_eq!(responder.handle(&commit).unwrap(), None);
+
+    assert!(proposer.state().is_finished() && responder.state().is_finished());
+    let (NegotiationState::Committed(a), NegotiationState::Committed(b)) = (proposer.state(), responder.state()) else {
+        panic!("{:?} / {:?}", proposer.state(), responder.state());
+    };
+    assert!(a.matches(b) && a.matches(&offer));
+    assert!((a.q_ab() - (-offer.delta_b / offer.delta_a)).abs() < 1e-12);
+}
+
+#[test]
+fn greedy_offers_draw_a_counter_offer() {
+    let (i, j) = parties();
+    let fair = fair_offer(&i, &j);
+    let greedy = Offer { delta_b: fair.delta_b * 0.1, ..fair };
+
+    let mut proposer = Proposer::new(1, i.clone(), profile(&i));
+    let mut responder = Responder::new(1, j.clone(), true, &ORACLE, 60);
+    let counter = responder.handle(&proposer.propose(greedy).unwrap()).unwrap().unwrap();
+    let Message::CounterOffer { offer, .. } = counter else { panic!("{counter:?}") };
+    assert!(offer.matches(&fair));
+    let accept = proposer.handle(&counter).unwrap().unwrap();
+    assert!(matches!(proposer.state(), NegotiationState::Accepted(_)));
+    let commit = responder.handle(&accept).unwrap().unwrap();
+    assert!(matches!(commit, Message::Commit { .. }));
+    assert_eq!(proposer.handle(&commit).unwrap(), None);
+    assert!(matches!(proposer.state(), NegotiationState::Committed(o) if o.matches(&fair)));
+
+    // without counter-offers the greedy proposal is simply refused
+    let mut proposer = Proposer::new(2, i.clone(), profile(&i));
+    let mut responder = Responder::new(2, j, false, &ORACLE, 60);
+    let reject = responder.handle(&proposer.propose(greedy).unwrap()).unwrap().unwrap();
+    assert_eq!(reject, Message::Reject { id: 2, reason: RejectReason::NoGain });
+    assert_eq!(proposer.handle(&reject).unwrap(), None);
+    assert_eq!(proposer.state(), &NegotiationState::Rejected(RejectReason::NoGain));
+}
+
+#[test]
+fn out_of_order_and_malformed_messages_are_errors() {
+    let (i, j) = parties();
+    let offer = fair_offer(&i, &j);
+    let mut proposer = Proposer::new(3, i.clone(), profile(&i));
+    let mut responder = Responder::new(3, j, true, &ORACLE, 60);
+
+    let stray = Message::Commit { id: 3, offer };
+    assert!(matches!(responder.handle(&stray), Err(ProtocolError::Unexpected { state: "open", message: "commit" })));
+    let other = Message::Accept { id: 4, offer };
+    assert_eq!(proposer.handle(&other), Err(ProtocolError::WrongNegotiation { expected: 3, found: 4 }));
+
+    let same_sign = Offer { delta_b: -offer.delta_b, ..offer };
+    assert_eq!(proposer.propose(same_sign), Err(ProtocolError::MalformedOffer));
+    let propose = Message::ProposeTrade { id: 3, offer: same_sign, proposer: profile(&i) };
+    let reject = responder.handle(&propose).unwrap().unwrap();
+    assert_eq!(reject, Message::Reject { id: 3, reason: RejectReason::Malformed });
+
+    proposer.propose(offer).unwrap();
+    assert!(proposer.propose(offer).is_err(), "a proposer opens once");
+}
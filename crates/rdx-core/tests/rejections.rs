@@ -0,0 +1,112 @@
+mod common;
+
+use std::collections::{HashMap, HashSet};
+
+use rdx_core::counterfactual::TradeEdit;
+use rdx_core::model::TransactionCost;
+use rdx_core::pareto_oracle::{DyadExchange, OracleStatus, ParetoOracle};
+use rdx_core::sim::{Engine, RejectionReason};
+
+#[test]
+fn every_failed_encounter_is_logged_without_changing_the_run() {
+    // small gains around convergence stop covering the fixed cost
+    let mut cfg = common::small_config();
+    cfg.rounds = 40;
+    cfg.transaction_cost = Some(TransactionCost { fixed: 0.0005, ..Default::default() });
+    let mut plain = Engine::new(cfg.clone()).unwrap();
+    plain.run_to_end();
+    assert!(plain.rejection_log().is_empty());
+
+    let mut engine = Engine::new(cfg.clone()).unwrap();
+    engine.record_rejections();
+    engine.run_to_end();
+    let log = engine.rejection_log().to_vec();
+    let state = engine.finish();
+    let plain = plain.finish();
+    assert_eq!(serde_json::to_string(&state.events).unwrap(), serde_json::to_string(&plain.events).unwrap());
+
+    let traded: HashSet<(usize, u64)> = state.events.iter().map(|e| (e.round, e.seq)).collect();
+    let encounters: usize = state.metrics.iter().map(|m| m.encounters).sum();
+    assert!(!log.is_empty() && !traded.is_empty());
+    assert_eq!(log.len() + traded.len(), encounters);
+    assert!(log.iter().all(|r| r.i != r.j && r.round < cfg.rounds));
+
+    let mut by_reason: HashMap<RejectionReason, usize> = HashMap::new();
+    for r in log.iter() {
+        *by_reason.entry(r.reason).or_default() += 1;
+    }
+    let cost_refused: usize = state.metrics.iter().map(|m| m.cost_refused).sum();
+    assert!(cost_refused > 0);
+    assert_eq!(by_reason.get(&RejectionReason::CostRefused).copied().unwrap_or(0), cost_refused);
+}
+
+#[test]
+fn identical_agents_have_identical_mrs() {
+    let mut engine = Engine::new(common::small_config()).unwrap();
+    let template = engine.state().agents[0].clone();
+    for a in engine.state_mut().agents.iter_mut() {
+        a.beta = template.beta.clone();
+        a.e = template.e.clone();
+        a.invalidate_utility();
+    }
+    engine.record_rejections();
+    engine.step_round();
+    let log = engine.rejection_log();
+    assert_eq!(log.len(), engine.state().metrics[0].encounters);
+    assert!(log.iter().all(|r| r.reason == RejectionReason::IdenticalMrs));
+}
+
+/// Never trades and reports every dyad as unbracketed.
+struct Stuck;
+
+impl ParetoOracle for Stuck {
+    fn solve_two_good_exchange(
+        &self,
+        _alpha_i: f64, ai: f64, bi: f64,
+        _alpha_j: f64, aj: f64, bj: f64,
+        _min_qty: f64,
+        _iters: usize,
+    ) -> DyadExchange {
+        DyadExchange {
+            q_ab: 1.0, ai_post: ai, bi_post: bi, aj_post: aj, bj_post: bj,
+            residual: 1.0, iterations: 0, status: OracleStatus::Unbracketed,
+        }
+    }
+}
+
+#[test]
+fn failing_oracles_are_told_from_missing_gains() {
+    let mut engine = Engine::new(common::small_config()).unwrap();
+    engine.set_oracle(Box::new(Stuck));
+    engine.record_rejections();
+    engine.step_round();
+    let log = engine.rejection_log();
+    assert_eq!(log.len(), engine.state().metrics[0].encounters);
+    assert!(log.iter().all(|r| r.reason == RejectionReason::DegenerateOracle));
+
+    // the probe does not count towards the round's oracle audit
+    let mut unrecorded = Engine::new(common::small_config()).unwrap();
+    unrecorded.set_oracle(Box::new(Stuck));
+    unrecorded.step_round();
+    assert_eq!(engine.state().metrics[0].oracle_unbracketed, unrecorded.state().metrics[0].oracle_unbracketed);
+}
+
+#[test]
+fn refusals_at_execution_carry_their_reason() {
+    let mut engine = Engine::new(common::small_config()).unwrap();
+    engine.record_rejections();
+    engine.edit_trade(0, TradeEdit::Remove);
+    engine.step_round();
+    let removed = engine.rejection_log().iter().filter(|r| r.reason == RejectionReason::Removed).count();
+    assert_eq!(removed, 1);
+
+    let mut cfg = common::small_config();
+    cfg.conserve_quantities = true;
+    cfg.min_qty = 0.5;
+    let mut engine = Engine::new(cfg).unwrap();
+    engine.record_rejections();
+    engine.run_to_end();
+    let capped = engine.rejection_log().iter().filter(|r| r.reason == RejectionReason::CappedOut).count();
+    let refused: usize = engine.state().metrics.iter().map(|m| m.cap_refused).sum();
+    assert!(capped >= refused);
+}
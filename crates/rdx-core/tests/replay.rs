@@ -0,0 +1,58 @@
+mod common;
+
+use rdx_core::error::RdxError;
+use rdx_core::model::{ConsumptionSpec, PairingMode};
+use rdx_core::sim::{init_agents, reconstruct, replay, replay_blockers, run};
+
+#[test]
+fn event_log_explains_the_run() {
+    let cfg = common::small_config();
+    let mut state = init_agents(&cfg).unwrap();
+    run(&cfg, &mut state).unwrap();
+    assert!(!state.events.is_empty());
+
+    let report = replay(&cfg, &state.events).expect("replay");
+    assert!(report.verified(), "{:?}", report.max_error);
+    assert_eq!(report.holdings.len(), cfg.rounds);
+    for (replayed, agent) in report.holdings.last().unwrap().iter().zip(state.agents.iter()) {
+        assert_eq!(replayed, &agent.e);
+    }
+}
+
+#[test]
+fn bundle_trades_replay_within_tolerance() {
+    let mut cfg = common::small_config();
+    cfg.pairing_mode = PairingMode::Bundle;
+    let mut state = init_agents(&cfg).unwrap();
+    run(&cfg, &mut state).unwrap();
+    assert!(replay(&cfg, &state.events).unwrap().verified());
+}
+
+#[test]
+fn tampered_logs_are_caught() {
+    let cfg = common::small_config();
+    let mut state = init_agents(&cfg).unwrap();
+    run(&cfg, &mut state).unwrap();
+
+    let mut events = state.events.clone();
+    let k = events.len() / 2;
+    events[k].delta_a_i *= 1.5;
+    let report = replay(&cfg, &events).unwrap();
+    assert_eq!(report.first_mismatch, Some(events[k].round));
+
+    let dropped: Vec<_> = state.events.iter().skip(1).cloned().collect();
+    assert_eq!(replay(&cfg, &dropped).unwrap().first_mismatch, Some(state.events[0].round));
+
+    let mut shuffled = state.events.clone();
+    shuffled.reverse();
+    assert!(matches!(reconstruct(&cfg, &shuffled), Err(RdxError::Replay(_))));
+}
+
+#[test]
+fn flows_outside_trades_block_replay() {
+    let mut cfg = common::small_config();
+    assert!(replay_blockers(&cfg).is_empty());
+    cfg.consumption = Some(ConsumptionSpec { rate: 0.1, income: 0.0 });
+    assert_eq!(replay_blockers(&cfg), vec!["consumption"]);
+    assert!(matches!(replay(&cfg, &[]), Err(RdxError::Replay(_))));
+}
@@ -0,0 +1,71 @@
+mod common;
+
+use rand::prelude::*;
+use rdx_core::model::ReplicationSpec;
+use rdx_core::replication::{aggregate, bootstrap_mean_ci, replicate, run_replications};
+use rdx_core::sim::{init_agents, run};
+
+fn spec(runs: usize) -> ReplicationSpec {
+    ReplicationSpec { runs, confidence: 0.9, bootstrap_samples: 400 }
+}
+
+#[test]
+fn replicate_zero_is_the_plain_run() {
+    let cfg = common::small_config();
+    let runs = replicate(&cfg, &spec(3)).expect("replicate");
+    assert_eq!(runs.len(), 3);
+
+    let mut state = init_agents(&cfg).unwrap();
+    run(&cfg, &mut state).unwrap();
+    let plain: Vec<(usize, f64)> = state.metrics.iter().map(|m| (m.trades, m.delta_u)).collect();
+    let first: Vec<(usize, f64)> = runs[0].iter().map(|m| (m.trades, m.delta_u)).collect();
+    assert_eq!(plain, first);
+    let second: Vec<(usize, f64)> = runs[1].iter().map(|m| (m.trades, m.delta_u)).collect();
+    assert_ne!(first, second, "replicates use different seeds");
+}
+
+#[test]
+fn aggregates_bracket_the_mean() {
+    let cfg = common::small_config();
+    let runs = replicate(&cfg, &spec(8)).unwrap();
+    let rows = aggregate(&runs, &spec(8), cfg.seed);
+    assert!(rows.iter().all(|r| r.ci_low <= r.mean + 1e-12 && r.mean <= r.ci_high + 1e-12), "{rows:?}");
+    assert!(rows.iter().all(|r| r.runs == 8 && r.round < cfg.rounds));
+    assert!(!rows.iter().any(|r| r.metric == "round"));
+
+    let trades: Vec<_> = rows.iter().filter(|r| r.metric == "trades").collect();
+    assert_eq!(trades.len(), cfg.rounds);
+    let direct = runs.iter().map(|r| r[0].trades as f64).sum::<f64>() / 8.0;
+    assert!((trades[0].mean - direct).abs() < 1e-12);
+    assert!(rows.iter().any(|r| r.metric == "gini_wealth"), "inequality measures are included");
+
+    // same seed, same intervals
+    let again = aggregate(&runs, &spec(8), cfg.seed);
+    assert!(rows.iter().zip(again.iter()).all(|(a, b)| a.ci_low == b.ci_low && a.ci_high == b.ci_high));
+}
+
+#[test]
+fn bootstrap_interval_narrows_with_more_data() {
+    let mut rng = StdRng::seed_from_u64(5);
+    let noise = |n: usize, rng: &mut StdRng| -> Vec<f64> { (0..n).map(|_| rng.gen::<f64>()).collect() };
+    let small = noise(10, &mut rng);
+    let large = noise(1000, &mut rng);
+    let (a, b) = bootstrap_mean_ci(&small, 0.95, 2000, &mut rng);
+    let (c, d) = bootstrap_mean_ci(&large, 0.95, 2000, &mut rng);
+    assert!(a < b && c < d);
+    assert!(d - c < b - a);
+    assert!(c < 0.5 && 0.5 < d, "[{c}, {d}] misses the true mean");
+    assert_eq!(bootstrap_mean_ci(&[2.0], 0.95, 100, &mut rng), (2.0, 2.0));
+}
+
+#[test]
+fn replications_come_from_the_config() {
+    let mut cfg = common::small_config();
+    assert!(run_replications(&cfg).unwrap().is_none());
+    cfg.replications = Some(spec(2));
+    let rows = run_replications(&cfg).unwrap().expect("aggregate");
+    assert!(rows.iter().all(|r| r.runs == 2));
+
+    cfg.replications = Some(ReplicationSpec { runs: 2, confidence: 1.5, bootstrap_samples: 10 });
+    assert!(init_agents(&cfg).is_err());
+}
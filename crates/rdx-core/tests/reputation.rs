@@ -0,0 +1,55 @@
+mod common;
+
+use rand::prelude::*;
+use rdx_core::matching::Matcher;
+use rdx_core::model::{Agent, MatchingMode, ReputationSpec};
+use rdx_core::sim::{init_agents, run};
+
+#[test]
+fn reputation_matching_favours_trusted_agents() {
+    let mut agents: Vec<Agent> = (0..10).map(|_| Agent { e: vec![1.0, 1.0], ..Default::default() }).collect();
+    agents[3].reputation = 9.0;
+    agents[5].reputation = -4.0;
+    let matcher = Matcher::for_round(&MatchingMode::Reputation { baseline: 1.0 }, &agents, None, None);
+    let mut rng = StdRng::seed_from_u64(11);
+    let mut hits = [0usize; 10];
+    for _ in 0..4000 {
+        let (i, j) = matcher.draw_pair(&mut rng).expect("population of 10");
+        assert_ne!(i, j);
+        hits[i] += 1;
+        hits[j] += 1;
+    }
+    // agent 3 carries 10 of 19 weight units, a negative reputation counts as none
+    assert!(hits[3] > 4 * hits[0]);
+    assert!(hits[5] > 0 && hits[5] < 2 * hits[0]);
+
+    // without a baseline only agents with positive reputation meet
+    agents[7].reputation = 1.0;
+    let matcher = Matcher::for_round(&MatchingMode::Reputation { baseline: 0.0 }, &agents, None, None);
+    for _ in 0..100 {
+        let (i, j) = matcher.draw_pair(&mut rng).expect("two trusted agents");
+        assert!([3, 7].contains(&i) && [3, 7].contains(&j));
+    }
+}
+
+#[test]
+fn reneging_costs_trades_and_reputation() {
+    let mut cfg = common::small_config();
+    cfg.reputation = Some(ReputationSpec::default());
+    cfg.matching = MatchingMode::Reputation { baseline: 1.0 };
+    let mut honest = init_agents(&cfg).expect("init");
+    run(&cfg, &mut honest).expect("run");
+    assert!(honest.metrics.iter().all(|m| m.reneged == 0 && m.mean_reputation >= 0.0));
+    assert!(honest.metrics.iter().all(|m| m.active_traders <= 2 * m.trades && m.active_traders <= 24));
+    assert!(honest.agents.iter().any(|a| a.reputation > 0.0));
+
+    cfg.reputation = Some(ReputationSpec { renege_prob: 0.5, ..Default::default() });
+    let mut flaky = init_agents(&cfg).expect("init");
+    run(&cfg, &mut flaky).expect("run");
+    let reneged: usize = flaky.metrics.iter().map(|m| m.reneged).sum();
+    let trades = |s: &rdx_core::sim::SimState| s.metrics.iter().map(|m| m.trades).sum::<usize>();
+    assert!(reneged > 0 && trades(&flaky) < trades(&honest));
+
+    cfg.reputation = None;
+    assert!(init_agents(&cfg).is_err());
+}
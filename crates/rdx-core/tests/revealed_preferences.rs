@@ -0,0 +1,33 @@
+mod common;
+
+use rdx_core::preferences::estimate_beta_from_events;
+use rdx_core::sim::Engine;
+
+#[test]
+fn full_steps_reveal_true_preferences() {
+    let mut cfg = common::small_config();
+    cfg.trade_step_cap_frac = 1.0;
+    cfg.rounds = 10;
+    let mut engine = Engine::new(cfg).expect("engine");
+    let mut history = Vec::new();
+    while !engine.is_finished() {
+        history.push(engine.state().agents.iter().map(|a| a.e.clone()).collect::<Vec<_>>());
+        engine.step_round();
+    }
+    let state = engine.finish();
+    let estimates = estimate_beta_from_events(&state.events, &history).expect("estimate");
+    assert_eq!(estimates.len(), state.agents.len());
+
+    let mut recovered = 0;
+    for (est, a) in estimates.iter().zip(state.agents.iter()) {
+        let Some(beta) = est else { continue };
+        for (x, y) in beta.iter().zip(a.beta.iter()) {
+            assert!((x - y).abs() < 1e-6, "{beta:?} vs {:?}", a.beta);
+        }
+        recovered += 1;
+    }
+    assert!(recovered > 0);
+
+    // a log referring to rounds past the history is rejected
+    assert!(estimate_beta_from_events(&state.events, &history[..1]).is_err());
+}
@@ -0,0 +1,44 @@
+mod common;
+
+use std::collections::HashSet;
+
+use rdx_core::matching::round_robin_pairs;
+use rdx_core::model::Scheduler;
+use rdx_core::sim::{init_agents, run};
+
+#[test]
+fn round_robin_substeps_are_disjoint_and_cover_all_pairs() {
+    for n in [2usize, 5, 8, 9] {
+        let order: Vec<usize> = (0..n).rev().collect();
+        let period = if n % 2 == 0 { n - 1 } else { n };
+        let mut seen = HashSet::new();
+        for step in 0..period {
+            let pairs = round_robin_pairs(&order, step);
+            assert_eq!(pairs.len(), n / 2);
+            let mut busy = HashSet::new();
+            for &(i, j) in &pairs {
+                assert!(busy.insert(i) && busy.insert(j), "agent reused within sub-step");
+                assert!(seen.insert((i.min(j), i.max(j))), "pair repeated within period");
+            }
+        }
+        assert_eq!(seen.len(), n * (n - 1) / 2);
+    }
+    assert!(round_robin_pairs(&[3], 0).is_empty());
+}
+
+#[test]
+fn round_robin_runs_are_reproducible() {
+    let mut cfg = common::small_config();
+    cfg.scheduler = Scheduler::RoundRobin;
+
+    let mut a = init_agents(&cfg).expect("init");
+    run(&cfg, &mut a).expect("run");
+    let mut b = init_agents(&cfg).expect("init");
+    run(&cfg, &mut b).expect("run");
+
+    assert!(!a.events.is_empty());
+    assert_eq!(a.events.len(), b.events.len());
+    for (x, y) in a.agents.iter().zip(b.agents.iter()) {
+        assert_eq!(x.e, y.e);
+    }
+}
@@ -0,0 +1,105 @@
+use std::fmt;
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+use rdx_core::math::{normalize_s, Scalar};
+use rdx_core::pareto_oracle::{CobbDouglasWalrasOracle, ParetoOracle, ShortSideOracle};
+use rdx_core::preferences::{
+    alpha_from_beta, alpha_from_beta_s, beta_from_alpha_to_base, beta_from_alpha_to_base_s, cd_utility, cd_utility_s,
+    demand_at_price, demand_at_price_s,
+};
+use rdx_core::trade::{mrs_to_base, mrs_to_base_s};
+
+#[test]
+fn f32_primitives_track_f64() {
+    let alphas = [0.5, 0.7, 0.2, 0.45];
+    let beta64 = beta_from_alpha_to_base(&alphas, 0, 1e-6).unwrap();
+    let beta32 = beta_from_alpha_to_base_s(&alphas.map(|a| a as f32), 0, 1e-6).unwrap();
+    let x64 = [1.5, 2.0, 0.3, 4.0];
+    let x32 = x64.map(|x| x as f32);
+
+    for (a, b) in beta64.iter().zip(beta32.iter()) {
+        assert!((a - *b as f64).abs() < 1e-6);
+    }
+    let u64 = cd_utility(&beta64, &x64, 1e-9);
+    let u32 = cd_utility_s(&beta32, &x32, 1e-9);
+    assert!((u64 - u32 as f64).abs() < 1e-5 * u64);
+    let m = mrs_to_base_s(&beta32, &x32, 2, 0, 1e-9) as f64;
+    assert!((mrs_to_base(&beta64, &x64, 2, 0, 1e-9) - m).abs() < 1e-5 * m);
+    assert!((alpha_from_beta(&beta64, 1, 2, 1e-6) - alpha_from_beta_s(&beta32, 1, 2, 1e-6) as f64).abs() < 1e-6);
+    let d = demand_at_price_s(&beta32, &x32, 1, 0, 1.3, 1e-9) as f64;
+    assert!((demand_at_price(&beta64, &x64, 1, 0, 1.3, 1e-9) - d).abs() < 1e-5);
+    assert!(beta_from_alpha_to_base_s(&[0.5f32, f32::NAN], 0, 1e-6).is_err());
+}
+
+#[test]
+fn oracles_run_in_f32() {
+    let narrow = [
+        CobbDouglasWalrasOracle.solve_two_good_exchange_s(0.7f32, 2.0, 1.0, 0.2, 1.5, 3.0, 1e-9, 80),
+        ShortSideOracle.solve_two_good_exchange_s(0.7f32, 2.0, 1.0, 0.2, 1.5, 3.0, 1e-9, 80),
+    ];
+    let wide: [&dyn ParetoOracle; 2] = [&CobbDouglasWalrasOracle, &ShortSideOracle];
+    for (ex, wide) in narrow.into_iter().zip(wide) {
+        let wide = wide.solve_two_good_exchange(0.7, 2.0, 1.0, 0.2, 1.5, 3.0, 1e-9, 80);
+        assert!((ex.q_ab as f64 - wide.q_ab).abs() < 1e-5 * wide.q_ab);
+        // goods are conserved to f32 precision
+        assert!((ex.ai_post + ex.aj_post - 3.5).abs() < 1e-5);
+        assert!((ex.bi_post + ex.bj_post - 4.0).abs() < 1e-5);
+    }
+    let checked = CobbDouglasWalrasOracle.try_solve_two_good_exchange_s(0.0f32, 2.0, 1.0, 0.0, 1.5, 3.0, 1e-9, 80);
+    assert!(checked.is_err());
+}
+
+/// Downstream fixed-point type: Q32.32 in an `i64`, with transcendental functions via `f64`.
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+struct Fixed(i64);
+
+const FRAC: f64 = (1u64 << 32) as f64;
+
+impl fmt::Display for Fixed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_f64())
+    }
+}
+
+impl Add for Fixed { type Output = Fixed; fn add(self, o: Fixed) -> Fixed { Fixed(self.0 + o.0) } }
+impl Sub for Fixed { type Output = Fixed; fn sub(self, o: Fixed) -> Fixed { Fixed(self.0 - o.0) } }
+impl Neg for Fixed { type Output = Fixed; fn neg(self) -> Fixed { Fixed(-self.0) } }
+impl Mul for Fixed {
+    type Output = Fixed;
+    fn mul(self, o: Fixed) -> Fixed { Fixed(((self.0 as i128 * o.0 as i128) >> 32) as i64) }
+}
+impl Div for Fixed {
+    type Output = Fixed;
+    fn div(self, o: Fixed) -> Fixed { Fixed((((self.0 as i128) << 32) / o.0 as i128) as i64) }
+}
+impl AddAssign for Fixed { fn add_assign(&mut self, o: Fixed) { *self = *self + o } }
+impl SubAssign for Fixed { fn sub_assign(&mut self, o: Fixed) { *self = *self - o } }
+impl MulAssign for Fixed { fn mul_assign(&mut self, o: Fixed) { *self = *self * o } }
+impl DivAssign for Fixed { fn div_assign(&mut self, o: Fixed) { *self = *self / o } }
+
+impl Scalar for Fixed {
+    const ZERO: Fixed = Fixed(0);
+    const ONE: Fixed = Fixed(1 << 32);
+
+    fn from_f64(x: f64) -> Fixed { Fixed((x * FRAC).round() as i64) }
+    fn to_f64(self) -> f64 { self.0 as f64 / FRAC }
+    fn ln(self) -> Fixed { Fixed::from_f64(self.to_f64().ln()) }
+    fn exp(self) -> Fixed { Fixed::from_f64(self.to_f64().exp()) }
+    fn sqrt(self) -> Fixed { Fixed::from_f64(self.to_f64().sqrt()) }
+}
+
+#[test]
+fn downstream_fixed_point_type_plugs_in() {
+    let f = Fixed::from_f64;
+    let mut beta = vec![f(1.0), f(3.0)];
+    normalize_s(&mut beta);
+    assert_eq!(beta[1], f(0.75));
+
+    let u = cd_utility_s(&beta, &[f(2.0), f(4.0)], f(1e-6)).to_f64();
+    assert!((u - 2f64.powf(0.25) * 4f64.powf(0.75)).abs() < 1e-6);
+
+    let oracle = CobbDouglasWalrasOracle;
+    let ex = oracle.solve_two_good_exchange_s(f(0.7), f(2.0), f(1.0), f(0.2), f(1.5), f(3.0), f(1e-6), 60);
+    let wide = oracle.solve_two_good_exchange(0.7, 2.0, 1.0, 0.2, 1.5, 3.0, 1e-6, 60);
+    assert!((ex.q_ab.to_f64() - wide.q_ab).abs() < 1e-6 * wide.q_ab);
+    assert!(((ex.ai_post + ex.aj_post).to_f64() - 3.5).abs() < 1e-6);
+}
@@ -0,0 +1,42 @@
+use rdx_core::scenarios::{preset, PRESETS};
+use rdx_core::sim::Engine;
+
+#[test]
+fn every_preset_builds_an_engine() {
+    for name in PRESETS {
+        let cfg = preset(name).unwrap_or_else(|e| panic!("{name}: {e}"));
+        assert!(Engine::new(cfg).is_ok(), "{name} failed engine checks");
+    }
+}
+
+#[test]
+fn barter_demo_runs_and_trades() {
+    let mut engine = Engine::new(preset("barter_demo").unwrap()).unwrap();
+    engine.run_to_end();
+    assert!(!engine.state().events.is_empty());
+}
+
+#[test]
+fn shock_and_recovery_cuts_the_shocked_goods_then_trades_on() {
+    let cfg = preset("shock_and_recovery").unwrap();
+    let shock = cfg.shocks[0].clone();
+    let mut engine = Engine::new(cfg).unwrap();
+    while engine.state().metrics.len() < shock.round {
+        engine.step_round();
+    }
+    let total = |engine: &Engine| -> f64 {
+        let agents = &engine.state().agents;
+        shock.goods.iter().map(|g| agents.iter().map(|a| a.e[g.index()]).sum::<f64>()).sum()
+    };
+    let before = total(&engine);
+    engine.step_round();
+    assert!(engine.state().metrics.last().unwrap().shocked > 0);
+    assert!(total(&engine) < 0.9 * before, "{} vs {before}", total(&engine));
+    engine.run_to_end();
+    assert!(engine.state().metrics[shock.round + 1..].iter().any(|m| m.trades > 0));
+}
+
+#[test]
+fn unknown_preset_is_an_error() {
+    assert!(preset("no_such_preset").is_err());
+}
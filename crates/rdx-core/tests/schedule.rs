@@ -0,0 +1,29 @@
+mod common;
+
+use rdx_core::model::{ParamChange, ScheduledChange};
+use rdx_core::sim::{init_agents, run, Engine};
+
+#[test]
+fn scheduled_changes_take_effect_at_their_round() {
+    let mut cfg = common::small_config();
+    cfg.schedule = vec![
+        ScheduledChange { round: 2, change: ParamChange::P2pEncountersPerRound(5) },
+        ScheduledChange { round: 4, change: ParamChange::TradeStepCapFrac(0.1) },
+    ];
+    let mut state = init_agents(&cfg).expect("init");
+    run(&cfg, &mut state).expect("run");
+    let encounters: Vec<usize> = state.metrics.iter().map(|m| m.encounters).collect();
+    assert_eq!(encounters, vec![40, 40, 5, 5, 5, 5]);
+
+    let mut engine = Engine::new(cfg.clone()).expect("engine");
+    engine.run_to_end();
+    assert_eq!(engine.config().p2p_encounters_per_round, 5);
+    assert_eq!(engine.config().trade_step_cap_frac, 0.1);
+}
+
+#[test]
+fn invalid_regimes_are_rejected_up_front() {
+    let mut cfg = common::small_config();
+    cfg.schedule = vec![ScheduledChange { round: 3, change: ParamChange::AlphaRange { low: 0.7, high: 0.2 } }];
+    assert!(init_agents(&cfg).is_err());
+}
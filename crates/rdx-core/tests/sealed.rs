@@ -0,0 +1,42 @@
+#![cfg(feature = "sealed")]
+
+mod common;
+
+use rand::prelude::*;
+use rdx_core::codec::sealed::{open, seal, SecretKey, SEALED_TAG};
+use rdx_core::codec::{CodecError, CodecFormat};
+use rdx_core::sim::init_agents;
+
+#[test]
+fn only_the_recipient_can_open() {
+    let mut rng = StdRng::seed_from_u64(11);
+    let beta = init_agents(&common::small_config()).unwrap().agents[0].beta.clone();
+    let alice = SecretKey::generate(&mut rng);
+    let eve = SecretKey::generate(&mut rng);
+
+    let bytes = seal(&alice.public_key(), CodecFormat::Bincode, &beta, &mut rng).unwrap();
+    assert_eq!(bytes[0], SEALED_TAG);
+    assert!(!bytes.windows(8).any(|w| w == beta[0].to_le_bytes()), "the payload is not in the clear");
+    assert_eq!(open::<Vec<f64>>(&alice, &bytes).unwrap(), beta);
+    assert!(matches!(open::<Vec<f64>>(&eve, &bytes), Err(CodecError::Unseal)));
+
+    let restored = SecretKey::from_bytes(alice.to_bytes());
+    assert_eq!(restored.public_key(), alice.public_key());
+    assert_eq!(open::<Vec<f64>>(&restored, &bytes).unwrap(), beta);
+
+    let again = seal(&alice.public_key(), CodecFormat::Bincode, &beta, &mut rng).unwrap();
+    assert_ne!(again, bytes, "every payload gets a fresh ephemeral key");
+}
+
+#[test]
+fn altered_and_foreign_payloads_are_rejected() {
+    let mut rng = StdRng::seed_from_u64(12);
+    let key = SecretKey::generate(&mut rng);
+    let mut bytes = seal(&key.public_key(), CodecFormat::Json, &[0.2, 0.8], &mut rng).unwrap();
+    let last = bytes.len() - 1;
+    bytes[last] ^= 1;
+    assert!(matches!(open::<Vec<f64>>(&key, &bytes), Err(CodecError::Unseal)));
+    assert!(matches!(open::<Vec<f64>>(&key, &bytes[..20]), Err(CodecError::Truncated)));
+    assert!(matches!(open::<Vec<f64>>(&key, b"[0.2,0.8]"), Err(CodecError::UnknownFormat(b'['))));
+    assert!(!format!("{key:?}").contains(&format!("{:?}", key.to_bytes())));
+}
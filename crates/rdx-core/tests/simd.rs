@@ -0,0 +1,50 @@
+#![cfg(feature = "simd")]
+
+use rdx_core::math::ln;
+use rdx_core::math::simd::{ln_in_place, ln_lanes, weighted_log_sum, LANES};
+use rdx_core::preferences::cd_utility;
+
+fn ulps(a: f64, b: f64) -> u64 {
+    a.to_bits().abs_diff(b.to_bits())
+}
+
+#[test]
+fn lane_logarithm_is_within_one_ulp() {
+    let mut x = 1e-300;
+    while x < 1e300 {
+        let lanes = ln_lanes([x, x * 1.37, x * 2.0, x * 3.1]);
+        for (k, f) in [1.0, 1.37, 2.0, 3.1].into_iter().enumerate() {
+            assert!(ulps(lanes[k], ln(x * f)) <= 1, "ln({})", x * f);
+        }
+        x *= 7.3;
+    }
+    assert_eq!(ln_lanes([1.0; LANES]), [0.0; LANES]);
+}
+
+#[test]
+fn special_inputs_fall_back_to_the_scalar_logarithm() {
+    let out = ln_lanes([0.0, f64::MIN_POSITIVE / 4.0, f64::INFINITY, 2.0]);
+    assert_eq!(out[0], f64::NEG_INFINITY);
+    assert_eq!(out[1], ln(f64::MIN_POSITIVE / 4.0));
+    assert_eq!(out[2], f64::INFINITY);
+    assert!(ln_lanes([f64::NAN, 1.0, 1.0, 1.0])[0].is_nan());
+
+    let mut v = vec![0.5, 1.5, 2.5, 3.5, 4.5, 5.5, 6.5];
+    ln_in_place(&mut v);
+    for (k, l) in v.iter().enumerate() {
+        assert!(ulps(*l, ln(0.5 + k as f64)) <= 1);
+    }
+}
+
+#[test]
+fn log_sum_matches_the_sequential_loop_to_rounding() {
+    for n in [1, 3, 4, 5, 64, 257, 1000] {
+        let beta: Vec<f64> = (0..n).map(|k| 1.0 / n as f64 + 1e-4 * (k % 7) as f64).collect();
+        let x: Vec<f64> = (0..n).map(|k| 0.25 + (k % 13) as f64).collect();
+        let sequential: f64 = beta.iter().zip(&x).map(|(b, x)| b * ln(x.max(1e-9))).sum();
+        let chunked = weighted_log_sum(&beta, &x, 1e-9);
+        assert!((chunked - sequential).abs() <= 1e-13 * sequential.abs().max(1.0), "n = {n}");
+        let u = cd_utility(&beta, &x, 1e-9);
+        assert!((u - sequential.exp()).abs() <= 1e-12 * u);
+    }
+}
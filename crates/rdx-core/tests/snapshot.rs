@@ -0,0 +1,75 @@
+mod common;
+
+use rdx_core::codec::{decode, encode};
+use rdx_core::model::Agent;
+use rdx_core::sim::Engine;
+use rdx_core::snapshot::{AgentSnapshot, PopulationSummary};
+
+fn agent() -> Agent {
+    Agent {
+        e: vec![1.2345, 0.5, 3.0],
+        beta: vec![0.2, 0.3, 0.5],
+        alpha_to_base: vec![0.5, 0.6, 0.7],
+        reaction_rules: Vec::new(),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn quantized_roundtrip_through_codec() {
+    let a = agent();
+    let snap = AgentSnapshot::new(&a, 1e-3, false).unwrap();
+    assert!(snap.beta.is_none());
+    assert!(snap.matches_beta(&a.beta));
+
+    let bytes = encode(&snap).unwrap();
+    let back: AgentSnapshot = decode(&bytes).unwrap();
+    assert_eq!(back, snap);
+    for (x, y) in back.endowments().iter().zip(a.e.iter()) {
+        assert!((x - y).abs() <= 0.5e-3 + 1e-12);
+    }
+}
+
+#[test]
+fn deltas_reconstruct_later_snapshot() {
+    let mut a = agent();
+    let before = AgentSnapshot::new(&a, 1e-3, false).unwrap();
+    a.e[1] = 0.75;
+    a.beta = vec![0.1, 0.4, 0.5];
+    let after = AgentSnapshot::new(&a, 1e-3, false).unwrap();
+
+    let delta = after.delta_from(&before).unwrap();
+    assert_eq!(delta.changes.len(), 1);
+    assert!(delta.beta_hash.is_some());
+
+    let mut rebuilt = before.clone();
+    rebuilt.apply_delta(&delta).unwrap();
+    assert_eq!(rebuilt, after);
+}
+
+#[test]
+fn invalid_quantum_is_an_error() {
+    assert!(AgentSnapshot::new(&agent(), 0.0, true).is_err());
+    assert!(AgentSnapshot::new(&agent(), f64::NAN, true).is_err());
+}
+
+#[test]
+fn incremental_summary_matches_recomputation() {
+    let mut engine = Engine::new(common::small_config()).expect("engine");
+    engine.track_summary();
+    for _ in 0..3 {
+        engine.step_round();
+    }
+    let tracked = engine.population_summary();
+    let fresh = PopulationSummary::from_agents(engine.round(), &engine.state().agents);
+    assert_eq!(tracked.count, fresh.count);
+    assert_eq!(tracked.beta, fresh.beta);
+    for (t, f) in tracked.holdings.iter().zip(fresh.holdings.iter()) {
+        assert_eq!(t.quantiles, f.quantiles);
+        assert!((t.mean - f.mean).abs() <= 1e-5 * f.mean.abs().max(1.0));
+    }
+
+    let bytes = encode(&tracked).unwrap();
+    let back: PopulationSummary = decode(&bytes).unwrap();
+    assert_eq!(back, tracked);
+}
@@ -0,0 +1,55 @@
+mod common;
+
+use rdx_core::error::RdxError;
+use rdx_core::metrics::wealth;
+use rdx_core::model::Agent;
+use rdx_core::preferences::cd_utility;
+use rdx_core::sim::{mean_endowments, AgentSoA, Engine};
+use rdx_core::trade::mrs_to_base;
+
+fn traded_state() -> rdx_core::sim::SimState {
+    let mut engine = Engine::new(common::small_config()).unwrap();
+    engine.run_to_end();
+    engine.finish()
+}
+
+#[test]
+fn kernels_match_per_agent_functions_bit_for_bit() {
+    let state = traded_state();
+    let soa = state.agents_soa().unwrap();
+    assert_eq!((soa.len(), soa.goods()), (24, 5));
+    let min_qty = 1e-9;
+    let prices = [1.0, 0.5, 2.0, 1.5];
+    let utilities = soa.utilities(min_qty);
+    let mrs = soa.mrs_to_base(2, 0, min_qty);
+    let w = soa.wealth(&prices);
+    for (k, a) in state.agents.iter().enumerate() {
+        assert_eq!(soa.e(k), &a.e[..]);
+        assert_eq!(soa.ids()[k], a.id);
+        assert_eq!(utilities[k], cd_utility(&a.beta, &a.e, min_qty));
+        assert_eq!(mrs[k], mrs_to_base(&a.beta, &a.e, 2, 0, min_qty));
+        assert_eq!(w[k], wealth(a, &prices));
+    }
+    assert_eq!(soa.mean_endowments(), mean_endowments(&state));
+}
+
+#[test]
+fn write_back_returns_edited_holdings() {
+    let mut state = traded_state();
+    let mut soa = state.agents_soa().unwrap();
+    soa.e_mut(3)[1] = 42.0;
+    soa.write_back(&mut state.agents).unwrap();
+    assert_eq!(state.agents[3].e[1], 42.0);
+    assert!(state.agents[3].utility_cache.is_none());
+    assert_eq!(AgentSoA::from_agents(&state.agents).unwrap(), soa);
+}
+
+#[test]
+fn ragged_populations_are_rejected() {
+    let agent = |n: usize| Agent::builder().endowment(vec![1.0; n]).build().unwrap();
+    let err = AgentSoA::from_agents(&[agent(3), agent(2)]).unwrap_err();
+    assert!(matches!(err, RdxError::DimensionMismatch { expected: 3, found: 2 }));
+    let soa = AgentSoA::from_agents(&[agent(3)]).unwrap();
+    let mut two = vec![agent(3), agent(3)];
+    assert!(soa.write_back(&mut two).is_err());
+}
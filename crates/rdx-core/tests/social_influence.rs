@@ -0,0 +1,55 @@
+mod common;
+
+use rdx_core::dynamics::imitate;
+use rdx_core::ids::{AgentIdx, GoodId};
+use rdx_core::model::{Agent, SocialInfluenceSpec, TradeEvent};
+use rdx_core::preferences::beta_from_alpha_to_base;
+use rdx_core::sim::{init_agents, run};
+
+fn agent(alpha: f64) -> Agent {
+    let alpha_to_base = vec![0.5, alpha];
+    let beta = beta_from_alpha_to_base(&alpha_to_base, 0, 1e-6).unwrap();
+    Agent { e: vec![1.0, 1.0], beta, alpha_to_base, ..Default::default() }
+}
+
+fn event(i: usize, j: usize, delta_u_i: f64, delta_u_j: f64) -> TradeEvent {
+    TradeEvent {
+        round: 0, i: AgentIdx(i), j: AgentIdx(j), good_a: GoodId(1), good_b: GoodId(0), q_ab: 1.0,
+        delta_a_i: 0.1, delta_b_i: -0.1, delta_u_i, delta_u_j, id_i: i as u64, id_j: j as u64, time: None,
+        cost_i: 0.0, cost_j: 0.0, tax: 0.0, speculative: false,
+        seq: 0, pre_i: [0; 32], pre_j: [0; 32], post_i: [0; 32], post_j: [0; 32],
+    }
+}
+
+#[test]
+fn less_successful_partners_imitate() {
+    let mut agents = vec![agent(0.2), agent(0.8), agent(0.6)];
+    // 0 gained less than 1 and than 2; 2 gained less than 1
+    let events = [event(0, 1, 0.1, 0.3), event(2, 0, 0.3, 0.1), event(2, 1, 0.05, 0.2)];
+    assert_eq!(imitate(&mut agents, &events, 0.5, 0).unwrap(), 2);
+
+    // targets use the alphas from before anyone moved
+    assert!((agents[0].alpha_to_base[1] - (0.2 + 0.5 * (0.7 - 0.2))).abs() < 1e-12);
+    assert!((agents[2].alpha_to_base[1] - 0.7).abs() < 1e-12);
+    assert_eq!(agents[1].alpha_to_base[1], 0.8);
+    assert_eq!(agents[0].beta, beta_from_alpha_to_base(&agents[0].alpha_to_base, 0, 1e-6).unwrap());
+}
+
+#[test]
+fn preferences_converge_under_influence() {
+    let spread = |agents: &[Agent]| {
+        let alphas: Vec<f64> = agents.iter().flat_map(|a| a.alpha_to_base.iter().skip(1).copied()).collect();
+        let mean = alphas.iter().sum::<f64>() / alphas.len() as f64;
+        alphas.iter().map(|a| (a - mean).powi(2)).sum::<f64>()
+    };
+    let mut cfg = common::small_config();
+    let initial = init_agents(&cfg).expect("init");
+    cfg.social_influence = Some(SocialInfluenceSpec { rate: 0.5 });
+    let mut state = init_agents(&cfg).expect("init");
+    run(&cfg, &mut state).expect("run");
+    assert!(state.metrics.iter().any(|m| m.imitations > 0));
+    assert!(spread(&state.agents) < spread(&initial.agents));
+
+    cfg.social_influence = Some(SocialInfluenceSpec { rate: 1.5 });
+    assert!(init_agents(&cfg).is_err());
+}
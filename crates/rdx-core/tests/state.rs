@@ -0,0 +1,75 @@
+mod common;
+
+use rdx_core::sim::{init_agents, reconstruct, run, SimState};
+use rdx_core::state::{inclusion_proof, leaf_hash, merkle_root, MerkleTree};
+
+fn finished() -> SimState {
+    let cfg = common::small_config();
+    let mut state = init_agents(&cfg).unwrap();
+    run(&cfg, &mut state).unwrap();
+    state
+}
+
+#[test]
+fn peers_replaying_the_log_agree_on_the_root() {
+    let cfg = common::small_config();
+    let state = finished();
+    let mut replayed = init_agents(&cfg).unwrap();
+    let holdings = reconstruct(&cfg, &state.events).unwrap();
+    for (agent, e) in replayed.agents.iter_mut().zip(holdings.last().unwrap()) {
+        agent.e = e.clone();
+    }
+    assert_eq!(merkle_root(&replayed), merkle_root(&state));
+}
+
+#[test]
+fn root_ignores_agent_order_but_not_holdings() {
+    let state = finished();
+    let root = merkle_root(&state);
+
+    let mut shuffled = state.clone();
+    shuffled.agents.reverse();
+    assert_eq!(merkle_root(&shuffled), root);
+
+    let mut changed = state.clone();
+    changed.agents[3].e[1] += 1e-12;
+    assert_ne!(merkle_root(&changed), root);
+
+    let mut zeroed = state.clone();
+    zeroed.agents[0].e[0] = 0.0;
+    let mut negative_zero = state;
+    negative_zero.agents[0].e[0] = -0.0;
+    assert_eq!(merkle_root(&zeroed), merkle_root(&negative_zero));
+}
+
+#[test]
+fn every_agent_has_a_verifying_proof() {
+    // 24 agents: the odd levels above 3 and 6 nodes exercise unpaired nodes
+    let state = finished();
+    let tree = MerkleTree::from_state(&state);
+    let root = tree.root();
+    assert_eq!(tree.len(), state.agents.len());
+    for agent in &state.agents {
+        let proof = tree.proof(agent.id).expect("a proof per agent");
+        assert!(proof.verify(&root, &agent.e), "agent {}", agent.id);
+        let mut wrong = agent.e.clone();
+        wrong[0] *= 1.5;
+        assert!(!proof.verify(&root, &wrong));
+    }
+    assert!(tree.proof(10_000).is_none());
+}
+
+#[test]
+fn small_trees() {
+    let mut state = finished();
+    state.agents.truncate(1);
+    let only = &state.agents[0];
+    assert_eq!(merkle_root(&state), leaf_hash(only.id, &only.e));
+    let proof = inclusion_proof(&state, only.id).unwrap();
+    assert!(proof.steps.is_empty());
+    assert!(proof.verify(&merkle_root(&state), &only.e));
+
+    state.agents.clear();
+    assert!(MerkleTree::from_state(&state).is_empty());
+    assert_ne!(merkle_root(&state), [0; 32]);
+}
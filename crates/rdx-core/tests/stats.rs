@@ -0,0 +1,59 @@
+use rand::prelude::*;
+use rdx_core::math::{weighted_mean, weighted_variance, Ema, Histogram, P2Quantile, WeightedStats};
+
+#[test]
+fn weighted_moments_match_direct_formulas() {
+    let x = [1.0, 2.0, 4.0];
+    let w = [1.0, 1.0, 2.0];
+    assert!((weighted_mean(&x, &w) - 2.75).abs() < 1e-12);
+    // Σ w (x - 2.75)² / 4 = (3.0625 + 0.5625 + 2 * 1.5625) / 4
+    assert!((weighted_variance(&x, &w) - 1.6875).abs() < 1e-12);
+    assert_eq!(weighted_mean(&[], &[]), 0.0);
+
+    let mut acc = WeightedStats::default();
+    assert_eq!(acc.min(), None);
+    acc.push(3.0, 0.0);
+    acc.push(f64::NAN, 1.0);
+    assert_eq!(acc.count(), 1);
+    assert_eq!(acc.mean(), 0.0);
+    assert_eq!(acc.min(), Some(3.0));
+}
+
+#[test]
+fn p2_tracks_uniform_quantiles() {
+    let mut rng = StdRng::seed_from_u64(11);
+    let mut median = P2Quantile::new(0.5);
+    let mut p90 = P2Quantile::new(0.9);
+    for _ in 0..20_000 {
+        let x: f64 = rng.gen();
+        median.push(x);
+        p90.push(x);
+    }
+    assert!((median.estimate() - 0.5).abs() < 0.02, "{}", median.estimate());
+    assert!((p90.estimate() - 0.9).abs() < 0.02, "{}", p90.estimate());
+
+    let mut few = P2Quantile::new(0.5);
+    for x in [5.0, 1.0, 3.0] {
+        few.push(x);
+    }
+    assert_eq!(few.estimate(), 3.0);
+}
+
+#[test]
+fn ema_and_histogram() {
+    let mut ema = Ema::new(0.5);
+    assert_eq!(ema.value(), None);
+    assert_eq!(ema.push(4.0), Some(4.0));
+    assert_eq!(ema.push(0.0), Some(2.0));
+    assert!((Ema::with_half_life(1.0).alpha() - 0.5).abs() < 1e-12);
+
+    assert!(Histogram::new(1.0, 1.0, 4).is_none());
+    let mut h = Histogram::new(0.0, 1.0, 4).expect("valid range");
+    for x in [-0.5, 0.0, 0.3, 0.3, 0.99, 1.0] {
+        h.push(x);
+    }
+    assert_eq!(h.counts(), &[1.0, 2.0, 0.0, 1.0]);
+    assert_eq!((h.underflow(), h.overflow()), (1.0, 1.0));
+    assert_eq!(h.bin_range(1), (0.25, 0.5));
+    assert_eq!(h.total(), 6.0);
+}
@@ -0,0 +1,37 @@
+mod common;
+
+use rdx_core::model::StepCap;
+use rdx_core::sim::{init_agents, run};
+use rdx_core::trade::{best_trade_against_base, default_oracle};
+
+#[test]
+fn candidates_carry_improving_concave_shapes() {
+    let cfg = common::small_config();
+    let state = init_agents(&cfg).expect("init");
+    let oracle = default_oracle();
+
+    let mut found = 0;
+    for w in state.agents.windows(2) {
+        let Some(c) = best_trade_against_base(&w[0], &w[1], cfg.base_good, cfg.min_qty, cfg.oracle_bisect_iters, &oracle) else { continue };
+        found += 1;
+        for s in [c.shape_i, c.shape_j] {
+            assert!(s.slope > 0.0, "a mutually improving trade starts uphill for both");
+            assert!(s.curvature <= 0.0);
+        }
+        let t = c.adaptive_step_frac(0.05, 0.8);
+        assert!((0.05..=0.8).contains(&t));
+    }
+    assert!(found > 0);
+}
+
+#[test]
+fn adaptive_step_cap_runs_and_is_validated() {
+    let mut cfg = common::small_config();
+    cfg.step_cap = StepCap::Adaptive { min_frac: 0.05, max_frac: 1.0 };
+    let mut state = init_agents(&cfg).expect("init");
+    run(&cfg, &mut state).expect("run");
+    assert!(!state.events.is_empty());
+
+    cfg.step_cap = StepCap::Adaptive { min_frac: 0.9, max_frac: 0.2 };
+    assert!(init_agents(&cfg).is_err());
+}
@@ -0,0 +1,94 @@
+mod common;
+
+use std::collections::HashMap;
+
+use rdx_core::model::{PostedPriceSpec, Scheduler, SimConfig, TriadSpec};
+use rdx_core::sim::{init_agents, run, Engine, SimState};
+
+fn run_config(cfg: &SimConfig) -> SimState {
+    let mut state = init_agents(cfg).expect("init");
+    run(cfg, &mut state).expect("run");
+    state
+}
+
+/// Most trades and cycles any one agent took part in within a round.
+fn busiest(state: &SimState) -> usize {
+    let mut counts: HashMap<(usize, usize), usize> = HashMap::new();
+    for e in state.events.iter() {
+        for m in [e.i, e.j] {
+            *counts.entry((e.round, m.index())).or_default() += 1;
+        }
+    }
+    for c in state.cycles.iter() {
+        for m in c.agents {
+            *counts.entry((c.round, m.index())).or_default() += 1;
+        }
+    }
+    counts.into_values().max().unwrap_or(0)
+}
+
+#[test]
+fn no_agent_trades_past_its_budget() {
+    let mut cfg = common::small_config();
+    assert!(busiest(&run_config(&cfg)) > 1);
+
+    cfg.max_trades_per_agent = Some(1);
+    let state = run_config(&cfg);
+    assert!(!state.events.is_empty());
+    assert_eq!(busiest(&state), 1);
+    assert!(state.metrics.iter().map(|m| m.over_budget).sum::<usize>() > 0);
+    assert!(state.metrics.iter().all(|m| m.trades <= cfg.num_agents / 2));
+
+    cfg.triads = Some(TriadSpec::default());
+    cfg.max_trades_per_agent = Some(2);
+    assert!(busiest(&run_config(&cfg)) <= 2);
+}
+
+#[test]
+fn round_robin_refuses_agents_over_budget() {
+    let mut cfg = common::small_config();
+    cfg.scheduler = Scheduler::RoundRobin;
+    cfg.p2p_encounters_per_round = 120;
+    cfg.max_trades_per_agent = Some(2);
+    let state = run_config(&cfg);
+    assert!(!state.events.is_empty());
+    assert!(busiest(&state) <= 2);
+    // a round-robin encounter cannot be redrawn, so the refused ones still count
+    assert!(state.metrics.iter().all(|m| m.encounters == 120));
+}
+
+#[test]
+fn marketplace_sales_count_against_the_budget() {
+    let mut cfg = common::small_config();
+    cfg.posted_prices = Some(PostedPriceSpec::default());
+    cfg.p2p_encounters_per_round = 0;
+    assert!(busiest(&run_config(&cfg)) > 1);
+
+    cfg.max_trades_per_agent = Some(1);
+    let state = run_config(&cfg);
+    assert!(state.metrics.iter().map(|m| m.market_sales).sum::<usize>() > 0);
+    assert_eq!(busiest(&state), 1);
+    assert!(state.metrics.iter().map(|m| m.over_budget).sum::<usize>() > 0);
+
+    // encounters spend the budget before the marketplace opens
+    cfg.p2p_encounters_per_round = 40;
+    assert_eq!(busiest(&run_config(&cfg)), 1);
+}
+
+#[test]
+fn a_slack_budget_leaves_the_run_unchanged() {
+    let mut cfg = common::small_config();
+    let unlimited = run_config(&cfg);
+    cfg.max_trades_per_agent = Some(1000);
+    let slack = run_config(&cfg);
+    assert_eq!(serde_json::to_string(&slack.events).unwrap(), serde_json::to_string(&unlimited.events).unwrap());
+    assert!(slack.metrics.iter().all(|m| m.over_budget == 0));
+}
+
+#[test]
+fn zero_budget_is_refused() {
+    let mut cfg = common::small_config();
+    cfg.max_trades_per_agent = Some(0);
+    assert!(!cfg.validate().is_empty());
+    assert!(Engine::new(cfg).is_err());
+}
@@ -0,0 +1,36 @@
+mod common;
+
+use rdx_core::sim::{init_agents, run, trade_graph};
+
+#[test]
+fn graph_conserves_traded_quantities() {
+    let cfg = common::small_config();
+    let mut state = init_agents(&cfg).expect("init");
+    run(&cfg, &mut state).expect("run");
+    assert!(!state.events.is_empty());
+
+    let g = trade_graph(&state);
+    assert_eq!(g.num_agents, cfg.num_agents);
+    let flow: f64 = g.edges.iter().map(|e| e.quantity).sum();
+    let traded: f64 = state.events.iter().map(|ev| ev.delta_a_i.abs() + ev.delta_b_i.abs()).sum();
+    assert!((flow - traded).abs() <= 1e-9 * traded.max(1.0));
+    assert_eq!(g.edges.iter().map(|e| e.trades).sum::<usize>(), 2 * state.events.len());
+    assert!(g.edges.iter().all(|e| e.from != e.to));
+}
+
+#[test]
+fn dot_and_graphml_list_every_edge() {
+    let cfg = common::small_config();
+    let mut state = init_agents(&cfg).expect("init");
+    run(&cfg, &mut state).expect("run");
+    let g = trade_graph(&state);
+
+    let dot = g.to_dot(&cfg.base_goods);
+    assert!(dot.starts_with("digraph trades {"));
+    assert_eq!(dot.matches(" -> ").count(), g.edges.len());
+
+    let xml = g.to_graphml(&cfg.base_goods);
+    assert_eq!(xml.matches("<edge ").count(), g.edges.len());
+    assert_eq!(xml.matches("<node ").count(), cfg.num_agents);
+    assert!(xml.trim_end().ends_with("</graphml>"));
+}
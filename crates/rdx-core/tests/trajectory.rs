@@ -0,0 +1,65 @@
+mod common;
+
+use std::collections::BTreeSet;
+use std::sync::{Arc, Mutex};
+use rdx_core::model::SimConfig;
+use rdx_core::preferences::cd_utility;
+use rdx_core::sim::{Engine, SimState};
+use rdx_core::trajectory::{AgentSelection, TrajectoryPoint, TrajectoryRecorder};
+
+fn record(cfg: &SimConfig, selection: AgentSelection) -> (Vec<TrajectoryPoint>, SimState) {
+    let recorder = Arc::new(Mutex::new(TrajectoryRecorder::new(selection, cfg.min_qty)));
+    let mut engine = Engine::new(cfg.clone()).unwrap();
+    engine.add_observer(Box::new(recorder.clone()));
+    engine.run_to_end();
+    let state = engine.finish();
+    let points = recorder.lock().unwrap().points().to_vec();
+    (points, state)
+}
+
+#[test]
+fn all_records_every_agent_every_round() {
+    let cfg = common::small_config();
+    let (points, state) = record(&cfg, AgentSelection::All);
+    assert_eq!(points.len(), cfg.num_agents * state.metrics.len());
+
+    // the last round's points are the final holdings
+    let last = state.metrics.last().unwrap().round;
+    let finals: Vec<_> = points.iter().filter(|p| p.round == last).collect();
+    for (p, a) in finals.iter().zip(state.agents.iter()) {
+        assert_eq!(p.id, a.id);
+        assert_eq!(p.e, a.e);
+        assert_eq!(p.utility, cd_utility(&a.beta, &a.e, cfg.min_qty));
+    }
+}
+
+#[test]
+fn sample_follows_the_same_agents() {
+    let cfg = common::small_config();
+    let selection = AgentSelection::Sample { count: 5, seed: 11 };
+    let (points, state) = record(&cfg, selection.clone());
+    assert_eq!(points.len(), 5 * state.metrics.len());
+
+    let ids = |round| points.iter().filter(|p| p.round == round).map(|p| p.id).collect::<BTreeSet<_>>();
+    let first = ids(state.metrics[0].round);
+    assert_eq!(first.len(), 5);
+    for m in state.metrics.iter() {
+        assert_eq!(ids(m.round), first);
+    }
+
+    // same seed, same sample
+    let (again, _) = record(&cfg, selection);
+    assert_eq!(again, points);
+}
+
+#[test]
+fn ids_record_only_those_listed() {
+    let cfg = common::small_config();
+    let engine = Engine::new(cfg.clone()).unwrap();
+    let agents = &engine.state().agents;
+    let wanted = vec![agents[3].id, agents[10].id, u64::MAX];
+    let (points, state) = record(&cfg, AgentSelection::Ids(wanted.clone()));
+
+    assert_eq!(points.len(), 2 * state.metrics.len());
+    assert!(points.iter().all(|p| wanted[..2].contains(&p.id)));
+}
@@ -0,0 +1,64 @@
+mod common;
+
+use rdx_core::ids::GoodId;
+use rdx_core::model::{Agent, CostSettlement, TransactionCost};
+use rdx_core::sim::{init_agents, run};
+use rdx_core::trade::{apply_trade, apply_trade_with_cost, TradeCandidate};
+
+fn pair() -> (Agent, Agent, TradeCandidate) {
+    let i = Agent { e: vec![2.0, 1.0], beta: vec![0.5, 0.5], alpha_to_base: vec![0.5, 0.5], ..Default::default() };
+    let j = Agent { e: vec![1.0, 2.0], beta: vec![0.5, 0.5], alpha_to_base: vec![0.5, 0.5], ..Default::default() };
+    // i buys 0.4 of good 1 for 0.4 of the base good
+    let cand = TradeCandidate {
+        good_a: GoodId(1), good_b: GoodId(0), q_ab: 1.0,
+        delta_a_i: 0.4, delta_b_i: -0.4, ..Default::default()
+    };
+    (i, j, cand)
+}
+
+#[test]
+fn costs_are_charged_in_base_good_or_burned() {
+    let (mut i0, mut j0, cand) = pair();
+    apply_trade(&mut i0, &mut j0, &cand, 1e-9).unwrap();
+
+    let (mut i, mut j, _) = pair();
+    let cost = TransactionCost { proportional: 0.0, fixed: 0.1, settlement: CostSettlement::BaseGood };
+    let (ci, cj) = apply_trade_with_cost(&mut i, &mut j, &cand, &cost, 0, 1e-9).unwrap();
+    assert_eq!((ci, cj), (0.1, 0.1));
+    assert!((i.e[0] - (i0.e[0] - 0.1)).abs() < 1e-12 && i.e[1] == i0.e[1]);
+    assert!((j.e[0] - (j0.e[0] - 0.1)).abs() < 1e-12 && j.e[1] == j0.e[1]);
+
+    // burned: 5% of each received leg is lost, the base good is untouched otherwise
+    let (mut i, mut j, _) = pair();
+    let cost = TransactionCost { proportional: 0.05, fixed: 0.0, settlement: CostSettlement::Burned };
+    apply_trade_with_cost(&mut i, &mut j, &cand, &cost, 0, 1e-9).unwrap();
+    assert!((i.e[1] - (i0.e[1] - 0.05 * 0.4)).abs() < 1e-12 && i.e[0] == i0.e[0]);
+    assert!((j.e[0] - (j0.e[0] - 0.05 * 0.4)).abs() < 1e-12 && j.e[1] == j0.e[1]);
+}
+
+#[test]
+fn trades_must_clear_their_cost() {
+    let mut cfg = common::small_config();
+    cfg.transaction_cost = Some(TransactionCost { proportional: 0.01, fixed: 0.0, ..Default::default() });
+    let mut state = init_agents(&cfg).expect("init");
+    run(&cfg, &mut state).expect("run");
+    let paid: f64 = state.metrics.iter().map(|m| m.costs).sum();
+    let charged: f64 = state.events.iter().map(|e| e.cost_i + e.cost_j).sum();
+    assert!(paid > 0.0);
+    assert!((paid - charged).abs() < 1e-9);
+    assert!(state.events.iter().all(|e| e.delta_u_i > 0.0 && e.delta_u_j > 0.0));
+
+    // a fee larger than any gain stops all trade
+    cfg.transaction_cost = Some(TransactionCost { proportional: 0.0, fixed: 10.0, ..Default::default() });
+    let mut state = init_agents(&cfg).expect("init");
+    run(&cfg, &mut state).expect("run");
+    assert!(state.events.is_empty());
+    assert!(state.metrics.iter().map(|m| m.cost_refused).sum::<usize>() > 0);
+}
+
+#[test]
+fn invalid_costs_are_rejected() {
+    let mut cfg = common::small_config();
+    cfg.transaction_cost = Some(TransactionCost { proportional: 1.0, ..Default::default() });
+    assert!(init_agents(&cfg).is_err());
+}
@@ -0,0 +1,47 @@
+mod common;
+
+use rdx_core::ids::GoodId;
+use rdx_core::model::{Agent, TriadSpec};
+use rdx_core::sim::{init_agents, run};
+use rdx_core::trade::{apply_three_way_cycle, best_three_way_cycle, TradeRules};
+
+#[test]
+fn cycle_resolves_missing_double_coincidence() {
+    // each agent holds the good the next one wants
+    let mut i = common::agent(vec![4.0, 0.1, 0.1], vec![0.1, 0.1, 0.8]);
+    let mut j = common::agent(vec![0.1, 4.0, 0.1], vec![0.8, 0.1, 0.1]);
+    let mut k = common::agent(vec![0.1, 0.1, 4.0], vec![0.1, 0.8, 0.1]);
+    let cycle = best_three_way_cycle(&i, &j, &k, 0.2, 1e-9, &TradeRules::default()).expect("cycle");
+    assert_eq!(cycle.goods, [GoodId(0), GoodId(1), GoodId(2)]);
+    assert!(cycle.delta_u.iter().all(|&du| du > 0.0));
+
+    let totals = |a: &[&Agent]| (0..3).map(|g| a.iter().map(|x| x.e[g]).sum::<f64>()).collect::<Vec<_>>();
+    let before = totals(&[&i, &j, &k]);
+    apply_three_way_cycle(&mut i, &mut j, &mut k, &cycle, 1e-9).unwrap();
+    for (b, a) in before.iter().zip(totals(&[&i, &j, &k])) {
+        assert!((b - a).abs() < 1e-12);
+    }
+    assert!(i.e[2] > 0.1 && j.e[0] > 0.1 && k.e[1] > 0.1);
+
+    // blocking a leg rules the cycle out
+    let rules = TradeRules { blocked: vec![false, true, false], ..Default::default() };
+    assert!(best_three_way_cycle(&i, &j, &k, 0.2, 1e-9, &rules).is_none());
+}
+
+#[test]
+fn engine_records_cycles() {
+    let mut cfg = common::small_config();
+    cfg.triads = Some(TriadSpec::default());
+    let mut state = init_agents(&cfg).expect("init");
+    run(&cfg, &mut state).expect("run");
+    let counted: usize = state.metrics.iter().map(|m| m.cycles).sum();
+    assert_eq!(counted, state.cycles.len());
+    for c in state.cycles.iter() {
+        assert!(c.delta_u.iter().all(|&du| du > 0.0));
+        let [a, b, k] = c.agents.map(|a| a.index());
+        assert!(a != b && b != k && a != k);
+    }
+
+    cfg.triads = Some(TriadSpec { step: 1.5 });
+    assert!(init_agents(&cfg).is_err());
+}
@@ -0,0 +1,57 @@
+mod common;
+
+use rdx_core::model::Agent;
+use rdx_core::preferences::{cd_utility, log_cd_utility};
+use rdx_core::sim::Engine;
+
+fn agent() -> Agent {
+    Agent::builder().endowment(vec![2.0, 1.0, 4.0]).beta(vec![0.2, 0.3, 0.5]).build().unwrap()
+}
+
+fn close(a: f64, b: f64) -> bool {
+    (a - b).abs() <= 1e-12 * a.abs().max(1.0)
+}
+
+#[test]
+fn fresh_cache_matches_cd_utility_exactly() {
+    let mut a = agent();
+    let exact = cd_utility(&a.beta, &a.e, 1e-9);
+    assert_eq!(a.utility(1e-9), exact);
+    a.refresh_utility(1e-9);
+    assert_eq!(a.utility(1e-9), exact);
+}
+
+#[test]
+fn set_holding_moves_the_cache_by_one_term() {
+    let mut a = agent();
+    a.refresh_utility(1e-9);
+    a.set_holding(1, 3.5, 1e-9);
+    a.set_holding(2, 0.0, 1e-9);
+    let cached = a.utility_cache.expect("kept").log_utility;
+    assert!(close(cached, log_cd_utility(&a.beta, &a.e, 1e-9)));
+    // a cache taken at another floor is dropped, not reused
+    a.set_holding(0, 1.0, 1e-6);
+    assert!(a.utility_cache.is_none());
+    assert_eq!(a.log_utility(1e-6), log_cd_utility(&a.beta, &a.e, 1e-6));
+}
+
+#[test]
+fn engine_caches_match_recomputation_after_trading() {
+    let cfg = common::small_config();
+    let min_qty = cfg.min_qty;
+    let mut engine = Engine::new(cfg).unwrap();
+    engine.run_to_end();
+    let state = engine.finish();
+    assert!(!state.events.is_empty());
+    let mut cached = 0;
+    for a in state.agents.iter() {
+        if let Some(c) = a.utility_cache {
+            assert!(close(c.log_utility, log_cd_utility(&a.beta, &a.e, min_qty)));
+            cached += 1;
+        }
+    }
+    assert!(cached > 0);
+    for ev in state.events.iter().filter(|e| !e.speculative) {
+        assert!(ev.delta_u_i > 0.0 && ev.delta_u_j > 0.0);
+    }
+}
@@ -0,0 +1,41 @@
+mod common;
+
+use std::collections::BTreeMap;
+use rdx_core::model::AgentGroupSpec;
+use rdx_core::reaction::ReactionRuleSpec;
+use rdx_core::scenarios;
+
+#[test]
+fn valid_configs_have_no_problems() {
+    assert!(common::small_config().validate().is_empty());
+    for name in scenarios::PRESETS {
+        assert!(scenarios::preset(name).unwrap().validate().is_empty(), "{name}");
+    }
+}
+
+#[test]
+fn every_problem_is_reported() {
+    let mut cfg = common::small_config();
+    cfg.base_good = 9;
+    cfg.base_goods_quantity = 4;
+    cfg.alpha_high = 1.5;
+    cfg.trade_step_cap_frac = 0.0;
+    cfg.min_qty = -1.0;
+    cfg.oracle_bisect_iters = 0;
+    cfg.reaction_rules = vec![ReactionRuleSpec {
+        id: "r0".into(), size_class: "S".into(), name: "press".into(), lead: 1,
+        inputs: BTreeMap::from([(2, 1.0)]), outputs: BTreeMap::from([(7, 1.0)]),
+    }];
+    cfg.num_agents = 8;
+    cfg.agent_groups = vec![AgentGroupSpec {
+        name: "a".into(), size: 8, endowment: None, alpha_low: Some(0.9), alpha_high: Some(0.2), reaction_rules: None,
+    }];
+
+    let problems = cfg.validate();
+    let fields: Vec<&str> = problems.iter().map(|p| p.field.as_str()).collect();
+    assert_eq!(fields, vec![
+        "base_goods_quantity", "base_good", "alpha_low", "agent_groups[0].alpha_low", "reaction_rules[0]",
+        "trade_step_cap_frac", "min_qty", "oracle_bisect_iters",
+    ]);
+    assert!(problems[4].to_string().contains("good 7"));
+}
@@ -0,0 +1,45 @@
+mod common;
+
+use rdx_core::ids::GoodId;
+use rdx_core::metrics::{update_emergent_prices, wealth};
+use rdx_core::model::Agent;
+use rdx_core::sim::{init_agents, run, PairRateStat};
+
+fn rate(good_a: usize, good_b: usize, mean_q_ab: f64) -> PairRateStat {
+    PairRateStat {
+        round: 0, good_a: GoodId(good_a), good_b: GoodId(good_b), trades: 1, volume_a: 1.0,
+        mean_q_ab, std_q_ab: 0.0, min_q_ab: mean_q_ab, max_q_ab: mean_q_ab,
+    }
+}
+
+#[test]
+fn wealth_values_bundle_at_prices() {
+    let a = Agent { e: vec![2.0, 1.0, 4.0], beta: vec![], alpha_to_base: vec![], reaction_rules: vec![], ..Default::default() };
+    assert_eq!(wealth(&a, &[1.0, 3.0, 0.5]), 7.0);
+    // missing prices count as 1
+    assert_eq!(wealth(&a, &[1.0]), 7.0);
+}
+
+#[test]
+fn emergent_prices_carry_forward_untraded_goods() {
+    let mut prices = vec![1.0; 3];
+    update_emergent_prices(&mut prices, &[rate(1, 0, 2.5), rate(2, 1, 9.0)], 0);
+    assert_eq!(prices, vec![1.0, 2.5, 1.0]);
+    update_emergent_prices(&mut prices, &[rate(2, 0, 0.4)], 0);
+    assert_eq!(prices, vec![1.0, 2.5, 0.4]);
+}
+
+#[test]
+fn rounds_record_prices_and_wealth_distribution() {
+    let cfg = common::small_config();
+    let mut state = init_agents(&cfg).expect("init");
+    run(&cfg, &mut state).expect("run");
+    for m in state.metrics.iter() {
+        assert_eq!(m.prices.len(), cfg.base_goods_quantity);
+        assert_eq!(m.prices[cfg.base_good], 1.0);
+        assert!(m.prices.iter().all(|p| p.is_finite() && *p > 0.0));
+        let q = m.inequality.wealth_quantiles;
+        assert!(q.windows(2).all(|w| w[0] <= w[1]));
+        assert!(m.inequality.mean_wealth > 0.0);
+    }
+}
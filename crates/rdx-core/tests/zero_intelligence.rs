@@ -0,0 +1,52 @@
+mod common;
+
+use rand::prelude::*;
+use rdx_core::counterfactual::zero_intelligence_baseline;
+use rdx_core::model::{DecisionNoise, ZeroIntelligenceSpec};
+use rdx_core::sim::{init_agents, run};
+use rdx_core::trade::{zero_intelligence_trade, TradeRules};
+
+#[test]
+fn random_proposals_stay_within_holdings() {
+    let i = common::agent(vec![4.0, 1.0, 1.0], vec![0.2, 0.4, 0.4]);
+    let j = common::agent(vec![1.0, 4.0, 4.0], vec![0.6, 0.2, 0.2]);
+    let mut rng = StdRng::seed_from_u64(11);
+    let mut accepted = 0;
+    for _ in 0..200 {
+        let unconstrained = zero_intelligence_trade(&i, &j, false, 1e-9, &TradeRules::default(), &mut rng)
+            .expect("both hold every good");
+        let (a, b) = (unconstrained.good_a.index(), unconstrained.good_b.index());
+        assert_ne!(a, b);
+        assert!(unconstrained.delta_a_i > 0.0 && unconstrained.delta_a_i <= j.e[a]);
+        assert!(unconstrained.delta_b_i < 0.0 && -unconstrained.delta_b_i <= i.e[b]);
+
+        if let Some(c) = zero_intelligence_trade(&i, &j, true, 1e-9, &TradeRules::default(), &mut rng) {
+            assert!(c.delta_u_i > 0.0 && c.delta_u_j > 0.0);
+            accepted += 1;
+        }
+    }
+    assert!(accepted > 0);
+
+    // with a single tradable good there is no pair to draw
+    let rules = TradeRules { blocked: vec![false, true, true], ..Default::default() };
+    assert!(zero_intelligence_trade(&i, &j, false, 1e-9, &rules, &mut rng).is_none());
+}
+
+#[test]
+fn oracle_beats_zero_intelligence_on_the_same_encounters() {
+    let cfg = common::small_config();
+    let split = zero_intelligence_baseline(&cfg, ZeroIntelligenceSpec::default()).expect("baseline");
+    assert!(split.total > 0.0 && split.matching > 0.0);
+    assert!(split.mechanism > 0.0);
+    assert!((split.total - split.matching - split.mechanism).abs() < 1e-12);
+
+    // unconstrained traders accept losing trades
+    let mut cfg = common::small_config();
+    cfg.zero_intelligence = Some(ZeroIntelligenceSpec { constrained: false });
+    let mut state = init_agents(&cfg).expect("init");
+    run(&cfg, &mut state).expect("run");
+    assert!(state.events.iter().any(|e| e.delta_u_i < 0.0 || e.delta_u_j < 0.0));
+
+    cfg.decision_noise = Some(DecisionNoise { sigma: 0.1 });
+    assert!(init_agents(&cfg).is_err());
+}
@@ -0,0 +1,22 @@
+use rdx_core::codec::CodecError;
+use rdx_core::protocol::ProtocolError;
+use thiserror::Error;
+use crate::payload::AgentAddress;
+
+#[derive(Debug, Error)]
+pub enum P2pError {
+    #[error(transparent)]
+    Codec(#[from] CodecError),
+
+    #[error(transparent)]
+    Protocol(#[from] ProtocolError),
+
+    #[error("payload signature does not verify against {0}")]
+    BadSignature(AgentAddress),
+
+    #[error("no hosted agent at {0}")]
+    UnknownAgent(AgentAddress),
+
+    #[error("no negotiation {id} with {peer}")]
+    UnknownNegotiation { peer: AgentAddress, id: u64 },
+}
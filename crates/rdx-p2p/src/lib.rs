@@ -0,0 +1,21 @@
+//! Distributed exchange prototype: each process hosts some agents of a population, gossips
+//! their signed preference profiles, negotiates trades with agents elsewhere through
+//! `rdx_core::protocol` and settles the committed ones with the shared Walras oracle.
+//!
+//! - payload: agent addresses, profile announcements and negotiation messages, signed with the
+//!   hosting node's Ed25519 key and encoded as `codec::Envelope`s
+//! - node: the transport-independent `Node` (directory of known profiles, negotiations in flight,
+//!   settlement of committed trades)
+//! - network (feature `libp2p`, on by default): gossipsub announcements, request-response
+//!   negotiation and mDNS discovery around a `Node`
+//! - error: `P2pError`
+
+pub mod error;
+#[cfg(feature = "libp2p")]
+pub mod network;
+pub mod node;
+pub mod payload;
+
+pub use error::P2pError;
+pub use node::{Node, NodeConfig, Settlement};
+pub use payload::{AgentAddress, Announcement, SignedPayload, Wire};
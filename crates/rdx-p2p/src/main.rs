@@ -0,0 +1,75 @@
+use std::time::Duration;
+use clap::Parser;
+use ed25519_dalek::SigningKey;
+use rand::rngs::OsRng;
+use rdx_core::model::SimConfig;
+use rdx_core::protocol::Party;
+use rdx_core::sim::init_agents;
+use rdx_p2p::network::{run, NetworkConfig};
+use rdx_p2p::{Node, NodeConfig};
+
+/// Host some agents of a config's population and trade them with other rdx-p2p processes
+#[derive(Parser, Debug)]
+#[command(name="rdx-p2p")]
+struct Args {
+    /// Config (JSON) whose initial population the agents come from
+    #[arg(long)]
+    config: String,
+
+    /// First agent of the population to host
+    #[arg(long, default_value_t=0)]
+    first: usize,
+
+    /// Number of agents to host
+    #[arg(long, default_value_t=1)]
+    count: usize,
+
+    /// Listen address (repeatable)
+    #[arg(long, default_value="/ip4/0.0.0.0/tcp/0")]
+    listen: Vec<String>,
+
+    /// Peer to dial at startup (repeatable); peers on the local network are found by mDNS
+    #[arg(long)]
+    dial: Vec<String>,
+
+    /// Milliseconds between announce-and-propose rounds
+    #[arg(long, default_value_t=2000)]
+    tick_ms: u64,
+
+    /// Publish differentially private exponents with this privacy budget
+    #[arg(long)]
+    epsilon: Option<f64>,
+
+    /// Reject poor proposals instead of countering with the oracle's exchange
+    #[arg(long)]
+    no_counter_offers: bool,
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let args = Args::parse();
+    let text = std::fs::read_to_string(&args.config)?;
+    let cfg: SimConfig = serde_json::from_str(&text)?;
+    let agents = init_agents(&cfg)?.agents;
+    let hosted: Vec<Party> = agents.iter()
+        .skip(args.first)
+        .take(args.count)
+        .map(|a| Party { beta: a.beta.clone(), holdings: a.e.clone(), min_qty: cfg.min_qty })
+        .collect();
+    anyhow::ensure!(!hosted.is_empty(), "agents {}..{} are not in the population", args.first, args.first + args.count);
+
+    let node_cfg = NodeConfig {
+        min_qty: cfg.min_qty,
+        oracle_iters: cfg.oracle_bisect_iters,
+        counter_offers: !args.no_counter_offers,
+        privacy_epsilon: args.epsilon,
+        ..NodeConfig::default()
+    };
+    let node = Node::new(SigningKey::generate(&mut OsRng), hosted, node_cfg, cfg.seed);
+    let net = NetworkConfig {
+        listen: args.listen.iter().map(|a| a.parse()).collect::<Result<_, _>>()?,
+        dial: args.dial.iter().map(|a| a.parse()).collect::<Result<_, _>>()?,
+        tick: Duration::from_millis(args.tick_ms),
+    };
+    run(node, net).await.map_err(|e| anyhow::anyhow!(e))
+}
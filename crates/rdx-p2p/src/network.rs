@@ -0,0 +1,167 @@
+//! libp2p transport for a `Node`: announcements over gossipsub, negotiation messages over a
+//! request-response protocol, peers found by mDNS or dialled explicitly.
+//!
+//! Every negotiation hop is a request whose response carries the counterpart's reply, if any;
+//! a reply that calls for an answer (an `Accept` after a counter-offer, say) goes out as the
+//! next request. Transport encryption and peer authentication come from Noise; the payload
+//! signatures bind each message to the node that hosts its agent, independently of the
+//! connection it arrived on.
+
+use std::collections::BTreeMap;
+use std::time::Duration;
+use futures::StreamExt;
+use libp2p::request_response::{self, ProtocolSupport};
+use libp2p::swarm::{NetworkBehaviour, SwarmEvent};
+use libp2p::{gossipsub, mdns, noise, tcp, yamux, Multiaddr, PeerId, StreamProtocol, Swarm};
+use serde::{Serialize, Deserialize};
+use crate::node::Node;
+use crate::payload::SignedPayload;
+
+/// Gossipsub topic of profile announcements.
+pub const ANNOUNCE_TOPIC: &str = "rdx/profiles/1";
+
+/// Request-response protocol of negotiation messages.
+pub const NEGOTIATE_PROTOCOL: &str = "/rdx/negotiate/1";
+
+/// Response to a negotiation request: the signed reply, if the message called for one.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Response(pub Option<SignedPayload>);
+
+#[derive(NetworkBehaviour)]
+pub struct Behaviour {
+    gossipsub: gossipsub::Behaviour,
+    negotiate: request_response::json::Behaviour<SignedPayload, Response>,
+    mdns: mdns::tokio::Behaviour,
+}
+
+#[derive(Clone, Debug)]
+pub struct NetworkConfig {
+    pub listen: Vec<Multiaddr>,
+    pub dial: Vec<Multiaddr>,
+    /// How often to re-announce profiles and look for trades.
+    pub tick: Duration,
+}
+
+impl Default for NetworkConfig {
+    fn default() -> Self {
+        NetworkConfig {
+            listen: vec!["/ip4/0.0.0.0/tcp/0".parse().expect("valid multiaddr")],
+            dial: Vec::new(),
+            tick: Duration::from_secs(2),
+        }
+    }
+}
+
+/// A swarm with an identity of its own (the node's payload key is separate).
+pub fn build_swarm() -> Result<Swarm<Behaviour>, Box<dyn std::error::Error + Send + Sync>> {
+    let swarm = libp2p::SwarmBuilder::with_new_identity()
+        .with_tokio()
+        .with_tcp(tcp::Config::default(), noise::Config::new, yamux::Config::default)?
+        .with_behaviour(|key| {
+            let gossipsub = gossipsub::Behaviour::new(
+                gossipsub::MessageAuthenticity::Signed(key.clone()),
+                gossipsub::Config::default(),
+            )?;
+            let negotiate = request_response::json::Behaviour::new(
+                [(StreamProtocol::new(NEGOTIATE_PROTOCOL), ProtocolSupport::Full)],
+                request_response::Config::default(),
+            );
+            let mdns = mdns::tokio::Behaviour::new(mdns::Config::default(), key.public().to_peer_id())?;
+            Ok(Behaviour { gossipsub, negotiate, mdns })
+        })?
+        .with_swarm_config(|c| c.with_idle_connection_timeout(Duration::from_secs(60)))
+        .build();
+    Ok(swarm)
+}
+
+/// Run `node` on the network until the process stops: announce and propose every tick,
+/// record announcements, answer and settle negotiations as they arrive.
+pub async fn run(mut node: Node, cfg: NetworkConfig) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let mut swarm = build_swarm()?;
+    let topic = gossipsub::IdentTopic::new(ANNOUNCE_TOPIC);
+    swarm.behaviour_mut().gossipsub.subscribe(&topic)?;
+    for addr in &cfg.listen {
+        swarm.listen_on(addr.clone())?;
+    }
+    for addr in &cfg.dial {
+        swarm.dial(addr.clone())?;
+    }
+
+    // which libp2p peer carries each payload node's agents, learned from gossip
+    let mut routes: BTreeMap<[u8; 32], PeerId> = BTreeMap::new();
+    let mut settled = 0;
+    let mut tick = tokio::time::interval(cfg.tick);
+    loop {
+        tokio::select! {
+            _ = tick.tick() => {
+                for announcement in node.announce()? {
+                    // no peers subscribed yet is not an error worth stopping for
+                    let _ = swarm.behaviour_mut().gossipsub.publish(topic.clone(), announcement.to_bytes());
+                }
+                for (to, proposal) in node.propose()? {
+                    match routes.get(&to.node) {
+                        Some(peer) => {
+                            swarm.behaviour_mut().negotiate.send_request(peer, proposal);
+                        }
+                        None => {
+                            node.abandon(to.node);
+                        }
+                    }
+                }
+            }
+            event = swarm.select_next_some() => match event {
+                SwarmEvent::NewListenAddr { address, .. } => eprintln!("listening on {address}"),
+                SwarmEvent::Behaviour(BehaviourEvent::Mdns(mdns::Event::Discovered(peers))) => {
+                    for (peer, _) in peers {
+                        swarm.behaviour_mut().gossipsub.add_explicit_peer(&peer);
+                    }
+                }
+                SwarmEvent::Behaviour(BehaviourEvent::Gossipsub(gossipsub::Event::Message { message, .. })) => {
+                    let Ok(payload) = SignedPayload::from_bytes(&message.data) else { continue };
+                    if let (Ok(true), Some(source)) = (node.receive_announcement(&payload), message.source) {
+                        routes.insert(payload.signer.node, source);
+                    }
+                }
+                SwarmEvent::Behaviour(BehaviourEvent::Negotiate(request_response::Event::Message { message, .. })) => {
+                    match message {
+                        request_response::Message::Request { request, channel, .. } => {
+                            let reply = node.receive(&request).unwrap_or_else(|e| {
+                                eprintln!("dropped negotiation message: {e}");
+                                None
+                            });
+                            let _ = swarm.behaviour_mut().negotiate.send_response(channel, Response(reply));
+                        }
+                        request_response::Message::Response { response: Response(Some(reply)), .. } => {
+                            match node.receive(&reply) {
+                                Ok(Some(next)) => match routes.get(&reply.signer.node) {
+                                    Some(peer) => {
+                                        swarm.behaviour_mut().negotiate.send_request(peer, next);
+                                    }
+                                    None => {
+                                        node.abandon(reply.signer.node);
+                                    }
+                                },
+                                Ok(None) => {}
+                                Err(e) => eprintln!("dropped negotiation reply: {e}"),
+                            }
+                        }
+                        request_response::Message::Response { response: Response(None), .. } => {}
+                    }
+                }
+                SwarmEvent::Behaviour(BehaviourEvent::Negotiate(request_response::Event::OutboundFailure {
+                    peer, ..
+                })) => {
+                    for (remote, _) in routes.iter().filter(|(_, p)| **p == peer) {
+                        node.abandon(*remote);
+                    }
+                }
+                _ => {}
+            },
+        }
+        for s in &node.settlements()[settled..] {
+            eprintln!("settled {} with {}: {:+.6} of good {}, {:+.6} of good {}",
+                s.local, s.remote, s.offer.delta_a, s.offer.good_a, s.offer.delta_b, s.offer.good_b);
+        }
+        settled = node.settlements().len();
+    }
+}
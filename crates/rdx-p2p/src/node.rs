@@ -0,0 +1,307 @@
+//! A process's share of the exchange: the agents it hosts, what it has heard about everyone
+//! else's, and the negotiations in flight. The node does no I/O; a transport (see `network`)
+//! feeds it payloads and sends what it returns.
+
+use std::collections::{BTreeMap, BTreeSet};
+use ed25519_dalek::SigningKey;
+use rand::prelude::*;
+use rdx_core::codec::{privatize_beta, CodecFormat};
+use rdx_core::pareto_oracle::CobbDouglasWalrasOracle;
+use rdx_core::protocol::{
+    Message, NegotiationId, NegotiationState, Offer, Party, Profile, Proposer, RejectReason, Responder,
+};
+use rdx_core::trade::evaluate_exchange;
+use crate::error::P2pError;
+use crate::payload::{AgentAddress, Announcement, SignedPayload, Wire};
+
+/// The oracle every node negotiates and settles with, so all sides compute the same exchange.
+pub static ORACLE: CobbDouglasWalrasOracle = CobbDouglasWalrasOracle;
+
+#[derive(Clone, Debug)]
+pub struct NodeConfig {
+    pub min_qty: f64,
+    pub oracle_iters: usize,
+    /// Answer poor proposals with the oracle's exchange instead of rejecting them.
+    pub counter_offers: bool,
+    /// Publish `privatize_beta(beta, epsilon)` instead of the true exponents.
+    pub privacy_epsilon: Option<f64>,
+    /// Encoding of signed payloads.
+    pub format: CodecFormat,
+}
+
+impl Default for NodeConfig {
+    fn default() -> Self {
+        NodeConfig {
+            min_qty: 1e-9,
+            oracle_iters: 60,
+            counter_offers: true,
+            privacy_epsilon: None,
+            format: CodecFormat::Bincode,
+        }
+    }
+}
+
+/// A committed trade, as applied to a hosted agent's holdings.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Settlement {
+    pub local: AgentAddress,
+    pub remote: AgentAddress,
+    pub negotiation: NegotiationId,
+    /// The exchange from the local agent's side: what it received, negative for what it gave.
+    pub offer: Offer,
+    /// The local agent proposed the trade.
+    pub proposed: bool,
+}
+
+struct Proposal {
+    local: u32,
+    remote: AgentAddress,
+    machine: Proposer,
+}
+
+struct Reply {
+    local: u32,
+    machine: Responder<'static>,
+}
+
+pub struct Node {
+    key: SigningKey,
+    cfg: NodeConfig,
+    agents: Vec<Party>,
+    seq: u64,
+    directory: BTreeMap<AgentAddress, (u64, Profile)>,
+    /// Remote profile version each local agent last proposed to.
+    tried: BTreeMap<(u32, AgentAddress), u64>,
+    busy: BTreeSet<u32>,
+    next_id: NegotiationId,
+    proposals: BTreeMap<NegotiationId, Proposal>,
+    replies: BTreeMap<(AgentAddress, NegotiationId), Reply>,
+    settlements: Vec<Settlement>,
+    rng: StdRng,
+}
+
+impl Node {
+    /// Host `agents` under `key`; `seed` drives privatization noise.
+    pub fn new(key: SigningKey, agents: Vec<Party>, cfg: NodeConfig, seed: u64) -> Node {
+        Node {
+            key,
+            cfg,
+            agents,
+            seq: 0,
+            directory: BTreeMap::new(),
+            tried: BTreeMap::new(),
+            busy: BTreeSet::new(),
+            next_id: 0,
+            proposals: BTreeMap::new(),
+            replies: BTreeMap::new(),
+            settlements: Vec::new(),
+            rng: StdRng::seed_from_u64(seed ^ 0x9E37_79B9_7F4A),
+        }
+    }
+
+    /// This node's public key, the `node` part of its agents' addresses.
+    pub fn public_key(&self) -> [u8; 32] {
+        self.key.verifying_key().to_bytes()
+    }
+
+    pub fn address(&self, agent: u32) -> AgentAddress {
+        AgentAddress { node: self.public_key(), agent }
+    }
+
+    pub fn agents(&self) -> &[Party] { &self.agents }
+
+    /// Profiles heard from other nodes, latest announcement per agent.
+    pub fn directory(&self) -> impl Iterator<Item = (&AgentAddress, &Profile)> {
+        self.directory.iter().map(|(a, (_, p))| (a, p))
+    }
+
+    /// Trades committed so far, in order.
+    pub fn settlements(&self) -> &[Settlement] { &self.settlements }
+
+    /// Negotiations not yet committed or rejected.
+    pub fn open_negotiations(&self) -> usize {
+        self.proposals.len() + self.replies.len()
+    }
+
+    /// Signed announcements of every hosted agent's current profile, to gossip.
+    pub fn announce(&mut self) -> Result<Vec<SignedPayload>, P2pError> {
+        self.seq += 1;
+        let mut out = Vec::with_capacity(self.agents.len());
+        for k in 0..self.agents.len() as u32 {
+            let profile = self.disclosed_profile(k)?;
+            let announcement = Announcement { from: self.address(k), seq: self.seq, profile };
+            out.push(SignedPayload::sign(&self.key, self.cfg.format, &announcement)?);
+        }
+        Ok(out)
+    }
+
+    /// Record a gossiped announcement. Own, stale and replayed announcements are ignored;
+    /// returns whether the directory changed.
+    pub fn receive_announcement(&mut self, payload: &SignedPayload) -> Result<bool, P2pError> {
+        let a: Announcement = payload.verify()?;
+        let stale = self.directory.get(&a.from).is_some_and(|(seq, _)| *seq >= a.seq);
+        if stale || a.from.node == self.public_key() {
+            return Ok(false);
+        }
+        self.directory.insert(a.from, (a.seq, a.profile));
+        Ok(true)
+    }
+
+    /// For each idle hosted agent, propose its best exchange with a known agent whose profile
+    /// changed since it last tried: the good pair and counterpart with the largest own gain under
+    /// `trade::evaluate_exchange`. Returns each proposal with the agent it is for.
+    pub fn propose(&mut self) -> Result<Vec<(AgentAddress, SignedPayload)>, P2pError> {
+        let mut out = Vec::new();
+        for k in 0..self.agents.len() as u32 {
+            if self.busy.contains(&k) {
+                continue;
+            }
+            let Some((remote, seq, offer)) = self.best_offer(k) else { continue };
+            self.tried.insert((k, remote), seq);
+            let id = self.next_id;
+            self.next_id += 1;
+            let party = self.agents[k as usize].clone();
+            let profile = self.disclosed_profile(k)?;
+            let mut machine = Proposer::new(id, party, profile);
+            let message = machine.propose(offer)?;
+            self.busy.insert(k);
+            self.proposals.insert(id, Proposal { local: k, remote, machine });
+            out.push((remote, self.wire(k, remote, message)?));
+        }
+        Ok(out)
+    }
+
+    fn best_offer(&self, k: u32) -> Option<(AgentAddress, u64, Offer)> {
+        let me = &self.agents[k as usize];
+        let goods = me.holdings.len();
+        let mut best: Option<(f64, AgentAddress, u64, Offer)> = None;
+        for (&remote, (seq, profile)) in &self.directory {
+            let tried = self.tried.get(&(k, remote)).is_some_and(|tried| tried >= seq);
+            if tried || profile.holdings.len() != goods {
+                continue;
+            }
+            for a in 0..goods {
+                for b in a + 1..goods {
+                    let Some(c) = evaluate_exchange(
+                        &me.beta,
+                        &me.holdings,
+                        &profile.beta,
+                        &profile.holdings,
+                        a,
+                        b,
+                        me.min_qty,
+                        self.cfg.oracle_iters,
+                        &ORACLE,
+                    ) else {
+                        continue;
+                    };
+                    if best.as_ref().is_some_and(|(gain, ..)| c.delta_u_i <= *gain) {
+                        continue;
+                    }
+                    best = Some((c.delta_u_i, remote, *seq, Offer::from(&c)));
+                }
+            }
+        }
+        best.map(|(_, remote, seq, offer)| (remote, seq, offer))
+    }
+
+    /// What agent `k` tells others: its holdings and, when privacy is on, freshly privatized
+    /// exponents.
+    fn disclosed_profile(&mut self, k: u32) -> Result<Profile, P2pError> {
+        let party = &self.agents[k as usize];
+        let beta = match self.cfg.privacy_epsilon {
+            Some(epsilon) => privatize_beta(&party.beta, epsilon, &mut self.rng)?,
+            None => party.beta.clone(),
+        };
+        Ok(Profile { beta, holdings: party.holdings.clone() })
+    }
+
+    /// Handle a negotiation payload addressed to one of this node's agents, settling the trade
+    /// if it commits, and return the signed reply to send back, if any.
+    pub fn receive(&mut self, payload: &SignedPayload) -> Result<Option<SignedPayload>, P2pError> {
+        let wire: Wire = payload.verify()?;
+        if wire.to.node != self.public_key() || wire.to.agent as usize >= self.agents.len() {
+            return Err(P2pError::UnknownAgent(wire.to));
+        }
+        let local = wire.to.agent;
+        let id = wire.message.id();
+        let reply = if let Message::ProposeTrade { .. } = wire.message {
+            self.answer(local, wire.from, &wire.message)?
+        } else if let Some(p) = self.proposals.get_mut(&id).filter(|p| p.remote == wire.from && p.local == local) {
+            let reply = p.machine.handle(&wire.message)?;
+            let state = p.machine.state().clone();
+            self.finish(id, wire.from, true, &state);
+            reply
+        } else if let Some(r) = self.replies.get_mut(&(wire.from, id)).filter(|r| r.local == local) {
+            let reply = r.machine.handle(&wire.message)?;
+            let state = r.machine.state().clone();
+            self.finish(id, wire.from, false, &state);
+            reply
+        } else {
+            return Err(P2pError::UnknownNegotiation { peer: wire.from, id });
+        };
+        reply.map(|m| self.wire(local, wire.from, m)).transpose()
+    }
+
+    fn answer(&mut self, local: u32, from: AgentAddress, message: &Message) -> Result<Option<Message>, P2pError> {
+        let id = message.id();
+        if self.busy.contains(&local) {
+            return Ok(Some(Message::Reject { id, reason: RejectReason::Busy }));
+        }
+        let party = self.agents[local as usize].clone();
+        let mut machine = Responder::new(id, party, self.cfg.counter_offers, &ORACLE, self.cfg.oracle_iters);
+        let reply = machine.handle(message)?;
+        if !machine.state().is_finished() {
+            self.busy.insert(local);
+            self.replies.insert((from, id), Reply { local, machine });
+        }
+        Ok(reply)
+    }
+
+    /// Drop a finished negotiation, applying its trade if it committed.
+    fn finish(&mut self, id: NegotiationId, remote: AgentAddress, proposed: bool, state: &NegotiationState) {
+        if !state.is_finished() {
+            return;
+        }
+        let local = if proposed {
+            self.proposals.remove(&id).map(|p| p.local)
+        } else {
+            self.replies.remove(&(remote, id)).map(|r| r.local)
+        };
+        let Some(local) = local else { return };
+        self.busy.remove(&local);
+        if let NegotiationState::Committed(offer) = state {
+            let sign = if proposed { 1.0 } else { -1.0 };
+            let holdings = &mut self.agents[local as usize].holdings;
+            holdings[offer.good_a.index()] += sign * offer.delta_a;
+            holdings[offer.good_b.index()] += sign * offer.delta_b;
+            self.tried.retain(|(k, _), _| *k != local);
+            let (local, offer) = (self.address(local), offer.scaled(sign));
+            self.settlements.push(Settlement { local, remote, negotiation: id, offer, proposed });
+        }
+    }
+
+    /// Give up every open negotiation with agents of `remote_node` (unreachable, or a
+    /// message was lost), freeing the local agents involved. Returns how many were dropped.
+    pub fn abandon(&mut self, remote_node: [u8; 32]) -> usize {
+        let before = self.open_negotiations();
+        let mut freed = Vec::new();
+        self.proposals.retain(|_, p| p.remote.node != remote_node || {
+            freed.push(p.local);
+            false
+        });
+        self.replies.retain(|(remote, _), r| remote.node != remote_node || {
+            freed.push(r.local);
+            false
+        });
+        for k in freed {
+            self.busy.remove(&k);
+        }
+        before - self.open_negotiations()
+    }
+
+    fn wire(&self, local: u32, to: AgentAddress, message: Message) -> Result<SignedPayload, P2pError> {
+        let wire = Wire { from: self.address(local), to, message };
+        SignedPayload::sign(&self.key, self.cfg.format, &wire)
+    }
+}
@@ -0,0 +1,97 @@
+//! What peers send each other: profile announcements for gossip and addressed negotiation
+//! messages, both wrapped in a `codec::Envelope` and signed by the sending node's key.
+
+use std::fmt;
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use serde::{Serialize, Deserialize, de::DeserializeOwned};
+use rdx_core::codec::{decode_tagged, encode_as, CodecFormat, Envelope};
+use rdx_core::protocol::{Message, Profile};
+use crate::error::P2pError;
+
+/// An agent on the network: the public key of the node hosting it and its index there.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct AgentAddress {
+    pub node: [u8; 32],
+    pub agent: u32,
+}
+
+impl fmt::Display for AgentAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for b in &self.node[..4] {
+            write!(f, "{b:02x}")?;
+        }
+        write!(f, "/{}", self.agent)
+    }
+}
+
+/// A hosted agent's published exponents and holdings. `seq` grows with every announcement of
+/// the agent, so receivers keep the latest and ignore replays.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Announcement {
+    pub from: AgentAddress,
+    pub seq: u64,
+    pub profile: Profile,
+}
+
+/// A negotiation message between two agents.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Wire {
+    pub from: AgentAddress,
+    pub to: AgentAddress,
+    pub message: Message,
+}
+
+/// Payloads whose signer is named inside them.
+pub trait Signed {
+    fn signer(&self) -> AgentAddress;
+}
+
+impl Signed for Announcement {
+    fn signer(&self) -> AgentAddress { self.from }
+}
+
+impl Signed for Wire {
+    fn signer(&self) -> AgentAddress { self.from }
+}
+
+/// Envelope bytes and an Ed25519 signature over them by `signer`'s node, who must also be
+/// the signer the payload names.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SignedPayload {
+    pub signer: AgentAddress,
+    pub body: Vec<u8>,
+    pub signature: Vec<u8>,
+}
+
+impl SignedPayload {
+    pub fn sign<T: Signed + Serialize>(
+        key: &SigningKey,
+        format: CodecFormat,
+        payload: &T,
+    ) -> Result<SignedPayload, P2pError> {
+        let body = Envelope::seal(format, payload)?.to_bytes();
+        let signature = key.sign(&body).to_bytes().to_vec();
+        Ok(SignedPayload { signer: payload.signer(), body, signature })
+    }
+
+    /// Check the signature, then decode the payload and check that it names the signer.
+    pub fn verify<T: Signed + DeserializeOwned>(&self) -> Result<T, P2pError> {
+        let bad = || P2pError::BadSignature(self.signer);
+        let key = VerifyingKey::from_bytes(&self.signer.node).map_err(|_| bad())?;
+        let signature = Signature::from_slice(&self.signature).map_err(|_| bad())?;
+        key.verify(&self.body, &signature).map_err(|_| bad())?;
+        let payload: T = Envelope::from_bytes(&self.body)?.open()?;
+        if payload.signer() != self.signer {
+            return Err(bad());
+        }
+        Ok(payload)
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        encode_as(CodecFormat::Bincode, self).expect("signed payloads encode with bincode")
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<SignedPayload, P2pError> {
+        Ok(decode_tagged(bytes)?)
+    }
+}
@@ -0,0 +1,112 @@
+use ed25519_dalek::SigningKey;
+use rand::prelude::*;
+use rdx_core::preferences::cd_utility;
+use rdx_core::protocol::Party;
+use rdx_p2p::{Node, NodeConfig, P2pError, SignedPayload};
+
+fn party(beta: [f64; 3], holdings: [f64; 3]) -> Party {
+    Party { beta: beta.to_vec(), holdings: holdings.to_vec(), min_qty: 1e-9 }
+}
+
+fn node(seed: u64, agents: Vec<Party>) -> Node {
+    let key = SigningKey::generate(&mut StdRng::seed_from_u64(seed));
+    Node::new(key, agents, NodeConfig::default(), seed)
+}
+
+fn gossip(from: &mut Node, to: &mut Node) {
+    for payload in from.announce().unwrap() {
+        to.receive_announcement(&payload).unwrap();
+    }
+}
+
+/// Deliver `payload` and every reply it triggers, back and forth, as a transport would.
+fn deliver<'a>(mut payload: SignedPayload, mut to: &'a mut Node, mut from: &'a mut Node) {
+    while let Some(reply) = to.receive(&payload).unwrap() {
+        payload = reply;
+        std::mem::swap(&mut to, &mut from);
+    }
+}
+
+fn totals(nodes: &[&Node]) -> Vec<f64> {
+    let mut sum = vec![0.0; 3];
+    for party in nodes.iter().flat_map(|n| n.agents()) {
+        sum.iter_mut().zip(&party.holdings).for_each(|(s, x)| *s += x);
+    }
+    sum
+}
+
+fn welfare(node: &Node) -> Vec<f64> {
+    node.agents().iter().map(|p| cd_utility(&p.beta, &p.holdings, p.min_qty)).collect()
+}
+
+#[test]
+fn nodes_trade_until_no_gains_are_left() {
+    let mut left = node(1, vec![party([0.7, 0.2, 0.1], [1.0, 4.0, 2.0]), party([0.2, 0.2, 0.6], [3.0, 1.0, 0.5])]);
+    let mut right = node(2, vec![party([0.1, 0.8, 0.1], [4.0, 0.5, 2.0])]);
+    let before = totals(&[&left, &right]);
+    let (u_left, u_right) = (welfare(&left), welfare(&right));
+
+    for _ in 0..6 {
+        gossip(&mut left, &mut right);
+        gossip(&mut right, &mut left);
+        for (to, payload) in left.propose().unwrap() {
+            assert_eq!(to.node, right.public_key());
+            deliver(payload, &mut right, &mut left);
+        }
+        for (_, payload) in right.propose().unwrap() {
+            deliver(payload, &mut left, &mut right);
+        }
+    }
+    assert!(!left.settlements().is_empty() && !right.settlements().is_empty());
+    assert_eq!(left.open_negotiations() + right.open_negotiations(), 0);
+    assert_eq!(left.settlements().len(), right.settlements().len());
+    let after = totals(&[&left, &right]);
+    assert!(before.iter().zip(&after).all(|(a, b)| (a - b).abs() < 1e-9), "goods are conserved");
+    for (old, new) in u_left.iter().chain(&u_right).zip(welfare(&left).iter().chain(&welfare(&right))) {
+        assert!(new >= old, "no agent loses: {old} -> {new}");
+    }
+    for s in left.settlements() {
+        let mirror = right.settlements().iter()
+            .find(|r| r.negotiation == s.negotiation && r.local == s.remote && r.proposed != s.proposed)
+            .unwrap();
+        assert!(s.offer.matches(&mirror.offer.scaled(-1.0)));
+    }
+}
+
+#[test]
+fn forged_and_stale_payloads_are_refused() {
+    let mut a = node(3, vec![party([0.5, 0.3, 0.2], [1.0, 1.0, 1.0])]);
+    let mut b = node(4, vec![party([0.2, 0.3, 0.5], [1.0, 1.0, 1.0])]);
+    let first = a.announce().unwrap().remove(0);
+    let second = a.announce().unwrap().remove(0);
+    assert!(b.receive_announcement(&second).unwrap());
+    assert!(!b.receive_announcement(&first).unwrap(), "an older announcement is ignored");
+    assert!(!b.receive_announcement(&second).unwrap(), "so is a replay");
+    assert_eq!(b.directory().count(), 1);
+
+    let mut forged = second.clone();
+    forged.signer.node = b.public_key();
+    assert!(matches!(b.receive_announcement(&forged), Err(P2pError::BadSignature(_))));
+    let mut tampered = second;
+    let last = tampered.body.len() - 1;
+    tampered.body[last] ^= 1;
+    assert!(matches!(b.receive_announcement(&tampered), Err(P2pError::BadSignature(_))));
+    let bytes = SignedPayload::from_bytes(&tampered.to_bytes()).unwrap();
+    assert_eq!(bytes, tampered);
+}
+
+#[test]
+fn abandoned_negotiations_free_the_agents() {
+    let mut a = node(5, vec![party([0.8, 0.1, 0.1], [1.0, 4.0, 4.0])]);
+    let mut b = node(6, vec![party([0.1, 0.8, 0.1], [4.0, 1.0, 4.0])]);
+    gossip(&mut b, &mut a);
+    let proposals = a.propose().unwrap();
+    assert_eq!(proposals.len(), 1);
+    assert!(a.propose().unwrap().is_empty(), "the agent is busy with its proposal");
+    assert_eq!(a.abandon(b.public_key()), 1);
+    assert_eq!(a.open_negotiations(), 0);
+
+    // the reply to an abandoned proposal is not for any negotiation
+    let reply = b.receive(&proposals[0].1).unwrap().unwrap();
+    assert!(matches!(a.receive(&reply), Err(P2pError::UnknownNegotiation { .. })));
+}
@@ -0,0 +1,281 @@
+//! HTTP service around the oracle and the simulator, for frontends and experiment orchestrators
+//! that do not link Rust.
+//!
+//! - `GET /health`: `ok`
+//! - `GET /v1/schema`: JSON Schema of the config format
+//! - `POST /v1/evaluate`: `trade::evaluate_exchange` on two sides' exponents and holdings; the
+//!   trade, or `null` when no exchange improves both
+//! - `POST /v1/runs`: run the posted `SimConfig` to the end; per-round metrics and a summary
+//! - `POST /v1/runs/stream`: the same as server-sent events, one `round` event per round as it
+//!   finishes, then `done` (or `error`)
+//!
+//! Bodies are JSON with the Rust field names. Invalid configs are answered with
+//! `422 Unprocessable Entity` and the list of `ConfigProblem`s; runs and evaluations beyond
+//! `Limits` with `413 Payload Too Large`.
+
+use std::convert::Infallible;
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde::{Serialize, Deserialize};
+use serde_json::json;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tower_http::cors::CorsLayer;
+use rdx_core::model::{ConfigProblem, SimConfig};
+use rdx_core::pareto_oracle::CobbDouglasWalrasOracle;
+use rdx_core::sim::{mean_endowments, Engine, RoundMetrics, SimState};
+use rdx_core::trade::{evaluate_exchange, TradeCandidate};
+
+/// Largest run or evaluation the server accepts, so one request cannot occupy it indefinitely.
+#[derive(Clone, Copy, Debug)]
+pub struct Limits {
+    /// Largest population a run may reach, counting the most entrants `demography` can add.
+    pub max_agents: usize,
+    pub max_rounds: usize,
+    /// Most P2P encounters a run may schedule over all its rounds, under every regime of `schedule`.
+    pub max_encounters: usize,
+    /// Most goods a run (counting `new_goods`) or evaluation may have.
+    pub max_goods: usize,
+    /// Most oracle iterations an evaluation (`oracle_iters`) or run (`oracle_bisect_iters`) may ask for.
+    pub max_oracle_iters: usize,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Limits {
+            max_agents: 10_000,
+            max_rounds: 10_000,
+            max_encounters: 10_000_000,
+            max_goods: 100,
+            max_oracle_iters: 1_000,
+        }
+    }
+}
+
+/// The service's routes, CORS open to any origin.
+pub fn app(limits: Limits) -> Router {
+    Router::new()
+        .route("/health", get(|| async { "ok" }))
+        .route("/v1/schema", get(|| async { Json(SimConfig::json_schema()) }))
+        .route("/v1/evaluate", post(evaluate))
+        .route("/v1/runs", post(run))
+        .route("/v1/runs/stream", post(stream))
+        .layer(CorsLayer::permissive())
+        .with_state(limits)
+}
+
+/// Why a request was refused, with the status it is answered with.
+#[derive(Debug)]
+pub enum ApiError {
+    InvalidConfig(Vec<ConfigProblem>),
+    TooLarge(String),
+    Failed(String),
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let (status, body) = match self {
+            ApiError::InvalidConfig(problems) => {
+                (StatusCode::UNPROCESSABLE_ENTITY, json!({ "error": "invalid config", "problems": problems }))
+            }
+            ApiError::TooLarge(message) => (StatusCode::PAYLOAD_TOO_LARGE, json!({ "error": message })),
+            ApiError::Failed(message) => (StatusCode::UNPROCESSABLE_ENTITY, json!({ "error": message })),
+        };
+        (status, Json(body)).into_response()
+    }
+}
+
+/// Body of `/v1/evaluate`: both sides' exponents and holdings and the good pair to trade.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EvaluateRequest {
+    pub beta_i: Vec<f64>,
+    pub e_i: Vec<f64>,
+    pub beta_j: Vec<f64>,
+    pub e_j: Vec<f64>,
+    pub good_a: usize,
+    pub good_b: usize,
+    #[serde(default = "default_min_qty")]
+    pub min_qty: f64,
+    #[serde(default = "default_oracle_iters")]
+    pub oracle_iters: usize,
+}
+
+/// A mutually beneficial trade: the good pair, its price, i's change in holdings (j's is the
+/// opposite) and both utility gains.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Trade {
+    pub good_a: usize,
+    pub good_b: usize,
+    pub q_ab: f64,
+    pub delta_a_i: f64,
+    pub delta_b_i: f64,
+    pub delta_u_i: f64,
+    pub delta_u_j: f64,
+}
+
+impl From<TradeCandidate> for Trade {
+    fn from(c: TradeCandidate) -> Trade {
+        Trade {
+            good_a: c.good_a.index(),
+            good_b: c.good_b.index(),
+            q_ab: c.q_ab,
+            delta_a_i: c.delta_a_i,
+            delta_b_i: c.delta_b_i,
+            delta_u_i: c.delta_u_i,
+            delta_u_j: c.delta_u_j,
+        }
+    }
+}
+
+async fn evaluate(
+    State(limits): State<Limits>,
+    Json(r): Json<EvaluateRequest>,
+) -> Result<Json<Option<Trade>>, ApiError> {
+    check_iters(&limits, r.oracle_iters)?;
+    check_goods(&limits, r.beta_i.len().max(r.e_i.len()).max(r.beta_j.len()).max(r.e_j.len()))?;
+    // the oracle is CPU-bound; keep it off the async workers
+    let c = tokio::task::spawn_blocking(move || {
+        evaluate_exchange(
+            &r.beta_i,
+            &r.e_i,
+            &r.beta_j,
+            &r.e_j,
+            r.good_a,
+            r.good_b,
+            r.min_qty,
+            r.oracle_iters,
+            &CobbDouglasWalrasOracle,
+        )
+    })
+    .await
+    .map_err(|e| ApiError::Failed(e.to_string()))?;
+    Ok(Json(c.map(Trade::from)))
+}
+
+/// End-of-run summary, as returned by `/v1/runs` and the final `done` event.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RunSummary {
+    pub rounds_run: usize,
+    pub stopped_at: Option<usize>,
+    pub trades: usize,
+    pub mean_endowments: Vec<f64>,
+}
+
+impl RunSummary {
+    fn of(state: &SimState) -> RunSummary {
+        RunSummary {
+            rounds_run: state.metrics.len(),
+            stopped_at: state.stopped_at,
+            trades: state.events.len(),
+            mean_endowments: mean_endowments(state),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RunResponse {
+    pub summary: RunSummary,
+    pub metrics: Vec<RoundMetrics>,
+}
+
+fn check(limits: &Limits, cfg: &SimConfig) -> Result<(), ApiError> {
+    let problems = cfg.validate();
+    if !problems.is_empty() {
+        return Err(ApiError::InvalidConfig(problems));
+    }
+    if peak_population(cfg) > limits.max_agents || cfg.rounds > limits.max_rounds {
+        return Err(ApiError::TooLarge(format!(
+            "runs are limited to {} agents and {} rounds",
+            limits.max_agents, limits.max_rounds
+        )));
+    }
+    if total_encounters(cfg) > limits.max_encounters {
+        return Err(ApiError::TooLarge(format!("runs are limited to {} encounters", limits.max_encounters)));
+    }
+    check_goods(limits, cfg.all_goods().len())?;
+    check_iters(limits, cfg.oracle_bisect_iters)
+}
+
+/// The initial population plus the most entrants `demography` can add over the run.
+fn peak_population(cfg: &SimConfig) -> usize {
+    let per_round = cfg.demography.as_ref().map_or(0, |d| d.entry_rate.max(0.0).ceil() as usize);
+    cfg.num_agents.saturating_add(per_round.saturating_mul(cfg.rounds))
+}
+
+/// P2P encounters over the whole run, each stretch of rounds under the regime `schedule` puts
+/// in force for it.
+fn total_encounters(cfg: &SimConfig) -> usize {
+    let mut regime = cfg.clone();
+    let mut changes: Vec<_> = cfg.schedule.iter().filter(|c| c.round < cfg.rounds).collect();
+    changes.sort_by_key(|c| c.round);
+    let (mut total, mut from) = (0usize, 0);
+    for c in changes {
+        total = total.saturating_add((c.round - from).saturating_mul(regime.p2p_encounters_per_round));
+        c.change.apply(&mut regime);
+        from = c.round;
+    }
+    total.saturating_add((cfg.rounds - from).saturating_mul(regime.p2p_encounters_per_round))
+}
+
+fn check_goods(limits: &Limits, goods: usize) -> Result<(), ApiError> {
+    if goods > limits.max_goods {
+        return Err(ApiError::TooLarge(format!("runs and evaluations are limited to {} goods", limits.max_goods)));
+    }
+    Ok(())
+}
+
+fn check_iters(limits: &Limits, iters: usize) -> Result<(), ApiError> {
+    if iters > limits.max_oracle_iters {
+        return Err(ApiError::TooLarge(format!("the oracle is limited to {} iterations", limits.max_oracle_iters)));
+    }
+    Ok(())
+}
+
+async fn run(State(limits): State<Limits>, Json(cfg): Json<SimConfig>) -> Result<Json<RunResponse>, ApiError> {
+    check(&limits, &cfg)?;
+    let state = tokio::task::spawn_blocking(move || -> Result<SimState, String> {
+        let mut engine = Engine::new(cfg).map_err(|e| e.to_string())?;
+        engine.run_to_end();
+        Ok(engine.finish())
+    })
+    .await
+    .map_err(|e| ApiError::Failed(e.to_string()))?
+    .map_err(ApiError::Failed)?;
+    Ok(Json(RunResponse { summary: RunSummary::of(&state), metrics: state.metrics }))
+}
+
+async fn stream(
+    State(limits): State<Limits>,
+    Json(cfg): Json<SimConfig>,
+) -> Result<Sse<ReceiverStream<Result<Event, Infallible>>>, ApiError> {
+    check(&limits, &cfg)?;
+    let (tx, rx) = mpsc::channel(16);
+    // the engine stays on the blocking thread; a closed stream (client gone) stops the run
+    tokio::task::spawn_blocking(move || {
+        let mut engine = match Engine::new(cfg) {
+            Ok(engine) => engine,
+            Err(e) => {
+                let _ = tx.blocking_send(Ok(Event::default().event("error").data(e.to_string())));
+                return;
+            }
+        };
+        while let Some(m) = engine.step_round() {
+            let event = Event::default().event("round").json_data(m).expect("round metrics serialize to JSON");
+            if tx.blocking_send(Ok(event)).is_err() {
+                return;
+            }
+        }
+        let summary = RunSummary::of(&engine.finish());
+        let event = Event::default().event("done").json_data(summary).expect("summaries serialize to JSON");
+        let _ = tx.blocking_send(Ok(event));
+    });
+    Ok(Sse::new(ReceiverStream::new(rx)).keep_alive(KeepAlive::default()))
+}
+
+fn default_min_qty() -> f64 { 1e-9 }
+
+fn default_oracle_iters() -> usize { 60 }
@@ -0,0 +1,47 @@
+use clap::Parser;
+use rdx_server::{app, Limits};
+
+/// Serve the oracle and the simulator over HTTP
+#[derive(Parser, Debug)]
+#[command(name="rdx-server")]
+struct Args {
+    /// Address to listen on
+    #[arg(long, default_value="127.0.0.1:8080")]
+    addr: String,
+
+    /// Largest population a posted config may reach, entrants included
+    #[arg(long, default_value_t=10_000)]
+    max_agents: usize,
+
+    /// Most rounds a posted config may run
+    #[arg(long, default_value_t=10_000)]
+    max_rounds: usize,
+
+    /// Most P2P encounters a posted config may schedule over all its rounds
+    #[arg(long, default_value_t=10_000_000)]
+    max_encounters: usize,
+
+    /// Most goods a posted config or evaluation may have
+    #[arg(long, default_value_t=100)]
+    max_goods: usize,
+
+    /// Most oracle iterations an evaluation or config may ask for
+    #[arg(long, default_value_t=1_000)]
+    max_oracle_iters: usize,
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let args = Args::parse();
+    let limits = Limits {
+        max_agents: args.max_agents,
+        max_rounds: args.max_rounds,
+        max_encounters: args.max_encounters,
+        max_goods: args.max_goods,
+        max_oracle_iters: args.max_oracle_iters,
+    };
+    let listener = tokio::net::TcpListener::bind(&args.addr).await?;
+    eprintln!("listening on {}", listener.local_addr()?);
+    axum::serve(listener, app(limits)).await?;
+    Ok(())
+}
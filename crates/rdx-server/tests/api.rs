@@ -0,0 +1,177 @@
+use axum::body::Body;
+use axum::http::{Request, StatusCode};
+use http_body_util::BodyExt;
+use serde_json::{json, Value};
+use tower::ServiceExt;
+use rdx_server::{app, Limits};
+
+fn small_config() -> Value {
+    json!({
+        "seed": 7,
+        "num_agents": 24,
+        "rounds": 6,
+        "p2p_encounters_per_round": 40,
+        "base_good": 0,
+        "initial_endowment_scale": 1.0,
+        "alpha_low": 0.1,
+        "alpha_high": 0.9,
+        "trade_step_cap_frac": 0.35,
+        "min_qty": 1e-9,
+        "oracle_bisect_iters": 60,
+        "base_goods_quantity": 5,
+        "base_goods": ["base", "g1", "g2", "g3", "g4"],
+        "reaction_rules": []
+    })
+}
+
+async fn post(limits: Limits, uri: &str, body: &Value) -> (StatusCode, String) {
+    let request = Request::post(uri)
+        .header("content-type", "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap();
+    let response = app(limits).oneshot(request).await.unwrap();
+    let status = response.status();
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    (status, String::from_utf8(bytes.to_vec()).unwrap())
+}
+
+#[tokio::test]
+async fn health_answers_ok() {
+    let request = Request::get("/health").body(Body::empty()).unwrap();
+    let response = app(Limits::default()).oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    assert_eq!(&bytes[..], b"ok");
+}
+
+#[tokio::test]
+async fn evaluate_finds_a_trade_between_opposite_tastes() {
+    let body = json!({
+        "beta_i": [0.8, 0.2], "e_i": [1.0, 1.0],
+        "beta_j": [0.2, 0.8], "e_j": [1.0, 1.0],
+        "good_a": 0, "good_b": 1
+    });
+    let (status, text) = post(Limits::default(), "/v1/evaluate", &body).await;
+    assert_eq!(status, StatusCode::OK);
+    let trade: Value = serde_json::from_str(&text).unwrap();
+    assert!(trade["delta_a_i"].as_f64().unwrap() > 0.0, "{text}");
+    assert!(trade["delta_u_i"].as_f64().unwrap() > 0.0);
+    assert!(trade["delta_u_j"].as_f64().unwrap() > 0.0);
+}
+
+#[tokio::test]
+async fn evaluate_returns_null_for_identical_agents() {
+    let body = json!({
+        "beta_i": [0.5, 0.5], "e_i": [1.0, 1.0],
+        "beta_j": [0.5, 0.5], "e_j": [1.0, 1.0],
+        "good_a": 0, "good_b": 1
+    });
+    let (status, text) = post(Limits::default(), "/v1/evaluate", &body).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(text, "null");
+}
+
+#[tokio::test]
+async fn run_returns_one_metrics_row_per_round() {
+    let (status, text) = post(Limits::default(), "/v1/runs", &small_config()).await;
+    assert_eq!(status, StatusCode::OK, "{text}");
+    let run: Value = serde_json::from_str(&text).unwrap();
+    let rounds = run["summary"]["rounds_run"].as_u64().unwrap() as usize;
+    assert_eq!(run["metrics"].as_array().unwrap().len(), rounds);
+    assert_eq!(run["summary"]["mean_endowments"].as_array().unwrap().len(), 5);
+}
+
+#[tokio::test]
+async fn invalid_configs_list_their_problems() {
+    let mut cfg = small_config();
+    cfg["alpha_low"] = json!(0.95);
+    let (status, text) = post(Limits::default(), "/v1/runs", &cfg).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    let body: Value = serde_json::from_str(&text).unwrap();
+    assert!(!body["problems"].as_array().unwrap().is_empty(), "{text}");
+}
+
+#[tokio::test]
+async fn runs_beyond_the_limits_are_refused() {
+    let limits = Limits { max_agents: 10, max_rounds: 100, ..Limits::default() };
+    let (status, _) = post(limits, "/v1/runs", &small_config()).await;
+    assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+
+    let limits = Limits { max_oracle_iters: 30, ..Limits::default() };
+    let (status, _) = post(limits, "/v1/runs", &small_config()).await;
+    assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+
+    let limits = Limits { max_goods: 4, ..Limits::default() };
+    let (status, _) = post(limits, "/v1/runs", &small_config()).await;
+    assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+    let mut cfg = small_config();
+    cfg["new_goods"] = json!([{ "round": 3, "name": "g5" }]);
+    let limits = Limits { max_goods: 5, ..Limits::default() };
+    let (status, _) = post(limits, "/v1/runs", &cfg).await;
+    assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+}
+
+#[tokio::test]
+async fn every_scheduled_regime_counts_towards_the_encounter_limit() {
+    // 6 rounds of 40 encounters
+    let limits = Limits { max_encounters: 240, ..Limits::default() };
+    let (status, text) = post(limits, "/v1/runs", &small_config()).await;
+    assert_eq!(status, StatusCode::OK, "{text}");
+    let mut cfg = small_config();
+    cfg["p2p_encounters_per_round"] = json!(1_000_000_000_000u64);
+    let (status, _) = post(Limits::default(), "/v1/runs", &cfg).await;
+    assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+
+    // the schedule raises the rate for the last two rounds
+    let mut cfg = small_config();
+    cfg["schedule"] = json!([{ "round": 4, "change": { "p2p_encounters_per_round": 41 } }]);
+    let (status, _) = post(limits, "/v1/runs", &cfg).await;
+    assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+    // ... and a change after the last round never takes effect
+    cfg["schedule"][0]["round"] = json!(6);
+    let (status, text) = post(limits, "/v1/runs", &cfg).await;
+    assert_eq!(status, StatusCode::OK, "{text}");
+}
+
+#[tokio::test]
+async fn entrants_count_towards_the_agent_limit() {
+    let mut cfg = small_config();
+    cfg["demography"] = json!({ "entry_rate": 0.5 });
+    // one entrant at most per round: 24 + 6
+    let limits = Limits { max_agents: 30, ..Limits::default() };
+    let (status, text) = post(limits, "/v1/runs", &cfg).await;
+    assert_eq!(status, StatusCode::OK, "{text}");
+    let limits = Limits { max_agents: 29, ..Limits::default() };
+    let (status, _) = post(limits, "/v1/runs", &cfg).await;
+    assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+}
+
+#[tokio::test]
+async fn evaluations_beyond_the_iteration_limit_are_refused() {
+    let body = json!({
+        "beta_i": [0.8, 0.2], "e_i": [1.0, 1.0],
+        "beta_j": [0.2, 0.8], "e_j": [1.0, 1.0],
+        "good_a": 0, "good_b": 1, "oracle_iters": 1_000_000_000
+    });
+    let (status, text) = post(Limits::default(), "/v1/evaluate", &body).await;
+    assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE, "{text}");
+    let limits = Limits { max_oracle_iters: 30, ..Limits::default() };
+    let mut body = body;
+    body["oracle_iters"] = json!(30);
+    let (status, _) = post(limits, "/v1/evaluate", &body).await;
+    assert_eq!(status, StatusCode::OK);
+    let limits = Limits { max_goods: 1, ..Limits::default() };
+    let (status, _) = post(limits, "/v1/evaluate", &body).await;
+    assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+}
+
+#[tokio::test]
+async fn stream_sends_rounds_then_done() {
+    let (status, text) = post(Limits::default(), "/v1/runs/stream", &small_config()).await;
+    assert_eq!(status, StatusCode::OK);
+    let rounds = text.matches("event: round").count();
+    assert!(rounds >= 1, "{text}");
+    assert!(rounds <= 6);
+    let done = text.find("event: done").expect("a done event");
+    assert!(text.rfind("event: round").unwrap() < done);
+}
@@ -0,0 +1,135 @@
+//! WebAssembly bindings for dyadic trade evaluation, so a browser demo or a JavaScript peer
+//! computes the same Pareto-optimal exchanges as the Rust core.
+//!
+//! - `betaFromAlphaToBase`: Cobb–Douglas exponents from alphas against the base good
+//! - `solveTwoGoodExchange`: the Walras oracle for one good pair between two agents
+//! - `evaluatePairwiseTrade`: `trade::evaluate_pairwise_trade` on two agents in their JSON form
+//!   (as in `SimState::agents`)
+//! - `evaluateExchange`: `trade::evaluate_exchange` on exponents and holdings alone
+//!
+//! Agents and trade candidates cross the boundary as plain objects with the Rust field names;
+//! vectors are `Float64Array`s. Invalid input raises a JavaScript `Error`.
+
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+use rdx_core::model::Agent;
+use rdx_core::pareto_oracle::CobbDouglasWalrasOracle;
+use rdx_core::trade::TradeCandidate;
+
+/// Post-trade allocation of a two-good exchange (`pareto_oracle::DyadExchange`).
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug)]
+pub struct DyadExchange {
+    /// Price ratio p_A / p_B.
+    pub q_ab: f64,
+    pub ai_post: f64,
+    pub bi_post: f64,
+    pub aj_post: f64,
+    pub bj_post: f64,
+    /// Excess demand for A at `q_ab` relative to the pair's supply of A.
+    pub residual: f64,
+    pub iterations: usize,
+}
+
+/// A mutually beneficial trade as returned to JavaScript: the good pair, its price, i's change in
+/// holdings (j's is the opposite) and both utility gains.
+#[derive(Serialize)]
+struct Candidate {
+    good_a: usize,
+    good_b: usize,
+    q_ab: f64,
+    delta_a_i: f64,
+    delta_b_i: f64,
+    delta_u_i: f64,
+    delta_u_j: f64,
+}
+
+impl From<TradeCandidate> for Candidate {
+    fn from(c: TradeCandidate) -> Self {
+        Candidate {
+            good_a: c.good_a.index(),
+            good_b: c.good_b.index(),
+            q_ab: c.q_ab,
+            delta_a_i: c.delta_a_i,
+            delta_b_i: c.delta_b_i,
+            delta_u_i: c.delta_u_i,
+            delta_u_j: c.delta_u_j,
+        }
+    }
+}
+
+fn to_js(candidate: Option<TradeCandidate>) -> Result<JsValue, JsError> {
+    match candidate {
+        Some(c) => Ok(serde_wasm_bindgen::to_value(&Candidate::from(c))?),
+        None => Ok(JsValue::NULL),
+    }
+}
+
+/// Exponents `beta` (summing to 1) from per-good alphas against `base`.
+#[wasm_bindgen(js_name = betaFromAlphaToBase)]
+pub fn beta_from_alpha_to_base(alpha_to_base: &[f64], base: usize, min_alpha: f64) -> Result<Vec<f64>, JsError> {
+    Ok(rdx_core::preferences::beta_from_alpha_to_base(alpha_to_base, base, min_alpha)?)
+}
+
+/// Walras allocation of goods A and B between agents with dyadic alphas `alpha_i`, `alpha_j`
+/// and holdings `(ai, bi)`, `(aj, bj)`. Raises for out-of-range alphas or an unbracketed price.
+#[allow(clippy::too_many_arguments)]
+#[wasm_bindgen(js_name = solveTwoGoodExchange)]
+pub fn solve_two_good_exchange(
+    alpha_i: f64,
+    ai: f64,
+    bi: f64,
+    alpha_j: f64,
+    aj: f64,
+    bj: f64,
+    min_qty: f64,
+    iters: usize,
+) -> Result<DyadExchange, JsError> {
+    let ex = CobbDouglasWalrasOracle.try_solve_two_good_exchange(alpha_i, ai, bi, alpha_j, aj, bj, min_qty, iters)?;
+    Ok(DyadExchange {
+        q_ab: ex.q_ab,
+        ai_post: ex.ai_post,
+        bi_post: ex.bi_post,
+        aj_post: ex.aj_post,
+        bj_post: ex.bj_post,
+        residual: ex.residual,
+        iterations: ex.iterations,
+    })
+}
+
+/// Best exchange of `good_a` for `good_b` between agents `i` and `j`, or `null` when it does not
+/// make both strictly better off.
+#[allow(clippy::too_many_arguments)]
+#[wasm_bindgen(js_name = evaluatePairwiseTrade)]
+pub fn evaluate_pairwise_trade(
+    i: JsValue,
+    j: JsValue,
+    good_a: usize,
+    good_b: usize,
+    base_good: usize,
+    min_qty: f64,
+    oracle_iters: usize,
+) -> Result<JsValue, JsError> {
+    let i: Agent = serde_wasm_bindgen::from_value(i)?;
+    let j: Agent = serde_wasm_bindgen::from_value(j)?;
+    let oracle = CobbDouglasWalrasOracle;
+    to_js(rdx_core::trade::evaluate_pairwise_trade(&i, &j, good_a, good_b, base_good, min_qty, oracle_iters, &oracle))
+}
+
+/// `evaluatePairwiseTrade` from the two sides' exponents and holdings, for peers that exchange
+/// preference payloads rather than agents.
+#[allow(clippy::too_many_arguments)]
+#[wasm_bindgen(js_name = evaluateExchange)]
+pub fn evaluate_exchange(
+    beta_i: &[f64],
+    e_i: &[f64],
+    beta_j: &[f64],
+    e_j: &[f64],
+    good_a: usize,
+    good_b: usize,
+    min_qty: f64,
+    oracle_iters: usize,
+) -> Result<JsValue, JsError> {
+    let oracle = CobbDouglasWalrasOracle;
+    to_js(rdx_core::trade::evaluate_exchange(beta_i, e_i, beta_j, e_j, good_a, good_b, min_qty, oracle_iters, &oracle))
+}